@@ -0,0 +1,301 @@
+//! Account command - manage and reconcile accounts
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use rust_decimal::Decimal;
+use treeline_core::services::NewAccount;
+
+use super::{get_context, resolve_account_id};
+
+#[derive(Subcommand)]
+pub enum AccountCommands {
+    /// Add a manual account (cash, property, ...) that isn't synced
+    Add {
+        /// Account name
+        name: String,
+        /// Account type, e.g. depository, investment, credit or loan (credit and loan are liabilities)
+        #[arg(long = "type", value_name = "TYPE")]
+        account_type: Option<String>,
+        /// ISO 4217 currency code (default USD)
+        #[arg(long)]
+        currency: Option<String>,
+        /// Opening balance (negative for what a credit card or loan owes)
+        #[arg(long, allow_negative_numbers = true)]
+        balance: Option<Decimal>,
+        /// Date of the opening balance (YYYY-MM-DD, defaults to today)
+        #[arg(long, requires = "balance")]
+        date: Option<NaiveDate>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Archive an account (keeps its history, hides it from status and sync)
+    Archive {
+        /// Account ID, ID prefix or name to archive
+        id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Unarchive a previously archived account
+    Unarchive {
+        /// Account ID, ID prefix or name to unarchive
+        id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Put an account in a group (e.g. Joint, Business) for status subtotals
+    Group {
+        /// Account ID, ID prefix or name to group
+        id: String,
+        /// Group name; omit to take the account out of its group
+        group: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Override the currency an account is reported in (kept across syncs)
+    Currency {
+        /// Account ID, ID prefix or name
+        id: String,
+        /// ISO 4217 currency code, e.g. GBP
+        currency: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Exclude provider accounts from sync (e.g. accounts shared by a SimpleFIN token)
+    Exclude {
+        /// Integration name (e.g. simplefin)
+        integration: String,
+        /// Provider account IDs to exclude; omit to list current exclusions
+        ids: Vec<String>,
+        /// Stop excluding the given IDs instead
+        #[arg(long)]
+        remove: bool,
+        /// Archive already-synced accounts that are now excluded
+        #[arg(long)]
+        archive: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Merge a duplicate account into another and delete the duplicate
+    Merge {
+        /// Account (ID, ID prefix or name) to merge and delete
+        source: String,
+        /// Account (ID, ID prefix or name) that keeps the transactions and balance history
+        target: String,
+        /// Skip confirmation prompt
+        #[arg(long, short = 'f')]
+        force: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare balance snapshots against balances derived from transactions
+    Reconcile {
+        /// Account ID, ID prefix or name to reconcile
+        id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: AccountCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        AccountCommands::Add { name, account_type, currency, balance, date, json } => {
+            let result = ctx.account_service.create_manual(NewAccount {
+                name,
+                account_type,
+                currency,
+                opening_balance: balance,
+                opening_date: balance.map(|_| date.unwrap_or_else(|| Local::now().date_naive())),
+            })?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} Added account {} ({})", "✓".green(), result.account.name, result.account.id);
+                match &result.opening_snapshot {
+                    Some(snapshot) => println!("  Opening balance: {} on {}", result.account.format_balance(), snapshot.snapshot_time.date()),
+                    None => println!("Set its balance with: tl balance set {} <AMOUNT>", result.account.id),
+                }
+            }
+            Ok(())
+        }
+        AccountCommands::Archive { id, json } => {
+            let result = ctx.account_service.archive(&resolve_account_id(&ctx, &id)?)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} Archived account: {}", "✓".green(), result.name);
+                println!("Its history is kept. Run 'tl status --include-archived' to see it.");
+            }
+            Ok(())
+        }
+        AccountCommands::Unarchive { id, json } => {
+            let result = ctx.account_service.unarchive(&resolve_account_id(&ctx, &id)?)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} Unarchived account: {}", "✓".green(), result.name);
+            }
+            Ok(())
+        }
+        AccountCommands::Group { id, group, json } => {
+            let result = ctx.account_service.set_group(&resolve_account_id(&ctx, &id)?, group.as_deref())?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else if let Some(group) = &result.group {
+                println!("{} {} is now in group: {}", "✓".green(), result.name, group);
+            } else {
+                println!("{} {} is no longer in a group", "✓".green(), result.name);
+            }
+            Ok(())
+        }
+        AccountCommands::Currency { id, currency, json } => {
+            let result = ctx.account_service.set_currency(&resolve_account_id(&ctx, &id)?, &currency)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} {} is now reported in {}", "✓".green(), result.name, result.currency);
+            }
+            Ok(())
+        }
+        AccountCommands::Exclude {
+            integration,
+            ids,
+            remove,
+            archive,
+            json,
+        } => {
+            let mut excluded = ctx.sync_service.get_excluded_accounts(&integration)?;
+            if remove {
+                excluded.retain(|id| !ids.contains(id));
+            } else {
+                excluded.extend(ids.iter().cloned());
+            }
+            if !ids.is_empty() {
+                ctx.sync_service.set_excluded_accounts(&integration, &excluded)?;
+            }
+            let excluded = ctx.sync_service.get_excluded_accounts(&integration)?;
+
+            let archived = if archive {
+                ctx.sync_service.archive_excluded_accounts(&integration)?
+            } else {
+                Vec::new()
+            };
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "integration": integration,
+                        "excluded": excluded,
+                        "archived": archived,
+                    }))?
+                );
+                return Ok(());
+            }
+
+            if excluded.is_empty() {
+                println!("No accounts excluded from {} sync.", integration);
+            } else {
+                println!("Excluded from {} sync:", integration);
+                for id in &excluded {
+                    println!("  {}", id);
+                }
+            }
+            if !archived.is_empty() {
+                println!("{} Archived {} already-synced account(s)", "✓".green(), archived.len());
+            }
+            Ok(())
+        }
+        AccountCommands::Merge { source, target, force, json } => {
+            let source = ctx.repository.resolve_account(&source)?.into_account(&source)?;
+            let target = ctx.repository.resolve_account(&target)?.into_account(&target)?;
+            if !force && !json {
+                let source_name = source.nickname.as_deref().unwrap_or(&source.name);
+                let target_name = target.nickname.as_deref().unwrap_or(&target.name);
+                use dialoguer::Confirm;
+                if !Confirm::new()
+                    .with_prompt(format!("Merge '{}' into '{}' and delete '{}'?", source_name, target_name, source_name))
+                    .default(false)
+                    .interact()?
+                {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+            }
+
+            let report = ctx.account_service.merge(&source.id.to_string(), &target.id.to_string())?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{} Merged account {} into {}", "✓".green(), report.source_id, report.target_id);
+                println!("  Transactions moved: {}", report.transactions_moved);
+                println!("  Balance snapshots moved: {}", report.snapshots_moved);
+                if report.duplicates_dropped > 0 {
+                    println!("  Duplicate transactions dropped: {}", report.duplicates_dropped);
+                }
+            }
+            Ok(())
+        }
+        AccountCommands::Reconcile { id, json } => {
+            let report = ctx.balance_service.reconcile(&resolve_account_id(&ctx, &id)?)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                return Ok(());
+            }
+
+            println!("{}", format!("Reconciliation: {}", report.account_name).bold());
+            match (&report.anchor_date, report.anchor_balance) {
+                (Some(date), Some(balance)) => println!("Starting balance: {} on {}", balance, date),
+                _ => {
+                    println!("No balance snapshots to reconcile against.");
+                    return Ok(());
+                }
+            }
+            println!();
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Date", "Snapshot", "Expected", "Discrepancy", "New Drift"]);
+            for entry in &report.entries {
+                let discrepancy = if entry.flagged {
+                    Cell::new(entry.discrepancy).fg(Color::Red)
+                } else {
+                    Cell::new(entry.discrepancy).fg(Color::Green)
+                };
+                table.add_row(vec![
+                    Cell::new(&entry.date),
+                    Cell::new(entry.snapshot_balance),
+                    Cell::new(entry.expected_balance),
+                    discrepancy,
+                    Cell::new(entry.new_drift),
+                ]);
+            }
+            println!("{}", table);
+            println!();
+
+            if report.flagged > 0 {
+                println!(
+                    "{} {} snapshot(s) differ from transactions by more than {}",
+                    "✗".red(),
+                    report.flagged,
+                    report.tolerance
+                );
+            } else {
+                println!("{} Snapshots match transactions", "✓".green());
+            }
+            Ok(())
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Backup command - manage database backups
 
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::Subcommand;
 use colored::Colorize;
@@ -37,6 +39,45 @@ pub enum BackupCommands {
         #[arg(long)]
         json: bool,
     },
+    /// Restore a single table from a backup, leaving everything else as is
+    RestoreTable {
+        /// Backup name to restore from
+        name: String,
+        /// Table to restore (e.g. sys_transactions_rules)
+        table: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show what changed in the database since a backup was taken
+    Diff {
+        /// Backup name to compare against
+        name: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check that a backup is intact and can be restored
+    Verify {
+        /// Backup name to verify
+        name: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export all data as JSON Lines files, e.g. to move it to another machine
+    Export {
+        /// Directory to write one .jsonl file per table into
+        dir: PathBuf,
+    },
+    /// Import JSON Lines files written by 'tl backup export'
+    Import {
+        /// Directory holding the .jsonl files
+        dir: PathBuf,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Clear all backups
     Clear {
         /// Skip confirmation prompt
@@ -137,12 +178,15 @@ pub fn run(command: BackupCommands) -> Result<()> {
                 }
             }
             match backup_service.restore(&name) {
-                Ok(()) => {
+                Ok(result) => {
                     log_event(&logger, LogEvent::new("restore_completed").with_command("backup restore"));
                     if json {
-                        println!("{}", serde_json::json!({"restored": name}));
+                        println!("{}", serde_json::to_string(&result)?);
                     } else {
                         println!("Database restored from backup: {}", name);
+                        if let Some(safety) = &result.pre_restore_backup {
+                            println!("Previous database saved as: {} (restore it to undo)", safety);
+                        }
                     }
                 }
                 Err(e) => {
@@ -156,6 +200,88 @@ pub fn run(command: BackupCommands) -> Result<()> {
                 }
             }
         }
+        BackupCommands::RestoreTable { name, table, json } => {
+            // Needs the live database to upsert rows into
+            let ctx = get_context()?;
+            let rows = ctx.backup_service.restore_table(&name, &table)?;
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({"backup": name, "table": table, "rows": rows})
+                );
+            } else {
+                println!("Restored {} row(s) of {} from backup: {}", rows, table, name);
+            }
+        }
+        BackupCommands::Diff { name, json } => {
+            // Needs the live database to compare against
+            let ctx = get_context()?;
+            let diff = ctx.backup_service.diff(&name)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+                return Ok(());
+            }
+
+            println!("Changes since backup: {}", name);
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["", "Added", "Removed", "Modified"]);
+            for (label, counts) in [
+                ("Accounts", &diff.accounts),
+                ("Transactions", &diff.transactions),
+                ("Balance snapshots", &diff.balance_snapshots),
+            ] {
+                table.add_row(vec![
+                    label.to_string(),
+                    counts.added.to_string(),
+                    counts.removed.to_string(),
+                    counts.modified.to_string(),
+                ]);
+            }
+            println!("{}", table);
+        }
+        BackupCommands::Verify { name, json } => {
+            // Verify works on an extracted copy, so no database access is needed
+            let backup_service = get_backup_service();
+            let report = backup_service.verify(&name)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if report.ok {
+                println!("{} {}", "OK".green(), report.name);
+                println!("  {}", report.message);
+            } else {
+                println!("{} {}", "FAILED".red(), report.name);
+                println!("  {}", report.message);
+            }
+
+            if !report.ok {
+                std::process::exit(1);
+            }
+        }
+        BackupCommands::Export { dir } => {
+            let ctx = get_context()?;
+            ctx.backup_service.export_jsonl(&dir)?;
+            println!("{} Exported data to {}", "✓".green(), dir.display());
+        }
+        BackupCommands::Import { dir, json } => {
+            let ctx = get_context()?;
+            let stats = ctx.backup_service.import_jsonl(&dir)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+                return Ok(());
+            }
+
+            println!("{} Imported {} row(s) from {}", "✓".green(), stats.rows, dir.display());
+            for (table, rows) in &stats.tables {
+                println!("  {}: {}", table, rows);
+            }
+            if !stats.skipped.is_empty() {
+                println!("{}", format!("Skipped files for unknown tables: {}", stats.skipped.join(", ")).yellow());
+            }
+        }
         BackupCommands::Clear { force, json } => {
             // Clear doesn't need database access
             let backup_service = get_backup_service();
@@ -0,0 +1,53 @@
+//! Balance command - set balances for accounts that can't be synced
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use colored::Colorize;
+use rust_decimal::Decimal;
+
+use super::{get_context, resolve_account_id};
+
+#[derive(Subcommand)]
+pub enum BalanceCommands {
+    /// Set an account's balance by hand (kept across syncs)
+    Set {
+        /// Account ID, ID prefix or name
+        account_id: String,
+        /// Balance at the end of the day
+        #[arg(allow_negative_numbers = true)]
+        amount: Decimal,
+        /// Date of the balance (YYYY-MM-DD, defaults to today)
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Why the balance was set, e.g. "counted the cash drawer"
+        #[arg(long)]
+        note: Option<String>,
+        /// Also add a manual transaction for the difference from the prior balance
+        #[arg(long)]
+        adjust: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: BalanceCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        BalanceCommands::Set { account_id, amount, date, note, adjust, json } => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let account_id = resolve_account_id(&ctx, &account_id)?;
+            let snapshot_id = ctx.balance_service.adjust_balance(&account_id, amount, date, note, adjust)?;
+            let snapshot = ctx.repository.get_balance_snapshots(Some(&account_id))?.into_iter().find(|s| s.id == snapshot_id).ok_or_else(|| anyhow::anyhow!("Balance snapshot was not stored"))?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&snapshot)?);
+            } else {
+                println!("{} Set balance of {} to {} as of {}", "✓".green(), account_id, snapshot.balance, date);
+            }
+            Ok(())
+        }
+    }
+}
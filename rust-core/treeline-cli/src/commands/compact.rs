@@ -12,9 +12,10 @@ struct CompactOutput {
     original_size: u64,
     compacted_size: u64,
     backup_name: Option<String>,
+    snapshots_removed: Option<usize>,
 }
 
-pub fn run(skip_backup: bool, json: bool) -> Result<()> {
+pub fn run(skip_backup: bool, dedupe_snapshots: bool, json: bool) -> Result<()> {
     let ctx = get_context()?;
 
     // Create safety backup first (unless skipped)
@@ -25,6 +26,13 @@ pub fn run(skip_backup: bool, json: bool) -> Result<()> {
         None
     };
 
+    // Dedupe first so compaction reclaims the freed space
+    let snapshots_removed = if dedupe_snapshots {
+        Some(ctx.compact_service.dedupe_snapshots()?)
+    } else {
+        None
+    };
+
     let result = ctx.compact_service.compact()?;
 
     if json {
@@ -32,6 +40,7 @@ pub fn run(skip_backup: bool, json: bool) -> Result<()> {
             original_size: result.original_size,
             compacted_size: result.compacted_size,
             backup_name,
+            snapshots_removed,
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
         return Ok(());
@@ -49,6 +58,10 @@ pub fn run(skip_backup: bool, json: bool) -> Result<()> {
         0.0
     };
 
+    if let Some(removed) = snapshots_removed {
+        println!("Removed {} duplicate balance snapshot(s)", removed);
+    }
+
     println!("{}", "Database compacted".green());
     println!("Before: {} bytes", result.original_size);
     println!("After: {} bytes", result.compacted_size);
@@ -0,0 +1,103 @@
+//! Config command - move settings, integrations and rules between machines
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+
+use super::get_context;
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Write settings, integrations and auto-tag rules to a bundle file
+    Export {
+        /// Bundle file to write
+        path: PathBuf,
+        /// Include API keys and access tokens (keep the file private)
+        #[arg(long)]
+        include_secrets: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Apply a bundle file written by 'tl config export'
+    Import {
+        /// Bundle file to read
+        path: PathBuf,
+        /// Replace existing settings, integrations and rules with the bundle's
+        #[arg(long)]
+        overwrite: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: ConfigCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        ConfigCommands::Export { path, include_secrets, json } => {
+            if include_secrets {
+                ctx.config_service.export_bundle_with_secrets(&path)?;
+            } else {
+                ctx.config_service.export_bundle(&path)?;
+            }
+
+            if json {
+                let result = serde_json::json!({
+                    "path": path.display().to_string(),
+                    "includes_secrets": include_secrets,
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} Exported configuration to {}", "✓".green(), path.display());
+                if include_secrets {
+                    println!("{}", "The bundle contains credentials - keep it private.".yellow());
+                } else {
+                    println!("Secrets were left out; use --include-secrets to keep them.");
+                }
+            }
+            Ok(())
+        }
+        ConfigCommands::Import { path, overwrite, json } => {
+            let result = ctx.config_service.import_bundle(&path, overwrite)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                return Ok(());
+            }
+
+            println!("{} Imported configuration from {}", "✓".green(), path.display());
+            println!("  Settings: {}", if result.settings_updated { "updated" } else { "unchanged" });
+            if !result.integrations_added.is_empty() {
+                println!("  Integrations added: {}", result.integrations_added.join(", "));
+            }
+            if !result.integrations_updated.is_empty() {
+                println!("  Integrations updated: {}", result.integrations_updated.join(", "));
+            }
+            if !result.integrations_skipped.is_empty() {
+                println!("  Integrations unchanged: {}", result.integrations_skipped.join(", "));
+            }
+            println!(
+                "  Rules: {} added, {} updated, {} skipped",
+                result.rules_added, result.rules_updated, result.rules_skipped
+            );
+            if !result.integrations_missing_secrets.is_empty() {
+                println!(
+                    "{}",
+                    format!(
+                        "Missing credentials for: {}. Run 'tl setup' for these integrations.",
+                        result.integrations_missing_secrets.join(", ")
+                    )
+                    .yellow()
+                );
+            }
+            if result.rules_skipped > 0 && !overwrite {
+                println!("Use --overwrite to replace rules that already exist.");
+            }
+            Ok(())
+        }
+    }
+}
@@ -5,7 +5,7 @@ use clap::Subcommand;
 use colored::Colorize;
 
 use super::get_treeline_dir;
-use treeline_core::services::DemoService;
+use treeline_core::services::{DemoOptions, DemoService};
 
 #[derive(Subcommand)]
 pub enum DemoCommands {
@@ -17,6 +17,24 @@ pub enum DemoCommands {
     Off,
     /// Show demo mode status
     Status,
+    /// Enable demo mode with freshly generated data of a chosen size
+    Generate {
+        /// Days of history to generate
+        #[arg(long, default_value_t = DemoOptions::default().days)]
+        days: u32,
+        /// Random seed; the same seed always generates the same data
+        #[arg(long, default_value_t = DemoOptions::default().seed)]
+        seed: u64,
+        /// Number of accounts to generate
+        #[arg(long, default_value_t = DemoOptions::default().accounts)]
+        accounts: usize,
+        /// Multiplier for everyday card spending (1 is roughly two transactions a day)
+        #[arg(long, default_value_t = DemoOptions::default().density)]
+        density: u32,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 pub fn run(command: Option<DemoCommands>) -> Result<()> {
@@ -36,6 +54,19 @@ pub fn run(command: Option<DemoCommands>) -> Result<()> {
             println!("{}", "Demo mode disabled".yellow());
             Ok(())
         }
+        Some(DemoCommands::Generate { days, seed, accounts, density, json }) => {
+            let stats = demo_service.generate(DemoOptions { accounts, days, density, seed })?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+                return Ok(());
+            }
+            println!("{}", "Demo mode enabled".green());
+            println!(
+                "Generated {} transactions and {} balance snapshots across {} accounts ({} to {}).",
+                stats.transactions, stats.balance_snapshots, stats.accounts, stats.start_date, stats.end_date
+            );
+            Ok(())
+        }
         Some(DemoCommands::Status) | None => {
             if demo_service.is_enabled()? {
                 println!("Demo mode is {}", "ON".green());
@@ -118,3 +118,47 @@ pub fn run(verbose: bool, json: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// List embedded migrations with their applied/pending state
+pub fn run_migrations(json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let status = ctx.doctor_service.migration_status()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    println!("{}", "Migrations".bold());
+    println!();
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Migration", "Status", "Applied At"]);
+
+    for entry in &status {
+        let status_cell = if entry.applied {
+            Cell::new("applied").fg(Color::Green)
+        } else {
+            Cell::new("pending").fg(Color::Yellow)
+        };
+        let applied_at = entry
+            .applied_at
+            .map(|at| at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+
+        table.add_row(vec![Cell::new(&entry.name), status_cell, Cell::new(applied_at)]);
+    }
+
+    println!("{}", table);
+    println!();
+
+    let pending = status.iter().filter(|e| !e.applied).count();
+    println!(
+        "Summary: {} applied, {} pending",
+        (status.len() - pending).to_string().green(),
+        pending.to_string().yellow(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! Edit command - change the same fields on several transactions
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use colored::Colorize;
+use rust_decimal::Decimal;
+use treeline_core::services::TransactionPatch;
+
+use super::get_context;
+
+pub fn run(ids: Vec<String>, description: Option<String>, date: Option<NaiveDate>, amount: Option<Decimal>, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+
+    let patch = TransactionPatch { description, transaction_date: date, amount };
+    let updated = ctx.transaction_service.bulk_update(&ids, patch)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "transactions_updated": updated }))?);
+    } else {
+        println!("{} Updated {} transaction(s)", "✓".green(), updated);
+    }
+    Ok(())
+}
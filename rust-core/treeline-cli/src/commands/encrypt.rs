@@ -86,13 +86,25 @@ pub fn run(command: Option<EncryptCommands>, password: Option<String>, json: boo
 
     match command {
         Some(EncryptCommands::Status) => {
-            let status = encryption_service.get_status()?;
+            let details = encryption_service.metadata()?;
 
             if json {
-                println!("{}", serde_json::to_string_pretty(&status)?);
+                println!("{}", serde_json::to_string_pretty(&details)?);
             } else {
-                if status.encrypted {
+                if details.encrypted {
                     println!("{}", "Database is encrypted".green());
+                    if let (Some(algorithm), Some(version)) = (&details.algorithm, details.version) {
+                        println!("  Algorithm: {} (v{})", algorithm, version);
+                    }
+                    if let Some(params) = &details.argon2_params {
+                        println!(
+                            "  Key derivation: {} KiB memory, {} iterations, parallelism {}",
+                            params.memory_cost, params.time_cost, params.parallelism
+                        );
+                    }
+                    if let Some(fingerprint) = &details.salt_fingerprint {
+                        println!("  Salt fingerprint: {}", fingerprint);
+                    }
                 } else {
                     println!("{}", "Database is not encrypted".yellow());
                 }
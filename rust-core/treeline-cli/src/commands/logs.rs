@@ -1,12 +1,19 @@
 //! Logs command - view and manage application logs
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
 use clap::Subcommand;
 use colored::Colorize;
 use comfy_table::{ContentArrangement, Table};
 
 use super::get_treeline_dir;
-use treeline_core::{EntryPoint, LoggingService};
+use treeline_core::{EntryPoint, LogEntry, LoggingService};
+
+/// How often --follow checks for new entries
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Most entries --follow reads per poll
+const FOLLOW_BATCH_LIMIT: usize = 10_000;
 
 #[derive(Subcommand)]
 pub enum LogsCommands {
@@ -18,7 +25,16 @@ pub enum LogsCommands {
         /// Show only errors
         #[arg(long)]
         errors: bool,
-        /// Output as JSON
+        /// Only entries since a time: 30m, 12h, 7d, a date (YYYY-MM-DD) or an RFC 3339 timestamp
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries with this event name (e.g. sync_failed)
+        #[arg(long)]
+        event: Option<String>,
+        /// Keep running and print new entries as they are logged
+        #[arg(long, short = 'f')]
+        follow: bool,
+        /// Output as JSON (one object per line with --follow)
         #[arg(long)]
         json: bool,
     },
@@ -48,22 +64,104 @@ fn get_logging_service() -> Result<LoggingService> {
 }
 
 fn format_timestamp(timestamp_ms: i64) -> String {
-    use chrono::{TimeZone, Utc};
     Utc.timestamp_millis_opt(timestamp_ms)
         .single()
         .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
         .unwrap_or_else(|| timestamp_ms.to_string())
 }
 
+/// Parse --since: a relative age like 30m/12h/7d, a date, or an RFC 3339 timestamp
+fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(unit) = value.chars().last().filter(|c| matches!(c, 'm' | 'h' | 'd')) {
+        if let Ok(amount) = value[..value.len() - 1].parse::<i64>() {
+            let age = match unit {
+                'm' => Duration::minutes(amount),
+                'h' => Duration::hours(amount),
+                _ => Duration::days(amount),
+            };
+            return Ok(Utc::now() - age);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .with_context(|| format!("Invalid --since value '{}': use 30m, 12h, 7d, YYYY-MM-DD or an RFC 3339 timestamp", value))
+}
+
+fn format_context(entry: &LogEntry) -> String {
+    [entry.command.as_deref(), entry.page.as_deref(), entry.integration.as_deref()]
+        .iter()
+        .filter_map(|&s| s)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_entry_line(entry: &LogEntry, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(entry)?);
+        return Ok(());
+    }
+    let mut line = format!("{}  {:<8} {}", format_timestamp(entry.timestamp).dimmed(), entry.entry_point, entry.event);
+    let context = format_context(entry);
+    if !context.is_empty() {
+        line.push_str(&format!(" ({})", context));
+    }
+    if let Some(message) = &entry.error_message {
+        line.push_str(&format!("  {}", message.red()));
+    }
+    println!("{}", line);
+    Ok(())
+}
+
+/// Entries matching the `list` filters, newest first
+fn fetch_entries(service: &LoggingService, since: Option<DateTime<Utc>>, event: Option<&str>, errors: bool, limit: usize) -> Result<Vec<LogEntry>> {
+    if errors {
+        service.query_errors(since, event, limit)
+    } else {
+        service.query(since, event, limit)
+    }
+}
+
+/// Print matching entries oldest first, then poll for new ones until interrupted
+fn follow(service: &LoggingService, since: Option<DateTime<Utc>>, event: Option<&str>, errors: bool, limit: usize, json: bool) -> Result<()> {
+    for entry in fetch_entries(service, since, event, errors, limit)?.iter().rev() {
+        print_entry_line(entry, json)?;
+    }
+
+    // Newest entry already seen, matching or not
+    let mut cursor = service.query(None, None, 1)?.first().map(|e| (e.timestamp, e.id));
+
+    loop {
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+        // Entries can share a millisecond, so re-read the cursor's and skip seen ids
+        let poll_since = cursor.and_then(|(timestamp, _)| Utc.timestamp_millis_opt(timestamp).single());
+        let mut new_entries = service.query(poll_since, None, FOLLOW_BATCH_LIMIT)?;
+        new_entries.retain(|e| cursor.is_none_or(|(_, id)| e.id > id));
+        if let Some(newest) = new_entries.first() {
+            cursor = Some((newest.timestamp, newest.id));
+        }
+        for entry in new_entries.iter().rev() {
+            if event.is_some_and(|name| entry.event != name) || (errors && entry.error_message.is_none()) {
+                continue;
+            }
+            print_entry_line(entry, json)?;
+        }
+    }
+}
+
 pub fn run(command: LogsCommands) -> Result<()> {
     match command {
-        LogsCommands::List { limit, errors, json } => {
+        LogsCommands::List { limit, errors, since, event, follow: follow_logs, json } => {
+            let since = since.as_deref().map(parse_since).transpose()?;
             let service = get_logging_service()?;
-            let entries = if errors {
-                service.get_errors(limit)?
-            } else {
-                service.get_recent(limit)?
-            };
+            if follow_logs {
+                return follow(&service, since, event.as_deref(), errors, limit, json);
+            }
+
+            let entries = fetch_entries(&service, since, event.as_deref(), errors, limit)?;
 
             if json {
                 println!("{}", serde_json::to_string_pretty(&entries)?);
@@ -80,15 +178,7 @@ pub fn run(command: LogsCommands) -> Result<()> {
             table.set_header(vec!["Time", "Entry", "Event", "Context", "Error"]);
 
             for entry in entries {
-                let context = [
-                    entry.command.as_deref(),
-                    entry.page.as_deref(),
-                    entry.integration.as_deref(),
-                ]
-                .iter()
-                .filter_map(|&s| s)
-                .collect::<Vec<_>>()
-                .join(", ");
+                let context = format_context(&entry);
 
                 let error_indicator = if entry.error_message.is_some() {
                     "!".red().to_string()
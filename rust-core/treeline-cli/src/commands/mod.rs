@@ -1,16 +1,24 @@
 //! CLI command implementations
 
+pub mod account;
 pub mod backup;
+pub mod balance;
 pub mod compact;
+pub mod config;
 pub mod demo;
 pub mod doctor;
+pub mod edit;
 pub mod encrypt;
 pub mod logs;
 pub mod plugin;
 pub mod query;
+pub mod statement;
+pub mod stats;
 pub mod status;
 pub mod sync;
 pub mod tag;
+pub mod transaction;
+pub mod transfer;
 
 use std::path::PathBuf;
 use anyhow::{Context, Result};
@@ -45,6 +53,14 @@ pub fn get_treeline_dir() -> PathBuf {
     }
 }
 
+/// Account ID for the ID, ID prefix, name or nickname the user typed
+///
+/// When that doesn't pin down one account the error lists the likely candidates.
+pub fn resolve_account_id(ctx: &TreelineContext, query: &str) -> Result<String> {
+    let account = ctx.repository.resolve_account(query)?.into_account(query)?;
+    Ok(account.id.to_string())
+}
+
 /// Get or create treeline context
 pub fn get_context() -> Result<TreelineContext> {
     let treeline_dir = get_treeline_dir();
@@ -93,11 +109,13 @@ pub fn get_context() -> Result<TreelineContext> {
         None
     };
 
-    TreelineContext::new(&treeline_dir, encryption_key.as_deref())
+    let ctx = TreelineContext::new(&treeline_dir, encryption_key.as_deref())
         .map_err(|e| {
             eprintln!("DEBUG: TreelineContext::new failed: {:?}", e);
             e
         })
-        .context("Failed to initialize treeline context")
+        .context("Failed to initialize treeline context")?;
+    ctx.repository.set_entry_point(EntryPoint::Cli);
+    Ok(ctx)
 }
 
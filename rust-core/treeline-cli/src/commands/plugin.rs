@@ -6,7 +6,7 @@ use anyhow::Result;
 use clap::Subcommand;
 use colored::Colorize;
 
-use super::get_treeline_dir;
+use super::{get_context, get_treeline_dir};
 use treeline_core::services::PluginService;
 
 #[derive(Subcommand)]
@@ -114,7 +114,8 @@ pub fn run(command: PluginCommands) -> Result<()> {
         }
 
         PluginCommands::Uninstall { plugin_id, json } => {
-            let result = plugin_service.uninstall_plugin(&plugin_id)?;
+            // Go through the context so the plugin's views are dropped too
+            let result = get_context()?.plugin_service.uninstall_plugin(&plugin_id)?;
 
             if !result.success {
                 if json {
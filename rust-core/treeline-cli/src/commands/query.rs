@@ -1,14 +1,48 @@
 //! Query command - execute SQL queries against the database
 
+use std::collections::HashMap;
 use std::io::{self, Read};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use comfy_table::{Table, ContentArrangement};
+use treeline_core::services::QueryService;
+use treeline_core::QueryResult;
 
-use super::get_context;
+use super::{get_context, get_treeline_dir};
+
+pub fn run(
+    sql: Option<&str>,
+    file: Option<&Path>,
+    format: &str,
+    timeout: Option<u64>,
+    max_rows: Option<usize>,
+    save_as: Option<&str>,
+    named: Option<&str>,
+    params: &[String],
+    explain: Option<bool>,
+) -> Result<()> {
+    let ctx = get_context()?;
+
+    let mut limits = ctx.query_service.limits();
+    if let Some(secs) = timeout {
+        limits.timeout = Some(Duration::from_secs(secs));
+    }
+    if max_rows.is_some() {
+        limits.max_rows = max_rows;
+    }
+
+    if let Some(name) = named {
+        let values = parse_params(params)?;
+        let query_service = QueryService::new(Arc::clone(&ctx.repository))
+            .with_limits(limits)
+            .with_treeline_dir(get_treeline_dir());
+        let result = query_service.run_named_with(name, &values)?;
+        return print_result(&result, format);
+    }
 
-pub fn run(sql: Option<&str>, file: Option<&Path>, format: &str) -> Result<()> {
     // Get SQL from: argument, file, or stdin
     let sql_content = if let Some(sql) = sql {
         sql.to_string()
@@ -22,15 +56,62 @@ pub fn run(sql: Option<&str>, file: Option<&Path>, format: &str) -> Result<()> {
             .context("Failed to read SQL from stdin")?;
         buffer
     } else {
-        anyhow::bail!("No SQL query provided. Use positional argument, --file, or pipe from stdin.");
+        anyhow::bail!("No SQL query provided. Use positional argument, --file, --named, or pipe from stdin.");
     };
 
-    let ctx = get_context()?;
-    let result = ctx.query_service.execute(&sql_content)?;
+    if let Some(name) = save_as {
+        ctx.query_service.save_named(name, &sql_content)?;
+        eprintln!("Saved query '{}'. Run it again with: tl query --named {}", name, name);
+    }
+
+    if let Some(analyze) = explain {
+        let query_service = QueryService::new(Arc::clone(&ctx.repository)).with_limits(limits);
+        let plan = if analyze {
+            query_service.explain_analyze(&sql_content)?
+        } else {
+            query_service.explain(&sql_content)?
+        };
+        return print_plan(&plan, format);
+    }
+
+    let result = ctx.query_service.execute_with_limits(&sql_content, limits)?;
+    print_result(&result, format)
+}
+
+/// Print EXPLAIN output; the plan text is already drawn as a tree, so it isn't put in a table
+fn print_plan(plan: &QueryResult, format: &str) -> Result<()> {
+    if format != "table" {
+        return print_result(plan, format);
+    }
+    for row in &plan.rows {
+        if let Some(text) = row.last() {
+            println!("{}", value_to_string(text));
+        }
+    }
+    Ok(())
+}
+
+/// Parse `NAME=VALUE` pairs for a named query's placeholders
+///
+/// Values that read as JSON numbers are bound as numbers, everything else as text.
+fn parse_params(params: &[String]) -> Result<HashMap<String, serde_json::Value>> {
+    let mut values = HashMap::new();
+    for param in params {
+        let (name, value) = param.split_once('=')
+            .with_context(|| format!("Invalid --param '{}': expected NAME=VALUE", param))?;
+        let value = match value.parse::<serde_json::Number>() {
+            Ok(number) => serde_json::Value::Number(number),
+            Err(_) => serde_json::Value::String(value.to_string()),
+        };
+        values.insert(name.trim().to_string(), value);
+    }
+    Ok(values)
+}
 
+fn print_result(result: &QueryResult, format: &str) -> Result<()> {
     match format {
         "json" => {
-            println!("{}", serde_json::to_string_pretty(&result)?);
+            println!("{}", serde_json::to_string_pretty(result)?);
         }
         "csv" => {
             // CSV output
@@ -0,0 +1,50 @@
+//! Statement command - one account's transactions and balances for a period
+
+use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate};
+use colored::Colorize;
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+
+use super::{get_context, resolve_account_id};
+use crate::output;
+
+pub fn run(account: &str, from: Option<NaiveDate>, to: Option<NaiveDate>, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let today = Local::now().date_naive();
+    let end = to.unwrap_or(today);
+    let start = from.unwrap_or_else(|| end.with_day(1).unwrap_or(end));
+    let statement = ctx.query_service.account_statement(&resolve_account_id(&ctx, account)?, start, end)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&statement)?);
+        return Ok(());
+    }
+
+    let currency = &statement.account.currency;
+    println!("{}", format!("Statement: {} ({} to {})", statement.account.name, output::date(start), output::date(end)).bold());
+    let estimated = if statement.opening_estimated { " (estimated)".dimmed().to_string() } else { String::new() };
+    println!("Opening balance: {}{}", output::amount(statement.opening_balance, currency), estimated);
+    println!();
+
+    if statement.lines.is_empty() {
+        println!("{}", "No transactions in this period.".dimmed());
+    } else {
+        let mut table = Table::new();
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec!["Date", "Description", "Amount", "Balance"]);
+        for line in &statement.lines {
+            let color = if line.amount.is_sign_negative() { Color::Red } else { Color::Green };
+            table.add_row(vec![
+                Cell::new(output::date(line.date)),
+                Cell::new(line.description.as_deref().unwrap_or("")),
+                Cell::new(output::amount(line.amount, currency)).fg(color),
+                Cell::new(output::amount(line.balance, currency)),
+            ]);
+        }
+        println!("{}", table);
+        println!();
+    }
+
+    println!("Closing balance: {}", output::amount(statement.closing_balance, currency).bold());
+    Ok(())
+}
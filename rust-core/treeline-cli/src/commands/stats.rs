@@ -0,0 +1,181 @@
+//! Stats command - spending statistics
+
+use anyhow::Result;
+use chrono::{Datelike, Local, Months, NaiveDate};
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use super::{get_context, resolve_account_id};
+use crate::output;
+
+/// Width of the longest bar in the histogram
+const BAR_WIDTH: i64 = 40;
+
+#[derive(Subcommand)]
+pub enum StatsCommands {
+    /// Show how expense sizes are distributed
+    Histogram {
+        /// Only include this account (ID, ID prefix or name)
+        #[arg(long)]
+        account: Option<String>,
+        /// Number of buckets
+        #[arg(long, default_value_t = 10)]
+        buckets: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show monthly spending on a tag
+    TagTrend {
+        /// Tag to follow
+        tag: String,
+        /// Number of months, including the current one
+        #[arg(long, default_value_t = 12)]
+        months: u32,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the merchants with the largest totals
+    Merchants {
+        /// Number of merchants to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show net worth over time
+    NetWorth {
+        /// First day (YYYY-MM-DD, defaults to one year before --end)
+        #[arg(long)]
+        start: Option<NaiveDate>,
+        /// Last day (YYYY-MM-DD, defaults to today)
+        #[arg(long)]
+        end: Option<NaiveDate>,
+        /// Currency to report in; others are converted with exchangeRates
+        #[arg(long, default_value = "USD")]
+        currency: String,
+        /// Output every day as JSON (the table shows month ends only)
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: StatsCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        StatsCommands::Histogram { account, buckets, json } => {
+            let account = account.map(|a| resolve_account_id(&ctx, &a)).transpose()?;
+            let histogram = ctx.query_service.amount_histogram(account.as_deref(), buckets)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&histogram)?);
+                return Ok(());
+            }
+
+            if histogram.is_empty() {
+                println!("No expenses found");
+                return Ok(());
+            }
+
+            let max_count = histogram.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+            for bucket in &histogram {
+                let bar_len = bucket.count * BAR_WIDTH / max_count;
+                println!(
+                    "{:>10.2} - {:<10.2} {} {}",
+                    bucket.lower,
+                    bucket.upper,
+                    "█".repeat(bar_len as usize).cyan(),
+                    bucket.count
+                );
+            }
+            Ok(())
+        }
+        StatsCommands::TagTrend { tag, months, json } => {
+            let trend = ctx.query_service.tag_trend(&tag, months)?;
+
+            if json {
+                let months: Vec<serde_json::Value> = trend
+                    .iter()
+                    .map(|(month, spend)| {
+                        serde_json::json!({ "month": month.format("%Y-%m").to_string(), "spend": spend })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&months)?);
+                return Ok(());
+            }
+
+            println!("{}", format!("Spending tagged '{}'", tag).bold());
+            let max_spend = trend.iter().map(|(_, spend)| *spend).max().unwrap_or_default();
+            for (month, spend) in &trend {
+                let bar_len = if max_spend.is_zero() {
+                    0
+                } else {
+                    (*spend * Decimal::from(BAR_WIDTH) / max_spend).to_i64().unwrap_or(0)
+                };
+                println!(
+                    "{} {:>10.2} {}",
+                    month.format("%Y-%m"),
+                    spend,
+                    "█".repeat(bar_len as usize).cyan()
+                );
+            }
+            Ok(())
+        }
+        StatsCommands::Merchants { limit, json } => {
+            let mut merchants = ctx.query_service.merchant_summary()?;
+            merchants.truncate(limit);
+
+            if json {
+                let merchants: Vec<serde_json::Value> = merchants
+                    .iter()
+                    .map(|(merchant, total, count)| serde_json::json!({ "merchant": merchant, "total": total, "count": count }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&merchants)?);
+                return Ok(());
+            }
+
+            if merchants.is_empty() {
+                println!("No transactions found");
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Merchant", "Total", "Transactions"]);
+            for (merchant, total, count) in &merchants {
+                table.add_row(vec![merchant.clone(), output::number(*total), count.to_string()]);
+            }
+            println!("{}", table);
+            Ok(())
+        }
+        StatsCommands::NetWorth { start, end, currency, json } => {
+            let end = end.unwrap_or_else(|| Local::now().date_naive());
+            let start = start.unwrap_or_else(|| end.checked_sub_months(Months::new(12)).unwrap_or(end));
+            let series = ctx.balance_service.net_worth_series(start, end, &currency)?;
+
+            if json {
+                let days: Vec<serde_json::Value> = series
+                    .iter()
+                    .map(|(date, net_worth)| serde_json::json!({ "date": date.to_string(), "net_worth": net_worth }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&days)?);
+                return Ok(());
+            }
+
+            println!("{}", format!("Net worth ({})", currency.to_uppercase()).bold());
+            for (i, (date, net_worth)) in series.iter().enumerate() {
+                let month_end = !matches!(series.get(i + 1), Some((next, _)) if next.month() == date.month());
+                if month_end {
+                    println!("{} {:>16}", output::date(*date), output::amount(*net_worth, &currency));
+                }
+            }
+            Ok(())
+        }
+    }
+}
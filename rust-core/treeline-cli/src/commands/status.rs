@@ -1,17 +1,30 @@
 //! Status command - show account status and summary
 
 use anyhow::Result;
+use chrono::{Datelike, Local, NaiveDate};
 use colored::Colorize;
-use comfy_table::{Table, ContentArrangement};
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use treeline_core::domain::DEFAULT_ACCOUNT_GROUP;
+use treeline_core::ports::ConnectionStatus;
 
 use super::get_context;
+use crate::output;
 
-pub fn run(json: bool) -> Result<()> {
+pub fn run(include_archived: bool, check_connections: bool, json: bool) -> Result<()> {
     let ctx = get_context()?;
-    let status = ctx.status_service.get_status()?;
+    let status = ctx.status_service.get_status(include_archived)?;
+    let connections = if check_connections {
+        Some(ctx.doctor_service.check_connections()?)
+    } else {
+        None
+    };
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&status)?);
+        let mut output = serde_json::to_value(&status)?;
+        if let Some(connections) = &connections {
+            output["connections"] = serde_json::to_value(connections)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
         return Ok(());
     }
 
@@ -27,18 +40,68 @@ pub fn run(json: bool) -> Result<()> {
     table.add_row(vec!["Transactions", &status.total_transactions.to_string()]);
     table.add_row(vec!["Balance Snapshots", &status.total_snapshots.to_string()]);
     table.add_row(vec!["Integrations", &status.total_integrations.to_string()]);
+    table.add_row(vec!["Net Worth", &output::number(status.net_worth)]);
 
     println!("{}", table);
     println!();
 
+    // Print net worth per group once any account has been grouped
+    if status.groups.iter().any(|g| g.name != DEFAULT_ACCOUNT_GROUP) {
+        println!("{}", "Net Worth by Group".bold());
+        let mut groups = Table::new();
+        groups.set_content_arrangement(ContentArrangement::Dynamic);
+        groups.set_header(vec!["Group", "Accounts", "Net Worth"]);
+        for group in &status.groups {
+            groups.add_row(vec![
+                Cell::new(&group.name),
+                Cell::new(group.account_count),
+                Cell::new(output::number(group.net_worth)),
+            ]);
+        }
+        groups.add_row(vec![
+            Cell::new("Total"),
+            Cell::new(status.total_accounts),
+            Cell::new(output::number(status.net_worth)),
+        ]);
+        println!("{}", groups);
+        println!();
+    }
+
+    // Print accounts, marking archived ones so they can't be mistaken for active
+    if !status.accounts.is_empty() {
+        println!("{}", "Accounts".bold());
+        for account in &status.accounts {
+            if account.is_archived {
+                println!("  • {} {}", account.name.dimmed(), "[archived]".yellow());
+            } else {
+                println!("  • {}", account.name);
+            }
+        }
+        println!();
+    }
+
     // Print date range
     if let (Some(earliest), Some(latest)) = (&status.date_range.earliest, &status.date_range.latest) {
         println!("Date range: {} to {}", earliest, latest);
         println!();
     }
 
-    // Print connected integrations
-    if !status.integration_names.is_empty() {
+    // Print connected integrations, with their health when checked
+    if let Some(connections) = &connections {
+        if !connections.is_empty() {
+            println!("{}", "Connected Integrations".bold());
+            for connection in connections {
+                let status = match connection.status {
+                    ConnectionStatus::Ok => "ok".green(),
+                    ConnectionStatus::AuthFailed => "auth failed".red(),
+                    ConnectionStatus::RateLimited => "rate limited".yellow(),
+                    ConnectionStatus::Unreachable => "unreachable".yellow(),
+                    ConnectionStatus::Unchecked => "unchecked".dimmed(),
+                };
+                println!("  • {} [{}] {}", connection.integration, status, connection.message.dimmed());
+            }
+        }
+    } else if !status.integration_names.is_empty() {
         println!("{}", "Connected Integrations".bold());
         for name in &status.integration_names {
             println!("  • {}", name);
@@ -47,3 +110,78 @@ pub fn run(json: bool) -> Result<()> {
 
     Ok(())
 }
+
+pub fn run_cash_flow(months: u32, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let cash_flow = ctx.status_service.cash_flow(months)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&cash_flow)?);
+        return Ok(());
+    }
+
+    println!("{}", "Cash Flow".bold());
+    println!();
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Month", "Income", "Expenses", "Net"]);
+
+    for entry in &cash_flow {
+        let month = if entry.is_partial {
+            format!("{} (so far)", entry.month)
+        } else {
+            entry.month.clone()
+        };
+        let net_color = if entry.net.is_sign_negative() { Color::Red } else { Color::Green };
+        table.add_row(vec![
+            Cell::new(month),
+            Cell::new(output::number(entry.income)),
+            Cell::new(output::number(entry.expenses)),
+            Cell::new(output::number(entry.net)).fg(net_color),
+        ]);
+    }
+
+    println!("{}", table);
+    println!("{}", "Transactions tagged 'transfer' are excluded.".dimmed());
+
+    Ok(())
+}
+
+pub fn run_by_tag(from: Option<NaiveDate>, to: Option<NaiveDate>, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let today = Local::now().date_naive();
+    let end = to.unwrap_or(today);
+    let start = from.unwrap_or_else(|| end.with_day(1).unwrap_or(end));
+    let spending = ctx.query_service.spending_by_tag(start, end)?;
+
+    if json {
+        let entries: Vec<_> = spending
+            .iter()
+            .map(|(tag, spend)| serde_json::json!({ "tag": tag, "spend": spend }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Spending by Tag ({} to {})", output::date(start), output::date(end)).bold());
+    println!();
+
+    if spending.is_empty() {
+        println!("{}", "No spending in this period.".dimmed());
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Tag", "Spent"]);
+
+    for (tag, spend) in &spending {
+        table.add_row(vec![Cell::new(tag), Cell::new(output::number(*spend))]);
+    }
+
+    println!("{}", table);
+    println!("{}", "Transactions with several tags count toward each. Transactions tagged 'transfer' are excluded.".dimmed());
+
+    Ok(())
+}
@@ -2,20 +2,27 @@
 
 use anyhow::Result;
 use colored::Colorize;
+use treeline_core::domain::Transaction;
+use treeline_core::services::{PlannedChanges, SyncPlan, SyncResult};
 use treeline_core::LogEvent;
 
 use super::{get_context, get_logger, log_event};
 
-pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()> {
+pub fn run(integration: Option<String>, dry_run: bool, continue_on_error: bool, json: bool) -> Result<()> {
+    if dry_run {
+        return run_dry_run(integration, json);
+    }
+
     let logger = get_logger();
     log_event(
         &logger,
         LogEvent::new("sync_started").with_command("sync"),
     );
 
-    let ctx = get_context()?;
+    let mut ctx = get_context()?;
+    ctx.sync_service.set_continue_on_error(continue_on_error);
     // CLI always syncs with transactions (balances_only = false)
-    let result = ctx.sync_service.sync(integration.as_deref(), dry_run, false);
+    let result = ctx.sync(integration.as_deref(), false, false);
 
     match &result {
         Ok(sync_result) => {
@@ -35,6 +42,20 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
                     );
                 }
             }
+            for warning in &sync_result.hook_warnings {
+                log_event(
+                    &logger,
+                    LogEvent::new("sync_hook_failed")
+                        .with_error(warning),
+                );
+            }
+            if let Some(error) = &sync_result.webhook_error {
+                log_event(
+                    &logger,
+                    LogEvent::new("sync_webhook_failed")
+                        .with_error(error),
+                );
+            }
         }
         Err(e) => {
             log_event(
@@ -49,11 +70,11 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
 
     if json {
         println!("{}", serde_json::to_string_pretty(&result)?);
-        return Ok(());
+        return check_failures(&result);
     }
 
-    if dry_run {
-        println!("{}", "DRY RUN - No changes applied".yellow());
+    if let Some(backup) = &result.backup {
+        println!("Backup created before sync: {}", backup);
         println!();
     }
 
@@ -71,14 +92,109 @@ pub fn run(integration: Option<String>, dry_run: bool, json: bool) -> Result<()>
             println!("  Transaction breakdown:");
             println!("    Discovered: {}", sync_result.transaction_stats.discovered);
             println!("    New: {}", sync_result.transaction_stats.new);
+            println!("    Updated: {} (pending)", sync_result.transaction_stats.updated);
             println!("    Skipped: {} (already exists)", sync_result.transaction_stats.skipped);
+            if sync_result.transaction_stats.removed > 0 {
+                println!("    Removed: {} (deleted by provider)", sync_result.transaction_stats.removed);
+            }
         }
         println!();
     }
 
+    for warning in &result.hook_warnings {
+        println!("{} {}", "Warning: plugin sync hook failed:".yellow(), warning);
+    }
+    if !result.hook_warnings.is_empty() {
+        println!();
+    }
+
+    if let Some(error) = &result.webhook_error {
+        println!("{} {}", "Warning: sync webhook failed:".yellow(), error);
+        println!();
+    }
+
     if result.results.is_empty() {
         println!("{}", "No integrations configured. Use 'tl setup' to add one.".yellow());
     }
 
+    check_failures(&result)
+}
+
+/// Exit with an error when any integration failed, after the others have synced
+fn check_failures(result: &SyncResult) -> Result<()> {
+    let failed = result.results.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} integration(s) failed to sync", failed, result.results.len());
+    }
     Ok(())
 }
+
+fn run_dry_run(integration: Option<String>, json: bool) -> Result<()> {
+    let ctx = get_context()?;
+    let plan = ctx.sync_service.sync_dry_run(integration.as_deref())?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+
+    print_plan(&plan);
+    Ok(())
+}
+
+/// One plan line: date, settled amount, description and any original foreign amount
+fn format_transaction(tx: &Transaction) -> String {
+    let original = tx.original_amount_label().map(|label| format!("  ({})", label)).unwrap_or_default();
+    format!("{}  {:>10.2}  {}{}", tx.transaction_date, tx.amount, tx.description.as_deref().unwrap_or(""), original)
+}
+
+fn print_plan(plan: &SyncPlan) {
+    println!("{}", "DRY RUN - No changes applied".yellow());
+    println!();
+
+    for int in &plan.integrations {
+        println!("{} {} ({}, {} to {})", "Plan:".cyan(), int.integration, int.sync_type, int.start_date, int.end_date);
+
+        print_changes("Accounts to create", &int.accounts_to_create, |a| a.name.clone());
+        print_changes("Accounts to update", &int.accounts_to_update, |a| a.name.clone());
+        print_changes("Transactions to insert", &int.transactions_to_insert, format_transaction);
+        print_changes("Transactions to update", &int.transactions_to_update, format_transaction);
+        print_changes("Transactions to remove", &int.transactions_to_remove, format_transaction);
+        print_changes("Balance snapshots to add", &int.snapshots_to_add, |s| {
+            format!("{}  {:.2}", s.snapshot_time.date(), s.balance)
+        });
+
+        if !int.accounts.is_empty() {
+            println!("  By account:");
+            for account in &int.accounts {
+                let action = if account.is_new { "new".green() } else { "existing".normal() };
+                println!(
+                    "    {} ({}): {} to insert, {} to update, {} unchanged, {} to remove, {} snapshot(s)",
+                    account.account_name,
+                    action,
+                    account.transactions_to_insert,
+                    account.transactions_to_update,
+                    account.transactions_to_skip,
+                    account.transactions_to_remove,
+                    account.snapshots_to_add
+                );
+            }
+        }
+
+        for warning in &int.provider_warnings {
+            println!("  {} {}", "Warning:".yellow(), warning);
+        }
+        println!();
+    }
+}
+
+fn print_changes<T>(label: &str, changes: &PlannedChanges<T>, describe: impl Fn(&T) -> String) {
+    println!("  {}: {}", label, changes.count);
+    for item in &changes.samples {
+        println!("    {}", describe(item).dimmed());
+    }
+    let remaining = changes.count - changes.samples.len() as i64;
+    if remaining > 0 {
+        println!("    {}", format!("... and {} more", remaining).dimmed());
+    }
+}
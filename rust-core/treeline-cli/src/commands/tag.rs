@@ -1,14 +1,93 @@
-//! Tag command - apply tags to transactions
+//! Tag command - apply tags to transactions, rename or delete tags
 
 use std::io::{self, Read};
 use std::process::exit;
 
 use anyhow::Result;
+use clap::Subcommand;
 use colored::Colorize;
+use comfy_table::{Cell, ContentArrangement, Table};
 
 use super::get_context;
 
-pub fn run(tags: &str, ids: Vec<String>, replace: bool, json: bool) -> Result<()> {
+#[derive(Subcommand)]
+pub enum TagCommands {
+    /// List all tags with usage counts
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rename a tag on every transaction and auto-tag rule
+    Rename {
+        /// Current tag name
+        old: String,
+        /// New tag name
+        new: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove a tag from every transaction and auto-tag rule
+    Delete {
+        /// Tag to remove
+        tag: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run_command(command: TagCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        TagCommands::List { json } => {
+            let stats = ctx.tag_service.list_tags()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+                return Ok(());
+            }
+            if stats.is_empty() {
+                println!("No tags found");
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Tag", "Transactions", "Total"]);
+            for stat in &stats {
+                table.add_row(vec![
+                    Cell::new(&stat.tag),
+                    Cell::new(stat.transaction_count),
+                    Cell::new(stat.total_amount),
+                ]);
+            }
+            println!("{}", table);
+            Ok(())
+        }
+        TagCommands::Rename { old, new, json } => {
+            let changed = ctx.tag_service.rename_tag(&old, &new)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "old": old, "new": new, "transactions_changed": changed }))?);
+            } else {
+                println!("{} Renamed tag '{}' to '{}' on {} transaction(s)", "✓".green(), old, new, changed);
+            }
+            Ok(())
+        }
+        TagCommands::Delete { tag, json } => {
+            let changed = ctx.tag_service.delete_tag(&tag)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "tag": tag, "transactions_changed": changed }))?);
+            } else {
+                println!("{} Removed tag '{}' from {} transaction(s)", "✓".green(), tag, changed);
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn run(tags: &str, ids: Vec<String>, condition: Option<&str>, preview: bool, force: bool, replace: bool, json: bool) -> Result<()> {
     let ctx = get_context()?;
 
     let tag_list: Vec<String> = tags.split(',')
@@ -16,37 +95,32 @@ pub fn run(tags: &str, ids: Vec<String>, replace: bool, json: bool) -> Result<()
         .filter(|s| !s.is_empty())
         .collect();
 
-    // Get IDs from argument or stdin
-    let id_list: Vec<String> = if ids.is_empty() && atty::isnt(atty::Stream::Stdin) {
-        // Read from stdin
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        let trimmed = buffer.trim();
-
-        // Parse IDs: Python uses EITHER newline OR comma (not both)
-        // If input contains newlines, split by newlines only
-        // Otherwise, split by commas
-        if trimmed.contains('\n') {
-            trimmed.lines()
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        } else {
-            trimmed.split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
+    let result = if let Some(condition) = condition {
+        let matches = ctx.tag_service.preview_condition(condition)?;
+        if preview {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&matches)?);
+            } else {
+                println!("{} of {} transaction(s) match; run without --preview to tag them", matches.matched, matches.total);
+            }
+            return Ok(());
+        }
+        if matches.matches_everything() && !force && !json {
+            use dialoguer::Confirm;
+            if !Confirm::new()
+                .with_prompt(format!("The condition matches all {} transactions. Tag them all with '{}'?", matches.total, tag_list.join(", ")))
+                .default(false)
+                .interact()?
+            {
+                println!("Cancelled.");
+                return Ok(());
+            }
         }
+        ctx.tag_service.apply_tags(&matches.transaction_ids, &tag_list, replace)?
     } else {
-        ids
+        ctx.tag_service.apply_tags(&read_ids(ids)?, &tag_list, replace)?
     };
 
-    if id_list.is_empty() {
-        anyhow::bail!("No transaction IDs provided. Use --ids or pipe IDs from stdin.");
-    }
-
-    let result = ctx.tag_service.apply_tags(&id_list, &tag_list, replace)?;
-
     if json {
         println!("{}", serde_json::to_string_pretty(&result)?);
         // Exit with code 1 if any errors (matches Python behavior)
@@ -56,6 +130,11 @@ pub fn run(tags: &str, ids: Vec<String>, replace: bool, json: bool) -> Result<()
         return Ok(());
     }
 
+    if result.results.is_empty() {
+        println!("No transactions matched");
+        return Ok(());
+    }
+
     // Human-readable output matching Python format
     if result.succeeded > 0 {
         println!("{} Successfully tagged {} transaction(s)", "✓".green(), result.succeeded);
@@ -76,3 +155,37 @@ pub fn run(tags: &str, ids: Vec<String>, replace: bool, json: bool) -> Result<()
 
     Ok(())
 }
+
+/// Transaction IDs from --ids, or from stdin when piped
+fn read_ids(ids: Vec<String>) -> Result<Vec<String>> {
+    // Get IDs from argument or stdin
+    let id_list: Vec<String> = if ids.is_empty() && atty::isnt(atty::Stream::Stdin) {
+        // Read from stdin
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        let trimmed = buffer.trim();
+
+        // Parse IDs: Python uses EITHER newline OR comma (not both)
+        // If input contains newlines, split by newlines only
+        // Otherwise, split by commas
+        if trimmed.contains('\n') {
+            trimmed.lines()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        } else {
+            trimmed.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        }
+    } else {
+        ids
+    };
+
+    if id_list.is_empty() {
+        anyhow::bail!("No transaction IDs provided. Use --ids, --where or pipe IDs from stdin.");
+    }
+
+    Ok(id_list)
+}
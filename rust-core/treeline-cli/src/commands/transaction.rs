@@ -0,0 +1,166 @@
+//! Transaction command - add, annotate and inspect individual transactions
+
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{Cell, ContentArrangement, Table};
+use rust_decimal::Decimal;
+
+use super::{get_context, resolve_account_id};
+
+#[derive(Subcommand)]
+pub enum TransactionCommands {
+    /// Add a manual transaction (kept across syncs)
+    #[command(alias = "new")]
+    Add {
+        /// Account ID, ID prefix or name
+        account_id: String,
+        /// Amount (negative for spending)
+        #[arg(allow_negative_numbers = true)]
+        amount: Decimal,
+        /// Transaction date (YYYY-MM-DD, defaults to today)
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Description
+        #[arg(short, long, alias = "desc")]
+        description: Option<String>,
+        /// Comma-separated tags
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// Also record a balance snapshot that includes this transaction
+        #[arg(long)]
+        update_balance: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Set a note on a transaction (kept across syncs, unlike the description)
+    Note {
+        /// Transaction ID
+        id: String,
+        /// Note text
+        text: Option<String>,
+        /// Remove the existing note instead
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show recent changes to a transaction (tags, notes, edits, deletes)
+    History {
+        /// Transaction ID
+        id: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: TransactionCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        TransactionCommands::Add {
+            account_id,
+            amount,
+            date,
+            description,
+            tags,
+            update_balance,
+            json,
+        } => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let account_id = resolve_account_id(&ctx, &account_id)?;
+            let tx = ctx
+                .transaction_service
+                .create_manual(&account_id, amount, date, description, tags)?;
+            let snapshot = if update_balance {
+                ctx.transaction_service.snapshot_balance_after(&tx)?
+            } else {
+                None
+            };
+
+            if json {
+                let result = serde_json::json!({
+                    "transaction": tx,
+                    "balance_snapshot": snapshot,
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} Added transaction {}", "✓".green(), tx.id);
+                println!(
+                    "  {}  {}  {}",
+                    tx.transaction_date,
+                    tx.amount,
+                    tx.description.as_deref().unwrap_or("")
+                );
+                if !tx.tags.is_empty() {
+                    println!("  Tags: {}", tx.tags.join(", "));
+                }
+                match snapshot {
+                    Some(s) => println!("  New balance: {}", s.balance),
+                    None if update_balance => println!(
+                        "{}",
+                        "  Account has no balance yet; no snapshot recorded".yellow()
+                    ),
+                    None => {}
+                }
+            }
+            Ok(())
+        }
+        TransactionCommands::Note { id, text, clear, json } => {
+            let notes = if clear {
+                None
+            } else {
+                Some(text.ok_or_else(|| {
+                    anyhow!("No note provided. Usage: tl transaction note <ID> <TEXT> (or --clear)")
+                })?)
+            };
+
+            ctx.repository.set_transaction_notes(&id, notes.clone())?;
+
+            if json {
+                let result = serde_json::json!({
+                    "transaction_id": id,
+                    "notes": notes,
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else if notes.is_some() {
+                println!("{} Saved note on transaction {}", "✓".green(), id);
+            } else {
+                println!("{} Cleared note on transaction {}", "✓".green(), id);
+            }
+            Ok(())
+        }
+        TransactionCommands::History { id, json } => {
+            let history = ctx.repository.get_transaction_history(&id)?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&history)?);
+                return Ok(());
+            }
+
+            if history.is_empty() {
+                println!("{}", format!("No recorded changes for transaction {}", id).dimmed());
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["When (UTC)", "Field", "Old", "New", "Source"]);
+            for entry in &history {
+                table.add_row(vec![
+                    Cell::new(entry.changed_at.format("%Y-%m-%d %H:%M:%S")),
+                    Cell::new(&entry.field),
+                    Cell::new(entry.old_value.as_deref().unwrap_or("-")),
+                    Cell::new(entry.new_value.as_deref().unwrap_or("-")),
+                    Cell::new(entry.entry_point.as_deref().unwrap_or("-")),
+                ]);
+            }
+            println!("{}", table);
+            Ok(())
+        }
+    }
+}
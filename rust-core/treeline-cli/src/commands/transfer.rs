@@ -0,0 +1,115 @@
+//! Transfer command - find and link transfers between your own accounts
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use comfy_table::{ContentArrangement, Table};
+use rust_decimal::Decimal;
+use treeline_core::services::TransferDetector;
+
+use super::get_context;
+use crate::output;
+
+#[derive(Subcommand)]
+pub enum TransferCommands {
+    /// List likely transfers that aren't linked yet
+    Detect {
+        /// Days the two sides may be apart
+        #[arg(long, default_value_t = 3)]
+        window_days: u32,
+        /// Largest difference between the amounts (e.g. a wire fee)
+        #[arg(long, default_value = "5.00")]
+        max_fee: Decimal,
+        /// Link every detected pair
+        #[arg(long)]
+        apply: bool,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Link two transactions as one transfer
+    Mark {
+        /// One side of the transfer
+        tx_a: String,
+        /// The other side of the transfer
+        tx_b: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(command: TransferCommands) -> Result<()> {
+    let ctx = get_context()?;
+
+    match command {
+        TransferCommands::Detect {
+            window_days,
+            max_fee,
+            apply,
+            json,
+        } => {
+            let detector = TransferDetector::new(ctx.repository.clone())
+                .with_window_days(window_days)
+                .with_max_fee(max_fee);
+            let pairs = detector.detect()?;
+
+            let mut linked = 0;
+            if apply {
+                for pair in &pairs {
+                    detector.mark_as_transfer(&pair.outflow_id, &pair.inflow_id)?;
+                    linked += 1;
+                }
+            }
+
+            if json {
+                let result = serde_json::json!({
+                    "pairs": pairs,
+                    "linked": linked,
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                return Ok(());
+            }
+
+            if pairs.is_empty() {
+                println!("No unlinked transfers found");
+                return Ok(());
+            }
+
+            let mut table = Table::new();
+            table.set_content_arrangement(ContentArrangement::Dynamic);
+            table.set_header(vec!["Date", "Amount", "Fee", "Days Apart", "Outflow", "Inflow"]);
+            for pair in &pairs {
+                table.add_row(vec![
+                    pair.date.to_string(),
+                    output::number(pair.amount),
+                    output::number(pair.fee),
+                    pair.days_apart.to_string(),
+                    pair.outflow_id.clone(),
+                    pair.inflow_id.clone(),
+                ]);
+            }
+            println!("{}", table);
+
+            if apply {
+                println!("{} Linked {} transfer(s)", "✓".green(), linked);
+            } else {
+                println!("Run with --apply to link them, or 'tl transfer mark <A> <B>' for one pair.");
+            }
+            Ok(())
+        }
+        TransferCommands::Mark { tx_a, tx_b, json } => {
+            let group_id = ctx.transfer_detector.mark_as_transfer(&tx_a, &tx_b)?;
+            if json {
+                let result = serde_json::json!({
+                    "transfer_group_id": group_id,
+                    "transaction_ids": [tx_a, tx_b],
+                });
+                println!("{}", serde_json::to_string_pretty(&result)?);
+            } else {
+                println!("{} Linked transfer {}", "✓".green(), group_id);
+            }
+            Ok(())
+        }
+    }
+}
@@ -9,7 +9,7 @@ use clap::{Parser, Subcommand};
 mod commands;
 mod output;
 
-use commands::{backup, compact, demo, doctor, encrypt, logs, plugin, query, status, sync, tag};
+use commands::{account, backup, balance, compact, config, demo, doctor, edit, encrypt, logs, plugin, query, statement, stats, status, sync, tag, transaction, transfer};
 
 /// Treeline - personal finance in your terminal
 #[derive(Parser)]
@@ -23,11 +23,38 @@ struct Cli {
 enum Commands {
     /// Show account status and summary
     Status {
+        /// Include archived accounts (and their balances in net worth)
+        #[arg(long)]
+        include_archived: bool,
+        /// Show monthly income vs. expenses instead
+        #[arg(long, visible_alias = "cashflow")]
+        cash_flow: bool,
+        /// Number of months for --cash-flow, including the current one
+        #[arg(long, default_value_t = 12, requires = "cash_flow")]
+        months: u32,
+        /// Check each integration's credentials with its provider
+        #[arg(long, conflicts_with = "cash_flow")]
+        check_connections: bool,
+        /// Show spending per tag instead
+        #[arg(long, conflicts_with_all = ["cash_flow", "check_connections"])]
+        by_tag: bool,
+        /// Start date for --by-tag (YYYY-MM-DD, default: start of this month)
+        #[arg(long, requires = "by_tag")]
+        from: Option<chrono::NaiveDate>,
+        /// End date for --by-tag (YYYY-MM-DD, default: today)
+        #[arg(long, requires = "by_tag")]
+        to: Option<chrono::NaiveDate>,
         /// Output as JSON
         #[arg(long)]
         json: bool,
     },
 
+    /// Manage accounts
+    Account {
+        #[command(subcommand)]
+        command: account::AccountCommands,
+    },
+
     /// Sync accounts and transactions from integrations
     Sync {
         /// Integration name (optional, syncs all if not specified)
@@ -35,6 +62,9 @@ enum Commands {
         /// Preview changes without applying
         #[arg(long)]
         dry_run: bool,
+        /// Keep syncing the other integrations when one fails (--continue-on-error=false stops at the first failure)
+        #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+        continue_on_error: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -53,15 +83,48 @@ enum Commands {
         /// Output as JSON (shorthand for --format json)
         #[arg(long)]
         json: bool,
+        /// Cancel the query after this many seconds (overrides queryTimeoutSecs)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Return at most this many rows (overrides queryMaxRows)
+        #[arg(long)]
+        max_rows: Option<usize>,
+        /// Save the query under this name, then run it (may use :name placeholders)
+        #[arg(long, value_name = "NAME")]
+        save_as: Option<String>,
+        /// Run the query saved under this name
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["sql", "file", "save_as"])]
+        named: Option<String>,
+        /// Value for a :name placeholder in a named query (repeatable)
+        #[arg(long = "param", value_name = "NAME=VALUE", requires = "named")]
+        params: Vec<String>,
+        /// Show the query plan instead of running the query
+        #[arg(long, conflicts_with_all = ["named", "save_as"])]
+        explain: bool,
+        /// With --explain, run the query and show time and rows per step
+        #[arg(long, requires = "explain")]
+        analyze: bool,
     },
 
-    /// Apply tags to transactions
+    /// Apply tags to transactions, or rename/delete a tag everywhere
+    #[command(args_conflicts_with_subcommands = true)]
     Tag {
+        #[command(subcommand)]
+        command: Option<tag::TagCommands>,
         /// Comma-separated tags to apply
-        tags: String,
+        tags: Option<String>,
         /// Transaction IDs to tag
         #[arg(long, value_delimiter = ',')]
         ids: Vec<String>,
+        /// Tag every transaction matching this SQL condition instead
+        #[arg(long = "where", value_name = "CONDITION", conflicts_with = "ids")]
+        condition: Option<String>,
+        /// Only show how many transactions --where matches, without tagging
+        #[arg(long, requires = "condition")]
+        preview: bool,
+        /// Skip confirmation when --where matches every transaction
+        #[arg(long, short = 'f')]
+        force: bool,
         /// Replace existing tags instead of appending
         #[arg(long)]
         replace: bool,
@@ -70,17 +133,84 @@ enum Commands {
         json: bool,
     },
 
+    /// Change the description, date or amount of several transactions at once
+    Edit {
+        /// Transaction IDs to edit
+        #[arg(long, value_delimiter = ',', required = true)]
+        ids: Vec<String>,
+        /// New description
+        #[arg(long)]
+        description: Option<String>,
+        /// New transaction date (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+        /// New amount (negative for spending)
+        #[arg(long, allow_negative_numbers = true)]
+        amount: Option<rust_decimal::Decimal>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Set account balances by hand
+    Balance {
+        #[command(subcommand)]
+        command: balance::BalanceCommands,
+    },
+
+    /// Add and annotate transactions
+    Transaction {
+        #[command(subcommand)]
+        command: transaction::TransactionCommands,
+    },
+
+    /// Show one account's transactions with opening, running and closing balances
+    Statement {
+        /// Account ID or name
+        account: String,
+        /// First day of the statement (YYYY-MM-DD, default: start of this month)
+        #[arg(long)]
+        from: Option<chrono::NaiveDate>,
+        /// Last day of the statement (YYYY-MM-DD, default: today)
+        #[arg(long)]
+        to: Option<chrono::NaiveDate>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Spending statistics
+    Stats {
+        #[command(subcommand)]
+        command: stats::StatsCommands,
+    },
+
+    /// Find and link transfers between your own accounts
+    Transfer {
+        #[command(subcommand)]
+        command: transfer::TransferCommands,
+    },
+
     /// Manage backups
     Backup {
         #[command(subcommand)]
         command: backup::BackupCommands,
     },
 
+    /// Export or import settings, integrations and rules
+    Config {
+        #[command(subcommand)]
+        command: config::ConfigCommands,
+    },
+
     /// Compact the database
     Compact {
         /// Skip creating safety backup
         #[arg(long)]
         skip_backup: bool,
+        /// Also keep only one balance snapshot per account per day
+        #[arg(long)]
+        dedupe_snapshots: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -91,6 +221,9 @@ enum Commands {
         /// Show verbose output
         #[arg(long, short)]
         verbose: bool,
+        /// List migrations and whether each is applied or pending
+        #[arg(long)]
+        migrations: bool,
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -154,16 +287,46 @@ fn main() -> ExitCode {
 
 fn run(cli: Cli) -> Result<()> {
     match cli.command {
-        Commands::Status { json } => status::run(json),
-        Commands::Sync { integration, dry_run, json } => sync::run(integration, dry_run, json),
-        Commands::Query { sql, file, format, json } => {
+        Commands::Status { include_archived, cash_flow, months, check_connections, by_tag, from, to, json } => {
+            if cash_flow {
+                status::run_cash_flow(months, json)
+            } else if by_tag {
+                status::run_by_tag(from, to, json)
+            } else {
+                status::run(include_archived, check_connections, json)
+            }
+        }
+        Commands::Account { command } => account::run(command),
+        Commands::Sync { integration, dry_run, continue_on_error, json } => sync::run(integration, dry_run, continue_on_error, json),
+        Commands::Query { sql, file, format, json, timeout, max_rows, save_as, named, params, explain, analyze } => {
             let fmt = if json { "json".to_string() } else { format };
-            query::run(sql.as_deref(), file.as_deref(), &fmt)
+            let explain = match (explain, analyze) {
+                (false, _) => None,
+                (true, analyze) => Some(analyze),
+            };
+            query::run(sql.as_deref(), file.as_deref(), &fmt, timeout, max_rows, save_as.as_deref(), named.as_deref(), &params, explain)
         }
-        Commands::Tag { tags, ids, replace, json } => tag::run(&tags, ids, replace, json),
+        Commands::Tag { command: Some(command), .. } => tag::run_command(command),
+        Commands::Tag { command: None, tags, ids, condition, preview, force, replace, json } => {
+            let tags = tags.ok_or_else(|| anyhow::anyhow!("No tags provided. Usage: tl tag <TAGS> --ids <IDS>"))?;
+            tag::run(&tags, ids, condition.as_deref(), preview, force, replace, json)
+        }
+        Commands::Edit { ids, description, date, amount, json } => edit::run(ids, description, date, amount, json),
+        Commands::Balance { command } => balance::run(command),
+        Commands::Transaction { command } => transaction::run(command),
+        Commands::Transfer { command } => transfer::run(command),
+        Commands::Statement { account, from, to, json } => statement::run(&account, from, to, json),
+        Commands::Stats { command } => stats::run(command),
         Commands::Backup { command } => backup::run(command),
-        Commands::Compact { skip_backup, json } => compact::run(skip_backup, json),
-        Commands::Doctor { verbose, json } => doctor::run(verbose, json),
+        Commands::Config { command } => config::run(command),
+        Commands::Compact { skip_backup, dedupe_snapshots, json } => compact::run(skip_backup, dedupe_snapshots, json),
+        Commands::Doctor { verbose, migrations, json } => {
+            if migrations {
+                doctor::run_migrations(json)
+            } else {
+                doctor::run(verbose, json)
+            }
+        }
         Commands::Encrypt { command, password, json } => encrypt::run(command, password, json),
         Commands::Decrypt { password, json } => encrypt::run_decrypt(password, json),
         Commands::Demo { command } => demo::run(command),
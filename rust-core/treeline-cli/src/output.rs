@@ -2,8 +2,16 @@
 
 #![allow(dead_code)]
 
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
 use colored::Colorize;
 use comfy_table::{presets::UTF8_FULL_CONDENSED, Table, ContentArrangement};
+use rust_decimal::Decimal;
+use treeline_core::config::Config;
+use treeline_core::domain::format;
+
+use crate::commands::get_treeline_dir;
 
 /// Print a success message
 pub fn success(msg: &str) {
@@ -49,3 +57,26 @@ pub fn format_size(bytes: u64) -> String {
         format!("{} bytes", bytes)
     }
 }
+
+/// Display locale from settings (app.locale), read once per run
+fn locale() -> Option<&'static str> {
+    static LOCALE: OnceLock<Option<String>> = OnceLock::new();
+    LOCALE
+        .get_or_init(|| Config::load(&get_treeline_dir()).ok().and_then(|c| c.locale))
+        .as_deref()
+}
+
+/// Format a money amount for the configured locale, e.g. $1,234.56 or 1.234,56 €
+pub fn amount(amount: Decimal, currency: &str) -> String {
+    format::format_amount(amount, currency, locale())
+}
+
+/// Format a number with two decimals for the configured locale
+pub fn number(amount: Decimal) -> String {
+    format::format_number(amount, locale())
+}
+
+/// Format a date for the configured locale
+pub fn date(date: NaiveDate) -> String {
+    format::format_date(date, locale())
+}
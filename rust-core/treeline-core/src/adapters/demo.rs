@@ -1,9 +1,11 @@
 //! Demo data provider for testing
 //!
-//! Generates realistic demo data matching Python CLI behavior:
+//! Generates realistic demo data matching Python CLI behavior. By default:
 //! - 6 accounts with proper balances
 //! - 180 days of transactions with realistic patterns
 //! - 180 days of balance history for all accounts
+//!
+//! The volume and date range are configurable through [`DemoOptions`].
 
 use std::f64::consts::PI;
 
@@ -12,6 +14,7 @@ use rust_decimal::Decimal;
 use uuid::Uuid;
 
 use crate::domain::{Account, BalanceSnapshot, Transaction};
+use crate::services::DemoOptions;
 
 /// Generate demo accounts
 pub fn generate_demo_accounts() -> Vec<Account> {
@@ -33,6 +36,8 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             updated_at: now,
             // Demo accounts are identified by name for deduplication
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             sf_id: None,
             sf_name: None,
             sf_currency: None,
@@ -50,6 +55,17 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         },
         Account {
             id: Uuid::parse_str("22222222-2222-2222-2222-222222222222").unwrap(),
@@ -65,6 +81,8 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             sf_id: None,
             sf_name: None,
             sf_currency: None,
@@ -82,6 +100,17 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         },
         Account {
             id: Uuid::parse_str("33333333-3333-3333-3333-333333333333").unwrap(),
@@ -97,6 +126,8 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             sf_id: None,
             sf_name: None,
             sf_currency: None,
@@ -114,6 +145,17 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         },
         Account {
             id: Uuid::parse_str("44444444-4444-4444-4444-444444444444").unwrap(),
@@ -129,6 +171,8 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             sf_id: None,
             sf_name: None,
             sf_currency: None,
@@ -146,6 +190,17 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         },
         Account {
             id: Uuid::parse_str("55555555-5555-5555-5555-555555555555").unwrap(),
@@ -161,6 +216,8 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             sf_id: None,
             sf_name: None,
             sf_currency: None,
@@ -178,6 +235,17 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         },
         Account {
             id: Uuid::parse_str("66666666-6666-6666-6666-666666666666").unwrap(),
@@ -193,6 +261,8 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             created_at: now,
             updated_at: now,
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             sf_id: None,
             sf_name: None,
             sf_currency: None,
@@ -210,240 +280,433 @@ pub fn generate_demo_accounts() -> Vec<Account> {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         },
     ]
 }
 
-/// Generate demo transactions (180 days of realistic data)
-pub fn generate_demo_transactions() -> Vec<Transaction> {
-    let now = Utc::now();
-    let today = now.date_naive();
-    let checking_id = Uuid::parse_str("11111111-1111-1111-1111-111111111111").unwrap();
-    let credit_chase_id = Uuid::parse_str("33333333-3333-3333-3333-333333333333").unwrap();
-    let credit_citi_id = Uuid::parse_str("44444444-4444-4444-4444-444444444444").unwrap();
+/// Demo data generated for one set of [`DemoOptions`]
+pub struct DemoData {
+    pub accounts: Vec<Account>,
+    pub transactions: Vec<Transaction>,
+    pub balance_snapshots: Vec<BalanceSnapshot>,
+}
 
-    let mut transactions = Vec::new();
+/// How each built-in demo account behaves, in `generate_demo_accounts` order
+#[derive(Clone, Copy)]
+enum AccountKind {
+    Checking,
+    Savings,
+    ChaseCard,
+    CitiCard,
+    /// Investment account with the given volatility
+    Market(f64),
+}
 
-    // Generate 180 days of transactions
-    for days_ago in 0..180 {
-        let date = today - Duration::days(days_ago);
-        let day_of_month = date.day();
+const ACCOUNT_KINDS: [AccountKind; 6] = [
+    AccountKind::Checking,
+    AccountKind::Savings,
+    AccountKind::ChaseCard,
+    AccountKind::CitiCard,
+    AccountKind::Market(0.08),
+    AccountKind::Market(0.06),
+];
 
-        // Paycheck on 1st and 15th (to checking)
-        if day_of_month == 1 || day_of_month == 15 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(425000, 2), // $4,250.00
-                "ACME CORP PAYROLL DIRECT DEPOSIT",
-                vec!["income".to_string(), "salary".to_string()],
-                now,
-            ));
-        }
+/// Checking balance kept on hand (per unit of density) before the rest is swept to savings
+const CHECKING_BUFFER_CENTS: i64 = 500000;
 
-        // Rent on 5th (from checking)
-        if day_of_month == 5 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-225000, 2), // -$2,250.00
-                "APARTMENT RENT PAYMENT",
-                vec!["rent".to_string(), "housing".to_string()],
-                now,
-            ));
+/// Generate demo accounts, transactions and balance history
+///
+/// Accounts cycle through the built-in set, numbering the repeats ("Primary
+/// Checking 2"). Checking, savings and credit card balances are simulated day
+/// by day from their opening balances, so each snapshot is exactly the
+/// previous one plus that day's transactions. Investment accounts have no
+/// transactions and follow a market-like curve instead.
+pub fn generate_demo_data(options: &DemoOptions, today: NaiveDate) -> DemoData {
+    let now = Utc::now();
+    let templates = generate_demo_accounts();
+    let group_size = templates.len();
+
+    let mut accounts = Vec::with_capacity(options.accounts);
+    for i in 0..options.accounts {
+        let template = &templates[i % group_size];
+        let copy = i / group_size;
+        let mut account = template.clone();
+        if copy > 0 {
+            account.id = Uuid::from_u128(template.id.as_u128() + copy as u128);
+            account.name = format!("{} {}", template.name, copy + 1);
+            account.nickname = template
+                .nickname
+                .as_ref()
+                .map(|nickname| format!("{} {}", nickname, copy + 1));
         }
+        accounts.push(account);
+    }
+    let kind = |i: usize| ACCOUNT_KINDS[i % group_size];
+
+    // Each repeat of the built-in set pays its cards from, and sweeps into,
+    // its own checking and savings accounts
+    let account_count = accounts.len();
+    let group_account = |i: usize, offset: usize| {
+        let index = i / group_size * group_size + offset;
+        (index < account_count).then_some(index)
+    };
+
+    let density = Decimal::from(options.density);
+    let mut ledger = Ledger {
+        account_ids: accounts.iter().map(|a| a.id).collect(),
+        balances: accounts
+            .iter()
+            .map(|a| a.balance.unwrap_or(Decimal::ZERO))
+            .collect(),
+        transactions: Vec::new(),
+        rng: SimpleRng::new(options.seed),
+        now,
+    };
+    let mut snapshots = Vec::new();
 
-        // Utilities on 10th (from checking)
-        if day_of_month == 10 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-15000, 2), // -$150.00
-                "CITY UTILITIES - ELECTRIC",
-                vec!["utilities".to_string()],
-                now,
-            ));
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-7500, 2), // -$75.00
-                "COMCAST INTERNET",
-                vec!["utilities".to_string(), "internet".to_string()],
-                now,
-            ));
-        }
+    let start = today - Duration::days(i64::from(options.days) - 1);
+    for date in start.iter_days().take(options.days as usize) {
+        let days_ago = (today - date).num_days();
+        let day_of_month = date.day();
 
-        // Savings transfer on 16th (from checking)
-        if day_of_month == 16 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-75000, 2), // -$750.00
-                "TRANSFER TO SAVINGS",
-                vec!["transfer".to_string(), "savings".to_string()],
-                now,
-            ));
+        for i in 0..account_count {
+            match kind(i) {
+                AccountKind::Checking => {
+                    post_checking_day(&mut ledger, i, date, density, group_account(i, 1))
+                }
+                AccountKind::Savings => {
+                    // Monthly interest at 4% a year
+                    if day_of_month == 28 {
+                        let interest = (ledger.balances[i] * Decimal::new(4, 2)
+                            / Decimal::from(12))
+                        .round_dp(2);
+                        if interest > Decimal::ZERO {
+                            ledger.post(
+                                i,
+                                date,
+                                interest,
+                                "INTEREST PAYMENT",
+                                &["interest", "income"],
+                            );
+                        }
+                    }
+                }
+                AccountKind::ChaseCard => {
+                    for round in 0..options.density {
+                        post_chase_spending(&mut ledger, i, date, days_ago + i64::from(round));
+                    }
+                    if day_of_month == 3 {
+                        ledger.post(
+                            i,
+                            date,
+                            Decimal::new(-1599, 2),
+                            "NETFLIX",
+                            &["entertainment", "subscription"],
+                        );
+                    }
+                    if day_of_month == 7 {
+                        ledger.post(
+                            i,
+                            date,
+                            Decimal::new(-1099, 2),
+                            "SPOTIFY PREMIUM",
+                            &["entertainment", "subscription"],
+                        );
+                    }
+                    // Paid in full on the 25th
+                    if day_of_month == 25 {
+                        if let Some(checking) = group_account(i, 0) {
+                            ledger.pay_card(i, checking, date, "CHASE CREDIT CARD PAYMENT");
+                        }
+                    }
+                }
+                AccountKind::CitiCard => {
+                    for round in 0..options.density {
+                        post_citi_spending(&mut ledger, i, date, days_ago + i64::from(round));
+                    }
+                    if day_of_month == 12 {
+                        ledger.post(
+                            i,
+                            date,
+                            Decimal::new(-999, 2),
+                            "AMAZON PRIME",
+                            &["subscription"],
+                        );
+                    }
+                    if day_of_month == 15 {
+                        ledger.post(
+                            i,
+                            date,
+                            Decimal::new(-4999, 2),
+                            "GYM MEMBERSHIP",
+                            &["health", "fitness"],
+                        );
+                    }
+                    // Paid in full on the 20th
+                    if day_of_month == 20 {
+                        if let Some(checking) = group_account(i, 0) {
+                            ledger.pay_card(i, checking, date, "CITI CREDIT CARD PAYMENT");
+                        }
+                    }
+                }
+                AccountKind::Market(_) => {}
+            }
         }
 
-        // Insurance on 20th (from checking)
-        if day_of_month == 20 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-18500, 2), // -$185.00
-                "STATE FARM AUTO INSURANCE",
-                vec!["insurance".to_string(), "auto".to_string()],
-                now,
-            ));
+        // End-of-day snapshots for every simulated account
+        for (i, account) in accounts.iter().enumerate() {
+            if !matches!(kind(i), AccountKind::Market(_)) {
+                let id = ledger.rng.uuid();
+                snapshots.push(create_snapshot(
+                    id,
+                    account.id,
+                    date,
+                    ledger.balances[i],
+                    now,
+                ));
+            }
         }
+    }
 
-        // Credit card payments on 25th (Chase) and 20th (Citi)
-        if day_of_month == 25 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-250000, 2), // -$2,500.00
-                "CHASE CREDIT CARD PAYMENT",
-                vec!["payment".to_string()],
-                now,
-            ));
-        }
-        if day_of_month == 20 {
-            transactions.push(create_transaction(
-                checking_id,
-                date,
-                Decimal::new(-100000, 2), // -$1,000.00
-                "CITI CREDIT CARD PAYMENT",
-                vec!["payment".to_string()],
-                now,
-            ));
+    for (i, account) in accounts.iter_mut().enumerate() {
+        match kind(i) {
+            AccountKind::Market(volatility) => {
+                let current = account.balance.unwrap_or(Decimal::ZERO);
+                snapshots.extend(market_snapshots(
+                    account.id,
+                    current.to_string().parse::<f64>().unwrap_or(0.0),
+                    volatility,
+                    options.days,
+                    today,
+                    &mut ledger.rng,
+                    now,
+                ));
+            }
+            _ => account.balance = Some(ledger.balances[i]),
         }
+    }
 
-        // Groceries every 3-4 days (on Chase card)
-        if days_ago % 3 == 0 {
-            let amounts = [-8523i64, -6745, -9234, -7100, -5899, -10523];
-            let amount = amounts[days_ago as usize % amounts.len()];
-            transactions.push(create_transaction(
-                credit_chase_id,
-                date,
-                Decimal::new(amount, 2),
-                "WHOLE FOODS MARKET",
-                vec!["groceries".to_string(), "food".to_string()],
-                now,
-            ));
-        }
+    DemoData {
+        accounts,
+        transactions: ledger.transactions,
+        balance_snapshots: snapshots,
+    }
+}
 
-        // Coffee every 2 days (on Citi card)
-        if days_ago % 2 == 0 {
-            transactions.push(create_transaction(
-                credit_citi_id,
-                date,
-                Decimal::new(-565, 2), // -$5.65
-                "STARBUCKS",
-                vec!["coffee".to_string(), "food".to_string()],
-                now,
-            ));
-        }
+/// Running balances and transactions of the simulated accounts
+struct Ledger {
+    account_ids: Vec<Uuid>,
+    balances: Vec<Decimal>,
+    transactions: Vec<Transaction>,
+    rng: SimpleRng,
+    now: chrono::DateTime<Utc>,
+}
 
-        // Dining out twice a week (on Chase card)
-        if days_ago % 3 == 1 || days_ago % 7 == 0 {
-            let restaurants = [
-                ("CHIPOTLE MEXICAN GRILL", -1250i64),
-                ("SWEETGREEN", -1450),
-                ("THE CAPITAL GRILLE", -8500),
-                ("PHO RESTAURANTS", -2200),
-                ("SHAKE SHACK", -1875),
-            ];
-            let (name, amount) = restaurants[days_ago as usize % restaurants.len()];
-            transactions.push(create_transaction(
-                credit_chase_id,
-                date,
-                Decimal::new(amount, 2),
-                name,
-                vec!["dining".to_string(), "food".to_string()],
-                now,
-            ));
-        }
+impl Ledger {
+    fn post(
+        &mut self,
+        index: usize,
+        date: NaiveDate,
+        amount: Decimal,
+        description: &str,
+        tags: &[&str],
+    ) {
+        self.balances[index] += amount;
+        let id = self.rng.uuid();
+        self.transactions.push(create_transaction(
+            id,
+            self.account_ids[index],
+            date,
+            amount,
+            description,
+            tags.iter().map(|t| t.to_string()).collect(),
+            self.now,
+        ));
+    }
 
-        // Gas every 7 days (on Citi card)
-        if days_ago % 7 == 0 {
-            transactions.push(create_transaction(
-                credit_citi_id,
-                date,
-                Decimal::new(-5500, 2), // -$55.00
-                "SHELL OIL",
-                vec!["gas".to_string(), "transportation".to_string()],
-                now,
-            ));
-        }
+    /// An everyday amount, varied by up to 15% either way
+    fn vary(&mut self, cents: i64) -> Decimal {
+        let factor = 0.85 + self.rng.next() * 0.3;
+        Decimal::new((cents as f64 * factor).round() as i64, 2)
+    }
 
-        // Subscriptions on various days
-        if day_of_month == 3 {
-            transactions.push(create_transaction(
-                credit_chase_id,
-                date,
-                Decimal::new(-1599, 2), // -$15.99
-                "NETFLIX",
-                vec!["entertainment".to_string(), "subscription".to_string()],
-                now,
-            ));
-        }
-        if day_of_month == 7 {
-            transactions.push(create_transaction(
-                credit_chase_id,
-                date,
-                Decimal::new(-1099, 2), // -$10.99
-                "SPOTIFY PREMIUM",
-                vec!["entertainment".to_string(), "subscription".to_string()],
-                now,
-            ));
-        }
-        if day_of_month == 12 {
-            transactions.push(create_transaction(
-                credit_citi_id,
+    /// Pay off a card's outstanding balance from a checking account
+    fn pay_card(&mut self, card: usize, checking: usize, date: NaiveDate, description: &str) {
+        let owed = -self.balances[card];
+        if owed > Decimal::ZERO {
+            self.post(
+                card,
                 date,
-                Decimal::new(-999, 2), // -$9.99
-                "AMAZON PRIME",
-                vec!["subscription".to_string()],
-                now,
-            ));
-        }
-        if day_of_month == 15 {
-            transactions.push(create_transaction(
-                credit_citi_id,
-                date,
-                Decimal::new(-4999, 2), // -$49.99
-                "GYM MEMBERSHIP",
-                vec!["health".to_string(), "fitness".to_string()],
-                now,
-            ));
+                owed,
+                "PAYMENT - THANK YOU",
+                &["payment", "transfer"],
+            );
+            self.post(checking, date, -owed, description, &["payment", "transfer"]);
         }
+    }
+}
 
-        // Shopping every 5 days (on Chase card)
-        if days_ago % 5 == 0 {
-            let shops = [
-                ("AMAZON.COM", -3299i64),
-                ("TARGET", -7850),
-                ("BEST BUY", -12999),
-                ("NORDSTROM", -18500),
-                ("HOME DEPOT", -8725),
-            ];
-            let (name, amount) = shops[days_ago as usize % shops.len()];
-            transactions.push(create_transaction(
-                credit_chase_id,
-                date,
-                Decimal::new(amount, 2),
-                name,
-                vec!["shopping".to_string()],
-                now,
-            ));
+/// Paychecks, bills and the savings sweep for a checking account
+fn post_checking_day(
+    ledger: &mut Ledger,
+    i: usize,
+    date: NaiveDate,
+    density: Decimal,
+    savings: Option<usize>,
+) {
+    let day_of_month = date.day();
+
+    // Paycheck on 1st and 15th, scaled with spending
+    if day_of_month == 1 || day_of_month == 15 {
+        ledger.post(
+            i,
+            date,
+            Decimal::new(425000, 2) * density,
+            "ACME CORP PAYROLL DIRECT DEPOSIT",
+            &["income", "salary"],
+        );
+    }
+    if day_of_month == 5 {
+        ledger.post(
+            i,
+            date,
+            Decimal::new(-225000, 2),
+            "APARTMENT RENT PAYMENT",
+            &["rent", "housing"],
+        );
+    }
+    if day_of_month == 10 {
+        ledger.post(
+            i,
+            date,
+            Decimal::new(-15000, 2),
+            "CITY UTILITIES - ELECTRIC",
+            &["utilities"],
+        );
+        ledger.post(
+            i,
+            date,
+            Decimal::new(-7500, 2),
+            "COMCAST INTERNET",
+            &["utilities", "internet"],
+        );
+    }
+    if day_of_month == 20 {
+        ledger.post(
+            i,
+            date,
+            Decimal::new(-18500, 2),
+            "STATE FARM AUTO INSURANCE",
+            &["insurance", "auto"],
+        );
+    }
+
+    // Sweep everything above the buffer to savings on the 16th
+    if day_of_month == 16 {
+        if let Some(savings) = savings {
+            let excess =
+                (ledger.balances[i] - Decimal::new(CHECKING_BUFFER_CENTS, 2) * density).floor();
+            if excess > Decimal::ZERO {
+                ledger.post(
+                    i,
+                    date,
+                    -excess,
+                    "TRANSFER TO SAVINGS",
+                    &["transfer", "savings"],
+                );
+                ledger.post(
+                    savings,
+                    date,
+                    excess,
+                    "TRANSFER FROM CHECKING",
+                    &["transfer", "savings"],
+                );
+            }
         }
     }
+}
+
+/// Groceries, dining out and shopping on the Chase card
+///
+/// `slot` picks the day in the repeating spending pattern.
+fn post_chase_spending(ledger: &mut Ledger, i: usize, date: NaiveDate, slot: i64) {
+    // Groceries every 3 days
+    if slot % 3 == 0 {
+        let amounts = [-8523i64, -6745, -9234, -7100, -5899, -10523];
+        let amount = ledger.vary(amounts[slot as usize % amounts.len()]);
+        ledger.post(
+            i,
+            date,
+            amount,
+            "WHOLE FOODS MARKET",
+            &["groceries", "food"],
+        );
+    }
+
+    // Dining out twice a week
+    if slot % 3 == 1 || slot % 7 == 0 {
+        let restaurants = [
+            ("CHIPOTLE MEXICAN GRILL", -1250i64),
+            ("SWEETGREEN", -1450),
+            ("THE CAPITAL GRILLE", -8500),
+            ("PHO RESTAURANTS", -2200),
+            ("SHAKE SHACK", -1875),
+        ];
+        let (name, cents) = restaurants[slot as usize % restaurants.len()];
+        let amount = ledger.vary(cents);
+        ledger.post(i, date, amount, name, &["dining", "food"]);
+    }
 
-    transactions
+    // Shopping every 5 days
+    if slot % 5 == 0 {
+        let shops = [
+            ("AMAZON.COM", -3299i64),
+            ("TARGET", -7850),
+            ("BEST BUY", -12999),
+            ("NORDSTROM", -18500),
+            ("HOME DEPOT", -8725),
+        ];
+        let (name, cents) = shops[slot as usize % shops.len()];
+        let amount = ledger.vary(cents);
+        ledger.post(i, date, amount, name, &["shopping"]);
+    }
+}
+
+/// Coffee and gas on the Citi card
+///
+/// `slot` picks the day in the repeating spending pattern.
+fn post_citi_spending(ledger: &mut Ledger, i: usize, date: NaiveDate, slot: i64) {
+    // Coffee every 2 days
+    if slot % 2 == 0 {
+        ledger.post(
+            i,
+            date,
+            Decimal::new(-565, 2),
+            "STARBUCKS",
+            &["coffee", "food"],
+        );
+    }
+
+    // Gas every 7 days
+    if slot % 7 == 0 {
+        let amount = ledger.vary(-5500);
+        ledger.post(i, date, amount, "SHELL OIL", &["gas", "transportation"]);
+    }
 }
 
 fn create_transaction(
+    id: Uuid,
     account_id: Uuid,
     date: NaiveDate,
     amount: Decimal,
@@ -451,7 +714,7 @@ fn create_transaction(
     tags: Vec<String>,
     now: chrono::DateTime<Utc>,
 ) -> Transaction {
-    let mut tx = Transaction::new(Uuid::new_v4(), account_id, amount, date);
+    let mut tx = Transaction::new(id, account_id, amount, date);
     tx.description = Some(description.to_string());
     tx.tags = tags;
     tx.created_at = now;
@@ -460,8 +723,28 @@ fn create_transaction(
     tx
 }
 
+fn create_snapshot(
+    id: Uuid,
+    account_id: Uuid,
+    date: NaiveDate,
+    balance: Decimal,
+    now: chrono::DateTime<Utc>,
+) -> BalanceSnapshot {
+    // Snapshot at end of day
+    BalanceSnapshot {
+        id,
+        account_id,
+        balance,
+        snapshot_time: date.and_hms_opt(23, 59, 59).unwrap(),
+        source: Some("sync".to_string()),
+        note: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
 /// Simple deterministic random number generator (LCG)
-/// Uses fixed seed for reproducibility
+/// Seeded so the same seed always produces the same demo data
 struct SimpleRng {
     state: u64,
 }
@@ -471,154 +754,65 @@ impl SimpleRng {
         Self { state: seed }
     }
 
-    fn next(&mut self) -> f64 {
+    fn next_u64(&mut self) -> u64 {
         // Linear congruential generator
         self.state = self
             .state
             .wrapping_mul(6364136223846793005)
             .wrapping_add(1442695040888963407);
-        // Convert to 0.0-1.0 range
-        (self.state >> 32) as f64 / u32::MAX as f64
+        self.state
     }
-}
 
-/// Generate demo balance snapshots (180 days of history for all accounts)
-pub fn generate_demo_balance_snapshots() -> Vec<BalanceSnapshot> {
-    let now = Utc::now();
-    let today = now.date_naive();
-
-    // Account configurations: (uuid, current_balance, balance_type, volatility)
-    let account_configs = [
-        (
-            "11111111-1111-1111-1111-111111111111",
-            4823.47_f64,
-            "checking",
-            0.02,
-        ),
-        (
-            "22222222-2222-2222-2222-222222222222",
-            18750.00,
-            "savings",
-            0.01,
-        ),
-        (
-            "33333333-3333-3333-3333-333333333333",
-            -2847.63,
-            "credit_chase",
-            0.15,
-        ),
-        (
-            "44444444-4444-4444-4444-444444444444",
-            -1245.89,
-            "credit_citi",
-            0.12,
-        ),
-        (
-            "55555555-5555-5555-5555-555555555555",
-            47823.15,
-            "market",
-            0.08,
-        ),
-        (
-            "66666666-6666-6666-6666-666666666666",
-            89432.67,
-            "market",
-            0.06,
-        ),
-    ];
+    fn next(&mut self) -> f64 {
+        // Convert to 0.0-1.0 range
+        (self.next_u64() >> 32) as f64 / u32::MAX as f64
+    }
 
-    let mut snapshots = Vec::new();
-    let mut rng = SimpleRng::new(42); // Fixed seed for reproducibility
-
-    for (uuid_str, current_balance, balance_type, volatility) in &account_configs {
-        let account_id = Uuid::parse_str(uuid_str).unwrap();
-        let mut balance = *current_balance;
-
-        // Generate 180 days of history going backward
-        for day in 0..180 {
-            let date = today - Duration::days(day);
-            let day_of_month = date.day();
-
-            match *balance_type {
-                "market" => {
-                    // Investment: Market fluctuations with upward trend
-                    // Working backward: so we divide by growth factor to get past values
-                    let daily_growth = 1.0 + (0.10 / 365.0); // ~10% annual
-                    let cycle = (day as f64 * 2.0 * PI / 60.0).sin() * 0.02; // 60-day cycles
-                    let noise = (rng.next() - 0.5) * volatility * 0.3;
-                    let daily_factor = daily_growth + cycle + noise;
-                    balance /= daily_factor;
-
-                    // Occasional larger moves (3% chance)
-                    if rng.next() < 0.03 {
-                        balance *= 1.0 + (rng.next() - 0.5) * 0.05;
-                    }
-                }
-                "savings" => {
-                    // Savings: Gradual growth with monthly deposits
-                    if day_of_month == 16 {
-                        balance -= 750.0; // Remove transfer going backward
-                    }
-                    if day_of_month == 28 {
-                        // Remove monthly interest
-                        let monthly_interest = balance * (0.04 / 12.0);
-                        balance -= monthly_interest;
-                    }
-                    balance *= 1.0 + (rng.next() - 0.5) * 0.002;
-                }
-                "credit_chase" => {
-                    // Chase credit card: paid on 25th
-                    if day_of_month == 25 {
-                        balance = current_balance * 0.15; // After payment
-                    } else if day_of_month < 25 {
-                        let progress = day_of_month as f64 / 25.0;
-                        balance = current_balance * (0.15 + 0.85 * progress);
-                    } else {
-                        let progress = (day_of_month - 25) as f64 / 5.0;
-                        balance = current_balance * (0.15 + 0.3 * progress);
-                    }
-                    balance *= 1.0 + (rng.next() - 0.5) * volatility;
-                }
-                "credit_citi" => {
-                    // Citi credit card: paid on 20th
-                    if day_of_month == 20 {
-                        balance = current_balance * 0.10; // After payment
-                    } else if day_of_month < 20 {
-                        let progress = day_of_month as f64 / 20.0;
-                        balance = current_balance * (0.10 + 0.90 * progress);
-                    } else {
-                        let progress = (day_of_month - 20) as f64 / 10.0;
-                        balance = current_balance * (0.10 + 0.35 * progress);
-                    }
-                    balance *= 1.0 + (rng.next() - 0.5) * volatility;
-                }
-                "checking" => {
-                    // Checking: Paycheck cycles + bill payments
-                    if day_of_month == 1 || day_of_month == 15 {
-                        balance = current_balance * 1.8; // After paycheck
-                    } else if day_of_month == 2 || day_of_month == 16 {
-                        balance = current_balance * 1.6; // Day after paycheck
-                    } else if day_of_month == 5 {
-                        balance = current_balance * 0.7; // After rent
-                    } else {
-                        let base_ratio = 0.8 + (rng.next() * 0.6);
-                        balance = current_balance * base_ratio;
-                    }
-                }
-                _ => {}
-            }
+    /// A random (version 4) UUID drawn from this generator
+    fn uuid(&mut self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_be_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_be_bytes());
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
 
-            // Create snapshot at end of day
-            let snapshot_time = date.and_hms_opt(23, 59, 59).unwrap();
-            snapshots.push(BalanceSnapshot {
-                id: Uuid::new_v4(),
-                account_id,
-                balance: Decimal::new((balance * 100.0).round() as i64, 2),
-                snapshot_time,
-                source: Some("sync".to_string()),
-                created_at: now,
-                updated_at: now,
-            });
+/// Balance history for an investment account, ending at `current_balance` today
+fn market_snapshots(
+    account_id: Uuid,
+    current_balance: f64,
+    volatility: f64,
+    days: u32,
+    today: NaiveDate,
+    rng: &mut SimpleRng,
+    now: chrono::DateTime<Utc>,
+) -> Vec<BalanceSnapshot> {
+    let mut snapshots = Vec::with_capacity(days as usize);
+    let mut balance = current_balance;
+
+    // Generate history going backward
+    for day in 0..days {
+        let date = today - Duration::days(i64::from(day));
+        let id = rng.uuid();
+        snapshots.push(create_snapshot(
+            id,
+            account_id,
+            date,
+            Decimal::new((balance * 100.0).round() as i64, 2),
+            now,
+        ));
+
+        // Market fluctuations with upward trend
+        // Working backward: so we divide by growth factor to get past values
+        let daily_growth = 1.0 + (0.10 / 365.0); // ~10% annual
+        let cycle = (day as f64 * 2.0 * PI / 60.0).sin() * 0.02; // 60-day cycles
+        let noise = (rng.next() - 0.5) * volatility * 0.3;
+        let daily_factor = daily_growth + cycle + noise;
+        balance /= daily_factor;
+
+        // Occasional larger moves (3% chance)
+        if rng.next() < 0.03 {
+            balance *= 1.0 + (rng.next() - 0.5) * 0.05;
         }
     }
 
@@ -631,7 +825,8 @@ pub fn generate_demo_balance_snapshots() -> Vec<BalanceSnapshot> {
 
 use crate::domain::result::Result;
 use crate::ports::{
-    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+    ConnectionHealth, ConnectionStatus, DataAggregationProvider, FetchAccountsResult,
+    FetchTransactionsResult, IntegrationProvider,
 };
 use serde_json::Value as JsonValue;
 
@@ -671,9 +866,10 @@ impl DataAggregationProvider for DemoDataProvider {
     }
 
     fn get_accounts(&self, _settings: &JsonValue) -> Result<FetchAccountsResult> {
+        let data = generate_demo_data(&DemoOptions::default(), Utc::now().date_naive());
         Ok(FetchAccountsResult {
-            accounts: generate_demo_accounts(),
-            balance_snapshots: generate_demo_balance_snapshots(),
+            accounts: data.accounts,
+            balance_snapshots: data.balance_snapshots,
             warnings: Vec::new(),
         })
     }
@@ -685,15 +881,15 @@ impl DataAggregationProvider for DemoDataProvider {
         _account_ids: &[String],
         _settings: &JsonValue,
     ) -> Result<FetchTransactionsResult> {
-        let transactions = generate_demo_transactions();
+        let data = generate_demo_data(&DemoOptions::default(), Utc::now().date_naive());
 
         // Convert to (provider_account_id, Transaction) pairs
         // For demo mode, we use account name as the provider ID (matched in sync service)
-        let accounts = generate_demo_accounts();
         let account_id_to_name: std::collections::HashMap<Uuid, String> =
-            accounts.into_iter().map(|a| (a.id, a.name)).collect();
+            data.accounts.into_iter().map(|a| (a.id, a.name)).collect();
 
-        let txs_with_ids: Vec<(String, Transaction)> = transactions
+        let txs_with_ids: Vec<(String, Transaction)> = data
+            .transactions
             .into_iter()
             .map(|tx| {
                 // Use account name as provider account ID for demo mode
@@ -707,7 +903,10 @@ impl DataAggregationProvider for DemoDataProvider {
 
         Ok(FetchTransactionsResult {
             transactions: txs_with_ids,
+            modified: Vec::new(),
+            removed: Vec::new(),
             warnings: Vec::new(),
+            updated_settings: None,
         })
     }
 }
@@ -717,4 +916,10 @@ impl IntegrationProvider for DemoDataProvider {
         // Demo integration needs no configuration
         Ok(serde_json::json!({}))
     }
+    fn check_connection(&self, _settings: &JsonValue) -> Result<ConnectionHealth> {
+        Ok(ConnectionHealth::new(
+            ConnectionStatus::Ok,
+            "Demo data is always available",
+        ))
+    }
 }
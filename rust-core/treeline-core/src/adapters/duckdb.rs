@@ -1,8 +1,14 @@
 //! DuckDB repository implementation
 
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread::ThreadId;
+use std::time::Duration;
 
 use fs2::FileExt;
 
@@ -10,12 +16,17 @@ use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use duckdb::{params, Connection};
 use rust_decimal::Decimal;
+use sqlparser::ast::{Query, SetExpr, Statement};
 use sqlparser::dialect::DuckDbDialect;
 use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer};
 use uuid::Uuid;
 
-use crate::domain::{Account, AutoTagRule, BalanceSnapshot, Transaction};
-use crate::services::MigrationService;
+use crate::domain::{
+    plugin_schema_name, Account, AutoTagRule, BalanceSnapshot, BatchInfo, DateBasis, PluginView,
+    Transaction,
+};
+use crate::services::{EntryPoint, MigrationService};
 
 /// Validate SQL syntax before execution to catch malformed queries early.
 /// This prevents crashes from malformed SQL reaching the database engine.
@@ -38,17 +49,249 @@ fn validate_sql_syntax(sql: &str) -> Result<()> {
     Ok(())
 }
 
+/// Check that `sql` consists only of read-only queries (SELECT/WITH)
+///
+/// The SQL is parsed rather than scanned for keywords, so a column named
+/// `update_count` is fine, while a write after a comment, tab or `;` is not.
+pub(crate) fn ensure_read_only(sql: &str) -> Result<()> {
+    let statements = Parser::parse_sql(&DuckDbDialect {}, sql).map_err(|e| {
+        let msg = e.to_string();
+        anyhow!("{}", msg.trim_start_matches("sql parser error: "))
+    })?;
+    let read_only = !statements.is_empty()
+        && statements.iter().all(
+            |statement| matches!(statement, Statement::Query(query) if is_read_only_query(query)),
+        );
+    if !read_only {
+        anyhow::bail!("Only SELECT queries are allowed");
+    }
+    Ok(())
+}
+
+/// Whether a parsed query only reads, including its CTEs
+///
+/// `WITH ... INSERT` parses as a query with an INSERT body, and
+/// `SELECT ... INTO` creates a table, so both count as writes.
+fn is_read_only_query(query: &Query) -> bool {
+    let ctes_read_only = query
+        .with
+        .iter()
+        .flat_map(|with| with.cte_tables.iter())
+        .all(|cte| is_read_only_query(&cte.query));
+    ctes_read_only && is_read_only_set_expr(&query.body)
+}
+
+fn is_read_only_set_expr(body: &SetExpr) -> bool {
+    match body {
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Query(query) => is_read_only_query(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            is_read_only_set_expr(left) && is_read_only_set_expr(right)
+        }
+        SetExpr::Values(_) | SetExpr::Table(_) => true,
+        _ => false,
+    }
+}
+
+/// Add `LIMIT max_rows` to a single SELECT that has no LIMIT or FETCH
+///
+/// Anything else (several statements, DESCRIBE, unparseable SQL) is
+/// returned unchanged.
+fn apply_row_limit(sql: &str, max_rows: Option<usize>) -> String {
+    let max_rows = match max_rows {
+        Some(max_rows) => max_rows,
+        None => return sql.to_string(),
+    };
+    let statements = match Parser::parse_sql(&DuckDbDialect {}, sql) {
+        Ok(statements) => statements,
+        Err(_) => return sql.to_string(),
+    };
+    match statements.as_slice() {
+        [Statement::Query(query)] if query.limit_clause.is_none() && query.fetch.is_none() => {
+            format!("{}\nLIMIT {}", trim_statement_end(sql), max_rows)
+        }
+        _ => sql.to_string(),
+    }
+}
+
+/// `sql` without the semicolons, comments and whitespace that end it
+///
+/// A LIMIT appended after `SELECT 1; -- note` would otherwise become a
+/// statement of its own.
+fn trim_statement_end(sql: &str) -> &str {
+    let tokens = match Tokenizer::new(&DuckDbDialect {}, sql).tokenize_with_location() {
+        Ok(tokens) => tokens,
+        Err(_) => return sql,
+    };
+    let end = match tokens.iter().rev().find(|t| {
+        !matches!(
+            t.token,
+            Token::Whitespace(_) | Token::SemiColon | Token::EOF
+        )
+    }) {
+        Some(last) => last.span.end,
+        None => return sql,
+    };
+
+    // Locations are 1-based lines and character columns
+    let (mut line, mut column) = (1, 1);
+    for (offset, c) in sql.char_indices() {
+        if line == end.line && column == end.column {
+            return &sql[..offset];
+        }
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    sql
+}
+
+/// Run `query` on `conn`, interrupting it once `timeout` has passed
+fn run_with_timeout<T>(
+    conn: &Connection,
+    timeout: Option<Duration>,
+    query: impl FnOnce(&Connection) -> Result<T>,
+) -> Result<T> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return query(conn),
+    };
+
+    let interrupt = conn.interrupt_handle();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let (done, finished) = mpsc::channel::<()>();
+    let watchdog = {
+        let timed_out = Arc::clone(&timed_out);
+        std::thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(timeout) {
+                timed_out.store(true, Ordering::SeqCst);
+                interrupt.interrupt();
+            }
+        })
+    };
+
+    let result = query(conn);
+    drop(done);
+    let _ = watchdog.join();
+
+    match result {
+        Err(_) if timed_out.load(Ordering::SeqCst) => Err(anyhow!(
+            "Query cancelled after exceeding the {}s time limit",
+            timeout.as_secs_f64()
+        )),
+        result => result,
+    }
+}
+
+/// Core tables that must exist once migrations have run.
+/// Used by `check_integrity` to detect interrupted migrations.
+const EXPECTED_SYS_TABLES: &[&str] = &[
+    "sys_migrations",
+    "sys_accounts",
+    "sys_transactions",
+    "sys_balance_snapshots",
+    "sys_integrations",
+    "sys_transactions_rules",
+];
+
+/// `sys_transactions` columns that hold provider transaction IDs.
+/// Column names can't be bound as parameters, so lookups are limited to these.
+const PROVIDER_ID_COLUMNS: &[&str] = &["sf_id", "lf_id", "pl_id"];
+
+/// Most IDs bound into one `IN (...)` list by `existing_external_ids`
+const EXISTING_IDS_CHUNK_SIZE: usize = 500;
+
+/// Idle read connections kept for reuse
+const READ_POOL_SIZE: usize = 4;
+
+/// How often a running [`PendingQuery`] re-checks whether it was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Failures callers may want to tell apart, e.g. to show a different UI
+///
+/// Returned by the repository's open and migration methods and some updates. It
+/// is a regular `std::error::Error`, so `?` turns it into an `anyhow::Error`
+/// and callers holding one can get it back with `downcast_ref`.
+#[derive(Debug, thiserror::Error)]
+pub enum RepositoryError {
+    /// The account, transaction or database file doesn't exist
+    #[error("{kind} not found: {id}")]
+    NotFound { kind: &'static str, id: String },
+
+    /// Another handle holds the database's filesystem lock
+    #[error("Database is locked by another process: {0}")]
+    Locked(PathBuf),
+
+    /// DuckDB rejected a statement or couldn't open the file
+    #[error("Database error: {0}")]
+    Sql(#[from] duckdb::Error),
+
+    /// A schema migration failed to apply
+    #[error("Migration failed: {0}")]
+    Migration(String),
+
+    /// The encrypted database couldn't be opened with the given key
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    /// Reading or creating the lock file failed
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The query was stopped through its [`QueryHandle`]
+    #[error("Query cancelled")]
+    Cancelled,
+}
+
+impl RepositoryError {
+    fn not_found(kind: &'static str, id: impl Into<String>) -> Self {
+        Self::NotFound {
+            kind,
+            id: id.into(),
+        }
+    }
+}
+
+/// Result of repository methods that report a [`RepositoryError`]
+pub type RepositoryResult<T> = std::result::Result<T, RepositoryError>;
+
 /// DuckDB repository implementation
 ///
 /// Uses a filesystem lock to prevent concurrent access from multiple processes
 /// (app, CLI, etc.). The lock is held for the lifetime of the repository.
+///
+/// Writes go through a single connection behind a mutex. Read methods use
+/// their own connections to the same database, so threads sharing one
+/// repository can read while another read or a write is running.
+///
+/// Hot queries (existence checks during sync, account and per-account
+/// transaction lookups) use `prepare_cached`, so each connection prepares
+/// them once and reuses the statement, keyed by its SQL text.
 pub struct DuckDbRepository {
     conn: Mutex<Connection>,
+    /// Idle read connections - see `read_conn()`
+    readers: Mutex<Vec<Connection>>,
+    /// Bumped when `compact()` swaps the database, so older read connections are dropped
+    reader_epoch: AtomicU64,
     db_path: PathBuf,
     encryption_key: Option<String>,
+    /// Opened with `access_mode=READ_ONLY` - see `new_read_only()`
+    read_only: bool,
     /// Filesystem lock file - held for the lifetime of the repository.
-    /// The lock is released when this file is dropped.
-    _lock_file: File,
+    /// The lock is released when this file is dropped. Read-only handles don't take it.
+    _lock_file: Option<File>,
+    /// Bumped after every write through this repository - see `data_version()`
+    data_version: AtomicU64,
+    /// Thread that has a `with_transaction` transaction open, if any.
+    /// Other threads wait for it to finish before using `conn` - see `lock_conn()`
+    transaction_owner: Mutex<Option<ThreadId>>,
+    /// Notified when a transaction ends
+    transaction_done: Condvar,
+    /// Recorded with audit entries - see `set_entry_point()`
+    entry_point: OnceLock<EntryPoint>,
 }
 
 impl DuckDbRepository {
@@ -59,27 +302,89 @@ impl DuckDbRepository {
     ///
     /// Uses a filesystem lock to prevent concurrent access from multiple processes
     /// or threads. The lock is held for the lifetime of the repository.
-    pub fn new(db_path: &Path, encryption_key: Option<&str>) -> Result<Self> {
+    pub fn new(db_path: &Path, encryption_key: Option<&str>) -> RepositoryResult<Self> {
+        Self::open(db_path, encryption_key, true)
+    }
+
+    /// Like `new`, but fails with [`RepositoryError::Locked`] instead of
+    /// waiting when another handle holds the filesystem lock
+    pub fn try_new(db_path: &Path, encryption_key: Option<&str>) -> RepositoryResult<Self> {
+        Self::open(db_path, encryption_key, false)
+    }
+
+    fn open(db_path: &Path, encryption_key: Option<&str>, wait: bool) -> RepositoryResult<Self> {
         // Acquire filesystem lock first
-        let lock_file = Self::acquire_file_lock(db_path)?;
+        let lock_file = Self::acquire_file_lock(db_path, wait)?;
 
         // Open DuckDB connection
-        let conn = Self::try_open_connection(db_path, encryption_key)?;
+        let conn = Self::try_open_connection(db_path, encryption_key, false)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            readers: Mutex::new(Vec::new()),
+            reader_epoch: AtomicU64::new(0),
+            db_path: db_path.to_path_buf(),
+            encryption_key: encryption_key.map(|k| k.to_string()),
+            read_only: false,
+            _lock_file: Some(lock_file),
+            data_version: AtomicU64::new(0),
+            transaction_owner: Mutex::new(None),
+            transaction_done: Condvar::new(),
+            entry_point: OnceLock::new(),
+        })
+    }
+
+    /// Open an existing database read-only
+    ///
+    /// Opens DuckDB with `access_mode=READ_ONLY` and does not take Treeline's
+    /// filesystem lock, so a long-lived reader (e.g. for UI queries) doesn't queue
+    /// behind other handles and several readers can be open at once. Read methods such as
+    /// `get_accounts`, `get_transactions` and `execute_query` work as usual;
+    /// any write (upserts, migrations, `ensure_schema`, `compact`) returns an error.
+    pub fn new_read_only(db_path: &Path, encryption_key: Option<&str>) -> RepositoryResult<Self> {
+        if !db_path.exists() {
+            return Err(RepositoryError::not_found(
+                "Database",
+                db_path.display().to_string(),
+            ));
+        }
+
+        let conn = Self::try_open_connection(db_path, encryption_key, true)?;
 
         Ok(Self {
             conn: Mutex::new(conn),
+            readers: Mutex::new(Vec::new()),
+            reader_epoch: AtomicU64::new(0),
             db_path: db_path.to_path_buf(),
             encryption_key: encryption_key.map(|k| k.to_string()),
-            _lock_file: lock_file,
+            read_only: true,
+            _lock_file: None,
+            data_version: AtomicU64::new(0),
+            transaction_owner: Mutex::new(None),
+            transaction_done: Condvar::new(),
+            entry_point: OnceLock::new(),
         })
     }
 
+    /// Say which app is making changes, for the transaction audit trail
+    ///
+    /// Only the first call has an effect. Until then, audit entries have no entry point.
+    pub fn set_entry_point(&self, entry_point: EntryPoint) {
+        let _ = self.entry_point.set(entry_point);
+    }
+
+    /// Whether this handle was opened with `new_read_only()`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Acquire a filesystem lock for the database.
     ///
     /// This prevents concurrent access from multiple processes (app, CLI, etc.).
-    /// Uses a blocking lock - if another process holds the lock, this will wait
-    /// until it's released. The OS kernel manages the queue of waiters.
-    fn acquire_file_lock(db_path: &Path) -> Result<File> {
+    /// With `wait`, if another process holds the lock this blocks until it's
+    /// released (the OS kernel manages the queue of waiters); otherwise it
+    /// returns [`RepositoryError::Locked`] straight away.
+    fn acquire_file_lock(db_path: &Path, wait: bool) -> RepositoryResult<File> {
         let lock_path = db_path.with_extension("duckdb.lock");
 
         // Ensure parent directory exists
@@ -93,38 +398,55 @@ impl DuckDbRepository {
             .write(true)
             .create(true)
             .truncate(false)
-            .open(&lock_path)
-            .map_err(|e| anyhow!("Failed to open lock file {}: {}", lock_path.display(), e))?;
+            .open(&lock_path)?;
+
+        if !wait {
+            return match lock_file.try_lock_exclusive() {
+                Ok(()) => Ok(lock_file),
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                    Err(RepositoryError::Locked(db_path.to_path_buf()))
+                }
+                Err(e) => Err(e.into()),
+            };
+        }
 
         // Acquire exclusive lock (blocks until available)
         // The OS kernel queues waiters and wakes them in order when lock is released
-        lock_file
-            .lock_exclusive()
-            .map_err(|e| anyhow!("Failed to acquire database lock: {}", e))?;
+        lock_file.lock_exclusive()?;
 
         Ok(lock_file)
     }
 
     /// Attempt to open a database connection (called by new() with retry logic)
-    fn try_open_connection(db_path: &Path, encryption_key: Option<&str>) -> Result<Connection> {
+    fn try_open_connection(
+        db_path: &Path,
+        encryption_key: Option<&str>,
+        read_only: bool,
+    ) -> RepositoryResult<Connection> {
         // IMPORTANT: Disable extension autoloading to avoid macOS code signing issues
         // (cached extensions in ~/.duckdb/extensions may have different Team IDs)
         let conn = if let Some(key) = encryption_key {
             // Encrypted database: open in-memory first, then ATTACH encrypted file
             let config = duckdb::Config::default().enable_autoload_extension(false)?;
             let conn = Connection::open_in_memory_with_flags(config)?;
+            let read_only_option = if read_only { ", READ_ONLY" } else { "" };
             conn.execute(
                 &format!(
-                    "ATTACH '{}' AS main_db (ENCRYPTION_KEY '{}')",
+                    "ATTACH '{}' AS main_db (ENCRYPTION_KEY '{}'{})",
                     db_path.display(),
-                    key
+                    key,
+                    read_only_option
                 ),
                 [],
-            )?;
+            )
+            .map_err(|e| RepositoryError::Encryption(e.to_string()))?;
             conn.execute("USE main_db", [])?;
             conn
         } else {
-            let config = duckdb::Config::default().enable_autoload_extension(false)?;
+            let mut config = duckdb::Config::default().enable_autoload_extension(false)?;
+            if read_only {
+                config = config.access_mode(duckdb::AccessMode::ReadOnly)?;
+            }
             Connection::open_with_flags(db_path, config)?
         };
 
@@ -135,17 +457,112 @@ impl DuckDbRepository {
         Ok(conn)
     }
 
+    /// Lock the shared connection, first waiting for any transaction another
+    /// thread has open on it to end
+    ///
+    /// The owner is checked again once the lock is held, since another
+    /// thread may have begun a transaction in between. `transaction_owner` is
+    /// never held while waiting for `conn`, so the two can't deadlock.
+    fn lock_conn(&self) -> MutexGuard<'_, Connection> {
+        let me = std::thread::current().id();
+        loop {
+            self.wait_for_transaction(me);
+            let conn = self.conn.lock().unwrap();
+            if self
+                .transaction_owner
+                .lock()
+                .unwrap()
+                .is_none_or(|owner| owner == me)
+            {
+                return conn;
+            }
+        }
+    }
+
+    /// Block until no thread other than `me` has a transaction open
+    fn wait_for_transaction(&self, me: ThreadId) {
+        let mut owner = self.transaction_owner.lock().unwrap();
+        while owner.is_some_and(|owner| owner != me) {
+            owner = self.transaction_done.wait(owner).unwrap();
+        }
+    }
+
+    /// Whether the current thread has a `with_transaction` transaction open
+    fn in_own_transaction(&self) -> bool {
+        *self.transaction_owner.lock().unwrap() == Some(std::thread::current().id())
+    }
+
+    /// Lock the connection for a write, bumping the data version once it's done
+    fn write_conn(&self) -> WriteConn<'_> {
+        WriteConn {
+            conn: self.lock_conn(),
+            data_version: &self.data_version,
+        }
+    }
+
+    /// A connection for reads, taken from the pool or cloned from the write connection
+    ///
+    /// Inside `with_transaction` reads on the transaction's thread use the
+    /// write connection instead, so they see its uncommitted changes. Other
+    /// threads keep using pooled connections, which don't.
+    fn read_conn(&self) -> Result<ReadConn<'_>> {
+        if self.in_own_transaction() {
+            return Ok(ReadConn::Shared(self.conn.lock().unwrap()));
+        }
+
+        let (pooled, epoch) = {
+            let mut readers = self.readers.lock().unwrap();
+            (readers.pop(), self.reader_epoch.load(Ordering::SeqCst))
+        };
+        let conn = match pooled {
+            Some(conn) => conn,
+            None => self.new_read_connection()?,
+        };
+
+        Ok(ReadConn::Pooled {
+            conn: Some(conn),
+            epoch,
+            repository: self,
+        })
+    }
+
+    /// Open another connection to the same database, outside the pool
+    fn new_read_connection(&self) -> Result<Connection> {
+        let conn = self.conn.lock().unwrap().try_clone()?;
+        if self.encryption_key.is_some() {
+            // USE is per connection; clones start in the in-memory catalog
+            conn.execute("USE main_db", [])?;
+        }
+        Ok(conn)
+    }
+
+    /// Counter that changes after every write through this repository
+    ///
+    /// Lets callers cache read results and tell when they may be stale. Writes
+    /// made by other processes or other handles to the same file are not counted.
+    pub fn data_version(&self) -> u64 {
+        self.data_version.load(Ordering::SeqCst)
+    }
+
     /// Run database migrations using the MigrationService
     ///
     /// Returns the migration result showing what was applied.
-    pub fn run_migrations(&self) -> Result<crate::services::MigrationResult> {
-        let conn = self.conn.lock().unwrap();
+    pub fn run_migrations(&self) -> RepositoryResult<crate::services::MigrationResult> {
+        let conn = self.write_conn();
         let migration_service = MigrationService::new(&conn);
-        migration_service.run_pending()
+        migration_service
+            .run_pending()
+            .map_err(|e| RepositoryError::Migration(format!("{:#}", e)))
+    }
+
+    /// Get applied/pending status of every migration without running any
+    pub fn migration_status(&self) -> Result<Vec<crate::services::MigrationStatusEntry>> {
+        let conn = self.read_conn()?;
+        MigrationService::new(&conn).status()
     }
 
     /// Ensure database schema exists (runs pending migrations)
-    pub fn ensure_schema(&self) -> Result<()> {
+    pub fn ensure_schema(&self) -> RepositoryResult<()> {
         self.run_migrations()?;
         Ok(())
     }
@@ -156,7 +573,7 @@ impl DuckDbRepository {
     /// (like backups) to ensure data consistency. Without this, data in the WAL
     /// file may not be included in the backup.
     pub fn checkpoint(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
         conn.execute_batch("CHECKPOINT")?;
         Ok(())
     }
@@ -166,12 +583,26 @@ impl DuckDbRepository {
         &self.db_path
     }
 
+    /// Key used to open this database, for opening its backups the same way
+    pub(crate) fn encryption_key(&self) -> Option<&str> {
+        self.encryption_key.as_deref()
+    }
+
     // === Account operations ===
 
-    pub fn get_accounts(&self) -> Result<Vec<Account>> {
-        let conn = self.conn.lock().unwrap();
+    /// Get accounts, optionally including archived ones
+    ///
+    /// Archived accounts are hidden by default (`include_archived = false`).
+    /// Pass `true` when every account is needed, e.g. to map provider IDs during sync.
+    pub fn get_accounts(&self, include_archived: bool) -> Result<Vec<Account>> {
+        let conn = self.read_conn()?;
+        let where_clause = if include_archived {
+            ""
+        } else {
+            " WHERE COALESCE(a.is_archived, FALSE) = FALSE"
+        };
         // Join with balance_snapshots to get the latest balance for each account
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(&format!(
             "SELECT a.account_id, a.name, a.nickname, a.account_type, a.currency,
                     a.external_ids, a.institution_name, a.institution_url, a.institution_domain,
                     a.created_at, a.updated_at,
@@ -182,9 +613,13 @@ impl DuckDbRepository {
                     a.sf_id, a.sf_name, a.sf_currency, a.sf_balance, a.sf_available_balance,
                     a.sf_balance_date, a.sf_org_name, a.sf_org_url, a.sf_org_domain, a.sf_extra,
                     a.lf_id, a.lf_name, a.lf_institution_name, a.lf_institution_logo,
-                    a.lf_provider, a.lf_currency, a.lf_status
-             FROM sys_accounts a",
-        )?;
+                    a.lf_provider, a.lf_currency, a.lf_status, a.is_archived,
+                    a.pl_id, a.pl_item_id, a.pl_name, a.pl_official_name, a.pl_mask,
+                    a.pl_type, a.pl_subtype, a.pl_balance_current, a.pl_balance_available, a.pl_currency,
+                    a.account_group
+             FROM sys_accounts a{}",
+            where_clause
+        ))?;
 
         let accounts = stmt
             .query_map([], |row| self.row_to_account(row))?
@@ -195,8 +630,8 @@ impl DuckDbRepository {
     }
 
     pub fn get_account_by_id(&self, id: &str) -> Result<Option<Account>> {
-        let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare_cached(
             "SELECT a.account_id, a.name, a.nickname, a.account_type, a.currency,
                     a.external_ids, a.institution_name, a.institution_url, a.institution_domain,
                     a.created_at, a.updated_at,
@@ -207,7 +642,10 @@ impl DuckDbRepository {
                     a.sf_id, a.sf_name, a.sf_currency, a.sf_balance, a.sf_available_balance,
                     a.sf_balance_date, a.sf_org_name, a.sf_org_url, a.sf_org_domain, a.sf_extra,
                     a.lf_id, a.lf_name, a.lf_institution_name, a.lf_institution_logo,
-                    a.lf_provider, a.lf_currency, a.lf_status
+                    a.lf_provider, a.lf_currency, a.lf_status, a.is_archived,
+                    a.pl_id, a.pl_item_id, a.pl_name, a.pl_official_name, a.pl_mask,
+                    a.pl_type, a.pl_subtype, a.pl_balance_current, a.pl_balance_available, a.pl_currency,
+                    a.account_group
              FROM sys_accounts a WHERE a.account_id = ?",
         )?;
 
@@ -216,6 +654,107 @@ impl DuckDbRepository {
         Ok(account)
     }
 
+    /// Find an account by name or nickname, ignoring case
+    ///
+    /// Archived accounts are included. Errors if more than one account matches.
+    pub fn find_account_by_name(&self, name: &str) -> Result<Option<Account>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT a.account_id, a.name, a.nickname, a.account_type, a.currency,
+                    a.external_ids, a.institution_name, a.institution_url, a.institution_domain,
+                    a.created_at, a.updated_at,
+                    (SELECT balance FROM sys_balance_snapshots bs
+                     WHERE bs.account_id = a.account_id
+                     ORDER BY bs.snapshot_time DESC LIMIT 1) as latest_balance,
+                    a.classification, a.is_manual,
+                    a.sf_id, a.sf_name, a.sf_currency, a.sf_balance, a.sf_available_balance,
+                    a.sf_balance_date, a.sf_org_name, a.sf_org_url, a.sf_org_domain, a.sf_extra,
+                    a.lf_id, a.lf_name, a.lf_institution_name, a.lf_institution_logo,
+                    a.lf_provider, a.lf_currency, a.lf_status, a.is_archived,
+                    a.pl_id, a.pl_item_id, a.pl_name, a.pl_official_name, a.pl_mask,
+                    a.pl_type, a.pl_subtype, a.pl_balance_current, a.pl_balance_available, a.pl_currency,
+                    a.account_group
+             FROM sys_accounts a
+             WHERE lower(a.name) = lower(?) OR lower(a.nickname) = lower(?)
+             ORDER BY a.name",
+        )?;
+        let rows = stmt.query_map(params![name, name], |row| self.row_to_account(row))?;
+
+        let mut matches = Vec::new();
+        for row in rows {
+            matches.push(row?);
+        }
+        if matches.len() > 1 {
+            let names: Vec<String> = matches
+                .iter()
+                .map(|a| format!("{} ({})", a.name, a.id))
+                .collect();
+            anyhow::bail!(
+                "Account name '{}' is ambiguous; it matches {}",
+                name,
+                names.join(", ")
+            );
+        }
+        Ok(matches.pop())
+    }
+
+    /// Find the account a user means by a full ID, an ID prefix, a name or a nickname
+    ///
+    /// Tries, in order: the full ID, an exact name or nickname (ignoring
+    /// case), then a prefix of the ID. The first of these with a single match
+    /// wins; several matches come back as candidates. ID prefixes need at
+    /// least four characters. Failing all three, accounts whose name or
+    /// nickname contains `query` are offered as candidates. Archived accounts
+    /// are included.
+    pub fn resolve_account(&self, query: &str) -> Result<AccountResolution> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(AccountResolution::Candidates(Vec::new()));
+        }
+        if Uuid::parse_str(query).is_ok() {
+            if let Some(account) = self.get_account_by_id(query)? {
+                return Ok(AccountResolution::Exact(account));
+            }
+        }
+
+        let accounts = self.get_accounts(true)?;
+        let needle = query.to_lowercase();
+        let names_of = |account: &Account| {
+            let mut names = vec![account.name.to_lowercase()];
+            names.extend(account.nickname.as_ref().map(|n| n.to_lowercase()));
+            names
+        };
+
+        let by_name: Vec<&Account> = accounts
+            .iter()
+            .filter(|a| names_of(*a).contains(&needle))
+            .collect();
+        // Like git, insist on a few characters so a short word can't match an ID by accident
+        let by_prefix: Vec<&Account> = accounts
+            .iter()
+            .filter(|a| needle.len() >= 4 && a.id.to_string().starts_with(&needle))
+            .collect();
+        let candidates = |matches: Vec<&Account>| {
+            let mut matches: Vec<Account> = matches.into_iter().cloned().collect();
+            matches.sort_by(|a, b| a.name.cmp(&b.name));
+            AccountResolution::Candidates(matches)
+        };
+        for matches in [by_name, by_prefix] {
+            match matches.len() {
+                0 => {}
+                1 => return Ok(AccountResolution::Exact(matches[0].clone())),
+                _ => return Ok(candidates(matches)),
+            }
+        }
+
+        Ok(candidates(
+            accounts
+                .iter()
+                .filter(|a| names_of(*a).iter().any(|n| n.contains(&needle)))
+                .collect(),
+        ))
+    }
+
     fn row_to_account(&self, row: &duckdb::Row) -> std::result::Result<Account, duckdb::Error> {
         // Column indices from SELECT:
         // 0: account_id, 1: name, 2: nickname, 3: account_type, 4: currency,
@@ -224,7 +763,10 @@ impl DuckDbRepository {
         // 14: sf_id, 15: sf_name, 16: sf_currency, 17: sf_balance, 18: sf_available_balance,
         // 19: sf_balance_date, 20: sf_org_name, 21: sf_org_url, 22: sf_org_domain, 23: sf_extra,
         // 24: lf_id, 25: lf_name, 26: lf_institution_name, 27: lf_institution_logo,
-        // 28: lf_provider, 29: lf_currency, 30: lf_status
+        // 28: lf_provider, 29: lf_currency, 30: lf_status, 31: is_archived,
+        // 32: pl_id, 33: pl_item_id, 34: pl_name, 35: pl_official_name, 36: pl_mask,
+        // 37: pl_type, 38: pl_subtype, 39: pl_balance_current, 40: pl_balance_available, 41: pl_currency,
+        // 42: account_group
         let id_str: String = row.get(0)?;
         // Note: column 5 (external_ids) is read but not used - kept for backwards compat
         let created_str: String = row.get(9).unwrap_or_default();
@@ -260,6 +802,14 @@ impl DuckDbRepository {
                 .ok()
                 .flatten()
                 .unwrap_or(false),
+            // Archived flag (column 31)
+            is_archived: row
+                .get::<_, Option<bool>>(31)
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            // Group (column 42)
+            account_group: row.get::<_, Option<String>>(42).ok().flatten(),
             // SimpleFIN fields (columns 14-23)
             sf_id: row.get(14).ok(),
             sf_name: row.get(15).ok(),
@@ -279,11 +829,30 @@ impl DuckDbRepository {
             lf_provider: row.get(28).ok(),
             lf_currency: row.get(29).ok(),
             lf_status: row.get(30).ok(),
+            // Plaid fields (columns 32-41)
+            pl_id: row.get(32).ok(),
+            pl_item_id: row.get(33).ok(),
+            pl_name: row.get(34).ok(),
+            pl_official_name: row.get(35).ok(),
+            pl_mask: row.get(36).ok(),
+            pl_type: row.get(37).ok(),
+            pl_subtype: row.get(38).ok(),
+            pl_balance_current: row
+                .get::<_, Option<f64>>(39)
+                .ok()
+                .flatten()
+                .map(|f| Decimal::try_from(f).unwrap_or_default()),
+            pl_balance_available: row
+                .get::<_, Option<f64>>(40)
+                .ok()
+                .flatten()
+                .map(|f| Decimal::try_from(f).unwrap_or_default()),
+            pl_currency: row.get(41).ok(),
         })
     }
 
     pub fn upsert_account(&self, account: &Account) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
         // Write empty JSON for external_ids - kept for backwards compat with DB schema
         let external_ids = "{}";
         let sf_extra = account.sf_extra.as_ref().map(|v| v.to_string());
@@ -298,14 +867,18 @@ impl DuckDbRepository {
                                        sf_id, sf_name, sf_currency, sf_balance, sf_available_balance,
                                        sf_balance_date, sf_org_name, sf_org_url, sf_org_domain, sf_extra,
                                        lf_id, lf_name, lf_institution_name, lf_institution_logo,
-                                       lf_provider, lf_currency, lf_status)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                       lf_provider, lf_currency, lf_status,
+                                       pl_id, pl_item_id, pl_name, pl_official_name, pl_mask,
+                                       pl_type, pl_subtype, pl_balance_current, pl_balance_available, pl_currency)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                     ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
              ON CONFLICT (account_id) DO UPDATE SET
                 name = EXCLUDED.name,
                 nickname = COALESCE(sys_accounts.nickname, EXCLUDED.nickname),
                 account_type = COALESCE(sys_accounts.account_type, EXCLUDED.account_type),
                 classification = COALESCE(sys_accounts.classification, EXCLUDED.classification),
-                currency = EXCLUDED.currency,
+                currency = CASE WHEN COALESCE(sys_accounts.currency_overridden, FALSE)
+                                THEN sys_accounts.currency ELSE EXCLUDED.currency END,
                 external_ids = EXCLUDED.external_ids,
                 institution_name = COALESCE(EXCLUDED.institution_name, sys_accounts.institution_name),
                 institution_url = COALESCE(EXCLUDED.institution_url, sys_accounts.institution_url),
@@ -328,7 +901,17 @@ impl DuckDbRepository {
                 lf_institution_logo = COALESCE(EXCLUDED.lf_institution_logo, sys_accounts.lf_institution_logo),
                 lf_provider = COALESCE(EXCLUDED.lf_provider, sys_accounts.lf_provider),
                 lf_currency = COALESCE(EXCLUDED.lf_currency, sys_accounts.lf_currency),
-                lf_status = COALESCE(EXCLUDED.lf_status, sys_accounts.lf_status)",
+                lf_status = COALESCE(EXCLUDED.lf_status, sys_accounts.lf_status),
+                pl_id = COALESCE(EXCLUDED.pl_id, sys_accounts.pl_id),
+                pl_item_id = COALESCE(EXCLUDED.pl_item_id, sys_accounts.pl_item_id),
+                pl_name = COALESCE(EXCLUDED.pl_name, sys_accounts.pl_name),
+                pl_official_name = COALESCE(EXCLUDED.pl_official_name, sys_accounts.pl_official_name),
+                pl_mask = COALESCE(EXCLUDED.pl_mask, sys_accounts.pl_mask),
+                pl_type = COALESCE(EXCLUDED.pl_type, sys_accounts.pl_type),
+                pl_subtype = COALESCE(EXCLUDED.pl_subtype, sys_accounts.pl_subtype),
+                pl_balance_current = COALESCE(EXCLUDED.pl_balance_current, sys_accounts.pl_balance_current),
+                pl_balance_available = COALESCE(EXCLUDED.pl_balance_available, sys_accounts.pl_balance_available),
+                pl_currency = COALESCE(EXCLUDED.pl_currency, sys_accounts.pl_currency)",
             params![
                 account.id.to_string(),
                 account.name,
@@ -360,12 +943,91 @@ impl DuckDbRepository {
                 account.lf_provider,
                 account.lf_currency,
                 account.lf_status,
+                account.pl_id,
+                account.pl_item_id,
+                account.pl_name,
+                account.pl_official_name,
+                account.pl_mask,
+                account.pl_type,
+                account.pl_subtype,
+                account
+                    .pl_balance_current
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                account
+                    .pl_balance_available
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                account.pl_currency,
             ],
         )?;
 
         Ok(())
     }
 
+    /// Archive or unarchive an account
+    ///
+    /// Archiving is distinct from deletion: transactions and balance snapshots
+    /// are kept, but the account is hidden from `get_accounts(false)` and skipped by sync.
+    /// Returns an error if the account doesn't exist.
+    pub fn set_account_archived(&self, account_id: &str, archived: bool) -> RepositoryResult<()> {
+        let conn = self.write_conn();
+        let updated = conn.execute(
+            "UPDATE sys_accounts SET is_archived = ?, updated_at = ? WHERE account_id = ?",
+            params![archived, Utc::now().to_rfc3339(), account_id],
+        )?;
+
+        if updated == 0 {
+            return Err(RepositoryError::not_found("Account", account_id));
+        }
+
+        Ok(())
+    }
+
+    /// File an account under a group, or take it out of its group with `None`
+    ///
+    /// Groups only affect reporting; sync never changes them.
+    /// Returns an error if the account doesn't exist.
+    pub fn set_account_group(&self, account_id: &str, group: Option<&str>) -> RepositoryResult<()> {
+        let conn = self.write_conn();
+        let updated = conn.execute(
+            "UPDATE sys_accounts SET account_group = ?, updated_at = ? WHERE account_id = ?",
+            params![group, Utc::now().to_rfc3339(), account_id],
+        )?;
+
+        if updated == 0 {
+            return Err(RepositoryError::not_found("Account", account_id));
+        }
+
+        Ok(())
+    }
+
+    /// Override the currency an account is reported in
+    ///
+    /// The code is normalized to uppercase and must be three letters (ISO 4217).
+    /// Once overridden, sync no longer replaces the currency with the provider's.
+    /// Returns an error if the code is invalid or the account doesn't exist.
+    pub fn set_account_currency(&self, account_id: &str, currency: &str) -> Result<()> {
+        let currency = Account::normalize_currency(currency);
+        if !Account::is_currency_code(&currency) {
+            return Err(anyhow!(
+                "Invalid currency code '{}': expected a 3-letter ISO 4217 code such as USD",
+                currency
+            ));
+        }
+
+        let conn = self.write_conn();
+        let updated = conn.execute(
+            "UPDATE sys_accounts SET currency = ?, currency_overridden = TRUE, updated_at = ?
+             WHERE account_id = ?",
+            params![currency, Utc::now().to_rfc3339(), account_id],
+        )?;
+
+        if updated == 0 {
+            return Err(anyhow!("Account not found: {}", account_id));
+        }
+
+        Ok(())
+    }
+
     /// Delete an account and all associated data (transactions, balance snapshots)
     ///
     /// This performs a cascade delete:
@@ -383,7 +1045,7 @@ impl DuckDbRepository {
     /// 2. Delete order respecting FK constraints (children before parent)
     /// 3. Each statement auto-committing on success
     pub fn delete_account(&self, account_id: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
 
         // Delete in order to respect foreign key constraints:
         // transactions and snapshots reference accounts, so delete them first
@@ -409,10 +1071,113 @@ impl DuckDbRepository {
         Ok(())
     }
 
+    /// Merge a duplicate account into another
+    ///
+    /// Moves all transactions (including soft-deleted ones) and balance
+    /// snapshots from `source_id` to `target_id`, then deletes the source.
+    /// When both accounts hold a transaction with the same provider ID only
+    /// the most recently updated copy is kept, so the target never ends up
+    /// with duplicate provider IDs. Provider account IDs the target lacks
+    /// (SimpleFIN, Lunchflow, Plaid) are taken from the source, so the next
+    /// sync matches the kept account instead of re-creating the duplicate.
+    ///
+    /// Everything but deleting the emptied source runs in one transaction.
+    /// That delete has to come after the commit (see `delete_account`); if it
+    /// fails, merging again finishes the job.
+    pub fn merge_accounts(
+        &self,
+        source_id: &str,
+        target_id: &str,
+    ) -> Result<crate::services::MergeReport> {
+        if source_id == target_id {
+            anyhow::bail!("Cannot merge an account into itself");
+        }
+
+        let (transactions_moved, snapshots_moved, duplicates_dropped) =
+            self.with_transaction(|| {
+                let conn = self.write_conn();
+                for id in [source_id, target_id] {
+                    let exists: i64 = conn.query_row(
+                        "SELECT COUNT(*) FROM sys_accounts WHERE account_id = ?",
+                        params![id],
+                        |row| row.get(0),
+                    )?;
+                    if exists == 0 {
+                        anyhow::bail!("Account not found: {}", id);
+                    }
+                }
+
+                // Resolve provider ID collisions first: drop whichever copy is older
+                let mut duplicates_dropped = 0;
+                for column in PROVIDER_ID_COLUMNS {
+                    duplicates_dropped += conn.execute(
+                        &format!(
+                            "DELETE FROM sys_transactions AS t
+                             WHERE t.account_id = ? AND t.{col} IS NOT NULL
+                               AND EXISTS (
+                                   SELECT 1 FROM sys_transactions AS s
+                                   WHERE s.account_id = ? AND s.{col} = t.{col}
+                                     AND s.updated_at > t.updated_at
+                               )",
+                            col = column
+                        ),
+                        params![target_id, source_id],
+                    )?;
+                    duplicates_dropped += conn.execute(
+                        &format!(
+                            "DELETE FROM sys_transactions AS s
+                             WHERE s.account_id = ? AND s.{col} IS NOT NULL
+                               AND EXISTS (
+                                   SELECT 1 FROM sys_transactions AS t
+                                   WHERE t.account_id = ? AND t.{col} = s.{col}
+                               )",
+                            col = column
+                        ),
+                        params![source_id, target_id],
+                    )?;
+                }
+
+                let transactions_moved = conn.execute(
+                    "UPDATE sys_transactions SET account_id = ?, updated_at = CURRENT_TIMESTAMP
+                     WHERE account_id = ?",
+                    params![target_id, source_id],
+                )?;
+                let snapshots_moved = conn.execute(
+                    "UPDATE sys_balance_snapshots SET account_id = ? WHERE account_id = ?",
+                    params![target_id, source_id],
+                )?;
+                conn.execute(
+                    "UPDATE sys_accounts AS t SET
+                        sf_id = COALESCE(t.sf_id, s.sf_id),
+                        lf_id = COALESCE(t.lf_id, s.lf_id),
+                        pl_id = COALESCE(t.pl_id, s.pl_id),
+                        pl_item_id = COALESCE(t.pl_item_id, s.pl_item_id),
+                        updated_at = CURRENT_TIMESTAMP
+                     FROM sys_accounts AS s
+                     WHERE t.account_id = ? AND s.account_id = ?",
+                    params![target_id, source_id],
+                )?;
+                Ok((transactions_moved, snapshots_moved, duplicates_dropped))
+            })?;
+
+        self.write_conn().execute(
+            "DELETE FROM sys_accounts WHERE account_id = ?",
+            params![source_id],
+        )?;
+
+        Ok(crate::services::MergeReport {
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            transactions_moved,
+            snapshots_moved,
+            duplicates_dropped,
+        })
+    }
+
     // === Transaction operations ===
 
     pub fn get_transactions(&self) -> Result<Vec<Transaction>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         // Note: CAST(tags AS VARCHAR) is required because duckdb-rs cannot read VARCHAR[]
         // directly as String. Without the CAST, row.get() silently fails and returns "[]".
         // See parse_duckdb_array() for the parsing logic.
@@ -421,7 +1186,10 @@ impl DuckDbRepository {
                     posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
                     created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
                     sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending
+                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending,
+                    pl_id, pl_account_id, pl_amount, pl_currency, pl_date::VARCHAR, pl_authorized_date::VARCHAR,
+                    pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                    original_amount, original_currency
              FROM sys_transactions
              WHERE deleted_at IS NULL"
         )?;
@@ -436,14 +1204,17 @@ impl DuckDbRepository {
 
     /// Get transactions for a specific account, ordered by transaction_date DESC
     pub fn get_transactions_by_account(&self, account_id: &str) -> Result<Vec<Transaction>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
                     posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
                     created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
                     sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending
+                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending,
+                    pl_id, pl_account_id, pl_amount, pl_currency, pl_date::VARCHAR, pl_authorized_date::VARCHAR,
+                    pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                    original_amount, original_currency
              FROM sys_transactions
              WHERE account_id = ? AND deleted_at IS NULL
              ORDER BY transaction_date DESC"
@@ -457,8 +1228,76 @@ impl DuckDbRepository {
         Ok(transactions)
     }
 
+    /// Transactions matching every set field of `filter`, newest first
+    ///
+    /// All values are bound as parameters, so filter contents never become SQL.
+    pub fn query_transactions(&self, filter: TransactionFilter) -> Result<Vec<Transaction>> {
+        let mut conditions = vec!["deleted_at IS NULL".to_string()];
+        let mut values: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
+
+        if let Some(account_id) = filter.account_id {
+            conditions.push("account_id = ?".to_string());
+            values.push(Box::new(account_id.to_string()));
+        }
+        if let Some(min_amount) = filter.min_amount {
+            conditions.push("amount >= ?::DECIMAL(15,2)".to_string());
+            values.push(Box::new(min_amount.to_string()));
+        }
+        if let Some(max_amount) = filter.max_amount {
+            conditions.push("amount <= ?::DECIMAL(15,2)".to_string());
+            values.push(Box::new(max_amount.to_string()));
+        }
+        if let Some(start_date) = filter.start_date {
+            conditions.push("transaction_date >= ?::DATE".to_string());
+            values.push(Box::new(start_date.to_string()));
+        }
+        if let Some(end_date) = filter.end_date {
+            conditions.push("transaction_date <= ?::DATE".to_string());
+            values.push(Box::new(end_date.to_string()));
+        }
+        if let Some(tag) = filter.tag {
+            conditions.push("list_contains(tags, ?)".to_string());
+            values.push(Box::new(tag));
+        }
+        if let Some(search) = filter.search {
+            // contains() rather than LIKE so % and _ in the term match literally
+            conditions.push(
+                "(contains(lower(COALESCE(description, '')), lower(?))
+                  OR contains(lower(COALESCE(notes, '')), lower(?)))"
+                    .to_string(),
+            );
+            values.push(Box::new(search.clone()));
+            values.push(Box::new(search));
+        }
+
+        let conn = self.read_conn()?;
+        // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
+        let mut stmt = conn.prepare(&format!(
+            "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
+                    posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
+                    created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
+                    sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
+                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending,
+                    pl_id, pl_account_id, pl_amount, pl_currency, pl_date::VARCHAR, pl_authorized_date::VARCHAR,
+                    pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                    original_amount, original_currency
+             FROM sys_transactions
+             WHERE {}
+             ORDER BY transaction_date DESC, created_at DESC",
+            conditions.join(" AND ")
+        ))?;
+
+        let param_refs: Vec<&dyn duckdb::ToSql> = values.iter().map(|b| b.as_ref()).collect();
+        let transactions = stmt
+            .query_map(param_refs.as_slice(), |row| self.row_to_transaction(row))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(transactions)
+    }
+
     pub fn get_transaction_count(&self) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM sys_transactions WHERE deleted_at IS NULL",
             [],
@@ -469,7 +1308,7 @@ impl DuckDbRepository {
 
     /// Get the maximum transaction date from the database
     pub fn get_max_transaction_date(&self) -> Result<Option<NaiveDate>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let result: Option<String> = conn.query_row(
             "SELECT MAX(transaction_date)::VARCHAR FROM sys_transactions WHERE deleted_at IS NULL",
             [],
@@ -478,8 +1317,35 @@ impl DuckDbRepository {
         Ok(result.map(|s| parse_date(&s)))
     }
 
+    /// Latest transaction date and transaction count per account, keyed by account ID
+    ///
+    /// Accounts without transactions are not returned.
+    pub fn get_account_activity(&self) -> Result<HashMap<String, (NaiveDate, i64)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT account_id, MAX(transaction_date)::VARCHAR, COUNT(*)
+             FROM sys_transactions
+             WHERE deleted_at IS NULL
+             GROUP BY account_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut result = HashMap::new();
+        for row in rows {
+            let (account_id, last_date, count) = row?;
+            result.insert(account_id, (parse_date(&last_date), count));
+        }
+        Ok(result)
+    }
+
     pub fn get_balance_snapshot_count(&self) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let count: i64 =
             conn.query_row("SELECT COUNT(*) FROM sys_balance_snapshots", [], |row| {
                 row.get(0)
@@ -488,13 +1354,24 @@ impl DuckDbRepository {
     }
 
     pub fn get_transaction_date_range(&self) -> Result<crate::services::DateRange> {
-        let conn = self.conn.lock().unwrap();
+        self.get_transaction_date_range_by(DateBasis::Transaction)
+    }
+
+    /// Earliest and latest transaction dates under the given date basis
+    pub fn get_transaction_date_range_by(
+        &self,
+        basis: DateBasis,
+    ) -> Result<crate::services::DateRange> {
+        let conn = self.read_conn()?;
         let result: (Option<String>, Option<String>) = conn.query_row(
-            "SELECT
-                MIN(transaction_date)::VARCHAR,
-                MAX(transaction_date)::VARCHAR
-             FROM sys_transactions
-             WHERE deleted_at IS NULL",
+            &format!(
+                "SELECT
+                    MIN({col})::VARCHAR,
+                    MAX({col})::VARCHAR
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL",
+                col = basis.sql_column()
+            ),
             [],
             |row| Ok((row.get(0)?, row.get(1)?)),
         )?;
@@ -504,6 +1381,227 @@ impl DuckDbRepository {
         })
     }
 
+    /// Income and expense totals per day for dates in [start, end)
+    ///
+    /// Transactions tagged `transfer` are left out so moving money between
+    /// your own accounts doesn't count as both income and spending.
+    /// Days without transactions are not returned; callers group the rest
+    /// into (fiscal) months.
+    pub fn get_daily_cash_flow(
+        &self,
+        basis: DateBasis,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Decimal, Decimal)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT CAST({col} AS VARCHAR) AS day,
+                    COALESCE(SUM(amount) FILTER (WHERE amount > 0), 0)::DOUBLE AS income,
+                    COALESCE(SUM(-amount) FILTER (WHERE amount < 0), 0)::DOUBLE AS expenses
+             FROM sys_transactions
+             WHERE deleted_at IS NULL
+               AND {col} >= ?::DATE AND {col} < ?::DATE
+               AND NOT COALESCE(list_contains(tags, 'transfer'), false)
+             GROUP BY day
+             ORDER BY day",
+            col = basis.sql_column()
+        ))?;
+        let rows = stmt.query_map(params![start.to_string(), end.to_string()], |row| {
+            let day: String = row.get(0)?;
+            let income: f64 = row.get(1)?;
+            let expenses: f64 = row.get(2)?;
+            Ok((
+                parse_date(&day),
+                Decimal::try_from(income)
+                    .unwrap_or(Decimal::ZERO)
+                    .round_dp(2),
+                Decimal::try_from(expenses)
+                    .unwrap_or(Decimal::ZERO)
+                    .round_dp(2),
+            ))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Spending per calendar month on transactions tagged `tag`, for dates in [start, end)
+    ///
+    /// Spending is the sum of expenses (negative amounts) as a positive
+    /// value, leaving out transfers. Returns (first day of month, spend) for
+    /// months with spending.
+    pub fn get_tag_monthly_spend(
+        &self,
+        tag: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Decimal)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT strftime(transaction_date, '%Y-%m-01') AS month,
+                    SUM(-amount)::DOUBLE AS spend
+             FROM (
+                 SELECT transaction_date, amount, unnest(tags) AS tag
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+                   AND amount < 0
+                   AND NOT COALESCE(list_contains(tags, 'transfer'), false)
+                   AND transaction_date >= ?::DATE AND transaction_date < ?::DATE
+             )
+             WHERE tag = ?
+             GROUP BY month
+             ORDER BY month",
+        )?;
+        let rows = stmt.query_map(params![start.to_string(), end.to_string(), tag], |row| {
+            let month: String = row.get(0)?;
+            let spend: f64 = row.get(1)?;
+            Ok((
+                parse_date(&month),
+                Decimal::try_from(spend)
+                    .unwrap_or(Decimal::ZERO)
+                    .round_dp(2),
+            ))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Spending per tag between `start` and `end` (inclusive), largest first
+    ///
+    /// Expenses are negative amounts, summed as positive values. A
+    /// transaction counts once toward each of its tags; untagged ones count
+    /// toward `untagged_label`. Transfers are left out.
+    pub fn get_spending_by_tag(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        untagged_label: &str,
+    ) -> Result<Vec<(String, Decimal)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT tag, SUM(-amount)::DOUBLE AS spend
+             FROM (
+                 SELECT amount,
+                        unnest(CASE WHEN COALESCE(len(tags), 0) = 0 THEN [?::VARCHAR] ELSE tags END) AS tag
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+                   AND amount < 0
+                   AND NOT COALESCE(list_contains(tags, 'transfer'), false)
+                   AND transaction_date >= ?::DATE AND transaction_date <= ?::DATE
+             )
+             GROUP BY tag
+             ORDER BY spend DESC, tag",
+        )?;
+        let rows = stmt.query_map(
+            params![untagged_label, start.to_string(), end.to_string()],
+            |row| {
+                let spend: f64 = row.get(1)?;
+                Ok((
+                    row.get::<_, String>(0)?,
+                    Decimal::try_from(spend)
+                        .unwrap_or(Decimal::ZERO)
+                        .round_dp(2),
+                ))
+            },
+        )?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Total amount and count per raw description, transfers excluded
+    pub fn get_description_totals(&self) -> Result<Vec<(String, Decimal, i64)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT description, SUM(amount)::DOUBLE, COUNT(*)
+             FROM sys_transactions
+             WHERE deleted_at IS NULL
+               AND description IS NOT NULL
+               AND NOT COALESCE(list_contains(tags, 'transfer'), false)
+             GROUP BY description",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let total: f64 = row.get(1)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                Decimal::try_from(total)
+                    .unwrap_or(Decimal::ZERO)
+                    .round_dp(2),
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Count expenses per equal-width bucket of their size
+    ///
+    /// Expenses are negative amounts, measured as positive values; transfers
+    /// are left out. Returns (bucket, count, min, max) per non-empty bucket,
+    /// with buckets numbered from 0 and min/max taken over all expenses.
+    /// When every expense is the same size they all land in bucket 0.
+    pub fn get_expense_histogram(
+        &self,
+        account_id: Option<&str>,
+        buckets: usize,
+    ) -> Result<Vec<(usize, i64, f64, f64)>> {
+        let conn = self.read_conn()?;
+        // DuckDB has no width_bucket(); this is the same calculation, with
+        // the maximum folded into the last bucket
+        let mut stmt = conn.prepare(
+            "WITH expenses AS (
+                 SELECT (-amount)::DOUBLE AS value
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+                   AND amount < 0
+                   AND NOT COALESCE(list_contains(tags, 'transfer'), false)
+                   AND (CAST(? AS VARCHAR) IS NULL OR account_id = ?)
+             ),
+             bounds AS (
+                 SELECT MIN(value) AS lo, MAX(value) AS hi FROM expenses
+             )
+             SELECT CASE
+                        WHEN hi = lo THEN 0
+                        ELSE LEAST(FLOOR((value - lo) / ((hi - lo) / ?))::BIGINT, ? - 1)
+                    END AS bucket,
+                    COUNT(*) AS count,
+                    lo,
+                    hi
+             FROM expenses, bounds
+             GROUP BY bucket, lo, hi
+             ORDER BY bucket",
+        )?;
+        let buckets = buckets as i64;
+        let rows = stmt.query_map(params![account_id, account_id, buckets, buckets], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as usize,
+                row.get::<_, i64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
     fn row_to_transaction(
         &self,
         row: &duckdb::Row,
@@ -513,7 +1611,10 @@ impl DuckDbRepository {
         // 5: posted_date, 6: tags, 7: external_ids, 8: deleted_at, 9: parent_transaction_id,
         // 10: created_at, 11: updated_at, 12: csv_fingerprint, 13: csv_batch_id, 14: is_manual, 15: tags_auto_applied,
         // 16: sf_id, 17: sf_posted, 18: sf_amount, 19: sf_description, 20: sf_transacted_at, 21: sf_pending, 22: sf_extra,
-        // 23: lf_id, 24: lf_account_id, 25: lf_amount, 26: lf_currency, 27: lf_date, 28: lf_merchant, 29: lf_description, 30: lf_is_pending
+        // 23: lf_id, 24: lf_account_id, 25: lf_amount, 26: lf_currency, 27: lf_date, 28: lf_merchant, 29: lf_description, 30: lf_is_pending,
+        // 31: pl_id, 32: pl_account_id, 33: pl_amount, 34: pl_currency, 35: pl_date, 36: pl_authorized_date,
+        // 37: pl_name, 38: pl_merchant_name, 39: pl_pending, 40: pl_category, 41: notes,
+        // 42: original_amount, 43: original_currency
         let id_str: String = row.get(0)?;
         let account_id_str: String = row.get(1)?;
         let amount: f64 = row.get(2).unwrap_or(0.0);
@@ -531,6 +1632,8 @@ impl DuckDbRepository {
         let updated_str: String = row.get(11).unwrap_or_default();
         let sf_extra_json: Option<String> = row.get(22).ok();
         let lf_date_str: Option<String> = row.get(27).ok();
+        let pl_date_str: Option<String> = row.get(35).ok();
+        let pl_authorized_date_str: Option<String> = row.get(36).ok();
 
         // Parse UUIDs - if these fail, skip the row rather than creating new UUIDs
         let id = Uuid::parse_str(&id_str).map_err(|e| {
@@ -550,6 +1653,7 @@ impl DuckDbRepository {
             account_id,
             amount,
             description: row.get(3).ok(),
+            notes: row.get(41).ok(),
             transaction_date: parse_date(&tx_date_str),
             posted_date: parse_date(&posted_date_str),
             tags,
@@ -557,6 +1661,13 @@ impl DuckDbRepository {
             parent_transaction_id: parent_id_str.and_then(|s| Uuid::parse_str(&s).ok()),
             created_at: parse_timestamp(&created_str),
             updated_at: parse_timestamp(&updated_str),
+            // Foreign currency (columns 42-43)
+            original_amount: row
+                .get::<_, Option<f64>>(42)
+                .ok()
+                .flatten()
+                .map(|f| Decimal::try_from(f).unwrap_or_default()),
+            original_currency: row.get(43).ok(),
             // CSV Import tracking (columns 12-13)
             csv_fingerprint: row.get(12).ok(),
             csv_batch_id: row.get(13).ok(),
@@ -593,11 +1704,29 @@ impl DuckDbRepository {
             lf_merchant: row.get(28).ok(),
             lf_description: row.get(29).ok(),
             lf_is_pending: row.get(30).ok(),
+            // Plaid fields (columns 31-40)
+            pl_id: row.get(31).ok(),
+            pl_account_id: row.get(32).ok(),
+            pl_amount: row
+                .get::<_, Option<f64>>(33)
+                .ok()
+                .flatten()
+                .map(|f| Decimal::try_from(f).unwrap_or_default()),
+            pl_currency: row.get(34).ok(),
+            pl_date: pl_date_str.map(|s| parse_date(&s)),
+            pl_authorized_date: pl_authorized_date_str.map(|s| parse_date(&s)),
+            pl_name: row.get(37).ok(),
+            pl_merchant_name: row.get(38).ok(),
+            pl_pending: row.get(39).ok(),
+            pl_category: row.get(40).ok(),
         })
     }
 
     pub fn upsert_transaction(&self, tx: &Transaction) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        tx.validate()
+            .with_context(|| format!("Refusing to store invalid transaction {}", tx.id))?;
+
+        let conn = self.write_conn();
         // Write empty JSON for external_ids - kept for backwards compat with DB schema
         let external_ids = "{}";
         let sf_extra = tx.sf_extra.as_ref().map(|v| v.to_string());
@@ -612,8 +1741,12 @@ impl DuckDbRepository {
                                            parent_transaction_id, created_at, updated_at,
                                            csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
                                            sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                                           lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending)
-             VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                           lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending,
+                                           pl_id, pl_account_id, pl_amount, pl_currency, pl_date, pl_authorized_date,
+                                           pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                                           original_amount, original_currency)
+             VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                     ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
              ON CONFLICT (transaction_id) DO UPDATE SET
                 account_id = EXCLUDED.account_id,
                 amount = EXCLUDED.amount,
@@ -627,6 +1760,7 @@ impl DuckDbRepository {
                 csv_fingerprint = COALESCE(EXCLUDED.csv_fingerprint, sys_transactions.csv_fingerprint),
                 csv_batch_id = COALESCE(EXCLUDED.csv_batch_id, sys_transactions.csv_batch_id),
                 is_manual = COALESCE(sys_transactions.is_manual, EXCLUDED.is_manual),
+                notes = COALESCE(sys_transactions.notes, EXCLUDED.notes),
                 tags_auto_applied = COALESCE(sys_transactions.tags_auto_applied, EXCLUDED.tags_auto_applied),
                 sf_id = COALESCE(EXCLUDED.sf_id, sys_transactions.sf_id),
                 sf_posted = COALESCE(EXCLUDED.sf_posted, sys_transactions.sf_posted),
@@ -642,7 +1776,19 @@ impl DuckDbRepository {
                 lf_date = COALESCE(EXCLUDED.lf_date, sys_transactions.lf_date),
                 lf_merchant = COALESCE(EXCLUDED.lf_merchant, sys_transactions.lf_merchant),
                 lf_description = COALESCE(EXCLUDED.lf_description, sys_transactions.lf_description),
-                lf_is_pending = COALESCE(EXCLUDED.lf_is_pending, sys_transactions.lf_is_pending)",
+                lf_is_pending = COALESCE(EXCLUDED.lf_is_pending, sys_transactions.lf_is_pending),
+                pl_id = COALESCE(EXCLUDED.pl_id, sys_transactions.pl_id),
+                pl_account_id = COALESCE(EXCLUDED.pl_account_id, sys_transactions.pl_account_id),
+                pl_amount = COALESCE(EXCLUDED.pl_amount, sys_transactions.pl_amount),
+                pl_currency = COALESCE(EXCLUDED.pl_currency, sys_transactions.pl_currency),
+                pl_date = COALESCE(EXCLUDED.pl_date, sys_transactions.pl_date),
+                pl_authorized_date = COALESCE(EXCLUDED.pl_authorized_date, sys_transactions.pl_authorized_date),
+                pl_name = COALESCE(EXCLUDED.pl_name, sys_transactions.pl_name),
+                pl_merchant_name = COALESCE(EXCLUDED.pl_merchant_name, sys_transactions.pl_merchant_name),
+                pl_pending = COALESCE(EXCLUDED.pl_pending, sys_transactions.pl_pending),
+                pl_category = COALESCE(EXCLUDED.pl_category, sys_transactions.pl_category),
+                original_amount = COALESCE(EXCLUDED.original_amount, sys_transactions.original_amount),
+                original_currency = COALESCE(EXCLUDED.original_currency, sys_transactions.original_currency)",
             tags_literal
         );
 
@@ -679,39 +1825,271 @@ impl DuckDbRepository {
                 tx.lf_merchant,
                 tx.lf_description,
                 tx.lf_is_pending,
+                tx.pl_id,
+                tx.pl_account_id,
+                tx.pl_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                tx.pl_currency,
+                tx.pl_date.map(|d| d.to_string()),
+                tx.pl_authorized_date.map(|d| d.to_string()),
+                tx.pl_name,
+                tx.pl_merchant_name,
+                tx.pl_pending,
+                tx.pl_category,
+                tx.notes,
+                tx.original_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                tx.original_currency,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Set or clear the user's notes on a transaction
+    ///
+    /// Notes are never touched by sync, so this is the only way to change them.
+    pub fn set_transaction_notes(
+        &self,
+        tx_id: &str,
+        notes: Option<String>,
+    ) -> RepositoryResult<()> {
+        let conn = self.write_conn();
+        let before = audit_snapshot(&conn, tx_id, &["notes"]);
+        let updated = conn.execute(
+            "UPDATE sys_transactions SET notes = ?, updated_at = ? WHERE transaction_id = ?",
+            params![notes, Utc::now().to_rfc3339(), tx_id],
+        )?;
+
+        if updated == 0 {
+            return Err(RepositoryError::not_found("Transaction", tx_id));
+        }
+        self.record_audit(&conn, tx_id, &["notes"], before);
+
+        Ok(())
+    }
+
+    /// Hide a transaction everywhere without removing it
+    ///
+    /// Returns an error if there is no live transaction with this ID.
+    pub fn soft_delete_transaction(&self, tx_id: &str) -> RepositoryResult<()> {
+        self.set_transaction_deleted(tx_id, true)
+    }
+
+    /// Bring back a transaction hidden by `soft_delete_transaction`
+    ///
+    /// Returns an error if there is no deleted transaction with this ID.
+    pub fn restore_transaction(&self, tx_id: &str) -> RepositoryResult<()> {
+        self.set_transaction_deleted(tx_id, false)
+    }
+
+    fn set_transaction_deleted(&self, tx_id: &str, deleted: bool) -> RepositoryResult<()> {
+        let conn = self.write_conn();
+        let before = audit_snapshot(&conn, tx_id, &["deleted_at"]);
+        let now = Utc::now().to_rfc3339();
+        let updated = if deleted {
+            conn.execute(
+                "UPDATE sys_transactions SET deleted_at = ?, updated_at = ?
+                 WHERE transaction_id = ? AND deleted_at IS NULL",
+                params![now, now, tx_id],
+            )?
+        } else {
+            conn.execute(
+                "UPDATE sys_transactions SET deleted_at = NULL, updated_at = ?
+                 WHERE transaction_id = ? AND deleted_at IS NOT NULL",
+                params![now, tx_id],
+            )?
+        };
+
+        if updated == 0 {
+            let kind = if deleted {
+                "Transaction"
+            } else {
+                "Deleted transaction"
+            };
+            return Err(RepositoryError::not_found(kind, tx_id));
+        }
+        self.record_audit(&conn, tx_id, &["deleted_at"], before);
+
+        Ok(())
+    }
+
+    /// Overwrite the given fields of a transaction, leaving the rest as they are
+    ///
+    /// Returns false when no live transaction has this ID.
+    pub fn patch_transaction(
+        &self,
+        tx_id: &str,
+        description: Option<&str>,
+        transaction_date: Option<NaiveDate>,
+        amount: Option<Decimal>,
+    ) -> Result<bool> {
+        let mut columns = Vec::new();
+        if description.is_some() {
+            columns.push("description");
+        }
+        if transaction_date.is_some() {
+            columns.push("transaction_date");
+        }
+        if amount.is_some() {
+            columns.push("amount");
+        }
+
+        let conn = self.write_conn();
+        let before = audit_snapshot(&conn, tx_id, &columns);
+        let updated = conn.execute(
+            "UPDATE sys_transactions SET
+                description = COALESCE(?, description),
+                transaction_date = COALESCE(?, transaction_date),
+                amount = COALESCE(?, amount),
+                updated_at = ?
+             WHERE transaction_id = ? AND deleted_at IS NULL",
+            params![
+                description,
+                transaction_date.map(|d| d.to_string()),
+                amount.map(|a| a.to_string().parse::<f64>().unwrap_or(0.0)),
+                Utc::now().to_rfc3339(),
+                tx_id
             ],
         )?;
 
+        if updated > 0 {
+            self.record_audit(&conn, tx_id, &columns, before);
+        }
+        Ok(updated > 0)
+    }
+
+    /// Append a row to the audit trail for each of `columns` that changed
+    ///
+    /// `before` comes from `audit_snapshot` taken ahead of the change. This is
+    /// best-effort: a failed audit write is ignored so it never fails the change.
+    fn record_audit(
+        &self,
+        conn: &Connection,
+        tx_id: &str,
+        columns: &[&str],
+        before: Vec<Option<String>>,
+    ) {
+        let after = audit_snapshot(conn, tx_id, columns);
+        let now = Utc::now().to_rfc3339();
+        let entry_point = self.entry_point.get().map(|e| e.as_str());
+        for ((column, old), new) in columns.iter().zip(before).zip(after) {
+            if old == new {
+                continue;
+            }
+            let _ = conn.execute(
+                "INSERT INTO sys_transaction_audit
+                    (audit_id, transaction_id, field, old_value, new_value, changed_at, entry_point)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    Uuid::new_v4().to_string(),
+                    tx_id,
+                    column,
+                    old,
+                    new,
+                    now,
+                    entry_point
+                ],
+            );
+        }
+    }
+
+    /// Changes recorded for a transaction, newest first
+    pub fn get_transaction_history(&self, tx_id: &str) -> Result<Vec<AuditEntry>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT field, old_value, new_value, changed_at::VARCHAR, entry_point
+             FROM sys_transaction_audit
+             WHERE transaction_id = ?
+             ORDER BY changed_at DESC, field",
+        )?;
+        let rows = stmt.query_map(params![tx_id], |row| {
+            let changed_at: String = row.get(3)?;
+            Ok(AuditEntry {
+                transaction_id: tx_id.to_string(),
+                field: row.get(0)?,
+                old_value: row.get(1)?,
+                new_value: row.get(2)?,
+                changed_at: parse_naive_datetime(&changed_at),
+                entry_point: row.get(4)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Link transactions as the sides of one transfer
+    pub fn set_transfer_group(&self, tx_ids: &[&str], group_id: &str) -> Result<()> {
+        let conn = self.write_conn();
+        for tx_id in tx_ids {
+            conn.execute(
+                "UPDATE sys_transactions SET transfer_group_id = ?, updated_at = CURRENT_TIMESTAMP
+                 WHERE transaction_id = ?",
+                params![group_id, tx_id],
+            )?;
+        }
         Ok(())
     }
 
+    /// Transfer group of each transaction that belongs to one, keyed by transaction ID
+    pub fn get_transfer_groups(&self) -> Result<HashMap<String, String>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT transaction_id, transfer_group_id FROM sys_transactions
+             WHERE transfer_group_id IS NOT NULL AND deleted_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut result = HashMap::new();
+        for row in rows {
+            let (tx_id, group_id) = row?;
+            result.insert(tx_id, group_id);
+        }
+        Ok(result)
+    }
+
     pub fn update_transaction_tags(&self, tx_id: &str, tags: &[String]) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
+        let before = audit_snapshot(&conn, tx_id, &["tags"]);
         let tags_literal = format_tags_array(tags);
         let sql = format!(
             "UPDATE sys_transactions SET tags = {}, updated_at = CURRENT_TIMESTAMP WHERE transaction_id = ?",
             tags_literal
         );
-        conn.execute(&sql, params![tx_id])?;
+        if conn.execute(&sql, params![tx_id])? > 0 {
+            self.record_audit(&conn, tx_id, &["tags"], before);
+        }
         Ok(())
     }
 
     /// Update transaction tags and mark them as auto-applied (by rules)
     pub fn update_transaction_tags_auto(&self, tx_id: &str, tags: &[String]) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
+        let before = audit_snapshot(&conn, tx_id, &["tags"]);
         let tags_literal = format_tags_array(tags);
         let sql = format!(
             "UPDATE sys_transactions SET tags = {}, tags_auto_applied = TRUE, updated_at = CURRENT_TIMESTAMP WHERE transaction_id = ?",
             tags_literal
         );
-        conn.execute(&sql, params![tx_id])?;
+        if conn.execute(&sql, params![tx_id])? > 0 {
+            self.record_audit(&conn, tx_id, &["tags"], before);
+        }
         Ok(())
     }
 
     /// Insert a transaction only if it doesn't already exist (skip existing to preserve user edits)
     /// Returns true if inserted, false if skipped
     pub fn insert_transaction_if_not_exists(&self, tx: &Transaction) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
+        tx.validate()
+            .with_context(|| format!("Refusing to store invalid transaction {}", tx.id))?;
+
+        let conn = self.write_conn();
         // Write empty JSON for external_ids - kept for backwards compat with DB schema
         let external_ids = "{}";
         let sf_extra = tx.sf_extra.as_ref().map(|v| v.to_string());
@@ -724,8 +2102,12 @@ impl DuckDbRepository {
                                            parent_transaction_id, created_at, updated_at,
                                            csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
                                            sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                                           lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending)
-             VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                           lf_id, lf_account_id, lf_amount, lf_currency, lf_date, lf_merchant, lf_description, lf_is_pending,
+                                           pl_id, pl_account_id, pl_amount, pl_currency, pl_date, pl_authorized_date,
+                                           pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                                           original_amount, original_currency)
+             VALUES (?, ?, ?, ?, ?, ?, {}, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                     ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
              ON CONFLICT (transaction_id) DO NOTHING",
             tags_literal
         );
@@ -758,48 +2140,274 @@ impl DuckDbRepository {
                 tx.lf_account_id,
                 tx.lf_amount
                     .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
-                tx.lf_currency,
+                tx.lf_currency,
+                tx.lf_date.map(|d| d.to_string()),
+                tx.lf_merchant,
+                tx.lf_description,
+                tx.lf_is_pending,
+                tx.pl_id,
+                tx.pl_account_id,
+                tx.pl_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                tx.pl_currency,
+                tx.pl_date.map(|d| d.to_string()),
+                tx.pl_authorized_date.map(|d| d.to_string()),
+                tx.pl_name,
+                tx.pl_merchant_name,
+                tx.pl_pending,
+                tx.pl_category,
+                tx.notes,
+                tx.original_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                tx.original_currency,
+            ],
+        )?;
+
+        Ok(rows_changed > 0)
+    }
+
+    /// Check if a transaction exists by ID
+    pub fn transaction_exists(&self, tx_id: &str) -> Result<bool> {
+        let conn = self.read_conn()?;
+        let count: i64 = conn
+            .prepare_cached("SELECT COUNT(*) FROM sys_transactions WHERE transaction_id = ?")?
+            .query_row(params![tx_id], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// Check if a transaction exists by SimpleFIN ID (indexed, fast)
+    pub fn transaction_exists_by_sf_id(&self, sf_id: &str) -> Result<bool> {
+        self.transaction_exists_by_external_id("sf_id", sf_id)
+    }
+
+    /// Check if a transaction exists by Lunchflow ID (indexed, fast)
+    pub fn transaction_exists_by_lf_id(&self, lf_id: &str) -> Result<bool> {
+        self.transaction_exists_by_external_id("lf_id", lf_id)
+    }
+
+    /// Check if a transaction exists by Plaid ID (indexed, fast)
+    pub fn transaction_exists_by_pl_id(&self, pl_id: &str) -> Result<bool> {
+        self.transaction_exists_by_external_id("pl_id", pl_id)
+    }
+
+    /// Check if a transaction exists by a provider ID column (sf_id, lf_id or pl_id)
+    pub fn transaction_exists_by_external_id(&self, column: &str, id: &str) -> Result<bool> {
+        if !PROVIDER_ID_COLUMNS.contains(&column) {
+            return Err(anyhow!("Not a provider ID column: {}", column));
+        }
+        let conn = self.read_conn()?;
+        // Sync checks every fetched transaction, so reuse the prepared statement
+        let count: i64 = conn
+            .prepare_cached(&format!(
+                "SELECT COUNT(*) FROM sys_transactions WHERE {} = ?",
+                column
+            ))?
+            .query_row(params![id], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// The SimpleFIN IDs in `ids` that are already stored
+    pub fn existing_sf_ids(&self, ids: &[String]) -> Result<HashSet<String>> {
+        self.existing_external_ids("sf_id", ids)
+    }
+
+    /// The Lunchflow IDs in `ids` that are already stored
+    pub fn existing_lf_ids(&self, ids: &[String]) -> Result<HashSet<String>> {
+        self.existing_external_ids("lf_id", ids)
+    }
+
+    /// The Plaid IDs in `ids` that are already stored
+    pub fn existing_pl_ids(&self, ids: &[String]) -> Result<HashSet<String>> {
+        self.existing_external_ids("pl_id", ids)
+    }
+
+    /// The subset of `ids` already stored in a provider ID column (sf_id, lf_id or pl_id)
+    ///
+    /// Looks the IDs up with one `IN` query per chunk of
+    /// `EXISTING_IDS_CHUNK_SIZE`, instead of one query per ID.
+    pub fn existing_external_ids(&self, column: &str, ids: &[String]) -> Result<HashSet<String>> {
+        if !PROVIDER_ID_COLUMNS.contains(&column) {
+            return Err(anyhow!("Not a provider ID column: {}", column));
+        }
+        let mut existing = HashSet::new();
+        if ids.is_empty() {
+            return Ok(existing);
+        }
+
+        let conn = self.read_conn()?;
+        for chunk in ids.chunks(EXISTING_IDS_CHUNK_SIZE) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT DISTINCT {} FROM sys_transactions WHERE {} IN ({})",
+                column, column, placeholders
+            ))?;
+            let param_refs: Vec<&dyn duckdb::ToSql> =
+                chunk.iter().map(|id| id as &dyn duckdb::ToSql).collect();
+            let rows = stmt.query_map(param_refs.as_slice(), |row| row.get::<_, String>(0))?;
+            for id in rows {
+                existing.insert(id?);
+            }
+        }
+        Ok(existing)
+    }
+
+    /// Check if the transaction with this provider ID is still pending
+    pub fn transaction_is_pending_by_external_id(&self, column: &str, id: &str) -> Result<bool> {
+        if !PROVIDER_ID_COLUMNS.contains(&column) {
+            return Err(anyhow!("Not a provider ID column: {}", column));
+        }
+        let conn = self.read_conn()?;
+        let count: i64 = conn
+            .prepare_cached(&format!(
+                "SELECT COUNT(*) FROM sys_transactions
+                 WHERE {} = ? AND COALESCE(sf_pending, lf_is_pending, pl_pending, false)",
+                column
+            ))?
+            .query_row(params![id], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// Refresh a pending transaction from the provider's latest copy
+    ///
+    /// Updates amount, dates, description and the provider's own columns of
+    /// the stored transaction with this provider ID, but only while it is
+    /// still pending. Posted transactions are left alone to preserve user
+    /// edits; tags and notes are never touched. Returns true if a row changed.
+    pub fn update_pending_transaction(
+        &self,
+        column: &str,
+        id: &str,
+        tx: &Transaction,
+    ) -> Result<bool> {
+        self.refresh_from_provider(column, id, tx, true)
+    }
+
+    /// Apply a change the provider reported for a transaction it sent before
+    ///
+    /// Like [`update_pending_transaction`](Self::update_pending_transaction),
+    /// but also for posted transactions, since the provider says its copy
+    /// changed (e.g. Plaid's `modified` list). Tags and notes are never touched.
+    /// Returns true if a row changed.
+    pub fn update_transaction_from_provider(
+        &self,
+        column: &str,
+        id: &str,
+        tx: &Transaction,
+    ) -> Result<bool> {
+        self.refresh_from_provider(column, id, tx, false)
+    }
+
+    fn refresh_from_provider(
+        &self,
+        column: &str,
+        id: &str,
+        tx: &Transaction,
+        only_pending: bool,
+    ) -> Result<bool> {
+        if !PROVIDER_ID_COLUMNS.contains(&column) {
+            return Err(anyhow!("Not a provider ID column: {}", column));
+        }
+        tx.validate()
+            .with_context(|| format!("Refusing to store invalid transaction {}", tx.id))?;
+
+        let conn = self.write_conn();
+        let sql = format!(
+            "UPDATE sys_transactions SET
+                amount = ?,
+                description = ?,
+                transaction_date = ?,
+                posted_date = ?,
+                updated_at = ?,
+                sf_posted = COALESCE(?, sf_posted),
+                sf_amount = COALESCE(?, sf_amount),
+                sf_description = COALESCE(?, sf_description),
+                sf_transacted_at = COALESCE(?, sf_transacted_at),
+                sf_pending = COALESCE(?, sf_pending),
+                lf_amount = COALESCE(?, lf_amount),
+                lf_date = COALESCE(?, lf_date),
+                lf_merchant = COALESCE(?, lf_merchant),
+                lf_description = COALESCE(?, lf_description),
+                lf_is_pending = COALESCE(?, lf_is_pending),
+                pl_amount = COALESCE(?, pl_amount),
+                pl_date = COALESCE(?, pl_date),
+                pl_authorized_date = COALESCE(?, pl_authorized_date),
+                pl_name = COALESCE(?, pl_name),
+                pl_merchant_name = COALESCE(?, pl_merchant_name),
+                pl_pending = COALESCE(?, pl_pending),
+                original_amount = COALESCE(?, original_amount),
+                original_currency = COALESCE(?, original_currency)
+             WHERE {} = ?{}",
+            column,
+            if only_pending {
+                " AND COALESCE(sf_pending, lf_is_pending, pl_pending, false)"
+            } else {
+                ""
+            }
+        );
+
+        let rows_changed = conn.execute(
+            &sql,
+            params![
+                tx.amount.to_string().parse::<f64>().unwrap_or(0.0),
+                tx.description,
+                tx.transaction_date.to_string(),
+                tx.posted_date.to_string(),
+                Utc::now().to_rfc3339(),
+                tx.sf_posted,
+                tx.sf_amount,
+                tx.sf_description,
+                tx.sf_transacted_at,
+                tx.sf_pending,
+                tx.lf_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
                 tx.lf_date.map(|d| d.to_string()),
                 tx.lf_merchant,
                 tx.lf_description,
                 tx.lf_is_pending,
+                tx.pl_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                tx.pl_date.map(|d| d.to_string()),
+                tx.pl_authorized_date.map(|d| d.to_string()),
+                tx.pl_name,
+                tx.pl_merchant_name,
+                tx.pl_pending,
+                tx.original_amount
+                    .map(|d| d.to_string().parse::<f64>().unwrap_or(0.0)),
+                tx.original_currency,
+                id,
             ],
         )?;
 
         Ok(rows_changed > 0)
     }
 
-    /// Check if a transaction exists by ID
-    pub fn transaction_exists(&self, tx_id: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM sys_transactions WHERE transaction_id = ?",
-            params![tx_id],
-            |row| row.get(0),
-        )?;
-        Ok(count > 0)
-    }
+    /// Provider category to tag mappings, keyed by lowercased category
+    pub fn get_category_map(&self) -> Result<HashMap<String, String>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare("SELECT provider_category, tag FROM sys_category_map")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
 
-    /// Check if a transaction exists by SimpleFIN ID (indexed, fast)
-    pub fn transaction_exists_by_sf_id(&self, sf_id: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM sys_transactions WHERE sf_id = ?",
-            params![sf_id],
-            |row| row.get(0),
-        )?;
-        Ok(count > 0)
+        let mut result = HashMap::new();
+        for row in rows {
+            let (category, tag) = row?;
+            result.insert(category, tag);
+        }
+        Ok(result)
     }
 
-    /// Check if a transaction exists by Lunchflow ID (indexed, fast)
-    pub fn transaction_exists_by_lf_id(&self, lf_id: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM sys_transactions WHERE lf_id = ?",
-            params![lf_id],
-            |row| row.get(0),
+    /// Map a provider category to a tag, replacing any existing mapping
+    pub fn set_category_mapping(&self, provider_category: &str, tag: &str) -> Result<()> {
+        let conn = self.write_conn();
+        conn.execute(
+            "INSERT INTO sys_category_map (provider_category, tag) VALUES (?, ?)
+             ON CONFLICT (provider_category) DO UPDATE SET
+                tag = EXCLUDED.tag,
+                updated_at = CURRENT_TIMESTAMP",
+            params![provider_category.trim().to_lowercase(), tag],
         )?;
-        Ok(count > 0)
+        Ok(())
     }
 
     /// Check if a CSV fingerprint exists in batches other than the current one
@@ -809,7 +2417,7 @@ impl DuckDbRepository {
         fingerprint: &str,
         current_batch_id: &str,
     ) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM sys_transactions WHERE csv_fingerprint = ? AND (csv_batch_id IS NULL OR csv_batch_id != ?)",
             params![fingerprint, current_batch_id],
@@ -818,15 +2426,79 @@ impl DuckDbRepository {
         Ok(count > 0)
     }
 
+    /// Every CSV import batch still in the database, newest first
+    pub fn list_import_batches(&self) -> Result<Vec<BatchInfo>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT t.csv_batch_id, MIN(t.created_at)::VARCHAR, t.account_id, a.name, COUNT(*)
+             FROM sys_transactions t
+             LEFT JOIN sys_accounts a ON a.account_id = t.account_id
+             WHERE t.csv_batch_id IS NOT NULL
+             GROUP BY t.csv_batch_id, t.account_id, a.name
+             ORDER BY MIN(t.created_at) DESC, t.csv_batch_id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let imported_at: String = row.get(1)?;
+            Ok(BatchInfo {
+                batch_id: row.get(0)?,
+                imported_at: parse_timestamp(&imported_at),
+                account_id: row.get(2)?,
+                account_name: row.get(3)?,
+                transaction_count: row.get(4)?,
+            })
+        })?;
+
+        let mut batches = Vec::new();
+        for row in rows {
+            batches.push(row?);
+        }
+        Ok(batches)
+    }
+
+    /// Hard-delete every transaction of a CSV import batch
+    ///
+    /// Balance snapshots the import created go too, and transfer partners
+    /// of deleted transactions are unlinked, all in one transaction.
+    /// Returns the number of transactions deleted.
+    pub fn delete_import_batch(&self, batch_id: &str) -> Result<usize> {
+        self.with_transaction(|| {
+            let conn = self.write_conn();
+
+            conn.execute(
+                "DELETE FROM sys_balance_snapshots WHERE import_batch_id = ?",
+                params![batch_id],
+            )?;
+
+            conn.execute(
+                "UPDATE sys_transactions SET transfer_group_id = NULL, updated_at = CURRENT_TIMESTAMP
+                 WHERE csv_batch_id IS DISTINCT FROM ?
+                   AND transfer_group_id IN (
+                       SELECT transfer_group_id FROM sys_transactions
+                       WHERE csv_batch_id = ? AND transfer_group_id IS NOT NULL
+                   )",
+                params![batch_id, batch_id],
+            )?;
+
+            let deleted = conn.execute(
+                "DELETE FROM sys_transactions WHERE csv_batch_id = ?",
+                params![batch_id],
+            )?;
+            Ok(deleted)
+        })
+    }
+
     pub fn get_transaction_by_id(&self, id: &str) -> Result<Option<Transaction>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
         let mut stmt = conn.prepare(
             "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
                     posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
                     created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
                     sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
-                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending
+                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending,
+                    pl_id, pl_account_id, pl_amount, pl_currency, pl_date::VARCHAR, pl_authorized_date::VARCHAR,
+                    pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                    original_amount, original_currency
              FROM sys_transactions WHERE transaction_id = ?"
         )?;
 
@@ -837,19 +2509,70 @@ impl DuckDbRepository {
         Ok(tx)
     }
 
+    /// The live (not deleted) transaction stored with this provider ID (sf_id, lf_id or pl_id)
+    pub fn get_transaction_by_external_id(
+        &self,
+        column: &str,
+        id: &str,
+    ) -> Result<Option<Transaction>> {
+        if !PROVIDER_ID_COLUMNS.contains(&column) {
+            return Err(anyhow!("Not a provider ID column: {}", column));
+        }
+        let conn = self.read_conn()?;
+        // CAST(tags AS VARCHAR) required - see get_transactions() for explanation
+        let mut stmt = conn.prepare(&format!(
+            "SELECT transaction_id, account_id, amount, description, transaction_date::VARCHAR,
+                    posted_date::VARCHAR, CAST(tags AS VARCHAR) as tags, external_ids, deleted_at, parent_transaction_id,
+                    created_at, updated_at, csv_fingerprint, csv_batch_id, is_manual, tags_auto_applied,
+                    sf_id, sf_posted, sf_amount, sf_description, sf_transacted_at, sf_pending, sf_extra,
+                    lf_id, lf_account_id, lf_amount, lf_currency, lf_date::VARCHAR, lf_merchant, lf_description, lf_is_pending,
+                    pl_id, pl_account_id, pl_amount, pl_currency, pl_date::VARCHAR, pl_authorized_date::VARCHAR,
+                    pl_name, pl_merchant_name, pl_pending, pl_category, notes,
+                    original_amount, original_currency
+             FROM sys_transactions WHERE {} = ? AND deleted_at IS NULL
+             LIMIT 1",
+            column
+        ))?;
+
+        let tx = stmt
+            .query_row([id], |row| self.row_to_transaction(row))
+            .ok();
+
+        Ok(tx)
+    }
+
     // === Balance snapshot operations ===
 
     pub fn add_balance_snapshot(&self, snapshot: &BalanceSnapshot) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        self.insert_balance_snapshot(snapshot, None)
+    }
+
+    /// Add a snapshot created by a CSV import, so undoing the batch removes it
+    pub fn add_import_balance_snapshot(
+        &self,
+        snapshot: &BalanceSnapshot,
+        batch_id: &str,
+    ) -> Result<()> {
+        self.insert_balance_snapshot(snapshot, Some(batch_id))
+    }
+
+    fn insert_balance_snapshot(
+        &self,
+        snapshot: &BalanceSnapshot,
+        import_batch_id: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.write_conn();
         conn.execute(
-            "INSERT INTO sys_balance_snapshots (snapshot_id, account_id, balance, snapshot_time, source, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO sys_balance_snapshots (snapshot_id, account_id, balance, snapshot_time, source, note, import_batch_id, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 snapshot.id.to_string(),
                 snapshot.account_id.to_string(),
                 snapshot.balance.to_string().parse::<f64>().unwrap_or(0.0),
                 snapshot.snapshot_time.to_string(),
                 snapshot.source.as_ref().map(|s| s.to_string()),
+                snapshot.note,
+                import_batch_id,
                 snapshot.created_at.to_rfc3339(),
                 snapshot.updated_at.to_rfc3339(),
             ],
@@ -858,13 +2581,13 @@ impl DuckDbRepository {
     }
 
     pub fn get_balance_snapshots(&self, account_id: Option<&str>) -> Result<Vec<BalanceSnapshot>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         // Cast TIMESTAMP and balance columns to VARCHAR so they can be read as strings with full precision
         let sql = if account_id.is_some() {
-            "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR
+            "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR, note
              FROM sys_balance_snapshots WHERE account_id = ? ORDER BY snapshot_time DESC"
         } else {
-            "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR
+            "SELECT snapshot_id, account_id, balance::VARCHAR, snapshot_time::VARCHAR, source, created_at::VARCHAR, updated_at::VARCHAR, note
              FROM sys_balance_snapshots ORDER BY snapshot_time DESC"
         };
 
@@ -889,7 +2612,7 @@ impl DuckDbRepository {
         new_balance: Decimal,
         new_source: &str,
     ) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
         conn.execute(
             "UPDATE sys_balance_snapshots SET balance = ?, source = ?, updated_at = ? WHERE snapshot_id = ?",
             params![
@@ -902,6 +2625,30 @@ impl DuckDbRepository {
         Ok(())
     }
 
+    /// Record why a balance snapshot was set (None clears the note)
+    pub fn set_balance_snapshot_note(&self, snapshot_id: &str, note: Option<&str>) -> Result<()> {
+        let conn = self.write_conn();
+        conn.execute(
+            "UPDATE sys_balance_snapshots SET note = ?, updated_at = ? WHERE snapshot_id = ?",
+            params![note, Utc::now().to_rfc3339(), snapshot_id],
+        )?;
+        Ok(())
+    }
+
+    /// Whether the account has a `manual` balance snapshot on the given date
+    pub fn has_manual_balance_snapshot(&self, account_id: &str, date: NaiveDate) -> Result<bool> {
+        let conn = self.read_conn()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sys_balance_snapshots
+             WHERE account_id = ?
+             AND source = 'manual'
+             AND CAST(snapshot_time AS DATE) = ?",
+            params![account_id, date.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
     /// Delete all balance snapshots for an account within a date range
     pub fn delete_balance_snapshots_in_range(
         &self,
@@ -909,7 +2656,7 @@ impl DuckDbRepository {
         start_date: NaiveDate,
         end_date: NaiveDate,
     ) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
         // Delete snapshots where the date part of snapshot_time falls within the range
         let deleted = conn.execute(
             "DELETE FROM sys_balance_snapshots
@@ -921,6 +2668,39 @@ impl DuckDbRepository {
         Ok(deleted)
     }
 
+    /// Keep one balance snapshot per account per day, returning how many were deleted
+    ///
+    /// The kept snapshot is the highest-priority source (manual, then
+    /// csv_import, then sync, then anything else), latest first. Manual
+    /// snapshots are never deleted, so each account keeps at least one snapshot.
+    pub fn dedupe_balance_snapshots(&self) -> Result<usize> {
+        let conn = self.write_conn();
+        let deleted = conn.execute(
+            "DELETE FROM sys_balance_snapshots
+             WHERE snapshot_id IN (
+                 SELECT snapshot_id FROM (
+                     SELECT snapshot_id, source, ROW_NUMBER() OVER (
+                         PARTITION BY account_id, CAST(snapshot_time AS DATE)
+                         ORDER BY
+                             CASE source
+                                 WHEN 'manual' THEN 0
+                                 WHEN 'csv_import' THEN 1
+                                 WHEN 'sync' THEN 2
+                                 ELSE 3
+                             END,
+                             snapshot_time DESC,
+                             created_at DESC,
+                             snapshot_id
+                     ) AS day_rank
+                     FROM sys_balance_snapshots
+                 )
+                 WHERE day_rank > 1 AND source IS DISTINCT FROM 'manual'
+             )",
+            [],
+        )?;
+        Ok(deleted)
+    }
+
     fn row_to_balance_snapshot(&self, row: &duckdb::Row) -> BalanceSnapshot {
         let id_str: String = row.get(0).unwrap_or_default();
         let account_id_str: String = row.get(1).unwrap_or_default();
@@ -929,6 +2709,7 @@ impl DuckDbRepository {
         let source: Option<String> = row.get(4).ok();
         let created_str: String = row.get(5).unwrap_or_default();
         let updated_str: String = row.get(6).unwrap_or_default();
+        let note: Option<String> = row.get(7).ok();
 
         BalanceSnapshot {
             id: Uuid::parse_str(&id_str).unwrap_or_else(|_| Uuid::new_v4()),
@@ -936,6 +2717,7 @@ impl DuckDbRepository {
             balance: Decimal::from_str_exact(&balance_str).unwrap_or_default(),
             snapshot_time: parse_naive_datetime(&snapshot_time_str),
             source,
+            note,
             created_at: parse_timestamp(&created_str),
             updated_at: parse_timestamp(&updated_str),
         }
@@ -944,52 +2726,91 @@ impl DuckDbRepository {
     // === Query operations ===
 
     pub fn execute_query(&self, sql: &str) -> Result<QueryResult> {
-        // Validate it's a read-only query by checking SQL statement type
-        // Only look at the first word after stripping whitespace/comments
-        let sql_trimmed = sql.trim();
-        let first_word = sql_trimmed
-            .split_whitespace()
-            .next()
-            .unwrap_or("")
-            .to_uppercase();
-        if first_word != "SELECT" && first_word != "WITH" {
-            anyhow::bail!("Only SELECT queries are allowed");
-        }
-
-        // Also block dangerous operations even in subqueries
-        let sql_upper = sql.to_uppercase();
-        // Use word boundaries to avoid false positives (deleted_at vs DELETE)
-        let dangerous_patterns = [
-            " INSERT ",
-            " UPDATE ",
-            " DROP ",
-            " CREATE ",
-            " ALTER ",
-            " TRUNCATE ",
-            "\nINSERT ",
-            "\nUPDATE ",
-            "\nDROP ",
-            "\nCREATE ",
-            "\nALTER ",
-            "\nTRUNCATE ",
-            "(INSERT ",
-            "(UPDATE ",
-            "(DROP ",
-            "(CREATE ",
-            "(ALTER ",
-            "(TRUNCATE ",
-        ];
-        for pattern in dangerous_patterns {
-            if sql_upper.contains(pattern) {
-                anyhow::bail!("Only SELECT queries are allowed");
-            }
+        self.execute_query_with_limits(sql, QueryLimits::default())
+    }
+
+    /// Like [`execute_query`](Self::execute_query), within the given limits
+    pub fn execute_query_with_limits(&self, sql: &str, limits: QueryLimits) -> Result<QueryResult> {
+        ensure_read_only(sql)?;
+
+        let sql = apply_row_limit(sql, limits.max_rows);
+        let conn = self.read_conn()?;
+        run_with_timeout(&conn, limits.timeout, |conn| {
+            self.select_rows(conn, &sql, &[])
+        })
+    }
+
+    /// DuckDB's plan for a read-only query, as EXPLAIN rows
+    ///
+    /// The SQL must be a single SELECT/WITH, checked the same way as
+    /// `execute_query`. With `analyze` the query actually runs (EXPLAIN
+    /// ANALYZE) so the plan includes per-operator timings and row counts;
+    /// the timeout in `limits` applies then.
+    pub fn explain_query(
+        &self,
+        sql: &str,
+        analyze: bool,
+        limits: QueryLimits,
+    ) -> Result<QueryResult> {
+        validate_sql_syntax(sql)?;
+        ensure_read_only(sql)?;
+        if Parser::parse_sql(&DuckDbDialect {}, sql)?.len() != 1 {
+            anyhow::bail!("Can only explain one query at a time");
         }
 
-        let conn = self.conn.lock().unwrap();
+        let keyword = if analyze {
+            "EXPLAIN ANALYZE"
+        } else {
+            "EXPLAIN"
+        };
+        let sql = format!("{} {}", keyword, sql.trim());
+        let conn = self.read_conn()?;
+        let timeout = if analyze { limits.timeout } else { None };
+        run_with_timeout(&conn, timeout, |conn| self.select_rows(conn, &sql, &[]))
+    }
+
+    /// Prepare a read-only query that another thread can cancel
+    ///
+    /// Nothing runs until [`PendingQuery::run`] is called. Meanwhile the
+    /// [`QueryHandle`] can be cloned and sent elsewhere, e.g. to a "Stop"
+    /// button; cancelling makes `run` return [`RepositoryError::Cancelled`].
+    /// `limits` apply as in [`execute_query_with_limits`](Self::execute_query_with_limits).
+    pub fn execute_query_cancellable(
+        &self,
+        sql: &str,
+        limits: QueryLimits,
+    ) -> Result<(QueryHandle, PendingQuery<'_>)> {
+        ensure_read_only(sql)?;
+
+        // Not from the pool: a late cancel() could leave the interrupt pending
+        // on the connection and stop whichever query used it next
+        let conn = self.new_read_connection()?;
+        let interrupt = conn.interrupt_handle();
+        let handle = QueryHandle {
+            interrupt: Arc::new(move || interrupt.interrupt()),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        let pending = PendingQuery {
+            repository: self,
+            conn,
+            sql: apply_row_limit(sql, limits.max_rows),
+            timeout: limits.timeout,
+            cancelled: Arc::clone(&handle.cancelled),
+        };
+        Ok((handle, pending))
+    }
+
+    /// Run a query and collect its columns and rows
+    fn select_rows(
+        &self,
+        conn: &Connection,
+        sql: &str,
+        params: &[&dyn duckdb::ToSql],
+    ) -> Result<QueryResult> {
         let mut stmt = conn.prepare(sql)?;
 
         // Execute query and iterate
-        let mut result_rows = stmt.query([])?;
+        let mut result_rows = stmt.query(params)?;
 
         // Collect all rows first
         let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
@@ -1048,6 +2869,13 @@ impl DuckDbRepository {
     /// For SELECT queries, returns columns and rows.
     /// For write queries (INSERT/UPDATE/DELETE), returns affected_rows count.
     pub fn execute_sql(&self, sql: &str) -> Result<QueryResult> {
+        self.execute_sql_with_limits(sql, QueryLimits::default())
+    }
+
+    /// Like [`execute_sql`](Self::execute_sql), within the given limits
+    ///
+    /// The timeout applies to writes too; the row limit only to SELECTs.
+    pub fn execute_sql_with_limits(&self, sql: &str, limits: QueryLimits) -> Result<QueryResult> {
         // Validate SQL syntax before execution to prevent crashes on malformed queries
         validate_sql_syntax(sql)?;
 
@@ -1063,60 +2891,23 @@ impl DuckDbRepository {
             || first_word == "DESCRIBE"
             || first_word == "SHOW";
 
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
 
         if is_select {
             // Read query - return columns and rows
-            let mut stmt = conn.prepare(sql)?;
-            let mut result_rows = stmt.query([])?;
-
-            let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
-            let mut column_count = 0;
-
-            while let Some(row) = result_rows.next()? {
-                if rows.is_empty() {
-                    column_count = row.as_ref().column_count();
-                }
-
-                let mut row_values: Vec<serde_json::Value> = Vec::new();
-                for i in 0..column_count {
-                    let value = self.get_column_value(row, i);
-                    row_values.push(value);
-                }
-                rows.push(row_values);
+            let result = run_with_timeout(&conn, limits.timeout, |conn| {
+                self.select_rows(conn, &apply_row_limit(sql, limits.max_rows), &[])
+            });
+            // WITH can also lead into an INSERT, UPDATE or DELETE
+            if first_word == "WITH" && ensure_read_only(sql).is_err() {
+                self.data_version.fetch_add(1, Ordering::SeqCst);
             }
-
-            drop(result_rows);
-
-            let columns: Vec<String> = if column_count > 0 {
-                (0..column_count)
-                    .map(|i| {
-                        stmt.column_name(i)
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("col{}", i))
-                    })
-                    .collect()
-            } else {
-                let count = stmt.column_count();
-                (0..count)
-                    .map(|i| {
-                        stmt.column_name(i)
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("col{}", i))
-                    })
-                    .collect()
-            };
-
-            let row_count = rows.len();
-
-            Ok(QueryResult {
-                columns,
-                rows,
-                row_count,
-            })
+            result
         } else {
             // Write query - return affected rows
-            let affected = conn.execute(sql, [])?;
+            let affected =
+                run_with_timeout(&conn, limits.timeout, |conn| Ok(conn.execute(sql, [])?))?;
+            self.data_version.fetch_add(1, Ordering::SeqCst);
 
             Ok(QueryResult {
                 columns: vec!["affected_rows".to_string()],
@@ -1133,6 +2924,18 @@ impl DuckDbRepository {
         &self,
         sql: &str,
         params: &[serde_json::Value],
+    ) -> Result<QueryResult> {
+        self.execute_sql_with_params_and_limits(sql, params, QueryLimits::default())
+    }
+
+    /// Like [`execute_sql_with_params`](Self::execute_sql_with_params), within the given limits
+    ///
+    /// The timeout applies to writes too; the row limit only to SELECTs.
+    pub fn execute_sql_with_params_and_limits(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+        limits: QueryLimits,
     ) -> Result<QueryResult> {
         // Validate SQL syntax before execution to prevent crashes on malformed queries
         validate_sql_syntax(sql)?;
@@ -1149,7 +2952,7 @@ impl DuckDbRepository {
             || first_word == "DESCRIBE"
             || first_word == "SHOW";
 
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock_conn();
 
         // Convert JSON params to DuckDB params
         let duckdb_params: Vec<Box<dyn duckdb::ToSql>> = params
@@ -1161,57 +2964,20 @@ impl DuckDbRepository {
 
         if is_select {
             // Read query - return columns and rows
-            let mut stmt = conn.prepare(sql)?;
-            let mut result_rows = stmt.query(param_refs.as_slice())?;
-
-            let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
-            let mut column_count = 0;
-
-            while let Some(row) = result_rows.next()? {
-                if rows.is_empty() {
-                    column_count = row.as_ref().column_count();
-                }
-
-                let mut row_values: Vec<serde_json::Value> = Vec::new();
-                for i in 0..column_count {
-                    let value = self.get_column_value(row, i);
-                    row_values.push(value);
-                }
-                rows.push(row_values);
+            let result = run_with_timeout(&conn, limits.timeout, |conn| {
+                self.select_rows(conn, &apply_row_limit(sql, limits.max_rows), &param_refs)
+            });
+            // WITH can also lead into an INSERT, UPDATE or DELETE
+            if first_word == "WITH" && ensure_read_only(sql).is_err() {
+                self.data_version.fetch_add(1, Ordering::SeqCst);
             }
-
-            drop(result_rows);
-
-            let columns: Vec<String> = if column_count > 0 {
-                (0..column_count)
-                    .map(|i| {
-                        stmt.column_name(i)
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("col{}", i))
-                    })
-                    .collect()
-            } else {
-                let count = stmt.column_count();
-                (0..count)
-                    .map(|i| {
-                        stmt.column_name(i)
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|_| format!("col{}", i))
-                    })
-                    .collect()
-            };
-
-            let row_count = rows.len();
-
-            Ok(QueryResult {
-                columns,
-                rows,
-                row_count,
-            })
+            result
         } else {
             // Write query - return affected rows
-            let mut stmt = conn.prepare(sql)?;
-            let affected = stmt.execute(param_refs.as_slice())?;
+            let affected = run_with_timeout(&conn, limits.timeout, |conn| {
+                Ok(conn.execute(sql, param_refs.as_slice())?)
+            })?;
+            self.data_version.fetch_add(1, Ordering::SeqCst);
 
             Ok(QueryResult {
                 columns: vec!["affected_rows".to_string()],
@@ -1407,7 +3173,7 @@ impl DuckDbRepository {
     // === Integration operations ===
 
     pub fn get_integrations(&self) -> Result<Vec<Integration>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let mut stmt =
             conn.prepare("SELECT integration_name, integration_settings FROM sys_integrations")?;
 
@@ -1426,7 +3192,7 @@ impl DuckDbRepository {
     }
 
     pub fn upsert_integration(&self, name: &str, settings: &serde_json::Value) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
         let settings_json = serde_json::to_string(settings)?;
         let now = chrono::Utc::now().to_rfc3339();
 
@@ -1443,7 +3209,7 @@ impl DuckDbRepository {
     }
 
     pub fn delete_integration(&self, name: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.write_conn();
         let rows = conn.execute(
             "DELETE FROM sys_integrations WHERE integration_name = ?",
             params![name],
@@ -1451,11 +3217,113 @@ impl DuckDbRepository {
         Ok(rows > 0)
     }
 
+    // === Plugin view operations ===
+
+    /// Create or replace a plugin's view in its schema and record it
+    ///
+    /// Returns the qualified view name, e.g. `plugin_budget.monthly_rollup`.
+    pub fn create_plugin_view(
+        &self,
+        plugin_id: &str,
+        view_name: &str,
+        sql: &str,
+    ) -> Result<String> {
+        let conn = self.write_conn();
+        let qualified = create_view_in_plugin_schema(&conn, plugin_id, view_name, sql)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO sys_plugin_views (plugin_id, view_name, sql, created_at)
+             VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
+            params![plugin_id, view_name, sql],
+        )?;
+        Ok(qualified)
+    }
+
+    /// Views registered by plugins, oldest first, optionally for one plugin
+    pub fn get_plugin_views(&self, plugin_id: Option<&str>) -> Result<Vec<PluginView>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT plugin_id, view_name, sql FROM sys_plugin_views
+             WHERE CAST(? AS VARCHAR) IS NULL OR plugin_id = ?
+             ORDER BY created_at, plugin_id, view_name",
+        )?;
+        let rows = stmt.query_map(params![plugin_id, plugin_id], |row| {
+            Ok(PluginView {
+                plugin_id: row.get(0)?,
+                view_name: row.get(1)?,
+                sql: row.get(2)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Columns of every table and view in a schema as (table, column, data type)
+    pub fn get_schema_columns(&self, schema: &str) -> Result<Vec<(String, String, String)>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT table_name, column_name, data_type
+             FROM information_schema.columns
+             WHERE table_schema = ?
+             ORDER BY table_name, ordinal_position",
+        )?;
+        let rows = stmt.query_map(params![schema], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Drop all views a plugin registered, returning how many were dropped
+    pub fn drop_plugin_views(&self, plugin_id: &str) -> Result<usize> {
+        let views = self.get_plugin_views(Some(plugin_id))?;
+        let conn = self.write_conn();
+        let schema = plugin_schema_name(plugin_id);
+        for view in &views {
+            conn.execute_batch(&format!(
+                "DROP VIEW IF EXISTS \"{}\".\"{}\"",
+                schema, view.view_name
+            ))?;
+        }
+        conn.execute(
+            "DELETE FROM sys_plugin_views WHERE plugin_id = ?",
+            params![plugin_id],
+        )?;
+        Ok(views.len())
+    }
+
+    /// Recreate every recorded plugin view, e.g. after compaction
+    pub fn recreate_plugin_views(&self) -> Result<usize> {
+        let views = self.get_plugin_views(None)?;
+        let conn = self.write_conn();
+        for view in &views {
+            create_view_in_plugin_schema(&conn, &view.plugin_id, &view.view_name, &view.sql)
+                .with_context(|| {
+                    format!(
+                        "Failed to recreate view {} for plugin {}",
+                        view.view_name, view.plugin_id
+                    )
+                })?;
+        }
+        Ok(views.len())
+    }
+
     // === Maintenance operations ===
 
     pub fn compact(&self) -> Result<()> {
         use std::fs;
 
+        if self.read_only {
+            anyhow::bail!("Cannot compact through a read-only database handle");
+        }
+
         // Note: We already hold the filesystem lock for the repository lifetime,
         // so no additional locking is needed here.
 
@@ -1522,7 +3390,15 @@ impl DuckDbRepository {
 
         // Close the main database connection temporarily
         // (we hold the internal mutex to prevent other threads from using the connection)
-        let mut conn_guard = self.conn.lock().unwrap();
+        let mut conn_guard = self.write_conn();
+
+        // Read connections share the old database; idle ones are closed now
+        // and busy ones are dropped instead of returning to the pool
+        {
+            let mut readers = self.readers.lock().unwrap();
+            readers.clear();
+            self.reader_epoch.fetch_add(1, Ordering::SeqCst);
+        }
 
         // Replace the old database with the compacted one
         // Backup the original first, then move temp in place
@@ -1568,7 +3444,7 @@ impl DuckDbRepository {
     // === Doctor checks ===
 
     pub fn check_orphaned_transactions(&self) -> Result<Vec<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             "SELECT t.transaction_id FROM sys_transactions t
              LEFT JOIN sys_accounts a ON t.account_id = a.account_id
@@ -1584,7 +3460,7 @@ impl DuckDbRepository {
     }
 
     pub fn check_orphaned_snapshots(&self) -> Result<Vec<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let mut stmt = conn.prepare(
             "SELECT s.snapshot_id FROM sys_balance_snapshots s
              LEFT JOIN sys_accounts a ON s.account_id = a.account_id
@@ -1600,7 +3476,7 @@ impl DuckDbRepository {
     }
 
     pub fn check_future_transactions(&self) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         // Use Rust-computed date to avoid ICU extension dependency
         let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1))
             .format("%Y-%m-%d")
@@ -1615,7 +3491,7 @@ impl DuckDbRepository {
     }
 
     pub fn count_untagged_transactions(&self) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM sys_transactions
              WHERE (tags IS NULL OR len(tags) = 0)
@@ -1627,7 +3503,7 @@ impl DuckDbRepository {
     }
 
     pub fn count_uncategorized_expenses(&self) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         let count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM sys_transactions
              WHERE amount < 0
@@ -1641,7 +3517,7 @@ impl DuckDbRepository {
 
     /// Check for transactions with unreasonable dates (before 1970 or more than 1 year in future)
     pub fn check_date_sanity(&self) -> Result<Vec<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
         // Use Rust-computed date to avoid ICU extension dependency
         let one_year_future = (chrono::Utc::now() + chrono::Duration::days(365))
             .format("%Y-%m-%d")
@@ -1672,27 +3548,269 @@ impl DuckDbRepository {
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(results)
+        Ok(results)
+    }
+
+    /// Check database file integrity
+    ///
+    /// Runs `PRAGMA database_size` and a `CHECKPOINT` (both read every block header,
+    /// so a corrupt file fails here), then verifies every core `sys_*` table exists.
+    /// A missing table usually means a migration was interrupted.
+    ///
+    /// Returns a list of problems; an empty list means the database is healthy.
+    pub fn check_integrity(&self) -> Result<Vec<String>> {
+        let mut problems = Vec::new();
+
+        {
+            let conn = self.lock_conn();
+            let size_check = conn.prepare("PRAGMA database_size").and_then(|mut stmt| {
+                let mut rows = stmt.query([])?;
+                while rows.next()?.is_some() {}
+                Ok(())
+            });
+            if let Err(e) = size_check {
+                problems.push(format!("PRAGMA database_size failed: {}", e));
+            }
+            // Read-only handles can't checkpoint
+            if !self.read_only {
+                if let Err(e) = conn.execute_batch("CHECKPOINT") {
+                    problems.push(format!("CHECKPOINT failed: {}", e));
+                }
+            }
+        }
+
+        for table in EXPECTED_SYS_TABLES {
+            if !self.table_exists(table)? {
+                problems.push(format!("Missing table: {}", table));
+            }
+        }
+
+        Ok(problems)
+    }
+
+    /// Check if a table exists
+    pub fn table_exists(&self, table_name: &str) -> Result<bool> {
+        let conn = self.read_conn()?;
+        // Split schema.table if present
+        let (schema, table) = if table_name.contains('.') {
+            let parts: Vec<&str> = table_name.split('.').collect();
+            (parts[0], parts[1])
+        } else {
+            ("main", table_name)
+        };
+
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM information_schema.tables
+             WHERE table_schema = ? AND table_name = ?",
+            [schema, table],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Map of primary key to `updated_at` for every row of a core table
+    ///
+    /// Only sys_accounts, sys_transactions and sys_balance_snapshots are
+    /// supported. Used to compare two databases row by row.
+    pub fn get_row_versions(&self, table: &str) -> Result<HashMap<String, String>> {
+        let id_column = match table {
+            "sys_accounts" => "account_id",
+            "sys_transactions" => "transaction_id",
+            "sys_balance_snapshots" => "snapshot_id",
+            _ => anyhow::bail!("Row versions are not available for table {}", table),
+        };
+
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, CAST(updated_at AS VARCHAR) FROM {}",
+            id_column, table
+        ))?;
+        let versions = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<HashMap<_, _>, _>>()?;
+        Ok(versions)
+    }
+
+    /// Upsert every row of `table` from another database file into this one
+    ///
+    /// The source is attached read-only (with this database's encryption key, if
+    /// any) and only columns present in both schemas are copied, so a backup
+    /// taken before a migration can still be restored. `table` is interpolated
+    /// into SQL - callers must validate it. Returns the number of rows written.
+    pub fn upsert_table_from(&self, source_db: &Path, table: &str) -> Result<usize> {
+        if self.read_only {
+            anyhow::bail!("Cannot restore into a read-only database handle");
+        }
+
+        let conn = self.write_conn();
+        let attach_options = match &self.encryption_key {
+            Some(key) => format!("ENCRYPTION_KEY '{}', READ_ONLY", key),
+            None => "READ_ONLY".to_string(),
+        };
+        conn.execute(
+            &format!(
+                "ATTACH '{}' AS restore_src ({})",
+                source_db.display(),
+                attach_options
+            ),
+            [],
+        )
+        .context("Failed to open backup database")?;
+
+        let result = (|| -> Result<usize> {
+            let live_catalog: String =
+                conn.query_row("SELECT current_database()", [], |row| row.get(0))?;
+
+            let columns_of = |catalog: &str| -> Result<Vec<String>> {
+                let mut stmt = conn.prepare(
+                    "SELECT column_name FROM information_schema.columns
+                     WHERE table_catalog = ? AND table_schema = 'main' AND table_name = ?
+                     ORDER BY ordinal_position",
+                )?;
+                let columns = stmt
+                    .query_map(params![catalog, table], |row| row.get::<_, String>(0))?
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                Ok(columns)
+            };
+
+            let source_columns = columns_of("restore_src")?;
+            if source_columns.is_empty() {
+                anyhow::bail!("Backup does not contain table {}", table);
+            }
+            let common: Vec<String> = columns_of(&live_catalog)?
+                .into_iter()
+                .filter(|c| source_columns.contains(c))
+                .map(|c| format!("\"{}\"", c))
+                .collect();
+            if common.is_empty() {
+                anyhow::bail!("Table {} has no columns in common with the backup", table);
+            }
+
+            let column_list = common.join(", ");
+            let rows = conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {table} ({cols}) SELECT {cols} FROM restore_src.main.{table}",
+                    table = table,
+                    cols = column_list
+                ),
+                [],
+            )?;
+            Ok(rows)
+        })();
+
+        let _ = conn.execute("DETACH restore_src", []);
+        result
     }
 
-    /// Check if a table exists
-    pub fn table_exists(&self, table_name: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
-        // Split schema.table if present
-        let (schema, table) = if table_name.contains('.') {
-            let parts: Vec<&str> = table_name.split('.').collect();
-            (parts[0], parts[1])
-        } else {
-            ("main", table_name)
+    /// Names of the `sys_*` tables in this database, sorted
+    pub fn sys_table_names(&self) -> Result<Vec<String>> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT table_name FROM information_schema.tables
+             WHERE table_catalog = current_database() AND table_schema = 'main'
+               AND table_type = 'BASE TABLE' AND starts_with(table_name, 'sys_')
+             ORDER BY table_name",
+        )?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(tables)
+    }
+
+    /// Write every row of `table` to `path` as JSON Lines, one object per row
+    ///
+    /// `table` is interpolated into SQL - callers must validate it.
+    /// Returns the number of rows written.
+    pub fn export_table_jsonl(&self, table: &str, path: &Path) -> Result<usize> {
+        let conn = self.read_conn()?;
+        let rows: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })?;
+        conn.execute(
+            &format!(
+                "COPY {} TO '{}' (FORMAT JSON)",
+                table,
+                path.display().to_string().replace('\'', "''")
+            ),
+            [],
+        )
+        .with_context(|| format!("Failed to export {}", table))?;
+        Ok(rows as usize)
+    }
+
+    /// Upsert the rows of a JSON Lines file written by [`export_table_jsonl`](Self::export_table_jsonl)
+    ///
+    /// Values are read as the live column types, and only columns present in
+    /// both the file and the table are copied, so an export from an older
+    /// schema can still be imported. `table` is interpolated into SQL - callers
+    /// must validate it. Returns the number of rows written.
+    pub fn import_table_jsonl(&self, table: &str, path: &Path) -> Result<usize> {
+        if self.read_only {
+            anyhow::bail!("Cannot import into a read-only database handle");
+        }
+
+        // Every line has the same keys, so the first one names the columns
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let first_line = match contents.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) => line,
+            None => return Ok(0),
         };
+        let keys: serde_json::Map<String, serde_json::Value> = serde_json::from_str(first_line)
+            .with_context(|| format!("{} is not a JSON Lines export", path.display()))?;
 
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM information_schema.tables
-             WHERE table_schema = ? AND table_name = ?",
-            [schema, table],
-            |row| row.get(0),
+        let conn = self.write_conn();
+        let mut stmt = conn.prepare(
+            "SELECT column_name, data_type FROM information_schema.columns
+             WHERE table_catalog = current_database() AND table_schema = 'main' AND table_name = ?
+             ORDER BY ordinal_position",
         )?;
-        Ok(count > 0)
+        let columns = stmt
+            .query_map([table], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if columns.is_empty() {
+            anyhow::bail!("Table {} does not exist", table);
+        }
+        let common: Vec<&(String, String)> = columns
+            .iter()
+            .filter(|(name, _)| keys.contains_key(name))
+            .collect();
+        if common.is_empty() {
+            anyhow::bail!(
+                "{} has no columns in common with table {}",
+                path.display(),
+                table
+            );
+        }
+
+        let column_list = common
+            .iter()
+            .map(|(name, _)| format!("\"{}\"", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let column_types = common
+            .iter()
+            .map(|(name, data_type)| format!("'{}': '{}'", name, data_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let rows = conn
+            .execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {table} ({cols})
+                     SELECT {cols} FROM read_json('{path}', format = 'newline_delimited', columns = {{{types}}})",
+                    table = table,
+                    cols = column_list,
+                    path = path.display().to_string().replace('\'', "''"),
+                    types = column_types
+                ),
+                [],
+            )
+            .with_context(|| format!("Failed to import {}", table))?;
+        Ok(rows)
     }
 
     // ========================================================================
@@ -1701,16 +3819,27 @@ impl DuckDbRepository {
 
     /// Get all enabled auto-tag rules, ordered by sort_order
     pub fn get_enabled_auto_tag_rules(&self) -> Result<Vec<AutoTagRule>> {
-        let conn = self.conn.lock().unwrap();
+        self.query_auto_tag_rules("WHERE enabled = true")
+    }
+
+    /// Get all auto-tag rules (enabled or not), ordered by sort_order
+    pub fn get_auto_tag_rules(&self) -> Result<Vec<AutoTagRule>> {
+        self.query_auto_tag_rules("")
+    }
+
+    fn query_auto_tag_rules(&self, where_clause: &str) -> Result<Vec<AutoTagRule>> {
+        let conn = self.read_conn()?;
         // CAST(tags AS VARCHAR) is critical here - without it, duckdb-rs silently fails
         // to read VARCHAR[] as String, returning "[]" and causing rules to have no tags.
         // This was the root cause of auto-tag rules not applying. See parse_duckdb_array().
-        let mut stmt = conn.prepare(
+        let sql = format!(
             "SELECT rule_id, name, sql_condition, CAST(tags AS VARCHAR) as tags_str, enabled, sort_order
              FROM sys_transactions_rules
-             WHERE enabled = true
-             ORDER BY sort_order, created_at"
-        )?;
+             {}
+             ORDER BY sort_order, created_at",
+            where_clause
+        );
+        let mut stmt = conn.prepare(&sql)?;
 
         let rules = stmt.query_map([], |row| {
             let tags_str: String = row.get(3).unwrap_or_else(|_| "[]".to_string());
@@ -1731,6 +3860,179 @@ impl DuckDbRepository {
         Ok(result)
     }
 
+    /// Insert an auto-tag rule, or replace the rule with the same ID
+    pub fn upsert_auto_tag_rule(&self, rule: &AutoTagRule) -> Result<()> {
+        let conn = self.write_conn();
+        let tags_literal = format_tags_array(&rule.tags);
+        let sql = format!(
+            "INSERT INTO sys_transactions_rules (rule_id, name, sql_condition, tags, enabled, sort_order)
+             VALUES (?, ?, ?, {}, ?, ?)
+             ON CONFLICT (rule_id) DO UPDATE SET
+                name = EXCLUDED.name,
+                sql_condition = EXCLUDED.sql_condition,
+                tags = EXCLUDED.tags,
+                enabled = EXCLUDED.enabled,
+                sort_order = EXCLUDED.sort_order,
+                updated_at = CURRENT_TIMESTAMP",
+            tags_literal
+        );
+        conn.execute(
+            &sql,
+            params![
+                rule.rule_id,
+                rule.name,
+                rule.sql_condition,
+                rule.enabled,
+                rule.sort_order
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the tags of an auto-tag rule
+    pub fn update_auto_tag_rule_tags(&self, rule_id: &str, tags: &[String]) -> Result<()> {
+        let conn = self.write_conn();
+        let tags_literal = format_tags_array(tags);
+        let sql = format!(
+            "UPDATE sys_transactions_rules SET tags = {}, updated_at = CURRENT_TIMESTAMP WHERE rule_id = ?",
+            tags_literal
+        );
+        conn.execute(&sql, params![rule_id])?;
+        Ok(())
+    }
+
+    /// Get usage count and total amount per tag, most used first
+    pub fn get_tag_stats(&self) -> Result<Vec<crate::services::TagStat>> {
+        let conn = self.read_conn()?;
+        // list_distinct guards against a transaction being counted twice for one tag
+        let mut stmt = conn.prepare(
+            "SELECT tag, COUNT(*) AS transaction_count, SUM(amount)::DOUBLE AS total_amount
+             FROM (
+                 SELECT unnest(list_distinct(tags)) AS tag, amount
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+             )
+             GROUP BY tag
+             ORDER BY transaction_count DESC, tag",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let total: Option<f64> = row.get(2)?;
+            Ok(crate::services::TagStat {
+                tag: row.get(0)?,
+                transaction_count: row.get(1)?,
+                total_amount: total
+                    .and_then(|t| Decimal::try_from(t).ok())
+                    .unwrap_or(Decimal::ZERO)
+                    .round_dp(2),
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Tag usage on transactions whose normalized description is `key`, leaving out one transaction
+    ///
+    /// Descriptions are normalized in SQL with the same steps as
+    /// [`Transaction::normalize_description`], so only the matching
+    /// transactions are read. Returns how many transactions match and, for
+    /// each tag they carry, how many carry it.
+    pub fn get_tag_counts_by_description_key(
+        &self,
+        key: &str,
+        exclude_tx_id: &str,
+    ) -> Result<(i64, Vec<(String, i64)>)> {
+        let conn = self.read_conn()?;
+        let mut stmt = conn.prepare(
+            r"WITH cleaned AS (
+                 SELECT tags,
+                        regexp_replace(regexp_replace(lower(description), '\bnull\b', '', 'g'),
+                                       'x{10,}\d{4}', '', 'g') AS d
+                 FROM sys_transactions
+                 WHERE deleted_at IS NULL
+                   AND description IS NOT NULL
+                   AND transaction_id != ?
+             ),
+             split AS (
+                 SELECT tags,
+                        regexp_split_to_array(d, '[x0-9]{7,12}') AS parts,
+                        regexp_extract_all(d, '[x0-9]{7,12}') AS numbers
+                 FROM cleaned
+             ),
+             similar AS (
+                 SELECT list_distinct(tags) AS tags
+                 FROM split
+                 -- Account-like numbers keep their last 4 digits, then only a-z0-9 is left
+                 WHERE regexp_replace(
+                           array_to_string(list_transform(parts, lambda part, i: part || COALESCE(
+                               CASE WHEN length(regexp_replace(numbers[i], '[^0-9]', '', 'g')) >= 4
+                                    THEN right(regexp_replace(numbers[i], '[^0-9]', '', 'g'), 4)
+                                    ELSE numbers[i] END, '')), ''),
+                           '[^a-z0-9]', '', 'g') = ?
+             )
+             SELECT NULL::VARCHAR AS tag, COUNT(*) FROM similar
+             UNION ALL
+             SELECT tag, COUNT(*) FROM (SELECT unnest(tags) AS tag FROM similar) GROUP BY tag",
+        )?;
+        let rows = stmt.query_map(params![exclude_tx_id, key], |row| {
+            Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut total = 0;
+        let mut counts = Vec::new();
+        for row in rows {
+            match row? {
+                (None, count) => total = count,
+                (Some(tag), count) => counts.push((tag, count)),
+            }
+        }
+        Ok((total, counts))
+    }
+
+    /// Get (transaction_id, tags) for every transaction carrying the given tag
+    pub fn get_transactions_with_tag(&self, tag: &str) -> Result<Vec<(String, Vec<String>)>> {
+        let conn = self.read_conn()?;
+        // Same CAST as auto-tag rules - see parse_duckdb_array()
+        let mut stmt = conn.prepare(
+            "SELECT transaction_id, CAST(tags AS VARCHAR)
+             FROM sys_transactions
+             WHERE list_contains(tags, ?)",
+        )?;
+        let rows = stmt.query_map(params![tag], |row| {
+            let id: String = row.get(0)?;
+            let tags_str: String = row.get(1).unwrap_or_else(|_| "[]".to_string());
+            Ok((id, parse_duckdb_array(&tags_str)))
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// IDs of all transactions in the `transactions` view matching a SQL condition
+    ///
+    /// The condition is spliced in as-is; callers must validate it first.
+    pub fn get_transaction_ids_matching(&self, sql_condition: &str) -> Result<Vec<String>> {
+        let conn = self.read_conn()?;
+        let sql = format!(
+            "SELECT transaction_id FROM transactions WHERE ({}) ORDER BY transaction_date, transaction_id",
+            sql_condition
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
     /// Get transaction IDs that match a SQL condition from a given set of IDs
     ///
     /// The sql_condition should be a valid SQL WHERE clause fragment
@@ -1744,7 +4046,7 @@ impl DuckDbRepository {
             return Ok(Vec::new());
         }
 
-        let conn = self.conn.lock().unwrap();
+        let conn = self.read_conn()?;
 
         // Build IN clause with UUIDs
         let id_list: Vec<String> = tx_ids.iter().map(|id| format!("'{}'", id)).collect();
@@ -1775,13 +4077,151 @@ impl DuckDbRepository {
         }
         Ok(result)
     }
-    
+
+    /// Run `f` inside a database transaction, rolling back everything it did if it fails
+    ///
+    /// Repository calls made by `f` join the transaction, and a nested call
+    /// simply runs inside the outer transaction. Other threads using this
+    /// repository meanwhile don't: their writes (and any `with_transaction`
+    /// of their own) wait until the transaction ends, and their reads use
+    /// separate connections that don't see its uncommitted changes. So `f`
+    /// must not wait on another thread that writes through this repository.
+    /// A failed statement aborts a DuckDB transaction, so `f` must not
+    /// swallow repository errors. DuckDB also checks foreign keys as if
+    /// earlier statements of the transaction weren't there, which rules out
+    /// deleting an account after its transactions (see `delete_account`);
+    /// inserting parents before children is fine.
+    pub fn with_transaction<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        let me = std::thread::current().id();
+        {
+            let mut owner = self.transaction_owner.lock().unwrap();
+            if *owner == Some(me) {
+                drop(owner);
+                return f();
+            }
+            while owner.is_some() {
+                owner = self.transaction_done.wait(owner).unwrap();
+            }
+            *owner = Some(me);
+        }
+        // Ends the transaction for other threads even if `f` panics
+        let _owner = TransactionOwner(self);
+
+        self.lock_conn()
+            .execute_batch("BEGIN TRANSACTION")
+            .context("Failed to start a transaction")?;
+
+        let result = f();
+
+        // Rolling back changes data too, so both ends count as a write
+        let conn = self.write_conn();
+        match result {
+            Ok(value) => conn
+                .execute_batch("COMMIT")
+                .inspect_err(|_| {
+                    let _ = conn.execute_batch("ROLLBACK");
+                })
+                .context("Failed to commit the transaction")
+                .map(|_| value),
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                Err(e)
+            }
+        }
+    }
+
     pub fn use_connection<T>(&self, func: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
-        let mut conn = self.conn.lock().unwrap();
+        let mut conn = self.write_conn();
         func(&mut conn)
     }
 }
 
+/// Clears the transaction owner when dropped - see [`DuckDbRepository::with_transaction`]
+struct TransactionOwner<'a>(&'a DuckDbRepository);
+
+impl Drop for TransactionOwner<'_> {
+    fn drop(&mut self) {
+        *self.0.transaction_owner.lock().unwrap() = None;
+        self.0.transaction_done.notify_all();
+    }
+}
+
+/// Connection guard for writes, see [`DuckDbRepository::write_conn`]
+///
+/// The data version is bumped on drop, while the lock is still held, so a
+/// reader can never see the new version together with the old data.
+struct WriteConn<'a> {
+    conn: MutexGuard<'a, Connection>,
+    data_version: &'a AtomicU64,
+}
+
+impl Deref for WriteConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl DerefMut for WriteConn<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+impl Drop for WriteConn<'_> {
+    fn drop(&mut self) {
+        self.data_version.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Connection guard for reads - see `DuckDbRepository::read_conn()`
+enum ReadConn<'a> {
+    /// A read connection, handed back to the pool when dropped
+    Pooled {
+        conn: Option<Connection>,
+        epoch: u64,
+        repository: &'a DuckDbRepository,
+    },
+    /// The write connection, while `with_transaction` has a transaction open
+    Shared(MutexGuard<'a, Connection>),
+}
+
+impl Deref for ReadConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            ReadConn::Pooled { conn, .. } => {
+                conn.as_ref().expect("read connection already released")
+            }
+            ReadConn::Shared(conn) => conn,
+        }
+    }
+}
+
+impl Drop for ReadConn<'_> {
+    fn drop(&mut self) {
+        if let ReadConn::Pooled {
+            conn,
+            epoch,
+            repository,
+        } = self
+        {
+            if let (Some(conn), Ok(mut readers)) = (conn.take(), repository.readers.lock()) {
+                if *epoch == repository.reader_epoch.load(Ordering::SeqCst)
+                    && readers.len() < READ_POOL_SIZE
+                {
+                    readers.push(conn);
+                }
+            }
+        }
+    }
+}
+
 /// Query result structure
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct QueryResult {
@@ -1790,6 +4230,154 @@ pub struct QueryResult {
     pub row_count: usize,
 }
 
+/// Guardrails for user SQL; the default sets no limits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Interrupt the query once it has run this long
+    pub timeout: Option<Duration>,
+    /// Add `LIMIT max_rows` to SELECTs that have no LIMIT of their own
+    pub max_rows: Option<usize>,
+}
+
+/// Stops a query started with [`DuckDbRepository::execute_query_cancellable`]
+#[derive(Clone)]
+pub struct QueryHandle {
+    interrupt: Arc<dyn Fn() + Send + Sync>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl QueryHandle {
+    /// Interrupt the query, from any thread
+    ///
+    /// Has no effect once the query has finished. Cancelling before it starts
+    /// means it never runs.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        (self.interrupt)();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A query waiting to run, paired with a [`QueryHandle`]
+pub struct PendingQuery<'a> {
+    repository: &'a DuckDbRepository,
+    conn: Connection,
+    sql: String,
+    timeout: Option<Duration>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl PendingQuery<'_> {
+    /// Run the query on this thread, blocking until it finishes or is cancelled
+    ///
+    /// A cancel at any point before this returns gives
+    /// [`RepositoryError::Cancelled`], even if the query got to finish.
+    pub fn run(self) -> Result<QueryResult> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err(RepositoryError::Cancelled.into());
+        }
+
+        // cancel() interrupts a running query, but an interrupt that lands
+        // before the query has started is lost, so keep interrupting until
+        // the query returns
+        let interrupt = self.conn.interrupt_handle();
+        let cancelled = Arc::clone(&self.cancelled);
+        let (done, finished) = mpsc::channel::<()>();
+        let watchdog = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(CANCEL_POLL_INTERVAL) {
+                if cancelled.load(Ordering::SeqCst) {
+                    interrupt.interrupt();
+                }
+            }
+        });
+
+        let result = run_with_timeout(&self.conn, self.timeout, |conn| {
+            self.repository.select_rows(conn, &self.sql, &[])
+        });
+        drop(done);
+        let _ = watchdog.join();
+
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err(RepositoryError::Cancelled.into());
+        }
+        result
+    }
+}
+
+/// One changed field in a transaction's audit trail
+///
+/// Values are stored as text: tags as a list such as `[food, travel]`, dates
+/// as `YYYY-MM-DD`. `changed_at` is in UTC.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AuditEntry {
+    pub transaction_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: NaiveDateTime,
+    /// "cli" or "desktop", if the app said - see [`DuckDbRepository::set_entry_point`]
+    pub entry_point: Option<String>,
+}
+
+/// Outcome of [`DuckDbRepository::resolve_account`]
+#[derive(Debug, Clone)]
+pub enum AccountResolution {
+    /// The query identifies exactly this account
+    Exact(Account),
+    /// Accounts the query might mean, sorted by name; empty if nothing came close
+    Candidates(Vec<Account>),
+}
+
+impl AccountResolution {
+    /// The matched account, or an error listing the candidates to pick from
+    pub fn into_account(self, query: &str) -> Result<Account> {
+        let candidates = match self {
+            AccountResolution::Exact(account) => return Ok(account),
+            AccountResolution::Candidates(candidates) => candidates,
+        };
+        match candidates.as_slice() {
+            [] => anyhow::bail!("Account not found: {}", query),
+            [account] => anyhow::bail!(
+                "Account not found: {}; did you mean {} ({})?",
+                query,
+                account.name,
+                account.id
+            ),
+            _ => {
+                let listed: Vec<String> = candidates
+                    .iter()
+                    .map(|a| format!("  {} ({})", a.name, a.id))
+                    .collect();
+                anyhow::bail!(
+                    "Account '{}' is ambiguous; did you mean one of:\n{}",
+                    query,
+                    listed.join("\n")
+                )
+            }
+        }
+    }
+}
+
+/// Criteria for [`DuckDbRepository::query_transactions`]
+///
+/// Unset fields don't filter; set fields are combined with AND. Amount and
+/// date bounds are inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilter {
+    pub account_id: Option<Uuid>,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Only transactions carrying this exact tag
+    pub tag: Option<String>,
+    /// Case-insensitive substring of the description or notes
+    pub search: Option<String>,
+}
+
 /// Integration info
 #[derive(Debug, Clone)]
 pub struct Integration {
@@ -1836,6 +4424,46 @@ fn parse_naive_datetime(s: &str) -> NaiveDateTime {
         .unwrap_or_else(|_| Utc::now().naive_utc())
 }
 
+/// Current values of transaction columns as text, for the audit trail
+///
+/// All None if the transaction can't be read, e.g. because it doesn't exist.
+fn audit_snapshot(conn: &Connection, tx_id: &str, columns: &[&str]) -> Vec<Option<String>> {
+    if columns.is_empty() {
+        return Vec::new();
+    }
+    let select: Vec<String> = columns
+        .iter()
+        .map(|column| format!("CAST({} AS VARCHAR)", column))
+        .collect();
+    let sql = format!(
+        "SELECT {} FROM sys_transactions WHERE transaction_id = ?",
+        select.join(", ")
+    );
+    conn.query_row(&sql, params![tx_id], |row| {
+        (0..columns.len())
+            .map(|i| row.get::<_, Option<String>>(i))
+            .collect::<duckdb::Result<Vec<_>>>()
+    })
+    .unwrap_or_else(|_| vec![None; columns.len()])
+}
+
+/// Create (or replace) a view in a plugin's schema, creating the schema if needed
+///
+/// Identifiers are quoted; callers validate them and the view SQL first.
+fn create_view_in_plugin_schema(
+    conn: &Connection,
+    plugin_id: &str,
+    view_name: &str,
+    sql: &str,
+) -> Result<String> {
+    let schema = plugin_schema_name(plugin_id);
+    conn.execute_batch(&format!(
+        "CREATE SCHEMA IF NOT EXISTS \"{schema}\";
+         CREATE OR REPLACE VIEW \"{schema}\".\"{view_name}\" AS {sql}"
+    ))?;
+    Ok(format!("{}.{}", schema, view_name))
+}
+
 /// Format tags as a DuckDB array literal: ['tag1', 'tag2']
 fn format_tags_array(tags: &[String]) -> String {
     if tags.is_empty() {
@@ -1881,6 +4509,10 @@ impl Drop for DuckDbRepository {
     fn drop(&mut self) {
         // Force a checkpoint to flush WAL to the main database file
         // This ensures clean shutdown and prevents WAL corruption on restart
+        // (read-only handles never write, so there is nothing to flush)
+        if self.read_only {
+            return;
+        }
         if let Ok(conn) = self.conn.lock() {
             let _ = conn.execute("CHECKPOINT", []);
         }
@@ -1892,6 +4524,38 @@ impl Drop for DuckDbRepository {
 mod tests {
     use super::*;
 
+    // ==================== Read-only Guard Tests ====================
+
+    #[test]
+    fn test_read_only_allows_keyword_like_columns() {
+        assert!(ensure_read_only("SELECT 1 AS update_count, 2 AS insert_date").is_ok());
+        assert!(ensure_read_only("SELECT * FROM transactions WHERE deleted_at IS NULL").is_ok());
+    }
+
+    #[test]
+    fn test_read_only_allows_ctes_and_unions() {
+        assert!(ensure_read_only("WITH t AS (SELECT 1 AS x) SELECT x FROM t").is_ok());
+        assert!(ensure_read_only("SELECT 1 UNION ALL SELECT 2").is_ok());
+        assert!(ensure_read_only("(SELECT 1)").is_ok());
+    }
+
+    #[test]
+    fn test_read_only_rejects_hidden_writes() {
+        assert!(ensure_read_only("SELECT 1;\tDELETE FROM x").is_err());
+        assert!(ensure_read_only("SELECT 1; -- harmless\nDROP TABLE x").is_err());
+        assert!(ensure_read_only("/* report */ UPDATE x SET a = 1").is_err());
+        assert!(ensure_read_only("INSERT\tINTO x VALUES (1)").is_err());
+        assert!(ensure_read_only("WITH t AS (SELECT 1) INSERT INTO x SELECT * FROM t").is_err());
+        assert!(ensure_read_only("CREATE TABLE x AS SELECT 1").is_err());
+    }
+
+    #[test]
+    fn test_read_only_rejects_unparseable_and_empty() {
+        assert!(ensure_read_only("SELEC * FROM x").is_err());
+        assert!(ensure_read_only("").is_err());
+        assert!(ensure_read_only("-- nothing").is_err());
+    }
+
     // ==================== Valid SQL Tests ====================
 
     #[test]
@@ -2241,4 +4905,44 @@ mod tests {
             vec!["groceries", "food"]
         );
     }
+
+    // ==================== apply_row_limit Tests ====================
+
+    #[test]
+    fn test_apply_row_limit_appends_limit() {
+        assert_eq!(
+            apply_row_limit("SELECT * FROM transactions;", Some(10)),
+            "SELECT * FROM transactions\nLIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_apply_row_limit_after_trailing_comment() {
+        assert_eq!(
+            apply_row_limit("SELECT 1; -- note\n", Some(10)),
+            "SELECT 1\nLIMIT 10"
+        );
+        assert_eq!(
+            apply_row_limit("SELECT '--;' AS s /* end */ ;", Some(10)),
+            "SELECT '--;' AS s\nLIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_apply_row_limit_keeps_existing_limit() {
+        let sql = "SELECT * FROM transactions LIMIT 5";
+        assert_eq!(apply_row_limit(sql, Some(10)), sql);
+    }
+
+    #[test]
+    fn test_apply_row_limit_without_max_rows() {
+        let sql = "SELECT * FROM transactions";
+        assert_eq!(apply_row_limit(sql, None), sql);
+    }
+
+    #[test]
+    fn test_apply_row_limit_skips_non_queries() {
+        let sql = "UPDATE sys_transactions SET tags = []";
+        assert_eq!(apply_row_limit(sql, Some(10)), sql);
+    }
 }
@@ -18,7 +18,8 @@ use uuid::Uuid;
 use crate::domain::result::{Error as DomainError, Result as DomainResult};
 use crate::domain::{Account, BalanceSnapshot, Transaction};
 use crate::ports::{
-    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+    ConnectionHealth, ConnectionStatus, DataAggregationProvider, FetchAccountsResult,
+    FetchTransactionsResult, IntegrationProvider,
 };
 
 // =============================================================================
@@ -94,6 +95,16 @@ pub struct LunchflowTransaction {
     /// API uses isPending (camelCase)
     #[serde(default, rename = "isPending")]
     pub is_pending: bool,
+    /// Amount in the purchase currency, for foreign transactions
+    #[serde(
+        default,
+        rename = "originalAmount",
+        deserialize_with = "deserialize_optional_amount"
+    )]
+    pub original_amount: Option<Decimal>,
+    /// Currency of `original_amount`
+    #[serde(default, rename = "originalCurrency")]
+    pub original_currency: Option<String>,
 }
 
 /// Deserialize ID that can be number or string
@@ -145,6 +156,30 @@ where
     }
 }
 
+/// Deserialize optional amount that can be number or string
+fn deserialize_optional_amount<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Decimal>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    let value: Option<JsonValue> = Option::deserialize(deserializer)?;
+    match value {
+        Some(JsonValue::Number(n)) => n
+            .to_string()
+            .parse::<Decimal>()
+            .map(Some)
+            .map_err(|e| D::Error::custom(format!("invalid decimal: {}", e))),
+        Some(JsonValue::String(s)) => s
+            .parse::<Decimal>()
+            .map(Some)
+            .map_err(|e| D::Error::custom(format!("invalid decimal: {}", e))),
+        Some(JsonValue::Null) | None => Ok(None),
+        _ => Err(D::Error::custom("expected number or string for amount")),
+    }
+}
+
 /// Result of syncing accounts from Lunchflow
 #[derive(Debug)]
 pub struct SyncedAccounts {
@@ -177,12 +212,47 @@ pub fn get_base_url() -> String {
     std::env::var(LUNCHFLOW_BASE_URL_ENV).unwrap_or_else(|_| LUNCHFLOW_PRODUCTION_URL.to_string())
 }
 
+/// Delay requested by a `Retry-After` header (delta-seconds form only)
+fn retry_after(response: &reqwest::blocking::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|secs| Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// Longest `Retry-After` we are willing to wait for
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Retry policy for transient Lunchflow failures (429s, connect errors, timeouts)
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    /// Total attempts per request, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each retry after that
+    pub base_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
 /// Lunchflow API client
 #[derive(Debug)]
 pub struct LunchflowClient {
     client: Client,
     api_key: String,
     base_url: String,
+    retry: RetryConfig,
 }
 
 impl LunchflowClient {
@@ -211,21 +281,85 @@ impl LunchflowClient {
             client,
             api_key: api_key.to_string(),
             base_url: base_url.trim_end_matches('/').to_string(),
+            retry: RetryConfig::default(),
         })
     }
 
-    /// Fetch all accounts from Lunchflow
-    pub fn get_accounts(&self) -> Result<SyncedAccounts> {
+    /// Override the retry policy for transient failures
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// GET a Lunchflow endpoint, retrying transient failures
+    ///
+    /// 429s and connect/timeout errors are retried with exponential backoff,
+    /// honoring `Retry-After` on 429s. Any other error status fails immediately.
+    fn get(&self, url: &str) -> Result<reqwest::blocking::Response> {
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .client
+                .get(url)
+                .header("x-api-key", &self.api_key)
+                .send();
+
+            // Some(wait) if the failure is transient; wait = server-requested delay
+            let transient = match &result {
+                Ok(response) if response.status().as_u16() == 429 => Some(retry_after(response)),
+                Err(e) if e.is_connect() || e.is_timeout() => Some(None),
+                _ => None,
+            };
+
+            match transient {
+                Some(wait) if attempt < self.retry.max_attempts => {
+                    let backoff = self.retry.base_delay * 2u32.pow(attempt - 1);
+                    std::thread::sleep(wait.unwrap_or(backoff));
+                    attempt += 1;
+                }
+                _ => {
+                    let response = result.map_err(|e| self.map_request_error(e))?;
+                    self.check_response_status(&response)?;
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// Check the API key still works with a single accounts request
+    ///
+    /// Unlike sync requests this is not retried, so a rate limit shows up
+    /// as such instead of stalling the check.
+    pub fn check_connection(&self) -> ConnectionHealth {
         let url = format!("{}/accounts", self.base_url);
 
-        let response = self
+        let response = match self
             .client
             .get(&url)
             .header("x-api-key", &self.api_key)
             .send()
-            .map_err(|e| self.map_request_error(e))?;
+        {
+            Ok(response) => response,
+            Err(e) => {
+                return ConnectionHealth::new(
+                    ConnectionStatus::Unreachable,
+                    self.map_request_error(e).to_string(),
+                )
+            }
+        };
+
+        let status = ConnectionStatus::from_http_status(response.status().as_u16());
+        match self.check_response_status(&response) {
+            Ok(()) => ConnectionHealth::new(status, "Connected to Lunchflow"),
+            Err(e) => ConnectionHealth::new(status, e.to_string()),
+        }
+    }
+
+    /// Fetch all accounts from Lunchflow
+    pub fn get_accounts(&self) -> Result<SyncedAccounts> {
+        let url = format!("{}/accounts", self.base_url);
 
-        self.check_response_status(&response)?;
+        let response = self.get(&url)?;
 
         // API returns { accounts: [...], total: N }
         let api_response: AccountsResponse = response
@@ -259,6 +393,7 @@ impl LunchflowClient {
                         balance,
                         snapshot_time: Utc::now().naive_utc(),
                         source: Some("sync".to_string()),
+                        note: None,
                         created_at: Utc::now(),
                         updated_at: Utc::now(),
                     });
@@ -298,14 +433,7 @@ impl LunchflowClient {
     fn fetch_account_balance(&self, account_id: &str) -> Result<(Decimal, String)> {
         let url = format!("{}/accounts/{}/balance", self.base_url, account_id);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("x-api-key", &self.api_key)
-            .send()
-            .map_err(|e| self.map_request_error(e))?;
-
-        self.check_response_status(&response)?;
+        let response = self.get(&url)?;
 
         let balance_response: BalanceResponse = response
             .json()
@@ -378,14 +506,7 @@ impl LunchflowClient {
             self.base_url, account_id, include_pending
         );
 
-        let response = self
-            .client
-            .get(&url)
-            .header("x-api-key", &self.api_key)
-            .send()
-            .map_err(|e| self.map_request_error(e))?;
-
-        self.check_response_status(&response)?;
+        let response = self.get(&url)?;
 
         // API returns { transactions: [...], total: N }
         let api_response: TransactionsResponse = response
@@ -421,6 +542,8 @@ impl LunchflowClient {
             updated_at: now,
             // Manual flag
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             // SimpleFIN fields (not applicable)
             sf_id: None,
             sf_name: None,
@@ -440,6 +563,17 @@ impl LunchflowClient {
             lf_provider: lf_account.provider.clone(),
             lf_currency: lf_account.currency.clone(),
             lf_status: lf_account.status.clone(),
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         }
     }
 
@@ -470,6 +604,7 @@ impl LunchflowClient {
             account_id: Uuid::nil(), // Will be set by sync service after mapping
             amount: lf_tx.amount,
             description, // Core field mapped from lf_description
+            notes: None,
             transaction_date: posted_date,
             posted_date,
             tags: vec![],
@@ -477,6 +612,9 @@ impl LunchflowClient {
             updated_at: now,
             deleted_at: None,
             parent_transaction_id: None,
+            // Foreign currency, when Lunchflow reports the purchase currency
+            original_amount: lf_tx.original_amount,
+            original_currency: lf_tx.original_currency.clone(),
             // CSV Import tracking (not applicable)
             csv_fingerprint: None,
             csv_batch_id: None,
@@ -501,6 +639,17 @@ impl LunchflowClient {
             lf_merchant: lf_tx.merchant.clone(),
             lf_description: lf_tx.description.clone(),
             lf_is_pending: Some(lf_tx.is_pending),
+            // Plaid fields (not applicable)
+            pl_id: None,
+            pl_account_id: None,
+            pl_amount: None,
+            pl_currency: None,
+            pl_date: None,
+            pl_authorized_date: None,
+            pl_name: None,
+            pl_merchant_name: None,
+            pl_pending: None,
+            pl_category: None,
         }
     }
 
@@ -564,6 +713,18 @@ impl DataAggregationProvider for LunchflowProvider {
         "lunchflow"
     }
 
+    fn account_external_id(&self, account: &Account) -> Option<String> {
+        account.lf_id.clone()
+    }
+
+    fn transaction_id_column(&self) -> Option<&'static str> {
+        Some("lf_id")
+    }
+
+    fn transaction_external_id(&self, tx: &Transaction) -> Option<String> {
+        tx.lf_id.clone()
+    }
+
     fn can_get_accounts(&self) -> bool {
         true
     }
@@ -641,7 +802,10 @@ impl DataAggregationProvider for LunchflowProvider {
 
         Ok(FetchTransactionsResult {
             transactions: synced.transactions,
+            modified: Vec::new(),
+            removed: Vec::new(),
             warnings: synced.warnings,
+            updated_settings: None,
         })
     }
 }
@@ -682,6 +846,26 @@ impl IntegrationProvider for LunchflowProvider {
 
         Ok(settings)
     }
+    fn check_connection(&self, settings: &JsonValue) -> DomainResult<ConnectionHealth> {
+        let api_key = settings
+            .get("apiKey")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                DomainError::Config("Lunchflow apiKey not found in settings".to_string())
+            })?;
+
+        // Check for custom base URL (for testing with mock server)
+        let base_url = settings.get("baseUrl").and_then(|v| v.as_str());
+
+        let client = if let Some(url) = base_url {
+            LunchflowClient::new_with_base_url(api_key, url)
+        } else {
+            LunchflowClient::new(api_key)
+        }
+        .map_err(|e| DomainError::Config(e.to_string()))?;
+
+        Ok(client.check_connection())
+    }
 }
 
 // =============================================================================
@@ -691,6 +875,7 @@ impl IntegrationProvider for LunchflowProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{http_response, mock_http_server};
 
     #[test]
     fn test_provider_name() {
@@ -746,6 +931,8 @@ mod tests {
             merchant: Some("Coffee Shop".to_string()),
             description: Some("Card payment".to_string()),
             is_pending: false,
+            original_amount: None,
+            original_currency: None,
         };
 
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
@@ -760,6 +947,21 @@ mod tests {
         assert_eq!(tx.lf_currency, Some("EUR".to_string()));
     }
 
+    #[test]
+    fn test_transaction_original_amount() {
+        let lf_tx: LunchflowTransaction = serde_json::from_str(
+            r#"{"id": "tx_789", "amount": -49.10, "currency": "USD", "date": "2025-01-15",
+                "originalAmount": "-45.00", "originalCurrency": "EUR"}"#,
+        )
+        .unwrap();
+
+        let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
+        let tx = client.map_transaction(&lf_tx);
+        assert_eq!(tx.amount, Decimal::new(-4910, 2));
+        assert_eq!(tx.original_amount, Some(Decimal::new(-4500, 2)));
+        assert_eq!(tx.original_currency, Some("EUR".to_string()));
+    }
+
     #[test]
     fn test_transaction_mapping_no_description() {
         let lf_tx = LunchflowTransaction {
@@ -771,6 +973,8 @@ mod tests {
             merchant: Some("Employer Inc".to_string()),
             description: None,
             is_pending: false,
+            original_amount: None,
+            original_currency: None,
         };
 
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
@@ -793,6 +997,8 @@ mod tests {
             merchant: Some("Grocery Store".to_string()),
             description: Some("".to_string()), // Empty string, not None
             is_pending: false,
+            original_amount: None,
+            original_currency: None,
         };
 
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
@@ -815,6 +1021,8 @@ mod tests {
             merchant: Some("Gas Station".to_string()),
             description: Some("   ".to_string()), // Whitespace only
             is_pending: false,
+            original_amount: None,
+            original_currency: None,
         };
 
         let client = LunchflowClient::new_with_base_url("test_key", "http://localhost").unwrap();
@@ -846,4 +1054,149 @@ mod tests {
             LunchflowClient::new_with_base_url("test_key", "http://localhost/api/").unwrap();
         assert_eq!(client.base_url, "http://localhost/api");
     }
+
+    /// Serve one canned response per connection; returns the base URL and a
+    /// handle yielding how many requests were served
+    fn mock_lunchflow(responses: Vec<String>) -> (String, std::thread::JoinHandle<usize>) {
+        let (base_url, server) = mock_http_server(responses);
+        let handle = std::thread::spawn(move || server.join().unwrap().len());
+        (base_url, handle)
+    }
+
+    fn fast_retry_client(base_url: &str) -> LunchflowClient {
+        LunchflowClient::new_with_base_url("test_key", base_url)
+            .unwrap()
+            .with_retry(RetryConfig {
+                max_attempts: 3,
+                base_delay: Duration::from_millis(10),
+            })
+    }
+
+    const BALANCE_BODY: &str = r#"{"balance": {"amount": 12.5, "currency": "EUR"}}"#;
+
+    #[test]
+    fn test_retry_after_rate_limit() {
+        let (base_url, server) = mock_lunchflow(vec![
+            http_response("429 Too Many Requests", "Retry-After: 0\r\n", "{}"),
+            http_response("200 OK", "", BALANCE_BODY),
+        ]);
+
+        let (balance, currency) = fast_retry_client(&base_url)
+            .fetch_account_balance("1")
+            .unwrap();
+
+        assert_eq!(balance, Decimal::new(125, 1));
+        assert_eq!(currency, "EUR");
+        assert_eq!(server.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_retry_get_accounts() {
+        let accounts_body = r#"{"accounts": [{"id": 1, "name": "Main", "institution_name": "Bank", "status": "ACTIVE"}], "total": 1}"#;
+        let (base_url, server) = mock_lunchflow(vec![
+            http_response("429 Too Many Requests", "", "{}"),
+            http_response("200 OK", "", accounts_body),
+            http_response("200 OK", "", BALANCE_BODY),
+        ]);
+
+        let synced = fast_retry_client(&base_url).get_accounts().unwrap();
+
+        assert_eq!(synced.accounts.len(), 1);
+        assert_eq!(synced.balance_snapshots.len(), 1);
+        assert!(synced.warnings.is_empty());
+        assert_eq!(server.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_retry_fetch_transactions() {
+        let txs_body = r#"{"transactions": [{"id": "t1", "amount": -5, "currency": "EUR", "date": "2024-01-15"}], "total": 1}"#;
+        let (base_url, server) = mock_lunchflow(vec![
+            http_response("429 Too Many Requests", "Retry-After: 0\r\n", "{}"),
+            http_response("200 OK", "", txs_body),
+        ]);
+
+        let txs = fast_retry_client(&base_url)
+            .fetch_account_transactions("1", true)
+            .unwrap();
+
+        assert_eq!(txs.len(), 1);
+        assert_eq!(server.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_no_retry_on_auth_failure() {
+        let (base_url, server) = mock_lunchflow(vec![http_response("401 Unauthorized", "", "{}")]);
+
+        let err = fast_retry_client(&base_url)
+            .fetch_account_balance("1")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("authentication failed"));
+        assert_eq!(server.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_max_attempts() {
+        let rate_limited = http_response("429 Too Many Requests", "Retry-After: 0\r\n", "{}");
+        let (base_url, server) = mock_lunchflow(vec![rate_limited; 3]);
+
+        let err = fast_retry_client(&base_url)
+            .fetch_account_balance("1")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("rate limit"));
+        assert_eq!(server.join().unwrap(), 3);
+    }
+    #[test]
+    fn test_check_connection_ok() {
+        let accounts_body = r#"{"accounts": [], "total": 0}"#;
+        let (base_url, server) = mock_lunchflow(vec![http_response("200 OK", "", accounts_body)]);
+
+        let health = LunchflowProvider::new()
+            .check_connection(&serde_json::json!({"apiKey": "test_key", "baseUrl": base_url}))
+            .unwrap();
+
+        assert_eq!(health.status, ConnectionStatus::Ok);
+        assert_eq!(server.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_check_connection_failures() {
+        for (response, expected) in [
+            ("401 Unauthorized", ConnectionStatus::AuthFailed),
+            ("402 Payment Required", ConnectionStatus::AuthFailed),
+            ("429 Too Many Requests", ConnectionStatus::RateLimited),
+            ("500 Internal Server Error", ConnectionStatus::Unreachable),
+        ] {
+            // A rate limit is reported, not retried
+            let (base_url, server) = mock_lunchflow(vec![http_response(response, "", "{}")]);
+
+            let health = fast_retry_client(&base_url).check_connection();
+
+            assert_eq!(health.status, expected, "{}", response);
+            assert!(health.message.starts_with("Lunchflow"));
+            assert_eq!(server.join().unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn test_check_connection_unreachable() {
+        // Bind then drop a listener so nothing is accepting on the port
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = fast_retry_client(&format!("http://127.0.0.1:{}", port));
+
+        let health = client.check_connection();
+
+        assert_eq!(health.status, ConnectionStatus::Unreachable);
+    }
+
+    #[test]
+    fn test_check_connection_requires_api_key() {
+        let result = LunchflowProvider::new().check_connection(&serde_json::json!({}));
+        assert!(result.is_err());
+    }
 }
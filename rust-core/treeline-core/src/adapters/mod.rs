@@ -4,10 +4,14 @@
 //! - DuckDB for the Repository port
 //! - SimpleFIN HTTP client for DataAggregationProvider
 //! - Lunchflow HTTP client for DataAggregationProvider (global banks)
+//! - Plaid HTTP client for DataAggregationProvider (US banks)
 //! - Demo data provider for testing
 //! - Local filesystem for BackupStorageProvider
+//! - Provider registry mapping integration names to providers
 
 pub mod demo;
 pub mod duckdb;
 pub mod lunchflow;
+pub mod plaid;
+pub mod registry;
 pub mod simplefin;
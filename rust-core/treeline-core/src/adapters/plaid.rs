@@ -0,0 +1,856 @@
+//! Plaid API client
+//!
+//! Handles communication with the Plaid API for account and transaction sync.
+//! Plaid covers many US institutions that SimpleFIN does not.
+//!
+//! API Documentation: https://plaid.com/docs/api/
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use reqwest::blocking::Client;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::domain::result::{Error as DomainError, Result as DomainResult};
+use crate::domain::{Account, BalanceSnapshot, Transaction};
+use crate::ports::{
+    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+};
+
+// =============================================================================
+// API Response Models (matching Plaid API spec)
+// =============================================================================
+
+/// Response from /accounts/get
+#[derive(Debug, Clone, Deserialize)]
+struct AccountsGetResponse {
+    accounts: Vec<PlaidAccount>,
+    item: PlaidItem,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PlaidItem {
+    item_id: String,
+}
+
+/// Plaid account from API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlaidAccount {
+    pub account_id: String,
+    pub balances: PlaidBalances,
+    #[serde(default)]
+    pub mask: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub official_name: Option<String>,
+    #[serde(rename = "type")]
+    pub account_type: String,
+    #[serde(default)]
+    pub subtype: Option<String>,
+}
+
+/// Balances embedded in each account
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlaidBalances {
+    #[serde(default)]
+    pub available: Option<Decimal>,
+    #[serde(default)]
+    pub current: Option<Decimal>,
+    #[serde(default)]
+    pub iso_currency_code: Option<String>,
+}
+
+/// Response from /transactions/sync
+#[derive(Debug, Clone, Deserialize)]
+struct TransactionsSyncResponse {
+    added: Vec<PlaidTransaction>,
+    modified: Vec<PlaidTransaction>,
+    removed: Vec<PlaidRemovedTransaction>,
+    next_cursor: String,
+    has_more: bool,
+}
+
+/// Entry of the `removed` list in /transactions/sync
+#[derive(Debug, Clone, Deserialize)]
+struct PlaidRemovedTransaction {
+    transaction_id: String,
+    #[serde(default)]
+    account_id: String,
+}
+
+/// Plaid transaction from API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlaidTransaction {
+    pub transaction_id: String,
+    pub account_id: String,
+    /// Plaid convention: positive = money out of the account
+    pub amount: Decimal,
+    #[serde(default)]
+    pub iso_currency_code: Option<String>,
+    pub date: String, // ISO date string YYYY-MM-DD
+    #[serde(default)]
+    pub authorized_date: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub merchant_name: Option<String>,
+    #[serde(default)]
+    pub pending: bool,
+    #[serde(default)]
+    pub personal_finance_category: Option<PlaidCategory>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlaidCategory {
+    pub primary: String,
+}
+
+/// Response from /item/public_token/exchange
+#[derive(Debug, Clone, Deserialize)]
+struct PublicTokenExchangeResponse {
+    access_token: String,
+    item_id: String,
+}
+
+/// Error body Plaid returns for non-200 responses
+#[derive(Debug, Clone, Default, Deserialize)]
+struct PlaidErrorResponse {
+    #[serde(default)]
+    error_code: String,
+    #[serde(default)]
+    error_message: String,
+}
+
+/// Result of syncing accounts from Plaid
+#[derive(Debug)]
+pub struct SyncedAccounts {
+    pub accounts: Vec<Account>,
+    pub balance_snapshots: Vec<BalanceSnapshot>,
+    pub warnings: Vec<String>,
+}
+
+/// Result of syncing transactions from Plaid
+#[derive(Debug)]
+pub struct SyncedTransactions {
+    /// Tuples of (plaid_account_id, transaction)
+    pub transactions: Vec<(String, Transaction)>,
+    /// Previously returned transactions that Plaid has since changed
+    pub modified: Vec<(String, Transaction)>,
+    /// Tuples of (plaid_account_id, plaid_transaction_id) deleted on Plaid's side
+    pub removed: Vec<(String, String)>,
+    pub warnings: Vec<String>,
+    /// Cursor to pass on the next call to resume where this one ended
+    pub next_cursor: String,
+}
+
+// =============================================================================
+// Plaid HTTP Client
+// =============================================================================
+
+/// Default production API URL
+const PLAID_PRODUCTION_URL: &str = "https://production.plaid.com";
+
+/// Environment variable to override the Plaid API base URL.
+/// Set this to https://sandbox.plaid.com for sandbox testing.
+pub const PLAID_BASE_URL_ENV: &str = "PLAID_BASE_URL";
+
+/// Maximum page size allowed by /transactions/sync
+const SYNC_PAGE_SIZE: u32 = 500;
+
+/// Get the Plaid base URL, checking environment variable first
+pub fn get_base_url() -> String {
+    std::env::var(PLAID_BASE_URL_ENV).unwrap_or_else(|_| PLAID_PRODUCTION_URL.to_string())
+}
+
+/// Plaid API client
+#[derive(Debug)]
+pub struct PlaidClient {
+    client: Client,
+    client_id: String,
+    secret: String,
+    base_url: String,
+}
+
+impl PlaidClient {
+    /// Create a new Plaid client with the given API credentials.
+    ///
+    /// Uses the `PLAID_BASE_URL` environment variable if set,
+    /// otherwise defaults to the production API.
+    pub fn new(client_id: &str, secret: &str) -> Result<Self> {
+        Self::new_with_base_url(client_id, secret, &get_base_url())
+    }
+
+    /// Create a new Plaid client with a custom base URL.
+    ///
+    /// Prefer using `new()` with the `PLAID_BASE_URL` env var for testing.
+    pub fn new_with_base_url(client_id: &str, secret: &str, base_url: &str) -> Result<Self> {
+        if client_id.is_empty() || secret.is_empty() {
+            anyhow::bail!("Plaid client ID and secret cannot be empty");
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            client_id: client_id.to_string(),
+            secret: secret.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Exchange a Link public token for a long-lived access token.
+    ///
+    /// Returns (access_token, item_id).
+    pub fn exchange_public_token(&self, public_token: &str) -> Result<(String, String)> {
+        let response: PublicTokenExchangeResponse = self.post(
+            "/item/public_token/exchange",
+            serde_json::json!({ "public_token": public_token }),
+        )?;
+        Ok((response.access_token, response.item_id))
+    }
+
+    /// Fetch all accounts (with balances) for an item
+    pub fn get_accounts(&self, access_token: &str) -> Result<SyncedAccounts> {
+        let response: AccountsGetResponse = self.post(
+            "/accounts/get",
+            serde_json::json!({ "access_token": access_token }),
+        )?;
+
+        let mut accounts = Vec::new();
+        let mut balance_snapshots = Vec::new();
+
+        for pl_account in &response.accounts {
+            let account = self.map_account(pl_account, &response.item.item_id);
+
+            // Balances come back in the same response - no extra request needed
+            if let Some(balance) = account.balance {
+                let now = Utc::now();
+                balance_snapshots.push(BalanceSnapshot {
+                    id: Uuid::new_v4(),
+                    account_id: account.id,
+                    balance,
+                    snapshot_time: now.naive_utc(),
+                    source: Some("sync".to_string()),
+                    note: None,
+                    created_at: now,
+                    updated_at: now,
+                });
+            }
+            accounts.push(account);
+        }
+
+        Ok(SyncedAccounts {
+            accounts,
+            balance_snapshots,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Fetch transactions using the /transactions/sync cursor pattern.
+    ///
+    /// Pages until `has_more` is false. Pass the returned `next_cursor` on the
+    /// next call to only receive changes since this one. `None` starts from the
+    /// beginning of the item's history.
+    ///
+    /// The cursor tracks the whole item, so every page is collected before
+    /// `account_ids` narrows the result; added, modified and removed entries
+    /// come back separately.
+    pub fn get_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        account_ids: Option<&[String]>,
+    ) -> Result<SyncedTransactions> {
+        let mut added = Vec::new();
+        let mut modified = Vec::new();
+        let mut removed = Vec::new();
+        let mut cursor = cursor.map(|c| c.to_string());
+
+        loop {
+            let mut body = serde_json::json!({
+                "access_token": access_token,
+                "count": SYNC_PAGE_SIZE,
+            });
+            if let Some(c) = &cursor {
+                body["cursor"] = serde_json::json!(c);
+            }
+
+            let page: TransactionsSyncResponse = self.post("/transactions/sync", body)?;
+            added.extend(page.added);
+            modified.extend(page.modified);
+            removed.extend(page.removed);
+
+            cursor = Some(page.next_cursor);
+            if !page.has_more {
+                break;
+            }
+        }
+
+        let wanted = |account_id: &str| match account_ids {
+            Some(ids) if !ids.is_empty() => ids.iter().any(|id| id == account_id),
+            _ => true,
+        };
+        let map = |txs: Vec<PlaidTransaction>| -> Vec<(String, Transaction)> {
+            txs.iter()
+                .filter(|pl_tx| wanted(&pl_tx.account_id))
+                .map(|pl_tx| (pl_tx.account_id.clone(), self.map_transaction(pl_tx)))
+                .collect()
+        };
+
+        Ok(SyncedTransactions {
+            transactions: map(added),
+            modified: map(modified),
+            removed: removed
+                .into_iter()
+                .filter(|r| r.account_id.is_empty() || wanted(&r.account_id))
+                .map(|r| (r.account_id, r.transaction_id))
+                .collect(),
+            warnings: Vec::new(),
+            next_cursor: cursor.unwrap_or_default(),
+        })
+    }
+
+    /// POST a request to Plaid, adding credentials to the JSON body
+    fn post<T: DeserializeOwned>(&self, path: &str, mut body: JsonValue) -> Result<T> {
+        body["client_id"] = serde_json::json!(self.client_id);
+        body["secret"] = serde_json::json!(self.secret);
+
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        let status = response.status().as_u16();
+        if status != 200 {
+            // Plaid puts the useful detail (error_code) in the JSON body
+            let error: PlaidErrorResponse = response.json().unwrap_or_default();
+            return Err(self.map_status_error(status, &error));
+        }
+
+        response
+            .json()
+            .with_context(|| format!("Failed to parse Plaid {} response", path))
+    }
+
+    /// Map Plaid account to domain Account
+    fn map_account(&self, pl_account: &PlaidAccount, item_id: &str) -> Account {
+        // Plaid uses the same type nomenclature we do (depository, credit, loan, ...)
+        let classification = Account::compute_classification(Some(&pl_account.account_type));
+
+        // Plaid reports amounts owed as positive; store liabilities as negative
+        // balances to match SimpleFIN and the rest of the app
+        let balance = pl_account.balances.current.map(|b| {
+            if classification == "liability" {
+                -b.abs()
+            } else {
+                b
+            }
+        });
+
+        let now = Utc::now();
+        Account {
+            id: Uuid::new_v4(),
+            name: pl_account.name.clone(),
+            nickname: None,
+            currency: pl_account
+                .balances
+                .iso_currency_code
+                .clone()
+                .unwrap_or_else(|| "USD".to_string()),
+            account_type: Some(pl_account.account_type.clone()),
+            classification: Some(classification),
+            balance,
+            institution_name: None,
+            institution_url: None,
+            institution_domain: None,
+            created_at: now,
+            updated_at: now,
+            // Manual flag
+            is_manual: false,
+            is_archived: false,
+            account_group: None,
+            // SimpleFIN fields (not applicable)
+            sf_id: None,
+            sf_name: None,
+            sf_currency: None,
+            sf_balance: None,
+            sf_available_balance: None,
+            sf_balance_date: None,
+            sf_org_name: None,
+            sf_org_url: None,
+            sf_org_domain: None,
+            sf_extra: None,
+            // Lunchflow fields (not applicable)
+            lf_id: None,
+            lf_name: None,
+            lf_institution_name: None,
+            lf_institution_logo: None,
+            lf_provider: None,
+            lf_currency: None,
+            lf_status: None,
+            // Plaid: Store raw fields from API
+            pl_id: Some(pl_account.account_id.clone()),
+            pl_item_id: Some(item_id.to_string()),
+            pl_name: Some(pl_account.name.clone()),
+            pl_official_name: pl_account.official_name.clone(),
+            pl_mask: pl_account.mask.clone(),
+            pl_type: Some(pl_account.account_type.clone()),
+            pl_subtype: pl_account.subtype.clone(),
+            pl_balance_current: pl_account.balances.current,
+            pl_balance_available: pl_account.balances.available,
+            pl_currency: pl_account.balances.iso_currency_code.clone(),
+        }
+    }
+
+    /// Map Plaid transaction to domain Transaction
+    fn map_transaction(&self, pl_tx: &PlaidTransaction) -> Transaction {
+        let posted_date = NaiveDate::parse_from_str(&pl_tx.date, "%Y-%m-%d")
+            .unwrap_or_else(|_| Utc::now().naive_utc().date());
+        let authorized_date = pl_tx
+            .authorized_date
+            .as_ref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+
+        // Map pl_name to core description (full transaction description)
+        // Falls back to pl_merchant_name if name is empty
+        let description = Some(pl_tx.name.clone())
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| {
+                pl_tx
+                    .merchant_name
+                    .as_ref()
+                    .filter(|m| !m.trim().is_empty())
+                    .cloned()
+            });
+
+        let now = Utc::now();
+        Transaction {
+            id: Uuid::new_v4(),
+            account_id: Uuid::nil(), // Will be set by sync service after mapping
+            // Plaid amounts are positive for money out; we use negative for expenses
+            amount: -pl_tx.amount,
+            description,
+            notes: None,
+            transaction_date: authorized_date.unwrap_or(posted_date),
+            posted_date,
+            tags: vec![],
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            parent_transaction_id: None,
+            // Foreign currency (not reported by Plaid)
+            original_amount: None,
+            original_currency: None,
+            // CSV Import tracking (not applicable)
+            csv_fingerprint: None,
+            csv_batch_id: None,
+            // Manual flag
+            is_manual: false,
+            // Auto-tag tracking (starts false, set true when rules apply)
+            tags_auto_applied: false,
+            // SimpleFIN fields (not applicable)
+            sf_id: None,
+            sf_posted: None,
+            sf_amount: None,
+            sf_description: None,
+            sf_transacted_at: None,
+            sf_pending: None,
+            sf_extra: None,
+            // Lunchflow fields (not applicable)
+            lf_id: None,
+            lf_account_id: None,
+            lf_amount: None,
+            lf_currency: None,
+            lf_date: None,
+            lf_merchant: None,
+            lf_description: None,
+            lf_is_pending: None,
+            // Plaid: Store raw fields from API
+            pl_id: Some(pl_tx.transaction_id.clone()),
+            pl_account_id: Some(pl_tx.account_id.clone()),
+            pl_amount: Some(pl_tx.amount),
+            pl_currency: pl_tx.iso_currency_code.clone(),
+            pl_date: Some(posted_date),
+            pl_authorized_date: authorized_date,
+            pl_name: Some(pl_tx.name.clone()),
+            pl_merchant_name: pl_tx.merchant_name.clone(),
+            pl_pending: Some(pl_tx.pending),
+            pl_category: pl_tx
+                .personal_finance_category
+                .as_ref()
+                .map(|c| c.primary.clone()),
+        }
+    }
+
+    /// Map request errors to user-friendly messages
+    fn map_request_error(&self, error: reqwest::Error) -> anyhow::Error {
+        if error.is_timeout() {
+            anyhow::anyhow!("Connection timed out after 120 seconds")
+        } else if error.is_connect() {
+            anyhow::anyhow!("Unable to connect to Plaid servers")
+        } else {
+            anyhow::anyhow!("Plaid request failed: {}", error)
+        }
+    }
+
+    /// Map a non-200 response to an appropriate error
+    fn map_status_error(&self, status: u16, error: &PlaidErrorResponse) -> anyhow::Error {
+        match (status, error.error_code.as_str()) {
+            (_, "ITEM_LOGIN_REQUIRED") => anyhow::anyhow!(
+                "Plaid login expired. Please re-link this institution through Plaid Link."
+            ),
+            (_, "INVALID_API_KEYS") | (401, _) => anyhow::anyhow!(
+                "Plaid authentication failed. Your client ID or secret may be invalid."
+            ),
+            (_, "INVALID_ACCESS_TOKEN") => anyhow::anyhow!(
+                "Plaid access token is invalid or revoked. Please set up the integration again."
+            ),
+            (429, _) => {
+                anyhow::anyhow!("Plaid rate limit exceeded. Please wait a moment and try again.")
+            }
+            (400, code) => anyhow::anyhow!(
+                "Plaid rejected the request ({}): {}",
+                code,
+                error.error_message
+            ),
+            (status, _) => anyhow::anyhow!("Plaid API error: HTTP {}", status),
+        }
+    }
+}
+
+// =============================================================================
+// PlaidProvider - implements DataAggregationProvider trait
+// =============================================================================
+
+/// Plaid data provider
+///
+/// Implements DataAggregationProvider and IntegrationProvider traits
+/// for syncing financial data via Plaid.
+///
+/// Settings: `clientId`, `secret`, `accessToken`, `itemId`, optional `cursor`
+/// (last /transactions/sync position) and `baseUrl`.
+pub struct PlaidProvider;
+
+impl PlaidProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Build a client and read the access token from stored settings
+    fn client_from_settings(settings: &JsonValue) -> DomainResult<(PlaidClient, String)> {
+        let get = |key: &str| {
+            settings
+                .get(key)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| DomainError::Config(format!("Plaid {} not found in settings", key)))
+        };
+        let client_id = get("clientId")?;
+        let secret = get("secret")?;
+        let access_token = get("accessToken")?;
+
+        // Check for custom base URL (for testing with mock server)
+        let base_url = settings.get("baseUrl").and_then(|v| v.as_str());
+
+        let client = if let Some(url) = base_url {
+            PlaidClient::new_with_base_url(client_id, secret, url)
+        } else {
+            PlaidClient::new(client_id, secret)
+        }
+        .map_err(|e| DomainError::Sync(e.to_string()))?;
+
+        Ok((client, access_token.to_string()))
+    }
+}
+
+impl Default for PlaidProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataAggregationProvider for PlaidProvider {
+    fn name(&self) -> &str {
+        "plaid"
+    }
+
+    fn account_external_id(&self, account: &Account) -> Option<String> {
+        account.pl_id.clone()
+    }
+
+    fn transaction_id_column(&self) -> Option<&'static str> {
+        Some("pl_id")
+    }
+
+    fn transaction_external_id(&self, tx: &Transaction) -> Option<String> {
+        tx.pl_id.clone()
+    }
+
+    fn transaction_category(&self, tx: &Transaction) -> Option<String> {
+        tx.pl_category.clone()
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    fn get_accounts(&self, settings: &JsonValue) -> DomainResult<FetchAccountsResult> {
+        let (client, access_token) = Self::client_from_settings(settings)?;
+
+        let synced = client
+            .get_accounts(&access_token)
+            .map_err(|e| DomainError::Sync(e.to_string()))?;
+
+        Ok(FetchAccountsResult {
+            accounts: synced.accounts,
+            balance_snapshots: synced.balance_snapshots,
+            warnings: synced.warnings,
+        })
+    }
+
+    /// Fetch transactions since the stored cursor.
+    ///
+    /// Plaid's sync endpoint is cursor-based, so the date range is ignored.
+    /// The new cursor is returned in `updated_settings` for the caller to persist.
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        account_ids: &[String],
+        settings: &JsonValue,
+    ) -> DomainResult<FetchTransactionsResult> {
+        let (client, access_token) = Self::client_from_settings(settings)?;
+        let cursor = settings.get("cursor").and_then(|v| v.as_str());
+
+        let ids = if account_ids.is_empty() {
+            None
+        } else {
+            Some(account_ids)
+        };
+
+        let synced = client
+            .get_transactions(&access_token, cursor, ids)
+            .map_err(|e| DomainError::Sync(e.to_string()))?;
+
+        let mut updated_settings = settings.clone();
+        updated_settings["cursor"] = serde_json::json!(synced.next_cursor);
+
+        Ok(FetchTransactionsResult {
+            transactions: synced.transactions,
+            modified: synced.modified,
+            removed: synced.removed,
+            warnings: synced.warnings,
+            updated_settings: Some(updated_settings),
+        })
+    }
+}
+
+impl IntegrationProvider for PlaidProvider {
+    fn setup(&self, options: &JsonValue) -> DomainResult<JsonValue> {
+        let get = |key: &str| {
+            options
+                .get(key)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| DomainError::Config(format!("Plaid {} required for setup", key)))
+        };
+        let client_id = get("clientId")?;
+        let secret = get("secret")?;
+        let public_token = get("publicToken")?;
+
+        // Check for custom base URL (for testing with mock server)
+        let base_url = options.get("baseUrl").and_then(|v| v.as_str());
+
+        let client = if let Some(url) = base_url {
+            PlaidClient::new_with_base_url(client_id, secret, url)
+        } else {
+            PlaidClient::new(client_id, secret)
+        }
+        .map_err(|e| DomainError::Sync(e.to_string()))?;
+
+        // Exchange the short-lived public token from Plaid Link for an access token
+        let (access_token, item_id) = client.exchange_public_token(public_token).map_err(|e| {
+            DomainError::Sync(format!("Failed to exchange Plaid public token: {}", e))
+        })?;
+
+        // Build settings to store
+        let mut settings = serde_json::json!({
+            "clientId": client_id,
+            "secret": secret,
+            "accessToken": access_token,
+            "itemId": item_id,
+        });
+
+        // Include base URL if custom (for testing)
+        if let Some(url) = base_url {
+            settings["baseUrl"] = serde_json::json!(url);
+        }
+
+        Ok(settings)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client() -> PlaidClient {
+        PlaidClient::new_with_base_url("client", "secret", "http://localhost").unwrap()
+    }
+
+    #[test]
+    fn test_provider_name() {
+        let provider = PlaidProvider::new();
+        assert_eq!(provider.name(), "plaid");
+    }
+
+    #[test]
+    fn test_reject_empty_credentials() {
+        let result = PlaidClient::new_with_base_url("", "secret", "http://localhost");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_account_mapping() {
+        let pl_account: PlaidAccount = serde_json::from_value(serde_json::json!({
+            "account_id": "acc_1",
+            "balances": {"available": 100.5, "current": 110.25, "iso_currency_code": "USD"},
+            "mask": "0000",
+            "name": "Plaid Checking",
+            "official_name": "Plaid Gold Standard 0% Interest Checking",
+            "type": "depository",
+            "subtype": "checking"
+        }))
+        .unwrap();
+
+        let account = test_client().map_account(&pl_account, "item_1");
+
+        assert_eq!(account.name, "Plaid Checking");
+        assert_eq!(account.account_type, Some("depository".to_string()));
+        assert_eq!(account.classification, Some("asset".to_string()));
+        assert_eq!(account.balance, Some(Decimal::new(11025, 2)));
+        assert_eq!(account.pl_id, Some("acc_1".to_string()));
+        assert_eq!(account.pl_item_id, Some("item_1".to_string()));
+        assert_eq!(account.pl_mask, Some("0000".to_string()));
+    }
+
+    #[test]
+    fn test_credit_account_balance_is_negative() {
+        let pl_account: PlaidAccount = serde_json::from_value(serde_json::json!({
+            "account_id": "acc_2",
+            "balances": {"available": null, "current": 410, "iso_currency_code": "USD"},
+            "name": "Plaid Credit Card",
+            "type": "credit",
+            "subtype": "credit card"
+        }))
+        .unwrap();
+
+        let account = test_client().map_account(&pl_account, "item_1");
+
+        assert_eq!(account.classification, Some("liability".to_string()));
+        assert_eq!(account.balance, Some(Decimal::new(-410, 0)));
+        // Raw field keeps Plaid's sign
+        assert_eq!(account.pl_balance_current, Some(Decimal::new(410, 0)));
+    }
+
+    #[test]
+    fn test_transaction_mapping() {
+        let pl_tx: PlaidTransaction = serde_json::from_value(serde_json::json!({
+            "transaction_id": "tx_1",
+            "account_id": "acc_1",
+            "amount": 12.5,
+            "iso_currency_code": "USD",
+            "date": "2025-01-16",
+            "authorized_date": "2025-01-15",
+            "name": "STARBUCKS STORE 1234",
+            "merchant_name": "Starbucks",
+            "pending": false,
+            "personal_finance_category": {"primary": "FOOD_AND_DRINK", "detailed": "FOOD_AND_DRINK_COFFEE"}
+        }))
+        .unwrap();
+
+        let tx = test_client().map_transaction(&pl_tx);
+
+        // Money out is positive in Plaid, negative in treeline
+        assert_eq!(tx.amount, Decimal::new(-125, 1));
+        assert_eq!(tx.pl_amount, Some(Decimal::new(125, 1)));
+        assert_eq!(tx.description, Some("STARBUCKS STORE 1234".to_string()));
+        assert_eq!(
+            tx.transaction_date,
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+        );
+        assert_eq!(
+            tx.posted_date,
+            NaiveDate::from_ymd_opt(2025, 1, 16).unwrap()
+        );
+        assert_eq!(tx.pl_id, Some("tx_1".to_string()));
+        assert_eq!(tx.pl_merchant_name, Some("Starbucks".to_string()));
+        assert_eq!(tx.pl_category, Some("FOOD_AND_DRINK".to_string()));
+    }
+
+    #[test]
+    fn test_status_error_mapping() {
+        let client = test_client();
+
+        let login = PlaidErrorResponse {
+            error_code: "ITEM_LOGIN_REQUIRED".to_string(),
+            error_message: "the login details of this item have changed".to_string(),
+        };
+        assert!(client
+            .map_status_error(400, &login)
+            .to_string()
+            .contains("re-link"));
+
+        let keys = PlaidErrorResponse {
+            error_code: "INVALID_API_KEYS".to_string(),
+            ..Default::default()
+        };
+        assert!(client
+            .map_status_error(400, &keys)
+            .to_string()
+            .contains("authentication failed"));
+
+        assert!(client
+            .map_status_error(429, &PlaidErrorResponse::default())
+            .to_string()
+            .contains("rate limit"));
+    }
+
+    #[test]
+    fn test_provider_setup_missing_public_token() {
+        let provider = PlaidProvider::new();
+        let result = provider.setup(&serde_json::json!({
+            "clientId": "client",
+            "secret": "secret"
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_default_base_url() {
+        // When PLAID_BASE_URL env var is not set, should use production
+        std::env::remove_var("PLAID_BASE_URL");
+        assert_eq!(get_base_url(), "https://production.plaid.com");
+    }
+}
@@ -0,0 +1,114 @@
+//! Provider registry - maps integration names to provider implementations
+//!
+//! Adding a new data provider means registering it here; SyncService looks
+//! providers up by the integration's stored name and never matches on names.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::adapters::demo::DemoDataProvider;
+use crate::adapters::lunchflow::LunchflowProvider;
+use crate::adapters::plaid::PlaidProvider;
+use crate::adapters::simplefin::SimpleFINProvider;
+use crate::ports::{DataAggregationProvider, IntegrationProvider};
+
+/// Builds a fresh data provider for one sync
+pub type ProviderFactory = Box<dyn Fn() -> Box<dyn DataAggregationProvider> + Send + Sync>;
+
+/// Registry of data providers and integration setup handlers, keyed by integration name
+pub struct ProviderRegistry {
+    factories: HashMap<String, ProviderFactory>,
+    setup_providers: HashMap<String, Arc<dyn IntegrationProvider>>,
+}
+
+impl ProviderRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            factories: HashMap::new(),
+            setup_providers: HashMap::new(),
+        }
+    }
+
+    /// Create a registry with the built-in providers (demo, SimpleFIN, Lunchflow, Plaid)
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+
+        registry.register("demo", || Box::new(DemoDataProvider::new()));
+        registry.register_setup("demo", Arc::new(DemoDataProvider::new()));
+
+        registry.register("simplefin", || Box::new(SimpleFINProvider::new()));
+        registry.register_setup("simplefin", Arc::new(SimpleFINProvider::new()));
+
+        // Global bank connections
+        registry.register("lunchflow", || Box::new(LunchflowProvider::new()));
+        registry.register_setup("lunchflow", Arc::new(LunchflowProvider::new()));
+
+        // US institutions not covered by SimpleFIN
+        registry.register("plaid", || Box::new(PlaidProvider::new()));
+        registry.register_setup("plaid", Arc::new(PlaidProvider::new()));
+
+        registry
+    }
+
+    /// Register a data provider factory, replacing any existing one with this name
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn() -> Box<dyn DataAggregationProvider> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// Register the handler that sets up a new integration with this name
+    pub fn register_setup(&mut self, name: &str, provider: Arc<dyn IntegrationProvider>) {
+        self.setup_providers.insert(name.to_string(), provider);
+    }
+
+    /// Build the data provider for an integration, if one is registered
+    pub fn create(&self, name: &str) -> Option<Box<dyn DataAggregationProvider>> {
+        self.factories.get(name).map(|factory| factory())
+    }
+
+    /// Setup handler for an integration, if one is registered
+    pub fn setup_provider(&self, name: &str) -> Option<Arc<dyn IntegrationProvider>> {
+        self.setup_providers.get(name).cloned()
+    }
+
+    /// Names of all registered data providers, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.factories.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_providers_registered() {
+        let registry = ProviderRegistry::with_builtin();
+        assert_eq!(
+            registry.names(),
+            vec!["demo", "lunchflow", "plaid", "simplefin"]
+        );
+        for name in registry.names() {
+            assert_eq!(registry.create(&name).unwrap().name(), name);
+            assert!(registry.setup_provider(&name).is_some());
+        }
+    }
+
+    #[test]
+    fn test_unknown_provider() {
+        let registry = ProviderRegistry::new();
+        assert!(registry.create("simplefin").is_none());
+        assert!(registry.setup_provider("simplefin").is_none());
+    }
+}
@@ -3,7 +3,7 @@
 //! Compatible with the Python CLI / Desktop App settings.json format:
 //! ```json
 //! {
-//!   "app": { "demoMode": false, ... },
+//!   "app": { "demoMode": false, "autoBackupOnSync": false, "dateBasis": "transaction", ... },
 //!   "plugins": { ... },
 //!   "importProfiles": { "profiles": { ... }, "accountMappings": { ... } }
 //! }
@@ -11,10 +11,15 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::Result;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::adapters::duckdb::QueryLimits;
+use crate::domain::{ConflictPolicy, DateBasis, FiscalCalendar};
+
 /// Raw settings.json structure (matching Python/App format)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -29,15 +34,74 @@ struct SettingsFile {
     disabled_plugins: Vec<String>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AppSettings {
     #[serde(default)]
     demo_mode: bool,
+    #[serde(default)]
+    auto_backup_on_sync: bool,
+    #[serde(default)]
+    category_mapping: bool,
+    #[serde(default)]
+    conflict_policy: ConflictPolicy,
+    #[serde(default)]
+    date_basis: DateBasis,
+    #[serde(default = "default_fiscal_start")]
+    fiscal_month_start_day: u32,
+    #[serde(default = "default_fiscal_start")]
+    fiscal_year_start_month: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sync_webhook_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sync_webhook_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    query_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    query_max_rows: Option<usize>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    exchange_rates: HashMap<String, Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_import_account: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    named_queries: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    locale: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sync_order: Vec<String>,
     #[serde(flatten)]
     other: HashMap<String, serde_json::Value>,
 }
 
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            demo_mode: false,
+            auto_backup_on_sync: false,
+            category_mapping: false,
+            conflict_policy: ConflictPolicy::default(),
+            date_basis: DateBasis::default(),
+            fiscal_month_start_day: default_fiscal_start(),
+            fiscal_year_start_month: default_fiscal_start(),
+            sync_webhook_url: None,
+            sync_webhook_secret: None,
+            query_timeout_secs: None,
+            query_max_rows: None,
+            exchange_rates: HashMap::new(),
+            default_import_account: None,
+            named_queries: HashMap::new(),
+            locale: None,
+            sync_order: Vec::new(),
+            other: HashMap::new(),
+        }
+    }
+}
+
+/// Fiscal months and years start like calendar ones unless configured
+fn default_fiscal_start() -> u32 {
+    1
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ImportProfilesContainer {
@@ -51,6 +115,36 @@ struct ImportProfilesContainer {
 #[derive(Debug, Clone)]
 pub struct Config {
     pub demo_mode: bool,
+    /// Create a backup before each (non dry-run) sync
+    pub auto_backup_on_sync: bool,
+    /// Tag new synced transactions from their provider category via `sys_category_map`
+    pub category_mapping: bool,
+    /// How sync treats provider changes to transactions that are already stored
+    pub conflict_policy: ConflictPolicy,
+    /// Which transaction date reports group by
+    pub date_basis: DateBasis,
+    /// Day of the month fiscal months start on (1 = calendar months)
+    pub fiscal_month_start_day: u32,
+    /// Fiscal month that starts the fiscal year (1 = January)
+    pub fiscal_year_start_month: u32,
+    /// URL that receives a JSON summary after each successful sync
+    pub sync_webhook_url: Option<String>,
+    /// Key for the HMAC-SHA256 signature sent with the webhook
+    pub sync_webhook_secret: Option<String>,
+    /// Cancel user SQL queries running longer than this (None = no limit)
+    pub query_timeout_secs: Option<u64>,
+    /// Most rows a user SELECT returns when it has no LIMIT (None = no limit)
+    pub query_max_rows: Option<usize>,
+    /// Value of one unit of each currency in a common base, e.g. USD = 1, EUR = 1.08
+    pub exchange_rates: HashMap<String, Decimal>,
+    /// Account (ID, name or nickname) CSV imports use when none is given
+    pub default_import_account: Option<String>,
+    /// Saved read-only queries by name, run with `tl query --named`
+    pub named_queries: HashMap<String, String>,
+    /// Locale for displaying amounts and dates, e.g. "de-DE" (None = US style)
+    pub locale: Option<String>,
+    /// Integration names in the order `tl sync` runs them; unlisted ones run last
+    pub sync_order: Vec<String>,
     pub import_profiles: HashMap<String, ImportProfile>,
     // Keep the raw settings for preservation when saving
     _raw_settings: SettingsFile,
@@ -60,6 +154,21 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             demo_mode: false,
+            auto_backup_on_sync: false,
+            category_mapping: false,
+            conflict_policy: ConflictPolicy::default(),
+            date_basis: DateBasis::default(),
+            fiscal_month_start_day: 1,
+            fiscal_year_start_month: 1,
+            sync_webhook_url: None,
+            sync_webhook_secret: None,
+            query_timeout_secs: None,
+            query_max_rows: None,
+            exchange_rates: HashMap::new(),
+            default_import_account: None,
+            named_queries: HashMap::new(),
+            locale: None,
+            sync_order: Vec::new(),
             import_profiles: HashMap::new(),
             _raw_settings: SettingsFile::default(),
         }
@@ -91,6 +200,21 @@ impl Config {
 
         Ok(Self {
             demo_mode,
+            auto_backup_on_sync: raw.app.auto_backup_on_sync,
+            category_mapping: raw.app.category_mapping,
+            conflict_policy: raw.app.conflict_policy,
+            date_basis: raw.app.date_basis,
+            fiscal_month_start_day: raw.app.fiscal_month_start_day,
+            fiscal_year_start_month: raw.app.fiscal_year_start_month,
+            sync_webhook_url: raw.app.sync_webhook_url.clone(),
+            sync_webhook_secret: raw.app.sync_webhook_secret.clone(),
+            query_timeout_secs: raw.app.query_timeout_secs,
+            query_max_rows: raw.app.query_max_rows,
+            exchange_rates: raw.app.exchange_rates.clone(),
+            default_import_account: raw.app.default_import_account.clone(),
+            named_queries: raw.app.named_queries.clone(),
+            locale: raw.app.locale.clone(),
+            sync_order: raw.app.sync_order.clone(),
             import_profiles: raw.import_profiles.profiles.clone(),
             _raw_settings: raw,
         })
@@ -111,6 +235,21 @@ impl Config {
 
         // Update only the fields we manage
         settings.app.demo_mode = self.demo_mode;
+        settings.app.auto_backup_on_sync = self.auto_backup_on_sync;
+        settings.app.category_mapping = self.category_mapping;
+        settings.app.conflict_policy = self.conflict_policy;
+        settings.app.date_basis = self.date_basis;
+        settings.app.fiscal_month_start_day = self.fiscal_month_start_day;
+        settings.app.fiscal_year_start_month = self.fiscal_year_start_month;
+        settings.app.sync_webhook_url = self.sync_webhook_url.clone();
+        settings.app.sync_webhook_secret = self.sync_webhook_secret.clone();
+        settings.app.query_timeout_secs = self.query_timeout_secs;
+        settings.app.query_max_rows = self.query_max_rows;
+        settings.app.exchange_rates = self.exchange_rates.clone();
+        settings.app.default_import_account = self.default_import_account.clone();
+        settings.app.named_queries = self.named_queries.clone();
+        settings.app.locale = self.locale.clone();
+        settings.app.sync_order = self.sync_order.clone();
         settings.import_profiles.profiles = self.import_profiles.clone();
 
         let content = serde_json::to_string_pretty(&settings)?;
@@ -118,6 +257,41 @@ impl Config {
         Ok(())
     }
 
+    /// Read settings.json as raw JSON, including fields the CLI doesn't manage
+    ///
+    /// Returns an empty object if there is no settings file yet.
+    pub fn load_raw(treeline_dir: &Path) -> Result<serde_json::Value> {
+        let settings_path = treeline_dir.join("settings.json");
+        if !settings_path.exists() {
+            return Ok(serde_json::json!({}));
+        }
+        let content = std::fs::read_to_string(&settings_path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write raw JSON to settings.json
+    pub fn save_raw(treeline_dir: &Path, settings: &serde_json::Value) -> Result<()> {
+        let content = serde_json::to_string_pretty(settings)?;
+        std::fs::write(treeline_dir.join("settings.json"), content)?;
+        Ok(())
+    }
+
+    /// Fiscal calendar that reports group transactions by
+    ///
+    /// Out-of-range settings fall back to calendar months and years.
+    pub fn fiscal_calendar(&self) -> FiscalCalendar {
+        FiscalCalendar::new(self.fiscal_month_start_day, self.fiscal_year_start_month)
+            .unwrap_or_default()
+    }
+
+    /// Guardrails for user SQL queries
+    pub fn query_limits(&self) -> QueryLimits {
+        QueryLimits {
+            timeout: self.query_timeout_secs.map(Duration::from_secs),
+            max_rows: self.query_max_rows,
+        }
+    }
+
     /// Enable demo mode
     pub fn enable_demo_mode(&mut self) {
         self.demo_mode = true;
@@ -166,6 +340,13 @@ pub struct ColumnMappings {
     /// Optional running balance column for balance snapshots
     #[serde(default)]
     pub balance: Option<String>,
+    /// Optional category column whose values become tags
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Optional account column for files covering several accounts; each row
+    /// goes to the account whose name or nickname matches the cell
+    #[serde(default)]
+    pub account: Option<String>,
 }
 
 impl Default for ColumnMappings {
@@ -177,6 +358,8 @@ impl Default for ColumnMappings {
             credit: None,
             debit: None,
             balance: None,
+            category: None,
+            account: None,
         }
     }
 }
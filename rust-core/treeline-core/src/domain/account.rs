@@ -6,6 +6,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use super::format;
+
+/// Group that accounts without an `account_group` are reported under
+pub const DEFAULT_ACCOUNT_GROUP: &str = "Ungrouped";
+
 /// A financial account owned by the user
 /// Note: account_type is a freeform string using Plaid nomenclature.
 /// Common values include "depository", "credit", "investment", "loan", "other"
@@ -33,6 +38,15 @@ pub struct Account {
     // =========================================================================
     /// True if this account was manually created by the user
     pub is_manual: bool,
+    /// True if the account has been closed/archived by the user.
+    /// Archived accounts keep their history but are skipped by sync
+    /// and excluded from net worth by default.
+    #[serde(default)]
+    pub is_archived: bool,
+    /// User-chosen group (e.g. "Joint", "Business") that status subtotals by.
+    /// Accounts without one fall into [`DEFAULT_ACCOUNT_GROUP`].
+    #[serde(default)]
+    pub account_group: Option<String>,
 
     // =========================================================================
     // SimpleFIN: ALL fields from API (https://www.simplefin.org/protocol.html)
@@ -75,6 +89,40 @@ pub struct Account {
     pub lf_currency: Option<String>,
     /// Status: "ACTIVE", "DISCONNECTED", "ERROR"
     pub lf_status: Option<String>,
+
+    // =========================================================================
+    // Plaid: raw fields from /accounts/get (https://plaid.com/docs/api/accounts/)
+    // =========================================================================
+    /// Plaid account_id (required for dedup)
+    #[serde(default)]
+    pub pl_id: Option<String>,
+    /// Plaid item_id (the institution login this account belongs to)
+    #[serde(default)]
+    pub pl_item_id: Option<String>,
+    /// Account name
+    #[serde(default)]
+    pub pl_name: Option<String>,
+    /// Official account name from the institution
+    #[serde(default)]
+    pub pl_official_name: Option<String>,
+    /// Last 2-4 digits of the account number
+    #[serde(default)]
+    pub pl_mask: Option<String>,
+    /// Account type: "depository", "credit", "loan", "investment", "other"
+    #[serde(default)]
+    pub pl_type: Option<String>,
+    /// Account subtype, e.g. "checking", "credit card"
+    #[serde(default)]
+    pub pl_subtype: Option<String>,
+    /// balances.current
+    #[serde(default)]
+    pub pl_balance_current: Option<Decimal>,
+    /// balances.available
+    #[serde(default)]
+    pub pl_balance_available: Option<Decimal>,
+    /// balances.iso_currency_code
+    #[serde(default)]
+    pub pl_currency: Option<String>,
 }
 
 impl Account {
@@ -96,6 +144,8 @@ impl Account {
             updated_at: now,
             // Manual flag
             is_manual: false,
+            is_archived: false,
+            account_group: None,
             // SimpleFIN fields
             sf_id: None,
             sf_name: None,
@@ -115,6 +165,17 @@ impl Account {
             lf_provider: None,
             lf_currency: None,
             lf_status: None,
+            // Plaid fields
+            pl_id: None,
+            pl_item_id: None,
+            pl_name: None,
+            pl_official_name: None,
+            pl_mask: None,
+            pl_type: None,
+            pl_subtype: None,
+            pl_balance_current: None,
+            pl_balance_available: None,
+            pl_currency: None,
         }
     }
 
@@ -132,6 +193,23 @@ impl Account {
         currency.trim().to_uppercase()
     }
 
+    /// Whether `code` looks like an ISO 4217 currency code: three uppercase letters
+    pub fn is_currency_code(code: &str) -> bool {
+        code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase())
+    }
+
+    /// The balance formatted for the account's currency, e.g. `$1,234.56` or `1.234,56 €`
+    ///
+    /// Uses the number style usual for the currency (see [`format::currency_locale`]).
+    /// An account without a balance shows as zero.
+    pub fn format_balance(&self) -> String {
+        format::format_amount(
+            self.balance.unwrap_or_default(),
+            &self.currency,
+            Some(format::currency_locale(&self.currency)),
+        )
+    }
+
     /// Validate account data
     pub fn validate(&self) -> Result<(), &'static str> {
         if self.name.trim().is_empty() {
@@ -154,6 +232,33 @@ mod tests {
         assert_eq!(Account::normalize_currency(" eur "), "EUR");
     }
 
+    #[test]
+    fn test_is_currency_code() {
+        assert!(Account::is_currency_code("USD"));
+        assert!(Account::is_currency_code("GBP"));
+        assert!(!Account::is_currency_code("usd"));
+        assert!(!Account::is_currency_code("US"));
+        assert!(!Account::is_currency_code("DOLLARS"));
+        assert!(!Account::is_currency_code("U$D"));
+    }
+
+    #[test]
+    fn test_format_balance() {
+        let mut account = Account::new(Uuid::new_v4(), "Brokerage");
+        account.balance = Some(Decimal::new(123456, 2));
+        assert_eq!(account.format_balance(), "$1,234.56");
+
+        account.currency = "EUR".to_string();
+        assert_eq!(account.format_balance(), "1.234,56 €");
+
+        account.currency = "GBP".to_string();
+        account.balance = Some(Decimal::new(-98765432, 2));
+        assert_eq!(account.format_balance(), "-£987,654.32");
+
+        account.balance = None;
+        assert_eq!(account.format_balance(), "£0.00");
+    }
+
     #[test]
     fn test_account_validation() {
         let mut account = Account::new(Uuid::new_v4(), "Test Account");
@@ -19,6 +19,9 @@ pub struct BalanceSnapshot {
     pub updated_at: DateTime<Utc>,
     /// How this snapshot was created (e.g., "sync", "manual", "backfill")
     pub source: Option<String>,
+    /// Why the balance was set by hand, if the user gave a reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 impl BalanceSnapshot {
@@ -33,6 +36,7 @@ impl BalanceSnapshot {
             created_at: now,
             updated_at: now,
             source: None,
+            note: None,
         }
     }
 
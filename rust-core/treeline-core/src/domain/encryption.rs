@@ -11,7 +11,7 @@ pub const DEFAULT_PARALLELISM: u32 = 4;
 pub const DEFAULT_HASH_LEN: u32 = 32;
 
 /// Argon2id parameters for key derivation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Argon2Params {
     pub time_cost: u32,
     pub memory_cost: u32,
@@ -101,6 +101,29 @@ impl EncryptionStatus {
     }
 }
 
+/// Encryption details for inspection, without any secret material
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionDetails {
+    pub encrypted: bool,
+    pub algorithm: Option<String>,
+    pub version: Option<u32>,
+    pub argon2_params: Option<Argon2Params>,
+    /// Short SHA-256 fingerprint of the key derivation salt
+    pub salt_fingerprint: Option<String>,
+}
+
+impl EncryptionDetails {
+    pub fn unencrypted() -> Self {
+        Self {
+            encrypted: false,
+            algorithm: None,
+            version: None,
+            argon2_params: None,
+            salt_fingerprint: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
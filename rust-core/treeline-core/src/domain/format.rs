@@ -0,0 +1,204 @@
+//! Locale-aware display of amounts and dates
+//!
+//! These helpers only shape what people see; stored values and JSON output
+//! keep their plain form. Locales are BCP 47 style tags such as `en-US` or
+//! `de-DE` (POSIX spellings like `de_DE.UTF-8` work too). Only the language
+//! and region matter, and anything unrecognised falls back to US formatting.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+/// Locale used when none is configured or the configured one is unknown
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// How one locale writes numbers, currencies and dates
+struct LocaleStyle {
+    group_separator: &'static str,
+    decimal_separator: char,
+    /// Currency symbol after the number (`1.234,56 €`) rather than before (`$1,234.56`)
+    symbol_after: bool,
+    date_format: &'static str,
+}
+
+const US: LocaleStyle = LocaleStyle {
+    group_separator: ",",
+    decimal_separator: '.',
+    symbol_after: false,
+    date_format: "%m/%d/%Y",
+};
+
+const UK: LocaleStyle = LocaleStyle {
+    group_separator: ",",
+    decimal_separator: '.',
+    symbol_after: false,
+    date_format: "%d/%m/%Y",
+};
+
+const GERMAN: LocaleStyle = LocaleStyle {
+    group_separator: ".",
+    decimal_separator: ',',
+    symbol_after: true,
+    date_format: "%d.%m.%Y",
+};
+
+const FRENCH: LocaleStyle = LocaleStyle {
+    group_separator: " ",
+    decimal_separator: ',',
+    symbol_after: true,
+    date_format: "%d/%m/%Y",
+};
+
+const SOUTHERN_EUROPE: LocaleStyle = LocaleStyle {
+    group_separator: ".",
+    decimal_separator: ',',
+    symbol_after: true,
+    date_format: "%d/%m/%Y",
+};
+
+fn style(locale: Option<&str>) -> &'static LocaleStyle {
+    let tag = locale
+        .unwrap_or(DEFAULT_LOCALE)
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-")
+        .to_lowercase();
+    let mut parts = tag.split('-');
+    let language = parts.next().unwrap_or_default();
+    let region = parts.next().unwrap_or_default();
+
+    match (language, region) {
+        ("en", "gb" | "ie" | "au" | "nz") => &UK,
+        ("de", _) => &GERMAN,
+        ("fr", _) => &FRENCH,
+        ("es" | "it" | "pt", _) => &SOUTHERN_EUROPE,
+        _ => &US,
+    }
+}
+
+/// Symbol for well-known currencies; others are shown by their code
+fn currency_symbol(currency: &str) -> Option<&'static str> {
+    match currency.to_uppercase().as_str() {
+        "USD" => Some("$"),
+        "EUR" => Some("€"),
+        "GBP" => Some("£"),
+        "JPY" => Some("¥"),
+        _ => None,
+    }
+}
+
+/// Locale whose number style is usual for a currency, e.g. `de-DE` for EUR
+///
+/// Used when showing an amount in its own currency rather than the user's
+/// locale. Currencies without a preference get [`DEFAULT_LOCALE`].
+pub fn currency_locale(currency: &str) -> &'static str {
+    match currency.trim().to_uppercase().as_str() {
+        "GBP" => "en-GB",
+        "EUR" => "de-DE",
+        _ => DEFAULT_LOCALE,
+    }
+}
+
+/// Split `amount`, rounded to cents, into its sign and its digits in `style`
+fn format_digits(amount: Decimal, style: &LocaleStyle) -> (&'static str, String) {
+    let rounded = amount.round_dp(2);
+    let text = format!("{:.2}", rounded.abs());
+    let (whole, cents) = text.split_once('.').unwrap_or((&text, "00"));
+
+    let mut digits = String::new();
+    for (i, c) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            digits.push_str(style.group_separator);
+        }
+        digits.push(c);
+    }
+    digits.push(style.decimal_separator);
+    digits.push_str(cents);
+
+    let sign = if rounded < Decimal::ZERO { "-" } else { "" };
+    (sign, digits)
+}
+
+/// Format a number with two decimals and the locale's separators, e.g. `1.234,56`
+pub fn format_number(amount: Decimal, locale: Option<&str>) -> String {
+    let (sign, digits) = format_digits(amount, style(locale));
+    format!("{}{}", sign, digits)
+}
+
+/// Format a money amount, e.g. `$1,234.56` (en-US) or `1.234,56 €` (de-DE)
+pub fn format_amount(amount: Decimal, currency: &str, locale: Option<&str>) -> String {
+    let style = style(locale);
+    let (sign, digits) = format_digits(amount, style);
+    let code = currency.to_uppercase();
+    match (currency_symbol(currency), style.symbol_after) {
+        (Some(symbol), false) => format!("{}{}{}", sign, symbol, digits),
+        (None, false) => format!("{}{} {}", sign, code, digits),
+        (symbol, true) => format!("{}{} {}", sign, digits, symbol.unwrap_or(code.as_str())),
+    }
+}
+
+/// Format a date the way the locale writes it, e.g. `03/01/2024` or `01.03.2024`
+pub fn format_date(date: NaiveDate, locale: Option<&str>) -> String {
+    date.format(style(locale).date_format).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_format_amount_us() {
+        let us = Some("en-US");
+        assert_eq!(format_amount(amount("1234.56"), "USD", us), "$1,234.56");
+        assert_eq!(
+            format_amount(amount("-1234567.891"), "USD", us),
+            "-$1,234,567.89"
+        );
+        assert_eq!(format_amount(amount("0.5"), "usd", us), "$0.50");
+        assert_eq!(format_amount(amount("12"), "CAD", us), "CAD 12.00");
+        assert_eq!(format_number(amount("999.999"), us), "1,000.00");
+    }
+
+    #[test]
+    fn test_format_amount_german() {
+        let de = Some("de-DE");
+        assert_eq!(format_amount(amount("1234.56"), "EUR", de), "1.234,56 €");
+        assert_eq!(format_amount(amount("-1234.56"), "EUR", de), "-1.234,56 €");
+        assert_eq!(format_amount(amount("1234.56"), "CHF", de), "1.234,56 CHF");
+        assert_eq!(
+            format_number(amount("1000000"), Some("de_DE.UTF-8")),
+            "1.000.000,00"
+        );
+    }
+
+    #[test]
+    fn test_format_amount_french() {
+        let fr = Some("fr-FR");
+        assert_eq!(format_amount(amount("1234.56"), "EUR", fr), "1 234,56 €");
+        assert_eq!(format_number(amount("-0.004"), fr), "0,00");
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(format_date(date, Some("en-US")), "03/01/2024");
+        assert_eq!(format_date(date, Some("en-GB")), "01/03/2024");
+        assert_eq!(format_date(date, Some("de-DE")), "01.03.2024");
+        assert_eq!(format_date(date, Some("fr-FR")), "01/03/2024");
+    }
+
+    #[test]
+    fn test_unknown_locale_falls_back_to_us() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(
+            format_amount(amount("1234.56"), "USD", Some("xx-YY")),
+            "$1,234.56"
+        );
+        assert_eq!(format_amount(amount("1234.56"), "USD", None), "$1,234.56");
+        assert_eq!(format_date(date, Some("")), "03/01/2024");
+    }
+}
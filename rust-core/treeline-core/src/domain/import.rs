@@ -0,0 +1,15 @@
+//! CSV import domain types
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// One past import, as listed for undo
+#[derive(Debug, Serialize)]
+pub struct BatchInfo {
+    pub batch_id: String,
+    /// When the first transaction of the batch was written
+    pub imported_at: DateTime<Utc>,
+    pub account_id: String,
+    pub account_name: Option<String>,
+    pub transaction_count: i64,
+}
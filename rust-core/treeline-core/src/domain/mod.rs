@@ -7,15 +7,22 @@ mod account;
 mod backup;
 pub mod balance;
 mod encryption;
+pub mod format;
+mod import;
+pub mod period;
+mod plugin;
 pub mod result;
 mod rule;
 mod transaction;
 mod user;
 
-pub use account::Account;
+pub use account::{Account, DEFAULT_ACCOUNT_GROUP};
 pub use backup::BackupMetadata;
 pub use balance::BalanceSnapshot;
-pub use encryption::{Argon2Params, EncryptionMetadata, EncryptionStatus};
+pub use encryption::{Argon2Params, EncryptionDetails, EncryptionMetadata, EncryptionStatus};
+pub use import::BatchInfo;
+pub use period::{FiscalCalendar, FiscalMonth};
+pub use plugin::{plugin_schema_name, PluginView};
 pub use rule::AutoTagRule;
-pub use transaction::Transaction;
+pub use transaction::{ConflictPolicy, DateBasis, Transaction, ValidationError};
 pub use user::User;
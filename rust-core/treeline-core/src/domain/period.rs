@@ -0,0 +1,193 @@
+//! Fiscal calendar - mapping dates to budgeting months and years
+//!
+//! Some users budget on a fiscal calendar rather than calendar months, e.g.
+//! a "month" that runs from one payday on the 25th to the day before the
+//! next. A fiscal month is named after the calendar month it starts in, so
+//! with a start day of 25 the 24th of March belongs to the February month.
+//! The default calendar (day 1, month 1) matches calendar months and years.
+
+use std::fmt;
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+
+use super::result::{Error, Result};
+
+/// When fiscal months and years start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiscalCalendar {
+    month_start_day: u32,
+    year_start_month: u32,
+}
+
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        Self {
+            month_start_day: 1,
+            year_start_month: 1,
+        }
+    }
+}
+
+impl FiscalCalendar {
+    /// Create a calendar whose months start on `month_start_day` (1-31) and
+    /// whose years start with the fiscal month named `year_start_month` (1-12)
+    ///
+    /// Months shorter than `month_start_day` start on their last day instead.
+    pub fn new(month_start_day: u32, year_start_month: u32) -> Result<Self> {
+        if !(1..=31).contains(&month_start_day) {
+            return Err(Error::validation(format!(
+                "Fiscal month start day must be between 1 and 31, got {}",
+                month_start_day
+            )));
+        }
+        if !(1..=12).contains(&year_start_month) {
+            return Err(Error::validation(format!(
+                "Fiscal year start month must be between 1 and 12, got {}",
+                year_start_month
+            )));
+        }
+        Ok(Self {
+            month_start_day,
+            year_start_month,
+        })
+    }
+
+    pub fn month_start_day(&self) -> u32 {
+        self.month_start_day
+    }
+
+    pub fn year_start_month(&self) -> u32 {
+        self.year_start_month
+    }
+
+    /// The fiscal month containing `date`
+    pub fn fiscal_month(&self, date: NaiveDate) -> FiscalMonth {
+        let month = FiscalMonth {
+            year: date.year(),
+            month: date.month(),
+        };
+        match self.start_of(month) {
+            Some(start) if date < start => month.offset(-1).unwrap_or(month),
+            _ => month,
+        }
+    }
+
+    /// The fiscal year containing `date`, named after the calendar year it starts in
+    pub fn fiscal_year(&self, date: NaiveDate) -> i32 {
+        let month = self.fiscal_month(date);
+        if month.month >= self.year_start_month {
+            month.year
+        } else {
+            month.year - 1
+        }
+    }
+
+    /// First day of a fiscal month, or None if it is out of chrono's range
+    pub fn start_of(&self, month: FiscalMonth) -> Option<NaiveDate> {
+        let first = NaiveDate::from_ymd_opt(month.year, month.month, 1)?;
+        let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+        first.with_day(self.month_start_day.min(last.day()))
+    }
+
+    /// Day after the last day of a fiscal month
+    pub fn end_of(&self, month: FiscalMonth) -> Option<NaiveDate> {
+        self.start_of(month.offset(1)?)
+    }
+}
+
+/// A fiscal month, named after the calendar month it starts in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct FiscalMonth {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl FiscalMonth {
+    /// The fiscal month `months` months later (or earlier, when negative)
+    pub fn offset(self, months: i32) -> Option<Self> {
+        let index = self
+            .year
+            .checked_mul(12)?
+            .checked_add(self.month as i32 - 1)?
+            .checked_add(months)?;
+        Some(Self {
+            year: index.div_euclid(12),
+            month: index.rem_euclid(12) as u32 + 1,
+        })
+    }
+}
+
+impl fmt::Display for FiscalMonth {
+    /// Formats as YYYY-MM
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn month(year: i32, month: u32) -> FiscalMonth {
+        FiscalMonth { year, month }
+    }
+
+    #[test]
+    fn test_default_is_calendar_months() {
+        let calendar = FiscalCalendar::default();
+        assert_eq!(calendar.fiscal_month(date(2024, 3, 1)), month(2024, 3));
+        assert_eq!(calendar.fiscal_month(date(2024, 3, 31)), month(2024, 3));
+        assert_eq!(calendar.fiscal_year(date(2024, 1, 1)), 2024);
+        assert_eq!(calendar.fiscal_year(date(2023, 12, 31)), 2023);
+        assert_eq!(calendar.start_of(month(2024, 2)), Some(date(2024, 2, 1)));
+        assert_eq!(calendar.end_of(month(2024, 12)), Some(date(2025, 1, 1)));
+    }
+
+    #[test]
+    fn test_day_before_start_falls_into_prior_month() {
+        let calendar = FiscalCalendar::new(25, 1).unwrap();
+        assert_eq!(calendar.fiscal_month(date(2024, 3, 24)), month(2024, 2));
+        assert_eq!(calendar.fiscal_month(date(2024, 3, 25)), month(2024, 3));
+        assert_eq!(calendar.fiscal_month(date(2024, 1, 10)), month(2023, 12));
+        assert_eq!(calendar.fiscal_year(date(2024, 1, 10)), 2023);
+        assert_eq!(calendar.start_of(month(2024, 2)), Some(date(2024, 2, 25)));
+        assert_eq!(calendar.end_of(month(2024, 2)), Some(date(2024, 3, 25)));
+    }
+
+    #[test]
+    fn test_start_day_clamped_to_short_months() {
+        let calendar = FiscalCalendar::new(31, 1).unwrap();
+        assert_eq!(calendar.start_of(month(2024, 2)), Some(date(2024, 2, 29)));
+        assert_eq!(calendar.fiscal_month(date(2024, 2, 28)), month(2024, 1));
+        assert_eq!(calendar.fiscal_month(date(2024, 2, 29)), month(2024, 2));
+        assert_eq!(calendar.fiscal_month(date(2024, 3, 30)), month(2024, 2));
+    }
+
+    #[test]
+    fn test_fiscal_year_start_month() {
+        let calendar = FiscalCalendar::new(1, 4).unwrap();
+        assert_eq!(calendar.fiscal_year(date(2024, 3, 31)), 2023);
+        assert_eq!(calendar.fiscal_year(date(2024, 4, 1)), 2024);
+        assert_eq!(calendar.fiscal_year(date(2024, 12, 31)), 2024);
+    }
+
+    #[test]
+    fn test_invalid_calendar() {
+        assert!(FiscalCalendar::new(0, 1).is_err());
+        assert!(FiscalCalendar::new(32, 1).is_err());
+        assert!(FiscalCalendar::new(1, 13).is_err());
+    }
+
+    #[test]
+    fn test_offset_and_display() {
+        assert_eq!(month(2024, 1).offset(-1), Some(month(2023, 12)));
+        assert_eq!(month(2024, 11).offset(3), Some(month(2025, 2)));
+        assert_eq!(month(2024, 1).offset(i32::MAX), None);
+        assert_eq!(month(2024, 3).to_string(), "2024-03");
+    }
+}
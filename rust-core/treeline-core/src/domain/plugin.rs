@@ -0,0 +1,16 @@
+//! Plugin domain types
+
+use serde::Serialize;
+
+/// A view a plugin registered in its schema
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginView {
+    pub plugin_id: String,
+    pub view_name: String,
+    pub sql: String,
+}
+
+/// Database schema owned by a plugin: `plugin_<id>` with hyphens as underscores
+pub fn plugin_schema_name(plugin_id: &str) -> String {
+    format!("plugin_{}", plugin_id.replace('-', "_"))
+}
@@ -1,9 +1,9 @@
 //! Auto-tag rule domain entity
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// An auto-tagging rule that applies tags to matching transactions
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoTagRule {
     /// Unique rule ID
     pub rule_id: String,
@@ -1,13 +1,133 @@
 //! Transaction domain model
 
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use regex::Regex;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
+use thiserror::Error;
 use uuid::Uuid;
 
+/// Earliest year accepted for transaction dates
+const MIN_YEAR: i32 = 1900;
+
+/// How far into the future a transaction date may be
+const MAX_YEARS_AHEAD: i32 = 10;
+
+/// Largest magnitude that fits the DECIMAL(15,2) amount column
+const MAX_ABS_AMOUNT: i64 = 10_000_000_000_000;
+
+/// Characters that would break the tags array literal round-trip
+const TAG_DELIMITERS: &[char] = &[',', '[', ']'];
+
+/// Which transaction date reports group by
+///
+/// Most reporting uses the date the transaction happened; some users reconcile
+/// against the date the money actually moved instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateBasis {
+    /// `transaction_date` - when the purchase was made
+    #[default]
+    Transaction,
+    /// `posted_date` - when the transaction cleared, falling back to `transaction_date`
+    Posted,
+}
+
+impl DateBasis {
+    /// SQL expression for this basis over sys_transactions
+    pub fn sql_column(&self) -> &'static str {
+        match self {
+            DateBasis::Transaction => "transaction_date",
+            DateBasis::Posted => "COALESCE(posted_date, transaction_date)",
+        }
+    }
+}
+
+/// What sync does when a provider sends a transaction that is already stored
+///
+/// Covers the description, amount, dates and tags the user may have edited.
+/// Notes are never touched by sync, whatever the policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Keep the stored transaction as it is, user edits included
+    #[default]
+    PreserveLocal,
+    /// Take the provider's description, amount and dates, and its tags when it sends any
+    PreferProvider,
+    /// Keep user edits, only filling in a description or tags the stored transaction lacks
+    MergeNonEmpty,
+}
+
+impl ConflictPolicy {
+    /// `local` updated from the provider's copy under this policy
+    ///
+    /// Returns None when the policy leaves the stored transaction unchanged.
+    pub fn resolve(self, local: &Transaction, provider: &Transaction) -> Option<Transaction> {
+        let mut merged = local.clone();
+        match self {
+            ConflictPolicy::PreserveLocal => return None,
+            ConflictPolicy::PreferProvider => {
+                if provider.description.is_some() {
+                    merged.description = provider.description.clone();
+                }
+                merged.amount = provider.amount;
+                merged.transaction_date = provider.transaction_date;
+                merged.posted_date = provider.posted_date;
+                if !provider.tags.is_empty() {
+                    merged.tags = provider.tags.clone();
+                }
+            }
+            ConflictPolicy::MergeNonEmpty => {
+                let has_description = local
+                    .description
+                    .as_deref()
+                    .is_some_and(|d| !d.trim().is_empty());
+                if !has_description {
+                    merged.description = provider.description.clone();
+                }
+                if local.tags.is_empty() {
+                    merged.tags = provider.tags.clone();
+                }
+            }
+        }
+
+        let changed = merged.description != local.description
+            || merged.amount != local.amount
+            || merged.transaction_date != local.transaction_date
+            || merged.posted_date != local.posted_date
+            || merged.tags != local.tags;
+        if !changed {
+            return None;
+        }
+        merged.updated_at = Utc::now();
+        Some(merged)
+    }
+}
+
+/// Reasons a transaction is rejected before it is written to the database
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    #[error("{field} {date} is outside the supported range ({min} to {max})")]
+    DateOutOfRange {
+        field: &'static str,
+        date: NaiveDate,
+        min: NaiveDate,
+        max: NaiveDate,
+    },
+
+    #[error("Amount {0} is too large to store")]
+    AmountOutOfRange(Decimal),
+
+    #[error("Tags must not be empty")]
+    EmptyTag,
+
+    #[error("Tag '{tag}' contains '{ch}', which is not allowed in tags")]
+    InvalidTagCharacter { tag: String, ch: char },
+}
+
 /// A single financial transaction belonging to an account
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -15,6 +135,9 @@ pub struct Transaction {
     pub account_id: Uuid,
     pub amount: Decimal,
     pub description: Option<String>,
+    /// User annotation, kept separate from the bank description so sync never overwrites it
+    #[serde(default)]
+    pub notes: Option<String>,
     pub transaction_date: NaiveDate,
     pub posted_date: NaiveDate,
     /// Tags for categorization
@@ -26,6 +149,17 @@ pub struct Transaction {
     /// Parent transaction ID for splits
     pub parent_transaction_id: Option<Uuid>,
 
+    // =========================================================================
+    // Foreign currency
+    // =========================================================================
+    /// Amount in the currency the purchase was made in, when the provider reports one.
+    /// `amount` is always what settled in the account's own currency.
+    #[serde(default)]
+    pub original_amount: Option<Decimal>,
+    /// Currency code of `original_amount`, e.g. "EUR" for a euro purchase on a USD card
+    #[serde(default)]
+    pub original_currency: Option<String>,
+
     // =========================================================================
     // CSV Import tracking
     // =========================================================================
@@ -83,6 +217,40 @@ pub struct Transaction {
     pub lf_description: Option<String>,
     /// Is transaction pending
     pub lf_is_pending: Option<bool>,
+
+    // =========================================================================
+    // Plaid: raw fields from /transactions/sync (https://plaid.com/docs/api/products/transactions/)
+    // =========================================================================
+    /// Plaid transaction_id (required for dedup)
+    #[serde(default)]
+    pub pl_id: Option<String>,
+    /// Plaid account_id
+    #[serde(default)]
+    pub pl_account_id: Option<String>,
+    /// Raw amount (Plaid convention: positive = money out)
+    #[serde(default)]
+    pub pl_amount: Option<Decimal>,
+    /// iso_currency_code
+    #[serde(default)]
+    pub pl_currency: Option<String>,
+    /// Posted date
+    #[serde(default)]
+    pub pl_date: Option<NaiveDate>,
+    /// Date the transaction was authorized
+    #[serde(default)]
+    pub pl_authorized_date: Option<NaiveDate>,
+    /// Raw transaction name
+    #[serde(default)]
+    pub pl_name: Option<String>,
+    /// Cleaned merchant name
+    #[serde(default)]
+    pub pl_merchant_name: Option<String>,
+    /// Is transaction pending
+    #[serde(default)]
+    pub pl_pending: Option<bool>,
+    /// personal_finance_category.primary, e.g. "FOOD_AND_DRINK"
+    #[serde(default)]
+    pub pl_category: Option<String>,
 }
 
 impl Transaction {
@@ -94,6 +262,7 @@ impl Transaction {
             account_id,
             amount,
             description: None,
+            notes: None,
             transaction_date,
             posted_date: transaction_date,
             tags: Vec::new(),
@@ -101,6 +270,9 @@ impl Transaction {
             updated_at: now,
             deleted_at: None,
             parent_transaction_id: None,
+            // Foreign currency
+            original_amount: None,
+            original_currency: None,
             // CSV Import tracking
             csv_fingerprint: None,
             csv_batch_id: None,
@@ -125,6 +297,85 @@ impl Transaction {
             lf_merchant: None,
             lf_description: None,
             lf_is_pending: None,
+            // Plaid fields
+            pl_id: None,
+            pl_account_id: None,
+            pl_amount: None,
+            pl_currency: None,
+            pl_date: None,
+            pl_authorized_date: None,
+            pl_name: None,
+            pl_merchant_name: None,
+            pl_pending: None,
+            pl_category: None,
+        }
+    }
+
+    /// The original foreign amount for display, e.g. "EUR -45.00"
+    ///
+    /// None unless both the amount and its currency are known.
+    pub fn original_amount_label(&self) -> Option<String> {
+        match (self.original_amount, &self.original_currency) {
+            (Some(amount), Some(currency)) => Some(format!("{} {:.2}", currency, amount)),
+            _ => None,
+        }
+    }
+
+    /// Check that the transaction is safe to store
+    ///
+    /// Rejects dates before 1900 or more than ten years ahead, amounts (settled
+    /// or original) that don't fit the amount column, and tags that are empty or contain the
+    /// array delimiters used when writing tags.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let min = NaiveDate::from_ymd_opt(MIN_YEAR, 1, 1).unwrap();
+        let today = Utc::now().date_naive();
+        let max = today
+            .with_year(today.year() + MAX_YEARS_AHEAD)
+            .unwrap_or(NaiveDate::MAX);
+
+        for (field, date) in [
+            ("transaction_date", self.transaction_date),
+            ("posted_date", self.posted_date),
+        ] {
+            if date < min || date > max {
+                return Err(ValidationError::DateOutOfRange {
+                    field,
+                    date,
+                    min,
+                    max,
+                });
+            }
+        }
+
+        for amount in std::iter::once(self.amount).chain(self.original_amount) {
+            if amount.abs() >= Decimal::from(MAX_ABS_AMOUNT) {
+                return Err(ValidationError::AmountOutOfRange(amount));
+            }
+        }
+
+        for tag in &self.tags {
+            if tag.trim().is_empty() {
+                return Err(ValidationError::EmptyTag);
+            }
+            if let Some(ch) = tag.chars().find(|c| TAG_DELIMITERS.contains(c)) {
+                return Err(ValidationError::InvalidTagCharacter {
+                    tag: tag.clone(),
+                    ch,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The date this transaction is reported under for the given basis
+    ///
+    /// `posted_date` is always populated on the domain model (it defaults to
+    /// `transaction_date`), so no fallback is needed here.
+    pub fn reporting_date(&self, basis: DateBasis) -> NaiveDate {
+        match basis {
+            DateBasis::Transaction => self.transaction_date,
+            DateBasis::Posted => self.posted_date,
         }
     }
 
@@ -176,7 +427,10 @@ impl Transaction {
     }
 
     /// Normalize description for fingerprint comparison
-    fn normalize_description(desc: Option<&str>) -> String {
+    ///
+    /// Lowercased alphanumerics only, with card masks and long account
+    /// numbers reduced, so the same transaction matches across exports.
+    pub fn normalize_description(desc: Option<&str>) -> String {
         let desc = desc.unwrap_or("").to_lowercase();
 
         // Remove literal "null" strings (common in CSV exports)
@@ -211,6 +465,45 @@ impl Transaction {
         special_re.replace_all(&normalized, "").to_string()
     }
 
+    /// Readable merchant name from a raw bank description
+    ///
+    /// Drops payment processor prefixes (`SQ *`, `TST*`, `PAYPAL *`, ...),
+    /// then cuts at the first later word containing a digit or `#`, which is
+    /// usually a store number followed by the location. So both
+    /// "SQ *COFFEE 12345 SEATTLE WA" and "SQ *COFFEE 678 PORTLAND OR" become
+    /// "Coffee". Returns None if no name is left, e.g. for a blank description.
+    pub fn normalize_merchant(desc: &str) -> Option<String> {
+        let processor_re =
+            Regex::new(r"(?i)^\s*(?:sq|tst|sp|pp|paypal|py|wpy|ic|cko|dd)\s?\*").unwrap();
+        let desc = processor_re.replace(desc, "");
+
+        let mut words: Vec<String> = Vec::new();
+        for word in desc.split(|c: char| c.is_whitespace() || c == '*') {
+            let word = word.trim_matches(|c: char| matches!(c, ',' | '-' | '.' | ':'));
+            if word.is_empty() {
+                continue;
+            }
+            // A number in the first word is part of the name (7-Eleven)
+            if !words.is_empty() && word.contains(|c: char| c.is_ascii_digit() || c == '#') {
+                break;
+            }
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                words.push(
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect(),
+                );
+            }
+        }
+
+        if words.is_empty() {
+            return None;
+        }
+        Some(words.join(" "))
+    }
+
     /// Normalize tags: deduplicate, trim whitespace, remove empty
     pub fn normalize_tags(tags: &[String]) -> Vec<String> {
         let mut seen = std::collections::HashSet::new();
@@ -269,6 +562,31 @@ mod tests {
         assert!(normalized.contains("7070"));
     }
 
+    #[test]
+    fn test_merchant_normalization() {
+        assert_eq!(
+            Transaction::normalize_merchant("SQ *COFFEE 12345 SEATTLE WA").as_deref(),
+            Some("Coffee")
+        );
+        assert_eq!(
+            Transaction::normalize_merchant("STARBUCKS #1234 SEATTLE").as_deref(),
+            Some("Starbucks")
+        );
+        assert_eq!(
+            Transaction::normalize_merchant("AMAZON.COM*AB12CD34E").as_deref(),
+            Some("Amazon.com")
+        );
+        assert_eq!(
+            Transaction::normalize_merchant("Whole Foods Market").as_deref(),
+            Some("Whole Foods Market")
+        );
+        assert_eq!(
+            Transaction::normalize_merchant("7-ELEVEN 38265").as_deref(),
+            Some("7-eleven")
+        );
+        assert_eq!(Transaction::normalize_merchant("   "), None);
+    }
+
     #[test]
     fn test_tag_normalization() {
         let tags = vec![
@@ -280,4 +598,110 @@ mod tests {
         let normalized = Transaction::normalize_tags(&tags);
         assert_eq!(normalized, vec!["food", "groceries"]);
     }
+
+    fn valid_transaction() -> Transaction {
+        Transaction::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Decimal::new(-5000, 2),
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_reporting_date_follows_basis() {
+        let mut tx = valid_transaction();
+        tx.posted_date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        assert_eq!(
+            tx.reporting_date(DateBasis::Transaction),
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+        );
+        assert_eq!(tx.reporting_date(DateBasis::Posted), tx.posted_date);
+        assert_eq!(DateBasis::default(), DateBasis::Transaction);
+    }
+
+    #[test]
+    fn test_validate_accepts_normal_transaction() {
+        let mut tx = valid_transaction();
+        tx.tags = vec!["food".to_string(), "dining out".to_string()];
+        assert_eq!(tx.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_ancient_date() {
+        let mut tx = valid_transaction();
+        tx.transaction_date = NaiveDate::from_ymd_opt(1, 1, 1).unwrap();
+        assert!(matches!(
+            tx.validate(),
+            Err(ValidationError::DateOutOfRange {
+                field: "transaction_date",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_far_future_posted_date() {
+        let mut tx = valid_transaction();
+        tx.posted_date = NaiveDate::from_ymd_opt(9999, 12, 31).unwrap();
+        assert!(matches!(
+            tx.validate(),
+            Err(ValidationError::DateOutOfRange {
+                field: "posted_date",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_oversized_amount() {
+        let mut tx = valid_transaction();
+        tx.amount = Decimal::MAX;
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::AmountOutOfRange(Decimal::MAX))
+        );
+
+        let mut tx = valid_transaction();
+        tx.original_amount = Some(Decimal::MIN);
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::AmountOutOfRange(Decimal::MIN))
+        );
+    }
+
+    #[test]
+    fn test_original_amount_label() {
+        let mut tx = valid_transaction();
+        assert_eq!(tx.original_amount_label(), None);
+
+        tx.original_amount = Some(Decimal::new(-4500, 2));
+        assert_eq!(tx.original_amount_label(), None);
+
+        tx.original_currency = Some("EUR".to_string());
+        assert_eq!(tx.original_amount_label(), Some("EUR -45.00".to_string()));
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_tag() {
+        let mut tx = valid_transaction();
+        tx.tags = vec!["food".to_string(), "  ".to_string()];
+        assert_eq!(tx.validate(), Err(ValidationError::EmptyTag));
+    }
+
+    #[test]
+    fn test_validate_rejects_tag_delimiters() {
+        for bad in ["a,b", "[food", "food]"] {
+            let mut tx = valid_transaction();
+            tx.tags = vec![bad.to_string()];
+            assert!(
+                matches!(
+                    tx.validate(),
+                    Err(ValidationError::InvalidTagCharacter { .. })
+                ),
+                "tag {:?} should be rejected",
+                bad
+            );
+        }
+    }
 }
@@ -14,6 +14,8 @@ pub mod log_migrations;
 pub mod migrations;
 pub mod ports;
 pub mod services;
+#[doc(hidden)]
+pub mod test_support;
 
 use std::path::Path;
 use std::sync::Arc;
@@ -25,7 +27,9 @@ use config::Config;
 use services::*;
 
 // Re-export commonly used types at crate root
-pub use adapters::duckdb::QueryResult;
+pub use adapters::duckdb::{
+    AuditEntry, PendingQuery, QueryHandle, QueryLimits, QueryResult, RepositoryError,
+};
 pub use domain::result::{Error, OperationResult};
 pub use domain::{
     Account, BackupMetadata, BalanceSnapshot, EncryptionMetadata, EncryptionStatus, Transaction,
@@ -40,12 +44,16 @@ pub use services::{EntryPoint, LogEntry, LogEvent, LoggingService};
 pub struct TreelineContext {
     pub config: Config,
     pub repository: Arc<DuckDbRepository>,
+    pub account_service: AccountService,
     pub status_service: StatusService,
     pub sync_service: SyncService,
     pub query_service: QueryService,
     pub tag_service: TagService,
+    pub transaction_service: TransactionService,
+    pub transfer_detector: TransferDetector,
     pub backup_service: BackupService,
     pub compact_service: CompactService,
+    pub config_service: ConfigService,
     pub doctor_service: DoctorService,
     pub encryption_service: EncryptionService,
     pub import_service: ImportService,
@@ -72,34 +80,50 @@ impl TreelineContext {
         repository.ensure_schema()?;
 
         // Create services
-        let status_service = StatusService::new(Arc::clone(&repository));
+        let account_service = AccountService::new(Arc::clone(&repository));
+        let status_service = StatusService::new(Arc::clone(&repository))
+            .with_date_basis(config.date_basis)
+            .with_fiscal_calendar(config.fiscal_calendar());
         let sync_service = SyncService::new(Arc::clone(&repository), treeline_dir.to_path_buf());
-        let query_service = QueryService::new(Arc::clone(&repository));
+        let query_service = QueryService::new(Arc::clone(&repository))
+            .with_limits(config.query_limits())
+            .with_treeline_dir(treeline_dir.to_path_buf());
         let tag_service = TagService::new(Arc::clone(&repository));
+        let transaction_service = TransactionService::new(Arc::clone(&repository));
+        let transfer_detector = TransferDetector::new(Arc::clone(&repository));
         let backup_service = BackupService::new_with_repository(
             treeline_dir.to_path_buf(),
             db_filename.to_string(),
             Arc::clone(&repository),
         );
         let compact_service = CompactService::new(Arc::clone(&repository));
+        let config_service =
+            ConfigService::new(Arc::clone(&repository), treeline_dir.to_path_buf());
         let doctor_service =
             DoctorService::new(Arc::clone(&repository), treeline_dir.to_path_buf());
         let encryption_service =
             EncryptionService::new(treeline_dir.to_path_buf(), db_path.clone());
         let import_service =
             ImportService::new(Arc::clone(&repository), treeline_dir.to_path_buf());
-        let balance_service = BalanceService::new(Arc::clone(&repository));
-        let plugin_service = services::PluginService::new(treeline_dir);
+        let balance_service = BalanceService::new(Arc::clone(&repository))
+            .with_date_basis(config.date_basis)
+            .with_exchange_rates(config.exchange_rates.clone());
+        let plugin_service =
+            services::PluginService::new_with_repository(treeline_dir, Arc::clone(&repository));
 
         Ok(Self {
             config,
             repository,
+            account_service,
             status_service,
             sync_service,
             query_service,
             tag_service,
+            transaction_service,
+            transfer_detector,
             backup_service,
             compact_service,
+            config_service,
             doctor_service,
             encryption_service,
             import_service,
@@ -107,4 +131,25 @@ impl TreelineContext {
             plugin_service,
         })
     }
+
+    /// Sync integrations, then run the plugins' post-sync hooks
+    ///
+    /// Same as [`SyncService::sync`], except that after a real (not dry-run)
+    /// sync each hook registered with [`PluginService::register_sync_hook`]
+    /// runs. Hook failures never fail the sync; they are collected in
+    /// [`SyncResult::hook_warnings`].
+    pub fn sync(
+        &self,
+        integration: Option<&str>,
+        dry_run: bool,
+        balances_only: bool,
+    ) -> Result<SyncResult> {
+        let mut result = self
+            .sync_service
+            .sync(integration, dry_run, balances_only)?;
+        if !dry_run {
+            result.hook_warnings = self.plugin_service.run_sync_hooks(self);
+        }
+        Ok(result)
+    }
 }
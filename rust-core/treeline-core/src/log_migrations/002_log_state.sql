@@ -0,0 +1,6 @@
+-- Small key/value store for log maintenance bookkeeping
+-- (e.g. when old entries were last pruned)
+CREATE TABLE sys_log_state (
+    key VARCHAR PRIMARY KEY,
+    value BIGINT NOT NULL
+);
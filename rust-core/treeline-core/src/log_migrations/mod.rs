@@ -16,4 +16,5 @@ pub const LOG_MIGRATIONS: &[(&str, &str)] = &[
         "001_initial_schema.sql",
         include_str!("001_initial_schema.sql"),
     ),
+    ("002_log_state.sql", include_str!("002_log_state.sql")),
 ];
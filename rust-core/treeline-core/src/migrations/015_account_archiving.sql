@@ -0,0 +1,12 @@
+-- Migration: Account archiving
+-- Lets users close an account without deleting its history.
+-- Archived accounts keep their transactions and balance snapshots, but are
+-- skipped by sync and excluded from net worth by default.
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS is_archived BOOLEAN DEFAULT FALSE;
+
+-- The accounts view uses SELECT *, so it must be recreated to pick up the new column
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
@@ -0,0 +1,82 @@
+-- Migration: Plaid provider columns
+-- Stores raw Plaid fields for accounts and transactions, following the same
+-- provider-prefixed pattern as SimpleFIN (sf_*) and Lunchflow (lf_*).
+
+-- =============================================================================
+-- TRANSACTIONS: Add columns
+-- =============================================================================
+
+-- Plaid: fields from /transactions/sync
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_id VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_account_id VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_amount DECIMAL(15,2);
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_currency VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_date DATE;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_authorized_date DATE;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_name VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_merchant_name VARCHAR;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_pending BOOLEAN;
+ALTER TABLE sys_transactions ADD COLUMN IF NOT EXISTS pl_category VARCHAR;
+
+CREATE INDEX IF NOT EXISTS idx_sys_transactions_pl_id ON sys_transactions(pl_id);
+
+-- =============================================================================
+-- ACCOUNTS: Add columns
+-- =============================================================================
+
+-- Plaid: fields from /accounts/get
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_id VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_item_id VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_name VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_official_name VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_mask VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_type VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_subtype VARCHAR;
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_balance_current DECIMAL(15,2);
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_balance_available DECIMAL(15,2);
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS pl_currency VARCHAR;
+
+-- =============================================================================
+-- UPDATE VIEWS
+-- =============================================================================
+
+-- Refresh accounts view to pick up new columns (SELECT * column list is cached)
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
+
+-- Add 'plaid' as a transaction source
+CREATE OR REPLACE VIEW transactions AS
+SELECT
+    -- Core fields (pass-through, already mapped by adapters)
+    t.transaction_id,
+    t.account_id,
+    t.amount,
+    t.description,
+    t.transaction_date,
+    t.posted_date,
+    t.tags,
+    t.parent_transaction_id,
+    t.tags_auto_applied,
+
+    -- Computed: source identification
+    -- Note: Demo mode uses its own database, so no 'demo' case needed here
+    CASE
+        WHEN t.sf_id IS NOT NULL THEN 'simplefin'
+        WHEN t.lf_id IS NOT NULL THEN 'lunchflow'
+        WHEN t.pl_id IS NOT NULL THEN 'plaid'
+        WHEN t.csv_batch_id IS NOT NULL THEN 'csv_import'
+        WHEN t.parent_transaction_id IS NOT NULL THEN 'split'
+        WHEN t.is_manual THEN 'manual'
+        ELSE 'unknown'
+    END AS source,
+
+    -- Account info (joined)
+    a.name AS account_name,
+    a.account_type,
+    a.currency,
+    a.institution_name
+FROM sys_transactions t
+LEFT JOIN sys_accounts a ON t.account_id = a.account_id
+WHERE t.deleted_at IS NULL;
@@ -0,0 +1,11 @@
+-- Migration: Plugin views
+-- Views registered by plugins in their own schema (plugin_<id>). The SQL is
+-- kept here so the views can be recreated, e.g. after compaction.
+
+CREATE TABLE IF NOT EXISTS sys_plugin_views (
+    plugin_id VARCHAR NOT NULL,
+    view_name VARCHAR NOT NULL,
+    sql VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (plugin_id, view_name)
+);
@@ -0,0 +1,11 @@
+-- Migration: Provider category map
+-- Maps a provider's transaction category (e.g. SimpleFIN's "Groceries") to a
+-- tag, used to tag newly synced transactions when category mapping is enabled.
+-- Categories are stored trimmed and lowercased so lookups ignore case.
+
+CREATE TABLE IF NOT EXISTS sys_category_map (
+    provider_category VARCHAR PRIMARY KEY,
+    tag VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
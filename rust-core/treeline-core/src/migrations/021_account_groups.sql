@@ -0,0 +1,12 @@
+-- Migration: Account groups
+-- Lets users file accounts under a group (e.g. "Joint", "Business") so status
+-- can show subtotals per group. Purely organizational: sync never sets or
+-- reads it.
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS account_group VARCHAR;
+
+-- The accounts view uses SELECT *, so it must be recreated to pick up the new column
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
@@ -0,0 +1,12 @@
+-- Migration: Account currency override
+-- Lets users correct the currency a provider reports for an account (e.g. a
+-- brokerage that reports USD for a fund priced in GBP). Sync keeps an
+-- overridden currency instead of replacing it.
+
+ALTER TABLE sys_accounts ADD COLUMN IF NOT EXISTS currency_overridden BOOLEAN DEFAULT FALSE;
+
+-- The accounts view uses SELECT *, so it must be recreated to pick up the new column
+DROP VIEW IF EXISTS accounts;
+
+CREATE VIEW accounts AS
+SELECT * FROM sys_accounts;
@@ -0,0 +1,18 @@
+-- Migration: Transaction audit trail
+-- One row per changed field whenever tags, notes, description, date, amount
+-- or the deleted state of a transaction change, so "why did this tag
+-- disappear" can be answered later. Values are stored as text. There is no
+-- foreign key, so the history outlives the transaction it describes.
+
+CREATE TABLE IF NOT EXISTS sys_transaction_audit (
+    audit_id VARCHAR PRIMARY KEY,
+    transaction_id VARCHAR NOT NULL,
+    field VARCHAR NOT NULL,
+    old_value VARCHAR,
+    new_value VARCHAR,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- "cli" or "desktop"; NULL when the caller didn't say
+    entry_point VARCHAR
+);
+
+CREATE INDEX IF NOT EXISTS idx_sys_transaction_audit_transaction_id ON sys_transaction_audit(transaction_id);
@@ -0,0 +1,22 @@
+-- Migration: Balance snapshot note
+-- Lets users record why they set a balance by hand (e.g. "counted the cash
+-- drawer"). NULL for synced, imported and backfilled snapshots.
+
+ALTER TABLE sys_balance_snapshots ADD COLUMN IF NOT EXISTS note VARCHAR;
+
+-- Update the balance_snapshots view to include the note
+CREATE OR REPLACE VIEW balance_snapshots AS
+SELECT
+    s.snapshot_id,
+    s.account_id,
+    s.balance,
+    s.snapshot_time,
+    s.source,
+    s.note,
+    s.created_at,
+    s.updated_at,
+    -- Account details
+    a.name AS account_name,
+    a.institution_name
+FROM sys_balance_snapshots s
+LEFT JOIN sys_accounts a ON s.account_id = a.account_id;
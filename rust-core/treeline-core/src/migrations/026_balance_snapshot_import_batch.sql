@@ -0,0 +1,6 @@
+-- Migration: Balance snapshot import batch
+-- Records which CSV import created a snapshot, so undoing the import removes
+-- exactly its snapshots. NULL for every other source and for snapshots
+-- imported before this migration.
+
+ALTER TABLE sys_balance_snapshots ADD COLUMN IF NOT EXISTS import_batch_id VARCHAR;
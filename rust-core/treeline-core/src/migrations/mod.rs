@@ -3,6 +3,7 @@
 //! Migrations are compiled into the binary at build time using include_str!.
 //! Each migration is a tuple of (name, sql_content).
 //! Migrations are sorted by name and applied in order.
+//! A migration may have a paired `NNN_name.down.sql` script used to roll it back.
 
 /// All migrations, embedded at compile time.
 /// Format: (filename, sql_content)
@@ -70,4 +71,54 @@ pub const MIGRATIONS: &[(&str, &str)] = &[
         "014_track_auto_applied_tags.sql",
         include_str!("014_track_auto_applied_tags.sql"),
     ),
+    (
+        "015_account_archiving.sql",
+        include_str!("015_account_archiving.sql"),
+    ),
+    (
+        "016_plaid_columns.sql",
+        include_str!("016_plaid_columns.sql"),
+    ),
+    (
+        "017_transaction_notes.sql",
+        include_str!("017_transaction_notes.sql"),
+    ),
+    (
+        "018_transfer_groups.sql",
+        include_str!("018_transfer_groups.sql"),
+    ),
+    ("019_plugin_views.sql", include_str!("019_plugin_views.sql")),
+    ("020_category_map.sql", include_str!("020_category_map.sql")),
+    (
+        "021_account_groups.sql",
+        include_str!("021_account_groups.sql"),
+    ),
+    (
+        "022_transaction_original_currency.sql",
+        include_str!("022_transaction_original_currency.sql"),
+    ),
+    (
+        "023_account_currency_override.sql",
+        include_str!("023_account_currency_override.sql"),
+    ),
+    (
+        "024_transaction_audit.sql",
+        include_str!("024_transaction_audit.sql"),
+    ),
+    (
+        "025_balance_snapshot_note.sql",
+        include_str!("025_balance_snapshot_note.sql"),
+    ),
+    (
+        "026_balance_snapshot_import_batch.sql",
+        include_str!("026_balance_snapshot_import_batch.sql"),
+    ),
 ];
+
+/// Down migrations, embedded at compile time.
+/// Format: (filename, sql_content), where filename is `NNN_name.down.sql`
+/// for the migration `NNN_name.sql` it reverts.
+///
+/// Only the most recently applied migration can be rolled back, so a down
+/// script is only useful if every later migration also has one.
+pub const DOWN_MIGRATIONS: &[(&str, &str)] = &[];
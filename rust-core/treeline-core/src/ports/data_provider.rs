@@ -3,7 +3,10 @@
 //! Defines the interface for fetching account and transaction data from
 //! external sources (SimpleFIN, demo data, CSV files, etc.)
 
+use std::collections::HashSet;
+
 use chrono::NaiveDate;
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 
 use crate::domain::result::Result;
@@ -22,7 +25,79 @@ pub struct FetchAccountsResult {
 pub struct FetchTransactionsResult {
     /// Transactions keyed by provider account ID
     pub transactions: Vec<(String, Transaction)>,
+    /// Transactions returned by an earlier sync that the provider has since
+    /// changed, keyed like `transactions`. Only cursor-based providers report these.
+    pub modified: Vec<(String, Transaction)>,
+    /// (provider account ID, provider transaction ID) of transactions the
+    /// provider has deleted since an earlier sync
+    pub removed: Vec<(String, String)>,
     pub warnings: Vec<String>,
+    /// Settings to persist after a successful sync (e.g. a new sync cursor).
+    /// None means the stored settings are unchanged.
+    pub updated_settings: Option<JsonValue>,
+}
+
+/// Outcome of checking an integration's connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionStatus {
+    /// Credentials accepted
+    Ok,
+    /// Credentials rejected, revoked or the subscription lapsed
+    AuthFailed,
+    /// Provider asked us to slow down
+    RateLimited,
+    /// Provider could not be reached or answered with an error
+    Unreachable,
+    /// Provider has no connection check
+    Unchecked,
+}
+
+impl ConnectionStatus {
+    /// Classify a provider's HTTP response status
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            200..=299 => ConnectionStatus::Ok,
+            401..=403 => ConnectionStatus::AuthFailed,
+            429 => ConnectionStatus::RateLimited,
+            _ => ConnectionStatus::Unreachable,
+        }
+    }
+}
+
+/// Connection status plus a message explaining it
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionHealth {
+    pub status: ConnectionStatus,
+    pub message: String,
+}
+
+impl ConnectionHealth {
+    pub fn new(status: ConnectionStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Integration settings key holding provider account IDs to leave out of sync
+pub const EXCLUDED_ACCOUNTS_KEY: &str = "excludedAccountIds";
+
+/// Provider account IDs the user has excluded from sync
+///
+/// Reads `excludedAccountIds` from integration settings; missing or malformed
+/// values mean nothing is excluded.
+pub fn excluded_account_ids(settings: &JsonValue) -> HashSet<String> {
+    settings
+        .get(EXCLUDED_ACCOUNTS_KEY)
+        .and_then(|v| v.as_array())
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| id.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// Data aggregation provider trait
@@ -63,6 +138,33 @@ pub trait DataAggregationProvider: Send + Sync {
         account_ids: &[String],
         settings: &JsonValue,
     ) -> Result<FetchTransactionsResult>;
+
+    /// Stable ID this provider uses for an account
+    ///
+    /// Sync matches fetched accounts to stored ones by this ID. Providers
+    /// without their own account IDs fall back to the account name.
+    fn account_external_id(&self, account: &Account) -> Option<String> {
+        Some(account.name.clone())
+    }
+
+    /// `sys_transactions` column holding this provider's transaction IDs (e.g. `sf_id`)
+    ///
+    /// None means transactions can't be matched by ID and are always inserted.
+    fn transaction_id_column(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// This provider's ID for a transaction, stored in [`transaction_id_column`](Self::transaction_id_column)
+    fn transaction_external_id(&self, _tx: &Transaction) -> Option<String> {
+        None
+    }
+
+    /// The provider's own category for a transaction, if it supplies one
+    ///
+    /// Used to tag new transactions through the user's category map.
+    fn transaction_category(&self, _tx: &Transaction) -> Option<String> {
+        None
+    }
 }
 
 /// Integration provider trait
@@ -78,4 +180,49 @@ pub trait IntegrationProvider: Send + Sync {
     /// # Returns
     /// Settings to store for this integration
     fn setup(&self, options: &JsonValue) -> Result<JsonValue>;
+
+    /// Check that stored settings still grant access, with one lightweight request
+    ///
+    /// Failures the provider reports (bad credentials, rate limits, outages)
+    /// come back as a [`ConnectionHealth`]; errors are for settings that
+    /// can't be used at all, such as a missing API key.
+    fn check_connection(&self, _settings: &JsonValue) -> Result<ConnectionHealth> {
+        Ok(ConnectionHealth::new(
+            ConnectionStatus::Unchecked,
+            "Connection check not supported",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_status_from_http_status() {
+        assert_eq!(
+            ConnectionStatus::from_http_status(200),
+            ConnectionStatus::Ok
+        );
+        assert_eq!(
+            ConnectionStatus::from_http_status(401),
+            ConnectionStatus::AuthFailed
+        );
+        assert_eq!(
+            ConnectionStatus::from_http_status(402),
+            ConnectionStatus::AuthFailed
+        );
+        assert_eq!(
+            ConnectionStatus::from_http_status(403),
+            ConnectionStatus::AuthFailed
+        );
+        assert_eq!(
+            ConnectionStatus::from_http_status(429),
+            ConnectionStatus::RateLimited
+        );
+        assert_eq!(
+            ConnectionStatus::from_http_status(503),
+            ConnectionStatus::Unreachable
+        );
+    }
 }
@@ -7,6 +7,7 @@ mod data_provider;
 mod repository;
 
 pub use data_provider::{
-    DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult, IntegrationProvider,
+    excluded_account_ids, ConnectionHealth, ConnectionStatus, DataAggregationProvider,
+    FetchAccountsResult, FetchTransactionsResult, IntegrationProvider, EXCLUDED_ACCOUNTS_KEY,
 };
 pub use repository::Repository;
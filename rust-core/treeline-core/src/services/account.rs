@@ -0,0 +1,207 @@
+//! Account service - account lifecycle management
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{Account, BalanceSnapshot};
+use crate::services::BalanceService;
+
+/// Account service for managing accounts
+pub struct AccountService {
+    repository: Arc<DuckDbRepository>,
+}
+
+impl AccountService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self { repository }
+    }
+
+    /// Create an account that isn't synced, such as cash or a house
+    ///
+    /// With an opening balance, a `manual` snapshot for the end of
+    /// `opening_date` (default today) is written in the same database
+    /// transaction, so the account shows in net worth straight away. Liability
+    /// balances (credit cards, loans) are what is owed, as a negative number.
+    pub fn create_manual(&self, new_account: NewAccount) -> Result<CreatedAccount> {
+        let mut account = Account::new(Uuid::new_v4(), new_account.name.trim());
+        account.account_type = new_account.account_type;
+        account.classification = Some(Account::compute_classification(
+            account.account_type.as_deref(),
+        ));
+        if let Some(currency) = new_account.currency {
+            account.currency = Account::normalize_currency(&currency);
+            if !Account::is_currency_code(&account.currency) {
+                anyhow::bail!(
+                    "Invalid currency code '{}': expected a 3-letter ISO 4217 code such as USD",
+                    account.currency
+                );
+            }
+        }
+        account.is_manual = true;
+        account.balance = new_account.opening_balance;
+        account.validate().map_err(|e| anyhow::anyhow!(e))?;
+
+        if new_account.opening_date.is_some() && new_account.opening_balance.is_none() {
+            anyhow::bail!("An opening date needs an opening balance");
+        }
+        if let Some(balance) = new_account.opening_balance {
+            if account.classification.as_deref() == Some("liability") && balance > Decimal::ZERO {
+                anyhow::bail!(
+                    "Liability balances are the amount owed as a negative number; did you mean -{}?",
+                    balance
+                );
+            }
+        }
+
+        let opening_date = new_account
+            .opening_date
+            .unwrap_or_else(|| Utc::now().date_naive());
+        let opening_snapshot = self.repository.with_transaction(|| {
+            self.repository.upsert_account(&account)?;
+            new_account
+                .opening_balance
+                .map(|balance| {
+                    BalanceService::new(self.repository.clone()).set_manual_balance(
+                        &account.id.to_string(),
+                        balance,
+                        opening_date,
+                    )
+                })
+                .transpose()
+        })?;
+
+        Ok(CreatedAccount {
+            account,
+            opening_snapshot,
+        })
+    }
+
+    /// Archive an account
+    ///
+    /// Unlike deletion, archiving keeps all transactions and balance snapshots.
+    /// The account is hidden from status, excluded from net worth and skipped by sync.
+    pub fn archive(&self, account_id: &str) -> Result<ArchiveResult> {
+        self.set_archived(account_id, true)
+    }
+
+    /// Unarchive a previously archived account
+    pub fn unarchive(&self, account_id: &str) -> Result<ArchiveResult> {
+        self.set_archived(account_id, false)
+    }
+
+    fn set_archived(&self, account_id: &str, archived: bool) -> Result<ArchiveResult> {
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+
+        self.repository.set_account_archived(account_id, archived)?;
+
+        Ok(ArchiveResult {
+            account_id: account.id.to_string(),
+            name: account.name,
+            is_archived: archived,
+        })
+    }
+
+    /// File an account under a group for reporting, or ungroup it with `None`
+    ///
+    /// The group name is trimmed; a blank name ungroups the account.
+    pub fn set_group(&self, account_id: &str, group: Option<&str>) -> Result<GroupResult> {
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+
+        let group = group.map(str::trim).filter(|g| !g.is_empty());
+        self.repository.set_account_group(account_id, group)?;
+
+        Ok(GroupResult {
+            account_id: account.id.to_string(),
+            name: account.name,
+            group: group.map(str::to_string),
+        })
+    }
+
+    /// Override the account's currency with an ISO 4217 code, kept across syncs
+    pub fn set_currency(&self, account_id: &str, currency: &str) -> Result<CurrencyResult> {
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+
+        self.repository.set_account_currency(account_id, currency)?;
+
+        Ok(CurrencyResult {
+            account_id: account.id.to_string(),
+            name: account.name,
+            currency: Account::normalize_currency(currency),
+        })
+    }
+
+    /// Merge a duplicate account into another, deleting the duplicate
+    ///
+    /// See [`DuckDbRepository::merge_accounts`] for how transactions that
+    /// both accounts hold are resolved.
+    pub fn merge(&self, source_id: &str, target_id: &str) -> Result<MergeReport> {
+        self.repository.merge_accounts(source_id, target_id)
+    }
+}
+
+/// Details for [`AccountService::create_manual`]
+#[derive(Debug, Clone, Default)]
+pub struct NewAccount {
+    pub name: String,
+    /// Plaid-style type such as "depository" or "credit"; decides asset vs liability
+    pub account_type: Option<String>,
+    /// ISO 4217 code (default USD)
+    pub currency: Option<String>,
+    pub opening_balance: Option<Decimal>,
+    /// Day the opening balance is for (default today)
+    pub opening_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedAccount {
+    pub account: Account,
+    /// The `manual` snapshot holding the opening balance, if one was given
+    pub opening_snapshot: Option<BalanceSnapshot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveResult {
+    pub account_id: String,
+    pub name: String,
+    pub is_archived: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupResult {
+    pub account_id: String,
+    pub name: String,
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CurrencyResult {
+    pub account_id: String,
+    pub name: String,
+    pub currency: String,
+}
+
+/// Rows moved by an account merge
+#[derive(Debug, Serialize)]
+pub struct MergeReport {
+    pub source_id: String,
+    pub target_id: String,
+    pub transactions_moved: usize,
+    pub snapshots_moved: usize,
+    /// Transactions dropped because the other account held a newer copy
+    pub duplicates_dropped: usize,
+}
@@ -3,13 +3,14 @@
 //! Creates ZIP archives containing the database and config files,
 //! compatible with the Python CLI backup format.
 
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde::Serialize;
 use zip::write::SimpleFileOptions;
 use zip::{ZipArchive, ZipWriter};
@@ -20,6 +21,18 @@ use crate::domain::BackupMetadata;
 /// Config files to include in backup (relative to treeline dir)
 const CONFIG_FILES: &[&str] = &["settings.json", "encryption.json"];
 
+/// Tables that can be restored individually with `restore_table()`
+const RESTORABLE_TABLES: &[&str] = &[
+    "sys_accounts",
+    "sys_transactions",
+    "sys_balance_snapshots",
+    "sys_integrations",
+    "sys_transactions_rules",
+];
+
+/// `sys_*` tables left out of JSON Lines exports; they describe the schema, not the data
+const JSONL_SKIPPED_TABLES: &[&str] = &["sys_migrations"];
+
 /// Backup service for database backup management
 ///
 /// The repository is optional - if provided, create() will checkpoint
@@ -94,8 +107,28 @@ impl BackupService {
         let backup_name = format!("treeline-{}-{:06}.zip", timestamp, micros);
         let backup_path = backups_dir.join(&backup_name);
 
-        // Create ZIP archive
-        let file = File::create(&backup_path).context("Failed to create backup file")?;
+        self.write_archive(&backup_path)?;
+
+        let metadata = fs::metadata(&backup_path)?;
+        let size_bytes = metadata.len();
+
+        // Apply retention policy
+        if let Some(max) = max_backups {
+            self.apply_retention(max)?;
+        }
+
+        Ok(BackupMetadata {
+            name: backup_name,
+            created_at: Utc::now(),
+            size_bytes,
+        })
+    }
+
+    /// Write the database and config files into a ZIP archive at `backup_path`
+    fn write_archive(&self, backup_path: &Path) -> Result<()> {
+        let db_path = self.treeline_dir.join(&self.db_filename);
+
+        let file = File::create(backup_path).context("Failed to create backup file")?;
         let mut zip = ZipWriter::new(file);
         let options =
             SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
@@ -120,20 +153,7 @@ impl BackupService {
         }
 
         zip.finish()?;
-
-        let metadata = fs::metadata(&backup_path)?;
-        let size_bytes = metadata.len();
-
-        // Apply retention policy
-        if let Some(max) = max_backups {
-            self.apply_retention(max)?;
-        }
-
-        Ok(BackupMetadata {
-            name: backup_name,
-            created_at: Utc::now(),
-            size_bytes,
-        })
+        Ok(())
     }
 
     /// List all backups (both .zip and legacy .duckdb formats)
@@ -167,8 +187,14 @@ impl BackupService {
             let metadata = fs::metadata(&path)?;
             let size_bytes = metadata.len();
 
-            // Parse timestamp from filename
-            let created_at = self.parse_backup_time(&name);
+            // Parse timestamp from filename, falling back to the file's mtime
+            let created_at = match self
+                .parse_backup_time(&name)
+                .or_else(|| metadata.modified().ok().map(chrono::DateTime::<Utc>::from))
+            {
+                Some(created_at) => created_at,
+                None => continue,
+            };
 
             backups.push(BackupMetadata {
                 name,
@@ -182,69 +208,67 @@ impl BackupService {
     }
 
     /// Parse creation time from backup filename
-    fn parse_backup_time(&self, backup_name: &str) -> chrono::DateTime<Utc> {
-        // Extract timestamp part: "treeline-TIMESTAMP.zip" or "treeline-TIMESTAMP.duckdb"
+    ///
+    /// Returns None if the name carries no timestamp we recognise.
+    fn parse_backup_time(&self, backup_name: &str) -> Option<chrono::DateTime<Utc>> {
+        // Extract timestamp part: "treeline-[pre-restore-]TIMESTAMP.zip" or "treeline-TIMESTAMP.duckdb"
         let ts = backup_name
             .strip_prefix("treeline-")
-            .and_then(|s| s.strip_suffix(".zip").or_else(|| s.strip_suffix(".duckdb")));
-
-        if let Some(ts) = ts {
-            // Try with microseconds first, then without
-            chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H-%M-%S-%f")
-                .or_else(|_| chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H-%M-%S"))
-                .map(|dt| dt.and_utc())
-                .unwrap_or_else(|_| Utc::now())
-        } else {
-            Utc::now()
-        }
+            .map(|s| s.strip_prefix("pre-restore-").unwrap_or(s))
+            .and_then(|s| s.strip_suffix(".zip").or_else(|| s.strip_suffix(".duckdb")))?;
+
+        // Try with microseconds first, then without
+        chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H-%M-%S-%f")
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H-%M-%S"))
+            .map(|dt| dt.and_utc())
+            .ok()
     }
 
     /// Restore from a backup
-    pub fn restore(&self, backup_name: &str) -> Result<()> {
+    ///
+    /// The backup is read and validated in full before anything is touched, so a
+    /// corrupt archive leaves the live database as it was. The current database
+    /// and config files are then saved as a `treeline-pre-restore-*.zip` backup,
+    /// whose name is returned so the restore can be undone.
+    pub fn restore(&self, backup_name: &str) -> Result<RestoreResult> {
         let backup_path = self.backups_dir().join(backup_name);
         if !backup_path.exists() {
             anyhow::bail!("Backup not found: {}", backup_name);
         }
 
+        // Read every entry up front - this also verifies the ZIP checksums
+        let zip_entries = if backup_name.ends_with(".zip") {
+            Some(self.read_archive(&backup_path).with_context(|| {
+                format!("Backup {} is corrupt; nothing was restored", backup_name)
+            })?)
+        } else {
+            None
+        };
+
         let db_path = self.treeline_dir.join(&self.db_filename);
 
-        // Create a backup of current state first
-        if db_path.exists() {
+        // Create a safety backup of current state first
+        let pre_restore_backup = if db_path.exists() {
             let now = Utc::now();
             let timestamp = now.format("%Y-%m-%dT%H-%M-%S");
             let micros = now.timestamp_subsec_micros();
-            let pre_restore_backup =
-                format!("treeline-pre-restore-{}-{:06}.zip", timestamp, micros);
-            let pre_restore_path = self.backups_dir().join(&pre_restore_backup);
-
-            // Create a quick backup of just the DB
-            let file = File::create(&pre_restore_path)?;
-            let mut zip = ZipWriter::new(file);
-            let options =
-                SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-
-            zip.start_file(&self.db_filename, options)?;
-            let mut db_file = File::open(&db_path)?;
-            let mut buffer = Vec::new();
-            db_file.read_to_end(&mut buffer)?;
-            zip.write_all(&buffer)?;
-            zip.finish()?;
-        }
+            let name = format!("treeline-pre-restore-{}-{:06}.zip", timestamp, micros);
+            fs::create_dir_all(self.backups_dir())?;
+            self.write_archive(&self.backups_dir().join(&name))
+                .context("Failed to create pre-restore backup; nothing was restored")?;
+            Some(name)
+        } else {
+            None
+        };
 
         // Restore based on backup format
-        if backup_name.ends_with(".zip") {
-            // New ZIP format - extract all files
-            let file = File::open(&backup_path)?;
-            let mut archive = ZipArchive::new(file)?;
-
+        if let Some(entries) = zip_entries {
+            // New ZIP format - write all files
             // Track which config files are in the backup
             let mut restored_configs: std::collections::HashSet<String> =
                 std::collections::HashSet::new();
 
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i)?;
-                let name = file.name().to_string();
-
+            for (name, contents) in entries {
                 let target_path = if name.ends_with(".duckdb") {
                     self.treeline_dir.join(&self.db_filename)
                 } else {
@@ -255,8 +279,7 @@ impl BackupService {
                     self.treeline_dir.join(&name)
                 };
 
-                let mut outfile = File::create(&target_path)?;
-                std::io::copy(&mut file, &mut outfile)?;
+                fs::write(&target_path, contents)?;
             }
 
             // Remove config files that were NOT in the backup
@@ -279,9 +302,268 @@ impl BackupService {
             fs::copy(&backup_path, &db_path).context("Failed to restore backup")?;
         }
 
+        Ok(RestoreResult {
+            restored: backup_name.to_string(),
+            pre_restore_backup,
+        })
+    }
+
+    /// Restore a single table from a backup into the live database
+    ///
+    /// Rows from the backup are upserted by primary key; rows that only exist
+    /// in the live database are left alone, and every other table is untouched.
+    /// Requires a repository. Returns the number of rows restored.
+    pub fn restore_table(&self, backup_name: &str, table: &str) -> Result<usize> {
+        if !RESTORABLE_TABLES.contains(&table) {
+            anyhow::bail!(
+                "Table {} can't be restored on its own (expected one of: {})",
+                table,
+                RESTORABLE_TABLES.join(", ")
+            );
+        }
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Restoring a table requires database access"))?;
+
+        let (_temp_dir, source_db) = self.extract_database(backup_name)?;
+        repository.upsert_table_from(&source_db, table)
+    }
+
+    /// Compare the live database with a backup
+    ///
+    /// Rows are matched by ID; a row present in both is counted as modified when
+    /// its `updated_at` differs. "Added" means present now but not in the backup,
+    /// so restoring the backup would undo it. Requires a repository.
+    pub fn diff(&self, backup_name: &str) -> Result<BackupDiff> {
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Diffing a backup requires database access"))?;
+
+        let (_temp_dir, backup_db) = self.extract_database(backup_name)?;
+        let backup_repo = DuckDbRepository::new_read_only(&backup_db, repository.encryption_key())
+            .with_context(|| format!("Failed to open backup {}", backup_name))?;
+
+        let diff_table = |table: &str| -> Result<TableDiff> {
+            let live = repository.get_row_versions(table)?;
+            let backup = backup_repo.get_row_versions(table)?;
+
+            let mut diff = TableDiff::default();
+            for (id, updated_at) in &live {
+                match backup.get(id) {
+                    None => diff.added += 1,
+                    Some(old) if old != updated_at => diff.modified += 1,
+                    Some(_) => {}
+                }
+            }
+            diff.removed = backup.keys().filter(|id| !live.contains_key(*id)).count();
+            Ok(diff)
+        };
+
+        Ok(BackupDiff {
+            backup: backup_name.to_string(),
+            accounts: diff_table("sys_accounts")?,
+            transactions: diff_table("sys_transactions")?,
+            balance_snapshots: diff_table("sys_balance_snapshots")?,
+        })
+    }
+
+    /// Export every `sys_*` table into `out_dir` as JSON Lines
+    ///
+    /// Writes one `<table>.jsonl` file per table, creating `out_dir` if needed.
+    /// Unlike a backup this is a logical copy: it can be imported into an
+    /// encrypted or unencrypted database, on another machine or a newer DuckDB
+    /// version. Plugin schemas are not included. Requires a repository.
+    pub fn export_jsonl(&self, out_dir: &Path) -> Result<()> {
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Exporting requires database access"))?;
+
+        fs::create_dir_all(out_dir)
+            .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+        for table in repository.sys_table_names()? {
+            if JSONL_SKIPPED_TABLES.contains(&table.as_str()) {
+                continue;
+            }
+            repository.export_table_jsonl(&table, &out_dir.join(format!("{}.jsonl", table)))?;
+        }
         Ok(())
     }
 
+    /// Import a directory written by [`export_jsonl`](Self::export_jsonl)
+    ///
+    /// Rows are upserted by primary key, so importing twice is harmless and rows
+    /// only in the live database are kept. Files for tables this database
+    /// doesn't have are skipped and reported. Requires a repository.
+    pub fn import_jsonl(&self, in_dir: &Path) -> Result<ImportStats> {
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Importing requires database access"))?;
+
+        let mut files: Vec<(String, PathBuf)> = Vec::new();
+        for entry in
+            fs::read_dir(in_dir).with_context(|| format!("Failed to read {}", in_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            if let Some(table) = path.file_stem().and_then(|s| s.to_str()) {
+                files.push((table.to_string(), path.clone()));
+            }
+        }
+        // Accounts first: transactions and balance snapshots reference them
+        files.sort_by(|(a, _), (b, _)| (a != "sys_accounts", a).cmp(&(b != "sys_accounts", b)));
+
+        let live_tables = repository.sys_table_names()?;
+        let mut stats = ImportStats::default();
+        for (table, path) in files {
+            if !live_tables.contains(&table) || JSONL_SKIPPED_TABLES.contains(&table.as_str()) {
+                stats.skipped.push(table);
+                continue;
+            }
+            let rows = repository.import_table_jsonl(&table, &path)?;
+            stats.rows += rows;
+            stats.tables.insert(table, rows);
+        }
+        Ok(stats)
+    }
+
+    /// Get a backup's database as a file on disk
+    ///
+    /// ZIP backups are extracted to a temp dir, which is deleted when the
+    /// returned handle is dropped; legacy .duckdb backups are used in place.
+    fn extract_database(&self, backup_name: &str) -> Result<(tempfile::TempDir, PathBuf)> {
+        let backup_path = self.backups_dir().join(backup_name);
+        if !backup_path.exists() {
+            anyhow::bail!("Backup not found: {}", backup_name);
+        }
+
+        let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
+        if !backup_name.ends_with(".zip") {
+            return Ok((temp_dir, backup_path));
+        }
+
+        let entries = self
+            .read_archive(&backup_path)
+            .with_context(|| format!("Backup {} is corrupt", backup_name))?;
+        let db_path = temp_dir.path().join(&self.db_filename);
+        for (entry_name, contents) in &entries {
+            if entry_name.ends_with(".duckdb") {
+                fs::write(&db_path, contents)?;
+            }
+        }
+        Ok((temp_dir, db_path))
+    }
+
+    /// Check that a backup can actually be restored
+    ///
+    /// Reads the whole archive (verifying checksums), extracts the database to a
+    /// temp dir and opens it read-only to confirm the core tables are there.
+    /// Encrypted backups can't be opened without the password, so they are
+    /// reported as unverified rather than ok. Failures are reported in the
+    /// result rather than returned as errors.
+    pub fn verify(&self, name: &str) -> Result<BackupVerifyReport> {
+        let backup_path = self.backups_dir().join(name);
+        if !backup_path.exists() {
+            anyhow::bail!("Backup not found: {}", name);
+        }
+
+        let report = |ok: bool, message: String| BackupVerifyReport {
+            name: name.to_string(),
+            ok,
+            message,
+        };
+
+        if !name.ends_with(".zip") {
+            // Legacy .duckdb backups are plain database files
+            return Ok(match self.verify_database_file(&backup_path) {
+                Ok(()) => report(
+                    true,
+                    "Database opens and core tables are present".to_string(),
+                ),
+                Err(e) => report(false, format!("Database check failed: {}", e)),
+            });
+        }
+
+        let entries = match self.read_archive_entries(&backup_path) {
+            Ok(entries) => entries,
+            Err(e) => return Ok(report(false, format!("Archive is unreadable: {}", e))),
+        };
+        if !has_database_entry(&entries) {
+            return Ok(report(false, "Archive has no database file".to_string()));
+        }
+
+        // Encrypted databases can't be opened without the password, so they
+        // can't be shown to restore
+        if entries.iter().any(|(n, _)| n == "encryption.json") {
+            return Ok(report(
+                false,
+                "Unverified: encrypted (archive is intact, but the database was not opened)"
+                    .to_string(),
+            ));
+        }
+
+        let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
+        let db_path = temp_dir.path().join(&self.db_filename);
+        for (entry_name, contents) in &entries {
+            if entry_name.ends_with(".duckdb") {
+                fs::write(&db_path, contents)?;
+            }
+        }
+
+        Ok(match self.verify_database_file(&db_path) {
+            Ok(()) => report(
+                true,
+                "Archive is intact, database opens and core tables are present".to_string(),
+            ),
+            Err(e) => report(false, format!("Database check failed: {}", e)),
+        })
+    }
+
+    /// Open a database file read-only and make sure it has the core tables
+    fn verify_database_file(&self, db_path: &Path) -> Result<()> {
+        let repo = DuckDbRepository::new_read_only(db_path, None)?;
+        if !repo.table_exists("sys_accounts")? {
+            anyhow::bail!("Missing table: sys_accounts");
+        }
+        Ok(())
+    }
+
+    /// Read all entries of a backup archive into memory
+    ///
+    /// Fails if the archive is unreadable or has no `.duckdb` entry.
+    fn read_archive(&self, backup_path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+        let entries = self.read_archive_entries(backup_path)?;
+        if !has_database_entry(&entries) {
+            anyhow::bail!("Backup does not contain a database file");
+        }
+        Ok(entries)
+    }
+
+    /// Read all entries of an archive, whatever they are
+    fn read_archive_entries(&self, backup_path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+        let file = File::open(backup_path)?;
+        let mut archive = ZipArchive::new(file)?;
+
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let name = file.name().to_string();
+            // Reject entries that would escape the treeline directory
+            if file.enclosed_name().is_none() {
+                anyhow::bail!("Invalid entry in backup: {}", name);
+            }
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+            entries.push((name, contents));
+        }
+        Ok(entries)
+    }
+
     /// Clear all backups (both .zip and legacy .duckdb)
     pub fn clear(&self) -> Result<ClearResult> {
         let backups = self.list()?;
@@ -295,6 +577,69 @@ impl BackupService {
         Ok(ClearResult { deleted: count })
     }
 
+    /// Delete backups that violate any constraint of the retention policy
+    ///
+    /// Backups are considered newest first: a backup is deleted if it falls past
+    /// `max_count`, is older than `max_age_days`, or would push the running total
+    /// size of kept backups over `max_total_bytes`. The newest backup is always
+    /// kept, so pruning never leaves nothing to restore from.
+    pub fn prune(&self, policy: RetentionPolicy) -> Result<PruneResult> {
+        let now = Utc::now();
+        let mut kept = 0usize;
+        let mut kept_bytes = 0u64;
+        let mut deleted = Vec::new();
+
+        for (i, backup) in self.list()?.into_iter().enumerate() {
+            let over_count = policy.max_count.is_some_and(|max| kept >= max);
+            let too_old = policy
+                .max_age_days
+                .is_some_and(|days| now - backup.created_at > Duration::days(days));
+            let over_size = policy
+                .max_total_bytes
+                .is_some_and(|max| kept_bytes + backup.size_bytes > max);
+
+            if i > 0 && (over_count || too_old || over_size) {
+                fs::remove_file(self.backups_dir().join(&backup.name))?;
+                deleted.push(backup.name);
+            } else {
+                kept += 1;
+                kept_bytes += backup.size_bytes;
+            }
+        }
+
+        Ok(PruneResult {
+            deleted,
+            remaining: kept as i64,
+            remaining_bytes: kept_bytes,
+        })
+    }
+
+    /// Delete backups older than `days`, based on the timestamp in their name
+    ///
+    /// Returns the number of backups deleted.
+    pub fn prune_older_than(&self, days: u32) -> Result<usize> {
+        let result = self.prune(RetentionPolicy {
+            max_age_days: Some(days as i64),
+            ..Default::default()
+        })?;
+        Ok(result.deleted.len())
+    }
+
+    /// Create a backup only if the newest existing one is older than `min_interval`
+    ///
+    /// Safe to call on every app launch. Returns the new backup, or None if a
+    /// recent enough backup already exists.
+    pub fn create_if_stale(&self, min_interval: Duration) -> Result<Option<BackupMetadata>> {
+        // list() is sorted newest first
+        if let Some(newest) = self.list()?.first() {
+            if Utc::now() - newest.created_at < min_interval {
+                return Ok(None);
+            }
+        }
+
+        self.create(None).map(Some)
+    }
+
     fn apply_retention(&self, max_backups: usize) -> Result<()> {
         let mut backups = self.list()?;
 
@@ -313,3 +658,75 @@ impl BackupService {
 pub struct ClearResult {
     pub deleted: i64,
 }
+
+/// Row-level differences between the live database and a backup
+#[derive(Debug, Serialize)]
+pub struct BackupDiff {
+    pub backup: String,
+    pub accounts: TableDiff,
+    pub transactions: TableDiff,
+    pub balance_snapshots: TableDiff,
+}
+
+/// Counts of rows that differ for one table, relative to the backup
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct TableDiff {
+    /// In the live database but not in the backup
+    pub added: usize,
+    /// In the backup but no longer in the live database
+    pub removed: usize,
+    /// In both, with a different `updated_at`
+    pub modified: usize,
+}
+
+/// Rows imported by [`BackupService::import_jsonl`]
+#[derive(Debug, Default, Serialize)]
+pub struct ImportStats {
+    /// Rows written per table
+    pub tables: BTreeMap<String, usize>,
+    /// Total rows written
+    pub rows: usize,
+    /// Files for tables this database doesn't have, by table name
+    pub skipped: Vec<String>,
+}
+
+/// Whether archive entries include the database
+fn has_database_entry(entries: &[(String, Vec<u8>)]) -> bool {
+    entries.iter().any(|(name, _)| name.ends_with(".duckdb"))
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupVerifyReport {
+    pub name: String,
+    /// True if the backup looks safe to restore
+    pub ok: bool,
+    /// What was checked, or the specific failure
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResult {
+    /// Name of the backup that was restored
+    pub restored: String,
+    /// Safety backup of the database as it was before the restore, if one existed
+    pub pre_restore_backup: Option<String>,
+}
+
+/// Constraints for pruning backups. Unset fields are not enforced.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    /// Keep at most this many backups
+    pub max_count: Option<usize>,
+    /// Delete backups older than this many days
+    pub max_age_days: Option<i64>,
+    /// Keep the newest backups whose combined size fits in this many bytes
+    pub max_total_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PruneResult {
+    /// Names of deleted backups
+    pub deleted: Vec<String>,
+    pub remaining: i64,
+    pub remaining_bytes: u64,
+}
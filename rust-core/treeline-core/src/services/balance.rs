@@ -11,16 +11,46 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::adapters::duckdb::DuckDbRepository;
-use crate::domain::BalanceSnapshot;
+use crate::domain::{BalanceSnapshot, DateBasis, Transaction};
+
+/// Snapshot source for balances entered by the user
+const MANUAL_SOURCE: &str = "manual";
+
+/// Description of transactions added by [`BalanceService::adjust_balance`]
+const ADJUSTMENT_DESCRIPTION: &str = "Balance adjustment";
 
 /// Balance service for balance snapshot management
 pub struct BalanceService {
     repository: Arc<DuckDbRepository>,
+    date_basis: DateBasis,
+    exchange_rates: HashMap<String, Decimal>,
 }
 
 impl BalanceService {
     pub fn new(repository: Arc<DuckDbRepository>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            date_basis: DateBasis::default(),
+            exchange_rates: HashMap::new(),
+        }
+    }
+
+    /// Assign transactions to days by this date basis when deriving balances
+    pub fn with_date_basis(mut self, date_basis: DateBasis) -> Self {
+        self.date_basis = date_basis;
+        self
+    }
+
+    /// Rates for converting between currencies in net worth totals
+    ///
+    /// Each rate is the value of one unit of that currency in a common base
+    /// currency (e.g. `USD = 1`, `EUR = 1.08`). Codes are matched ignoring case.
+    pub fn with_exchange_rates(mut self, exchange_rates: HashMap<String, Decimal>) -> Self {
+        self.exchange_rates = exchange_rates
+            .into_iter()
+            .map(|(currency, rate)| (currency.to_uppercase(), rate))
+            .collect();
+        self
     }
 
     /// Add a manual balance snapshot
@@ -62,7 +92,8 @@ impl BalanceService {
             account_id: account_uuid,
             balance,
             snapshot_time,
-            source: Some("manual".to_string()),
+            source: Some(MANUAL_SOURCE.to_string()),
+            note: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -77,6 +108,107 @@ impl BalanceService {
         })
     }
 
+    /// Set an account's balance as of the end of `as_of`
+    ///
+    /// For accounts Treeline can't sync (cash, property, ...). Writes a
+    /// `manual` snapshot at 23:59:59.999999 so it is the day's final balance;
+    /// if a manual snapshot already exists for that day it is updated
+    /// instead. Sync never adds a snapshot on a day with a manual one.
+    pub fn set_manual_balance(
+        &self,
+        account_id: &str,
+        balance: Decimal,
+        as_of: NaiveDate,
+    ) -> Result<BalanceSnapshot> {
+        self.write_manual_balance(account_id, balance, as_of, None)
+    }
+
+    /// Set an account's balance by hand, recording why and optionally adjusting
+    ///
+    /// Works like [`set_manual_balance`](Self::set_manual_balance) and also
+    /// stores `note` on the snapshot. With `create_adjustment`, a manual
+    /// transaction for the difference from the prior balance is added too, so
+    /// the account's transactions add up to the new balance. The prior balance
+    /// is the latest snapshot up to the end of `as_of` (zero when there is
+    /// none); an unchanged balance adds no transaction. Returns the snapshot ID.
+    pub fn adjust_balance(
+        &self,
+        account_id: &str,
+        balance: Decimal,
+        as_of: NaiveDate,
+        note: Option<String>,
+        create_adjustment: bool,
+    ) -> Result<Uuid> {
+        let cutoff = end_of_day(as_of);
+        // Snapshots come newest first
+        let prior_balance = self
+            .repository
+            .get_balance_snapshots(Some(account_id))?
+            .into_iter()
+            .find(|s| s.snapshot_time <= cutoff)
+            .map(|s| s.balance)
+            .unwrap_or(Decimal::ZERO);
+
+        self.repository.with_transaction(|| {
+            let snapshot =
+                self.write_manual_balance(account_id, balance, as_of, note.as_deref())?;
+
+            let delta = balance - prior_balance;
+            if create_adjustment && !delta.is_zero() {
+                let mut tx = Transaction::new(Uuid::new_v4(), snapshot.account_id, delta, as_of);
+                tx.description = Some(match &note {
+                    Some(note) => format!("{}: {}", ADJUSTMENT_DESCRIPTION, note),
+                    None => ADJUSTMENT_DESCRIPTION.to_string(),
+                });
+                tx.is_manual = true;
+                self.repository.upsert_transaction(&tx)?;
+            }
+            Ok(snapshot.id)
+        })
+    }
+
+    /// Write the day's manual snapshot, keeping any existing note unless `note` is given
+    fn write_manual_balance(
+        &self,
+        account_id: &str,
+        balance: Decimal,
+        as_of: NaiveDate,
+        note: Option<&str>,
+    ) -> Result<BalanceSnapshot> {
+        if self.repository.get_account_by_id(account_id)?.is_none() {
+            anyhow::bail!("Account not found: {}", account_id);
+        }
+        let account_uuid = Uuid::parse_str(account_id)?;
+
+        let existing = self
+            .repository
+            .get_balance_snapshots(Some(account_id))?
+            .into_iter()
+            .find(|s| {
+                s.snapshot_time.date() == as_of && s.source.as_deref() == Some(MANUAL_SOURCE)
+            });
+        if let Some(mut snapshot) = existing {
+            self.repository.update_balance_snapshot(
+                &snapshot.id.to_string(),
+                balance,
+                MANUAL_SOURCE,
+            )?;
+            if let Some(note) = note {
+                self.repository
+                    .set_balance_snapshot_note(&snapshot.id.to_string(), Some(note))?;
+                snapshot.note = Some(note.to_string());
+            }
+            snapshot.balance = balance;
+            snapshot.updated_at = Utc::now();
+            return Ok(snapshot);
+        }
+
+        let mut snapshot = BalanceSnapshot::from_manual(account_uuid, balance, end_of_day(as_of));
+        snapshot.note = note.map(str::to_string);
+        self.repository.add_balance_snapshot(&snapshot)?;
+        Ok(snapshot)
+    }
+
     /// Preview what balance snapshots would be created/replaced
     ///
     /// Returns a list of BalanceSnapshotPreview showing calculated end-of-day balances
@@ -125,13 +257,9 @@ impl BalanceService {
         let mut transactions_by_date: HashMap<NaiveDate, Vec<&crate::domain::Transaction>> =
             HashMap::new();
         for tx in &transactions {
-            *daily_totals
-                .entry(tx.transaction_date)
-                .or_insert(Decimal::ZERO) += tx.amount;
-            transactions_by_date
-                .entry(tx.transaction_date)
-                .or_default()
-                .push(tx);
+            let date = tx.reporting_date(self.date_basis);
+            *daily_totals.entry(date).or_insert(Decimal::ZERO) += tx.amount;
+            transactions_by_date.entry(date).or_default().push(tx);
         }
 
         // Collect ALL dates we need to consider:
@@ -245,7 +373,7 @@ impl BalanceService {
         let mut daily_totals: HashMap<NaiveDate, Decimal> = HashMap::new();
         for tx in &transactions {
             *daily_totals
-                .entry(tx.transaction_date)
+                .entry(tx.reporting_date(self.date_basis))
                 .or_insert(Decimal::ZERO) += tx.amount;
         }
 
@@ -323,6 +451,7 @@ impl BalanceService {
                     balance: current_balance,
                     snapshot_time: end_of_day,
                     source: Some("backfill".to_string()),
+                    note: None,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
                 };
@@ -340,6 +469,205 @@ impl BalanceService {
             snapshots_skipped: 0,
         })
     }
+
+    /// Daily balance series for charting, using step interpolation
+    ///
+    /// Each day carries forward the most recent snapshot at or before it (the last
+    /// snapshot of the day wins). Days before the first snapshot are omitted.
+    pub fn daily_balances(
+        &self,
+        account_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Decimal)>> {
+        if start > end {
+            anyhow::bail!("Start date {} is after end date {}", start, end);
+        }
+
+        let snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+        Ok(daily_series(snapshots, start, end))
+    }
+
+    /// Daily net worth in `currency` across all (non-archived) accounts
+    ///
+    /// Sums each account's daily balance (as in `daily_balances`) per day,
+    /// converted with the configured exchange rates. Balances are used as
+    /// stored, so liabilities (stored negative) reduce the total, and accounts
+    /// with no snapshot yet on a given day contribute zero. Every day in the range
+    /// is included. Fails if an account's currency has no exchange rate.
+    pub fn net_worth_series(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        currency: &str,
+    ) -> Result<Vec<(NaiveDate, Decimal)>> {
+        if start > end {
+            anyhow::bail!("Start date {} is after end date {}", start, end);
+        }
+
+        // One query for all snapshots rather than one per account
+        let mut snapshots_by_account: HashMap<Uuid, Vec<BalanceSnapshot>> = HashMap::new();
+        for snapshot in self.repository.get_balance_snapshots(None)? {
+            snapshots_by_account
+                .entry(snapshot.account_id)
+                .or_default()
+                .push(snapshot);
+        }
+
+        let days: Vec<NaiveDate> = start.iter_days().take_while(|d| *d <= end).collect();
+        let mut totals = vec![Decimal::ZERO; days.len()];
+        for account in self.repository.get_accounts(false)? {
+            let snapshots = match snapshots_by_account.remove(&account.id) {
+                Some(snapshots) => snapshots,
+                None => continue,
+            };
+            let rate = self.conversion_rate(&account.currency, currency)?;
+            for (date, balance) in daily_series(snapshots, start, end) {
+                totals[(date - start).num_days() as usize] += balance * rate;
+            }
+        }
+
+        Ok(days
+            .into_iter()
+            .zip(totals)
+            .map(|(date, total)| (date, total.round_dp(2)))
+            .collect())
+    }
+
+    /// Multiplier converting amounts in `from` to `to`
+    fn conversion_rate(&self, from: &str, to: &str) -> Result<Decimal> {
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        if from == to {
+            return Ok(Decimal::ONE);
+        }
+        let rate = |currency: &str| {
+            self.exchange_rates
+                .get(currency)
+                .copied()
+                .filter(|rate| !rate.is_zero())
+                .ok_or_else(|| anyhow::anyhow!("No exchange rate configured for {}", currency))
+        };
+        Ok(rate(&from)? / rate(&to)?)
+    }
+
+    /// Compare balance snapshots against balances derived from transactions
+    ///
+    /// The earliest snapshot is the anchor. For every later snapshot, the expected
+    /// balance is the anchor plus all transactions after the anchor date up to and
+    /// including the snapshot date. Discrepancies above the tolerance are flagged.
+    pub fn reconcile(&self, account_id: &str) -> Result<ReconcileReport> {
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+
+        let tolerance = Decimal::new(1, 2); // 0.01
+
+        // Oldest first so the anchor comes first
+        let mut snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+        snapshots.sort_by_key(|s| s.snapshot_time);
+
+        let mut report = ReconcileReport {
+            account_id: account_id.to_string(),
+            account_name: account.name,
+            tolerance,
+            anchor_date: None,
+            anchor_balance: None,
+            entries: Vec::new(),
+            flagged: 0,
+        };
+
+        let anchor = match snapshots.first() {
+            Some(anchor) => anchor,
+            None => return Ok(report),
+        };
+        let anchor_date = anchor.snapshot_time.date();
+        report.anchor_date = Some(anchor_date.to_string());
+        report.anchor_balance = Some(anchor.balance);
+
+        // Transactions on the anchor date are assumed to be included in the anchor
+        let mut transactions: Vec<(NaiveDate, Decimal)> = self
+            .repository
+            .get_transactions_by_account(account_id)?
+            .into_iter()
+            .map(|tx| (tx.reporting_date(self.date_basis), tx.amount))
+            .filter(|(date, _)| *date > anchor_date)
+            .collect();
+        transactions.sort_by_key(|(date, _)| *date);
+
+        let mut running_total = Decimal::ZERO;
+        let mut tx_iter = transactions.iter().peekable();
+        let mut previous_discrepancy = Decimal::ZERO;
+
+        for snapshot in snapshots.iter().skip(1) {
+            let snapshot_date = snapshot.snapshot_time.date();
+            while let Some((date, amount)) = tx_iter.peek() {
+                if *date > snapshot_date {
+                    break;
+                }
+                running_total += *amount;
+                tx_iter.next();
+            }
+
+            let expected = anchor.balance + running_total;
+            let discrepancy = snapshot.balance - expected;
+            let flagged = discrepancy.abs() > tolerance;
+            if flagged {
+                report.flagged += 1;
+            }
+
+            report.entries.push(ReconcileEntry {
+                date: snapshot_date.to_string(),
+                snapshot_balance: snapshot.balance,
+                expected_balance: expected,
+                discrepancy,
+                new_drift: discrepancy - previous_discrepancy,
+                source: snapshot.source.clone(),
+                flagged,
+            });
+            previous_discrepancy = discrepancy;
+        }
+
+        Ok(report)
+    }
+}
+
+/// Timestamp of a manual snapshot, so it is the day's final balance
+fn end_of_day(date: NaiveDate) -> NaiveDateTime {
+    NaiveDateTime::new(
+        date,
+        NaiveTime::from_hms_micro_opt(23, 59, 59, 999999).unwrap(),
+    )
+}
+
+/// Step-interpolated daily balances from one account's snapshots,
+/// as described on [`BalanceService::daily_balances`]
+fn daily_series(
+    mut snapshots: Vec<BalanceSnapshot>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<(NaiveDate, Decimal)> {
+    snapshots.sort_by_key(|s| s.snapshot_time);
+
+    let mut result = Vec::new();
+    let mut snapshot_iter = snapshots.iter().peekable();
+    let mut current: Option<Decimal> = None;
+
+    for date in start.iter_days().take_while(|d| *d <= end) {
+        while let Some(snapshot) = snapshot_iter.peek() {
+            if snapshot.snapshot_time.date() > date {
+                break;
+            }
+            current = Some(snapshot.balance);
+            snapshot_iter.next();
+        }
+
+        if let Some(balance) = current {
+            result.push((date, balance));
+        }
+    }
+
+    result
 }
 
 #[derive(Debug, Serialize)]
@@ -382,3 +710,34 @@ pub struct BackfillExecuteResult {
     pub snapshots_updated: i64,
     pub snapshots_skipped: i64,
 }
+
+/// Snapshot-vs-transactions reconciliation for one account
+#[derive(Debug, Serialize)]
+pub struct ReconcileReport {
+    pub account_id: String,
+    pub account_name: String,
+    /// Discrepancies larger than this are flagged
+    pub tolerance: Decimal,
+    /// Date of the earliest snapshot, used as the starting balance
+    pub anchor_date: Option<String>,
+    pub anchor_balance: Option<Decimal>,
+    /// One entry per snapshot after the anchor, oldest first
+    pub entries: Vec<ReconcileEntry>,
+    /// Number of flagged entries
+    pub flagged: i64,
+}
+
+/// Reconciliation of a single balance snapshot
+#[derive(Debug, Serialize)]
+pub struct ReconcileEntry {
+    pub date: String,
+    pub snapshot_balance: Decimal,
+    /// Anchor balance plus transactions up to this date
+    pub expected_balance: Decimal,
+    /// snapshot_balance - expected_balance
+    pub discrepancy: Decimal,
+    /// Change in discrepancy since the previous snapshot - shows where drift started
+    pub new_drift: Decimal,
+    pub source: Option<String>,
+    pub flagged: bool,
+}
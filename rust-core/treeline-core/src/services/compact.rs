@@ -23,17 +23,32 @@ impl CompactService {
 
         self.repository.compact()?;
 
+        // Plugin views live outside the core schema; make sure they are all back
+        let plugin_views = self.repository.recreate_plugin_views()?;
+
         let compacted_size = self.repository.get_db_size()?;
 
         Ok(CompactResult {
             original_size,
             compacted_size,
+            plugin_views,
         })
     }
+
+    /// Remove near-duplicate balance snapshots left by repeated syncs
+    ///
+    /// Keeps one snapshot per account per day: the one from the most trusted
+    /// source (manual > csv_import > sync), latest first. Manual snapshots are
+    /// always kept. Returns the number of snapshots deleted.
+    pub fn dedupe_snapshots(&self) -> Result<usize> {
+        self.repository.dedupe_balance_snapshots()
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct CompactResult {
     pub original_size: u64,
     pub compacted_size: u64,
+    /// Plugin views recreated after compaction
+    pub plugin_views: usize,
 }
@@ -7,13 +7,48 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
 
-use crate::adapters::demo::{
-    generate_demo_accounts, generate_demo_balance_snapshots, generate_demo_transactions,
-};
+use crate::adapters::demo::generate_demo_data;
 use crate::adapters::duckdb::DuckDbRepository;
 use crate::config::Config;
 
+/// Shape of the generated demo data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemoOptions {
+    /// Number of accounts; past the six built-in ones they repeat with numbered names
+    pub accounts: usize,
+    /// Days of history, ending today
+    pub days: u32,
+    /// Everyday card spending per day, as a multiple of the default
+    /// pattern (roughly two transactions a day)
+    pub density: u32,
+    /// Random seed; the same options always produce the same data
+    pub seed: u64,
+}
+
+impl Default for DemoOptions {
+    fn default() -> Self {
+        Self {
+            accounts: 6,
+            days: 180,
+            density: 1,
+            seed: 42,
+        }
+    }
+}
+
+/// What [`DemoService::generate`] created
+#[derive(Debug, Clone, Serialize)]
+pub struct GenStats {
+    pub accounts: usize,
+    pub transactions: usize,
+    pub balance_snapshots: usize,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
 /// Demo service for managing demo mode
 pub struct DemoService {
     treeline_dir: PathBuf,
@@ -32,13 +67,22 @@ impl DemoService {
         Ok(config.demo_mode)
     }
 
-    /// Enable demo mode
+    /// Enable demo mode with the default demo data
+    pub fn enable(&self) -> Result<()> {
+        self.generate(DemoOptions::default()).map(|_| ())
+    }
+
+    /// Enable demo mode with freshly generated demo data
     ///
     /// This will:
     /// 1. Delete any existing demo database (fresh start)
     /// 2. Enable demo mode in config
-    /// 3. Create demo database with sample data
-    pub fn enable(&self) -> Result<()> {
+    /// 3. Create demo database with sample data shaped by `options`
+    pub fn generate(&self, options: DemoOptions) -> Result<GenStats> {
+        if options.accounts == 0 || options.days == 0 || options.density == 0 {
+            anyhow::bail!("Demo accounts, days and density must all be at least 1");
+        }
+
         // Delete existing demo database for a fresh start
         let demo_db = self.treeline_dir.join("demo.duckdb");
         let demo_wal = self.treeline_dir.join("demo.duckdb.wal");
@@ -61,22 +105,25 @@ impl DemoService {
         // Add demo integration
         repository.upsert_integration("demo", &serde_json::json!({}))?;
 
-        // Add demo accounts
-        for account in generate_demo_accounts() {
-            repository.upsert_account(&account)?;
+        let today = Utc::now().date_naive();
+        let data = generate_demo_data(&options, today);
+        for account in &data.accounts {
+            repository.upsert_account(account)?;
         }
-
-        // Add demo transactions
-        for tx in generate_demo_transactions() {
-            repository.upsert_transaction(&tx)?;
+        for tx in &data.transactions {
+            repository.upsert_transaction(tx)?;
         }
-
-        // Add demo balance snapshots
-        for snapshot in generate_demo_balance_snapshots() {
-            let _ = repository.add_balance_snapshot(&snapshot);
+        for snapshot in &data.balance_snapshots {
+            let _ = repository.add_balance_snapshot(snapshot);
         }
 
-        Ok(())
+        Ok(GenStats {
+            accounts: data.accounts.len(),
+            transactions: data.transactions.len(),
+            balance_snapshots: data.balance_snapshots.len(),
+            start_date: today - chrono::Duration::days(i64::from(options.days) - 1),
+            end_date: today,
+        })
     }
 
     /// Disable demo mode
@@ -1,19 +1,25 @@
 //! Doctor service - database health checks
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
 use serde::Serialize;
 use serde_json::json;
 
 use crate::adapters::duckdb::DuckDbRepository;
+use crate::adapters::registry::ProviderRegistry;
+use crate::ports::ConnectionStatus;
+use crate::services::{MergeReport, MigrationStatusEntry, PluginService};
 
 /// Doctor service for health checks
 pub struct DoctorService {
     repository: Arc<DuckDbRepository>,
-    #[allow(dead_code)]
     treeline_dir: PathBuf,
+    registry: ProviderRegistry,
 }
 
 impl DoctorService {
@@ -21,13 +27,151 @@ impl DoctorService {
         Self {
             repository,
             treeline_dir,
+            registry: ProviderRegistry::with_builtin(),
         }
     }
 
+    /// Check integrations with these providers instead of the built-in ones
+    pub fn with_registry(mut self, registry: ProviderRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Check every integration's credentials against its provider
+    ///
+    /// Makes one network request per integration. Settings the provider
+    /// can't use at all (e.g. a missing API key) count as failed auth.
+    pub fn check_connections(&self) -> Result<Vec<IntegrationHealth>> {
+        let integrations = self.repository.get_integrations()?;
+        Ok(integrations
+            .into_iter()
+            .map(|integration| {
+                let (status, message) = match self.registry.setup_provider(&integration.name) {
+                    Some(provider) => match provider.check_connection(&integration.settings) {
+                        Ok(health) => (health.status, health.message),
+                        Err(e) => (ConnectionStatus::AuthFailed, e.to_string()),
+                    },
+                    None => (
+                        ConnectionStatus::Unchecked,
+                        format!("Unknown provider: {}", integration.name),
+                    ),
+                };
+                IntegrationHealth {
+                    integration: integration.name,
+                    status,
+                    message,
+                }
+            })
+            .collect())
+    }
+
+    /// List embedded migrations and whether each has been applied
+    pub fn migration_status(&self) -> Result<Vec<MigrationStatusEntry>> {
+        self.repository.migration_status()
+    }
+
+    /// Find active accounts that look like the same real account
+    ///
+    /// Accounts match when their name, institution and currency agree,
+    /// ignoring case and extra whitespace - typically one bank account
+    /// linked through two providers, which double-counts its balance.
+    pub fn check_duplicate_accounts(&self) -> Result<Vec<DuplicateAccountGroup>> {
+        let activity = self.repository.get_account_activity()?;
+
+        let mut groups: BTreeMap<(String, String, String), Vec<DuplicateAccount>> = BTreeMap::new();
+        for account in self.repository.get_accounts(false)? {
+            let key = (
+                normalize_name(&account.name),
+                normalize_name(account.institution_name.as_deref().unwrap_or("")),
+                account.currency.to_uppercase(),
+            );
+            let id = account.id.to_string();
+            let (last_activity, transaction_count) = match activity.get(&id) {
+                Some((date, count)) => (Some(*date), *count),
+                None => (None, 0),
+            };
+            groups.entry(key).or_default().push(DuplicateAccount {
+                account_id: id,
+                name: account.name,
+                institution_name: account.institution_name,
+                balance: account.balance,
+                last_activity,
+                transaction_count,
+            });
+        }
+
+        Ok(groups
+            .into_iter()
+            .filter(|(_, accounts)| accounts.len() > 1)
+            .map(|((name, institution, currency), accounts)| {
+                // Most recent activity wins, then the fuller history
+                let suggested_keep = accounts
+                    .iter()
+                    .rev()
+                    .max_by_key(|a| (a.last_activity, a.transaction_count))
+                    .map(|a| a.account_id.clone())
+                    .unwrap_or_default();
+                DuplicateAccountGroup {
+                    name,
+                    institution,
+                    currency,
+                    accounts,
+                    suggested_keep,
+                }
+            })
+            .collect())
+    }
+
+    /// Merge duplicate accounts into `keep`
+    ///
+    /// Each account in `remove` has its transactions and balance snapshots
+    /// moved to `keep` and is then deleted. All accounts are checked before
+    /// anything is merged.
+    pub fn merge_accounts(&self, keep: &str, remove: &[String]) -> Result<Vec<MergeReport>> {
+        if remove.iter().any(|id| id == keep) {
+            anyhow::bail!("Cannot merge an account into itself");
+        }
+        for id in std::iter::once(keep).chain(remove.iter().map(String::as_str)) {
+            if self.repository.get_account_by_id(id)?.is_none() {
+                anyhow::bail!("Account not found: {}", id);
+            }
+        }
+
+        remove
+            .iter()
+            .map(|id| self.repository.merge_accounts(id, keep))
+            .collect()
+    }
+
     /// Run all health checks
     pub fn run_checks(&self) -> Result<DoctorResult> {
         let mut checks = std::collections::HashMap::new();
 
+        // Database integrity - file readable and all core tables present
+        let problems = self.repository.check_integrity()?;
+        checks.insert(
+            "database_integrity".to_string(),
+            CheckResult {
+                status: if problems.is_empty() { "pass" } else { "error" }.to_string(),
+                message: if problems.is_empty() {
+                    "Database file and schema are intact".to_string()
+                } else {
+                    format!("{} integrity problem(s) found", problems.len())
+                },
+                details: if problems.is_empty() {
+                    None
+                } else {
+                    Some(problems.iter().map(|p| json!({ "problem": p })).collect())
+                },
+            },
+        );
+
+        // The remaining checks query core tables - if any are missing they would
+        // fail with an opaque SQL error, so report the integrity problem alone
+        if !problems.is_empty() {
+            return Ok(Self::summarize(checks));
+        }
+
         // Orphaned transactions
         let orphaned_txs = self.repository.check_orphaned_transactions()?;
         let orphan_details: Vec<serde_json::Value> = orphaned_txs
@@ -230,43 +374,156 @@ impl DoctorService {
             );
         }
 
-        // Integration connectivity - test via dry-run sync
-        let integrations = self.repository.get_integrations()?;
-        if integrations.is_empty() {
-            checks.insert(
-                "integration_connectivity".to_string(),
-                CheckResult {
-                    status: "pass".to_string(),
-                    message: "No integrations configured".to_string(),
-                    details: None,
+        // Duplicate accounts - the same account linked twice double-counts net worth
+        let duplicates = self.check_duplicate_accounts()?;
+        let duplicate_details: Vec<serde_json::Value> = duplicates
+            .iter()
+            .flat_map(|group| {
+                group.accounts.iter().map(move |a| {
+                    let suggestion = if a.account_id == group.suggested_keep {
+                        "keep"
+                    } else {
+                        "merge"
+                    };
+                    json!({
+                        "group": group.name,
+                        "account_id": a.account_id,
+                        "balance": a.balance,
+                        "last_activity": a.last_activity,
+                        "suggestion": suggestion
+                    })
+                })
+            })
+            .collect();
+        checks.insert(
+            "duplicate_accounts".to_string(),
+            CheckResult {
+                status: if duplicates.is_empty() {
+                    "pass"
+                } else {
+                    "warning"
+                }
+                .to_string(),
+                message: if duplicates.is_empty() {
+                    "No duplicate accounts found".to_string()
+                } else {
+                    format!(
+                        "{} account(s) appear more than once; merge with `tl account merge`",
+                        duplicates.len()
+                    )
                 },
-            );
-        } else {
-            // For now, just report integrations are configured
-            // Full implementation would do a dry-run sync to test connectivity
-            checks.insert(
-                "integration_connectivity".to_string(),
-                CheckResult {
-                    status: "pass".to_string(),
-                    message: format!("All {} integration(s) connected", integrations.len()),
-                    details: None,
+                details: if duplicates.is_empty() {
+                    None
+                } else {
+                    Some(duplicate_details)
                 },
-            );
+            },
+        );
+
+        // Plugin schemas - tables a plugin declares must exist with the declared columns
+        let plugins =
+            PluginService::new_with_repository(&self.treeline_dir, self.repository.clone());
+        let mut schema_problems = Vec::new();
+        for plugin in plugins.list_plugins()? {
+            match plugins.validate_schema(&plugin.id) {
+                Ok(problems) => schema_problems.extend(
+                    problems
+                        .into_iter()
+                        .map(|p| json!({ "plugin": plugin.id, "problem": p })),
+                ),
+                Err(e) => {
+                    schema_problems.push(json!({ "plugin": plugin.id, "problem": e.to_string() }))
+                }
+            }
         }
+        checks.insert(
+            "plugin_schemas".to_string(),
+            CheckResult {
+                status: if schema_problems.is_empty() {
+                    "pass"
+                } else {
+                    "warning"
+                }
+                .to_string(),
+                message: if schema_problems.is_empty() {
+                    "Plugin tables match their manifests".to_string()
+                } else {
+                    format!("{} plugin schema problem(s) found", schema_problems.len())
+                },
+                details: if schema_problems.is_empty() {
+                    None
+                } else {
+                    Some(schema_problems)
+                },
+            },
+        );
 
-        // Calculate summary
+        // Integration connectivity - failed auth needs the user to reconnect,
+        // rate limits and outages usually clear up on their own
+        let connections = self.check_connections()?;
+        let auth_failures = connections
+            .iter()
+            .filter(|c| c.status == ConnectionStatus::AuthFailed)
+            .count();
+        let degraded = connections
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c.status,
+                    ConnectionStatus::RateLimited | ConnectionStatus::Unreachable
+                )
+            })
+            .count();
+        checks.insert(
+            "integration_connectivity".to_string(),
+            CheckResult {
+                status: if auth_failures > 0 {
+                    "error"
+                } else if degraded > 0 {
+                    "warning"
+                } else {
+                    "pass"
+                }
+                .to_string(),
+                message: if connections.is_empty() {
+                    "No integrations configured".to_string()
+                } else if auth_failures > 0 {
+                    format!("{} integration(s) failed authentication", auth_failures)
+                } else if degraded > 0 {
+                    format!("{} integration(s) could not be checked", degraded)
+                } else {
+                    format!("All {} integration(s) connected", connections.len())
+                },
+                details: if connections.is_empty() {
+                    None
+                } else {
+                    Some(
+                        connections
+                            .iter()
+                            .map(|c| serde_json::to_value(c).unwrap_or_default())
+                            .collect(),
+                    )
+                },
+            },
+        );
+
+        Ok(Self::summarize(checks))
+    }
+
+    /// Build the final result, counting checks by status
+    fn summarize(checks: std::collections::HashMap<String, CheckResult>) -> DoctorResult {
         let passed = checks.values().filter(|c| c.status == "pass").count() as i64;
         let warnings = checks.values().filter(|c| c.status == "warning").count() as i64;
         let errors = checks.values().filter(|c| c.status == "error").count() as i64;
 
-        Ok(DoctorResult {
+        DoctorResult {
             checks,
             summary: DoctorSummary {
                 passed,
                 warnings,
                 errors,
             },
-        })
+        }
     }
 }
 
@@ -284,9 +541,51 @@ pub struct CheckResult {
     pub details: Option<Vec<serde_json::Value>>,
 }
 
+/// Accounts that look like the same real account
+#[derive(Debug, Serialize)]
+pub struct DuplicateAccountGroup {
+    /// Normalized account name shared by the group
+    pub name: String,
+    /// Normalized institution name, empty when unknown
+    pub institution: String,
+    pub currency: String,
+    pub accounts: Vec<DuplicateAccount>,
+    /// Account with the most recent activity, to merge the others into
+    pub suggested_keep: String,
+}
+
+/// One member of a [`DuplicateAccountGroup`]
+#[derive(Debug, Serialize)]
+pub struct DuplicateAccount {
+    pub account_id: String,
+    pub name: String,
+    pub institution_name: Option<String>,
+    /// Latest balance snapshot
+    pub balance: Option<Decimal>,
+    /// Date of the most recent transaction
+    pub last_activity: Option<NaiveDate>,
+    pub transaction_count: i64,
+}
+
+/// Connection health of one integration
+#[derive(Debug, Serialize)]
+pub struct IntegrationHealth {
+    pub integration: String,
+    pub status: ConnectionStatus,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DoctorSummary {
     pub passed: i64,
     pub warnings: i64,
     pub errors: i64,
 }
+
+/// Lowercase and collapse whitespace so near-identical names compare equal
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
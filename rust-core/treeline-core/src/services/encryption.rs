@@ -4,13 +4,15 @@
 
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use base64::Engine;
 use duckdb::Connection;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 
-use crate::domain::{EncryptionMetadata, EncryptionStatus};
+use crate::domain::{Argon2Params, EncryptionDetails, EncryptionMetadata, EncryptionStatus};
 
 /// Default Argon2 parameters matching Python CLI
 const DEFAULT_TIME_COST: u32 = 3;
@@ -18,10 +20,16 @@ const DEFAULT_MEMORY_COST: u32 = 65536; // 64 MiB
 const DEFAULT_PARALLELISM: u32 = 4;
 const DEFAULT_HASH_LEN: u32 = 32;
 
+/// Upper bounds for `tune_params`, so unlocking stays possible on smaller machines
+const MAX_TUNED_MEMORY_COST: u32 = 524288; // 512 MiB
+const MAX_TUNED_TIME_COST: u32 = 10;
+
 /// Encryption service for database encryption
 pub struct EncryptionService {
     treeline_dir: PathBuf,
     db_path: PathBuf,
+    /// Used for the next `encrypt`; existing databases keep the params in their metadata
+    argon2_params: Argon2Params,
 }
 
 impl EncryptionService {
@@ -29,20 +37,72 @@ impl EncryptionService {
         Self {
             treeline_dir,
             db_path,
+            argon2_params: Argon2Params {
+                time_cost: DEFAULT_TIME_COST,
+                memory_cost: DEFAULT_MEMORY_COST,
+                parallelism: DEFAULT_PARALLELISM,
+                hash_len: DEFAULT_HASH_LEN,
+            },
         }
     }
 
+    /// Derive keys with these params the next time the database is encrypted
+    ///
+    /// Typically the result of `tune_params`. Databases that are already
+    /// encrypted are unaffected: their key is always derived with the params
+    /// stored in their metadata, so new params take effect after a decrypt and
+    /// encrypt.
+    pub fn with_argon2_params(mut self, params: Argon2Params) -> Self {
+        self.argon2_params = params;
+        self
+    }
+
+    /// Pick Argon2 params that take roughly `target_ms` to derive a key here
+    ///
+    /// Starts from the defaults, which are also the minimum, and doubles the
+    /// memory cost while a derivation stays under half the target, then
+    /// raises the time cost to fill the rest. Memory stops at 512 MiB and the
+    /// time cost at 10 passes. Nothing is changed; pass the result to
+    /// `with_argon2_params` to use it.
+    pub fn tune_params(&self, target_ms: u64) -> Result<Argon2Params> {
+        use rand::Rng;
+        let salt: [u8; 16] = rand::thread_rng().gen();
+        let target = Duration::from_millis(target_ms);
+        let measure = |params: &Argon2Params| -> Result<Duration> {
+            let start = Instant::now();
+            self.derive_key("benchmark", &salt, params)?;
+            Ok(start.elapsed())
+        };
+
+        let mut params = Argon2Params {
+            time_cost: DEFAULT_TIME_COST,
+            memory_cost: DEFAULT_MEMORY_COST,
+            parallelism: DEFAULT_PARALLELISM,
+            hash_len: DEFAULT_HASH_LEN,
+        };
+        let mut elapsed = measure(&params)?;
+
+        while elapsed * 2 <= target && params.memory_cost * 2 <= MAX_TUNED_MEMORY_COST {
+            params.memory_cost *= 2;
+            elapsed = measure(&params)?;
+        }
+
+        // Derivation time grows about linearly with the number of passes
+        if !elapsed.is_zero() && elapsed < target {
+            let scale = target.as_secs_f64() / elapsed.as_secs_f64();
+            let passes = (params.time_cost as f64 * scale).floor() as u32;
+            params.time_cost = passes.clamp(DEFAULT_TIME_COST, MAX_TUNED_TIME_COST);
+        }
+
+        Ok(params)
+    }
+
     fn encryption_file(&self) -> PathBuf {
         self.treeline_dir.join("encryption.json")
     }
 
     /// Derive encryption key from password using Argon2id
-    fn derive_key(
-        &self,
-        password: &str,
-        salt: &[u8],
-        params: &crate::domain::Argon2Params,
-    ) -> Result<Vec<u8>> {
+    fn derive_key(&self, password: &str, salt: &[u8], params: &Argon2Params) -> Result<Vec<u8>> {
         let argon2_params = argon2::Params::new(
             params.memory_cost,
             params.time_cost,
@@ -78,6 +138,30 @@ impl EncryptionService {
         Ok(EncryptionStatus::from_metadata(&metadata))
     }
 
+    /// Encryption details: key derivation parameters and a salt fingerprint
+    ///
+    /// Never includes the salt itself or anything derived from the password.
+    pub fn metadata(&self) -> Result<EncryptionDetails> {
+        let enc_file = self.encryption_file();
+        if !enc_file.exists() {
+            return Ok(EncryptionDetails::unencrypted());
+        }
+
+        let content = fs::read_to_string(&enc_file)?;
+        let metadata: EncryptionMetadata = serde_json::from_str(&content)?;
+        let salt = base64::engine::general_purpose::STANDARD
+            .decode(&metadata.salt)
+            .context("Invalid salt in encryption metadata")?;
+
+        Ok(EncryptionDetails {
+            encrypted: metadata.encrypted,
+            algorithm: Some(metadata.algorithm),
+            version: Some(metadata.version),
+            argon2_params: Some(metadata.argon2_params),
+            salt_fingerprint: Some(hex::encode(&Sha256::digest(&salt)[..8])),
+        })
+    }
+
     /// Check if database is encrypted
     pub fn is_encrypted(&self) -> Result<bool> {
         let status = self.get_status()?;
@@ -106,6 +190,39 @@ impl EncryptionService {
         Ok(hex::encode(&key))
     }
 
+    /// Check a password against the encrypted database without changing anything
+    ///
+    /// Derives the key and opens the database read-only with it. Returns
+    /// false for a wrong password; errors if the database isn't encrypted.
+    pub fn verify_password(&self, password: &str) -> Result<bool> {
+        let key_hex = self.derive_key_for_connection(password)?;
+        Ok(self.key_opens_database(&key_hex))
+    }
+
+    /// Whether `key_hex` can attach and read the encrypted database
+    fn key_opens_database(&self, key_hex: &str) -> bool {
+        // IMPORTANT: Disable extension autoloading to avoid macOS code signing issues
+        let attempt = || -> Result<()> {
+            let config = duckdb::Config::default().enable_autoload_extension(false)?;
+            let conn = Connection::open_in_memory_with_flags(config)?;
+            conn.execute_batch(&format!(
+                "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}', READ_ONLY)",
+                self.db_path.display(),
+                key_hex
+            ))?;
+
+            // Try to read something to verify
+            conn.execute_batch("USE enc")?;
+            conn.query_row(
+                "SELECT table_name FROM information_schema.tables LIMIT 1",
+                [],
+                |_| Ok(()),
+            )?;
+            Ok(())
+        };
+        attempt().is_ok()
+    }
+
     /// Enable encryption
     pub fn encrypt(
         &self,
@@ -129,13 +246,7 @@ impl EncryptionService {
         let salt: [u8; 16] = rand::thread_rng().gen();
         let salt_b64 = base64::engine::general_purpose::STANDARD.encode(&salt);
 
-        // Create Argon2 params
-        let argon2_params = crate::domain::Argon2Params {
-            time_cost: DEFAULT_TIME_COST,
-            memory_cost: DEFAULT_MEMORY_COST,
-            parallelism: DEFAULT_PARALLELISM,
-            hash_len: DEFAULT_HASH_LEN,
-        };
+        let argon2_params = self.argon2_params.clone();
 
         // Derive key
         let key = self.derive_key(password, &salt, &argon2_params)?;
@@ -235,29 +346,8 @@ impl EncryptionService {
         let key_hex = hex::encode(&key);
 
         // Verify password by attempting to read the encrypted database
-        // IMPORTANT: Disable extension autoloading to avoid macOS code signing issues
-        {
-            let config = duckdb::Config::default()
-                .enable_autoload_extension(false)
-                .context("Failed to configure database")?;
-            let conn = Connection::open_in_memory_with_flags(config)
-                .context("Failed to open in-memory connection")?;
-            conn.execute_batch(&format!(
-                "ATTACH '{}' AS enc (ENCRYPTION_KEY '{}', READ_ONLY)",
-                self.db_path.display(),
-                key_hex
-            ))
-            .map_err(|_| anyhow::anyhow!("Invalid password"))?;
-
-            // Try to read something to verify
-            conn.execute_batch("USE enc")
-                .map_err(|_| anyhow::anyhow!("Invalid password"))?;
-            conn.query_row(
-                "SELECT table_name FROM information_schema.tables LIMIT 1",
-                [],
-                |_| Ok(()),
-            )
-            .map_err(|_| anyhow::anyhow!("Invalid password"))?;
+        if !self.key_opens_database(&key_hex) {
+            anyhow::bail!("Invalid password");
         }
 
         // Create backup first
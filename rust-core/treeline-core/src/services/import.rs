@@ -1,11 +1,13 @@
 //! Import service - CSV transaction import
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use encoding_rs::{Encoding, UTF_8};
 use regex::Regex;
 use rust_decimal::Decimal;
 use serde::Serialize;
@@ -14,11 +16,13 @@ use uuid::Uuid;
 
 use crate::adapters::duckdb::DuckDbRepository;
 use crate::config::{ColumnMappings, Config, ImportOptions as ConfigImportOptions, ImportProfile};
-use crate::domain::{BalanceSnapshot, Transaction};
+pub use crate::domain::BatchInfo;
+use crate::domain::{Account, BalanceSnapshot, Transaction};
 use crate::services::TagService;
 
 /// Number format for parsing amounts
-#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NumberFormat {
     /// US format: 1,234.56 (comma=thousands, dot=decimal)
     #[default]
@@ -39,6 +43,38 @@ impl NumberFormat {
     }
 }
 
+/// Parse a field delimiter name: `tab`, `,`, `;` or `|`
+pub fn parse_delimiter(s: &str) -> Option<u8> {
+    match s.trim() {
+        t if t.eq_ignore_ascii_case("tab") || t == "\\t" => Some(b'\t'),
+        "," => Some(b','),
+        ";" => Some(b';'),
+        "|" => Some(b'|'),
+        _ => None,
+    }
+}
+
+/// Name of a field delimiter that `parse_delimiter` accepts, e.g. `tab`
+fn delimiter_name(delimiter: u8) -> String {
+    match delimiter {
+        b'\t' => "tab".to_string(),
+        other => (other as char).to_string(),
+    }
+}
+
+fn serialize_delimiter<S: serde::Serializer>(
+    delimiter: &Option<u8>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match delimiter {
+        Some(delimiter) => serializer.serialize_str(&delimiter_name(*delimiter)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Data rows `detect_columns` looks at to guess the number format
+const FORMAT_SAMPLE_ROWS: usize = 50;
+
 /// Import options for CSV processing
 #[derive(Debug, Default)]
 pub struct ImportOptions {
@@ -50,6 +86,18 @@ pub struct ImportOptions {
     pub skip_rows: u32,
     /// Number format for parsing amounts
     pub number_format: NumberFormat,
+    /// Field delimiter; auto-detected from the header row when unset
+    pub delimiter: Option<u8>,
+    /// Text encoding label such as "windows-1252" or "latin1" (default UTF-8)
+    pub encoding: Option<String>,
+    /// chrono format for the date column, used instead of guessing (e.g. "%d/%m/%Y")
+    pub date_format: Option<String>,
+    /// Split category values into several tags on this separator
+    /// (unset = each value is a single tag)
+    pub category_separator: Option<String>,
+    /// With an account column mapped, create accounts for names that match
+    /// none (otherwise their rows are skipped)
+    pub create_missing_accounts: bool,
     /// Anchor balance for calculating historical balances (preview only)
     pub anchor_balance: Option<Decimal>,
     /// Anchor date for the anchor balance (preview only)
@@ -79,7 +127,39 @@ impl ImportService {
         Ok(config.import_profiles)
     }
 
+    /// Account ID to import into, from an ID, ID prefix, name or nickname
+    ///
+    /// Falls back to the configured `default_import_account` when `account`
+    /// is None. Names and nicknames match case-insensitively; see
+    /// [`DuckDbRepository::resolve_account`].
+    pub fn resolve_account(&self, account: Option<&str>) -> Result<String> {
+        let account = match account {
+            Some(account) => account.to_string(),
+            None => Config::load(&self.treeline_dir)?
+                .default_import_account
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No account given and no default import account configured (app.defaultImportAccount)"
+                    )
+                })?,
+        };
+
+        let found = self
+            .repository
+            .resolve_account(&account)?
+            .into_account(&account)?;
+        Ok(found.id.to_string())
+    }
+
     /// Import transactions from CSV
+    ///
+    /// The file is imported all or nothing: if storing any row or balance
+    /// snapshot fails, none of them are kept.
+    ///
+    /// With an account column mapped, each row goes to the account named in
+    /// it (by name or nickname, ignoring case) and `account_id` only takes
+    /// rows whose cell is blank. See [`ImportOptions::create_missing_accounts`]
+    /// for names no account has.
     pub fn import(
         &self,
         file_path: &Path,
@@ -87,6 +167,66 @@ impl ImportService {
         mappings: &ColumnMappings,
         options: &ImportOptions,
         preview_only: bool,
+    ) -> Result<ImportResult> {
+        self.import_file(
+            file_path,
+            account_id,
+            mappings,
+            options,
+            preview_only,
+            &mut HashSet::new(),
+        )
+    }
+
+    /// Import several CSV files with the same mappings, e.g. monthly exports
+    ///
+    /// Files are imported in order, each as its own batch, and results come
+    /// back in the same order; see [`ImportTotals::from_results`] for the
+    /// aggregate. A row already seen in an earlier file of the run counts as
+    /// a duplicate just like one already in the database. Stops at the first
+    /// file that fails; files before it stay imported.
+    pub fn import_many(
+        &self,
+        files: &[PathBuf],
+        account_id: &str,
+        mappings: &ColumnMappings,
+        options: &ImportOptions,
+    ) -> Result<Vec<ImportResult>> {
+        if files.is_empty() {
+            anyhow::bail!("No files to import");
+        }
+        if let Some(missing) = files.iter().find(|f| !f.is_file()) {
+            anyhow::bail!("File not found: {}", missing.display());
+        }
+
+        let mut seen_fingerprints = HashSet::new();
+        let mut results = Vec::with_capacity(files.len());
+        for file in files {
+            let result = self
+                .import_file(
+                    file,
+                    account_id,
+                    mappings,
+                    options,
+                    false,
+                    &mut seen_fingerprints,
+                )
+                .with_context(|| format!("Failed to import {}", file.display()))?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Import one file; `seen_fingerprints` holds rows from earlier files of the
+    /// same run, which count as duplicates, and gains this file's rows
+    fn import_file(
+        &self,
+        file_path: &Path,
+        account_id: &str,
+        mappings: &ColumnMappings,
+        options: &ImportOptions,
+        preview_only: bool,
+        seen_fingerprints: &mut HashSet<String>,
     ) -> Result<ImportResult> {
         // Verify account exists
         if self.repository.get_account_by_id(account_id)?.is_none() {
@@ -95,15 +235,15 @@ impl ImportService {
 
         let account_uuid = Uuid::parse_str(account_id).context("Invalid account ID")?;
 
+        if let Some(format) = &options.date_format {
+            validate_date_format(format)?;
+        }
+
+        let content = read_decoded(file_path, options.encoding.as_deref())?;
+
         // Read CSV with optional row skipping
         let (headers, records) = if options.skip_rows > 0 {
-            // Use raw reader to skip rows before header
-            use std::fs::File;
-            use std::io::{BufRead, BufReader};
-
-            let file = File::open(file_path).context("Failed to open CSV file")?;
-            let buf_reader = BufReader::new(file);
-            let mut lines = buf_reader.lines();
+            let mut lines = content.lines();
 
             // Skip leading rows
             for _ in 0..options.skip_rows {
@@ -111,29 +251,18 @@ impl ImportService {
             }
 
             // Read header line
-            let header_line = lines
-                .next()
-                .ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "No header row found after skipping {} rows",
-                        options.skip_rows
-                    )
-                })?
-                .context("Failed to read header line")?;
-
-            // Detect delimiter (semicolon common in EU, comma in US)
-            let semicolons = header_line.matches(';').count();
-            let commas = header_line.matches(',').count();
-            let tabs = header_line.matches('\t').count();
-            let delimiter = if semicolons > commas && semicolons > tabs {
-                b';'
-            } else if tabs > commas && tabs > semicolons {
-                b'\t'
-            } else {
-                b','
-            };
-
-            // Parse headers using csv crate with detected delimiter
+            let header_line = lines.next().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No header row found after skipping {} rows",
+                    options.skip_rows
+                )
+            })?;
+
+            let delimiter = options
+                .delimiter
+                .unwrap_or_else(|| detect_delimiter(header_line));
+
+            // Parse headers using csv crate with the chosen delimiter
             let mut header_reader = csv::ReaderBuilder::new()
                 .has_headers(false)
                 .delimiter(delimiter)
@@ -152,8 +281,7 @@ impl ImportService {
                 .collect();
 
             // Collect remaining lines as data
-            let remaining_content: String =
-                lines.filter_map(|l| l.ok()).collect::<Vec<_>>().join("\n");
+            let remaining_content: String = lines.collect::<Vec<_>>().join("\n");
 
             // Parse remaining content as CSV records with same delimiter
             let mut data_reader = csv::ReaderBuilder::new()
@@ -167,8 +295,11 @@ impl ImportService {
             (headers, records)
         } else {
             // Standard path: first row is header
-            let mut reader =
-                csv::Reader::from_path(file_path).context("Failed to read CSV file")?;
+            let mut builder = csv::ReaderBuilder::new();
+            if let Some(delimiter) = options.delimiter {
+                builder.delimiter(delimiter);
+            }
+            let mut reader = builder.from_reader(content.as_bytes());
 
             // Clean headers: trim and strip # prefix
             let headers: Vec<String> = reader
@@ -214,25 +345,67 @@ impl ImportService {
             .as_ref()
             .and_then(|d| headers.iter().position(|h| h == d.as_str()));
 
+        // Optional category column, turned into tags
+        let category_idx = mappings
+            .category
+            .as_ref()
+            .and_then(|c| headers.iter().position(|h| h == c.as_str()));
+
         // Optional balance column for running balance snapshots
         let balance_idx = mappings
             .balance
             .as_ref()
             .and_then(|b| headers.iter().position(|h| h == b.as_str()));
 
+        // Optional account column, for files covering several accounts
+        let account_idx = match &mappings.account {
+            Some(column) => Some(
+                headers
+                    .iter()
+                    .position(|h| h == column.as_str())
+                    .context(format!("Account column '{}' not found", column))?,
+            ),
+            None => None,
+        };
+        let mut router = match account_idx {
+            Some(_) => AccountRouter::new(&self.repository.get_accounts(true)?),
+            None => AccountRouter::default(),
+        };
+
         let mut transactions = Vec::new();
-        let mut skipped = 0;
-        // Track end-of-day balances: for each date, store the last balance seen
-        let mut end_of_day_balances: HashMap<NaiveDate, Decimal> = HashMap::new();
+        // File line of each parsed transaction, to report duplicates by row
+        let mut rows = Vec::new();
+        let mut skipped_rows = Vec::new();
+        // Lines before the data that the record positions don't count
+        let line_offset = if options.skip_rows > 0 {
+            options.skip_rows as usize + 1
+        } else {
+            0
+        };
+        // Rows whose date or amount didn't parse, to spot a wrong column mapping
+        let mut bad_dates = 0usize;
+        let mut bad_amounts = 0usize;
+        // Track end-of-day balances: per account and date, store the last balance seen
+        let mut end_of_day_balances: HashMap<Uuid, HashMap<NaiveDate, Decimal>> = HashMap::new();
         // Track per-row balance for preview display
         let mut preview_balances: Vec<Option<String>> = Vec::new();
 
-        for record in &records {
+        for (i, record) in records.iter().enumerate() {
+            let row = record
+                .position()
+                .map(|p| p.line() as usize + line_offset)
+                .unwrap_or(line_offset.max(1) + i + 1);
+            let mut skip = |reason| skipped_rows.push(SkippedRow { row, reason });
+
             // Parse date
             let date_str = record.get(date_idx).unwrap_or("");
-            let date = parse_date(date_str);
+            let date = match &options.date_format {
+                Some(format) => NaiveDate::parse_from_str(date_str.trim(), format).ok(),
+                None => parse_date(date_str),
+            };
             if date.is_none() {
-                skipped += 1;
+                skip(SkipReason::UnparseableDate);
+                bad_dates += 1;
                 continue;
             }
             let date = date.unwrap();
@@ -292,7 +465,8 @@ impl ImportService {
             };
 
             if amount.is_none() {
-                skipped += 1;
+                skip(SkipReason::MissingAmount);
+                bad_amounts += 1;
                 continue;
             }
 
@@ -303,19 +477,41 @@ impl ImportService {
                 amount = -amount;
             }
 
+            // Pick the row's account; a blank account cell means the given account
+            let row_account = match account_idx.and_then(|i| record.get(i)).map(str::trim) {
+                Some(name) if !name.is_empty() => {
+                    match router.route(name, options.create_missing_accounts) {
+                        Some(id) => id,
+                        None => {
+                            skip(SkipReason::UnknownAccount);
+                            continue;
+                        }
+                    }
+                }
+                _ => account_uuid,
+            };
+
             // Get description
             let description = desc_idx.and_then(|i| record.get(i)).map(|s| s.to_string());
 
             // Generate fingerprint for deduplication
-            let fingerprint =
-                generate_fingerprint(account_id, &date, &amount, description.as_deref());
-
-            let mut tx = Transaction::new(Uuid::new_v4(), account_uuid, amount, date);
+            let fingerprint = generate_fingerprint(
+                &row_account.to_string(),
+                &date,
+                &amount,
+                description.as_deref(),
+            );
+
+            let mut tx = Transaction::new(Uuid::new_v4(), row_account, amount, date);
             tx.description = description;
             // Use dedicated csv_fingerprint column for deduplication
             tx.csv_fingerprint = Some(fingerprint.clone());
+            if let Some(category) = category_idx.and_then(|i| record.get(i)) {
+                tx.tags = category_tags(category, options.category_separator.as_deref());
+            }
 
             transactions.push(tx);
+            rows.push(row);
 
             // Collect balance for end-of-day snapshot (if balance column is mapped)
             // We store the last balance seen for each date as we iterate through rows
@@ -326,7 +522,10 @@ impl ImportService {
                         parse_amount_with_format(balance_str, options.number_format)
                     {
                         // Overwrite - we want the last balance for each date in CSV order
-                        end_of_day_balances.insert(date, balance);
+                        end_of_day_balances
+                            .entry(row_account)
+                            .or_default()
+                            .insert(date, balance);
                         Some(balance.to_string())
                     } else {
                         None
@@ -344,8 +543,9 @@ impl ImportService {
         let discovered = transactions.len() as i64;
         let fingerprints_checked = discovered;
 
-        // Generate batch ID for this import
-        let batch_id = format!("import_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+        // Generate batch ID for this import, fine-grained enough that two
+        // imports in quick succession can still be undone separately
+        let batch_id = format!("import_{}", chrono::Utc::now().format("%Y%m%d_%H%M%S_%6f"));
 
         // For preview mode, return all parsed transactions without deduplication
         // User wants to see what's in the CSV, not what will be imported
@@ -423,14 +623,23 @@ impl ImportService {
             sorted_indices.sort_by_key(|&i| transactions[i].transaction_date);
             sorted_indices.reverse(); // Newest first
 
+            let warnings =
+                mapping_mismatch_warning(&headers, records.len(), bad_dates, bad_amounts)
+                    .into_iter()
+                    .chain(router.warnings())
+                    .collect();
+
             return Ok(ImportResult {
                 batch_id,
                 discovered,
                 imported: 0, // Not importing in preview
-                skipped: skipped as i64,
+                skipped: skipped_rows.len() as i64,
+                skipped_rows,
                 fingerprints_checked: 0,      // Not checking in preview
                 balance_snapshots_created: 0, // Not creating in preview
                 preview: true,
+                warnings,
+                accounts_created: router.created_names(),
                 transactions: Some(
                     sorted_indices
                         .iter()
@@ -450,21 +659,29 @@ impl ImportService {
 
         // Deduplicate: check which fingerprints already exist in csv_fingerprint column
         let mut new_transactions = Vec::new();
-        let mut duplicate_count = 0i64;
+        let mut file_fingerprints = Vec::new();
 
-        for tx in transactions {
+        for (tx, row) in transactions.into_iter().zip(rows) {
             if let Some(fp) = tx.csv_fingerprint.as_ref() {
-                // Check csv_fingerprint column for existing transactions
-                if self
-                    .repository
-                    .csv_fingerprint_exists_in_other_batches(fp, "")?
+                file_fingerprints.push(fp.clone());
+                // Check earlier files of this run, then the csv_fingerprint column
+                if seen_fingerprints.contains(fp)
+                    || self
+                        .repository
+                        .csv_fingerprint_exists_in_other_batches(fp, "")?
                 {
-                    duplicate_count += 1;
+                    skipped_rows.push(SkippedRow {
+                        row,
+                        reason: SkipReason::DuplicateFingerprint,
+                    });
                     continue;
                 }
             }
             new_transactions.push(tx);
         }
+        skipped_rows.sort_by_key(|s| s.row);
+        // Only after the loop, so repeated rows within one file are still kept
+        seen_fingerprints.extend(file_fingerprints);
 
         let imported = new_transactions.len() as i64;
 
@@ -477,53 +694,29 @@ impl ImportService {
         // Collect IDs for auto-tagging
         let new_tx_ids: Vec<Uuid> = new_transactions.iter().map(|tx| tx.id).collect();
 
-        for tx in &new_transactions {
-            self.repository.upsert_transaction(tx)?;
-        }
+        // Accounts, transactions and snapshots go in together, so a failure
+        // part way through leaves nothing behind
+        let balance_snapshots_created = self.repository.with_transaction(|| {
+            for account in &router.created {
+                self.repository.upsert_account(account)?;
+            }
+            for tx in &new_transactions {
+                self.repository.upsert_transaction(tx)?;
+            }
+            let mut created = 0;
+            for (id, balances) in &end_of_day_balances {
+                created +=
+                    self.create_balance_snapshots(&batch_id, &id.to_string(), *id, balances)?;
+            }
+            Ok(created)
+        })?;
 
         // Apply auto-tag rules to newly imported transactions
+        let mut warnings = router.warnings();
         if !new_tx_ids.is_empty() {
-            // Best-effort tagging - don't fail import if rules fail
-            let _ = self.tag_service.apply_auto_tag_rules(&new_tx_ids);
-        }
-
-        // Create balance snapshots from collected end-of-day balances
-        let mut balance_snapshots_created = 0i64;
-        if !end_of_day_balances.is_empty() {
-            // Get existing snapshots for deduplication
-            let existing_snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
-
-            for (date, balance) in &end_of_day_balances {
-                // Create end-of-day timestamp (23:59:59.999999)
-                let snapshot_time = NaiveDateTime::new(
-                    *date,
-                    NaiveTime::from_hms_micro_opt(23, 59, 59, 999999).unwrap(),
-                );
-
-                // Check for duplicate: same account + date + balance (within 0.01)
-                let is_duplicate = existing_snapshots.iter().any(|s| {
-                    s.snapshot_time.date() == *date
-                        && (s.balance - *balance).abs() < Decimal::new(1, 2)
-                });
-
-                if is_duplicate {
-                    continue;
-                }
-
-                let snapshot = BalanceSnapshot {
-                    id: Uuid::new_v4(),
-                    account_id: account_uuid,
-                    balance: *balance,
-                    snapshot_time,
-                    source: Some("csv_import".to_string()),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                };
-
-                // Best-effort - don't fail import if snapshot insert fails
-                if self.repository.add_balance_snapshot(&snapshot).is_ok() {
-                    balance_snapshots_created += 1;
-                }
+            // Best-effort tagging - the import stands, but say the rules failed
+            if let Err(e) = self.tag_service.apply_auto_tag_rules(&new_tx_ids) {
+                warnings.push(format!("Auto-tag rules failed: {:#}", e));
             }
         }
 
@@ -531,14 +724,89 @@ impl ImportService {
             batch_id,
             discovered,
             imported,
-            skipped: skipped + duplicate_count,
+            skipped: skipped_rows.len() as i64,
+            skipped_rows,
             fingerprints_checked,
             balance_snapshots_created,
             preview: false,
+            warnings,
+            accounts_created: router.created_names(),
             transactions: None,
         })
     }
 
+    /// Create end-of-day balance snapshots, skipping ones the account already has
+    ///
+    /// Returns the number of snapshots created.
+    fn create_balance_snapshots(
+        &self,
+        batch_id: &str,
+        account_id: &str,
+        account_uuid: Uuid,
+        end_of_day_balances: &HashMap<NaiveDate, Decimal>,
+    ) -> Result<i64> {
+        if end_of_day_balances.is_empty() {
+            return Ok(0);
+        }
+
+        // Get existing snapshots for deduplication
+        let existing_snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+
+        let mut balance_snapshots_created = 0i64;
+        for (date, balance) in end_of_day_balances {
+            // Create end-of-day timestamp (23:59:59.999999)
+            let snapshot_time = NaiveDateTime::new(
+                *date,
+                NaiveTime::from_hms_micro_opt(23, 59, 59, 999999).unwrap(),
+            );
+
+            // Check for duplicate: same account + date + balance (within 0.01)
+            let is_duplicate = existing_snapshots.iter().any(|s| {
+                s.snapshot_time.date() == *date && (s.balance - *balance).abs() < Decimal::new(1, 2)
+            });
+
+            if is_duplicate {
+                continue;
+            }
+
+            let snapshot = BalanceSnapshot {
+                id: Uuid::new_v4(),
+                account_id: account_uuid,
+                balance: *balance,
+                snapshot_time,
+                source: Some("csv_import".to_string()),
+                note: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            };
+
+            // Runs inside the import's transaction, which a failed insert aborts,
+            // so errors must propagate rather than be skipped
+            self.repository
+                .add_import_balance_snapshot(&snapshot, batch_id)?;
+            balance_snapshots_created += 1;
+        }
+
+        Ok(balance_snapshots_created)
+    }
+
+    /// Past imports that can still be undone, newest first
+    pub fn list_batches(&self) -> Result<Vec<BatchInfo>> {
+        self.repository.list_import_batches()
+    }
+
+    /// Undo an import by deleting every transaction it added
+    ///
+    /// Balance snapshots created from the file's balance column are removed
+    /// as well. Returns the number of transactions removed.
+    pub fn undo_batch(&self, batch_id: &str) -> Result<usize> {
+        let removed = self.repository.delete_import_batch(batch_id)?;
+        if removed == 0 {
+            anyhow::bail!("Import batch not found: {}", batch_id);
+        }
+        Ok(removed)
+    }
+
     /// Save an import profile
     pub fn save_profile(
         &self,
@@ -551,7 +819,7 @@ impl ImportService {
             name.to_string(),
             ImportProfile {
                 column_mappings: mappings.clone(),
-                date_format: None,
+                date_format: options.date_format.clone(),
                 skip_rows: 0,
                 options: ConfigImportOptions {
                     flip_signs: options.flip_signs,
@@ -572,98 +840,400 @@ impl ImportService {
     /// Auto-detect column mapping from CSV headers
     ///
     /// Returns best-guess mapping for date, amount, description, and optionally debit/credit columns.
-    /// Matches Python CLI behavior with same pattern matching.
+    /// Matches Python CLI behavior with same pattern matching, plus a confidence
+    /// score per detected field.
+    ///
+    /// Also guesses the delimiter from the header row and the number format
+    /// from the first rows of the amount (or debit/credit) columns, so a
+    /// preview can start from them.
     pub fn detect_columns(&self, file_path: &Path) -> Result<DetectedColumns> {
-        let mut reader = csv::Reader::from_path(file_path).context("Failed to read CSV file")?;
+        let content = read_decoded(file_path, None)?;
+        let delimiter = detect_delimiter(content.lines().next().unwrap_or_default());
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .from_reader(content.as_bytes());
 
         let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+        let samples: Vec<csv::StringRecord> = reader
+            .records()
+            .take(FORMAT_SAMPLE_ROWS)
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut detected = detect_columns_from_headers(headers);
+        let amount_indices: Vec<usize> = [&detected.amount, &detected.debit, &detected.credit]
+            .into_iter()
+            .flatten()
+            .filter_map(|column| detected.all_headers.iter().position(|h| h == column))
+            .collect();
+        let amounts = samples
+            .iter()
+            .flat_map(|record| amount_indices.iter().filter_map(|&i| record.get(i)));
+        detected.number_format = detect_number_format(amounts);
+        detected.delimiter = Some(delimiter);
 
-        let date_patterns = [
-            "date",
-            "transaction date",
-            "trans date",
-            "txn date",
-            "txndate",
-            "posted",
-            "post date",
-            "dt",
-        ];
-        let desc_patterns = [
-            "description",
-            "desc",
-            "memo",
-            "payee",
-            "merchant",
-            "details",
-            "narration",
-        ];
-        let amount_patterns = ["amount", "amt", "total", "transaction amount"];
-        let debit_patterns = ["debit", "dr", "withdrawal", "debit amount"];
-        let credit_patterns = ["credit", "cr", "deposit", "credit amount"];
-
-        let mut detected = DetectedColumns::default();
-
-        // Find date column
-        for header in &headers {
-            let header_lower = header.to_lowercase();
-            if date_patterns.iter().any(|p| header_lower.contains(p)) {
-                detected.date = Some(header.clone());
-                break;
-            }
-        }
+        Ok(detected)
+    }
+}
 
-        // Find amount column (prefer single amount column)
-        for header in &headers {
-            let header_lower = header.to_lowercase();
-            if amount_patterns.iter().any(|p| header_lower.contains(p)) {
-                detected.amount = Some(header.clone());
-                break;
-            }
-        }
+/// Warn when so many rows fail to parse that the mapping is probably wrong
+///
+/// Fires when more than half of the rows have an unparseable date or amount,
+/// and suggests the columns `detect_columns` would pick instead.
+fn mapping_mismatch_warning(
+    headers: &[String],
+    rows: usize,
+    bad_dates: usize,
+    bad_amounts: usize,
+) -> Option<String> {
+    if (bad_dates + bad_amounts) * 2 <= rows {
+        return None;
+    }
 
-        // If no 'amount' found, check for debit/credit
-        if detected.amount.is_none() {
-            for header in &headers {
-                let header_lower = header.to_lowercase();
-                if debit_patterns.iter().any(|p| header_lower.contains(p)) {
-                    detected.debit = Some(header.clone());
-                }
-                if credit_patterns.iter().any(|p| header_lower.contains(p)) {
-                    detected.credit = Some(header.clone());
-                }
+    let field = if bad_dates == 0 {
+        "amount"
+    } else if bad_amounts == 0 {
+        "date"
+    } else {
+        "date or amount"
+    };
+    let detected = detect_columns_from_headers(headers.to_vec());
+    let suggestions: Vec<String> = [
+        ("date", &detected.date),
+        ("amount", &detected.amount),
+        ("description", &detected.description),
+        ("debit", &detected.debit),
+        ("credit", &detected.credit),
+    ]
+    .into_iter()
+    .filter_map(|(name, column)| column.as_ref().map(|c| format!("{}='{}'", name, c)))
+    .collect();
+
+    let mut warning = format!(
+        "{} of {} rows have no valid {}; the column mapping is probably wrong",
+        bad_dates + bad_amounts,
+        rows,
+        field
+    );
+    if !suggestions.is_empty() {
+        warning.push_str(&format!(". Detected columns: {}", suggestions.join(", ")));
+    }
+    Some(warning)
+}
+
+/// Routes the rows of a multi-account file by their account column
+#[derive(Default)]
+struct AccountRouter {
+    /// Lowercased account names and nicknames, to the accounts that have them
+    by_name: HashMap<String, Vec<Uuid>>,
+    /// Accounts to create for names no existing account has
+    created: Vec<Account>,
+    /// Rows skipped per account cell, with the reason
+    skipped: BTreeMap<String, (usize, &'static str)>,
+}
+
+impl AccountRouter {
+    fn new(accounts: &[Account]) -> Self {
+        let mut by_name: HashMap<String, Vec<Uuid>> = HashMap::new();
+        for account in accounts {
+            let mut names = vec![account.name.trim().to_lowercase()];
+            names.extend(account.nickname.as_ref().map(|n| n.trim().to_lowercase()));
+            names.dedup();
+            for name in names {
+                by_name.entry(name).or_default().push(account.id);
             }
         }
+        Self {
+            by_name,
+            ..Self::default()
+        }
+    }
 
-        // Find description column
-        for header in &headers {
-            let header_lower = header.to_lowercase();
-            // Skip if this is the date column
-            if detected.date.as_ref() == Some(header) {
-                continue;
+    /// The account for a row whose account cell is `name`, or None to skip the row
+    fn route(&mut self, name: &str, create_missing: bool) -> Option<Uuid> {
+        let key = name.to_lowercase();
+        match self.by_name.get(&key).map(Vec::as_slice) {
+            Some([id]) => Some(*id),
+            Some(_) => {
+                self.skip(name, "the name matches several accounts");
+                None
+            }
+            None if create_missing => {
+                let mut account = Account::new(Uuid::new_v4(), name);
+                account.is_manual = true;
+                self.by_name.insert(key, vec![account.id]);
+                let id = account.id;
+                self.created.push(account);
+                Some(id)
             }
-            if desc_patterns.iter().any(|p| header_lower.contains(p)) {
-                detected.description = Some(header.clone());
-                break;
+            None => {
+                self.skip(
+                    name,
+                    "no account has this name (allow creating missing accounts to add it)",
+                );
+                None
             }
         }
+    }
 
-        // Fallback for description
-        if detected.description.is_none() {
-            let fallback_patterns = ["name", "type", "ref", "reference", "category"];
-            for header in &headers {
-                let header_lower = header.to_lowercase();
-                if detected.date.as_ref() == Some(header) {
-                    continue;
-                }
-                if fallback_patterns.iter().any(|p| header_lower.contains(p)) {
-                    detected.description = Some(header.clone());
-                    break;
+    fn skip(&mut self, name: &str, reason: &'static str) {
+        self.skipped
+            .entry(name.to_string())
+            .or_insert((0, reason))
+            .0 += 1;
+    }
+
+    fn created_names(&self) -> Vec<String> {
+        self.created.iter().map(|a| a.name.clone()).collect()
+    }
+
+    /// One warning per account cell whose rows were skipped
+    fn warnings(&self) -> Vec<String> {
+        self.skipped
+            .iter()
+            .map(|(name, (rows, reason))| {
+                format!("Skipped {} row(s) for account '{}': {}", rows, name, reason)
+            })
+            .collect()
+    }
+}
+
+/// Tags for a category value: lowercased, whitespace collapsed, empties dropped
+fn category_tags(category: &str, separator: Option<&str>) -> Vec<String> {
+    let parts: Vec<&str> = match separator {
+        Some(separator) if !separator.is_empty() => category.split(separator).collect(),
+        _ => vec![category],
+    };
+    let tags: Vec<String> = parts
+        .iter()
+        .map(|part| {
+            part.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        })
+        .collect();
+    Transaction::normalize_tags(&tags)
+}
+
+/// Read a file as text, decoding it from `encoding` (UTF-8 when unset)
+///
+/// A byte order mark overrides the requested encoding. Bytes that are
+/// invalid in the encoding become U+FFFD rather than failing the import.
+fn read_decoded(file_path: &Path, encoding: Option<&str>) -> Result<String> {
+    let encoding = match encoding {
+        Some(label) => Encoding::for_label(label.trim().as_bytes())
+            .ok_or_else(|| anyhow::anyhow!("Unknown encoding: {}", label))?,
+        None => UTF_8,
+    };
+    let bytes = std::fs::read(file_path).context("Failed to read CSV file")?;
+    let (content, _, _) = encoding.decode(&bytes);
+    Ok(content.into_owned())
+}
+
+/// Guess the delimiter from a header line (semicolon common in EU, comma in US)
+fn detect_delimiter(header_line: &str) -> u8 {
+    let semicolons = header_line.matches(';').count();
+    let commas = header_line.matches(',').count();
+    let tabs = header_line.matches('\t').count();
+    if semicolons > commas && semicolons > tabs {
+        b';'
+    } else if tabs > commas && tabs > semicolons {
+        b'\t'
+    } else {
+        b','
+    }
+}
+
+/// Guess the number format from sample amounts
+///
+/// Each amount votes for a format if its separators give it away (see
+/// `number_format_hint`); None when no amount does, e.g. whole numbers only.
+/// EuSpace is only picked when no amount groups thousands with dots, since
+/// it would misread `1.234,56`.
+fn detect_number_format<'a>(amounts: impl IntoIterator<Item = &'a str>) -> Option<NumberFormat> {
+    let (mut us, mut eu, mut eu_space, mut dot_grouped) = (0, 0, 0, 0);
+    for amount in amounts {
+        match number_format_hint(amount) {
+            Some(NumberFormat::Us) => us += 1,
+            Some(NumberFormat::Eu) => {
+                eu += 1;
+                if amount.contains('.') {
+                    dot_grouped += 1;
                 }
             }
+            Some(NumberFormat::EuSpace) => eu_space += 1,
+            None => {}
         }
+    }
 
-        Ok(detected)
+    if us + eu + eu_space == 0 {
+        None
+    } else if us >= eu + eu_space {
+        Some(NumberFormat::Us)
+    } else if eu_space > 0 && dot_grouped == 0 {
+        Some(NumberFormat::EuSpace)
+    } else {
+        Some(NumberFormat::Eu)
+    }
+}
+
+/// The number format a single amount must be in, if it's unambiguous
+///
+/// `1,234.56` and `1.234,56` decide by whichever separator comes last, and a
+/// lone separator followed by one or two digits is the decimal point. A lone
+/// separator before exactly three digits (`1,234`) could be either.
+fn number_format_hint(amount: &str) -> Option<NumberFormat> {
+    let number: String = strip_currency_suffix(amount.trim())
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | ' ' | '\u{a0}' | '\u{202f}'))
+        .collect();
+    let number = number.trim();
+    let space_grouped = number.contains([' ', '\u{a0}', '\u{202f}']);
+    let digits_after = |pos: usize| {
+        number[pos + 1..]
+            .chars()
+            .filter(char::is_ascii_digit)
+            .count()
+    };
+
+    match (number.rfind('.'), number.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => Some(NumberFormat::Eu),
+        (Some(_), Some(_)) => Some(NumberFormat::Us),
+        (None, Some(comma)) => match (number.matches(',').count(), digits_after(comma)) {
+            (1, 1 | 2) if space_grouped => Some(NumberFormat::EuSpace),
+            (1, 1 | 2) => Some(NumberFormat::Eu),
+            (1, _) => None,
+            _ => Some(NumberFormat::Us),
+        },
+        (Some(dot), None) => match (number.matches('.').count(), digits_after(dot)) {
+            (1, 3) => None,
+            (1, _) => Some(NumberFormat::Us),
+            _ => Some(NumberFormat::Eu),
+        },
+        (None, None) => None,
+    }
+}
+
+/// Confidence for a header equal to one of the field's patterns
+const EXACT_MATCH_CONFIDENCE: f32 = 1.0;
+/// Confidence for a header containing one of the field's patterns
+const CONTAINS_MATCH_CONFIDENCE: f32 = 0.6;
+/// Confidence for a header only matching the description fallbacks
+const FALLBACK_MATCH_CONFIDENCE: f32 = 0.3;
+
+/// Guess the column mapping from CSV headers
+///
+/// Each field takes the best-scoring header; among equally good headers the
+/// first one wins.
+fn detect_columns_from_headers(headers: Vec<String>) -> DetectedColumns {
+    let date_patterns = [
+        "date",
+        "transaction date",
+        "trans date",
+        "txn date",
+        "txndate",
+        "posted",
+        "post date",
+        "dt",
+    ];
+    let desc_patterns = [
+        "description",
+        "desc",
+        "memo",
+        "payee",
+        "merchant",
+        "details",
+        "narration",
+    ];
+    let amount_patterns = ["amount", "amt", "total", "transaction amount"];
+    let debit_patterns = ["debit", "dr", "withdrawal", "debit amount"];
+    let credit_patterns = ["credit", "cr", "deposit", "credit amount"];
+    let fallback_desc_patterns = ["name", "type", "ref", "reference", "category"];
+
+    let mut detected = DetectedColumns::default();
+    let confidence = &mut detected.confidence;
+
+    detected.date = record(
+        confidence,
+        "date",
+        best_match(&headers, &date_patterns, None),
+    );
+
+    // Prefer a single amount column; otherwise look for debit/credit
+    let amount = best_match(&headers, &amount_patterns, None);
+    if amount.is_none() {
+        detected.debit = record(
+            confidence,
+            "debit",
+            best_match(&headers, &debit_patterns, None),
+        );
+        detected.credit = record(
+            confidence,
+            "credit",
+            best_match(&headers, &credit_patterns, None),
+        );
+    }
+    detected.amount = record(confidence, "amount", amount);
+
+    // The description is never the date column
+    let date_column = detected.date.as_deref();
+    let description = best_match(&headers, &desc_patterns, date_column).or_else(|| {
+        best_match(&headers, &fallback_desc_patterns, date_column)
+            .map(|(header, _)| (header, FALLBACK_MATCH_CONFIDENCE))
+    });
+    detected.description = record(confidence, "description", description);
+
+    detected.all_headers = headers;
+    detected
+}
+
+/// Note a detected field's confidence, returning its header
+fn record(
+    confidence: &mut HashMap<String, f32>,
+    field: &str,
+    detected: Option<(String, f32)>,
+) -> Option<String> {
+    let (header, score) = detected?;
+    confidence.insert(field.to_string(), score);
+    Some(header)
+}
+
+/// The header best matching `patterns` (case-insensitive), with its confidence
+fn best_match(headers: &[String], patterns: &[&str], skip: Option<&str>) -> Option<(String, f32)> {
+    let mut best: Option<(String, f32)> = None;
+    for header in headers {
+        if skip == Some(header.as_str()) {
+            continue;
+        }
+        let header_lower = header.trim().to_lowercase();
+        let confidence = if patterns.iter().any(|p| header_lower == *p) {
+            EXACT_MATCH_CONFIDENCE
+        } else if patterns.iter().any(|p| header_lower.contains(p)) {
+            CONTAINS_MATCH_CONFIDENCE
+        } else {
+            continue;
+        };
+        let better = match &best {
+            Some((_, best_confidence)) => confidence > *best_confidence,
+            None => true,
+        };
+        if better {
+            best = Some((header.clone(), confidence));
+        }
     }
+    best
+}
+
+/// Reject chrono format strings with unknown specifiers, which would
+/// otherwise quietly skip every row
+fn validate_date_format(format: &str) -> Result<()> {
+    let has_error = StrftimeItems::new(format).any(|item| matches!(item, Item::Error));
+    if format.trim().is_empty() || has_error {
+        anyhow::bail!("Invalid date format: {}", format);
+    }
+    Ok(())
 }
 
 fn parse_date(s: &str) -> Option<NaiveDate> {
@@ -829,6 +1399,22 @@ pub struct DetectedColumns {
     pub debit: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub credit: Option<String>,
+    /// How sure detection is per detected field ("date", "amount", ...), from 0 to 1
+    ///
+    /// 1.0 means the header exactly matched a known name, 0.6 that it contained
+    /// one and 0.3 that it was only a fallback guess.
+    pub confidence: HashMap<String, f32>,
+    /// Every header in the file, in order, for offering alternatives
+    pub all_headers: Vec<String>,
+    /// Field delimiter guessed from the header row, e.g. `;` or `tab`
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_delimiter"
+    )]
+    pub delimiter: Option<u8>,
+    /// Number format guessed from sample amounts; unset if they didn't tell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_format: Option<NumberFormat>,
 }
 
 #[derive(Debug, Serialize)]
@@ -841,17 +1427,78 @@ pub struct ImportResult {
     pub imported: i64,
     /// Skipped transactions (invalid or duplicate)
     pub skipped: i64,
+    /// Why each skipped row was skipped, in file order (duplicates are only
+    /// known outside preview, which doesn't deduplicate)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_rows: Vec<SkippedRow>,
     /// Number of fingerprints checked for deduplication
     pub fingerprints_checked: i64,
     /// Number of balance snapshots created from running balance column
     pub balance_snapshots_created: i64,
     /// Whether this was a preview (no changes applied)
     pub preview: bool,
+    /// Problems spotted, such as a column mapping that looks wrong (preview
+    /// only) or rows skipped for an unknown account
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Names of accounts created from the account column (in preview, that would be)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub accounts_created: Vec<String>,
     /// Transaction previews (only in preview mode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transactions: Option<Vec<TransactionPreview>>,
 }
 
+/// A CSV row that was not imported
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedRow {
+    /// Line number in the file, counting the header (and any skipped rows)
+    pub row: usize,
+    pub reason: SkipReason,
+}
+
+/// Why a CSV row was not imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The date cell is empty or doesn't match the date format
+    UnparseableDate,
+    /// No amount, or debit/credit, cell could be read as a number
+    MissingAmount,
+    /// The account cell names no account and accounts aren't being created
+    UnknownAccount,
+    /// Already imported, by an earlier import or an earlier file of this one
+    DuplicateFingerprint,
+}
+
+/// Totals across the per-file results of [`ImportService::import_many`]
+#[derive(Debug, Default, Serialize)]
+pub struct ImportTotals {
+    pub files: usize,
+    pub discovered: i64,
+    pub imported: i64,
+    pub skipped: i64,
+    pub balance_snapshots_created: i64,
+}
+
+impl ImportTotals {
+    pub fn from_results(results: &[ImportResult]) -> Self {
+        results.iter().fold(
+            Self {
+                files: results.len(),
+                ..Self::default()
+            },
+            |mut totals, r| {
+                totals.discovered += r.discovered;
+                totals.imported += r.imported;
+                totals.skipped += r.skipped;
+                totals.balance_snapshots_created += r.balance_snapshots_created;
+                totals
+            },
+        )
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct TransactionPreview {
     pub date: String,
@@ -942,6 +1589,14 @@ mod tests {
         assert_eq!(parse_date(""), None);
     }
 
+    #[test]
+    fn test_validate_date_format() {
+        assert!(validate_date_format("%d/%m/%Y").is_ok());
+        assert!(validate_date_format("%d.%m.%y").is_ok());
+        assert!(validate_date_format("%Q").is_err());
+        assert!(validate_date_format(" ").is_err());
+    }
+
     // ==========================================================================
     // European format tests - with proper format parameter
     // ==========================================================================
@@ -998,4 +1653,151 @@ mod tests {
         assert_eq!(NumberFormat::from_str("eu_space"), NumberFormat::EuSpace);
         assert_eq!(NumberFormat::from_str("unknown"), NumberFormat::Us); // default
     }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter("tab"), Some(b'\t'));
+        assert_eq!(parse_delimiter("TAB"), Some(b'\t'));
+        assert_eq!(parse_delimiter(","), Some(b','));
+        assert_eq!(parse_delimiter(";"), Some(b';'));
+        assert_eq!(parse_delimiter("|"), Some(b'|'));
+        assert_eq!(parse_delimiter(":"), None);
+    }
+
+    #[test]
+    fn test_category_tags() {
+        assert_eq!(
+            category_tags("Food &  Dining ", None),
+            vec!["food & dining"]
+        );
+        assert_eq!(
+            category_tags("Food > Groceries > food", Some(">")),
+            vec!["food", "groceries"]
+        );
+        assert!(category_tags("  ", None).is_empty());
+        assert!(category_tags("", Some("/")).is_empty());
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter("Date;Amount;Description"), b';');
+        assert_eq!(detect_delimiter("Date\tAmount\tDescription"), b'\t');
+        assert_eq!(detect_delimiter("Date,Amount,Description"), b',');
+        // Pipes are never guessed
+        assert_eq!(detect_delimiter("Date|Amount|Description"), b',');
+    }
+
+    #[test]
+    fn test_number_format_hint() {
+        assert_eq!(number_format_hint("1,234.56"), Some(NumberFormat::Us));
+        assert_eq!(number_format_hint("-12.5"), Some(NumberFormat::Us));
+        assert_eq!(number_format_hint("1,234,567"), Some(NumberFormat::Us));
+        assert_eq!(number_format_hint("1.234,56"), Some(NumberFormat::Eu));
+        assert_eq!(number_format_hint("-12,50 EUR"), Some(NumberFormat::Eu));
+        assert_eq!(number_format_hint("1.234.567"), Some(NumberFormat::Eu));
+        assert_eq!(
+            number_format_hint("8 019,40 PLN"),
+            Some(NumberFormat::EuSpace)
+        );
+        // Thousands or three decimals? Can't tell
+        assert_eq!(number_format_hint("1,234"), None);
+        assert_eq!(number_format_hint("1.234"), None);
+        assert_eq!(number_format_hint("100"), None);
+        assert_eq!(number_format_hint(""), None);
+    }
+
+    #[test]
+    fn test_detect_number_format() {
+        assert_eq!(
+            detect_number_format(["12.34", "1,234", "-5.00"]),
+            Some(NumberFormat::Us)
+        );
+        assert_eq!(
+            detect_number_format(["12,34", "1.234", "-1.005,00"]),
+            Some(NumberFormat::Eu)
+        );
+        assert_eq!(
+            detect_number_format(["1 234,56", "-7,20"]),
+            Some(NumberFormat::EuSpace)
+        );
+        assert_eq!(
+            detect_number_format(["1 234,56", "1.234,56"]),
+            Some(NumberFormat::Eu)
+        );
+        assert_eq!(detect_number_format(["100", "1,234"]), None);
+    }
+
+    #[test]
+    fn test_delimiter_name_round_trips() {
+        for delimiter in [b',', b';', b'|', b'\t'] {
+            assert_eq!(parse_delimiter(&delimiter_name(delimiter)), Some(delimiter));
+        }
+    }
+
+    // ==========================================================================
+    // Column detection tests
+    // ==========================================================================
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_columns_exact_matches() {
+        let detected = detect_columns_from_headers(headers(&["Date", "Amount", "Description"]));
+
+        assert_eq!(detected.date.as_deref(), Some("Date"));
+        assert_eq!(detected.amount.as_deref(), Some("Amount"));
+        assert_eq!(detected.description.as_deref(), Some("Description"));
+        assert_eq!(detected.confidence["date"], EXACT_MATCH_CONFIDENCE);
+        assert_eq!(detected.confidence["amount"], EXACT_MATCH_CONFIDENCE);
+        assert_eq!(detected.confidence["description"], EXACT_MATCH_CONFIDENCE);
+        assert_eq!(
+            detected.all_headers,
+            headers(&["Date", "Amount", "Description"])
+        );
+    }
+
+    #[test]
+    fn test_detect_columns_prefers_exact_over_contains() {
+        let detected = detect_columns_from_headers(headers(&[
+            "Posting Date",
+            "date",
+            "Amount (EUR)",
+            "Account Name",
+        ]));
+
+        assert_eq!(detected.date.as_deref(), Some("date"));
+        assert_eq!(detected.confidence["date"], EXACT_MATCH_CONFIDENCE);
+        assert_eq!(detected.amount.as_deref(), Some("Amount (EUR)"));
+        assert_eq!(detected.confidence["amount"], CONTAINS_MATCH_CONFIDENCE);
+        // Only the fallback patterns match a description
+        assert_eq!(detected.description.as_deref(), Some("Account Name"));
+        assert_eq!(
+            detected.confidence["description"],
+            FALLBACK_MATCH_CONFIDENCE
+        );
+    }
+
+    #[test]
+    fn test_detect_columns_debit_credit() {
+        let detected =
+            detect_columns_from_headers(headers(&["Txn Date", "Memo", "Withdrawal", "Deposit"]));
+
+        assert!(detected.amount.is_none());
+        assert!(!detected.confidence.contains_key("amount"));
+        assert_eq!(detected.debit.as_deref(), Some("Withdrawal"));
+        assert_eq!(detected.confidence["debit"], EXACT_MATCH_CONFIDENCE);
+        assert_eq!(detected.credit.as_deref(), Some("Deposit"));
+        assert_eq!(detected.confidence["credit"], EXACT_MATCH_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_detect_columns_nothing_found() {
+        let detected = detect_columns_from_headers(headers(&["foo", "bar"]));
+
+        assert!(detected.date.is_none());
+        assert!(detected.confidence.is_empty());
+        assert_eq!(detected.all_headers.len(), 2);
+    }
 }
@@ -11,11 +11,23 @@ use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use duckdb::Connection;
 use serde::{Deserialize, Serialize};
 
 use crate::log_migrations::LOG_MIGRATIONS;
 
+/// Entries older than this are pruned automatically
+pub const LOG_RETENTION_DAYS: u32 = 90;
+
+/// Most entries kept by automatic pruning
+pub const LOG_MAX_ROWS: usize = 100_000;
+
+/// `sys_log_state` key holding when logs were last pruned (unix ms)
+const LAST_PRUNED_AT_KEY: &str = "last_pruned_at";
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
 /// Counter for generating unique IDs within the same millisecond
 static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
 
@@ -62,7 +74,7 @@ pub enum EntryPoint {
 }
 
 impl EntryPoint {
-    fn as_str(&self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             EntryPoint::Cli => "cli",
             EntryPoint::Desktop => "desktop",
@@ -162,7 +174,9 @@ impl LoggingService {
     /// Create a new logging service
     ///
     /// Opens or creates logs.duckdb in the treeline directory and runs
-    /// any pending migrations.
+    /// any pending migrations. At most once a day it also prunes entries
+    /// beyond [`LOG_RETENTION_DAYS`] and [`LOG_MAX_ROWS`]; a failed prune
+    /// is ignored so it never gets in the way of logging.
     pub fn new(
         treeline_dir: &Path,
         entry_point: EntryPoint,
@@ -180,6 +194,7 @@ impl LoggingService {
         };
 
         service.run_migrations()?;
+        let _ = service.prune_if_due();
 
         Ok(service)
     }
@@ -192,7 +207,10 @@ impl LoggingService {
 
     /// Run any pending migrations
     fn run_migrations(&self) -> Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
 
         // Check if migrations table exists
         let table_exists: bool = conn
@@ -205,7 +223,9 @@ impl LoggingService {
 
         // Bootstrap migrations table if needed
         if !table_exists {
-            if let Some((name, sql)) = LOG_MIGRATIONS.iter().find(|(n, _)| *n == "000_migrations.sql")
+            if let Some((name, sql)) = LOG_MIGRATIONS
+                .iter()
+                .find(|(n, _)| *n == "000_migrations.sql")
             {
                 conn.execute_batch(sql)?;
                 conn.execute(
@@ -245,7 +265,10 @@ impl LoggingService {
     /// app_version, and platform are automatically added from the service
     /// configuration.
     pub fn log(&self, event: LogEvent) -> Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
 
         conn.execute(
             r#"
@@ -300,7 +323,10 @@ impl LoggingService {
     ///
     /// Returns the most recent entries, up to the specified limit.
     pub fn get_recent(&self, limit: usize) -> Result<Vec<LogEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -334,9 +360,101 @@ impl LoggingService {
         Ok(entries)
     }
 
+    /// Query log entries, newest first
+    ///
+    /// `since` keeps entries logged at or after that instant and `event_type`
+    /// keeps entries with exactly that event name; both are optional.
+    pub fn query(
+        &self,
+        since: Option<DateTime<Utc>>,
+        event_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<LogEntry>> {
+        self.query_filtered(since, event_type, false, limit)
+    }
+
+    /// Like [`query`](Self::query), keeping only entries with an error
+    ///
+    /// The filters apply before `limit`, so this returns up to `limit`
+    /// matching errors rather than filtering the latest `limit` errors.
+    pub fn query_errors(
+        &self,
+        since: Option<DateTime<Utc>>,
+        event_type: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<LogEntry>> {
+        self.query_filtered(since, event_type, true, limit)
+    }
+
+    fn query_filtered(
+        &self,
+        since: Option<DateTime<Utc>>,
+        event_type: Option<&str>,
+        errors_only: bool,
+        limit: usize,
+    ) -> Result<Vec<LogEntry>> {
+        let mut conditions = vec!["1 = 1".to_string()];
+        let mut values: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
+
+        if errors_only {
+            conditions.push("error_message IS NOT NULL".to_string());
+        }
+        if let Some(since) = since {
+            conditions.push("timestamp >= ?".to_string());
+            values.push(Box::new(since.timestamp_millis()));
+        }
+        if let Some(event_type) = event_type {
+            conditions.push("event = ?".to_string());
+            values.push(Box::new(event_type.to_string()));
+        }
+        values.push(Box::new(limit as i64));
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT id, timestamp, entry_point, app_version, platform,
+                   event, integration, page, command, error_message, error_details
+            FROM sys_logs
+            WHERE {}
+            ORDER BY timestamp DESC, id DESC
+            LIMIT ?
+            "#,
+            conditions.join(" AND ")
+        ))?;
+
+        let param_refs: Vec<&dyn duckdb::ToSql> = values.iter().map(|b| b.as_ref()).collect();
+        let entries = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok(LogEntry {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    entry_point: row.get(2)?,
+                    app_version: row.get(3)?,
+                    platform: row.get(4)?,
+                    event: row.get(5)?,
+                    integration: row.get(6)?,
+                    page: row.get(7)?,
+                    command: row.get(8)?,
+                    error_message: row.get(9)?,
+                    error_details: row.get(10)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
+
     /// Query log entries with errors
     pub fn get_errors(&self, limit: usize) -> Result<Vec<LogEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
 
         let mut stmt = conn.prepare(
             r#"
@@ -373,26 +491,85 @@ impl LoggingService {
 
     /// Get the total number of log entries
     pub fn count(&self) -> Result<u64> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         let count: u64 = conn.query_row("SELECT COUNT(*) FROM sys_logs", [], |row| row.get(0))?;
         Ok(count)
     }
 
     /// Delete logs older than the specified timestamp (unix ms)
     pub fn delete_before(&self, timestamp_ms: i64) -> Result<u64> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        let deleted = conn.execute(
-            "DELETE FROM sys_logs WHERE timestamp < ?",
-            [timestamp_ms],
-        )?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let deleted = conn.execute("DELETE FROM sys_logs WHERE timestamp < ?", [timestamp_ms])?;
         Ok(deleted as u64)
     }
 
+    /// Delete entries older than `max_age_days`, then all but the newest `max_rows`
+    ///
+    /// Runs in a single transaction and records when it ran. Returns the
+    /// number of entries deleted.
+    pub fn prune(&self, max_age_days: u32, max_rows: usize) -> Result<usize> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+
+        let now = now_ms();
+        let cutoff = now - i64::from(max_age_days) * DAY_MS;
+
+        let tx = conn.transaction()?;
+        let by_age = tx.execute("DELETE FROM sys_logs WHERE timestamp < ?", [cutoff])?;
+        let by_count = tx.execute(
+            r#"
+            DELETE FROM sys_logs
+            WHERE id NOT IN (
+                SELECT id FROM sys_logs ORDER BY timestamp DESC, id DESC LIMIT ?
+            )
+            "#,
+            [max_rows as i64],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO sys_log_state (key, value) VALUES (?, ?)",
+            duckdb::params![LAST_PRUNED_AT_KEY, now],
+        )?;
+        tx.commit()?;
+
+        Ok(by_age + by_count)
+    }
+
+    /// Prune with the default limits unless that already happened in the last day
+    fn prune_if_due(&self) -> Result<usize> {
+        let last_pruned: Option<i64> = {
+            let conn = self
+                .conn
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+            conn.query_row(
+                "SELECT max(value) FROM sys_log_state WHERE key = ?",
+                [LAST_PRUNED_AT_KEY],
+                |row| row.get(0),
+            )?
+        };
+
+        if last_pruned.is_some_and(|last| now_ms() - last < DAY_MS) {
+            return Ok(0);
+        }
+        self.prune(LOG_RETENTION_DAYS, LOG_MAX_ROWS)
+    }
+
     /// Export logs to a file for troubleshooting
     ///
     /// Creates a copy of the logs database that can be sent for analysis.
     pub fn export(&self, output_path: &Path) -> Result<PathBuf> {
-        let conn = self.conn.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
 
         // Force checkpoint to ensure all data is written
         conn.execute("CHECKPOINT", [])?;
@@ -469,10 +646,35 @@ mod tests {
         let errors = service.get_errors(10).unwrap();
         assert_eq!(errors.len(), 1);
         assert_eq!(errors[0].event, "sync_failed");
-        assert_eq!(errors[0].error_message, Some("Connection timeout".to_string()));
+        assert_eq!(
+            errors[0].error_message,
+            Some("Connection timeout".to_string())
+        );
         assert_eq!(errors[0].error_details, Some("at line 42".to_string()));
     }
 
+    #[test]
+    fn test_query_errors_filters_before_limit() {
+        let dir = tempdir().unwrap();
+        let service = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+
+        service.log_error("sync_failed", "timeout", None).unwrap();
+        service.log_event("sync_failed").unwrap();
+        service
+            .log_error("import_failed", "bad file", None)
+            .unwrap();
+        service
+            .log_error("import_failed", "bad date", None)
+            .unwrap();
+
+        // The latest error is another event, but a matching one is still found
+        let errors = service.query_errors(None, Some("sync_failed"), 1).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error_message, Some("timeout".to_string()));
+
+        assert_eq!(service.query_errors(None, None, 10).unwrap().len(), 3);
+    }
+
     #[test]
     fn test_count_and_delete() {
         let dir = tempdir().unwrap();
@@ -490,6 +692,99 @@ mod tests {
         assert_eq!(service.count().unwrap(), 0);
     }
 
+    #[test]
+    fn test_query_filters() {
+        let dir = tempdir().unwrap();
+        let service = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+
+        service.log_event("sync_started").unwrap();
+        service.log_command("status").unwrap();
+        service.log_event("sync_started").unwrap();
+
+        let all = service.query(None, None, 10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all[0].id > all[2].id);
+
+        let syncs = service.query(None, Some("sync_started"), 10).unwrap();
+        assert_eq!(syncs.len(), 2);
+        assert!(syncs.iter().all(|e| e.event == "sync_started"));
+
+        assert_eq!(service.query(None, None, 1).unwrap().len(), 1);
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(service.query(Some(future), None, 10).unwrap().is_empty());
+        let past = Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(service.query(Some(past), None, 10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempdir().unwrap();
+        let service = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+
+        let insert_old = |event: &str, age_days: i64| {
+            let conn = service.conn.lock().unwrap();
+            conn.execute(
+                "INSERT INTO sys_logs (id, timestamp, entry_point, app_version, platform, event)
+                 VALUES (?, ?, 'cli', '1.0.0', 'linux', ?)",
+                duckdb::params![generate_id(), now_ms() - age_days * DAY_MS, event],
+            )
+            .unwrap();
+        };
+        insert_old("ancient", 400);
+        insert_old("old", 100);
+        insert_old("last_month", 20);
+        service.log_event("recent").unwrap();
+
+        // Age-based: only entries past the cutoff go
+        assert_eq!(service.prune(30, 1000).unwrap(), 2);
+        let events: Vec<String> = service
+            .get_recent(10)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.event)
+            .collect();
+        assert_eq!(events, vec!["recent", "last_month"]);
+
+        // Count-based: the newest entries are kept
+        assert_eq!(service.prune(30, 1).unwrap(), 1);
+        assert_eq!(service.get_recent(10).unwrap()[0].event, "recent");
+        assert_eq!(service.count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_prune_on_open_is_throttled() {
+        let dir = tempdir().unwrap();
+        let service = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+        {
+            let conn = service.conn.lock().unwrap();
+            conn.execute(
+                "INSERT INTO sys_logs (id, timestamp, entry_point, app_version, platform, event)
+                 VALUES (?, ?, 'cli', '1.0.0', 'linux', 'old')",
+                duckdb::params![generate_id(), now_ms() - 400 * DAY_MS],
+            )
+            .unwrap();
+        }
+        drop(service);
+
+        // Opening pruned moments ago, so the old entry survives a reopen
+        let service = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+        assert_eq!(service.count().unwrap(), 1);
+
+        {
+            let conn = service.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE sys_log_state SET value = value - ? WHERE key = ?",
+                duckdb::params![2 * DAY_MS, LAST_PRUNED_AT_KEY],
+            )
+            .unwrap();
+        }
+        drop(service);
+
+        let service = LoggingService::new(dir.path(), EntryPoint::Cli, "1.0.0").unwrap();
+        assert_eq!(service.count().unwrap(), 0);
+    }
+
     #[test]
     fn test_export() {
         let dir = tempdir().unwrap();
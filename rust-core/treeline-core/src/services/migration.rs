@@ -2,11 +2,16 @@
 //!
 //! Migrations are SQL files embedded at compile time. Each migration is
 //! tracked in the sys_migrations table to ensure idempotent execution.
+//! A migration can be reverted if it has a paired `NNN_name.down.sql` script.
+
+use std::collections::HashMap;
 
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use duckdb::Connection;
+use serde::Serialize;
 
-use crate::migrations::MIGRATIONS;
+use crate::migrations::{DOWN_MIGRATIONS, MIGRATIONS};
 
 /// Result of running migrations
 #[derive(Debug)]
@@ -15,17 +20,48 @@ pub struct MigrationResult {
     pub applied: Vec<String>,
     /// Count of migrations that were already applied
     pub already_applied: usize,
+    /// Names of migrations reverted by a rollback
+    pub rolled_back: Vec<String>,
+}
+
+/// Applied/pending state of a single embedded migration
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatusEntry {
+    /// Migration filename (e.g. "001_initial_schema.sql")
+    pub name: String,
+    /// Whether the migration is recorded in sys_migrations
+    pub applied: bool,
+    /// When the migration was applied, if known
+    pub applied_at: Option<NaiveDateTime>,
 }
 
 /// Service for managing database migrations
 pub struct MigrationService<'a> {
     conn: &'a Connection,
+    migrations: &'a [(&'a str, &'a str)],
+    down_migrations: &'a [(&'a str, &'a str)],
 }
 
 impl<'a> MigrationService<'a> {
     /// Create a new migration service with a database connection
     pub fn new(conn: &'a Connection) -> Self {
-        Self { conn }
+        Self::with_migrations(conn, MIGRATIONS, DOWN_MIGRATIONS)
+    }
+
+    /// Create a migration service with a custom set of migrations
+    ///
+    /// Both lists use the same (filename, sql_content) format as the embedded
+    /// migrations; down scripts are named `NNN_name.down.sql`.
+    pub fn with_migrations(
+        conn: &'a Connection,
+        migrations: &'a [(&'a str, &'a str)],
+        down_migrations: &'a [(&'a str, &'a str)],
+    ) -> Self {
+        Self {
+            conn,
+            migrations,
+            down_migrations,
+        }
     }
 
     /// Run all pending migrations
@@ -40,7 +76,11 @@ impl<'a> MigrationService<'a> {
 
         // Bootstrap: run the first migration (000_migrations.sql) if sys_migrations doesn't exist
         let bootstrap_ran = if !self.migrations_table_exists()? {
-            if let Some((name, sql)) = MIGRATIONS.iter().find(|(n, _)| *n == "000_migrations.sql") {
+            if let Some((name, sql)) = self
+                .migrations
+                .iter()
+                .find(|(n, _)| *n == "000_migrations.sql")
+            {
                 self.conn.execute_batch(sql)?;
                 self.record_migration(name)?;
                 newly_applied.push(name.to_string());
@@ -62,7 +102,7 @@ impl<'a> MigrationService<'a> {
         };
 
         // Apply pending migrations in order (skip bootstrap, we already handled it)
-        for (name, sql) in MIGRATIONS.iter() {
+        for (name, sql) in self.migrations.iter() {
             if *name == "000_migrations.sql" {
                 continue; // Already handled above
             }
@@ -84,6 +124,46 @@ impl<'a> MigrationService<'a> {
         Ok(MigrationResult {
             applied: newly_applied,
             already_applied,
+            rolled_back: Vec::new(),
+        })
+    }
+
+    /// Roll back the most recently applied migration
+    ///
+    /// Runs the paired `NNN_name.down.sql` script and removes the migration
+    /// from sys_migrations, so the next `run_pending` applies it again.
+    pub fn rollback_last(&self) -> Result<MigrationResult> {
+        let applied = self.get_applied()?;
+        let last = applied
+            .last()
+            .ok_or_else(|| anyhow::anyhow!("No migrations have been applied"))?;
+
+        let down_name = format!("{}.down.sql", last.trim_end_matches(".sql"));
+        let (_, sql) = self
+            .down_migrations
+            .iter()
+            .find(|(n, _)| *n == down_name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Cannot roll back {}: no down migration ({}) exists",
+                    last,
+                    down_name
+                )
+            })?;
+
+        self.conn.execute_batch(sql)?;
+        self.conn.execute(
+            "DELETE FROM sys_migrations WHERE migration_name = ?",
+            [last.as_str()],
+        )?;
+
+        // Same WAL replay concern as run_pending
+        let _ = self.conn.execute("CHECKPOINT", []);
+
+        Ok(MigrationResult {
+            applied: Vec::new(),
+            already_applied: applied.len() - 1,
+            rolled_back: vec![last.clone()],
         })
     }
 
@@ -115,10 +195,42 @@ impl<'a> MigrationService<'a> {
         Ok(result)
     }
 
+    /// Get applied/pending status of every embedded migration, without running anything
+    pub fn status(&self) -> Result<Vec<MigrationStatusEntry>> {
+        let mut applied_at: HashMap<String, Option<NaiveDateTime>> = HashMap::new();
+
+        // Fresh database: nothing applied yet
+        if self.migrations_table_exists()? {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT migration_name, applied_at::VARCHAR FROM sys_migrations")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })?;
+            for row in rows {
+                let (name, at) = row?;
+                let at =
+                    at.and_then(|s| NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S%.f").ok());
+                applied_at.insert(name, at);
+            }
+        }
+
+        Ok(self
+            .migrations
+            .iter()
+            .map(|(name, _)| MigrationStatusEntry {
+                name: name.to_string(),
+                applied: applied_at.contains_key(*name),
+                applied_at: applied_at.get(*name).copied().flatten(),
+            })
+            .collect())
+    }
+
     /// Get list of pending migration names
     pub fn get_pending(&self) -> Result<Vec<String>> {
         let applied = self.get_applied()?;
-        let pending: Vec<String> = MIGRATIONS
+        let pending: Vec<String> = self
+            .migrations
             .iter()
             .filter(|(name, _)| !applied.contains(&name.to_string()))
             .map(|(name, _)| name.to_string())
@@ -176,4 +288,110 @@ mod tests {
         // All migrations except 000 should be pending
         assert_eq!(pending.len(), MIGRATIONS.len() - 1);
     }
+
+    const TEST_MIGRATIONS: &[(&str, &str)] = &[
+        (
+            "000_migrations.sql",
+            include_str!("../migrations/000_migrations.sql"),
+        ),
+        (
+            "001_widgets.sql",
+            "CREATE TABLE widgets (id INTEGER, name VARCHAR);",
+        ),
+        (
+            "002_widget_color.sql",
+            "ALTER TABLE widgets ADD COLUMN color VARCHAR;",
+        ),
+    ];
+
+    fn column_exists(conn: &Connection, table: &str, column: &str) -> bool {
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM information_schema.columns WHERE table_name = ? AND column_name = ?",
+                [table, column],
+                |row| row.get(0),
+            )
+            .unwrap();
+        count > 0
+    }
+
+    #[test]
+    fn test_rollback_last_runs_down_script() {
+        let conn = Connection::open_in_memory().unwrap();
+        let down: &[(&str, &str)] = &[(
+            "002_widget_color.down.sql",
+            "ALTER TABLE widgets DROP COLUMN color;",
+        )];
+        let service = MigrationService::with_migrations(&conn, TEST_MIGRATIONS, down);
+
+        service.run_pending().unwrap();
+        assert!(column_exists(&conn, "widgets", "color"));
+
+        let result = service.rollback_last().unwrap();
+        assert_eq!(result.rolled_back, vec!["002_widget_color.sql".to_string()]);
+        assert_eq!(result.already_applied, 2);
+
+        // Schema change reversed and tracking table updated
+        assert!(!column_exists(&conn, "widgets", "color"));
+        assert!(column_exists(&conn, "widgets", "name"));
+        assert!(!service
+            .get_applied()
+            .unwrap()
+            .contains(&"002_widget_color.sql".to_string()));
+        assert_eq!(service.get_pending().unwrap(), vec!["002_widget_color.sql"]);
+
+        // Migration can be applied again
+        let result = service.run_pending().unwrap();
+        assert_eq!(result.applied, vec!["002_widget_color.sql".to_string()]);
+        assert!(column_exists(&conn, "widgets", "color"));
+    }
+
+    #[test]
+    fn test_rollback_last_without_down_script() {
+        let conn = Connection::open_in_memory().unwrap();
+        let service = MigrationService::with_migrations(&conn, TEST_MIGRATIONS, &[]);
+        service.run_pending().unwrap();
+
+        let err = service.rollback_last().unwrap_err();
+        assert!(err.to_string().contains("002_widget_color.down.sql"));
+
+        // Nothing was removed from the tracking table
+        assert_eq!(service.get_applied().unwrap().len(), 3);
+        assert!(column_exists(&conn, "widgets", "color"));
+    }
+
+    #[test]
+    fn test_status_partially_applied() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        // Apply only the first 3 migrations
+        let applied_count = 3;
+        let service = MigrationService::with_migrations(&conn, &MIGRATIONS[..applied_count], &[]);
+        service.run_pending().unwrap();
+
+        let service = MigrationService::new(&conn);
+        let status = service.status().unwrap();
+
+        assert_eq!(status.len(), MIGRATIONS.len());
+        for (entry, (name, _)) in status.iter().zip(MIGRATIONS.iter()) {
+            assert_eq!(entry.name, *name);
+        }
+        for entry in &status[..applied_count] {
+            assert!(entry.applied, "{} should be applied", entry.name);
+            assert!(entry.applied_at.is_some());
+        }
+        for entry in &status[applied_count..] {
+            assert!(!entry.applied, "{} should be pending", entry.name);
+            assert!(entry.applied_at.is_none());
+        }
+    }
+
+    #[test]
+    fn test_status_on_empty_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        let status = MigrationService::new(&conn).status().unwrap();
+
+        assert_eq!(status.len(), MIGRATIONS.len());
+        assert!(status.iter().all(|e| !e.applied));
+    }
 }
@@ -3,9 +3,11 @@
 //! Services coordinate domain logic and port interactions. Each service
 //! focuses on a specific use case or feature area.
 
+mod account;
 mod backup;
 mod balance;
 mod compact;
+mod config;
 mod demo;
 mod doctor;
 pub mod encryption;
@@ -17,18 +19,44 @@ mod query;
 mod status;
 mod sync;
 mod tag;
+mod transaction;
+pub mod transfer;
+pub mod webhook;
 
-pub use backup::BackupService;
-pub use balance::{BackfillExecuteResult, BalanceService, BalanceSnapshotPreview};
+pub use account::{
+    AccountService, ArchiveResult, CreatedAccount, CurrencyResult, GroupResult, MergeReport,
+    NewAccount,
+};
+pub use backup::{
+    BackupDiff, BackupService, BackupVerifyReport, ImportStats, PruneResult, RestoreResult,
+    RetentionPolicy, TableDiff,
+};
+pub use balance::{
+    BackfillExecuteResult, BalanceService, BalanceSnapshotPreview, ReconcileEntry, ReconcileReport,
+};
 pub use compact::CompactService;
-pub use demo::DemoService;
-pub use doctor::DoctorService;
+pub use config::{BundleImportResult, BundledIntegration, ConfigBundle, ConfigService};
+pub use demo::{DemoOptions, DemoService, GenStats};
+pub use doctor::{DoctorService, DuplicateAccount, DuplicateAccountGroup, IntegrationHealth};
 pub use encryption::EncryptionService;
-pub use import::{ImportOptions, ImportResult, ImportService, NumberFormat};
+pub use import::{
+    parse_delimiter, BatchInfo, ImportOptions, ImportResult, ImportService, ImportTotals,
+    NumberFormat, SkipReason, SkippedRow,
+};
 pub use logging::{EntryPoint, LogEntry, LogEvent, LoggingService};
-pub use migration::{MigrationResult, MigrationService};
-pub use plugin::{PluginInfo, PluginManifest, PluginResult, PluginService, UpdateInfo};
-pub use query::QueryService;
-pub use status::{AccountSummary, DateRange, StatusService, StatusSummary};
-pub use sync::SyncService;
-pub use tag::{AutoTagResult, TagResult, TagResultEntry, TagService};
+pub use migration::{MigrationResult, MigrationService, MigrationStatusEntry};
+pub use plugin::{
+    PluginInfo, PluginManifest, PluginResult, PluginService, PluginView, SyncHook, UpdateInfo,
+    SYNC_COMPLETE_HOOK,
+};
+pub use query::{
+    AccountStatement, CacheStats, HistogramBucket, QueryService, StatementLine, UNTAGGED,
+};
+pub use status::{
+    AccountSummary, DateRange, GroupSubtotal, MonthlyCashFlow, StatusService, StatusSummary,
+};
+pub use sync::{AccountPlan, IntegrationPlan, PlannedChanges, SyncPlan, SyncResult, SyncService};
+pub use tag::{AutoTagResult, ConditionPreview, TagResult, TagResultEntry, TagService, TagStat};
+pub use transaction::{TransactionPatch, TransactionService};
+pub use transfer::{TransferDetector, TransferPair, TRANSFER_TAG};
+pub use webhook::{SyncWebhook, SyncWebhookPayload};
@@ -1,15 +1,26 @@
 //! Plugin management service
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::ops::ControlFlow;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use sqlparser::ast::{ObjectName, Query, Visit, Visitor};
+use sqlparser::dialect::DuckDbDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use crate::adapters::duckdb::{ensure_read_only, DuckDbRepository, QueryResult};
+pub use crate::domain::{plugin_schema_name, PluginView};
+use crate::TreelineContext;
 
 // Embed plugin template files at compile time
 // These point to the actual plugin-template directory, so there's no duplication
@@ -29,9 +40,22 @@ mod embedded_template {
         include_str!("../../../../plugin-template/.github/workflows/release.yml");
 }
 
+/// Manifest `hooks` entry for plugins that implement [`SyncHook`]
+pub const SYNC_COMPLETE_HOOK: &str = "onSyncComplete";
+
+/// Callback a plugin runs after every successful sync
+///
+/// Registered with [`PluginService::register_sync_hook`] and run by
+/// [`TreelineContext::sync`], once all synced data is written.
+pub trait SyncHook: Send + Sync {
+    fn on_sync_complete(&self, ctx: &TreelineContext) -> Result<()>;
+}
+
 /// Plugin service for managing external plugins
 pub struct PluginService {
     plugins_dir: PathBuf,
+    repository: Option<Arc<DuckDbRepository>>,
+    sync_hooks: Vec<(String, Box<dyn SyncHook>)>,
 }
 
 #[derive(Debug, Serialize)]
@@ -60,6 +84,12 @@ pub struct PluginManifest {
     pub source: String,
     #[serde(default)]
     pub permissions: serde_json::Value,
+    /// Tables the plugin creates in its schema: table name to column name to type
+    #[serde(default)]
+    pub tables: BTreeMap<String, BTreeMap<String, String>>,
+    /// Lifecycle hooks the plugin wants called, e.g. [`SYNC_COMPLETE_HOOK`]
+    #[serde(default)]
+    pub hooks: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -72,6 +102,416 @@ pub struct PluginInfo {
     pub source: String,
 }
 
+/// Canonical spelling of a DuckDB column type, so aliases like TEXT and VARCHAR compare equal
+fn normalize_sql_type(data_type: &str) -> String {
+    let upper: String = data_type
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+    let (base, args) = match upper.find('(') {
+        Some(i) => upper.split_at(i),
+        None => (upper.as_str(), ""),
+    };
+    let base = match base {
+        "TEXT" | "STRING" | "CHAR" | "BPCHAR" => "VARCHAR",
+        "INT" | "INT4" | "SIGNED" => "INTEGER",
+        "INT8" | "LONG" => "BIGINT",
+        "INT2" | "SHORT" => "SMALLINT",
+        "BOOL" | "LOGICAL" => "BOOLEAN",
+        "FLOAT8" => "DOUBLE",
+        "REAL" | "FLOAT4" => "FLOAT",
+        "NUMERIC" => "DECIMAL",
+        "DATETIME" => "TIMESTAMP",
+        other => other,
+    };
+    // VARCHAR(n) is plain VARCHAR in DuckDB
+    if base == "VARCHAR" {
+        return base.to_string();
+    }
+    format!("{}{}", base, args)
+}
+
+/// Core views a plugin may read but not write
+const PLUGIN_READABLE_VIEWS: [&str; 2] = ["transactions", "accounts"];
+
+/// How a statement uses a table name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableUse {
+    Read,
+    Write,
+    Schema,
+}
+
+/// Table-reference state for one level of parentheses
+#[derive(Default)]
+struct SqlLevel {
+    /// The next name is a table
+    expecting: Option<TableUse>,
+    /// Commas at this level introduce another table (FROM a, b; DROP TABLE a, b)
+    list: Option<TableUse>,
+    /// Inside a WITH clause, waiting for the next CTE name
+    expecting_cte: bool,
+    in_with: bool,
+    /// A SELECT, UPDATE or DELETE started at this level, so FROM names tables
+    /// rather than being part of EXTRACT(.. FROM ..) and friends
+    query: bool,
+    seen_token: bool,
+}
+
+/// Check that a plugin id is valid
+///
+/// Ids are lowercase ASCII letters, digits and hyphens. DuckDB folds
+/// identifier case and [`plugin_schema_name`] turns hyphens into
+/// underscores, so allowing upper case or underscores would let two
+/// plugins share a schema.
+fn check_plugin_id(plugin: &str) -> Result<()> {
+    if plugin.is_empty()
+        || !plugin
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        anyhow::bail!(
+            "Invalid plugin id: {} (use lowercase letters, digits and hyphens)",
+            plugin
+        );
+    }
+    Ok(())
+}
+
+/// Check that SQL run on behalf of a plugin only touches the plugin's data
+///
+/// The tokens are checked first: every name in a table position (after
+/// FROM, JOIN, INTO, UPDATE, TABLE, ...) must be in the plugin's schema,
+/// except that the `transactions` and `accounts` views and the statement's
+/// own CTEs may be read. Any `sys_*` name is rejected wherever it appears,
+/// and only query, DML and table/view/index/sequence/schema DDL statements
+/// are allowed. The parsed statement is then walked so that tables
+/// referenced anywhere, such as a PIVOT inside a subquery, are checked too.
+fn check_plugin_sql(plugin: &str, sql: &str) -> Result<()> {
+    let tokens = Tokenizer::new(&DuckDbDialect {}, sql)
+        .tokenize()
+        .map_err(|e| anyhow::anyhow!("Invalid SQL: {}", e))?;
+    let tokens: Vec<Token> = tokens
+        .into_iter()
+        .filter(|t| !matches!(t, Token::Whitespace(_)))
+        .collect();
+
+    let schema = plugin_schema_name(plugin);
+    let mut ctes: HashSet<String> = HashSet::new();
+    let mut levels = vec![SqlLevel {
+        query: true,
+        ..SqlLevel::default()
+    }];
+    let mut statement_start = true;
+    let mut needs_object = false;
+    let mut in_delete = false;
+    let mut in_index = false;
+    let mut prev_keyword = Keyword::NoKeyword;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        i += 1;
+        let level_index = levels.len() - 1;
+        let first_in_level = !levels[level_index].seen_token;
+        levels[level_index].seen_token = true;
+
+        let word = match token {
+            Token::Word(word) => word,
+            Token::SemiColon => {
+                levels = vec![SqlLevel {
+                    query: true,
+                    ..SqlLevel::default()
+                }];
+                statement_start = true;
+                in_delete = false;
+                in_index = false;
+                continue;
+            }
+            Token::LParen => {
+                // A subquery or column list, not a table name
+                levels[level_index].expecting = None;
+                levels.push(SqlLevel::default());
+                continue;
+            }
+            Token::RParen => {
+                if levels.len() == 1 {
+                    anyhow::bail!("Invalid SQL: unbalanced parentheses");
+                }
+                levels.pop();
+                continue;
+            }
+            Token::Comma => {
+                let level = &mut levels[level_index];
+                if level.in_with {
+                    level.expecting_cte = true;
+                } else if level.list.is_some() {
+                    level.expecting = level.list;
+                }
+                continue;
+            }
+            Token::EOF => continue,
+            _ => {
+                // String literals in table position are file reads
+                if levels[level_index].expecting.is_some() {
+                    anyhow::bail!("Plugin SQL may only reference tables by name");
+                }
+                continue;
+            }
+        };
+
+        if word.value.to_lowercase().starts_with("sys_") {
+            anyhow::bail!("Plugins may not access {}", word.value);
+        }
+        let keyword = if word.quote_style.is_none() {
+            word.keyword
+        } else {
+            Keyword::NoKeyword
+        };
+
+        if statement_start {
+            statement_start = false;
+            match keyword {
+                Keyword::SELECT | Keyword::WITH | Keyword::INSERT | Keyword::UPDATE => {}
+                Keyword::DELETE => in_delete = true,
+                Keyword::CREATE | Keyword::ALTER | Keyword::DROP => needs_object = true,
+                _ => anyhow::bail!("Plugins may not run {} statements", word.value),
+            }
+        } else if needs_object {
+            match keyword {
+                Keyword::OR
+                | Keyword::REPLACE
+                | Keyword::TEMP
+                | Keyword::TEMPORARY
+                | Keyword::UNIQUE => {}
+                Keyword::TABLE
+                | Keyword::VIEW
+                | Keyword::INDEX
+                | Keyword::SEQUENCE
+                | Keyword::SCHEMA => needs_object = false,
+                _ => anyhow::bail!("Plugins may not create or drop {} objects", word.value),
+            }
+        }
+
+        let level = &mut levels[level_index];
+        if level.expecting_cte && keyword != Keyword::RECURSIVE {
+            ctes.insert(word.value.to_lowercase());
+            level.expecting_cte = false;
+            continue;
+        }
+
+        if let Some(table_use) = level.expecting {
+            if matches!(
+                keyword,
+                Keyword::IF | Keyword::NOT | Keyword::EXISTS | Keyword::ONLY | Keyword::LATERAL
+            ) {
+                continue;
+            }
+            level.expecting = None;
+            let mut parts = vec![word.value.to_lowercase()];
+            while let (Some(Token::Period), Some(Token::Word(next))) =
+                (tokens.get(i), tokens.get(i + 1))
+            {
+                if next.value.to_lowercase().starts_with("sys_") {
+                    anyhow::bail!("Plugins may not access {}", next.value);
+                }
+                parts.push(next.value.to_lowercase());
+                i += 2;
+            }
+            check_plugin_table(plugin, &schema, &parts, table_use, &ctes)?;
+            continue;
+        }
+
+        if level.in_with
+            && !matches!(
+                keyword,
+                Keyword::AS | Keyword::RECURSIVE | Keyword::MATERIALIZED | Keyword::NOT
+            )
+        {
+            level.in_with = false;
+        }
+
+        match keyword {
+            Keyword::WITH => {
+                level.in_with = true;
+                level.expecting_cte = true;
+            }
+            Keyword::SELECT => {
+                level.query = true;
+                level.list = None;
+            }
+            Keyword::FROM if level.query || first_in_level => {
+                let table_use = if in_delete && prev_keyword == Keyword::DELETE {
+                    TableUse::Write
+                } else {
+                    TableUse::Read
+                };
+                level.query = true;
+                level.expecting = Some(table_use);
+                level.list = Some(table_use);
+            }
+            Keyword::USING if in_delete => {
+                level.expecting = Some(TableUse::Read);
+                level.list = Some(TableUse::Read);
+            }
+            Keyword::JOIN | Keyword::REFERENCES => level.expecting = Some(TableUse::Read),
+            Keyword::INTO => level.expecting = Some(TableUse::Write),
+            Keyword::UPDATE => {
+                level.query = true;
+                level.expecting = Some(TableUse::Write);
+            }
+            Keyword::TABLE | Keyword::VIEW | Keyword::SEQUENCE => {
+                level.expecting = Some(TableUse::Write);
+                level.list = Some(TableUse::Write);
+            }
+            Keyword::SCHEMA => level.expecting = Some(TableUse::Schema),
+            Keyword::INDEX => in_index = true,
+            Keyword::ON if in_index => {
+                in_index = false;
+                level.expecting = Some(TableUse::Write);
+            }
+            Keyword::TO if prev_keyword == Keyword::RENAME => {
+                level.expecting = Some(TableUse::Write)
+            }
+            Keyword::WHERE
+            | Keyword::GROUP
+            | Keyword::ORDER
+            | Keyword::LIMIT
+            | Keyword::HAVING
+            | Keyword::WINDOW
+            | Keyword::QUALIFY
+            | Keyword::UNION
+            | Keyword::EXCEPT
+            | Keyword::INTERSECT
+            | Keyword::RETURNING
+            | Keyword::SET
+            | Keyword::VALUES => level.list = None,
+            _ => {}
+        }
+        prev_keyword = keyword;
+    }
+
+    let statements = Parser::parse_sql(&DuckDbDialect {}, sql)
+        .map_err(|e| anyhow::anyhow!("Invalid SQL: {}", e))?;
+    let mut relations = PluginRelations {
+        plugin,
+        schema: &schema,
+        scopes: Vec::new(),
+        cte_bodies: Vec::new(),
+    };
+    match statements.visit(&mut relations) {
+        ControlFlow::Break(e) => Err(e),
+        ControlFlow::Continue(()) => Ok(()),
+    }
+}
+
+/// Visitor checking every table a parsed statement references
+struct PluginRelations<'a> {
+    plugin: &'a str,
+    schema: &'a str,
+    /// CTE names visible in each enclosing query, innermost last
+    scopes: Vec<HashSet<String>>,
+    /// CTE bodies not yet visited, with the name each one defines
+    cte_bodies: Vec<(*const Query, String)>,
+}
+
+impl Visitor for PluginRelations<'_> {
+    type Break = anyhow::Error;
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut scope = HashSet::new();
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                let name = cte.alias.name.value.to_lowercase();
+                // A recursive CTE may refer to itself; otherwise the name
+                // is only visible once its body has been checked
+                if with.recursive {
+                    scope.insert(name.clone());
+                }
+                self.cte_bodies.push((&*cte.query as *const Query, name));
+            }
+        }
+        self.scopes.push(scope);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        self.scopes.pop();
+        if let Some(index) = self
+            .cte_bodies
+            .iter()
+            .position(|(body, _)| std::ptr::eq(*body, query))
+        {
+            let (_, name) = self.cte_bodies.remove(index);
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert(name);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        let parts: Option<Vec<String>> = relation
+            .0
+            .iter()
+            .map(|part| part.as_ident().map(|ident| ident.value.to_lowercase()))
+            .collect();
+        let readable = |name: &String| PLUGIN_READABLE_VIEWS.contains(&name.as_str());
+        let allowed = match parts.as_deref() {
+            Some([owner, _]) if owner == self.schema => true,
+            Some([owner, name]) if owner == "main" => readable(name),
+            Some([name]) => readable(name) || self.scopes.iter().any(|scope| scope.contains(name)),
+            _ => false,
+        };
+        if allowed {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(anyhow::anyhow!(
+                "Plugin {} may not access {}: plugin tables live in schema {}",
+                self.plugin,
+                relation,
+                self.schema
+            ))
+        }
+    }
+}
+
+/// Whether a plugin may use the table named by `parts` in this way
+fn check_plugin_table(
+    plugin: &str,
+    schema: &str,
+    parts: &[String],
+    table_use: TableUse,
+    ctes: &HashSet<String>,
+) -> Result<()> {
+    let allowed = match (parts, table_use) {
+        ([name], TableUse::Schema) => name == schema,
+        ([owner, _], TableUse::Read | TableUse::Write) if owner == schema => true,
+        ([owner, name], TableUse::Read) if owner == "main" => {
+            PLUGIN_READABLE_VIEWS.contains(&name.as_str())
+        }
+        ([name], TableUse::Read) => {
+            PLUGIN_READABLE_VIEWS.contains(&name.as_str()) || ctes.contains(name)
+        }
+        _ => false,
+    };
+    if !allowed {
+        let action = match table_use {
+            TableUse::Read => "read",
+            TableUse::Write | TableUse::Schema => "write",
+        };
+        anyhow::bail!(
+            "Plugin {} may not {} {}: plugin tables live in schema {}",
+            plugin,
+            action,
+            parts.join("."),
+            schema
+        );
+    }
+    Ok(())
+}
+
 #[derive(Debug, Serialize)]
 pub struct UpdateInfo {
     pub plugin_id: String,
@@ -85,7 +525,19 @@ pub struct UpdateInfo {
 impl PluginService {
     pub fn new(treeline_dir: &Path) -> Self {
         let plugins_dir = treeline_dir.join("plugins");
-        Self { plugins_dir }
+        Self {
+            plugins_dir,
+            repository: None,
+            sync_hooks: Vec::new(),
+        }
+    }
+
+    /// Create a plugin service that can also manage plugin views in the database
+    pub fn new_with_repository(treeline_dir: &Path, repository: Arc<DuckDbRepository>) -> Self {
+        Self {
+            repository: Some(repository),
+            ..Self::new(treeline_dir)
+        }
     }
 
     /// Create a new plugin from embedded template
@@ -127,8 +579,6 @@ impl PluginService {
         fs::create_dir_all(plugin_dir.join("scripts"))?;
         fs::create_dir_all(plugin_dir.join(".github/workflows"))?;
 
-        // Compute plugin names
-        let table_safe_name = name.replace('-', "_");
         let display_name = name.replace('-', " ").replace('_', " ");
         let display_name: String = display_name
             .split_whitespace()
@@ -149,7 +599,7 @@ impl PluginService {
         manifest["name"] = serde_json::Value::String(display_name);
         manifest["permissions"] = serde_json::json!({
             "read": ["transactions", "accounts"],
-            "schemaName": format!("plugin_{}", table_safe_name)
+            "schemaName": plugin_schema_name(name)
         });
         fs::write(
             plugin_dir.join("manifest.json"),
@@ -492,6 +942,10 @@ impl PluginService {
 
         fs::remove_dir_all(&plugin_dir)?;
 
+        if let Some(repository) = &self.repository {
+            repository.drop_plugin_views(plugin_id)?;
+        }
+
         Ok(PluginResult {
             success: true,
             plugin_id: Some(plugin_id.to_string()),
@@ -500,6 +954,167 @@ impl PluginService {
         })
     }
 
+    /// Register a view that extends the query surface with plugin data
+    ///
+    /// The view lives in the plugin's schema (see [`plugin_schema_name`]), so
+    /// plugins can't collide with each other or with core views, and is
+    /// recorded so it survives compaction. `sql` must be a single SELECT and
+    /// may only read what [`execute_plugin_sql`](Self::execute_plugin_sql)
+    /// allows, so a view can't expose other data through the plugin's schema.
+    /// Registering the same view name again replaces it.
+    pub fn register_view(&self, plugin: &str, view_name: &str, sql: &str) -> Result<()> {
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Plugin views need a database connection"))?;
+
+        check_plugin_id(plugin)?;
+        if view_name.is_empty() || !view_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            anyhow::bail!(
+                "View name must contain only letters, numbers, and underscores: {}",
+                view_name
+            );
+        }
+
+        let statements = Parser::parse_sql(&DuckDbDialect {}, sql)
+            .map_err(|e| anyhow::anyhow!("Invalid view SQL: {}", e))?;
+        if statements.len() != 1 {
+            anyhow::bail!("View SQL must be a single SELECT query");
+        }
+        ensure_read_only(sql).map_err(|e| anyhow::anyhow!("Invalid view SQL: {}", e))?;
+        check_plugin_sql(plugin, sql)?;
+
+        repository.create_plugin_view(plugin, view_name, sql)?;
+        Ok(())
+    }
+
+    /// Views registered by a plugin
+    pub fn list_views(&self, plugin: &str) -> Result<Vec<PluginView>> {
+        match &self.repository {
+            Some(repository) => repository.get_plugin_views(Some(plugin)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Compare a plugin's declared tables with what exists in the database
+    ///
+    /// The manifest's `tables` are looked up in the plugin's schema
+    /// (`permissions.schemaName`, else [`plugin_schema_name`]). Returns one
+    /// message per missing table, missing column or type mismatch; an empty
+    /// list means the schema matches. Extra tables and columns are allowed.
+    pub fn validate_schema(&self, plugin: &str) -> Result<Vec<String>> {
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Plugin schema checks need a database connection"))?;
+
+        let manifest_path = self.plugins_dir.join(plugin).join("manifest.json");
+        let content = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Plugin not found: {}", plugin))?;
+        let manifest: PluginManifest = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid manifest for plugin {}", plugin))?;
+
+        let schema = manifest
+            .permissions
+            .get("schemaName")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| plugin_schema_name(&manifest.id));
+
+        let mut actual: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for (table, column, data_type) in repository.get_schema_columns(&schema)? {
+            actual.entry(table).or_default().insert(column, data_type);
+        }
+
+        let mut problems = Vec::new();
+        for (table, columns) in &manifest.tables {
+            let existing = match actual.get(table) {
+                Some(existing) => existing,
+                None => {
+                    problems.push(format!("Table {}.{} is missing", schema, table));
+                    continue;
+                }
+            };
+            for (column, declared) in columns {
+                match existing.get(column) {
+                    None => {
+                        problems.push(format!("Column {}.{}.{} is missing", schema, table, column))
+                    }
+                    Some(found) if normalize_sql_type(found) != normalize_sql_type(declared) => {
+                        problems.push(format!(
+                            "Column {}.{}.{} is {}, but the manifest declares {}",
+                            schema, table, column, found, declared
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        Ok(problems)
+    }
+
+    /// Run SQL on behalf of a plugin, confined to the plugin's own data
+    ///
+    /// Plugins own the tables in their schema (see [`plugin_schema_name`])
+    /// and may also read the `transactions` and `accounts` views. Everything
+    /// else, including all `sys_*` tables, is rejected before the SQL reaches
+    /// the database.
+    pub fn execute_plugin_sql(&self, plugin: &str, sql: &str) -> Result<QueryResult> {
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Plugin SQL needs a database connection"))?;
+
+        check_plugin_id(plugin)?;
+        check_plugin_sql(plugin, sql)?;
+
+        repository.execute_sql(sql)
+    }
+
+    /// Register a plugin's post-sync callback
+    ///
+    /// The plugin must be installed and list [`SYNC_COMPLETE_HOOK`] in its
+    /// manifest's `hooks`. Registering again replaces the earlier callback.
+    pub fn register_sync_hook(
+        &mut self,
+        plugin_id: &str,
+        hook: impl SyncHook + 'static,
+    ) -> Result<()> {
+        let manifest_path = self.plugins_dir.join(plugin_id).join("manifest.json");
+        let content = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Plugin {} is not installed", plugin_id))?;
+        let manifest: PluginManifest = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid manifest for plugin {}", plugin_id))?;
+        if !manifest.hooks.iter().any(|h| h == SYNC_COMPLETE_HOOK) {
+            anyhow::bail!(
+                "Plugin {} does not declare the {} hook in its manifest",
+                plugin_id,
+                SYNC_COMPLETE_HOOK
+            );
+        }
+
+        self.sync_hooks.retain(|(id, _)| id != plugin_id);
+        self.sync_hooks
+            .push((plugin_id.to_string(), Box::new(hook)));
+        Ok(())
+    }
+
+    /// Run every registered post-sync callback, in registration order
+    ///
+    /// A failing hook doesn't stop the others; each failure is returned as a
+    /// warning prefixed with the plugin id.
+    pub fn run_sync_hooks(&self, ctx: &TreelineContext) -> Vec<String> {
+        self.sync_hooks
+            .iter()
+            .filter_map(|(plugin_id, hook)| {
+                hook.on_sync_complete(ctx)
+                    .err()
+                    .map(|e| format!("{}: {:#}", plugin_id, e))
+            })
+            .collect()
+    }
+
     /// List installed plugins
     pub fn list_plugins(&self) -> Result<Vec<PluginInfo>> {
         let mut plugins = Vec::new();
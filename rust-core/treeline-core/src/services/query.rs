@@ -1,24 +1,97 @@
-//! Query service - SQL query execution
+//! Query service - SQL query execution and data export
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlparser::dialect::DuckDbDialect;
+use sqlparser::parser::Parser;
 
-use crate::adapters::duckdb::{DuckDbRepository, QueryResult};
+use crate::adapters::duckdb::{ensure_read_only, DuckDbRepository, QueryLimits, QueryResult};
+use crate::config::Config;
+use crate::domain::{Account, Transaction};
+
+/// Pseudo-tag that `spending_by_tag` groups untagged transactions under
+pub const UNTAGGED: &str = "untagged";
 
 /// Query service for SQL execution
 pub struct QueryService {
     repository: Arc<DuckDbRepository>,
+    limits: QueryLimits,
+    treeline_dir: Option<PathBuf>,
+    cache: Option<Mutex<QueryCache>>,
 }
 
 impl QueryService {
     pub fn new(repository: Arc<DuckDbRepository>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            limits: QueryLimits::default(),
+            treeline_dir: None,
+            cache: None,
+        }
+    }
+
+    /// Create a query service that caches the results of read queries
+    ///
+    /// Up to `capacity` SELECT/WITH results are kept in memory, keyed by their
+    /// SQL and parameters, and reused for `ttl`; the least recently used one is
+    /// dropped to make room. Any write through the repository empties the
+    /// cache. Writes by other processes are only picked up once `ttl` passes.
+    pub fn new_with_cache(
+        repository: Arc<DuckDbRepository>,
+        capacity: usize,
+        ttl: Duration,
+    ) -> Self {
+        let mut service = Self::new(repository);
+        if capacity > 0 {
+            service.cache = Some(Mutex::new(QueryCache::new(capacity, ttl)));
+        }
+        service
+    }
+
+    /// Keep named queries in this directory's settings.json
+    pub fn with_treeline_dir(mut self, treeline_dir: PathBuf) -> Self {
+        self.treeline_dir = Some(treeline_dir);
+        self
+    }
+
+    /// Apply a timeout and row limit to every `execute`/`execute_sql` call
+    pub fn with_limits(mut self, limits: QueryLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Execute a read-only SQL query (SELECT only)
     pub fn execute(&self, sql: &str) -> Result<QueryResult> {
-        self.repository.execute_query(sql)
+        self.execute_with_limits(sql, self.limits)
+    }
+
+    /// Execute a read-only SQL query with limits overriding the configured ones
+    pub fn execute_with_limits(&self, sql: &str, limits: QueryLimits) -> Result<QueryResult> {
+        self.cached(sql, &[], limits.max_rows, || {
+            self.repository.execute_query_with_limits(sql, limits)
+        })
+    }
+
+    /// Show how DuckDB would run a read-only query, without running it
+    ///
+    /// Returns the EXPLAIN rows; writes are rejected as in `execute`.
+    pub fn explain(&self, sql: &str) -> Result<QueryResult> {
+        self.repository.explain_query(sql, false, self.limits)
+    }
+
+    /// Run a read-only query under EXPLAIN ANALYZE, with the configured timeout
+    ///
+    /// The plan includes how long each step took and how many rows it produced,
+    /// which shows where a slow query spends its time.
+    pub fn explain_analyze(&self, sql: &str) -> Result<QueryResult> {
+        self.repository.explain_query(sql, true, self.limits)
     }
 
     /// Execute arbitrary SQL (read or write)
@@ -26,7 +99,101 @@ impl QueryService {
     /// For SELECT queries, returns columns and rows.
     /// For write queries (INSERT/UPDATE/DELETE), returns affected_rows count.
     pub fn execute_sql(&self, sql: &str) -> Result<QueryResult> {
-        self.repository.execute_sql(sql)
+        self.execute_sql_with_limits(sql, self.limits)
+    }
+
+    /// Execute arbitrary SQL with limits overriding the configured ones
+    pub fn execute_sql_with_limits(&self, sql: &str, limits: QueryLimits) -> Result<QueryResult> {
+        self.cached(sql, &[], limits.max_rows, || {
+            self.repository.execute_sql_with_limits(sql, limits)
+        })
+    }
+
+    /// The limits applied by `execute` and `execute_sql`
+    pub fn limits(&self) -> QueryLimits {
+        self.limits
+    }
+
+    /// Save a read-only query under `name`, replacing any query of that name
+    ///
+    /// `sql` must be a single SELECT; it is stored in settings.json.
+    pub fn save_named(&self, name: &str, sql: &str) -> Result<()> {
+        let treeline_dir = self.named_queries_dir()?;
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!(
+                "Query name must contain only letters, numbers, hyphens and underscores: {}",
+                name
+            );
+        }
+        check_named_sql(sql)?;
+
+        let mut config = Config::load(treeline_dir)?;
+        config
+            .named_queries
+            .insert(name.to_string(), sql.trim().to_string());
+        config.save(treeline_dir)
+    }
+
+    /// SQL of the query saved under `name`
+    pub fn named_query(&self, name: &str) -> Result<String> {
+        let config = Config::load(self.named_queries_dir()?)?;
+        config
+            .named_queries
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No saved query named {}", name))
+    }
+
+    /// Run the query saved under `name`, with the configured limits
+    pub fn run_named(&self, name: &str) -> Result<QueryResult> {
+        self.run_named_with(name, &HashMap::new())
+    }
+
+    /// Run the query saved under `name`, binding its `:param` placeholders
+    ///
+    /// Each placeholder becomes a bound `?` parameter, so values never become
+    /// SQL. Fails, naming every missing parameter, unless `params` has a value
+    /// for each placeholder; extra entries are ignored. Runs with the
+    /// configured limits, like [`run_named`](Self::run_named).
+    pub fn run_named_with(
+        &self,
+        name: &str,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> Result<QueryResult> {
+        let sql = self.named_query(name)?;
+        // settings.json can be edited or imported, so don't trust what was saved
+        check_named_sql(&sql)?;
+        let (rewritten, placeholders) = rewrite_named_params(&sql);
+        if placeholders.is_empty() {
+            return self.execute(&sql);
+        }
+
+        let mut missing: Vec<&str> = Vec::new();
+        for placeholder in &placeholders {
+            if !params.contains_key(placeholder) && !missing.contains(&placeholder.as_str()) {
+                missing.push(placeholder);
+            }
+        }
+        if !missing.is_empty() {
+            anyhow::bail!("Query {} needs values for: {}", name, missing.join(", "));
+        }
+
+        let values: Vec<serde_json::Value> =
+            placeholders.iter().map(|p| params[p].clone()).collect();
+        self.cached(&rewritten, &values, self.limits.max_rows, || {
+            self.repository
+                .execute_sql_with_params_and_limits(&rewritten, &values, self.limits)
+        })
+    }
+
+    fn named_queries_dir(&self) -> Result<&Path> {
+        self.treeline_dir
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Named queries need the treeline directory"))
     }
 
     /// Execute parameterized SQL (read or write)
@@ -37,6 +204,669 @@ impl QueryService {
         sql: &str,
         params: &[serde_json::Value],
     ) -> Result<QueryResult> {
-        self.repository.execute_sql_with_params(sql, params)
+        self.cached(sql, params, None, || {
+            self.repository.execute_sql_with_params(sql, params)
+        })
+    }
+
+    /// Drop every cached result
+    pub fn invalidate(&self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().clear();
+        }
+    }
+
+    /// Hit and miss counts of the result cache, if it is enabled
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| {
+            let cache = cache.lock().unwrap();
+            CacheStats {
+                hits: cache.hits,
+                misses: cache.misses,
+                entries: cache.entries.len(),
+            }
+        })
+    }
+
+    /// Serve a read query from the cache, or run it with `run` and cache the result
+    fn cached(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+        max_rows: Option<usize>,
+        run: impl FnOnce() -> Result<QueryResult>,
+    ) -> Result<QueryResult> {
+        let cache = match &self.cache {
+            Some(cache) if is_cacheable(sql) => cache,
+            _ => return run(),
+        };
+
+        // Read the version before running, so a write racing the query
+        // leaves the entry tagged with the older version
+        let data_version = self.repository.data_version();
+        let key = CacheKey {
+            sql: sql.to_string(),
+            params: serde_json::to_string(params)?,
+            max_rows,
+        };
+        if let Some(result) = cache.lock().unwrap().get(&key, data_version) {
+            return Ok(result);
+        }
+
+        let result = run()?;
+        cache
+            .lock()
+            .unwrap()
+            .insert(key, result.clone(), data_version);
+        Ok(result)
+    }
+
+    /// Distribution of expense sizes in equal-width buckets
+    ///
+    /// Spans the smallest to the largest expense (as a positive amount),
+    /// optionally for one account, with transfers left out. Every bucket is
+    /// returned, including empty ones. If all expenses are the same size the
+    /// result is a single bucket; with no expenses it is empty.
+    pub fn amount_histogram(
+        &self,
+        account_id: Option<&str>,
+        buckets: usize,
+    ) -> Result<Vec<HistogramBucket>> {
+        if buckets == 0 {
+            anyhow::bail!("Number of buckets must be at least 1");
+        }
+
+        let rows = self.repository.get_expense_histogram(account_id, buckets)?;
+        let (min, max) = match rows.first() {
+            Some(&(_, _, min, max)) => (to_amount(min), to_amount(max)),
+            None => return Ok(Vec::new()),
+        };
+
+        if min == max {
+            return Ok(vec![HistogramBucket {
+                lower: min,
+                upper: max,
+                count: rows.iter().map(|&(_, count, _, _)| count).sum(),
+            }]);
+        }
+
+        let width = (max - min) / Decimal::from(buckets as u64);
+        let mut histogram: Vec<HistogramBucket> = (0..buckets)
+            .map(|i| {
+                let lower = min + width * Decimal::from(i as u64);
+                let upper = if i + 1 == buckets { max } else { lower + width };
+                HistogramBucket {
+                    lower: lower.round_dp(2),
+                    upper: upper.round_dp(2),
+                    count: 0,
+                }
+            })
+            .collect();
+        for (bucket, count, _, _) in rows {
+            if let Some(entry) = histogram.get_mut(bucket) {
+                entry.count += count;
+            }
+        }
+        Ok(histogram)
+    }
+
+    /// Spending per tag between `start` and `end` (inclusive)
+    ///
+    /// Totals are positive amounts spent, largest first. A transaction with
+    /// several tags counts toward each of them, and untagged transactions are
+    /// grouped under [`UNTAGGED`]. Transfers are left out.
+    pub fn spending_by_tag(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(String, Decimal)>> {
+        if start > end {
+            anyhow::bail!("Start date {} is after end date {}", start, end);
+        }
+        self.repository.get_spending_by_tag(start, end, UNTAGGED)
+    }
+
+    /// Total amount and transaction count per merchant
+    ///
+    /// Descriptions are grouped by [`Transaction::normalize_merchant`], so
+    /// "SQ *COFFEE 12345 SEATTLE WA" and "SQ *COFFEE 678 PORTLAND OR" count
+    /// as one merchant. Totals are net (refunds offset purchases) and
+    /// transfers are left out. Sorted by absolute total, largest first.
+    pub fn merchant_summary(&self) -> Result<Vec<(String, Decimal, i64)>> {
+        let mut merchants: HashMap<String, (Decimal, i64)> = HashMap::new();
+        for (description, total, count) in self.repository.get_description_totals()? {
+            if let Some(merchant) = Transaction::normalize_merchant(&description) {
+                let entry = merchants.entry(merchant).or_insert((Decimal::ZERO, 0));
+                entry.0 += total;
+                entry.1 += count;
+            }
+        }
+
+        let mut summary: Vec<(String, Decimal, i64)> = merchants
+            .into_iter()
+            .map(|(merchant, (total, count))| (merchant, total, count))
+            .collect();
+        summary.sort_by(|a, b| b.1.abs().cmp(&a.1.abs()).then_with(|| a.0.cmp(&b.0)));
+        Ok(summary)
+    }
+
+    /// Monthly spending on a tag over the last `months` calendar months
+    ///
+    /// Returns one entry per month, oldest first and keyed by the month's
+    /// first day, including the current month. Months without spending are
+    /// zero, so a tag that was never used gives an all-zero trend. Transfers
+    /// are left out, as in [`spending_by_tag`](Self::spending_by_tag).
+    pub fn tag_trend(&self, tag: &str, months: u32) -> Result<Vec<(NaiveDate, Decimal)>> {
+        self.tag_trend_as_of(tag, months, Utc::now().date_naive())
+    }
+
+    /// Like [`tag_trend`](Self::tag_trend), counting back from `today`
+    pub fn tag_trend_as_of(
+        &self,
+        tag: &str,
+        months: u32,
+        today: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Decimal)>> {
+        if months == 0 {
+            anyhow::bail!("Number of months must be at least 1");
+        }
+
+        let current = today.with_day(1).unwrap_or(today);
+        let range = current
+            .checked_sub_months(Months::new(months - 1))
+            .zip(current.checked_add_months(Months::new(1)));
+        let (start, end) = range.ok_or_else(|| anyhow::anyhow!("Too many months: {}", months))?;
+
+        let spend: HashMap<NaiveDate, Decimal> = self
+            .repository
+            .get_tag_monthly_spend(tag, start, end)?
+            .into_iter()
+            .collect();
+
+        Ok((0..months)
+            .filter_map(|i| start.checked_add_months(Months::new(i)))
+            .map(|month| (month, spend.get(&month).copied().unwrap_or(Decimal::ZERO)))
+            .collect())
+    }
+
+    /// Export an account's transactions as an OFX 2.x bank statement
+    ///
+    /// Each transaction becomes an `<STMTTRN>` with its UUID as `<FITID>`, so a
+    /// re-import can recognise it. The ledger balance comes from the latest
+    /// balance snapshot, falling back to the account balance.
+    pub fn export_ofx(&self, path: &Path, account_id: &str) -> Result<()> {
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+
+        let mut transactions = self.repository.get_transactions_by_account(account_id)?;
+        transactions.sort_by_key(|tx| (tx.posted_date, tx.transaction_date));
+
+        // Snapshots are returned newest first
+        let ledger = self
+            .repository
+            .get_balance_snapshots(Some(account_id))?
+            .into_iter()
+            .next()
+            .map(|s| (s.balance, s.snapshot_time))
+            .unwrap_or_else(|| {
+                (
+                    account.balance.unwrap_or(Decimal::ZERO),
+                    Utc::now().naive_utc(),
+                )
+            });
+
+        let ofx = build_ofx(&account, &transactions, ledger, Utc::now().naive_utc());
+        std::fs::write(path, ofx)
+            .with_context(|| format!("Failed to write OFX file: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Statement for one account between `start` and `end` (inclusive)
+    ///
+    /// Transactions are ordered by posted date, as a bank statement would list
+    /// them, each with the balance after it. The opening balance is the last
+    /// snapshot before `start`, carried forward over any transactions between
+    /// it and `start`. Without such a snapshot it is worked back from the
+    /// closing balance and the period's transactions. The closing balance comes
+    /// from the nearest snapshot (or, failing that, the account balance)
+    /// adjusted by the transactions in between, so a gap in the data shows up
+    /// as a difference between it and the last running balance.
+    pub fn account_statement(
+        &self,
+        account_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<AccountStatement> {
+        if start > end {
+            anyhow::bail!("Start date {} is after end date {}", start, end);
+        }
+        let account = self
+            .repository
+            .get_account_by_id(account_id)?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", account_id))?;
+
+        let mut transactions = self.repository.get_transactions_by_account(account_id)?;
+        transactions.sort_by_key(|tx| (tx.posted_date, tx.transaction_date, tx.created_at));
+        let sum_between = |after: NaiveDate, up_to: NaiveDate| -> Decimal {
+            transactions
+                .iter()
+                .filter(|tx| tx.posted_date > after && tx.posted_date <= up_to)
+                .map(|tx| tx.amount)
+                .sum()
+        };
+
+        // Oldest first
+        let mut snapshots = self.repository.get_balance_snapshots(Some(account_id))?;
+        snapshots.sort_by_key(|s| s.snapshot_time);
+
+        let closing_balance = match snapshots
+            .iter()
+            .rev()
+            .find(|s| s.snapshot_time.date() <= end)
+        {
+            Some(s) => s.balance + sum_between(s.snapshot_time.date(), end),
+            None => match snapshots.first() {
+                Some(s) => s.balance - sum_between(end, s.snapshot_time.date()),
+                None => {
+                    let after_end: Decimal = transactions
+                        .iter()
+                        .filter(|tx| tx.posted_date > end)
+                        .map(|tx| tx.amount)
+                        .sum();
+                    account.balance.unwrap_or(Decimal::ZERO) - after_end
+                }
+            },
+        };
+
+        let period: Vec<&Transaction> = transactions
+            .iter()
+            .filter(|tx| tx.posted_date >= start && tx.posted_date <= end)
+            .collect();
+        let period_total: Decimal = period.iter().map(|tx| tx.amount).sum();
+
+        let day_before_start = start.pred_opt().unwrap_or(start);
+        let opening_snapshot = snapshots
+            .iter()
+            .rev()
+            .find(|s| s.snapshot_time.date() < start);
+        let (opening_balance, opening_estimated) = match opening_snapshot {
+            Some(s) => (
+                s.balance + sum_between(s.snapshot_time.date(), day_before_start),
+                false,
+            ),
+            None => (closing_balance - period_total, true),
+        };
+
+        let mut running = opening_balance;
+        let lines = period
+            .into_iter()
+            .map(|tx| {
+                running += tx.amount;
+                StatementLine {
+                    transaction_id: tx.id.to_string(),
+                    date: tx.posted_date,
+                    description: tx.description.clone(),
+                    amount: tx.amount,
+                    balance: running,
+                }
+            })
+            .collect();
+
+        Ok(AccountStatement {
+            account,
+            start,
+            end,
+            opening_balance,
+            opening_estimated,
+            closing_balance,
+            lines,
+        })
+    }
+}
+
+/// One account's transactions and balances over a period
+///
+/// Built by [`QueryService::account_statement`] for rendering as PDF, HTML
+/// or a table.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountStatement {
+    pub account: Account,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub opening_balance: Decimal,
+    /// True when no snapshot precedes `start` and the opening balance was
+    /// worked back from the closing balance
+    pub opening_estimated: bool,
+    pub closing_balance: Decimal,
+    pub lines: Vec<StatementLine>,
+}
+
+/// A transaction on a statement, with the account balance after it
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementLine {
+    pub transaction_id: String,
+    pub date: NaiveDate,
+    pub description: Option<String>,
+    pub amount: Decimal,
+    pub balance: Decimal,
+}
+
+/// One bar of an amount histogram: expenses from `lower` up to `upper`
+///
+/// Buckets include their lower bound; the last one also includes `upper`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistogramBucket {
+    pub lower: Decimal,
+    pub upper: Decimal,
+    pub count: i64,
+}
+
+fn to_amount(value: f64) -> Decimal {
+    Decimal::try_from(value)
+        .unwrap_or(Decimal::ZERO)
+        .round_dp(2)
+}
+
+/// Render an OFX 2.x document with a single bank statement
+fn build_ofx(
+    account: &Account,
+    transactions: &[Transaction],
+    (ledger_balance, ledger_time): (Decimal, NaiveDateTime),
+    now: NaiveDateTime,
+) -> String {
+    let ofx_date = |d: NaiveDate| d.format("%Y%m%d").to_string();
+    let ofx_datetime = |dt: NaiveDateTime| dt.format("%Y%m%d%H%M%S").to_string();
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    out.push_str(
+        "<?OFX OFXHEADER=\"200\" VERSION=\"202\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n",
+    );
+    out.push_str("<OFX>\n");
+
+    // Sign-on response (required by the spec)
+    out.push_str("<SIGNONMSGSRSV1><SONRS>\n");
+    out.push_str("<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\n");
+    out.push_str(&format!("<DTSERVER>{}</DTSERVER>\n", ofx_datetime(now)));
+    out.push_str("<LANGUAGE>ENG</LANGUAGE>\n");
+    out.push_str("</SONRS></SIGNONMSGSRSV1>\n");
+
+    out.push_str("<BANKMSGSRSV1><STMTTRNRS>\n");
+    out.push_str("<TRNUID>0</TRNUID>\n");
+    out.push_str("<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\n");
+    out.push_str("<STMTRS>\n");
+    out.push_str(&format!(
+        "<CURDEF>{}</CURDEF>\n",
+        xml_escape(&account.currency)
+    ));
+    out.push_str("<BANKACCTFROM>\n");
+    // No routing number is stored, so BANKID is a placeholder
+    out.push_str("<BANKID>000000000</BANKID>\n");
+    out.push_str(&format!("<ACCTID>{}</ACCTID>\n", account.id));
+    out.push_str("<ACCTTYPE>CHECKING</ACCTTYPE>\n");
+    out.push_str("</BANKACCTFROM>\n");
+
+    out.push_str("<BANKTRANLIST>\n");
+    let start = transactions
+        .iter()
+        .map(|tx| tx.posted_date)
+        .min()
+        .unwrap_or(now.date());
+    let end = transactions
+        .iter()
+        .map(|tx| tx.posted_date)
+        .max()
+        .unwrap_or(now.date());
+    out.push_str(&format!("<DTSTART>{}</DTSTART>\n", ofx_date(start)));
+    out.push_str(&format!("<DTEND>{}</DTEND>\n", ofx_date(end)));
+
+    for tx in transactions {
+        // OFX uses the same sign convention we do: negative = money out
+        let trn_type = if tx.amount < Decimal::ZERO {
+            "DEBIT"
+        } else {
+            "CREDIT"
+        };
+        let description = tx.description.as_deref().unwrap_or("");
+
+        out.push_str("<STMTTRN>\n");
+        out.push_str(&format!("<TRNTYPE>{}</TRNTYPE>\n", trn_type));
+        out.push_str(&format!(
+            "<DTPOSTED>{}</DTPOSTED>\n",
+            ofx_date(tx.posted_date)
+        ));
+        out.push_str(&format!(
+            "<DTUSER>{}</DTUSER>\n",
+            ofx_date(tx.transaction_date)
+        ));
+        out.push_str(&format!("<TRNAMT>{:.2}</TRNAMT>\n", tx.amount));
+        out.push_str(&format!("<FITID>{}</FITID>\n", tx.id));
+        // NAME is limited to 32 characters; MEMO keeps the full description
+        let name: String = description.chars().take(32).collect();
+        out.push_str(&format!("<NAME>{}</NAME>\n", xml_escape(&name)));
+        if !description.is_empty() {
+            out.push_str(&format!("<MEMO>{}</MEMO>\n", xml_escape(description)));
+        }
+        out.push_str("</STMTTRN>\n");
     }
+    out.push_str("</BANKTRANLIST>\n");
+
+    out.push_str("<LEDGERBAL>\n");
+    out.push_str(&format!("<BALAMT>{:.2}</BALAMT>\n", ledger_balance));
+    out.push_str(&format!("<DTASOF>{}</DTASOF>\n", ofx_datetime(ledger_time)));
+    out.push_str("</LEDGERBAL>\n");
+
+    out.push_str("</STMTRS>\n");
+    out.push_str("</STMTTRNRS></BANKMSGSRSV1>\n");
+    out.push_str("</OFX>\n");
+    out
+}
+
+/// Check that SQL is fit to save as a named query: a single read-only SELECT
+pub(crate) fn check_named_sql(sql: &str) -> Result<()> {
+    let statements = Parser::parse_sql(&DuckDbDialect {}, sql)
+        .map_err(|e| anyhow::anyhow!("Invalid SQL: {}", e))?;
+    if statements.len() != 1 {
+        anyhow::bail!("Named queries must be a single SELECT query");
+    }
+    ensure_read_only(sql).map_err(|e| anyhow::anyhow!("Invalid named query: {}", e))
+}
+
+/// Escape text for inclusion in an XML element
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Only read-only queries are worth caching; anything else may write
+fn is_cacheable(sql: &str) -> bool {
+    ensure_read_only(sql).is_ok()
+}
+
+/// How often cached results were reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Results currently cached
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    sql: String,
+    /// Bound parameters as JSON
+    params: String,
+    /// Row limit, since it changes the result
+    max_rows: Option<usize>,
+}
+
+struct CachedResult {
+    result: QueryResult,
+    cached_at: Instant,
+    /// Value of `QueryCache::clock` when last read
+    last_used: u64,
+}
+
+/// LRU cache of read query results with a time to live
+struct QueryCache {
+    capacity: usize,
+    ttl: Duration,
+    /// Repository data version the entries were read at
+    data_version: u64,
+    entries: HashMap<CacheKey, CachedResult>,
+    /// Ticks on every lookup; the entry with the lowest `last_used` is the least recent
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl QueryCache {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            data_version: 0,
+            entries: HashMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Look up a fresh result, emptying the cache if the data has changed
+    fn get(&mut self, key: &CacheKey, data_version: u64) -> Option<QueryResult> {
+        if data_version > self.data_version {
+            self.clear();
+            self.data_version = data_version;
+        } else if data_version < self.data_version {
+            // Read before a write another lookup has already seen
+            self.misses += 1;
+            return None;
+        }
+
+        self.clock += 1;
+        let fresh = self
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.cached_at.elapsed() < self.ttl);
+        if !fresh {
+            self.entries.remove(key);
+            self.misses += 1;
+            return None;
+        }
+
+        self.hits += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.result.clone())
+    }
+
+    /// Cache a result read at `data_version`, unless the data has changed since
+    fn insert(&mut self, key: CacheKey, result: QueryResult, data_version: u64) {
+        if data_version != self.data_version {
+            return;
+        }
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(
+            key,
+            CachedResult {
+                result,
+                cached_at: Instant::now(),
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+/// Replace `:name` placeholders with `?`, returning the SQL and the names in order
+///
+/// Names repeat when a placeholder is used more than once. `::` casts and
+/// anything inside quotes or comments are left alone.
+fn rewrite_named_params(sql: &str) -> (String, Vec<String>) {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut rewritten = String::with_capacity(sql.len());
+    let mut names = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                rewritten.push(c);
+                i += 1;
+            }
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                rewritten.push(c);
+                i += 1;
+            }
+            // Comments are copied as they are, so `:word` in them isn't a placeholder
+            None if c == '-' && chars.get(i + 1) == Some(&'-') => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|&c| c == '\n')
+                    .map_or(chars.len(), |n| i + n);
+                rewritten.extend(&chars[i..end]);
+                i = end;
+            }
+            None if c == '/' && chars.get(i + 1) == Some(&'*') => {
+                let end = chars[i + 2..]
+                    .windows(2)
+                    .position(|w| w == ['*', '/'])
+                    .map_or(chars.len(), |n| i + 2 + n + 2);
+                rewritten.extend(&chars[i..end]);
+                i = end;
+            }
+            None if c == ':' && chars.get(i + 1) == Some(&':') => {
+                rewritten.push_str("::");
+                i += 2;
+            }
+            None if c == ':'
+                && chars
+                    .get(i + 1)
+                    .is_some_and(|n| n.is_ascii_alphabetic() || *n == '_') =>
+            {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_')
+                {
+                    end += 1;
+                }
+                names.push(chars[start..end].iter().collect());
+                rewritten.push('?');
+                i = end;
+            }
+            None => {
+                rewritten.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    (rewritten, names)
 }
@@ -1,29 +1,85 @@
 //! Status service - account and transaction summaries
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
 use serde::Serialize;
 
 use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{Account, DateBasis, FiscalCalendar, FiscalMonth, DEFAULT_ACCOUNT_GROUP};
 
 /// Status service for account summaries
 pub struct StatusService {
     repository: Arc<DuckDbRepository>,
+    date_basis: DateBasis,
+    fiscal_calendar: FiscalCalendar,
 }
 
 impl StatusService {
     pub fn new(repository: Arc<DuckDbRepository>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            date_basis: DateBasis::default(),
+            fiscal_calendar: FiscalCalendar::default(),
+        }
+    }
+
+    /// Report the transaction date range by this date basis instead
+    pub fn with_date_basis(mut self, date_basis: DateBasis) -> Self {
+        self.date_basis = date_basis;
+        self
+    }
+
+    /// Group cash flow by fiscal months instead of calendar months
+    pub fn with_fiscal_calendar(mut self, fiscal_calendar: FiscalCalendar) -> Self {
+        self.fiscal_calendar = fiscal_calendar;
+        self
     }
 
     /// Get overall status summary
-    pub fn get_status(&self) -> Result<StatusSummary> {
-        let accounts = self.repository.get_accounts()?;
+    ///
+    /// Archived accounts are excluded from the account list and net worth
+    /// unless `include_archived` is true. Net worth is also subtotaled per
+    /// account group, ungrouped accounts counting toward [`DEFAULT_ACCOUNT_GROUP`].
+    pub fn get_status(&self, include_archived: bool) -> Result<StatusSummary> {
+        let accounts = self.repository.get_accounts(include_archived)?;
         let transaction_count = self.repository.get_transaction_count()?;
         let snapshot_count = self.repository.get_balance_snapshot_count()?;
         let integrations = self.repository.get_integrations()?;
-        let date_range = self.repository.get_transaction_date_range()?;
+        let date_range = self
+            .repository
+            .get_transaction_date_range_by(self.date_basis)?;
+
+        let net_worth = accounts.iter().map(net_worth_contribution).sum::<Decimal>();
+
+        // Named groups alphabetically, then the default group
+        let mut groups: Vec<GroupSubtotal> = Vec::new();
+        for account in &accounts {
+            let name = account
+                .account_group
+                .as_deref()
+                .unwrap_or(DEFAULT_ACCOUNT_GROUP);
+            let index = match groups.iter().position(|g| g.name == name) {
+                Some(index) => index,
+                None => {
+                    groups.push(GroupSubtotal {
+                        name: name.to_string(),
+                        account_count: 0,
+                        net_worth: Decimal::ZERO,
+                    });
+                    groups.len() - 1
+                }
+            };
+            groups[index].account_count += 1;
+            groups[index].net_worth += net_worth_contribution(account);
+        }
+        groups.sort_by(|a, b| {
+            (a.name == DEFAULT_ACCOUNT_GROUP, &a.name)
+                .cmp(&(b.name == DEFAULT_ACCOUNT_GROUP, &b.name))
+        });
 
         Ok(StatusSummary {
             total_accounts: accounts.len() as i64,
@@ -31,17 +87,91 @@ impl StatusService {
             total_snapshots: snapshot_count,
             total_integrations: integrations.len() as i64,
             integration_names: integrations.iter().map(|i| i.name.clone()).collect(),
+            net_worth,
+            groups,
             accounts: accounts
                 .into_iter()
                 .map(|a| AccountSummary {
                     id: a.id.to_string(),
                     name: a.name,
                     institution_name: a.institution_name,
+                    is_archived: a.is_archived,
+                    group: a.account_group,
                 })
                 .collect(),
             date_range,
         })
     }
+
+    /// Income vs. expenses for the last `months` fiscal months, oldest first
+    ///
+    /// Fiscal months are calendar months unless a fiscal calendar is set.
+    /// The current month is included and flagged as partial. Months without
+    /// any transactions are reported as zeros.
+    pub fn cash_flow(&self, months: u32) -> Result<Vec<MonthlyCashFlow>> {
+        self.cash_flow_as_of(months, Local::now().date_naive())
+    }
+
+    /// Same as [`cash_flow`](Self::cash_flow), treating `today` as the current day
+    pub fn cash_flow_as_of(&self, months: u32, today: NaiveDate) -> Result<Vec<MonthlyCashFlow>> {
+        if months == 0 {
+            anyhow::bail!("Number of months must be at least 1");
+        }
+
+        let calendar = self.fiscal_calendar;
+        let current_month = calendar.fiscal_month(today);
+        let first_month = i32::try_from(months - 1)
+            .ok()
+            .and_then(|back| current_month.offset(-back));
+        let range = first_month.and_then(|first| {
+            Some((
+                first,
+                calendar.start_of(first)?,
+                calendar.end_of(current_month)?,
+            ))
+        });
+        let (first_month, start, end) =
+            range.ok_or_else(|| anyhow::anyhow!("Too many months: {}", months))?;
+
+        let mut totals: HashMap<FiscalMonth, (Decimal, Decimal)> = HashMap::new();
+        for (day, income, expenses) in
+            self.repository
+                .get_daily_cash_flow(self.date_basis, start, end)?
+        {
+            let entry = totals
+                .entry(calendar.fiscal_month(day))
+                .or_insert((Decimal::ZERO, Decimal::ZERO));
+            entry.0 += income;
+            entry.1 += expenses;
+        }
+
+        let mut result = Vec::with_capacity(months as usize);
+        let mut next = Some(first_month);
+        while let Some(month) = next.filter(|m| *m <= current_month) {
+            let (income, expenses) = totals
+                .get(&month)
+                .copied()
+                .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+            result.push(MonthlyCashFlow {
+                month: month.to_string(),
+                income,
+                expenses,
+                net: income - expenses,
+                is_partial: month == current_month,
+            });
+            next = month.offset(1);
+        }
+
+        Ok(result)
+    }
+}
+
+/// What an account adds to net worth: its latest balance, or nothing without one
+///
+/// Balances are used as stored, so liabilities (stored negative) reduce net
+/// worth, as in [`BalanceService::net_worth_series`](crate::services::BalanceService::net_worth_series).
+fn net_worth_contribution(account: &Account) -> Decimal {
+    account.balance.unwrap_or_default()
 }
 
 #[derive(Debug, Serialize)]
@@ -51,6 +181,9 @@ pub struct StatusSummary {
     pub total_snapshots: i64,
     pub total_integrations: i64,
     pub integration_names: Vec<String>,
+    pub net_worth: Decimal,
+    /// Net worth per account group; the subtotals add up to `net_worth`
+    pub groups: Vec<GroupSubtotal>,
     pub accounts: Vec<AccountSummary>,
     pub date_range: DateRange,
 }
@@ -60,6 +193,15 @@ pub struct AccountSummary {
     pub id: String,
     pub name: String,
     pub institution_name: Option<String>,
+    pub is_archived: bool,
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupSubtotal {
+    pub name: String,
+    pub account_count: i64,
+    pub net_worth: Decimal,
 }
 
 #[derive(Debug, Serialize)]
@@ -67,3 +209,16 @@ pub struct DateRange {
     pub earliest: Option<String>,
     pub latest: Option<String>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct MonthlyCashFlow {
+    /// Fiscal month as YYYY-MM, named after the calendar month it starts in
+    pub month: String,
+    /// Sum of positive amounts
+    pub income: Decimal,
+    /// Sum of negative amounts, as a positive number
+    pub expenses: Decimal,
+    pub net: Decimal,
+    /// True for the current month, which is still in progress
+    pub is_partial: bool,
+}
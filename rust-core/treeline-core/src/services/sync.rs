@@ -1,114 +1,247 @@
 //! Sync service - synchronize accounts and transactions from integrations
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use anyhow::Result;
-use chrono::{Duration, Utc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::Serialize;
 use uuid::Uuid;
 
-use crate::adapters::demo::DemoDataProvider;
-use crate::adapters::duckdb::DuckDbRepository;
-use crate::adapters::lunchflow::LunchflowProvider;
-use crate::adapters::simplefin::SimpleFINProvider;
-use crate::ports::{DataAggregationProvider, IntegrationProvider};
-use crate::services::TagService;
+use crate::adapters::duckdb::{DuckDbRepository, Integration};
+use crate::adapters::registry::ProviderRegistry;
+use crate::config::Config;
+use crate::domain::{Account, BalanceSnapshot, ConflictPolicy, Transaction};
+use crate::ports::{excluded_account_ids, DataAggregationProvider, EXCLUDED_ACCOUNTS_KEY};
+use crate::services::{BackupService, SyncWebhook, TagService};
+
+/// Integration settings key: when the last sync completed (RFC 3339)
+pub(crate) const LAST_SYNCED_AT_KEY: &str = "lastSyncedAt";
+
+/// Integration settings key: newest transaction date fetched so far (YYYY-MM-DD)
+pub(crate) const LAST_SYNCED_TX_DATE_KEY: &str = "lastSyncedTxDate";
+
+/// How many example items a dry-run plan keeps per kind of change
+const PLAN_SAMPLE_SIZE: usize = 5;
 
 /// Sync service for account and transaction synchronization
 pub struct SyncService {
     repository: Arc<DuckDbRepository>,
     tag_service: TagService,
-    _treeline_dir: PathBuf,
-    providers: HashMap<String, Arc<dyn DataAggregationProvider>>,
-    integration_providers: HashMap<String, Arc<dyn IntegrationProvider>>,
+    treeline_dir: PathBuf,
+    registry: ProviderRegistry,
+    refresh_pending: bool,
+    continue_on_error: bool,
 }
 
 impl SyncService {
     pub fn new(repository: Arc<DuckDbRepository>, treeline_dir: PathBuf) -> Self {
-        let mut providers: HashMap<String, Arc<dyn DataAggregationProvider>> = HashMap::new();
-        let mut integration_providers: HashMap<String, Arc<dyn IntegrationProvider>> =
-            HashMap::new();
-
-        // Register built-in providers
-        let demo = Arc::new(DemoDataProvider::new());
-        providers.insert("demo".to_string(), demo.clone());
-        integration_providers.insert("demo".to_string(), demo);
-
-        let simplefin = Arc::new(SimpleFINProvider::new());
-        providers.insert("simplefin".to_string(), simplefin.clone());
-        integration_providers.insert("simplefin".to_string(), simplefin);
-
-        // Register Lunchflow provider (global bank connections)
-        let lunchflow = Arc::new(LunchflowProvider::new());
-        providers.insert("lunchflow".to_string(), lunchflow.clone());
-        integration_providers.insert("lunchflow".to_string(), lunchflow);
-
         let tag_service = TagService::new(repository.clone());
 
         Self {
             repository,
             tag_service,
-            _treeline_dir: treeline_dir,
-            providers,
-            integration_providers,
+            treeline_dir,
+            registry: ProviderRegistry::with_builtin(),
+            refresh_pending: true,
+            continue_on_error: true,
         }
     }
 
+    /// Create a sync service that only knows a single provider
+    ///
+    /// The provider serves the integration named after `provider.name()`.
+    /// Mainly useful for driving sync with a mock provider in tests.
+    pub fn new_with_provider<P>(
+        repository: Arc<DuckDbRepository>,
+        treeline_dir: PathBuf,
+        provider: P,
+    ) -> Self
+    where
+        P: DataAggregationProvider + Clone + 'static,
+    {
+        let mut registry = ProviderRegistry::new();
+        let name = provider.name().to_string();
+        registry.register(&name, move || Box::new(provider.clone()));
+        Self::new(repository, treeline_dir).with_registry(registry)
+    }
+
+    /// Use these providers instead of the built-in ones
+    pub fn with_registry(mut self, registry: ProviderRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Refresh already-synced transactions that are still pending (default true)
+    ///
+    /// When a pending transaction posts, providers usually keep its ID but may
+    /// adjust the amount, date or description. With this enabled those rows
+    /// are updated from the provider; when disabled they are skipped like any
+    /// other existing transaction.
+    pub fn with_refresh_pending(mut self, refresh_pending: bool) -> Self {
+        self.refresh_pending = refresh_pending;
+        self
+    }
+
+    /// Keep syncing the other integrations when one fails (default true)
+    ///
+    /// Failures are then reported in [`IntegrationSyncResult::error`] instead
+    /// of failing the whole sync. Only applies when syncing all integrations;
+    /// a sync of one named integration always returns its error.
+    pub fn with_continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Change [`with_continue_on_error`](Self::with_continue_on_error) on an existing service
+    pub fn set_continue_on_error(&mut self, continue_on_error: bool) {
+        self.continue_on_error = continue_on_error;
+    }
+
     /// Sync from all integrations or a specific one
     ///
     /// If `balances_only` is true, skips transaction fetching entirely.
     /// This is useful for users who just want to track account balances.
+    ///
+    /// With `dry_run` nothing is written and [`SyncResult::plan`] lists what
+    /// would have changed, as [`sync_dry_run`](Self::sync_dry_run) does.
+    ///
+    /// When a sync webhook is configured, a summary is POSTed to it once
+    /// everything is written; see [`SyncResult::webhook_error`].
+    ///
+    /// All integrations run in the configured `sync_order`. One that fails is
+    /// recorded in its result and the rest still sync, unless disabled with
+    /// [`with_continue_on_error`](Self::with_continue_on_error).
     pub fn sync(
         &self,
         integration: Option<&str>,
         dry_run: bool,
         balances_only: bool,
     ) -> Result<SyncResult> {
+        let config = Config::load(&self.treeline_dir)?;
         let integrations = self.repository.get_integrations()?;
         let mut results = Vec::new();
 
         let integrations_to_sync: Vec<_> = if let Some(name) = integration {
             integrations.iter().filter(|i| i.name == name).collect()
         } else {
-            integrations.iter().collect()
+            ordered_integrations(&integrations, &config.sync_order)
         };
 
         if integrations_to_sync.is_empty() {
             anyhow::bail!("No integrations configured");
         }
 
+        // Back up before applying changes, if enabled in settings
+        let backup = if !dry_run && config.auto_backup_on_sync {
+            Some(self.create_pre_sync_backup()?)
+        } else {
+            None
+        };
+
+        let mut plans = Vec::new();
         for int in integrations_to_sync {
-            let result = self.sync_integration(&int.name, &int.settings, dry_run, balances_only)?;
-            results.push(result);
+            match self.sync_integration(&int.name, &int.settings, dry_run, balances_only) {
+                Ok((result, plan)) => {
+                    results.push(result);
+                    plans.push(plan);
+                }
+                Err(e) if integration.is_none() && self.continue_on_error => {
+                    results.push(IntegrationSyncResult::failed(&int.name, &e));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to sync {}", int.name));
+                }
+            }
         }
 
-        Ok(SyncResult {
-            results,
-            new_accounts_without_type: Vec::new(),
+        let mut result = SyncResult::new(results, backup);
+        if dry_run {
+            result.plan = Some(SyncPlan {
+                integrations: plans,
+            });
+        }
+        if !dry_run {
+            if let Some(webhook) = SyncWebhook::from_config(&config) {
+                // Everything is already written; a failed delivery is reported, not fatal
+                result.webhook_error = webhook.send(&result).err().map(|e| format!("{:#}", e));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Work out what a sync would change without writing anything
+    ///
+    /// Fetches from the providers and compares against the current database,
+    /// using the same matching and deduplication as `sync()`. No backup is
+    /// taken and integration watermarks are left alone.
+    pub fn sync_dry_run(&self, integration: Option<&str>) -> Result<SyncPlan> {
+        let config = Config::load(&self.treeline_dir)?;
+        let integrations = self.repository.get_integrations()?;
+        let integrations_to_plan: Vec<_> = match integration {
+            Some(name) => integrations.iter().filter(|i| i.name == name).collect(),
+            None => ordered_integrations(&integrations, &config.sync_order),
+        };
+
+        if integrations_to_plan.is_empty() {
+            anyhow::bail!("No integrations configured");
+        }
+
+        let mut plans = Vec::new();
+        for int in integrations_to_plan {
+            let (_, plan) = self.sync_integration(&int.name, &int.settings, true, false)?;
+            plans.push(plan);
+        }
+
+        Ok(SyncPlan {
+            integrations: plans,
         })
     }
 
+    /// Create a backup of the current database, returning its name
+    fn create_pre_sync_backup(&self) -> Result<String> {
+        let db_filename = self
+            .repository
+            .db_path()
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid database path"))?
+            .to_string();
+        let backup_service = BackupService::new_with_repository(
+            self.treeline_dir.clone(),
+            db_filename,
+            self.repository.clone(),
+        );
+        let backup = backup_service.create(None)?;
+        Ok(backup.name)
+    }
+
     fn sync_integration(
         &self,
         name: &str,
         settings: &serde_json::Value,
         dry_run: bool,
         balances_only: bool,
-    ) -> Result<IntegrationSyncResult> {
-        // Look up provider by name
+    ) -> Result<(IntegrationSyncResult, IntegrationPlan)> {
+        // Look up provider by the integration's name
         let provider = self
-            .providers
-            .get(name)
+            .registry
+            .create(name)
             .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", name))?;
 
         let now = Utc::now();
         let end_date = now.naive_utc().date();
 
-        // Calculate start date based on sync type
-        let max_tx_date = self.repository.get_max_transaction_date()?;
-        let (start_date, is_incremental) = match max_tx_date {
+        // Calculate start date based on sync type. Prefer this integration's own
+        // watermark; integrations synced before watermarks existed fall back to
+        // the newest transaction overall.
+        let watermark = match last_synced_tx_date(settings) {
+            Some(date) => Some(date),
+            None => self.repository.get_max_transaction_date()?,
+        };
+        let (start_date, is_incremental) = match watermark {
+            // Overlap a week so late-posting transactions aren't missed
             Some(max_date) => (max_date - Duration::days(7), true),
             None => ((now - Duration::days(90)).naive_utc().date(), false),
         };
@@ -118,27 +251,25 @@ impl SyncService {
         } else {
             "initial"
         };
+        let mut plan = IntegrationPlan::new(name, sync_type, start_date, end_date);
 
         // Fetch accounts from provider
         let accounts_result = provider.get_accounts(settings)?;
         let mut provider_warnings = accounts_result.warnings;
 
         // Build map of provider external ID to internal account ID
-        // Use provider-specific columns (sf_id/lf_id) for mapping
-        let existing_accounts = self.repository.get_accounts()?;
+        // Archived accounts are included so they still match (and aren't re-created),
+        // but their external IDs are tracked separately so they can be skipped.
+        let existing_accounts = self.repository.get_accounts(true)?;
+        let excluded_ext_ids = excluded_account_ids(settings);
         let mut external_to_internal: HashMap<String, Uuid> = HashMap::new();
+        let mut archived_ext_ids: HashSet<String> = HashSet::new();
 
         for existing in &existing_accounts {
-            // Get provider-specific ID column
-            let ext_id = match name {
-                "simplefin" => existing.sf_id.clone(),
-                "lunchflow" => existing.lf_id.clone(),
-                // Demo mode: use the account name as the external ID (stable across syncs)
-                "demo" => Some(existing.name.clone()),
-                _ => None,
-            };
-
-            if let Some(id) = ext_id {
+            if let Some(id) = provider.account_external_id(existing) {
+                if existing.is_archived {
+                    archived_ext_ids.insert(id.clone());
+                }
                 external_to_internal.insert(id, existing.id);
             }
         }
@@ -146,15 +277,7 @@ impl SyncService {
         // Track original account IDs for balance snapshot mapping
         let mut orig_to_ext: HashMap<Uuid, String> = HashMap::new();
         for account in &accounts_result.accounts {
-            let ext_id = match name {
-                "simplefin" => account.sf_id.clone(),
-                "lunchflow" => account.lf_id.clone(),
-                // Demo mode: use the account name as the external ID
-                "demo" => Some(account.name.clone()),
-                _ => None,
-            };
-
-            if let Some(id) = ext_id {
+            if let Some(id) = provider.account_external_id(account) {
                 orig_to_ext.insert(account.id, id);
             }
         }
@@ -162,19 +285,18 @@ impl SyncService {
         // Process accounts
         let mut accounts_synced = 0i64;
         for mut account in accounts_result.accounts {
-            // Get external ID from provider-specific column
-            let ext_id = match name {
-                "simplefin" => account.sf_id.clone(),
-                "lunchflow" => account.lf_id.clone(),
-                // Demo mode: use the account name as the external ID
-                "demo" => Some(account.name.clone()),
-                _ => None,
-            };
-            let ext_id = ext_id.unwrap_or_default();
+            let ext_id = provider.account_external_id(&account).unwrap_or_default();
+
+            // Archived and excluded accounts are left untouched by sync
+            if archived_ext_ids.contains(&ext_id) || excluded_ext_ids.contains(&ext_id) {
+                continue;
+            }
 
             if let Some(&existing_id) = external_to_internal.get(&ext_id) {
                 // Existing account - update ID
                 account.id = existing_id;
+                plan.account(account.id).account_name = account.name.clone();
+                plan.accounts_to_update.record(&account);
                 if !dry_run {
                     self.repository.upsert_account(&account)?;
                 }
@@ -182,6 +304,10 @@ impl SyncService {
                 // New account
                 external_to_internal.insert(ext_id, account.id);
                 accounts_synced += 1;
+                let account_plan = plan.account(account.id);
+                account_plan.account_name = account.name.clone();
+                account_plan.is_new = true;
+                plan.accounts_to_create.record(&account);
                 if !dry_run {
                     self.repository.upsert_account(&account)?;
                 }
@@ -189,21 +315,39 @@ impl SyncService {
         }
 
         // Save balance snapshots
-        if !dry_run {
-            for snapshot in accounts_result.balance_snapshots {
-                if let Some(ext_id) = orig_to_ext.get(&snapshot.account_id) {
-                    if let Some(&internal_id) = external_to_internal.get(ext_id) {
-                        let mut updated = snapshot;
-                        updated.account_id = internal_id;
-                        let _ = self.repository.add_balance_snapshot(&updated);
+        let mut snapshots_created = 0i64;
+        for snapshot in accounts_result.balance_snapshots {
+            if let Some(ext_id) = orig_to_ext.get(&snapshot.account_id) {
+                if archived_ext_ids.contains(ext_id) || excluded_ext_ids.contains(ext_id) {
+                    continue;
+                }
+                if let Some(&internal_id) = external_to_internal.get(ext_id) {
+                    // A balance the user set by hand wins for that day
+                    if self.repository.has_manual_balance_snapshot(
+                        &internal_id.to_string(),
+                        snapshot.snapshot_time.date(),
+                    )? {
+                        continue;
+                    }
+                    let mut updated = snapshot;
+                    updated.account_id = internal_id;
+                    plan.snapshots_to_add.record(&updated);
+                    plan.account(internal_id).snapshots_to_add += 1;
+                    if dry_run || self.repository.add_balance_snapshot(&updated).is_ok() {
+                        snapshots_created += 1;
                     }
                 }
             }
         }
 
+        // Settings to store once the sync succeeds (providers may update them)
+        let mut stored_settings = settings.clone();
+        let mut newest_tx_date = last_synced_tx_date(settings);
+        let mut removed_count = 0i64;
+
         // Skip transaction fetching entirely if balances_only mode
-        let (discovered, new_count, skipped_count) = if balances_only {
-            (0, 0, 0)
+        let (discovered, new_count, updated_count, skipped_count) = if balances_only {
+            (0, 0, 0, 0)
         } else {
             // Fetch transactions (excluding per-account balances-only settings)
             // Check accountSettings for balancesOnly flag on each account
@@ -212,6 +356,10 @@ impl SyncService {
             let ext_account_ids: Vec<String> = external_to_internal
                 .keys()
                 .filter(|ext_id| {
+                    // Never fetch transactions for archived or excluded accounts
+                    if archived_ext_ids.contains(*ext_id) || excluded_ext_ids.contains(*ext_id) {
+                        return false;
+                    }
                     // Include account only if NOT marked as balancesOnly
                     if let Some(settings_map) = account_settings {
                         if let Some(acc_settings) = settings_map.get(*ext_id) {
@@ -232,53 +380,168 @@ impl SyncService {
                 provider.get_transactions(start_date, end_date, &ext_account_ids, settings)?;
             provider_warnings.extend(txs_result.warnings);
 
+            // Providers may return transactions for accounts we didn't ask for
+            // (e.g. SimpleFIN returns everything), so drop archived and excluded ones here too
+            let synced_account = |ext_id: &String| {
+                !archived_ext_ids.contains(ext_id) && !excluded_ext_ids.contains(ext_id)
+            };
+            let modified_ids: HashSet<String> = txs_result
+                .modified
+                .iter()
+                .filter_map(|(_, tx)| provider.transaction_external_id(tx))
+                .collect();
+            // Changed transactions go through the same loop; `modified_ids` sends
+            // the ones already stored to the update path
+            let transactions: Vec<_> = txs_result
+                .transactions
+                .into_iter()
+                .chain(txs_result.modified)
+                .filter(|(ext_id, _)| synced_account(ext_id))
+                .collect();
+            let removed: Vec<String> = txs_result
+                .removed
+                .into_iter()
+                .filter(|(ext_id, _)| synced_account(ext_id))
+                .map(|(_, id)| id)
+                .collect();
+
+            newest_tx_date = transactions
+                .iter()
+                .map(|(_, tx)| tx.transaction_date)
+                .chain(newest_tx_date)
+                .max();
+
             // Process transactions with deduplication
-            let (new_count, skipped_count) = self.process_transactions(
-                name,
-                txs_result.transactions,
+            let (new_count, updated_count, skipped_count, new_tx_ids) = self.process_transactions(
+                &*provider,
+                transactions,
+                &modified_ids,
                 &external_to_internal,
                 dry_run,
+                &mut plan,
             )?;
 
-            let discovered = new_count + skipped_count;
-            (discovered, new_count, skipped_count)
+            // Apply auto-tag rules to newly synced transactions. Best-effort:
+            // the sync stands, but say the rules failed
+            if !new_tx_ids.is_empty() {
+                if let Err(e) = self.tag_service.apply_auto_tag_rules(&new_tx_ids) {
+                    provider_warnings.push(format!("Auto-tag rules failed: {:#}", e));
+                }
+            }
+            removed_count = self.remove_transactions(&*provider, &removed, dry_run, &mut plan)?;
+
+            // Provider state (e.g. Plaid's sync cursor) is persisted below, only
+            // once the transactions it covers have been stored
+            if let Some(updated) = txs_result.updated_settings {
+                stored_settings = updated;
+            }
+
+            let discovered = new_count + updated_count + skipped_count;
+            (discovered, new_count, updated_count, skipped_count)
         };
 
-        Ok(IntegrationSyncResult {
+        // Record the watermark for the next incremental sync
+        if !dry_run {
+            if let Some(obj) = stored_settings.as_object_mut() {
+                obj.insert(
+                    LAST_SYNCED_AT_KEY.to_string(),
+                    serde_json::json!(now.to_rfc3339()),
+                );
+                if let Some(date) = newest_tx_date {
+                    obj.insert(
+                        LAST_SYNCED_TX_DATE_KEY.to_string(),
+                        serde_json::json!(date.format("%Y-%m-%d").to_string()),
+                    );
+                }
+            }
+            self.repository.upsert_integration(name, &stored_settings)?;
+        }
+
+        plan.provider_warnings = provider_warnings.clone();
+        // Accounts the provider didn't list but that still got transactions
+        for account_plan in &mut plan.accounts {
+            if account_plan.account_name.is_empty() {
+                if let Some(existing) = existing_accounts
+                    .iter()
+                    .find(|a| a.id.to_string() == account_plan.account_id)
+                {
+                    account_plan.account_name = existing.name.clone();
+                }
+            }
+        }
+
+        let result = IntegrationSyncResult {
             integration: name.to_string(),
             accounts_synced,
+            accounts_updated: plan.accounts_to_update.count,
+            snapshots_created,
             transactions_synced: new_count,
             transaction_stats: TransactionStats {
                 discovered,
                 new: new_count,
+                updated: updated_count,
                 skipped: skipped_count,
+                removed: removed_count,
             },
             sync_type: sync_type.to_string(),
             start_date: start_date.format("%Y-%m-%d").to_string(),
             end_date: end_date.format("%Y-%m-%d").to_string(),
             provider_warnings,
             error: None,
-        })
+        };
+        Ok((result, plan))
     }
 
     /// Process transactions with deduplication logic
     ///
     /// Deduplication strategy:
-    /// 1. Check by provider-specific ID column (sf_id or lf_id) - indexed, fast
+    /// 1. Check by the provider's transaction ID column (sf_id, lf_id or pl_id),
+    ///    looking up every incoming ID in one batch before the loop
     /// 2. Check by fingerprint (account + date + amount + description hash)
     ///
-    /// If either exists, skip the transaction to preserve user edits.
+    /// If either exists, the configured [`ConflictPolicy`] decides what happens;
+    /// the default keeps the stored transaction to preserve user edits. The
+    /// exceptions are transactions the provider reports as changed (their IDs
+    /// are in `modified_ids`) and, with `refresh_pending`, transactions stored
+    /// as pending: their amount, date and description are updated from the provider.
+    ///
+    /// Returns (new, updated, skipped) counts and the IDs of the transactions
+    /// inserted (none in a dry run).
     fn process_transactions(
         &self,
-        provider_name: &str,
-        transactions: Vec<(String, crate::domain::Transaction)>,
+        provider: &dyn DataAggregationProvider,
+        transactions: Vec<(String, Transaction)>,
+        modified_ids: &HashSet<String>,
         external_to_internal: &HashMap<String, Uuid>,
         dry_run: bool,
-    ) -> Result<(i64, i64)> {
+        plan: &mut IntegrationPlan,
+    ) -> Result<(i64, i64, i64, Vec<Uuid>)> {
         let mut new_count = 0i64;
+        let mut updated_count = 0i64;
         let mut skipped_count = 0i64;
         let mut new_tx_ids: Vec<Uuid> = Vec::new();
 
+        let config = Config::load(&self.treeline_dir)?;
+
+        // Opt-in: tag new transactions from their provider category
+        let category_map = if config.category_mapping {
+            self.repository.get_category_map()?
+        } else {
+            HashMap::new()
+        };
+
+        // Provider IDs already stored, fetched once rather than per transaction
+        let mut existing_ids = match provider.transaction_id_column() {
+            Some(column) => {
+                let ids: Vec<String> = transactions
+                    .iter()
+                    .filter_map(|(_, tx)| provider.transaction_external_id(tx))
+                    .collect();
+                self.repository.existing_external_ids(column, &ids)?
+            }
+            None => HashSet::new(),
+        };
+
         for (ext_account_id, mut tx) in transactions {
             // Map to internal account ID
             let internal_account_id = match external_to_internal.get(&ext_account_id) {
@@ -289,43 +552,133 @@ impl SyncService {
 
             // Check if exists by provider-specific ID column (indexed, fast)
             // Note: csv_fingerprint is only for CSV imports, not provider syncs
-            let already_exists = match provider_name {
-                "simplefin" => {
-                    if let Some(ref sf_id) = tx.sf_id {
-                        self.repository.transaction_exists_by_sf_id(sf_id)?
-                    } else {
-                        false
-                    }
+            // Providers without transaction IDs (e.g. demo) always insert
+            let external_id = match (
+                provider.transaction_id_column(),
+                provider.transaction_external_id(&tx),
+            ) {
+                (Some(column), Some(id)) => Some((column, id)),
+                _ => None,
+            };
+            // Recording new IDs makes a repeat within this batch count as existing
+            let already_exists = match &external_id {
+                Some((_, id)) => !existing_ids.insert(id.clone()),
+                None => false,
+            };
+
+            if !already_exists {
+                apply_category_mapping(provider, &category_map, &mut tx);
+                new_count += 1;
+                plan.account(tx.account_id).transactions_to_insert += 1;
+                plan.transactions_to_insert.record(&tx);
+                if !dry_run {
+                    new_tx_ids.push(tx.id);
+                    self.repository.upsert_transaction(&tx)?;
+                }
+                continue;
+            }
+
+            let refreshed = match &external_id {
+                Some((column, id)) if modified_ids.contains(id) => {
+                    dry_run
+                        || self
+                            .repository
+                            .update_transaction_from_provider(column, id, &tx)?
                 }
-                "lunchflow" => {
-                    if let Some(ref lf_id) = tx.lf_id {
-                        self.repository.transaction_exists_by_lf_id(lf_id)?
+                Some((column, id)) if self.refresh_pending => {
+                    if dry_run {
+                        self.repository
+                            .transaction_is_pending_by_external_id(column, id)?
                     } else {
-                        false
+                        self.repository
+                            .update_pending_transaction(column, id, &tx)?
                     }
                 }
-                // Demo mode: no provider ID, always insert (demo has its own DB)
                 _ => false,
             };
 
-            if already_exists {
-                skipped_count += 1;
-            } else {
-                new_count += 1;
+            // Otherwise apply the conflict policy to the stored copy
+            let resolved = match &external_id {
+                Some((column, id))
+                    if !refreshed && config.conflict_policy != ConflictPolicy::PreserveLocal =>
+                {
+                    self.repository
+                        .get_transaction_by_external_id(column, id)?
+                        .and_then(|local| config.conflict_policy.resolve(&local, &tx))
+                }
+                _ => None,
+            };
+            if let Some(merged) = &resolved {
                 if !dry_run {
-                    new_tx_ids.push(tx.id);
-                    self.repository.upsert_transaction(&tx)?;
+                    self.repository.upsert_transaction(merged)?;
                 }
             }
+
+            if refreshed || resolved.is_some() {
+                updated_count += 1;
+                plan.account(tx.account_id).transactions_to_update += 1;
+                plan.transactions_to_update
+                    .record(resolved.as_ref().unwrap_or(&tx));
+            } else {
+                skipped_count += 1;
+                plan.account(tx.account_id).transactions_to_skip += 1;
+            }
         }
 
-        // Apply auto-tag rules to newly synced transactions
-        if !dry_run && !new_tx_ids.is_empty() {
-            // Best-effort tagging - don't fail sync if rules fail
-            let _ = self.tag_service.apply_auto_tag_rules(&new_tx_ids);
+        Ok((new_count, updated_count, skipped_count, new_tx_ids))
+    }
+
+    /// Soft-delete stored transactions the provider reports as deleted
+    ///
+    /// `removed` holds provider transaction IDs; ones never stored are ignored.
+    /// Returns how many stored transactions were (or would be) removed.
+    fn remove_transactions(
+        &self,
+        provider: &dyn DataAggregationProvider,
+        removed: &[String],
+        dry_run: bool,
+        plan: &mut IntegrationPlan,
+    ) -> Result<i64> {
+        let column = match provider.transaction_id_column() {
+            Some(column) => column,
+            None => return Ok(0),
+        };
+
+        let mut removed_count = 0i64;
+        for id in removed {
+            let tx = match self.repository.get_transaction_by_external_id(column, id)? {
+                Some(tx) => tx,
+                None => continue,
+            };
+            removed_count += 1;
+            plan.account(tx.account_id).transactions_to_remove += 1;
+            plan.transactions_to_remove.record(&tx);
+            if !dry_run {
+                self.repository
+                    .soft_delete_transaction(&tx.id.to_string())?;
+            }
         }
+        Ok(removed_count)
+    }
 
-        Ok((new_count, skipped_count))
+    /// Tag new transactions in `provider_category` with `tag`
+    ///
+    /// Only applies while `categoryMapping` is enabled in settings, and only
+    /// to transactions that arrive untagged. Categories match ignoring case.
+    pub fn set_category_mapping(&self, provider_category: &str, tag: &str) -> Result<()> {
+        if provider_category.trim().is_empty() {
+            anyhow::bail!("Provider category cannot be empty");
+        }
+        let tag = tag.trim();
+        if tag.is_empty() {
+            anyhow::bail!("Tag cannot be empty");
+        }
+        self.repository.set_category_mapping(provider_category, tag)
+    }
+
+    /// Current provider category to tag mappings, keyed by lowercased category
+    pub fn category_mappings(&self) -> Result<HashMap<String, String>> {
+        self.repository.get_category_map()
     }
 
     /// List configured integrations
@@ -348,6 +701,86 @@ impl SyncService {
         Ok(())
     }
 
+    /// When an integration last completed a (non dry-run) sync
+    ///
+    /// Returns None if it has never been synced.
+    pub fn last_sync_time(&self, integration: &str) -> Result<Option<DateTime<Utc>>> {
+        let settings = self.integration_settings(integration)?;
+        Ok(settings
+            .get(LAST_SYNCED_AT_KEY)
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    /// Get the provider account IDs excluded from sync for an integration
+    pub fn get_excluded_accounts(&self, integration: &str) -> Result<Vec<String>> {
+        let settings = self.integration_settings(integration)?;
+        let mut ids: Vec<String> = excluded_account_ids(&settings).into_iter().collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Replace the list of provider account IDs excluded from sync
+    ///
+    /// Excluded accounts are skipped during both account and transaction sync.
+    /// Accounts that were already synced are kept as-is; see
+    /// `archive_excluded_accounts()` to hide them.
+    pub fn set_excluded_accounts(&self, integration: &str, ids: &[String]) -> Result<()> {
+        let mut settings = self.integration_settings(integration)?;
+        let obj = settings
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Invalid settings for integration: {}", integration))?;
+
+        let mut unique: Vec<String> = Vec::new();
+        for id in ids {
+            if !unique.contains(id) {
+                unique.push(id.clone());
+            }
+        }
+        if unique.is_empty() {
+            obj.remove(EXCLUDED_ACCOUNTS_KEY);
+        } else {
+            obj.insert(EXCLUDED_ACCOUNTS_KEY.to_string(), serde_json::json!(unique));
+        }
+
+        self.repository.upsert_integration(integration, &settings)
+    }
+
+    /// Archive already-synced accounts that are now excluded from sync
+    ///
+    /// Returns the internal IDs of the accounts that were archived.
+    pub fn archive_excluded_accounts(&self, integration: &str) -> Result<Vec<String>> {
+        let excluded = excluded_account_ids(&self.integration_settings(integration)?);
+        let provider = self
+            .registry
+            .create(integration)
+            .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", integration))?;
+        let mut archived = Vec::new();
+
+        for account in self.repository.get_accounts(false)? {
+            if provider
+                .account_external_id(&account)
+                .is_some_and(|id| excluded.contains(&id))
+            {
+                let id = account.id.to_string();
+                self.repository.set_account_archived(&id, true)?;
+                archived.push(id);
+            }
+        }
+
+        Ok(archived)
+    }
+
+    fn integration_settings(&self, integration: &str) -> Result<serde_json::Value> {
+        self.repository
+            .get_integrations()?
+            .into_iter()
+            .find(|i| i.name == integration)
+            .map(|i| i.settings)
+            .ok_or_else(|| anyhow::anyhow!("Integration not found: {}", integration))
+    }
+
     /// Set up a new integration using the appropriate provider
     pub fn setup_integration(
         &self,
@@ -355,8 +788,8 @@ impl SyncService {
         options: &serde_json::Value,
     ) -> Result<()> {
         let provider = self
-            .integration_providers
-            .get(provider_name)
+            .registry
+            .setup_provider(provider_name)
             .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", provider_name))?;
 
         let settings = provider.setup(options)?;
@@ -394,33 +827,305 @@ impl SyncService {
         }
         self.setup_integration("lunchflow", &options)
     }
+
+    /// Set up Plaid integration (convenience method)
+    ///
+    /// # Arguments
+    /// * `client_id` / `secret` - Plaid API credentials
+    /// * `public_token` - Public token returned by Plaid Link, exchanged for an access token
+    /// * `base_url` - Optional custom base URL for testing (None = production)
+    pub fn setup_plaid(
+        &self,
+        client_id: &str,
+        secret: &str,
+        public_token: &str,
+        base_url: Option<&str>,
+    ) -> Result<()> {
+        let mut options = serde_json::json!({
+            "clientId": client_id,
+            "secret": secret,
+            "publicToken": public_token
+        });
+        if let Some(url) = base_url {
+            options["baseUrl"] = serde_json::json!(url);
+        }
+        self.setup_integration("plaid", &options)
+    }
+}
+
+/// Newest transaction date seen by the last sync of an integration
+/// Give an untagged transaction the tag its provider category maps to
+///
+/// Some providers (SimpleFIN) already copy the raw category into the tags;
+/// a transaction tagged with nothing but that still counts as untagged.
+fn apply_category_mapping(
+    provider: &dyn DataAggregationProvider,
+    category_map: &HashMap<String, String>,
+    tx: &mut Transaction,
+) {
+    if category_map.is_empty() {
+        return;
+    }
+    let category = match provider.transaction_category(tx) {
+        Some(category) => category,
+        None => return,
+    };
+    let untagged = match tx.tags.as_slice() {
+        [] => true,
+        [only] => only == &category,
+        _ => false,
+    };
+    if !untagged {
+        return;
+    }
+    if let Some(tag) = category_map.get(&category.trim().to_lowercase()) {
+        tx.tags = vec![tag.clone()];
+    }
+}
+
+fn last_synced_tx_date(settings: &serde_json::Value) -> Option<NaiveDate> {
+    settings
+        .get(LAST_SYNCED_TX_DATE_KEY)
+        .and_then(|v| v.as_str())
+        .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+}
+
+/// Integrations in `sync_order`, followed by any it doesn't list, by name
+fn ordered_integrations<'a>(
+    integrations: &'a [Integration],
+    sync_order: &[String],
+) -> Vec<&'a Integration> {
+    let mut ordered: Vec<&Integration> = integrations.iter().collect();
+    ordered.sort_by_key(|i| {
+        let position = sync_order.iter().position(|name| *name == i.name);
+        (position.unwrap_or(usize::MAX), i.name.clone())
+    });
+    ordered
 }
 
+/// Outcome of a sync: totals across integrations plus one result per integration
 #[derive(Debug, Serialize)]
 pub struct SyncResult {
+    /// Per-integration results, in sync order; see [`SyncResult::integration`]
     pub results: Vec<IntegrationSyncResult>,
     pub new_accounts_without_type: Vec<String>,
+    /// Backup created before syncing (when auto_backup_on_sync is enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+    pub accounts_created: i64,
+    pub accounts_updated: i64,
+    pub transactions_inserted: i64,
+    /// Pending transactions refreshed from their provider
+    pub transactions_updated: i64,
+    pub transactions_skipped: i64,
+    pub snapshots_created: i64,
+    /// Provider warnings from every integration, prefixed with its name
+    pub warnings: Vec<String>,
+    /// Post-sync plugin hooks that failed, prefixed with the plugin id
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hook_warnings: Vec<String>,
+    /// Why the sync webhook could not be delivered, if one is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_error: Option<String>,
+    /// What a dry run would have changed (None for a real sync)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<SyncPlan>,
+}
+
+impl SyncResult {
+    pub(crate) fn new(results: Vec<IntegrationSyncResult>, backup: Option<String>) -> Self {
+        let total = |f: fn(&IntegrationSyncResult) -> i64| results.iter().map(f).sum::<i64>();
+        let warnings = results
+            .iter()
+            .flat_map(|r| {
+                r.provider_warnings
+                    .iter()
+                    .map(move |w| format!("{}: {}", r.integration, w))
+            })
+            .collect();
+
+        Self {
+            accounts_created: total(|r| r.accounts_synced),
+            accounts_updated: total(|r| r.accounts_updated),
+            transactions_inserted: total(|r| r.transaction_stats.new),
+            transactions_updated: total(|r| r.transaction_stats.updated),
+            transactions_skipped: total(|r| r.transaction_stats.skipped),
+            snapshots_created: total(|r| r.snapshots_created),
+            warnings,
+            results,
+            new_accounts_without_type: Vec::new(),
+            backup,
+            hook_warnings: Vec::new(),
+            webhook_error: None,
+            plan: None,
+        }
+    }
+
+    /// Result for one integration, if it was synced
+    pub fn integration(&self, name: &str) -> Option<&IntegrationSyncResult> {
+        self.results.iter().find(|r| r.integration == name)
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct IntegrationSyncResult {
     pub integration: String,
+    /// Accounts created by this sync
     pub accounts_synced: i64,
+    /// Existing accounts refreshed from the provider
+    pub accounts_updated: i64,
+    pub snapshots_created: i64,
     pub transactions_synced: i64,
     pub transaction_stats: TransactionStats,
     pub sync_type: String,
     pub start_date: String,
     pub end_date: String,
     pub provider_warnings: Vec<String>,
+    /// Why this integration failed; its counts are then all zero
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+impl IntegrationSyncResult {
+    /// Result for an integration whose sync failed with `error`
+    fn failed(integration: &str, error: &anyhow::Error) -> Self {
+        Self {
+            integration: integration.to_string(),
+            accounts_synced: 0,
+            accounts_updated: 0,
+            snapshots_created: 0,
+            transactions_synced: 0,
+            transaction_stats: TransactionStats {
+                discovered: 0,
+                new: 0,
+                updated: 0,
+                skipped: 0,
+                removed: 0,
+            },
+            sync_type: String::new(),
+            start_date: String::new(),
+            end_date: String::new(),
+            provider_warnings: Vec::new(),
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct TransactionStats {
     pub discovered: i64,
     pub new: i64,
+    /// Stored transactions updated: refreshed pending ones, or per the conflict policy
+    pub updated: i64,
     pub skipped: i64,
+    /// Stored transactions soft-deleted because the provider deleted them
+    pub removed: i64,
+}
+
+/// What a sync would change, per integration
+#[derive(Debug, Serialize)]
+pub struct SyncPlan {
+    pub integrations: Vec<IntegrationPlan>,
+}
+
+/// Planned changes for one integration
+#[derive(Debug, Serialize)]
+pub struct IntegrationPlan {
+    pub integration: String,
+    pub sync_type: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub accounts_to_create: PlannedChanges<Account>,
+    pub accounts_to_update: PlannedChanges<Account>,
+    pub transactions_to_insert: PlannedChanges<Transaction>,
+    /// Stored transactions that would be updated: refreshed pending ones, or per the conflict policy
+    pub transactions_to_update: PlannedChanges<Transaction>,
+    /// Stored transactions the provider deleted, to be soft-deleted
+    pub transactions_to_remove: PlannedChanges<Transaction>,
+    pub snapshots_to_add: PlannedChanges<BalanceSnapshot>,
+    /// The same changes broken down by account
+    pub accounts: Vec<AccountPlan>,
+    pub provider_warnings: Vec<String>,
+}
+
+impl IntegrationPlan {
+    fn new(integration: &str, sync_type: &str, start_date: NaiveDate, end_date: NaiveDate) -> Self {
+        Self {
+            integration: integration.to_string(),
+            sync_type: sync_type.to_string(),
+            start_date: start_date.format("%Y-%m-%d").to_string(),
+            end_date: end_date.format("%Y-%m-%d").to_string(),
+            accounts_to_create: PlannedChanges::default(),
+            accounts_to_update: PlannedChanges::default(),
+            transactions_to_insert: PlannedChanges::default(),
+            transactions_to_update: PlannedChanges::default(),
+            transactions_to_remove: PlannedChanges::default(),
+            snapshots_to_add: PlannedChanges::default(),
+            accounts: Vec::new(),
+            provider_warnings: Vec::new(),
+        }
+    }
+
+    /// Entry for an account, added on first use
+    fn account(&mut self, account_id: Uuid) -> &mut AccountPlan {
+        let account_id = account_id.to_string();
+        let index = match self
+            .accounts
+            .iter()
+            .position(|a| a.account_id == account_id)
+        {
+            Some(index) => index,
+            None => {
+                self.accounts.push(AccountPlan {
+                    account_id,
+                    ..AccountPlan::default()
+                });
+                self.accounts.len() - 1
+            }
+        };
+        &mut self.accounts[index]
+    }
+}
+
+/// Planned changes for one account
+#[derive(Debug, Default, Serialize)]
+pub struct AccountPlan {
+    pub account_id: String,
+    pub account_name: String,
+    /// Whether sync would create the account rather than update it
+    pub is_new: bool,
+    pub transactions_to_insert: i64,
+    /// Stored transactions that would be updated: refreshed pending ones, or per the conflict policy
+    pub transactions_to_update: i64,
+    /// Transactions already stored, left as they are
+    pub transactions_to_skip: i64,
+    pub transactions_to_remove: i64,
+    pub snapshots_to_add: i64,
+}
+
+/// Number of planned changes of one kind, with the first few as examples
+#[derive(Debug, Serialize)]
+pub struct PlannedChanges<T> {
+    pub count: i64,
+    pub samples: Vec<T>,
+}
+
+impl<T: Clone> PlannedChanges<T> {
+    fn record(&mut self, item: &T) {
+        self.count += 1;
+        if self.samples.len() < PLAN_SAMPLE_SIZE {
+            self.samples.push(item.clone());
+        }
+    }
+}
+
+impl<T> Default for PlannedChanges<T> {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            samples: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -432,7 +1137,6 @@ pub struct IntegrationInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::Transaction;
     use chrono::NaiveDate;
     use rust_decimal::Decimal;
 
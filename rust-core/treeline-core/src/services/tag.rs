@@ -3,10 +3,14 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use rust_decimal::Decimal;
 use serde::Serialize;
+use sqlparser::dialect::DuckDbDialect;
+use sqlparser::parser::Parser;
 use uuid::Uuid;
 
-use crate::adapters::duckdb::DuckDbRepository;
+use crate::adapters::duckdb::{ensure_read_only, DuckDbRepository};
+use crate::domain::Transaction;
 
 /// Tag service for transaction tagging
 pub struct TagService {
@@ -144,6 +148,150 @@ impl TagService {
         })
     }
 
+    /// Apply tags to every transaction matching a SQL condition
+    ///
+    /// `sql_condition` is a WHERE clause over the `transactions` view, e.g.
+    /// `amount < -100 AND description ILIKE '%fee%'`. It must keep the query
+    /// a single read-only SELECT; anything else is rejected before running.
+    pub fn tag_by_condition(
+        &self,
+        sql_condition: &str,
+        tags: &[String],
+        replace: bool,
+    ) -> Result<TagResult> {
+        let tx_ids = self.ids_matching(sql_condition)?;
+        self.apply_tags(&tx_ids, tags, replace)
+    }
+
+    /// Which transactions a condition matches, without tagging anything
+    ///
+    /// Validated like [`tag_by_condition`](Self::tag_by_condition). Lets
+    /// callers confirm the match count first, and catch a condition that
+    /// would tag every transaction.
+    pub fn preview_condition(&self, sql_condition: &str) -> Result<ConditionPreview> {
+        let transaction_ids = self.ids_matching(sql_condition)?;
+        let total = self.repository.get_transaction_count()? as usize;
+        Ok(ConditionPreview {
+            matched: transaction_ids.len(),
+            total,
+            transaction_ids,
+        })
+    }
+
+    /// Validate a WHERE condition and return the IDs it matches
+    fn ids_matching(&self, sql_condition: &str) -> Result<Vec<String>> {
+        let sql_condition = sql_condition.trim();
+        if sql_condition.is_empty() {
+            anyhow::bail!("Condition cannot be empty");
+        }
+        validate_condition(sql_condition)?;
+
+        self.repository.get_transaction_ids_matching(sql_condition)
+    }
+
+    /// List all tags with how many transactions carry them, most used first
+    pub fn list_tags(&self) -> Result<Vec<TagStat>> {
+        self.repository.get_tag_stats()
+    }
+
+    /// Rename a tag on every transaction and auto-tag rule
+    ///
+    /// Transactions that already carry `new` just lose `old`, so no duplicates are created.
+    /// Returns the number of transactions changed.
+    pub fn rename_tag(&self, old: &str, new: &str) -> Result<usize> {
+        let old = old.trim();
+        let new = new.trim();
+        if old.is_empty() || new.is_empty() {
+            anyhow::bail!("Tag names cannot be empty");
+        }
+        if old == new {
+            anyhow::bail!("New tag name is the same as the old one");
+        }
+
+        let rename = |tags: &[String]| -> Vec<String> {
+            let has_new = tags.iter().any(|t| t == new);
+            let mut result = Vec::with_capacity(tags.len());
+            for tag in tags {
+                if tag == old {
+                    if !has_new && !result.iter().any(|t: &String| t == new) {
+                        result.push(new.to_string());
+                    }
+                } else {
+                    result.push(tag.clone());
+                }
+            }
+            result
+        };
+
+        self.rewrite_tag(old, rename)
+    }
+
+    /// Remove a tag from every transaction and auto-tag rule
+    ///
+    /// Returns the number of transactions changed.
+    pub fn delete_tag(&self, tag: &str) -> Result<usize> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            anyhow::bail!("Tag name cannot be empty");
+        }
+
+        self.rewrite_tag(tag, |tags: &[String]| {
+            tags.iter().filter(|t| *t != tag).cloned().collect()
+        })
+    }
+
+    /// Suggest tags for a transaction from how similar ones were tagged
+    ///
+    /// Similar means another transaction whose description matches after
+    /// [`Transaction::normalize_description`]. Each tag's confidence is the
+    /// share of those transactions carrying it, from 0 to 1. Tags the
+    /// transaction already has are left out. Returns at most `limit` tags,
+    /// most confident first, and an empty list when there is no history.
+    pub fn suggest_tags(&self, tx_id: &str, limit: usize) -> Result<Vec<(String, f32)>> {
+        let tx = self
+            .repository
+            .get_transaction_by_id(tx_id)?
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", tx_id))?;
+        let key = Transaction::normalize_description(tx.description.as_deref());
+        if key.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (similar, tag_counts) = self
+            .repository
+            .get_tag_counts_by_description_key(&key, tx_id)?;
+        if similar == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut suggestions: Vec<(String, f32)> = tag_counts
+            .into_iter()
+            .filter(|(tag, _)| !tx.tags.contains(tag))
+            .map(|(tag, count)| (tag, count as f32 / similar as f32))
+            .collect();
+        suggestions.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+
+    /// Apply `rewrite` to the tags of every transaction and rule containing `tag`
+    fn rewrite_tag(&self, tag: &str, rewrite: impl Fn(&[String]) -> Vec<String>) -> Result<usize> {
+        let transactions = self.repository.get_transactions_with_tag(tag)?;
+        for (tx_id, tags) in &transactions {
+            self.repository
+                .update_transaction_tags(tx_id, &rewrite(tags))?;
+        }
+
+        for rule in self.repository.get_auto_tag_rules()? {
+            if rule.tags.iter().any(|t| t == tag) {
+                self.repository
+                    .update_auto_tag_rule_tags(&rule.rule_id, &rewrite(&rule.tags))?;
+            }
+        }
+
+        Ok(transactions.len())
+    }
+
     fn apply_tags_to_transaction(
         &self,
         tx_id: &str,
@@ -178,6 +326,37 @@ impl TagService {
     }
 }
 
+/// Check that a WHERE condition can't turn the match query into anything but a SELECT
+fn validate_condition(sql_condition: &str) -> Result<()> {
+    let sql = format!(
+        "SELECT transaction_id FROM transactions WHERE ({})",
+        sql_condition
+    );
+    // The condition must not close the WHERE and start another statement
+    let statements = Parser::parse_sql(&DuckDbDialect {}, &sql)
+        .map_err(|e| anyhow::anyhow!("Invalid condition: {}", e))?;
+    if statements.len() != 1 {
+        anyhow::bail!("Condition must be a read-only filter");
+    }
+    ensure_read_only(&sql).map_err(|_| anyhow::anyhow!("Condition must be a read-only filter"))
+}
+
+/// Transactions a tag condition matches, from [`TagService::preview_condition`]
+#[derive(Debug, Serialize)]
+pub struct ConditionPreview {
+    pub matched: usize,
+    /// All transactions the condition was checked against
+    pub total: usize,
+    pub transaction_ids: Vec<String>,
+}
+
+impl ConditionPreview {
+    /// Whether the condition matches every transaction (and there are some)
+    pub fn matches_everything(&self) -> bool {
+        self.total > 0 && self.matched == self.total
+    }
+}
+
 /// Result structure matching Python CLI output
 #[derive(Debug, Serialize)]
 pub struct TagResult {
@@ -197,6 +376,16 @@ pub struct TagResultEntry {
     pub error: Option<String>,
 }
 
+/// Usage statistics for a single tag
+#[derive(Debug, Serialize)]
+pub struct TagStat {
+    pub tag: String,
+    /// Number of transactions carrying the tag
+    pub transaction_count: i64,
+    /// Sum of amounts across those transactions
+    pub total_amount: Decimal,
+}
+
 /// Result of applying auto-tag rules
 #[derive(Debug, Serialize)]
 pub struct AutoTagResult {
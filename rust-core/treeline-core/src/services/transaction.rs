@@ -0,0 +1,159 @@
+//! Transaction service - manually entered transactions
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::{BalanceSnapshot, Transaction};
+use crate::services::TagService;
+
+/// Fields to change on every transaction in a bulk edit
+///
+/// Fields left as None keep each transaction's current value.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionPatch {
+    pub description: Option<String>,
+    pub transaction_date: Option<NaiveDate>,
+    pub amount: Option<Decimal>,
+}
+
+impl TransactionPatch {
+    /// True when the patch would not change anything
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.transaction_date.is_none() && self.amount.is_none()
+    }
+}
+
+/// Transaction service for entering transactions by hand
+pub struct TransactionService {
+    repository: Arc<DuckDbRepository>,
+    tag_service: TagService,
+}
+
+impl TransactionService {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        let tag_service = TagService::new(repository.clone());
+        Self {
+            repository,
+            tag_service,
+        }
+    }
+
+    /// Add a manual transaction (e.g. cash spending the bank never sees)
+    ///
+    /// The transaction is flagged `is_manual` so sync never overwrites it,
+    /// and enabled auto-tag rules are applied just like for synced rows. If
+    /// applying them fails, the error says the transaction was still added.
+    /// Returns the transaction as stored, including any rule tags.
+    pub fn create_manual(
+        &self,
+        account_id: &str,
+        amount: Decimal,
+        date: NaiveDate,
+        description: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<Transaction> {
+        if self.repository.get_account_by_id(account_id)?.is_none() {
+            anyhow::bail!("Account not found: {}", account_id);
+        }
+        let account_uuid = Uuid::parse_str(account_id)?;
+
+        let mut tx = Transaction::new(Uuid::new_v4(), account_uuid, amount, date);
+        tx.description = description;
+        tx.tags = tags;
+        tx.is_manual = true;
+
+        self.repository.upsert_transaction(&tx)?;
+        self.tag_service
+            .apply_auto_tag_rules(&[tx.id])
+            .with_context(|| {
+                format!(
+                    "Added transaction {}, but applying auto-tag rules failed",
+                    tx.id
+                )
+            })?;
+
+        self.repository
+            .get_transaction_by_id(&tx.id.to_string())?
+            .context("Manual transaction was not stored")
+    }
+
+    /// Apply the same edit to several transactions at once
+    ///
+    /// Only the fields set in `patch` change, and `updated_at` is bumped on
+    /// every row. All IDs are checked before anything is written, so an
+    /// unknown ID or an edit that would make a row invalid changes nothing.
+    /// Returns the number of transactions updated.
+    pub fn bulk_update(&self, ids: &[String], patch: TransactionPatch) -> Result<usize> {
+        if patch.is_empty() {
+            anyhow::bail!("Nothing to update: set a description, date or amount");
+        }
+
+        let mut unique_ids: Vec<&String> = Vec::new();
+        for id in ids {
+            if !unique_ids.contains(&id) {
+                unique_ids.push(id);
+            }
+        }
+
+        for id in &unique_ids {
+            let mut tx = self
+                .repository
+                .get_transaction_by_id(id)?
+                .filter(|tx| tx.deleted_at.is_none())
+                .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", id))?;
+            if let Some(description) = &patch.description {
+                tx.description = Some(description.clone());
+            }
+            if let Some(date) = patch.transaction_date {
+                tx.transaction_date = date;
+            }
+            if let Some(amount) = patch.amount {
+                tx.amount = amount;
+            }
+            tx.validate()
+                .with_context(|| format!("Refusing to apply edit to transaction {}", id))?;
+        }
+
+        self.repository.with_transaction(|| {
+            let mut updated = 0;
+            for id in &unique_ids {
+                if self.repository.patch_transaction(
+                    id,
+                    patch.description.as_deref(),
+                    patch.transaction_date,
+                    patch.amount,
+                )? {
+                    updated += 1;
+                }
+            }
+            Ok(updated)
+        })
+    }
+
+    /// Record a manual balance snapshot that includes a new manual transaction
+    ///
+    /// Adds the transaction amount to the account's latest balance. Returns
+    /// None when the account has no balance yet, since there is nothing to adjust.
+    pub fn snapshot_balance_after(&self, tx: &Transaction) -> Result<Option<BalanceSnapshot>> {
+        let account = self
+            .repository
+            .get_account_by_id(&tx.account_id.to_string())?
+            .ok_or_else(|| anyhow::anyhow!("Account not found: {}", tx.account_id))?;
+
+        let balance = match account.balance {
+            Some(balance) => balance,
+            None => return Ok(None),
+        };
+
+        let snapshot =
+            BalanceSnapshot::from_manual(account.id, balance + tx.amount, Utc::now().naive_utc());
+        self.repository.add_balance_snapshot(&snapshot)?;
+
+        Ok(Some(snapshot))
+    }
+}
@@ -0,0 +1,266 @@
+//! Transfer detection - pair up money moved between the user's own accounts
+//!
+//! A transfer shows up twice: an outflow from one account and an inflow to
+//! another. Left alone, both count towards spending and income. Linked
+//! transfers share a `transfer_group_id` and carry the `transfer` tag,
+//! which reports use to leave them out.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::adapters::duckdb::DuckDbRepository;
+use crate::domain::Transaction;
+
+/// Tag applied to both sides of a transfer
+pub const TRANSFER_TAG: &str = "transfer";
+
+/// Words banks use for transfers; two descriptions that both contain one
+/// are treated as matching even if they share no other words
+const TRANSFER_KEYWORDS: &[&str] = &["transfer", "transfers", "xfer", "tfr"];
+
+/// Finds and links transfers between accounts
+pub struct TransferDetector {
+    repository: Arc<DuckDbRepository>,
+    window_days: i64,
+    max_fee: Decimal,
+    min_similarity: f64,
+}
+
+impl TransferDetector {
+    pub fn new(repository: Arc<DuckDbRepository>) -> Self {
+        Self {
+            repository,
+            window_days: 3,
+            max_fee: Decimal::new(500, 2),
+            min_similarity: 0.2,
+        }
+    }
+
+    /// Allow the two sides to be up to this many days apart (default 3)
+    pub fn with_window_days(mut self, days: u32) -> Self {
+        self.window_days = days as i64;
+        self
+    }
+
+    /// Allow the amounts to differ by up to this much, e.g. a wire fee (default 5.00)
+    pub fn with_max_fee(mut self, max_fee: Decimal) -> Self {
+        self.max_fee = max_fee.abs();
+        self
+    }
+
+    /// Find likely transfers that aren't linked yet
+    ///
+    /// Pairs an outflow with an inflow in a different account when the dates
+    /// are within the window, the amounts match up to the allowed fee and the
+    /// descriptions are similar. Each transaction appears in at most one pair;
+    /// exact amounts win over fee-adjusted ones, then closer dates.
+    pub fn detect(&self) -> Result<Vec<TransferPair>> {
+        let grouped = self.repository.get_transfer_groups()?;
+        let transactions: Vec<Transaction> = self
+            .repository
+            .get_transactions()?
+            .into_iter()
+            .filter(|tx| !grouped.contains_key(&tx.id.to_string()))
+            .collect();
+
+        let outflows: Vec<&Transaction> = transactions
+            .iter()
+            .filter(|tx| tx.amount < Decimal::ZERO)
+            .collect();
+        let mut inflows: Vec<&Transaction> = transactions
+            .iter()
+            .filter(|tx| tx.amount > Decimal::ZERO)
+            .collect();
+        inflows.sort_by_key(|tx| tx.transaction_date);
+
+        let window = Duration::days(self.window_days);
+        let mut candidates = Vec::new();
+        for outflow in &outflows {
+            let earliest = outflow.transaction_date - window;
+            let latest = outflow.transaction_date + window;
+            let first = inflows.partition_point(|tx| tx.transaction_date < earliest);
+
+            for inflow in inflows[first..]
+                .iter()
+                .take_while(|tx| tx.transaction_date <= latest)
+            {
+                if inflow.account_id == outflow.account_id {
+                    continue;
+                }
+
+                // Positive when less arrived than left, e.g. the sender paid a fee
+                let fee = -outflow.amount - inflow.amount;
+                if fee.abs() > self.max_fee {
+                    continue;
+                }
+
+                let similarity = description_similarity(
+                    outflow.description.as_deref(),
+                    inflow.description.as_deref(),
+                );
+                if similarity < self.min_similarity {
+                    continue;
+                }
+
+                candidates.push(TransferPair {
+                    outflow_id: outflow.id.to_string(),
+                    inflow_id: inflow.id.to_string(),
+                    outflow_account_id: outflow.account_id.to_string(),
+                    inflow_account_id: inflow.account_id.to_string(),
+                    date: outflow.transaction_date,
+                    amount: -outflow.amount,
+                    fee,
+                    days_apart: (inflow.transaction_date - outflow.transaction_date)
+                        .num_days()
+                        .abs(),
+                    similarity,
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            a.fee
+                .abs()
+                .cmp(&b.fee.abs())
+                .then(a.days_apart.cmp(&b.days_apart))
+                .then(
+                    b.similarity
+                        .partial_cmp(&a.similarity)
+                        .unwrap_or(Ordering::Equal),
+                )
+        });
+
+        let mut used: HashSet<String> = HashSet::new();
+        let mut pairs: Vec<TransferPair> = candidates
+            .into_iter()
+            .filter(|pair| {
+                if used.contains(&pair.outflow_id) || used.contains(&pair.inflow_id) {
+                    return false;
+                }
+                used.insert(pair.outflow_id.clone());
+                used.insert(pair.inflow_id.clone());
+                true
+            })
+            .collect();
+        pairs.sort_by(|a, b| a.date.cmp(&b.date).then(a.outflow_id.cmp(&b.outflow_id)));
+
+        Ok(pairs)
+    }
+
+    /// Link two transactions as one transfer and tag both `transfer`
+    ///
+    /// Returns the ID of the new transfer group.
+    pub fn mark_as_transfer(&self, tx_a: &str, tx_b: &str) -> Result<String> {
+        if tx_a == tx_b {
+            anyhow::bail!("A transfer needs two different transactions");
+        }
+
+        let a = self
+            .repository
+            .get_transaction_by_id(tx_a)?
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", tx_a))?;
+        let b = self
+            .repository
+            .get_transaction_by_id(tx_b)?
+            .ok_or_else(|| anyhow::anyhow!("Transaction not found: {}", tx_b))?;
+        if a.account_id == b.account_id {
+            anyhow::bail!("Both transactions are in the same account");
+        }
+
+        let group_id = Uuid::new_v4().to_string();
+        self.repository
+            .set_transfer_group(&[tx_a, tx_b], &group_id)?;
+
+        for tx in [a, b] {
+            if !tx.tags.iter().any(|t| t == TRANSFER_TAG) {
+                let mut tags = tx.tags;
+                tags.push(TRANSFER_TAG.to_string());
+                self.repository
+                    .update_transaction_tags(&tx.id.to_string(), &tags)?;
+            }
+        }
+
+        Ok(group_id)
+    }
+}
+
+/// A likely transfer: money leaving one account and arriving in another
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferPair {
+    pub outflow_id: String,
+    pub inflow_id: String,
+    pub outflow_account_id: String,
+    pub inflow_account_id: String,
+    /// Date of the outflow
+    pub date: NaiveDate,
+    /// Amount that left the sending account
+    pub amount: Decimal,
+    /// Amount sent minus amount received; non-zero when a fee was taken
+    pub fee: Decimal,
+    pub days_apart: i64,
+    /// Description similarity from 0.0 to 1.0
+    pub similarity: f64,
+}
+
+/// How alike two descriptions are, from 0.0 to 1.0
+///
+/// Jaccard similarity of their words. Descriptions that both mention a
+/// transfer score 1.0, since banks word each side differently
+/// ("Transfer to Savings" vs. "Transfer from Checking").
+fn description_similarity(a: Option<&str>, b: Option<&str>) -> f64 {
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (words(a), words(b)),
+        _ => return 0.0,
+    };
+
+    let mentions_transfer =
+        |w: &HashSet<String>| w.iter().any(|w| TRANSFER_KEYWORDS.contains(&w.as_str()));
+    if mentions_transfer(&a) && mentions_transfer(&b) {
+        return 1.0;
+    }
+
+    let total = a.union(&b).count();
+    if total == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / total as f64
+}
+
+fn words(description: &str) -> HashSet<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_descriptions_match() {
+        assert_eq!(
+            description_similarity(Some("Online Transfer to SAV 1234"), Some("XFER from CHK")),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_description_similarity() {
+        let score = description_similarity(Some("ZELLE PAYMENT JOHN"), Some("Zelle payment"));
+        assert!((score - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            description_similarity(Some("Coffee shop"), Some("Payroll")),
+            0.0
+        );
+        assert_eq!(description_similarity(None, Some("Transfer")), 0.0);
+    }
+}
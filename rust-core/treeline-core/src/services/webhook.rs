@@ -0,0 +1,264 @@
+//! Sync webhook - POST a summary of each successful sync
+//!
+//! Opt-in through `syncWebhookUrl` in settings.json. When
+//! `syncWebhookSecret` is also set, the body is signed with HMAC-SHA256 and
+//! the signature sent as `X-Treeline-Signature: sha256=<hex>`, so receivers
+//! can check the request came from someone holding the secret.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::config::Config;
+use crate::services::sync::SyncResult;
+
+/// Header carrying the payload signature
+pub const SIGNATURE_HEADER: &str = "X-Treeline-Signature";
+
+/// Event name sent in every payload
+const SYNC_COMPLETED_EVENT: &str = "sync.completed";
+
+/// Webhook delivery must not hold up the sync for long
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON body POSTed to the webhook
+#[derive(Debug, Serialize)]
+pub struct SyncWebhookPayload {
+    pub event: &'static str,
+    pub synced_at: DateTime<Utc>,
+    pub integrations: Vec<IntegrationSummary>,
+    /// Transactions inserted across all integrations
+    pub new_transactions: i64,
+    /// Provider warnings from every integration, prefixed with its name
+    pub warnings: Vec<String>,
+}
+
+/// What one integration's sync changed
+#[derive(Debug, Serialize)]
+pub struct IntegrationSummary {
+    pub integration: String,
+    pub accounts_created: i64,
+    pub accounts_updated: i64,
+    pub new_transactions: i64,
+    pub warnings: Vec<String>,
+    /// Why this integration failed, when the sync carried on without it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SyncWebhookPayload {
+    pub fn from_result(result: &SyncResult) -> Self {
+        Self {
+            event: SYNC_COMPLETED_EVENT,
+            synced_at: Utc::now(),
+            integrations: result
+                .results
+                .iter()
+                .map(|r| IntegrationSummary {
+                    integration: r.integration.clone(),
+                    accounts_created: r.accounts_synced,
+                    accounts_updated: r.accounts_updated,
+                    new_transactions: r.transaction_stats.new,
+                    warnings: r.provider_warnings.clone(),
+                    error: r.error.clone(),
+                })
+                .collect(),
+            new_transactions: result.transactions_inserted,
+            warnings: result.warnings.clone(),
+        }
+    }
+}
+
+/// Where to send sync summaries, and how to sign them
+pub struct SyncWebhook {
+    url: String,
+    secret: Option<String>,
+}
+
+impl SyncWebhook {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+        }
+    }
+
+    /// Sign payloads with this secret
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// The webhook configured in settings, if any
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let url = config.sync_webhook_url.as_deref()?.trim();
+        if url.is_empty() {
+            return None;
+        }
+        let webhook = Self::new(url);
+        Some(match config.sync_webhook_secret.as_deref() {
+            Some(secret) if !secret.is_empty() => webhook.with_secret(secret),
+            _ => webhook,
+        })
+    }
+
+    /// POST a summary of `result`, failing on connection errors and non-2xx responses
+    pub fn send(&self, result: &SyncResult) -> Result<()> {
+        let body = serde_json::to_vec(&SyncWebhookPayload::from_result(result))?;
+
+        let client = Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .context("Failed to create HTTP client")?;
+        let mut request = client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign_payload(secret, &body));
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .context("Failed to deliver sync webhook")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Sync webhook returned HTTP {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Signature header value for a payload: `sha256=` followed by the hex HMAC
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::sync::{IntegrationSyncResult, TransactionStats};
+    use crate::test_support::{http_response, mock_http_server, request_header as header};
+
+    /// Accept one request, answer with `status` and return the raw request
+    fn mock_receiver(status: &str) -> (String, std::thread::JoinHandle<String>) {
+        let (base_url, server) = mock_http_server(vec![http_response(status, "", "")]);
+        let handle = std::thread::spawn(move || server.join().unwrap().remove(0));
+        (format!("{}/hook", base_url), handle)
+    }
+
+    fn body(request: &str) -> &str {
+        request.split_once("\r\n\r\n").unwrap().1
+    }
+
+    fn sample_result() -> SyncResult {
+        SyncResult::new(
+            vec![IntegrationSyncResult {
+                integration: "lunchflow".to_string(),
+                accounts_synced: 1,
+                accounts_updated: 2,
+                snapshots_created: 3,
+                transactions_synced: 4,
+                transaction_stats: TransactionStats {
+                    discovered: 5,
+                    new: 4,
+                    updated: 0,
+                    skipped: 1,
+                    removed: 0,
+                },
+                sync_type: "incremental".to_string(),
+                start_date: "2024-01-01".to_string(),
+                end_date: "2024-01-31".to_string(),
+                provider_warnings: vec!["Account 1 is disconnected".to_string()],
+                error: None,
+            }],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_send_signed_payload() {
+        let (url, server) = mock_receiver("200 OK");
+        SyncWebhook::new(url)
+            .with_secret("s3cret")
+            .send(&sample_result())
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook "));
+        assert_eq!(header(&request, "content-type"), Some("application/json"));
+        assert_eq!(
+            header(&request, SIGNATURE_HEADER),
+            Some(sign_payload("s3cret", body(&request).as_bytes()).as_str())
+        );
+
+        let payload: serde_json::Value = serde_json::from_str(body(&request)).unwrap();
+        assert_eq!(payload["event"], "sync.completed");
+        assert_eq!(payload["new_transactions"], 4);
+        assert_eq!(payload["integrations"][0]["integration"], "lunchflow");
+        assert_eq!(payload["integrations"][0]["accounts_updated"], 2);
+        assert_eq!(
+            payload["warnings"][0],
+            "lunchflow: Account 1 is disconnected"
+        );
+    }
+
+    #[test]
+    fn test_payload_reports_failed_integrations() {
+        let mut result = sample_result();
+        let payload = serde_json::to_value(SyncWebhookPayload::from_result(&result)).unwrap();
+        assert!(payload["integrations"][0].get("error").is_none());
+
+        result.results[0].error = Some("Invalid API key".to_string());
+        let payload = serde_json::to_value(SyncWebhookPayload::from_result(&result)).unwrap();
+        assert_eq!(payload["integrations"][0]["error"], "Invalid API key");
+    }
+
+    #[test]
+    fn test_send_unsigned_without_secret() {
+        let (url, server) = mock_receiver("204 No Content");
+        SyncWebhook::new(url).send(&sample_result()).unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(header(&request, SIGNATURE_HEADER), None);
+    }
+
+    #[test]
+    fn test_send_fails_on_error_status() {
+        let (url, server) = mock_receiver("500 Internal Server Error");
+        let err = SyncWebhook::new(url).send(&sample_result()).unwrap_err();
+        assert!(err.to_string().contains("500"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = Config::default();
+        assert!(SyncWebhook::from_config(&config).is_none());
+
+        config.sync_webhook_url = Some("  ".to_string());
+        assert!(SyncWebhook::from_config(&config).is_none());
+
+        config.sync_webhook_url = Some("https://example.com/hook".to_string());
+        config.sync_webhook_secret = Some(String::new());
+        let webhook = SyncWebhook::from_config(&config).unwrap();
+        assert_eq!(webhook.url, "https://example.com/hook");
+        assert!(webhook.secret.is_none());
+    }
+}
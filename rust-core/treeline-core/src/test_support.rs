@@ -0,0 +1,69 @@
+//! Helpers shared by unit and integration tests
+//!
+//! Not part of the public API.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::JoinHandle;
+
+/// Serve one canned response per connection on a local port.
+///
+/// Returns the base URL (`http://127.0.0.1:<port>`) and a handle yielding the
+/// raw requests that were served, in order.
+pub fn mock_http_server(responses: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            requests.push(read_request(&mut stream));
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+        requests
+    });
+    (base_url, handle)
+}
+
+/// Build a complete HTTP/1.1 response with a JSON body
+pub fn http_response(status: &str, extra_headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+        status,
+        body.len(),
+        extra_headers,
+        body
+    )
+}
+
+/// Read headers and a Content-Length body from a stream
+pub fn read_request(stream: &mut TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(header_end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            let headers = String::from_utf8_lossy(&data[..header_end]);
+            let content_length = request_header(&headers, "content-length")
+                .and_then(|value| value.parse::<usize>().ok())
+                .unwrap_or(0);
+            if data.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8(data).unwrap()
+}
+
+/// Look up a header value (case-insensitive) in a raw request
+pub fn request_header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    let headers = request.split("\r\n\r\n").next().unwrap_or(request);
+    headers.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.eq_ignore_ascii_case(name).then_some(value.trim())
+    })
+}
@@ -14,7 +14,7 @@ use std::time::{Duration, Instant};
 use tempfile::TempDir;
 use uuid::Uuid;
 
-use treeline_core::adapters::duckdb::DuckDbRepository;
+use treeline_core::adapters::duckdb::{DuckDbRepository, RepositoryError};
 use treeline_core::domain::Account;
 
 /// Number of concurrent threads for stress tests.
@@ -120,7 +120,7 @@ fn test_concurrent_repository_instances_writing() {
 
     // Verify database integrity by reading all accounts
     let repo = DuckDbRepository::new(&db_path, None).unwrap();
-    let accounts = repo.get_accounts().unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
     println!("Accounts in database: {}", accounts.len());
 
     // All operations should succeed with proper locking
@@ -191,7 +191,7 @@ fn test_concurrent_read_write_operations() {
                     }
                 } else {
                     // Read operation
-                    if let Err(e) = repo.get_accounts() {
+                    if let Err(e) = repo.get_accounts(false) {
                         eprintln!("Thread {}: Read error: {}", thread_id, e);
                         read_errors.fetch_add(1, Ordering::SeqCst);
                     }
@@ -442,7 +442,7 @@ fn test_high_contention_writes() {
 
     // Verify final state
     let repo = DuckDbRepository::new(&db_path, None).unwrap();
-    let accounts = repo.get_accounts().unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
 
     println!("\n=== High Contention Results ===");
     println!("Errors: {}", total_errors);
@@ -493,8 +493,8 @@ fn test_database_integrity_after_concurrent_ops() {
                 for i in 0..ITERATIONS_PER_THREAD {
                     let account = create_test_account(&format!("integrity_t{}_i{}", thread_id, i));
                     let _ = repo.upsert_account(&account);
-                    let _ = repo.get_accounts();
-                    let _ = repo.get_accounts();
+                    let _ = repo.get_accounts(false);
+                    let _ = repo.get_accounts(false);
                 }
             }
         });
@@ -512,7 +512,7 @@ fn test_database_integrity_after_concurrent_ops() {
     let repo = DuckDbRepository::new(&db_path, None).unwrap();
 
     // Should be able to read accounts
-    let accounts = repo.get_accounts();
+    let accounts = repo.get_accounts(false);
     assert!(accounts.is_ok(), "Should be able to read accounts");
 
     let account_list = accounts.unwrap();
@@ -591,7 +591,7 @@ fn test_startup_race_condition() {
             match DuckDbRepository::new(&db_path, None) {
                 Ok(repo) => {
                     // Try to do sync-like operations
-                    match repo.get_accounts() {
+                    match repo.get_accounts(false) {
                         Ok(accounts) => {
                             println!("Sync thread: Read {} accounts", accounts.len());
                         }
@@ -776,7 +776,7 @@ fn test_concurrent_bulk_writes() {
 
     // Verify final state
     let repo = DuckDbRepository::new(&db_path, None).unwrap();
-    let accounts = repo.get_accounts().unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
 
     println!("\n=== Bulk Write Results ===");
     println!("Total writes attempted: {}", expected_total);
@@ -886,7 +886,7 @@ fn test_mixed_heavy_workload() {
             };
 
             for i in 0..ops_per_thread {
-                match repo.get_accounts() {
+                match repo.get_accounts(false) {
                     Ok(accounts) => {
                         read_count.fetch_add(1, Ordering::SeqCst);
                         // Verify we can read the accounts (at least pre-populated ones)
@@ -929,7 +929,7 @@ fn test_mixed_heavy_workload() {
 
     // Verify final state
     let repo = DuckDbRepository::new(&db_path, None).unwrap();
-    let final_accounts = repo.get_accounts().unwrap();
+    let final_accounts = repo.get_accounts(false).unwrap();
     println!("Final account count: {}", final_accounts.len());
 
     assert_eq!(w_errors, 0, "No write errors should occur");
@@ -1015,7 +1015,7 @@ fn test_rapid_open_write_close_cycle() {
 
     // Verify
     let repo = DuckDbRepository::new(&db_path, None).unwrap();
-    let accounts = repo.get_accounts().unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
 
     println!("\n=== Rapid Cycle Results ===");
     println!("Total commands: {}", expected_total);
@@ -1031,3 +1031,101 @@ fn test_rapid_open_write_close_cycle() {
         "Should have correct account count"
     );
 }
+
+/// Test: Threads sharing one repository read while a slow query is running.
+///
+/// Reads use their own connections, so a long SELECT on one thread must not
+/// hold up `get_accounts` or `get_transaction_count` on the others.
+#[test]
+fn test_concurrent_reads_during_slow_query() {
+    use std::sync::atomic::AtomicBool;
+    use treeline_core::adapters::duckdb::QueryLimits;
+
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test_concurrent_reads.duckdb");
+
+    let repo = Arc::new(DuckDbRepository::new(&db_path, None).unwrap());
+    repo.ensure_schema().unwrap();
+    repo.upsert_account(&create_test_account("reader")).unwrap();
+
+    // Runs until the timeout interrupts it
+    let slow_running = Arc::new(AtomicBool::new(true));
+    let slow = {
+        let repo = Arc::clone(&repo);
+        let slow_running = Arc::clone(&slow_running);
+        thread::spawn(move || {
+            let limits = QueryLimits {
+                timeout: Some(Duration::from_secs(3)),
+                max_rows: None,
+            };
+            let result = repo.execute_query_with_limits(
+                "SELECT COUNT(*) FROM range(100000) a, range(100000) b WHERE (a.range * b.range) % 7 = 3",
+                limits,
+            );
+            slow_running.store(false, Ordering::SeqCst);
+            result
+        })
+    };
+    thread::sleep(Duration::from_millis(200));
+
+    let readers: Vec<_> = (0..THREAD_COUNT)
+        .map(|_| {
+            let repo = Arc::clone(&repo);
+            thread::spawn(move || {
+                let start = Instant::now();
+                for _ in 0..ITERATIONS_PER_THREAD {
+                    assert_eq!(repo.get_accounts(false).unwrap().len(), 1);
+                    assert_eq!(repo.get_transaction_count().unwrap(), 0);
+                }
+                start.elapsed()
+            })
+        })
+        .collect();
+
+    for reader in readers {
+        let elapsed = reader.join().unwrap();
+        assert!(
+            elapsed < Duration::from_secs(2),
+            "Reads waited {:?} behind the slow query",
+            elapsed
+        );
+    }
+    assert!(
+        slow_running.load(Ordering::SeqCst),
+        "Reads should finish while the slow query is still running"
+    );
+
+    let slow_result = slow.join().unwrap();
+    assert!(slow_result.is_err(), "Slow query should hit its timeout");
+}
+
+/// Opening a database another handle holds fails fast with `Locked` instead of waiting
+#[test]
+fn test_try_new_on_locked_database_returns_locked() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+
+    let holder = DuckDbRepository::new(&db_path, None).unwrap();
+
+    let start = Instant::now();
+    let result = DuckDbRepository::try_new(&db_path, None);
+    assert!(
+        matches!(result, Err(RepositoryError::Locked(ref path)) if *path == db_path),
+        "Expected Locked, got {:?}",
+        result.err()
+    );
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // Still reachable through anyhow for callers that only see that
+    let err: anyhow::Error = match DuckDbRepository::try_new(&db_path, None) {
+        Ok(_) => panic!("A second handle should not get the lock"),
+        Err(e) => e.into(),
+    };
+    assert!(matches!(
+        err.downcast_ref::<RepositoryError>(),
+        Some(RepositoryError::Locked(_))
+    ));
+
+    drop(holder);
+    assert!(DuckDbRepository::try_new(&db_path, None).is_ok());
+}
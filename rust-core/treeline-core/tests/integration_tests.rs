@@ -5,20 +5,39 @@
 //!
 //! Run with: cargo test --test integration_tests -- --nocapture
 
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
 use uuid::Uuid;
 
-use chrono::{NaiveDate, Utc};
+use chrono::{Datelike, NaiveDate, Utc};
 use rust_decimal::Decimal;
 
-use treeline_core::adapters::duckdb::DuckDbRepository;
-use treeline_core::config::ColumnMappings;
-use treeline_core::domain::{Account, BalanceSnapshot, Transaction};
+use treeline_core::adapters::demo::{generate_demo_data, DemoData};
+use treeline_core::adapters::duckdb::{
+    AccountResolution, DuckDbRepository, QueryLimits, RepositoryError, TransactionFilter,
+};
+use treeline_core::adapters::registry::ProviderRegistry;
+use treeline_core::config::{ColumnMappings, Config};
+use treeline_core::domain::result::Result as DomainResult;
+use treeline_core::domain::{
+    Account, Argon2Params, AutoTagRule, BalanceSnapshot, ConflictPolicy, DateBasis, FiscalCalendar,
+    Transaction, DEFAULT_ACCOUNT_GROUP,
+};
+use treeline_core::ports::{
+    ConnectionStatus, DataAggregationProvider, FetchAccountsResult, FetchTransactionsResult,
+    IntegrationProvider,
+};
 use treeline_core::services::{
-    BackupService, ImportOptions, ImportService, NumberFormat, TagService,
+    AccountService, BackupService, BalanceService, CompactService, ConfigService, DemoOptions,
+    DemoService, DoctorService, EncryptionService, ImportOptions, ImportResult, ImportService,
+    ImportTotals, NewAccount, NumberFormat, PluginService, QueryService, RetentionPolicy,
+    SkipReason, StatusService, SyncHook, SyncService, TableDiff, TagService, TransactionPatch,
+    TransactionService, TransferDetector, TRANSFER_TAG, UNTAGGED,
 };
+use treeline_core::{EntryPoint, TreelineContext};
 
 // ============================================================================
 // Test Helpers
@@ -81,7 +100,7 @@ fn test_delete_account_removes_all_related_data() {
     repo.add_balance_snapshot(&snapshot).unwrap();
 
     // Verify data exists
-    let accounts_before = repo.get_accounts().unwrap();
+    let accounts_before = repo.get_accounts(false).unwrap();
     let transactions_before = repo.get_transactions().unwrap();
     let snapshots_before = repo.get_balance_snapshot_count().unwrap();
     assert_eq!(accounts_before.len(), 1);
@@ -92,7 +111,7 @@ fn test_delete_account_removes_all_related_data() {
     repo.delete_account(&account_id.to_string()).unwrap();
 
     // Verify ALL related data is gone
-    let accounts_after = repo.get_accounts().unwrap();
+    let accounts_after = repo.get_accounts(false).unwrap();
     let transactions_after = repo.get_transactions().unwrap();
 
     assert_eq!(accounts_after.len(), 0, "Account should be deleted");
@@ -130,7 +149,7 @@ fn test_delete_account_no_orphans_on_nonexistent_account() {
     );
 
     // Verify existing data is intact
-    let accounts = repo.get_accounts().unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
     let transactions = repo.get_transactions().unwrap();
     assert_eq!(accounts.len(), 2, "Existing accounts should remain");
     assert_eq!(transactions.len(), 2, "Existing transactions should remain");
@@ -166,7 +185,7 @@ fn test_delete_account_isolation() {
     repo.delete_account(&account1.id.to_string()).unwrap();
 
     // Verify account2's data is intact
-    let accounts = repo.get_accounts().unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
     let transactions = repo.get_transactions().unwrap();
 
     assert_eq!(accounts.len(), 1, "Only account1 should be deleted");
@@ -182,579 +201,6653 @@ fn test_delete_account_isolation() {
     );
 }
 
+/// Test merging a duplicate account moves its history and removes it
+#[test]
+fn test_merge_accounts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account_service = AccountService::new(repo.clone());
+
+    let mut source = create_test_account("Checking (old connection)");
+    source.lf_id = Some("lf-acc-1".to_string());
+    let target = create_test_account("Checking");
+    repo.upsert_account(&source).unwrap();
+    repo.upsert_account(&target).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let older = Utc::now() - chrono::Duration::days(1);
+    let add = |account_id: Uuid, lf_id: Option<&str>, newer: bool| {
+        let mut tx = create_test_transaction(account_id, -1000, date);
+        tx.lf_id = lf_id.map(str::to_string);
+        if !newer {
+            tx.updated_at = older;
+        }
+        repo.upsert_transaction(&tx).unwrap();
+        tx
+    };
+    add(source.id, None, true);
+    add(source.id, None, true);
+    add(target.id, None, true);
+    // Both accounts hold lf-1 and lf-2; the newer copy of each must win
+    let newer_lf1 = add(source.id, Some("lf-1"), true);
+    add(target.id, Some("lf-1"), false);
+    add(source.id, Some("lf-2"), false);
+    let newer_lf2 = add(target.id, Some("lf-2"), true);
+    repo.add_balance_snapshot(&create_balance_snapshot(source.id, Decimal::new(5000, 2)))
+        .unwrap();
+
+    let report = account_service
+        .merge(&source.id.to_string(), &target.id.to_string())
+        .unwrap();
+    assert_eq!(report.transactions_moved, 3);
+    assert_eq!(report.duplicates_dropped, 2);
+    assert_eq!(report.snapshots_moved, 1);
+
+    let accounts = repo.get_accounts(true).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].id, target.id);
+    // The provider link moves over, so sync keeps matching the survivor
+    assert_eq!(accounts[0].lf_id.as_deref(), Some("lf-acc-1"));
+
+    let transactions = repo.get_transactions().unwrap();
+    assert_eq!(transactions.len(), 5);
+    assert!(transactions.iter().all(|t| t.account_id == target.id));
+    let ids: HashSet<Uuid> = transactions.iter().map(|t| t.id).collect();
+    assert!(ids.contains(&newer_lf1.id));
+    assert!(ids.contains(&newer_lf2.id));
+    assert_eq!(
+        repo.get_balance_snapshots(Some(&target.id.to_string()))
+            .unwrap()
+            .len(),
+        1
+    );
+
+    assert!(account_service
+        .merge(&target.id.to_string(), &target.id.to_string())
+        .is_err());
+    assert!(account_service
+        .merge(&source.id.to_string(), &target.id.to_string())
+        .is_err());
+}
+
 // ============================================================================
-// Backup Service Tests
+// Account Archiving Tests
 // ============================================================================
 
-/// Test that backup includes all data and can be listed
+/// Test that archiving hides an account but keeps its history
 #[test]
-fn test_backup_create_and_list() {
+fn test_archive_account_keeps_history() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
 
-    // Add some data
-    let account = create_test_account("Backup Test Account");
+    let account = create_test_account("Closed Account");
     repo.upsert_account(&account).unwrap();
-
     let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    let tx = create_test_transaction(account.id, 5000, date);
+    let tx = create_test_transaction(account.id, 1000, date);
     repo.upsert_transaction(&tx).unwrap();
 
-    // Create backup service with repository (for checkpointing)
-    let backup_service = BackupService::new_with_repository(
-        temp_dir.path().to_path_buf(),
-        "test.duckdb".to_string(),
-        repo.clone(),
+    repo.set_account_archived(&account.id.to_string(), true)
+        .unwrap();
+
+    assert!(
+        repo.get_accounts(false).unwrap().is_empty(),
+        "Archived account should be hidden by default"
+    );
+    let all = repo.get_accounts(true).unwrap();
+    assert_eq!(all.len(), 1);
+    assert!(all[0].is_archived);
+    assert_eq!(
+        repo.get_transactions_by_account(&account.id.to_string())
+            .unwrap()
+            .len(),
+        1,
+        "Archiving must not delete transactions"
     );
 
-    // Create backup
-    let backup_result = backup_service.create(None);
-    assert!(backup_result.is_ok(), "Backup should succeed");
-    let backup = backup_result.unwrap();
-    assert!(backup.name.starts_with("treeline-"), "Backup name format");
-    assert!(backup.name.ends_with(".zip"), "Backup should be a zip file");
-    assert!(backup.size_bytes > 0, "Backup should have content");
+    // Re-syncing the account must not clear the archived flag
+    repo.upsert_account(&account).unwrap();
+    assert!(repo.get_accounts(true).unwrap()[0].is_archived);
 
-    // List backups
-    let backups = backup_service.list().unwrap();
-    assert_eq!(backups.len(), 1, "Should have one backup");
-    assert_eq!(backups[0].name, backup.name);
+    repo.set_account_archived(&account.id.to_string(), false)
+        .unwrap();
+    assert_eq!(repo.get_accounts(false).unwrap().len(), 1);
 }
 
-/// Test backup retention policy
+/// Test that archiving a missing account is an error
 #[test]
-fn test_backup_retention_policy() {
+fn test_archive_nonexistent_account() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
 
-    let backup_service = BackupService::new_with_repository(
-        temp_dir.path().to_path_buf(),
-        "test.duckdb".to_string(),
-        repo.clone(),
-    );
-
-    // Create 5 backups
-    for _ in 0..5 {
-        backup_service.create(None).unwrap();
-        std::thread::sleep(std::time::Duration::from_millis(10)); // Ensure unique timestamps
-    }
-
-    let backups_before = backup_service.list().unwrap();
-    assert_eq!(backups_before.len(), 5, "Should have 5 backups");
+    let service = AccountService::new(repo);
+    let err = service.archive(&Uuid::new_v4().to_string()).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<RepositoryError>(),
+        Some(RepositoryError::NotFound {
+            kind: "Account",
+            ..
+        })
+    ));
+}
 
-    // Create one more with max_backups = 3
-    backup_service.create(Some(3)).unwrap();
+/// Opening failures are reported as distinct repository errors
+#[test]
+fn test_repository_open_errors() {
+    let temp_dir = TempDir::new().unwrap();
 
-    let backups_after = backup_service.list().unwrap();
-    assert_eq!(
-        backups_after.len(),
-        3,
-        "Should have at most 3 backups after retention"
+    let missing = DuckDbRepository::new_read_only(&temp_dir.path().join("missing.duckdb"), None);
+    assert!(matches!(
+        missing,
+        Err(RepositoryError::NotFound {
+            kind: "Database",
+            ..
+        })
+    ));
+
+    let db_path = temp_dir.path().join("encrypted.duckdb");
+    let key = "00".repeat(32);
+    {
+        let repo = DuckDbRepository::new(&db_path, Some(&key)).unwrap();
+        repo.ensure_schema().unwrap();
+    }
+    let wrong_key = "11".repeat(32);
+    let result = DuckDbRepository::new(&db_path, Some(&wrong_key));
+    assert!(
+        matches!(result, Err(RepositoryError::Encryption(_))),
+        "Expected Encryption, got {:?}",
+        result.err()
     );
+    assert!(DuckDbRepository::new(&db_path, Some(&key)).is_ok());
 }
 
-/// Test backup restore
+/// Test that archived accounts are excluded from net worth unless requested
 #[test]
-fn test_backup_restore() {
+fn test_status_excludes_archived_from_net_worth() {
     let temp_dir = TempDir::new().unwrap();
-    let db_path = temp_dir.path().join("test.duckdb");
+    let repo = create_test_repo(&temp_dir);
 
-    // Create initial state
-    {
-        let repo = DuckDbRepository::new(&db_path, None).unwrap();
-        repo.ensure_schema().unwrap();
+    let active = create_test_account("Active");
+    let closed = create_test_account("Closed");
+    repo.upsert_account(&active).unwrap();
+    repo.upsert_account(&closed).unwrap();
+    repo.add_balance_snapshot(&create_balance_snapshot(active.id, Decimal::new(10000, 2)))
+        .unwrap();
+    repo.add_balance_snapshot(&create_balance_snapshot(closed.id, Decimal::new(5000, 2)))
+        .unwrap();
 
-        let account = create_test_account("Original Account");
-        repo.upsert_account(&account).unwrap();
-    }
+    AccountService::new(repo.clone())
+        .archive(&closed.id.to_string())
+        .unwrap();
 
-    // Create backup (with repo for checkpoint)
-    let backup_name;
-    {
-        let repo = Arc::new(DuckDbRepository::new(&db_path, None).unwrap());
-        let backup_service = BackupService::new_with_repository(
-            temp_dir.path().to_path_buf(),
-            "test.duckdb".to_string(),
-            repo.clone(),
-        );
-        let backup = backup_service.create(None).unwrap();
-        backup_name = backup.name;
-    }
+    let status_service = StatusService::new(repo);
+    let status = status_service.get_status(false).unwrap();
+    assert_eq!(status.total_accounts, 1);
+    assert_eq!(status.net_worth, Decimal::new(10000, 2));
+
+    let status = status_service.get_status(true).unwrap();
+    assert_eq!(status.total_accounts, 2);
+    assert_eq!(status.net_worth, Decimal::new(15000, 2));
+    assert!(status
+        .accounts
+        .iter()
+        .any(|a| a.name == "Closed" && a.is_archived));
+}
 
-    // Modify data
-    {
-        let repo = DuckDbRepository::new(&db_path, None).unwrap();
-        let account2 = create_test_account("New Account After Backup");
-        repo.upsert_account(&account2).unwrap();
+/// Test that status subtotals net worth per account group
+#[test]
+fn test_status_group_subtotals() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
 
-        let accounts = repo.get_accounts().unwrap();
-        assert_eq!(accounts.len(), 2, "Should have 2 accounts before restore");
+    let joint = create_test_account("Joint Checking");
+    let business = create_test_account("Business Checking");
+    let loose = create_test_account("Wallet");
+    for (account, cents) in [(&joint, 10000), (&business, 25000), (&loose, 500)] {
+        repo.upsert_account(account).unwrap();
+        repo.add_balance_snapshot(&create_balance_snapshot(account.id, Decimal::new(cents, 2)))
+            .unwrap();
     }
 
-    // Restore from backup (without repo - restore doesn't need checkpoint)
-    {
-        let backup_service =
-            BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
-        backup_service.restore(&backup_name).unwrap();
-    }
+    let account_service = AccountService::new(repo.clone());
+    let result = account_service
+        .set_group(&joint.id.to_string(), Some(" Joint "))
+        .unwrap();
+    assert_eq!(result.group.as_deref(), Some("Joint"));
+    account_service
+        .set_group(&business.id.to_string(), Some("Business"))
+        .unwrap();
+    assert!(account_service
+        .set_group(&Uuid::new_v4().to_string(), Some("Joint"))
+        .is_err());
+
+    // Re-syncing an account must not clear its group
+    repo.upsert_account(&business).unwrap();
+
+    let status = StatusService::new(repo.clone()).get_status(false).unwrap();
+    let groups: Vec<(&str, i64, Decimal)> = status
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.account_count, g.net_worth))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("Business", 1, Decimal::new(25000, 2)),
+            ("Joint", 1, Decimal::new(10000, 2)),
+            (DEFAULT_ACCOUNT_GROUP, 1, Decimal::new(500, 2)),
+        ]
+    );
+    assert_eq!(status.net_worth, Decimal::new(35500, 2));
 
-    // Verify restored state
-    {
-        let repo = DuckDbRepository::new(&db_path, None).unwrap();
-        let accounts = repo.get_accounts().unwrap();
-        assert_eq!(
-            accounts.len(),
-            1,
-            "Should have only 1 account after restore"
-        );
-        assert!(
-            accounts[0].name.contains("Original"),
-            "Should be the original account"
-        );
-    }
+    // A blank group ungroups the account
+    account_service
+        .set_group(&business.id.to_string(), Some(""))
+        .unwrap();
+    let status = StatusService::new(repo).get_status(false).unwrap();
+    assert_eq!(status.groups.len(), 2);
+    assert_eq!(status.groups[1].name, DEFAULT_ACCOUNT_GROUP);
+    assert_eq!(status.groups[1].account_count, 2);
 }
 
-// ============================================================================
-// Tag Service Tests
-// ============================================================================
-
-/// Test applying tags to transactions
+/// Test that status net worth uses balances as stored, like the net worth series
 #[test]
-fn test_tag_apply_to_transactions() {
+fn test_status_net_worth_matches_series() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
-    let tag_service = TagService::new(repo.clone());
+    let today = Utc::now().date_naive();
 
-    // Create account and transaction
-    let account = create_test_account("Tag Test Account");
-    repo.upsert_account(&account).unwrap();
+    let checking = create_test_account("Checking");
+    repo.upsert_account(&checking).unwrap();
+    add_snapshot_on(&repo, checking.id, 100000, today);
 
-    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    let tx = create_test_transaction(account.id, 1000, date);
-    repo.upsert_transaction(&tx).unwrap();
+    // Stored positive, as some providers report card balances
+    let mut card = create_test_account("Credit Card");
+    card.classification = Some("liability".to_string());
+    repo.upsert_account(&card).unwrap();
+    add_snapshot_on(&repo, card.id, 20000, today);
 
-    // Apply tags
-    let tags = vec!["groceries".to_string(), "food".to_string()];
-    let result = tag_service
-        .apply_tags(&[tx.id.to_string()], &tags, false)
+    let status = StatusService::new(repo.clone()).get_status(false).unwrap();
+    let series = BalanceService::new(repo)
+        .net_worth_series(today, today, "USD")
         .unwrap();
-
-    assert_eq!(result.succeeded, 1);
-    assert_eq!(result.failed, 0);
-
-    // Verify tags were applied
-    let updated_tx = repo.get_transaction_by_id(&tx.id.to_string()).unwrap();
-    assert!(updated_tx.is_some());
-    let updated_tx = updated_tx.unwrap();
-    assert!(updated_tx.tags.contains(&"groceries".to_string()));
-    assert!(updated_tx.tags.contains(&"food".to_string()));
+    assert_eq!(status.net_worth, Decimal::new(120000, 2));
+    assert_eq!(status.net_worth, series[0].1);
 }
 
-/// Test tag merging (additive)
+/// Test that the status date range follows the configured date basis
 #[test]
-fn test_tag_merge_additive() {
+fn test_status_date_range_by_posted_date() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
-    let tag_service = TagService::new(repo.clone());
 
-    // Create account and transaction with initial tags
-    let account = create_test_account("Tag Merge Test");
+    let account = create_test_account("Date Basis");
     repo.upsert_account(&account).unwrap();
-
-    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    let mut tx = create_test_transaction(account.id, 1000, date);
-    tx.tags = vec!["existing".to_string()];
+    let mut tx = create_test_transaction(
+        account.id,
+        -1000,
+        NaiveDate::from_ymd_opt(2024, 1, 30).unwrap(),
+    );
+    tx.posted_date = NaiveDate::from_ymd_opt(2024, 2, 2).unwrap();
     repo.upsert_transaction(&tx).unwrap();
 
-    // Apply new tags (additive, not replace)
-    let new_tags = vec!["new_tag".to_string()];
-    tag_service
-        .apply_tags(&[tx.id.to_string()], &new_tags, false)
-        .unwrap();
+    let status = StatusService::new(repo.clone()).get_status(false).unwrap();
+    assert_eq!(status.date_range.latest, Some("2024-01-30".to_string()));
 
-    // Verify both old and new tags exist
-    let updated_tx = repo
-        .get_transaction_by_id(&tx.id.to_string())
-        .unwrap()
+    // The preference round-trips through settings.json
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    assert_eq!(config.date_basis, DateBasis::Transaction);
+    config.date_basis = DateBasis::Posted;
+    config.save(temp_dir.path()).unwrap();
+    let config = Config::load(temp_dir.path()).unwrap();
+    assert_eq!(config.date_basis, DateBasis::Posted);
+
+    let status = StatusService::new(repo)
+        .with_date_basis(config.date_basis)
+        .get_status(false)
         .unwrap();
-    assert!(
-        updated_tx.tags.contains(&"existing".to_string()),
-        "Original tag should remain"
-    );
-    assert!(
-        updated_tx.tags.contains(&"new_tag".to_string()),
-        "New tag should be added"
-    );
+    assert_eq!(status.date_range.earliest, Some("2024-02-02".to_string()));
+    assert_eq!(status.date_range.latest, Some("2024-02-02".to_string()));
 }
 
-/// Test tag replacement
+/// Test monthly cash flow: gaps, the partial current month and transfers
 #[test]
-fn test_tag_replace() {
+fn test_status_cash_flow() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
-    let tag_service = TagService::new(repo.clone());
 
-    // Create account and transaction with initial tags
-    let account = create_test_account("Tag Replace Test");
+    let account = create_test_account("Cash Flow");
     repo.upsert_account(&account).unwrap();
+    let day = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+    for (amount, date) in [
+        (100000, day(1, 5)),
+        (-20050, day(1, 20)),
+        (-5000, day(3, 2)),
+        (99900, NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()),
+        (100, day(4, 1)),
+    ] {
+        repo.upsert_transaction(&create_test_transaction(account.id, amount, date))
+            .unwrap();
+    }
+    let mut transfer = create_test_transaction(account.id, -50000, day(3, 3));
+    transfer.tags = vec!["transfer".to_string()];
+    repo.upsert_transaction(&transfer).unwrap();
 
-    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    let mut tx = create_test_transaction(account.id, 1000, date);
-    tx.tags = vec!["old_tag".to_string()];
-    repo.upsert_transaction(&tx).unwrap();
+    let status_service = StatusService::new(repo);
+    let cash_flow = status_service.cash_flow_as_of(3, day(3, 10)).unwrap();
 
-    // Replace tags
-    let new_tags = vec!["replacement".to_string()];
-    tag_service
-        .apply_tags(&[tx.id.to_string()], &new_tags, true)
-        .unwrap();
+    let months: Vec<&str> = cash_flow.iter().map(|m| m.month.as_str()).collect();
+    assert_eq!(months, vec!["2024-01", "2024-02", "2024-03"]);
 
-    // Verify only new tags exist
-    let updated_tx = repo
-        .get_transaction_by_id(&tx.id.to_string())
-        .unwrap()
-        .unwrap();
-    assert!(
-        !updated_tx.tags.contains(&"old_tag".to_string()),
-        "Old tag should be gone"
-    );
-    assert!(
-        updated_tx.tags.contains(&"replacement".to_string()),
-        "New tag should exist"
-    );
+    assert_eq!(cash_flow[0].income, Decimal::new(100000, 2));
+    assert_eq!(cash_flow[0].expenses, Decimal::new(20050, 2));
+    assert_eq!(cash_flow[0].net, Decimal::new(79950, 2));
+    assert!(!cash_flow[0].is_partial);
+
+    assert_eq!(cash_flow[1].net, Decimal::ZERO);
+
+    // The transfer is left out of the current, partial month
+    assert_eq!(cash_flow[2].income, Decimal::ZERO);
+    assert_eq!(cash_flow[2].expenses, Decimal::new(5000, 2));
+    assert!(cash_flow[2].is_partial);
+
+    assert!(status_service.cash_flow(0).is_err());
 }
 
-/// Test applying tags to invalid transaction ID
+/// Test cash flow grouped by fiscal months starting on the 25th
 #[test]
-fn test_tag_invalid_transaction_id() {
+fn test_status_cash_flow_fiscal_months() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
-    let tag_service = TagService::new(repo.clone());
 
-    // Try to tag with invalid UUID
-    let tags = vec!["test".to_string()];
-    let result = tag_service.apply_tags(&["not-a-valid-uuid".to_string()], &tags, false);
+    let account = create_test_account("Fiscal");
+    repo.upsert_account(&account).unwrap();
+    let day = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+    for (amount, date) in [
+        (300000, day(2, 25)),
+        (-4000, day(3, 24)),
+        (-1500, day(3, 25)),
+    ] {
+        repo.upsert_transaction(&create_test_transaction(account.id, amount, date))
+            .unwrap();
+    }
 
-    assert!(result.is_ok());
-    let result = result.unwrap();
-    assert_eq!(result.failed, 1, "Should fail for invalid UUID");
-    assert_eq!(result.succeeded, 0);
+    // The setting round-trips through settings.json
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    assert_eq!(config.fiscal_calendar(), FiscalCalendar::default());
+    config.fiscal_month_start_day = 25;
+    config.save(temp_dir.path()).unwrap();
+    let config = Config::load(temp_dir.path()).unwrap();
+    assert_eq!(config.fiscal_month_start_day, 25);
+    assert_eq!(config.fiscal_year_start_month, 1);
+
+    let status_service =
+        StatusService::new(repo.clone()).with_fiscal_calendar(config.fiscal_calendar());
+    let cash_flow = status_service.cash_flow_as_of(2, day(4, 1)).unwrap();
+
+    let months: Vec<&str> = cash_flow.iter().map(|m| m.month.as_str()).collect();
+    assert_eq!(months, vec!["2024-02", "2024-03"]);
+
+    // The 24th still belongs to the fiscal month that started on Feb 25
+    assert_eq!(cash_flow[0].income, Decimal::new(300000, 2));
+    assert_eq!(cash_flow[0].expenses, Decimal::new(4000, 2));
+    assert!(!cash_flow[0].is_partial);
+    assert_eq!(cash_flow[1].expenses, Decimal::new(1500, 2));
+    assert!(cash_flow[1].is_partial);
+
+    // Calendar months put both expenses in March
+    let cash_flow = StatusService::new(repo)
+        .cash_flow_as_of(2, day(4, 1))
+        .unwrap();
+    assert_eq!(cash_flow[0].month, "2024-03");
+    assert_eq!(cash_flow[0].expenses, Decimal::new(5500, 2));
 }
 
 // ============================================================================
-// Import Service Tests
+// Balance Reconciliation Tests
 // ============================================================================
 
-/// Test CSV import with valid data
+/// Setting a manual balance twice on one day updates the same snapshot
 #[test]
-fn test_csv_import_valid_data() {
+fn test_set_manual_balance() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
 
-    // Create account for import
-    let account = create_test_account("Import Test Account");
+    let account = create_test_account("Cash");
     repo.upsert_account(&account).unwrap();
+    let id = account.id.to_string();
+    let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
 
-    // Create CSV file
-    let csv_content = r#"date,amount,description
-2024-01-15,12.34,Coffee Shop
-2024-01-16,-45.67,Restaurant
-2024-01-17,100.00,Refund"#;
+    let first = balance_service
+        .set_manual_balance(&id, Decimal::new(5000, 2), day)
+        .unwrap();
+    assert_eq!(first.source.as_deref(), Some("manual"));
+    assert_eq!(
+        first.snapshot_time,
+        day.and_hms_micro_opt(23, 59, 59, 999999).unwrap()
+    );
 
-    let csv_path = temp_dir.path().join("test_import.csv");
+    let second = balance_service
+        .set_manual_balance(&id, Decimal::new(7500, 2), day)
+        .unwrap();
+    assert_eq!(second.id, first.id);
+
+    let snapshots = repo.get_balance_snapshots(Some(&id)).unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].balance, Decimal::new(7500, 2));
+
+    // Another day gets its own snapshot and becomes the current balance
+    balance_service
+        .set_manual_balance(&id, Decimal::new(9000, 2), day.succ_opt().unwrap())
+        .unwrap();
+    assert_eq!(repo.get_balance_snapshots(Some(&id)).unwrap().len(), 2);
+    let account = repo.get_account_by_id(&id).unwrap().unwrap();
+    assert_eq!(account.balance, Some(Decimal::new(9000, 2)));
+
+    assert!(balance_service
+        .set_manual_balance(&Uuid::new_v4().to_string(), Decimal::ONE, day)
+        .is_err());
+}
+
+/// A balance adjustment without a transaction only writes a noted snapshot
+#[test]
+fn test_adjust_balance_snapshot_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Cash");
+    repo.upsert_account(&account).unwrap();
+    let id = account.id.to_string();
+    let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+    balance_service
+        .set_manual_balance(&id, Decimal::new(10000, 2), day)
+        .unwrap();
+    let snapshot_id = balance_service
+        .adjust_balance(
+            &id,
+            Decimal::new(8000, 2),
+            day.succ_opt().unwrap(),
+            Some("counted the wallet".to_string()),
+            false,
+        )
+        .unwrap();
+
+    let snapshots = repo.get_balance_snapshots(Some(&id)).unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0].id, snapshot_id);
+    assert_eq!(snapshots[0].balance, Decimal::new(8000, 2));
+    assert_eq!(snapshots[0].source.as_deref(), Some("manual"));
+    assert_eq!(snapshots[0].note.as_deref(), Some("counted the wallet"));
+    assert!(snapshots[1].note.is_none());
+    assert!(repo.get_transactions_by_account(&id).unwrap().is_empty());
+
+    // Setting the same day again keeps the reason
+    balance_service
+        .set_manual_balance(&id, Decimal::new(8500, 2), day.succ_opt().unwrap())
+        .unwrap();
+    let latest = &repo.get_balance_snapshots(Some(&id)).unwrap()[0];
+    assert_eq!(latest.id, snapshot_id);
+    assert_eq!(latest.note.as_deref(), Some("counted the wallet"));
+}
+
+/// An adjusting transaction carries the signed difference from the prior balance
+#[test]
+fn test_adjust_balance_with_transaction() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Cash");
+    repo.upsert_account(&account).unwrap();
+    let id = account.id.to_string();
+    let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+    let next_day = day.succ_opt().unwrap();
+
+    balance_service
+        .set_manual_balance(&id, Decimal::new(10000, 2), day)
+        .unwrap();
+    balance_service
+        .adjust_balance(
+            &id,
+            Decimal::new(7550, 2),
+            next_day,
+            Some("cash spent".to_string()),
+            true,
+        )
+        .unwrap();
+
+    let transactions = repo.get_transactions_by_account(&id).unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].amount, Decimal::new(-2450, 2));
+    assert_eq!(transactions[0].transaction_date, next_day);
+    assert!(transactions[0].is_manual);
+    assert_eq!(
+        transactions[0].description.as_deref(),
+        Some("Balance adjustment: cash spent")
+    );
+
+    // A higher balance gives a positive adjustment; an unchanged one none
+    balance_service
+        .adjust_balance(&id, Decimal::new(12000, 2), next_day, None, true)
+        .unwrap();
+    balance_service
+        .adjust_balance(&id, Decimal::new(12000, 2), next_day, None, true)
+        .unwrap();
+    let amounts: Vec<Decimal> = repo
+        .get_transactions_by_account(&id)
+        .unwrap()
+        .iter()
+        .map(|tx| tx.amount)
+        .collect();
+    assert_eq!(amounts.len(), 2);
+    assert!(amounts.contains(&Decimal::new(4450, 2)));
+    assert_eq!(
+        repo.get_account_by_id(&id).unwrap().unwrap().balance,
+        Some(Decimal::new(12000, 2))
+    );
+}
+
+/// Creating a manual account with an opening balance shows it straight away
+#[test]
+fn test_create_manual_account_with_opening_balance() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account_service = AccountService::new(repo.clone());
+    let day = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+    let created = account_service
+        .create_manual(NewAccount {
+            name: "Wallet".to_string(),
+            currency: Some("eur".to_string()),
+            opening_balance: Some(Decimal::new(12050, 2)),
+            opening_date: Some(day),
+            ..Default::default()
+        })
+        .unwrap();
+    let snapshot = created.opening_snapshot.unwrap();
+    assert_eq!(snapshot.source.as_deref(), Some("manual"));
+    assert_eq!(snapshot.snapshot_time.date(), day);
+
+    let accounts = repo.get_accounts(false).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].id, created.account.id);
+    assert_eq!(accounts[0].name, "Wallet");
+    assert_eq!(accounts[0].currency, "EUR");
+    assert!(accounts[0].is_manual);
+    assert_eq!(accounts[0].classification.as_deref(), Some("asset"));
+    assert_eq!(accounts[0].balance, Some(Decimal::new(12050, 2)));
+
+    // Without an opening balance there is no snapshot
+    let created = account_service
+        .create_manual(NewAccount {
+            name: "House".to_string(),
+            account_type: Some("property".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(created.opening_snapshot.is_none());
+    let house = repo
+        .get_account_by_id(&created.account.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(house.balance, None);
+
+    // Liabilities owe money, so a positive opening balance is a sign mistake
+    let card = NewAccount {
+        name: "Card".to_string(),
+        account_type: Some("credit".to_string()),
+        opening_balance: Some(Decimal::new(50000, 2)),
+        ..Default::default()
+    };
+    assert!(account_service.create_manual(card.clone()).is_err());
+    let created = account_service
+        .create_manual(NewAccount {
+            opening_balance: Some(Decimal::new(-50000, 2)),
+            ..card
+        })
+        .unwrap();
+    assert_eq!(created.account.classification.as_deref(), Some("liability"));
+
+    // Bad input leaves nothing behind
+    assert!(account_service
+        .create_manual(NewAccount {
+            name: "Bad".to_string(),
+            currency: Some("dollars".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+    assert!(account_service
+        .create_manual(NewAccount {
+            name: "Undated".to_string(),
+            opening_date: Some(day),
+            ..Default::default()
+        })
+        .is_err());
+    assert_eq!(repo.get_accounts(false).unwrap().len(), 3);
+}
+
+/// Sync doesn't add a snapshot on a day the user set the balance by hand
+#[test]
+fn test_sync_keeps_manual_balance() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let balance_service = BalanceService::new(repo.clone());
+    sync_service.setup_demo().unwrap();
+    sync_service.sync(None, false, false).unwrap();
+
+    let accounts = repo.get_accounts(false).unwrap();
+    let manual_id = accounts[0].id.to_string();
+    let synced_day = repo.get_balance_snapshots(Some(&manual_id)).unwrap()[0]
+        .snapshot_time
+        .date();
+    balance_service
+        .set_manual_balance(&manual_id, Decimal::new(123456, 2), synced_day)
+        .unwrap();
+    let on_synced_day = || {
+        repo.get_balance_snapshots(Some(&manual_id))
+            .unwrap()
+            .into_iter()
+            .filter(|s| s.snapshot_time.date() == synced_day)
+            .count()
+    };
+    let before = on_synced_day();
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert!(result.snapshots_created > 0);
+
+    assert_eq!(on_synced_day(), before);
+    let account = repo.get_account_by_id(&manual_id).unwrap().unwrap();
+    assert_eq!(account.balance, Some(Decimal::new(123456, 2)));
+}
+
+/// Add an end-of-day balance snapshot on a given date
+fn add_snapshot_on(repo: &DuckDbRepository, account_id: Uuid, cents: i64, date: NaiveDate) {
+    let snapshot = BalanceSnapshot::new(
+        account_id,
+        Decimal::new(cents, 2),
+        date.and_hms_opt(23, 59, 59).unwrap(),
+    );
+    repo.add_balance_snapshot(&snapshot).unwrap();
+}
+
+/// Snapshots that match the transaction history show zero drift
+#[test]
+fn test_reconcile_consistent_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Consistent");
+    repo.upsert_account(&account).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+    add_snapshot_on(&repo, account.id, 100000, day(1));
+    repo.upsert_transaction(&create_test_transaction(account.id, -2500, day(2)))
+        .unwrap();
+    repo.upsert_transaction(&create_test_transaction(account.id, 50000, day(3)))
+        .unwrap();
+    add_snapshot_on(&repo, account.id, 147500, day(3));
+    repo.upsert_transaction(&create_test_transaction(account.id, -7500, day(5)))
+        .unwrap();
+    add_snapshot_on(&repo, account.id, 140000, day(6));
+
+    let report = balance_service.reconcile(&account.id.to_string()).unwrap();
+
+    assert_eq!(report.anchor_date, Some("2024-01-01".to_string()));
+    assert_eq!(report.entries.len(), 2);
+    assert_eq!(report.flagged, 0);
+    for entry in &report.entries {
+        assert_eq!(entry.discrepancy, Decimal::ZERO);
+        assert!(!entry.flagged);
+    }
+}
+
+/// A transaction that hasn't posted yet only reconciles on the posted basis
+#[test]
+fn test_reconcile_by_posted_date() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Pending Purchase");
+    repo.upsert_account(&account).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+    add_snapshot_on(&repo, account.id, 100000, day(1));
+    let mut tx = create_test_transaction(account.id, -2500, day(2));
+    tx.posted_date = day(4);
+    repo.upsert_transaction(&tx).unwrap();
+    // The bank balance on day 3 doesn't include the purchase yet
+    add_snapshot_on(&repo, account.id, 100000, day(3));
+    add_snapshot_on(&repo, account.id, 97500, day(5));
+
+    let by_transaction = BalanceService::new(repo.clone())
+        .reconcile(&account.id.to_string())
+        .unwrap();
+    assert_eq!(by_transaction.entries[0].discrepancy, Decimal::new(2500, 2));
+
+    let by_posted = BalanceService::new(repo.clone())
+        .with_date_basis(DateBasis::Posted)
+        .reconcile(&account.id.to_string())
+        .unwrap();
+    assert_eq!(by_posted.flagged, 0);
+    for entry in &by_posted.entries {
+        assert_eq!(entry.discrepancy, Decimal::ZERO);
+    }
+}
+
+/// A missing transaction shows up as drift from the snapshot after it
+#[test]
+fn test_reconcile_flags_gap() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Drifting");
+    repo.upsert_account(&account).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+    add_snapshot_on(&repo, account.id, 100000, day(1));
+    repo.upsert_transaction(&create_test_transaction(account.id, -2500, day(2)))
+        .unwrap();
+    add_snapshot_on(&repo, account.id, 97500, day(3));
+    // Bank balance dropped by an extra $40 that has no transaction
+    repo.upsert_transaction(&create_test_transaction(account.id, -1000, day(4)))
+        .unwrap();
+    add_snapshot_on(&repo, account.id, 92500, day(5));
+    add_snapshot_on(&repo, account.id, 92500, day(6));
+
+    let report = balance_service.reconcile(&account.id.to_string()).unwrap();
+
+    assert_eq!(report.entries.len(), 3);
+    assert!(!report.entries[0].flagged);
+
+    // Gap first appears on the 5th and persists after
+    assert!(report.entries[1].flagged);
+    assert_eq!(report.entries[1].date, "2024-01-05");
+    assert_eq!(report.entries[1].discrepancy, Decimal::new(-4000, 2));
+    assert_eq!(report.entries[1].new_drift, Decimal::new(-4000, 2));
+    assert!(report.entries[2].flagged);
+    assert_eq!(report.entries[2].new_drift, Decimal::ZERO);
+    assert_eq!(report.flagged, 2);
+}
+
+/// Gaps between snapshots carry the previous balance forward
+#[test]
+fn test_daily_balances_fills_gaps() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Daily");
+    repo.upsert_account(&account).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    add_snapshot_on(&repo, account.id, 10000, day(1));
+    add_snapshot_on(&repo, account.id, 25000, day(4));
+
+    let series = balance_service
+        .daily_balances(&account.id.to_string(), day(1), day(5))
+        .unwrap();
+
+    let expected: Vec<(NaiveDate, Decimal)> = vec![
+        (day(1), Decimal::new(10000, 2)),
+        (day(2), Decimal::new(10000, 2)),
+        (day(3), Decimal::new(10000, 2)),
+        (day(4), Decimal::new(25000, 2)),
+        (day(5), Decimal::new(25000, 2)),
+    ];
+    assert_eq!(series, expected);
+}
+
+/// A range past the last snapshot stays flat at the last balance
+#[test]
+fn test_daily_balances_carries_forward_past_last_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Flat");
+    repo.upsert_account(&account).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    add_snapshot_on(&repo, account.id, 10000, day(1));
+    add_snapshot_on(&repo, account.id, 12000, day(2));
+
+    // Range starts after every snapshot
+    let series = balance_service
+        .daily_balances(&account.id.to_string(), day(10), day(12))
+        .unwrap();
+
+    assert_eq!(series.len(), 3);
+    assert!(series.iter().all(|(_, b)| *b == Decimal::new(12000, 2)));
+    assert_eq!(series[0].0, day(10));
+    assert_eq!(series[2].0, day(12));
+}
+
+/// Days before the first snapshot are omitted
+#[test]
+fn test_daily_balances_omits_days_before_first_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let account = create_test_account("Late Start");
+    repo.upsert_account(&account).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    add_snapshot_on(&repo, account.id, 5000, day(5));
+
+    let series = balance_service
+        .daily_balances(&account.id.to_string(), day(1), day(6))
+        .unwrap();
+
+    assert_eq!(
+        series,
+        vec![
+            (day(5), Decimal::new(5000, 2)),
+            (day(6), Decimal::new(5000, 2))
+        ]
+    );
+
+    // No snapshots at all in or before the range
+    let series = balance_service
+        .daily_balances(&account.id.to_string(), day(1), day(4))
+        .unwrap();
+    assert!(series.is_empty());
+}
+
+/// Net worth sums assets and subtracts liabilities per day
+#[test]
+fn test_net_worth_series() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let balance_service = BalanceService::new(repo.clone());
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 4, d).unwrap();
+
+    let mut checking = create_test_account("Checking");
+    checking.classification = Some("asset".to_string());
+    repo.upsert_account(&checking).unwrap();
+    add_snapshot_on(&repo, checking.id, 100000, day(1));
+    add_snapshot_on(&repo, checking.id, 80000, day(4));
+
+    // Opened later - contributes nothing before its first snapshot
+    let mut savings = create_test_account("Savings");
+    savings.classification = Some("asset".to_string());
+    repo.upsert_account(&savings).unwrap();
+    add_snapshot_on(&repo, savings.id, 500000, day(3));
+
+    let mut card = create_test_account("Credit Card");
+    card.account_type = Some("credit".to_string());
+    card.classification = Some("liability".to_string());
+    repo.upsert_account(&card).unwrap();
+    add_snapshot_on(&repo, card.id, -20000, day(2));
+
+    let series = balance_service
+        .net_worth_series(day(1), day(5), "USD")
+        .unwrap();
+
+    let expected: Vec<(NaiveDate, Decimal)> = vec![
+        (day(1), Decimal::new(100000, 2)),
+        (day(2), Decimal::new(80000, 2)),
+        (day(3), Decimal::new(580000, 2)),
+        (day(4), Decimal::new(560000, 2)),
+        (day(5), Decimal::new(560000, 2)),
+    ];
+    assert_eq!(series, expected);
+}
+
+/// Net worth converts each account into the target currency and leaves
+/// archived accounts out
+#[test]
+fn test_net_worth_series_converts_currencies() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let day = |d| NaiveDate::from_ymd_opt(2024, 4, d).unwrap();
+
+    let checking = create_test_account("Checking");
+    repo.upsert_account(&checking).unwrap();
+    add_snapshot_on(&repo, checking.id, 100000, day(1));
+
+    let mut euro = create_test_account("Girokonto");
+    euro.currency = "EUR".to_string();
+    repo.upsert_account(&euro).unwrap();
+    add_snapshot_on(&repo, euro.id, 50000, day(2));
+
+    let old = create_test_account("Old Account");
+    repo.upsert_account(&old).unwrap();
+    add_snapshot_on(&repo, old.id, 999900, day(1));
+    repo.set_account_archived(&old.id.to_string(), true)
+        .unwrap();
+
+    // No rates configured: only an all-USD total would be possible
+    let err = BalanceService::new(repo.clone())
+        .net_worth_series(day(1), day(2), "USD")
+        .unwrap_err();
+    assert!(err.to_string().contains("EUR"), "got: {}", err);
+
+    let rates = HashMap::from([
+        ("usd".to_string(), Decimal::ONE),
+        ("EUR".to_string(), Decimal::new(125, 2)),
+    ]);
+    let balance_service = BalanceService::new(repo.clone()).with_exchange_rates(rates);
+
+    let series = balance_service
+        .net_worth_series(day(1), day(2), "USD")
+        .unwrap();
+    assert_eq!(
+        series,
+        vec![
+            (day(1), Decimal::new(100000, 2)),
+            (day(2), Decimal::new(162500, 2)),
+        ]
+    );
+
+    let series = balance_service
+        .net_worth_series(day(2), day(2), "eur")
+        .unwrap();
+    assert_eq!(series, vec![(day(2), Decimal::new(130000, 2))]);
+}
+
+/// Generated demo data follows its options, and every snapshot of an account
+/// with transactions is the previous one plus that day's transactions
+#[test]
+fn test_demo_generate_reconciles() {
+    let temp_dir = TempDir::new().unwrap();
+    let demo_service = DemoService::new(temp_dir.path());
+    let options = DemoOptions {
+        accounts: 8,
+        days: 45,
+        density: 2,
+        ..DemoOptions::default()
+    };
+    let stats = demo_service.generate(options).unwrap();
+    assert_eq!(stats.accounts, 8);
+    assert!(demo_service.is_enabled().unwrap());
+
+    let repo = DuckDbRepository::new(&temp_dir.path().join("demo.duckdb"), None).unwrap();
+    let accounts = repo.get_accounts(true).unwrap();
+    assert_eq!(accounts.len(), 8);
+    assert!(accounts.iter().any(|a| a.name == "Primary Checking 2"));
+    assert!(accounts.iter().any(|a| a.name == "High-Yield Savings 2"));
+
+    let first_day = Utc::now().date_naive() - chrono::Duration::days(44);
+    let mut reconciled = 0;
+    for account in &accounts {
+        let id = account.id.to_string();
+        let mut snapshots = repo.get_balance_snapshots(Some(&id)).unwrap();
+        snapshots.sort_by_key(|s| s.snapshot_time);
+        assert_eq!(snapshots.len(), 45, "{}", account.name);
+
+        let transactions = repo.get_transactions_by_account(&id).unwrap();
+        if transactions.is_empty() {
+            continue;
+        }
+        assert!(transactions
+            .iter()
+            .all(|tx| tx.transaction_date >= first_day));
+
+        for pair in snapshots.windows(2) {
+            let day = pair[1].snapshot_time.date();
+            let day_total: Decimal = transactions
+                .iter()
+                .filter(|tx| tx.transaction_date == day)
+                .map(|tx| tx.amount)
+                .sum();
+            assert_eq!(
+                (pair[1].balance - pair[0].balance).round_dp(2),
+                day_total,
+                "{} on {}",
+                account.name,
+                day
+            );
+        }
+        assert_eq!(
+            snapshots.last().unwrap().balance.round_dp(2),
+            account.balance.unwrap().round_dp(2)
+        );
+        reconciled += 1;
+    }
+    // Both checking accounts, both savings accounts and the two cards
+    assert_eq!(reconciled, 6);
+
+    let invalid = DemoOptions {
+        days: 0,
+        ..DemoOptions::default()
+    };
+    assert!(demo_service.generate(invalid).is_err());
+}
+
+/// The same seed generates the same demo data, and a year of it has a
+/// realistic number of transactions
+#[test]
+fn test_demo_generate_seed() {
+    let today = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+    let options = |seed| DemoOptions {
+        days: 60,
+        seed,
+        ..DemoOptions::default()
+    };
+    let fingerprint = |data: &DemoData| {
+        let transactions: Vec<_> = data
+            .transactions
+            .iter()
+            .map(|tx| {
+                (
+                    tx.id,
+                    tx.account_id,
+                    tx.transaction_date,
+                    tx.amount,
+                    tx.description.clone(),
+                )
+            })
+            .collect();
+        let snapshots: Vec<_> = data
+            .balance_snapshots
+            .iter()
+            .map(|s| (s.id, s.account_id, s.snapshot_time, s.balance))
+            .collect();
+        (transactions, snapshots)
+    };
+
+    let first = generate_demo_data(&options(7), today);
+    let second = generate_demo_data(&options(7), today);
+    assert!(!first.transactions.is_empty());
+    assert_eq!(fingerprint(&first), fingerprint(&second));
+
+    let other = generate_demo_data(&options(8), today);
+    assert_ne!(fingerprint(&first), fingerprint(&other));
+
+    // Roughly 65 transactions a month across the default accounts
+    let temp_dir = TempDir::new().unwrap();
+    let stats = DemoService::new(temp_dir.path())
+        .generate(DemoOptions {
+            days: 365,
+            ..DemoOptions::default()
+        })
+        .unwrap();
+    assert!(
+        (700..=900).contains(&stats.transactions),
+        "{} transactions",
+        stats.transactions
+    );
+    assert_eq!(stats.balance_snapshots, 6 * 365);
+    assert_eq!(
+        stats.end_date - stats.start_date,
+        chrono::Duration::days(364)
+    );
+}
+
+// ============================================================================
+// Read-only Repository Tests
+// ============================================================================
+
+/// Test that several read-only handles can query while writes are rejected
+#[test]
+fn test_read_only_repository() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+
+    {
+        let repo = create_test_repo(&temp_dir);
+        let account = create_test_account("Readable");
+        repo.upsert_account(&account).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        repo.upsert_transaction(&create_test_transaction(account.id, -500, date))
+            .unwrap();
+    }
+
+    // Two readers open at the same time
+    let reader1 = DuckDbRepository::new_read_only(&db_path, None).unwrap();
+    let reader2 = DuckDbRepository::new_read_only(&db_path, None).unwrap();
+    assert!(reader1.is_read_only());
+
+    for reader in [&reader1, &reader2] {
+        assert_eq!(reader.get_accounts(false).unwrap().len(), 1);
+        assert_eq!(reader.get_transactions().unwrap().len(), 1);
+        let result = reader
+            .execute_query("SELECT COUNT(*) FROM sys_accounts")
+            .unwrap();
+        assert_eq!(result.rows.len(), 1);
+    }
+
+    // Writes error instead of modifying the database
+    assert!(reader1
+        .upsert_account(&create_test_account("Not Allowed"))
+        .is_err());
+    assert_eq!(reader2.get_accounts(false).unwrap().len(), 1);
+}
+
+/// Test that opening a missing database read-only fails instead of creating it
+#[test]
+fn test_read_only_repository_missing_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("missing.duckdb");
+
+    assert!(DuckDbRepository::new_read_only(&db_path, None).is_err());
+    assert!(!db_path.exists());
+}
+
+// ============================================================================
+// Backup Service Tests
+// ============================================================================
+
+/// Test that backup includes all data and can be listed
+#[test]
+fn test_backup_create_and_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    // Add some data
+    let account = create_test_account("Backup Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let tx = create_test_transaction(account.id, 5000, date);
+    repo.upsert_transaction(&tx).unwrap();
+
+    // Create backup service with repository (for checkpointing)
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+
+    // Create backup
+    let backup_result = backup_service.create(None);
+    assert!(backup_result.is_ok(), "Backup should succeed");
+    let backup = backup_result.unwrap();
+    assert!(backup.name.starts_with("treeline-"), "Backup name format");
+    assert!(backup.name.ends_with(".zip"), "Backup should be a zip file");
+    assert!(backup.size_bytes > 0, "Backup should have content");
+
+    // List backups
+    let backups = backup_service.list().unwrap();
+    assert_eq!(backups.len(), 1, "Should have one backup");
+    assert_eq!(backups[0].name, backup.name);
+}
+
+/// Test that restoring a corrupt backup leaves the live database untouched
+#[test]
+fn test_backup_restore_corrupt_zip_leaves_database_untouched() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+
+    {
+        let repo = create_test_repo(&temp_dir);
+        repo.upsert_account(&create_test_account("Keep Me"))
+            .unwrap();
+    }
+    let original = std::fs::read(&db_path).unwrap();
+
+    let backups_dir = temp_dir.path().join("backups");
+    std::fs::create_dir_all(&backups_dir).unwrap();
+    std::fs::write(
+        backups_dir.join("treeline-bad.zip"),
+        b"this is not a zip file",
+    )
+    .unwrap();
+
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    let result = backup_service.restore("treeline-bad.zip");
+
+    assert!(result.is_err(), "Corrupt backup should fail to restore");
+    assert_eq!(std::fs::read(&db_path).unwrap(), original);
+    // Nothing was touched, so no safety backup is needed
+    assert!(!backup_service
+        .list()
+        .unwrap()
+        .iter()
+        .any(|b| b.name.starts_with("treeline-pre-restore-")));
+
+    let repo = DuckDbRepository::new(&db_path, None).unwrap();
+    let accounts = repo.get_accounts(false).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].name, "Keep Me");
+}
+
+/// Test that a backup without a database entry is rejected
+#[test]
+fn test_backup_restore_rejects_zip_without_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+
+    {
+        let repo = create_test_repo(&temp_dir);
+        repo.upsert_account(&create_test_account("Keep Me"))
+            .unwrap();
+    }
+    let original = std::fs::read(&db_path).unwrap();
+
+    let backups_dir = temp_dir.path().join("backups");
+    std::fs::create_dir_all(&backups_dir).unwrap();
+    {
+        use std::io::Write;
+        let file = std::fs::File::create(backups_dir.join("treeline-empty.zip")).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        zip.start_file("settings.json", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.finish().unwrap();
+    }
+
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    let err = backup_service.restore("treeline-empty.zip").unwrap_err();
+
+    assert!(format!("{:#}", err).contains("does not contain a database file"));
+    assert_eq!(std::fs::read(&db_path).unwrap(), original);
+    assert!(!temp_dir.path().join("settings.json").exists());
+}
+
+/// Test that a freshly created backup verifies OK
+#[test]
+fn test_backup_verify_fresh_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    repo.upsert_account(&create_test_account("Verify Me"))
+        .unwrap();
+
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+    let backup = backup_service.create(None).unwrap();
+
+    let report = backup_service.verify(&backup.name).unwrap();
+    assert!(report.ok, "Fresh backup should verify: {}", report.message);
+    assert_eq!(report.name, backup.name);
+}
+
+/// Test that a corrupt backup is reported as failed rather than erroring
+#[test]
+fn test_backup_verify_corrupt_zip() {
+    let temp_dir = TempDir::new().unwrap();
+    let backups_dir = temp_dir.path().join("backups");
+    std::fs::create_dir_all(&backups_dir).unwrap();
+    std::fs::write(
+        backups_dir.join("treeline-bad.zip"),
+        b"this is not a zip file",
+    )
+    .unwrap();
+
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    let report = backup_service.verify("treeline-bad.zip").unwrap();
+
+    assert!(!report.ok);
+    assert!(report.message.contains("Archive is unreadable"));
+
+    // A missing backup is still an error
+    assert!(backup_service.verify("treeline-missing.zip").is_err());
+}
+
+/// Test that verify names a missing database and doesn't pass encrypted archives
+#[test]
+fn test_backup_verify_without_openable_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let backups_dir = temp_dir.path().join("backups");
+    std::fs::create_dir_all(&backups_dir).unwrap();
+    let write_zip = |name: &str, entries: &[(&str, &[u8])]| {
+        use std::io::Write;
+        let file = std::fs::File::create(backups_dir.join(name)).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        for (entry, contents) in entries {
+            zip.start_file(*entry, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+    };
+    write_zip("treeline-empty.zip", &[("settings.json", &b"{}"[..])]);
+    write_zip(
+        "treeline-encrypted.zip",
+        &[
+            ("test.duckdb", &b"encrypted bytes"[..]),
+            ("encryption.json", &b"{}"[..]),
+        ],
+    );
+
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    let report = backup_service.verify("treeline-empty.zip").unwrap();
+    assert!(!report.ok);
+    assert_eq!(report.message, "Archive has no database file");
+
+    let report = backup_service.verify("treeline-encrypted.zip").unwrap();
+    assert!(!report.ok);
+    assert!(report.message.starts_with("Unverified: encrypted"));
+}
+
+/// Test restoring only the auto-tag rules from a backup
+#[test]
+fn test_backup_restore_table_leaves_other_tables_alone() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Restore Table Test");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let tx = create_test_transaction(account.id, -2500, date);
+    repo.upsert_transaction(&tx).unwrap();
+    insert_tag_rule(&repo, "rule-1", "['groceries']");
+
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+    let backup = backup_service.create(None).unwrap();
+
+    // Change the rules and add a transaction after the backup was taken
+    repo.update_auto_tag_rule_tags("rule-1", &["food".to_string()])
+        .unwrap();
+    let new_tx = create_test_transaction(account.id, -900, date);
+    repo.upsert_transaction(&new_tx).unwrap();
+
+    let restored = backup_service
+        .restore_table(&backup.name, "sys_transactions_rules")
+        .unwrap();
+
+    assert_eq!(restored, 1);
+    assert_eq!(rule_tags(&repo, "rule-1"), vec!["groceries"]);
+    let transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    assert_eq!(transactions.len(), 2, "Transactions should be untouched");
+}
+
+/// Test that a JSON Lines export imports into a fresh database unchanged
+#[test]
+fn test_jsonl_export_import_round_trip() {
+    let source_dir = TempDir::new().unwrap();
+    let source = create_test_repo(&source_dir);
+
+    let account = create_test_account("Round Trip");
+    source.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx = create_test_transaction(account.id, -1234, date);
+    tx.description = Some("Joe's \"Diner\"".to_string());
+    tx.tags = vec!["food".to_string(), "eating out".to_string()];
+    source.upsert_transaction(&tx).unwrap();
+    source
+        .upsert_transaction(&create_test_transaction(account.id, 50000, date))
+        .unwrap();
+    source
+        .add_balance_snapshot(&create_balance_snapshot(account.id, Decimal::new(98765, 2)))
+        .unwrap();
+    insert_tag_rule(&source, "rule-1", "['groceries']");
+
+    let export_dir = source_dir.path().join("export");
+    BackupService::new_with_repository(
+        source_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        source.clone(),
+    )
+    .export_jsonl(&export_dir)
+    .unwrap();
+    assert!(export_dir.join("sys_transactions.jsonl").exists());
+    assert!(!export_dir.join("sys_migrations.jsonl").exists());
+
+    let target_dir = TempDir::new().unwrap();
+    let target = create_test_repo(&target_dir);
+    let import_service = BackupService::new_with_repository(
+        target_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        target.clone(),
+    );
+    let stats = import_service.import_jsonl(&export_dir).unwrap();
+    assert_eq!(stats.tables["sys_accounts"], 1);
+    assert_eq!(stats.tables["sys_transactions"], 2);
+    assert_eq!(stats.tables["sys_balance_snapshots"], 1);
+    assert!(stats.skipped.is_empty());
+
+    let counts = |repo: &DuckDbRepository| {
+        (
+            repo.get_accounts(true).unwrap().len(),
+            repo.get_transaction_count().unwrap(),
+            repo.get_balance_snapshot_count().unwrap(),
+        )
+    };
+    assert_eq!(counts(&target), counts(&source));
+    assert_eq!(rule_tags(&target, "rule-1"), vec!["groceries"]);
+
+    let imported = target
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .expect("transaction should be imported");
+    assert_eq!(imported.account_id, tx.account_id);
+    assert_eq!(imported.amount, tx.amount);
+    assert_eq!(imported.description, tx.description);
+    assert_eq!(imported.transaction_date, tx.transaction_date);
+    assert_eq!(imported.tags, tx.tags);
+
+    // Rows are upserted, so a second import changes nothing
+    import_service.import_jsonl(&export_dir).unwrap();
+    assert_eq!(counts(&target), counts(&source));
+}
+
+/// Test diffing the live database against a backup
+#[test]
+fn test_backup_diff() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Diff Test");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let txs: Vec<Transaction> = (1..=3)
+        .map(|i| create_test_transaction(account.id, -100 * i, date))
+        .collect();
+    for tx in &txs {
+        repo.upsert_transaction(tx).unwrap();
+    }
+
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+    let backup = backup_service.create(None).unwrap();
+
+    // Add two, delete one, modify one
+    for cents in [-700, -800] {
+        repo.upsert_transaction(&create_test_transaction(account.id, cents, date))
+            .unwrap();
+    }
+    let deleted_id = txs[0].id.to_string();
+    repo.use_connection(|conn| {
+        conn.execute(
+            "DELETE FROM sys_transactions WHERE transaction_id = ?",
+            duckdb::params![deleted_id],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+    let mut modified = txs[1].clone();
+    modified.description = Some("Edited".to_string());
+    modified.updated_at = Utc::now() + chrono::Duration::seconds(5);
+    repo.upsert_transaction(&modified).unwrap();
+
+    let diff = backup_service.diff(&backup.name).unwrap();
+    assert_eq!(diff.transactions.added, 2);
+    assert_eq!(diff.transactions.removed, 1);
+    assert_eq!(diff.transactions.modified, 1);
+    assert_eq!(diff.accounts, TableDiff::default());
+    assert_eq!(diff.balance_snapshots, TableDiff::default());
+}
+
+/// Test that only known tables can be restored individually
+#[test]
+fn test_backup_restore_table_rejects_unknown_table() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+    let backup = backup_service.create(None).unwrap();
+
+    assert!(backup_service
+        .restore_table(&backup.name, "sys_migrations")
+        .is_err());
+    assert!(backup_service
+        .restore_table(&backup.name, "sys_accounts; DROP TABLE sys_accounts")
+        .is_err());
+}
+
+/// Write a fake backup file with a given age and size
+fn write_fake_backup(temp_dir: &TempDir, age: chrono::Duration, size: usize) -> String {
+    let backups_dir = temp_dir.path().join("backups");
+    std::fs::create_dir_all(&backups_dir).unwrap();
+    let created = Utc::now() - age;
+    let name = format!(
+        "treeline-{}-{:06}.zip",
+        created.format("%Y-%m-%dT%H-%M-%S"),
+        created.timestamp_subsec_micros()
+    );
+    std::fs::write(backups_dir.join(&name), vec![0u8; size]).unwrap();
+    name
+}
+
+/// Test prune_older_than only removes stale backups
+#[test]
+fn test_backup_prune_older_than() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+
+    let fresh = write_fake_backup(&temp_dir, chrono::Duration::hours(6), 10);
+    let edge = write_fake_backup(&temp_dir, chrono::Duration::days(29), 10);
+    write_fake_backup(&temp_dir, chrono::Duration::days(31), 10);
+    write_fake_backup(&temp_dir, chrono::Duration::days(365), 10);
+
+    let deleted = backup_service.prune_older_than(30).unwrap();
+    assert_eq!(deleted, 2);
+
+    let mut remaining: Vec<String> = backup_service
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    remaining.sort();
+    let mut expected = vec![fresh, edge];
+    expected.sort();
+    assert_eq!(remaining, expected);
+}
+
+/// Test that sync creates a backup first when auto_backup_on_sync is enabled
+#[test]
+fn test_sync_auto_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    sync_service.setup_demo().unwrap();
+
+    // Disabled by default
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert!(result.backup.is_none());
+    assert!(backup_service.list().unwrap().is_empty());
+
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.auto_backup_on_sync = true;
+    config.save(temp_dir.path()).unwrap();
+
+    // Dry runs change nothing, so no backup
+    let result = sync_service.sync(None, true, false).unwrap();
+    assert!(result.backup.is_none());
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    let backup = result.backup.expect("Sync should create a backup");
+    let backups = backup_service.list().unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].name, backup);
+}
+
+/// Test that sync POSTs a signed summary to the configured webhook, and
+/// that an unreachable webhook doesn't fail the sync
+#[test]
+fn test_sync_webhook() {
+    use treeline_core::services::webhook::{sign_payload, SIGNATURE_HEADER};
+    use treeline_core::test_support::{http_response, mock_http_server, request_header};
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    sync_service.setup_demo().unwrap();
+
+    let (base_url, server) = mock_http_server(vec![http_response("200 OK", "", "")]);
+    let url = format!("{}/hook", base_url);
+
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.sync_webhook_url = Some(url);
+    config.sync_webhook_secret = Some("hook-secret".to_string());
+    config.save(temp_dir.path()).unwrap();
+
+    // Dry runs don't notify
+    sync_service.sync(None, true, false).unwrap();
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert!(result.webhook_error.is_none());
+
+    let request = server.join().unwrap().remove(0);
+    let (_, body) = request.split_once("\r\n\r\n").unwrap();
+    let signature = request_header(&request, SIGNATURE_HEADER).expect("Webhook should be signed");
+    assert_eq!(signature, sign_payload("hook-secret", body.as_bytes()));
+
+    let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(payload["integrations"][0]["integration"], "demo");
+    assert_eq!(payload["new_transactions"], result.transactions_inserted);
+
+    // Nothing listens here any more; the sync still succeeds
+    let unreachable = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.sync_webhook_url = Some(format!("http://{}/hook", unreachable.local_addr().unwrap()));
+    config.save(temp_dir.path()).unwrap();
+    drop(unreachable);
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert!(result.webhook_error.is_some());
+}
+
+/// Test that the sync result totals what was written to the database
+#[test]
+fn test_sync_result_counts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    sync_service.setup_demo().unwrap();
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    let accounts = repo.get_accounts(true).unwrap().len() as i64;
+    assert!(accounts > 0);
+    assert_eq!(result.accounts_created, accounts);
+    assert_eq!(result.accounts_updated, 0);
+    assert_eq!(
+        result.transactions_inserted,
+        repo.get_transaction_count().unwrap()
+    );
+    assert!(result.transactions_inserted > 0);
+    assert_eq!(result.transactions_skipped, 0);
+    assert_eq!(
+        result.snapshots_created,
+        repo.get_balance_snapshot_count().unwrap()
+    );
+    assert!(result.warnings.is_empty());
+
+    let demo = result.integration("demo").unwrap();
+    assert_eq!(demo.accounts_synced, result.accounts_created);
+    assert_eq!(demo.snapshots_created, result.snapshots_created);
+    assert!(result.integration("simplefin").is_none());
+
+    // The second sync finds the same accounts and updates them instead
+    let snapshots_before = repo.get_balance_snapshot_count().unwrap();
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert_eq!(result.accounts_created, 0);
+    assert_eq!(result.accounts_updated, accounts);
+    assert_eq!(
+        result.snapshots_created,
+        repo.get_balance_snapshot_count().unwrap() - snapshots_before
+    );
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["accounts_updated"], accounts);
+    assert_eq!(json["results"][0]["integration"], "demo");
+}
+
+/// Test that each sync records a watermark used by the next one
+#[test]
+fn test_sync_watermark_advances() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    sync_service.setup_demo().unwrap();
+    assert!(sync_service.last_sync_time("demo").unwrap().is_none());
+
+    // Dry runs don't record anything
+    sync_service.sync(None, true, false).unwrap();
+    assert!(sync_service.last_sync_time("demo").unwrap().is_none());
+
+    let before = Utc::now();
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert_eq!(result.results[0].sync_type, "initial");
+    let first = sync_service
+        .last_sync_time("demo")
+        .unwrap()
+        .expect("watermark should be recorded");
+    assert!(first >= before - chrono::Duration::seconds(1));
+
+    let settings = repo
+        .get_integrations()
+        .unwrap()
+        .into_iter()
+        .find(|i| i.name == "demo")
+        .unwrap()
+        .settings;
+    let tx_watermark = settings["lastSyncedTxDate"].as_str().unwrap().to_string();
+    assert_eq!(
+        Some(tx_watermark.clone()),
+        repo.get_max_transaction_date()
+            .unwrap()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+    );
+
+    // The next run starts a week before the stored watermark
+    let mut settings = settings;
+    settings["lastSyncedTxDate"] = serde_json::json!("2024-03-15");
+    repo.upsert_integration("demo", &settings).unwrap();
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert_eq!(result.results[0].sync_type, "incremental");
+    assert_eq!(result.results[0].start_date, "2024-03-08");
+    let second = sync_service.last_sync_time("demo").unwrap().unwrap();
+    assert!(second >= first);
+}
+
+/// Test that a manual transaction is tagged by rules and survives later syncs
+#[test]
+fn test_manual_transaction_survives_sync() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let transaction_service = TransactionService::new(repo.clone());
+    sync_service.setup_demo().unwrap();
+    sync_service.sync(None, false, false).unwrap();
+    insert_tag_rule(&repo, "spending", "['spending']");
+
+    let account = repo.get_accounts(false).unwrap().remove(0);
+    let date = Utc::now().date_naive();
+    let tx = transaction_service
+        .create_manual(
+            &account.id.to_string(),
+            Decimal::new(-1250, 2),
+            date,
+            Some("Farmers market (cash)".to_string()),
+            vec!["cash".to_string()],
+        )
+        .unwrap();
+    assert!(tx.is_manual);
+    assert_eq!(tx.tags, vec!["cash", "spending"]);
+    let listed = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    assert!(listed.iter().any(|t| t.id == tx.id && t.is_manual));
+
+    let snapshot = transaction_service
+        .snapshot_balance_after(&tx)
+        .unwrap()
+        .expect("demo accounts have a balance");
+    assert_eq!(snapshot.balance, account.balance.unwrap() + tx.amount);
+
+    sync_service.sync(None, false, false).unwrap();
+
+    let stored = repo
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .expect("manual transaction should survive sync");
+    assert!(stored.is_manual);
+    assert_eq!(stored.description.as_deref(), Some("Farmers market (cash)"));
+    assert_eq!(stored.amount, Decimal::new(-1250, 2));
+}
+
+/// Test that manual transactions require an existing account
+#[test]
+fn test_manual_transaction_unknown_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let transaction_service = TransactionService::new(repo.clone());
+
+    let result = transaction_service.create_manual(
+        &Uuid::new_v4().to_string(),
+        Decimal::new(-500, 2),
+        NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        None,
+        vec![],
+    );
+    assert!(result.is_err());
+    assert_eq!(repo.get_transaction_count().unwrap(), 0);
+}
+
+/// Bulk edit changes only the patched field on every listed transaction
+#[test]
+fn test_bulk_update_description_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let transaction_service = TransactionService::new(repo.clone());
+
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let mut originals = Vec::new();
+    for (i, amount) in [-1250, -4000, 2500].into_iter().enumerate() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1 + i as u32).unwrap();
+        let mut tx = create_test_transaction(account.id, amount, date);
+        tx.description = Some(format!("Original {}", i));
+        repo.upsert_transaction(&tx).unwrap();
+        originals.push(
+            repo.get_transaction_by_id(&tx.id.to_string())
+                .unwrap()
+                .unwrap(),
+        );
+    }
+    let untouched = create_test_transaction(
+        account.id,
+        -999,
+        NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+    );
+    repo.upsert_transaction(&untouched).unwrap();
+
+    let ids: Vec<String> = originals.iter().map(|tx| tx.id.to_string()).collect();
+    let patch = TransactionPatch {
+        description: Some("Groceries".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(transaction_service.bulk_update(&ids, patch).unwrap(), 3);
+
+    for original in &originals {
+        let tx = repo
+            .get_transaction_by_id(&original.id.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(tx.description.as_deref(), Some("Groceries"));
+        assert_eq!(tx.amount, original.amount);
+        assert_eq!(tx.transaction_date, original.transaction_date);
+        assert!(tx.updated_at >= original.updated_at);
+    }
+
+    let other = repo
+        .get_transaction_by_id(&untouched.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(other.description, None);
+}
+
+/// An empty patch or an unknown ID is rejected without changing anything
+#[test]
+fn test_bulk_update_rejects_bad_input() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let transaction_service = TransactionService::new(repo.clone());
+
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+    let tx = create_test_transaction(
+        account.id,
+        -1250,
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+    );
+    repo.upsert_transaction(&tx).unwrap();
+
+    let ids = vec![tx.id.to_string()];
+    assert!(transaction_service
+        .bulk_update(&ids, TransactionPatch::default())
+        .is_err());
+
+    let ids = vec![tx.id.to_string(), Uuid::new_v4().to_string()];
+    let patch = TransactionPatch {
+        amount: Some(Decimal::new(-5000, 2)),
+        ..Default::default()
+    };
+    assert!(transaction_service.bulk_update(&ids, patch).is_err());
+
+    let stored = repo
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.amount, Decimal::new(-1250, 2));
+}
+
+#[test]
+fn test_transaction_history_records_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    repo.set_entry_point(EntryPoint::Cli);
+    let transaction_service = TransactionService::new(repo.clone());
+
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+    let tx = create_test_transaction(
+        account.id,
+        -1250,
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+    );
+    repo.upsert_transaction(&tx).unwrap();
+    let tx_id = tx.id.to_string();
+    assert!(repo.get_transaction_history(&tx_id).unwrap().is_empty());
+
+    repo.update_transaction_tags(&tx_id, &["coffee".to_string()])
+        .unwrap();
+    repo.set_transaction_notes(&tx_id, Some("with Sam".to_string()))
+        .unwrap();
+    let patch = TransactionPatch {
+        amount: Some(Decimal::new(-1500, 2)),
+        ..Default::default()
+    };
+    transaction_service
+        .bulk_update(&[tx_id.clone()], patch)
+        .unwrap();
+    // Setting the same value again is not a change
+    repo.set_transaction_notes(&tx_id, Some("with Sam".to_string()))
+        .unwrap();
+
+    let history = repo.get_transaction_history(&tx_id).unwrap();
+    let fields: HashSet<&str> = history.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(history.len(), 3);
+    assert_eq!(fields, HashSet::from(["tags", "notes", "amount"]));
+    for entry in &history {
+        assert_eq!(entry.transaction_id, tx_id);
+        assert_eq!(entry.entry_point.as_deref(), Some("cli"));
+    }
+    let tags = history.iter().find(|e| e.field == "tags").unwrap();
+    assert_eq!(tags.old_value.as_deref(), Some("[]"));
+    assert_eq!(tags.new_value.as_deref(), Some("[coffee]"));
+    let notes = history.iter().find(|e| e.field == "notes").unwrap();
+    assert_eq!(notes.old_value, None);
+    assert_eq!(notes.new_value.as_deref(), Some("with Sam"));
+    let amount = history.iter().find(|e| e.field == "amount").unwrap();
+    assert_eq!(
+        amount
+            .new_value
+            .as_deref()
+            .map(|v| v.parse::<Decimal>().unwrap()),
+        Some(Decimal::new(-1500, 2))
+    );
+
+    // Soft-delete and restore each leave a row, and history outlives the delete
+    repo.soft_delete_transaction(&tx_id).unwrap();
+    assert!(repo
+        .get_transaction_by_id(&tx_id)
+        .unwrap()
+        .unwrap()
+        .deleted_at
+        .is_some());
+    assert!(matches!(
+        repo.soft_delete_transaction(&tx_id),
+        Err(RepositoryError::NotFound { .. })
+    ));
+    repo.restore_transaction(&tx_id).unwrap();
+    assert!(repo
+        .get_transaction_by_id(&tx_id)
+        .unwrap()
+        .unwrap()
+        .deleted_at
+        .is_none());
+
+    let deletes: Vec<_> = repo
+        .get_transaction_history(&tx_id)
+        .unwrap()
+        .into_iter()
+        .filter(|e| e.field == "deleted_at")
+        .collect();
+    assert_eq!(deletes.len(), 2);
+    assert!(deletes
+        .iter()
+        .any(|e| e.old_value.is_none() && e.new_value.is_some()));
+    assert!(deletes
+        .iter()
+        .any(|e| e.old_value.is_some() && e.new_value.is_none()));
+}
+
+/// Minimal provider with one account and two transactions identified by sf_id.
+/// fake-1 arrives untagged, fake-2 already tagged; both carry a category.
+#[derive(Clone)]
+struct FakeProvider;
+
+impl DataAggregationProvider for FakeProvider {
+    fn name(&self) -> &str {
+        "fake"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    fn get_accounts(&self, _settings: &serde_json::Value) -> DomainResult<FetchAccountsResult> {
+        let account = create_test_account("Fake Checking");
+        let snapshot = create_balance_snapshot(account.id, Decimal::new(50000, 2));
+        Ok(FetchAccountsResult {
+            accounts: vec![account],
+            balance_snapshots: vec![snapshot],
+            warnings: Vec::new(),
+        })
+    }
+
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        _account_ids: &[String],
+        _settings: &serde_json::Value,
+    ) -> DomainResult<FetchTransactionsResult> {
+        let date = Utc::now().date_naive();
+        let transactions = [
+            ("fake-1", "Groceries", None),
+            ("fake-2", "Dining", Some("date night")),
+        ]
+        .into_iter()
+        .map(|(id, category, tag)| {
+            let mut tx = create_test_transaction(Uuid::new_v4(), -1000, date);
+            tx.sf_id = Some(id.to_string());
+            tx.sf_extra = Some(serde_json::json!({ "category": category }));
+            tx.tags = tag.into_iter().map(str::to_string).collect();
+            ("Fake Checking".to_string(), tx)
+        })
+        .collect();
+        Ok(FetchTransactionsResult {
+            transactions,
+            modified: Vec::new(),
+            removed: Vec::new(),
+            warnings: Vec::new(),
+            updated_settings: None,
+        })
+    }
+
+    fn transaction_id_column(&self) -> Option<&'static str> {
+        Some("sf_id")
+    }
+
+    fn transaction_external_id(&self, tx: &Transaction) -> Option<String> {
+        tx.sf_id.clone()
+    }
+
+    fn transaction_category(&self, tx: &Transaction) -> Option<String> {
+        tx.sf_extra.as_ref()?["category"]
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+impl IntegrationProvider for FakeProvider {
+    fn setup(&self, _options: &serde_json::Value) -> DomainResult<serde_json::Value> {
+        Ok(serde_json::json!({}))
+    }
+}
+
+/// Provider whose service is down: every fetch fails
+#[derive(Clone)]
+struct FailingProvider;
+
+impl DataAggregationProvider for FailingProvider {
+    fn name(&self) -> &str {
+        "broken"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        false
+    }
+
+    fn get_accounts(&self, _settings: &serde_json::Value) -> DomainResult<FetchAccountsResult> {
+        Err(treeline_core::domain::result::Error::Sync(
+            "service unavailable".to_string(),
+        ))
+    }
+
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        _account_ids: &[String],
+        _settings: &serde_json::Value,
+    ) -> DomainResult<FetchTransactionsResult> {
+        Err(treeline_core::domain::result::Error::Sync(
+            "service unavailable".to_string(),
+        ))
+    }
+}
+
+/// Lunchflow stand-in: two fixed accounts plus whatever transactions the
+/// test has queued, as (account lf_id, transaction lf_id, cents, pending).
+/// Clones share the queue, like a real connection seeing new activity.
+#[derive(Clone, Default)]
+struct MockLunchflowProvider {
+    transactions: Arc<Mutex<Vec<(&'static str, &'static str, i64, bool)>>>,
+}
+
+impl MockLunchflowProvider {
+    fn push(&self, account: &'static str, tx_id: &'static str, cents: i64) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .push((account, tx_id, cents, false));
+    }
+
+    fn push_pending(&self, account: &'static str, tx_id: &'static str, cents: i64) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .push((account, tx_id, cents, true));
+    }
+
+    /// Report a queued transaction as posted with its final amount
+    fn post(&self, tx_id: &str, cents: i64) {
+        for entry in self.transactions.lock().unwrap().iter_mut() {
+            if entry.1 == tx_id {
+                entry.2 = cents;
+                entry.3 = false;
+            }
+        }
+    }
+}
+
+impl DataAggregationProvider for MockLunchflowProvider {
+    fn name(&self) -> &str {
+        "lunchflow"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        true
+    }
+
+    fn get_accounts(&self, _settings: &serde_json::Value) -> DomainResult<FetchAccountsResult> {
+        // Like a real provider, every fetch hands out fresh internal IDs
+        let mut result = FetchAccountsResult::default();
+        for (lf_id, name, cents) in [
+            ("lf-acc-1", "Everyday Checking", 120000),
+            ("lf-acc-2", "Rainy Day Savings", 500000),
+        ] {
+            let mut account = create_test_account(name);
+            account.lf_id = Some(lf_id.to_string());
+            result
+                .balance_snapshots
+                .push(create_balance_snapshot(account.id, Decimal::new(cents, 2)));
+            result.accounts.push(account);
+        }
+        Ok(result)
+    }
+
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        _account_ids: &[String],
+        _settings: &serde_json::Value,
+    ) -> DomainResult<FetchTransactionsResult> {
+        let date = Utc::now().date_naive();
+        let transactions = self
+            .transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|&(account, tx_id, cents, pending)| {
+                let mut tx = create_test_transaction(Uuid::new_v4(), cents, date);
+                tx.lf_id = Some(tx_id.to_string());
+                tx.lf_is_pending = Some(pending);
+                tx.description = Some(if pending {
+                    format!("PENDING Mock {}", tx_id)
+                } else {
+                    format!("Mock {}", tx_id)
+                });
+                (account.to_string(), tx)
+            })
+            .collect();
+        Ok(FetchTransactionsResult {
+            transactions,
+            ..Default::default()
+        })
+    }
+
+    fn account_external_id(&self, account: &Account) -> Option<String> {
+        account.lf_id.clone()
+    }
+
+    fn transaction_id_column(&self) -> Option<&'static str> {
+        Some("lf_id")
+    }
+
+    fn transaction_external_id(&self, tx: &Transaction) -> Option<String> {
+        tx.lf_id.clone()
+    }
+}
+
+/// Test the whole sync-to-repository path: first sync inserts, second sync
+/// of overlapping data dedups by provider ID and keeps user edits
+#[test]
+fn test_sync_inserts_and_dedups_through_mock_provider() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let provider = MockLunchflowProvider::default();
+    provider.push("lf-acc-1", "lf-tx-1", -2500);
+    provider.push("lf-acc-1", "lf-tx-2", -4000);
+    provider.push("lf-acc-2", "lf-tx-3", 10000);
+
+    let sync_service = SyncService::new_with_provider(
+        repo.clone(),
+        temp_dir.path().to_path_buf(),
+        provider.clone(),
+    );
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    let first = &result.results[0];
+    assert_eq!(first.sync_type, "initial");
+    assert_eq!(first.accounts_synced, 2);
+    assert_eq!(first.transaction_stats.new, 3);
+    assert_eq!(first.transaction_stats.skipped, 0);
+    assert_eq!(repo.get_accounts(false).unwrap().len(), 2);
+    assert_eq!(repo.get_balance_snapshot_count().unwrap(), 2);
+    assert_eq!(repo.get_transaction_count().unwrap(), 3);
+
+    // User edits between syncs
+    let checking = repo
+        .get_accounts(false)
+        .unwrap()
+        .into_iter()
+        .find(|a| a.lf_id.as_deref() == Some("lf-acc-1"))
+        .unwrap();
+    assert_eq!(checking.balance, Some(Decimal::new(120000, 2)));
+    let mut renamed = checking.clone();
+    renamed.nickname = Some("Bills".to_string());
+    repo.upsert_account(&renamed).unwrap();
+
+    let rent = repo
+        .get_transactions()
+        .unwrap()
+        .into_iter()
+        .find(|tx| tx.lf_id.as_deref() == Some("lf-tx-1"))
+        .unwrap();
+    repo.update_transaction_tags(&rent.id.to_string(), &["rent".to_string()])
+        .unwrap();
+
+    // The provider returns everything again plus one new transaction
+    provider.push("lf-acc-2", "lf-tx-4", 2500);
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    let second = &result.results[0];
+    assert_eq!(second.sync_type, "incremental");
+    assert_eq!(second.accounts_synced, 0);
+    assert_eq!(second.transaction_stats.new, 1);
+    assert_eq!(second.transaction_stats.skipped, 3);
+
+    assert_eq!(repo.get_accounts(false).unwrap().len(), 2);
+    assert_eq!(repo.get_transaction_count().unwrap(), 4);
+    assert_eq!(repo.get_balance_snapshot_count().unwrap(), 4);
+
+    let checking = repo
+        .get_account_by_id(&checking.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(checking.nickname.as_deref(), Some("Bills"));
+    let rent = repo
+        .get_transaction_by_id(&rent.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(rent.tags, vec!["rent"]);
+}
+
+/// Test that a pending transaction is refreshed when it posts, while
+/// transactions that were already posted stay as they are
+#[test]
+fn test_sync_updates_pending_transactions() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let provider = MockLunchflowProvider::default();
+    provider.push_pending("lf-acc-1", "lf-tx-1", -2500);
+    provider.push("lf-acc-1", "lf-tx-2", -4000);
+
+    let sync_service = SyncService::new_with_provider(
+        repo.clone(),
+        temp_dir.path().to_path_buf(),
+        provider.clone(),
+    );
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+    sync_service.sync(Some("lunchflow"), false, false).unwrap();
+
+    let find = |lf_id: &str| {
+        repo.get_transactions()
+            .unwrap()
+            .into_iter()
+            .find(|tx| tx.lf_id.as_deref() == Some(lf_id))
+            .unwrap()
+    };
+    let pending = find("lf-tx-1");
+    assert_eq!(pending.lf_is_pending, Some(true));
+    repo.update_transaction_tags(&pending.id.to_string(), &["dining".to_string()])
+        .unwrap();
+
+    // The pending charge posts with a tip added; the provider also reports a
+    // different amount for the already-posted one, which must be ignored
+    provider.post("lf-tx-1", -3000);
+    provider.post("lf-tx-2", -4100);
+
+    let dry_run = sync_service.sync(Some("lunchflow"), true, false).unwrap();
+    assert_eq!(dry_run.results[0].transaction_stats.updated, 1);
+    assert_eq!(find("lf-tx-1").amount, Decimal::new(-2500, 2));
+
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    let stats = &result.results[0].transaction_stats;
+    assert_eq!(stats.new, 0);
+    assert_eq!(stats.updated, 1);
+    assert_eq!(stats.skipped, 1);
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+
+    let posted = find("lf-tx-1");
+    assert_eq!(posted.id, pending.id);
+    assert_eq!(posted.amount, Decimal::new(-3000, 2));
+    assert_eq!(posted.description.as_deref(), Some("Mock lf-tx-1"));
+    assert_eq!(posted.lf_is_pending, Some(false));
+    assert_eq!(posted.tags, vec!["dining"]);
+
+    assert_eq!(find("lf-tx-2").amount, Decimal::new(-4000, 2));
+
+    // Once posted, the transaction is no longer refreshed
+    provider.post("lf-tx-1", -3100);
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    assert_eq!(result.results[0].transaction_stats.updated, 0);
+    assert_eq!(result.results[0].transaction_stats.skipped, 2);
+    assert_eq!(find("lf-tx-1").amount, Decimal::new(-3000, 2));
+}
+
+/// Cursor-style stand-in (like Plaid): each fetch hands out, then clears, the
+/// queued added, modified and removed entries for the Lunchflow mock accounts
+#[derive(Clone, Default)]
+struct MockCursorProvider {
+    changes: Arc<Mutex<FetchTransactionsResult>>,
+}
+
+impl MockCursorProvider {
+    fn transaction(tx_id: &str, cents: i64) -> (String, Transaction) {
+        let mut tx = create_test_transaction(Uuid::new_v4(), cents, Utc::now().date_naive());
+        tx.lf_id = Some(tx_id.to_string());
+        ("lf-acc-1".to_string(), tx)
+    }
+}
+
+impl DataAggregationProvider for MockCursorProvider {
+    fn name(&self) -> &str {
+        "lunchflow"
+    }
+
+    fn can_get_accounts(&self) -> bool {
+        true
+    }
+
+    fn can_get_transactions(&self) -> bool {
+        true
+    }
+
+    fn can_get_balances(&self) -> bool {
+        false
+    }
+
+    fn get_accounts(&self, settings: &serde_json::Value) -> DomainResult<FetchAccountsResult> {
+        MockLunchflowProvider::default().get_accounts(settings)
+    }
+
+    fn get_transactions(
+        &self,
+        _start_date: NaiveDate,
+        _end_date: NaiveDate,
+        _account_ids: &[String],
+        _settings: &serde_json::Value,
+    ) -> DomainResult<FetchTransactionsResult> {
+        Ok(std::mem::take(&mut *self.changes.lock().unwrap()))
+    }
+
+    fn account_external_id(&self, account: &Account) -> Option<String> {
+        account.lf_id.clone()
+    }
+
+    fn transaction_id_column(&self) -> Option<&'static str> {
+        Some("lf_id")
+    }
+
+    fn transaction_external_id(&self, tx: &Transaction) -> Option<String> {
+        tx.lf_id.clone()
+    }
+}
+
+/// Test that provider-reported changes update stored transactions, even posted
+/// ones, and that provider deletions soft-delete them
+#[test]
+fn test_sync_applies_modified_and_removed_transactions() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let provider = MockCursorProvider::default();
+    provider.changes.lock().unwrap().transactions = vec![
+        MockCursorProvider::transaction("lf-tx-1", -2500),
+        MockCursorProvider::transaction("lf-tx-2", -4000),
+    ];
+
+    let sync_service = SyncService::new_with_provider(
+        repo.clone(),
+        temp_dir.path().to_path_buf(),
+        provider.clone(),
+    );
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+    sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+
+    let find = |lf_id: &str| {
+        repo.get_transactions()
+            .unwrap()
+            .into_iter()
+            .find(|tx| tx.lf_id.as_deref() == Some(lf_id))
+    };
+    let coffee = find("lf-tx-1").unwrap();
+    repo.update_transaction_tags(&coffee.id.to_string(), &["coffee".to_string()])
+        .unwrap();
+
+    let queue = || {
+        let mut changes = provider.changes.lock().unwrap();
+        changes.modified = vec![MockCursorProvider::transaction("lf-tx-1", -2750)];
+        changes.removed = vec![("lf-acc-1".to_string(), "lf-tx-2".to_string())];
+    };
+
+    queue();
+    let dry_run = sync_service.sync(Some("lunchflow"), true, false).unwrap();
+    assert_eq!(dry_run.results[0].transaction_stats.updated, 1);
+    assert_eq!(dry_run.results[0].transaction_stats.removed, 1);
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+
+    queue();
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    let stats = &result.results[0].transaction_stats;
+    assert_eq!(stats.new, 0);
+    assert_eq!(stats.updated, 1);
+    assert_eq!(stats.removed, 1);
+
+    let coffee_now = find("lf-tx-1").unwrap();
+    assert_eq!(coffee_now.id, coffee.id);
+    assert_eq!(coffee_now.amount, Decimal::new(-2750, 2));
+    assert_eq!(coffee_now.tags, vec!["coffee"]);
+    assert!(find("lf-tx-2").is_none());
+    assert_eq!(repo.get_transaction_count().unwrap(), 1);
+}
+
+/// Test that a dry-run plan writes nothing and matches what the real sync does
+#[test]
+fn test_sync_dry_run_plan_matches_sync() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let provider = MockLunchflowProvider::default();
+    provider.push("lf-acc-1", "lf-tx-1", -2500);
+    provider.push("lf-acc-1", "lf-tx-2", -4000);
+    provider.push("lf-acc-2", "lf-tx-3", 10000);
+
+    let sync_service = SyncService::new_with_provider(
+        repo.clone(),
+        temp_dir.path().to_path_buf(),
+        provider.clone(),
+    );
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+
+    let plan = sync_service.sync_dry_run(Some("lunchflow")).unwrap();
+    let planned = &plan.integrations[0];
+    assert_eq!(planned.sync_type, "initial");
+    assert_eq!(planned.accounts_to_create.count, 2);
+    assert_eq!(planned.accounts_to_update.count, 0);
+    assert_eq!(planned.transactions_to_insert.count, 3);
+    assert_eq!(planned.snapshots_to_add.count, 2);
+    let mut planned_ids: Vec<String> = planned
+        .transactions_to_insert
+        .samples
+        .iter()
+        .filter_map(|tx| tx.lf_id.clone())
+        .collect();
+    planned_ids.sort();
+
+    // Nothing was written, not even the sync watermark
+    assert_eq!(repo.get_accounts(true).unwrap().len(), 0);
+    assert_eq!(repo.get_transaction_count().unwrap(), 0);
+    assert_eq!(repo.get_balance_snapshot_count().unwrap(), 0);
+
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    let synced = &result.results[0];
+    assert_eq!(synced.sync_type, planned.sync_type);
+    assert_eq!(synced.accounts_synced, planned.accounts_to_create.count);
+    assert_eq!(
+        synced.transaction_stats.new,
+        planned.transactions_to_insert.count
+    );
+    assert_eq!(
+        repo.get_balance_snapshot_count().unwrap(),
+        planned.snapshots_to_add.count
+    );
+    let mut inserted_ids: Vec<String> = repo
+        .get_transactions()
+        .unwrap()
+        .into_iter()
+        .filter_map(|tx| tx.lf_id)
+        .collect();
+    inserted_ids.sort();
+    assert_eq!(inserted_ids, planned_ids);
+
+    // Once synced, only the new transaction is planned
+    provider.push("lf-acc-2", "lf-tx-4", 2500);
+    let plan = sync_service.sync_dry_run(None).unwrap();
+    let planned = &plan.integrations[0];
+    assert_eq!(planned.accounts_to_create.count, 0);
+    assert_eq!(planned.accounts_to_update.count, 2);
+    assert_eq!(planned.transactions_to_insert.count, 1);
+    assert_eq!(
+        planned.transactions_to_insert.samples[0].lf_id.as_deref(),
+        Some("lf-tx-4")
+    );
+    assert_eq!(repo.get_transaction_count().unwrap(), 3);
+}
+
+/// Test that a dry-run sync returns its plan broken down per account
+#[test]
+fn test_sync_dry_run_per_account_plan() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let provider = MockLunchflowProvider::default();
+    provider.push("lf-acc-1", "lf-tx-1", -2500);
+    provider.push("lf-acc-2", "lf-tx-2", 10000);
+
+    let sync_service = SyncService::new_with_provider(
+        repo.clone(),
+        temp_dir.path().to_path_buf(),
+        provider.clone(),
+    );
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+
+    let account_plans = |result: &treeline_core::services::SyncResult| {
+        let plan = result.plan.as_ref().expect("dry run should return a plan");
+        let mut accounts: Vec<_> = plan.integrations[0]
+            .accounts
+            .iter()
+            .map(|a| {
+                (
+                    a.account_name.clone(),
+                    a.is_new,
+                    a.transactions_to_insert,
+                    a.transactions_to_skip,
+                    a.snapshots_to_add,
+                )
+            })
+            .collect();
+        accounts.sort();
+        accounts
+    };
+
+    let dry_run = sync_service.sync(None, true, false).unwrap();
+    assert_eq!(
+        account_plans(&dry_run),
+        vec![
+            ("Everyday Checking".to_string(), true, 1, 0, 1),
+            ("Rainy Day Savings".to_string(), true, 1, 0, 1),
+        ]
+    );
+    assert_eq!(repo.get_accounts(true).unwrap().len(), 0);
+
+    let result = sync_service.sync(None, false, false).unwrap();
+    assert!(result.plan.is_none());
+
+    provider.push("lf-acc-2", "lf-tx-3", 2500);
+    let dry_run = sync_service.sync(None, true, false).unwrap();
+    assert_eq!(
+        account_plans(&dry_run),
+        vec![
+            ("Everyday Checking".to_string(), false, 0, 1, 1),
+            ("Rainy Day Savings".to_string(), false, 1, 1, 1),
+        ]
+    );
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+}
+
+/// Test a full sync through a provider registered at runtime
+#[test]
+fn test_sync_with_registered_provider() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let mut registry = ProviderRegistry::new();
+    registry.register("fake", || Box::new(FakeProvider));
+    registry.register_setup("fake", Arc::new(FakeProvider));
+    let sync_service =
+        SyncService::new(repo.clone(), temp_dir.path().to_path_buf()).with_registry(registry);
+
+    assert!(sync_service.setup_demo().is_err());
+    sync_service
+        .setup_integration("fake", &serde_json::json!({}))
+        .unwrap();
+
+    let result = sync_service.sync(Some("fake"), false, false).unwrap();
+    assert_eq!(result.results[0].accounts_synced, 1);
+    assert_eq!(result.results[0].transaction_stats.new, 2);
+
+    let accounts = repo.get_accounts(false).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].balance, Some(Decimal::new(50000, 2)));
+
+    // A second sync matches the account by name and transactions by ID
+    let result = sync_service.sync(Some("fake"), false, false).unwrap();
+    assert_eq!(result.results[0].accounts_synced, 0);
+    assert_eq!(result.results[0].transaction_stats.skipped, 2);
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+}
+
+/// A failing integration is reported and the others still sync, in sync_order
+#[test]
+fn test_sync_continues_past_failing_integration() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let mut registry = ProviderRegistry::new();
+    registry.register("fake", || Box::new(FakeProvider));
+    registry.register("broken", || Box::new(FailingProvider));
+    repo.upsert_integration("fake", &serde_json::json!({}))
+        .unwrap();
+    repo.upsert_integration("broken", &serde_json::json!({}))
+        .unwrap();
+
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.sync_order = vec!["broken".to_string(), "fake".to_string()];
+    config.save(temp_dir.path()).unwrap();
+
+    let sync_service =
+        SyncService::new(repo.clone(), temp_dir.path().to_path_buf()).with_registry(registry);
+    let result = sync_service.sync(None, false, false).unwrap();
+
+    let order: Vec<_> = result
+        .results
+        .iter()
+        .map(|r| r.integration.as_str())
+        .collect();
+    assert_eq!(order, vec!["broken", "fake"]);
+
+    let broken = result.integration("broken").unwrap();
+    assert!(broken
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("service unavailable"));
+    let fake = result.integration("fake").unwrap();
+    assert!(fake.error.is_none());
+    assert_eq!(fake.transaction_stats.new, 2);
+    assert_eq!(result.transactions_inserted, 2);
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+
+    // Naming the failing integration, or opting out, fails the sync
+    assert!(sync_service.sync(Some("broken"), false, false).is_err());
+    let strict = sync_service.with_continue_on_error(false);
+    let err = strict.sync(None, false, false).unwrap_err();
+    assert!(format!("{:#}", err).contains("broken"));
+}
+
+/// Sync FakeProvider into a fresh database, with or without category mapping
+fn sync_fake_with_category_map(category_mapping: bool) -> HashMap<String, Vec<String>> {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service =
+        SyncService::new_with_provider(repo.clone(), temp_dir.path().to_path_buf(), FakeProvider);
+    repo.upsert_integration("fake", &serde_json::json!({}))
+        .unwrap();
+
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.category_mapping = category_mapping;
+    config.save(temp_dir.path()).unwrap();
+    sync_service
+        .set_category_mapping("  GROCERIES ", "food")
+        .unwrap();
+    sync_service
+        .set_category_mapping("dining", "eating out")
+        .unwrap();
+    assert!(sync_service.set_category_mapping("travel", " ").is_err());
+    assert_eq!(
+        sync_service.category_mappings().unwrap()["groceries"],
+        "food"
+    );
+
+    sync_service.sync(Some("fake"), false, false).unwrap();
+    repo.get_transactions()
+        .unwrap()
+        .into_iter()
+        .map(|tx| (tx.sf_id.unwrap(), tx.tags))
+        .collect()
+}
+
+/// Test that provider categories become tags only when mapping is enabled,
+/// and never override tags a transaction already has
+#[test]
+fn test_sync_category_mapping() {
+    let tags = sync_fake_with_category_map(true);
+    assert_eq!(tags["fake-1"], vec!["food"]);
+    assert_eq!(tags["fake-2"], vec!["date night"]);
+
+    let tags = sync_fake_with_category_map(false);
+    assert!(tags["fake-1"].is_empty());
+    assert_eq!(tags["fake-2"], vec!["date night"]);
+}
+
+/// Test that excluded accounts are skipped by sync
+#[test]
+fn test_sync_skips_excluded_accounts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    sync_service.setup_demo().unwrap();
+
+    // Demo mode uses the account name as the provider account ID
+    sync_service
+        .set_excluded_accounts("demo", &["Primary Checking".to_string()])
+        .unwrap();
+    assert_eq!(
+        sync_service.get_excluded_accounts("demo").unwrap(),
+        vec!["Primary Checking"]
+    );
+
+    sync_service.sync(None, false, false).unwrap();
+
+    let accounts = repo.get_accounts(true).unwrap();
+    assert!(!accounts.is_empty());
+    assert!(!accounts.iter().any(|a| a.name == "Primary Checking"));
+
+    assert!(sync_service
+        .set_excluded_accounts("missing", &["x".to_string()])
+        .is_err());
+}
+
+/// Test archiving accounts that were synced before being excluded
+#[test]
+fn test_sync_archive_excluded_accounts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let sync_service = SyncService::new(repo.clone(), temp_dir.path().to_path_buf());
+    sync_service.setup_demo().unwrap();
+    sync_service.sync(None, false, false).unwrap();
+
+    let checking = repo
+        .get_accounts(false)
+        .unwrap()
+        .into_iter()
+        .find(|a| a.name == "Primary Checking")
+        .unwrap();
+    let tx_count = repo
+        .get_transactions_by_account(&checking.id.to_string())
+        .unwrap()
+        .len();
+
+    sync_service
+        .set_excluded_accounts("demo", &["Primary Checking".to_string()])
+        .unwrap();
+    let archived = sync_service.archive_excluded_accounts("demo").unwrap();
+    assert_eq!(archived, vec![checking.id.to_string()]);
+    assert!(!repo
+        .get_accounts(false)
+        .unwrap()
+        .iter()
+        .any(|a| a.id == checking.id));
+
+    // Re-syncing neither re-creates nor updates the excluded account
+    sync_service.sync(None, false, false).unwrap();
+    let matching: Vec<_> = repo
+        .get_accounts(true)
+        .unwrap()
+        .into_iter()
+        .filter(|a| a.name == "Primary Checking")
+        .collect();
+    assert_eq!(matching.len(), 1);
+    assert_eq!(
+        repo.get_transactions_by_account(&checking.id.to_string())
+            .unwrap()
+            .len(),
+        tx_count
+    );
+}
+
+/// Test pruning backups older than max_age_days
+#[test]
+fn test_backup_prune_by_age() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+
+    let recent = write_fake_backup(&temp_dir, chrono::Duration::days(1), 10);
+    let week_old = write_fake_backup(&temp_dir, chrono::Duration::days(8), 10);
+    let month_old = write_fake_backup(&temp_dir, chrono::Duration::days(40), 10);
+
+    let result = backup_service
+        .prune(RetentionPolicy {
+            max_age_days: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(result.deleted.len(), 2);
+    assert!(result.deleted.contains(&week_old));
+    assert!(result.deleted.contains(&month_old));
+
+    let remaining: Vec<String> = backup_service
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    assert_eq!(remaining, vec![recent]);
+}
+
+/// Test pruning backups by total size, keeping the newest
+#[test]
+fn test_backup_prune_by_total_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+
+    let newest = write_fake_backup(&temp_dir, chrono::Duration::hours(1), 400);
+    let middle = write_fake_backup(&temp_dir, chrono::Duration::hours(2), 400);
+    let oldest = write_fake_backup(&temp_dir, chrono::Duration::hours(3), 400);
+
+    let result = backup_service
+        .prune(RetentionPolicy {
+            max_total_bytes: Some(1000),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(result.deleted, vec![oldest]);
+    assert_eq!(result.remaining, 2);
+    assert_eq!(result.remaining_bytes, 800);
+
+    let remaining: Vec<String> = backup_service
+        .list()
+        .unwrap()
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    assert_eq!(remaining, vec![newest, middle]);
+}
+
+/// Test that all retention constraints apply together
+#[test]
+fn test_backup_prune_combined_policy() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+
+    for hours in 1..=4 {
+        write_fake_backup(&temp_dir, chrono::Duration::hours(hours), 100);
+    }
+    write_fake_backup(&temp_dir, chrono::Duration::days(30), 100);
+
+    let result = backup_service
+        .prune(RetentionPolicy {
+            max_count: Some(3),
+            max_age_days: Some(7),
+            max_total_bytes: Some(10_000),
+        })
+        .unwrap();
+
+    assert_eq!(result.deleted.len(), 2);
+    assert_eq!(backup_service.list().unwrap().len(), 3);
+}
+
+/// Test that pre-restore backups age like any other, names without a timestamp
+/// fall back to the file's modification time, and the newest backup is never pruned
+#[test]
+fn test_backup_prune_dates_every_backup_and_keeps_newest() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+
+    let recent = write_fake_backup(&temp_dir, chrono::Duration::days(1), 10);
+    let created = Utc::now() - chrono::Duration::days(40);
+    let pre_restore = format!(
+        "treeline-pre-restore-{}-{:06}.zip",
+        created.format("%Y-%m-%dT%H-%M-%S"),
+        created.timestamp_subsec_micros()
+    );
+    let backups_dir = temp_dir.path().join("backups");
+    std::fs::write(backups_dir.join(&pre_restore), vec![0u8; 10]).unwrap();
+    // Renamed by hand: dated by its mtime, which is now
+    let renamed = "treeline-before-upgrade.zip".to_string();
+    std::fs::write(backups_dir.join(&renamed), vec![0u8; 10]).unwrap();
+
+    let result = backup_service
+        .prune(RetentionPolicy {
+            max_age_days: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(result.deleted, vec![pre_restore]);
+
+    let result = backup_service
+        .prune(RetentionPolicy {
+            max_total_bytes: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(result.deleted, vec![recent]);
+    assert_eq!(result.remaining, 1);
+    assert_eq!(backup_service.list().unwrap()[0].name, renamed);
+}
+
+/// Test create_if_stale only backs up when the newest backup is old enough
+#[test]
+fn test_backup_create_if_stale() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+
+    // No backups yet - always stale
+    let created = backup_service
+        .create_if_stale(chrono::Duration::days(1))
+        .unwrap();
+    assert!(created.is_some());
+
+    // Fresh backup exists - nothing to do
+    let created = backup_service
+        .create_if_stale(chrono::Duration::days(1))
+        .unwrap();
+    assert!(created.is_none());
+    assert_eq!(backup_service.list().unwrap().len(), 1);
+
+    // Zero interval - always back up
+    let created = backup_service
+        .create_if_stale(chrono::Duration::zero())
+        .unwrap();
+    assert!(created.is_some());
+    assert_eq!(backup_service.list().unwrap().len(), 2);
+}
+
+/// Test backup retention policy
+#[test]
+fn test_backup_retention_policy() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let backup_service = BackupService::new_with_repository(
+        temp_dir.path().to_path_buf(),
+        "test.duckdb".to_string(),
+        repo.clone(),
+    );
+
+    // Create 5 backups
+    for _ in 0..5 {
+        backup_service.create(None).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10)); // Ensure unique timestamps
+    }
+
+    let backups_before = backup_service.list().unwrap();
+    assert_eq!(backups_before.len(), 5, "Should have 5 backups");
+
+    // Create one more with max_backups = 3
+    backup_service.create(Some(3)).unwrap();
+
+    let backups_after = backup_service.list().unwrap();
+    assert_eq!(
+        backups_after.len(),
+        3,
+        "Should have at most 3 backups after retention"
+    );
+}
+
+/// Test backup restore
+#[test]
+fn test_backup_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+
+    // Create initial state
+    {
+        let repo = DuckDbRepository::new(&db_path, None).unwrap();
+        repo.ensure_schema().unwrap();
+
+        let account = create_test_account("Original Account");
+        repo.upsert_account(&account).unwrap();
+    }
+
+    // Create backup (with repo for checkpoint)
+    let backup_name;
+    {
+        let repo = Arc::new(DuckDbRepository::new(&db_path, None).unwrap());
+        let backup_service = BackupService::new_with_repository(
+            temp_dir.path().to_path_buf(),
+            "test.duckdb".to_string(),
+            repo.clone(),
+        );
+        let backup = backup_service.create(None).unwrap();
+        backup_name = backup.name;
+    }
+
+    // Modify data
+    {
+        let repo = DuckDbRepository::new(&db_path, None).unwrap();
+        let account2 = create_test_account("New Account After Backup");
+        repo.upsert_account(&account2).unwrap();
+
+        let accounts = repo.get_accounts(false).unwrap();
+        assert_eq!(accounts.len(), 2, "Should have 2 accounts before restore");
+    }
+
+    // Restore from backup (without repo - restore doesn't need checkpoint)
+    {
+        let backup_service =
+            BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+        let result = backup_service.restore(&backup_name).unwrap();
+        let safety = result
+            .pre_restore_backup
+            .expect("Should create a safety backup");
+        assert!(safety.starts_with("treeline-pre-restore-"));
+        assert!(temp_dir.path().join("backups").join(&safety).exists());
+    }
+
+    // Verify restored state
+    {
+        let repo = DuckDbRepository::new(&db_path, None).unwrap();
+        let accounts = repo.get_accounts(false).unwrap();
+        assert_eq!(
+            accounts.len(),
+            1,
+            "Should have only 1 account after restore"
+        );
+        assert!(
+            accounts[0].name.contains("Original"),
+            "Should be the original account"
+        );
+    }
+}
+
+// ============================================================================
+// Encryption Service Tests
+// ============================================================================
+
+/// Test encryption details and password checks before and after encrypting
+#[test]
+fn test_encryption_metadata_and_verify_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+    {
+        let repo = create_test_repo(&temp_dir);
+        repo.upsert_account(&create_test_account("Secret")).unwrap();
+    }
+
+    let encryption_service = EncryptionService::new(temp_dir.path().to_path_buf(), db_path.clone());
+    let details = encryption_service.metadata().unwrap();
+    assert!(!details.encrypted);
+    assert!(details.argon2_params.is_none());
+    assert!(details.salt_fingerprint.is_none());
+    assert!(encryption_service.verify_password("hunter2").is_err());
+
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    encryption_service
+        .encrypt("hunter2", &backup_service)
+        .unwrap();
+
+    let details = encryption_service.metadata().unwrap();
+    assert!(details.encrypted);
+    assert_eq!(details.algorithm.as_deref(), Some("argon2id"));
+    let params = details.argon2_params.unwrap();
+    assert_eq!(params.memory_cost, 65536);
+    assert_eq!(params.time_cost, 3);
+    assert_eq!(params.parallelism, 4);
+    let fingerprint = details.salt_fingerprint.unwrap();
+    assert_eq!(fingerprint.len(), 16);
+    assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+
+    // Checking passwords leaves the database file as it was
+    let before = std::fs::read(&db_path).unwrap();
+    assert!(encryption_service.verify_password("hunter2").unwrap());
+    assert!(!encryption_service.verify_password("wrong").unwrap());
+    assert_eq!(std::fs::read(&db_path).unwrap(), before);
+    assert!(encryption_service.is_encrypted().unwrap());
+}
+
+/// Tuned params never drop below the defaults and stay within the caps
+#[test]
+fn test_tune_argon2_params_in_sane_range() {
+    let temp_dir = TempDir::new().unwrap();
+    let encryption_service = EncryptionService::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("test.duckdb"),
+    );
+
+    // A target no machine can meet keeps the defaults
+    assert_eq!(
+        encryption_service.tune_params(1).unwrap(),
+        Argon2Params::default()
+    );
+
+    let params = encryption_service.tune_params(300).unwrap();
+    assert!((65536..=524288).contains(&params.memory_cost));
+    assert!(params.memory_cost.is_power_of_two());
+    assert!((3..=10).contains(&params.time_cost));
+    assert_eq!(params.parallelism, 4);
+    assert_eq!(params.hash_len, 32);
+}
+
+/// A database keeps opening with the params it was encrypted with
+#[test]
+fn test_encrypted_database_uses_stored_argon2_params() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.duckdb");
+    {
+        let repo = create_test_repo(&temp_dir);
+        repo.upsert_account(&create_test_account("Secret")).unwrap();
+    }
+
+    let custom = Argon2Params {
+        time_cost: 4,
+        memory_cost: 131072,
+        parallelism: 2,
+        hash_len: 32,
+    };
+    let backup_service =
+        BackupService::new(temp_dir.path().to_path_buf(), "test.duckdb".to_string());
+    EncryptionService::new(temp_dir.path().to_path_buf(), db_path.clone())
+        .with_argon2_params(custom.clone())
+        .encrypt("hunter2", &backup_service)
+        .unwrap();
+
+    // A service configured with other params still derives the stored ones
+    let encryption_service = EncryptionService::new(temp_dir.path().to_path_buf(), db_path);
+    assert_eq!(
+        encryption_service.metadata().unwrap().argon2_params,
+        Some(custom)
+    );
+    assert!(encryption_service.verify_password("hunter2").unwrap());
+}
+
+// ============================================================================
+// Tag Service Tests
+// ============================================================================
+
+/// Test applying tags to transactions
+#[test]
+fn test_tag_apply_to_transactions() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    // Create account and transaction
+    let account = create_test_account("Tag Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let tx = create_test_transaction(account.id, 1000, date);
+    repo.upsert_transaction(&tx).unwrap();
+
+    // Apply tags
+    let tags = vec!["groceries".to_string(), "food".to_string()];
+    let result = tag_service
+        .apply_tags(&[tx.id.to_string()], &tags, false)
+        .unwrap();
+
+    assert_eq!(result.succeeded, 1);
+    assert_eq!(result.failed, 0);
+
+    // Verify tags were applied
+    let updated_tx = repo.get_transaction_by_id(&tx.id.to_string()).unwrap();
+    assert!(updated_tx.is_some());
+    let updated_tx = updated_tx.unwrap();
+    assert!(updated_tx.tags.contains(&"groceries".to_string()));
+    assert!(updated_tx.tags.contains(&"food".to_string()));
+}
+
+/// Test tag merging (additive)
+#[test]
+fn test_tag_merge_additive() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    // Create account and transaction with initial tags
+    let account = create_test_account("Tag Merge Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx = create_test_transaction(account.id, 1000, date);
+    tx.tags = vec!["existing".to_string()];
+    repo.upsert_transaction(&tx).unwrap();
+
+    // Apply new tags (additive, not replace)
+    let new_tags = vec!["new_tag".to_string()];
+    tag_service
+        .apply_tags(&[tx.id.to_string()], &new_tags, false)
+        .unwrap();
+
+    // Verify both old and new tags exist
+    let updated_tx = repo
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert!(
+        updated_tx.tags.contains(&"existing".to_string()),
+        "Original tag should remain"
+    );
+    assert!(
+        updated_tx.tags.contains(&"new_tag".to_string()),
+        "New tag should be added"
+    );
+}
+
+/// Test tag replacement
+#[test]
+fn test_tag_replace() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    // Create account and transaction with initial tags
+    let account = create_test_account("Tag Replace Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx = create_test_transaction(account.id, 1000, date);
+    tx.tags = vec!["old_tag".to_string()];
+    repo.upsert_transaction(&tx).unwrap();
+
+    // Replace tags
+    let new_tags = vec!["replacement".to_string()];
+    tag_service
+        .apply_tags(&[tx.id.to_string()], &new_tags, true)
+        .unwrap();
+
+    // Verify only new tags exist
+    let updated_tx = repo
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert!(
+        !updated_tx.tags.contains(&"old_tag".to_string()),
+        "Old tag should be gone"
+    );
+    assert!(
+        updated_tx.tags.contains(&"replacement".to_string()),
+        "New tag should exist"
+    );
+}
+
+/// Test applying tags to invalid transaction ID
+#[test]
+fn test_tag_invalid_transaction_id() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    // Try to tag with invalid UUID
+    let tags = vec!["test".to_string()];
+    let result = tag_service.apply_tags(&["not-a-valid-uuid".to_string()], &tags, false);
+
+    assert!(result.is_ok());
+    let result = result.unwrap();
+    assert_eq!(result.failed, 1, "Should fail for invalid UUID");
+    assert_eq!(result.succeeded, 0);
+}
+
+/// Test tagging every transaction matching a SQL condition
+#[test]
+fn test_tag_by_condition() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Fees");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut ids = Vec::new();
+    for (amount, description) in [
+        (-25000, "Overdraft FEE"),
+        (-500, "ATM fee"),
+        (-30000, "Rent"),
+    ] {
+        let mut tx = create_test_transaction(account.id, amount, date);
+        tx.description = Some(description.to_string());
+        tx.tags = vec!["existing".to_string()];
+        repo.upsert_transaction(&tx).unwrap();
+        ids.push(tx.id.to_string());
+    }
+
+    let tags = vec!["fees".to_string()];
+    let result = tag_service
+        .tag_by_condition("amount < -100 AND description ILIKE '%fee%'", &tags, false)
+        .unwrap();
+    assert_eq!(result.succeeded, 1);
+    assert_eq!(result.results[0].transaction_id, ids[0]);
+
+    let tags_of = |id: &str| repo.get_transaction_by_id(id).unwrap().unwrap().tags;
+    assert_eq!(tags_of(&ids[0]), vec!["existing", "fees"]);
+    assert_eq!(tags_of(&ids[1]), vec!["existing"]);
+    assert_eq!(tags_of(&ids[2]), vec!["existing"]);
+
+    // Replace mode swaps out the existing tags of every match
+    let result = tag_service
+        .tag_by_condition("description ILIKE '%fee%'", &tags, true)
+        .unwrap();
+    assert_eq!(result.succeeded, 2);
+    assert_eq!(tags_of(&ids[1]), vec!["fees"]);
+
+    // Nothing matching is not an error
+    let result = tag_service
+        .tag_by_condition("amount > 1000000", &tags, false)
+        .unwrap();
+    assert!(result.results.is_empty());
+}
+
+/// Previewing a condition counts its matches without tagging anything
+#[test]
+fn test_tag_condition_preview() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Shopping");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for (amount, description) in [
+        (-15000, "AMAZON MKTP"),
+        (-2000, "Amazon Prime"),
+        (-900, "Cafe"),
+    ] {
+        let mut tx = create_test_transaction(account.id, amount, date);
+        tx.description = Some(description.to_string());
+        repo.upsert_transaction(&tx).unwrap();
+    }
+
+    let preview = tag_service
+        .preview_condition("amount < -100 AND description ILIKE '%amazon%'")
+        .unwrap();
+    assert_eq!(preview.matched, 1);
+    assert_eq!(preview.total, 3);
+    assert_eq!(preview.transaction_ids.len(), 1);
+    assert!(!preview.matches_everything());
+    assert!(repo
+        .get_transaction_by_id(&preview.transaction_ids[0])
+        .unwrap()
+        .unwrap()
+        .tags
+        .is_empty());
+
+    // A condition that is always true is flagged before anything is tagged
+    let preview = tag_service.preview_condition("amount = amount").unwrap();
+    assert!(preview.matches_everything());
+    assert!(tag_service
+        .preview_condition("1=1; DROP TABLE sys_transactions")
+        .is_err());
+}
+
+/// Test that tag-by-condition refuses anything but a read-only filter
+#[test]
+fn test_tag_by_condition_rejects_statements() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Guarded");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    repo.upsert_transaction(&create_test_transaction(account.id, -100, date))
+        .unwrap();
+
+    let tags = vec!["x".to_string()];
+    for condition in [
+        "1=1); DROP TABLE sys_transactions; --",
+        "amount < 0; DROP TABLE sys_transactions",
+        "DROP TABLE sys_transactions",
+        "",
+    ] {
+        assert!(
+            tag_service
+                .tag_by_condition(condition, &tags, false)
+                .is_err(),
+            "condition should be rejected: {}",
+            condition
+        );
+    }
+    assert_eq!(repo.get_transaction_count().unwrap(), 1);
+}
+
+/// Test tag listing with usage counts and totals
+#[test]
+fn test_tag_list_counts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Tag List Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for (amount, tags) in [
+        (-1000, vec!["groceries"]),
+        (-2550, vec!["groceries", "weekly"]),
+        (-400, vec!["grocery"]),
+        (5000, vec![]),
+    ] {
+        let mut tx = create_test_transaction(account.id, amount, date);
+        tx.tags = tags.into_iter().map(String::from).collect();
+        repo.upsert_transaction(&tx).unwrap();
+    }
+
+    let stats = tag_service.list_tags().unwrap();
+
+    assert_eq!(stats.len(), 3);
+    assert_eq!(stats[0].tag, "groceries");
+    assert_eq!(stats[0].transaction_count, 2);
+    assert_eq!(stats[0].total_amount, Decimal::new(-3550, 2));
+    // Ties are sorted by name
+    assert_eq!(stats[1].tag, "grocery");
+    assert_eq!(stats[1].transaction_count, 1);
+    assert_eq!(stats[2].tag, "weekly");
+    assert_eq!(stats[2].total_amount, Decimal::new(-2550, 2));
+}
+
+/// Insert an auto-tag rule directly (there is no service for creating rules yet)
+fn insert_tag_rule(repo: &DuckDbRepository, rule_id: &str, tags: &str) {
+    repo.use_connection(|conn| {
+        conn.execute(
+            &format!(
+                "INSERT INTO sys_transactions_rules (rule_id, name, sql_condition, tags)
+                 VALUES (?, ?, 'amount < 0', {})",
+                tags
+            ),
+            duckdb::params![rule_id, rule_id],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+}
+
+fn rule_tags(repo: &DuckDbRepository, rule_id: &str) -> Vec<String> {
+    repo.get_auto_tag_rules()
+        .unwrap()
+        .into_iter()
+        .find(|r| r.rule_id == rule_id)
+        .unwrap()
+        .tags
+}
+
+/// Test renaming a tag across transactions and rules
+#[test]
+fn test_tag_rename_everywhere() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Tag Rename Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx1 = create_test_transaction(account.id, 1000, date);
+    tx1.tags = vec!["groceries".to_string(), "weekly".to_string()];
+    repo.upsert_transaction(&tx1).unwrap();
+
+    // Already has the new tag - must not end up with it twice
+    let mut tx2 = create_test_transaction(account.id, 2000, date);
+    tx2.tags = vec!["food".to_string(), "groceries".to_string()];
+    repo.upsert_transaction(&tx2).unwrap();
+
+    // Untouched
+    let mut tx3 = create_test_transaction(account.id, 3000, date);
+    tx3.tags = vec!["rent".to_string()];
+    repo.upsert_transaction(&tx3).unwrap();
+
+    insert_tag_rule(&repo, "grocery-rule", "['groceries', 'weekly']");
+
+    let changed = tag_service.rename_tag("groceries", "food").unwrap();
+    assert_eq!(changed, 2);
+
+    let get_tags = |id: Uuid| {
+        repo.get_transaction_by_id(&id.to_string())
+            .unwrap()
+            .unwrap()
+            .tags
+    };
+    assert_eq!(get_tags(tx1.id), vec!["food", "weekly"]);
+    assert_eq!(get_tags(tx2.id), vec!["food"]);
+    assert_eq!(get_tags(tx3.id), vec!["rent"]);
+    assert_eq!(rule_tags(&repo, "grocery-rule"), vec!["food", "weekly"]);
+}
+
+/// Test deleting a tag across transactions and rules
+#[test]
+fn test_tag_delete_everywhere() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Tag Delete Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx1 = create_test_transaction(account.id, 1000, date);
+    tx1.tags = vec!["groceries".to_string(), "weekly".to_string()];
+    repo.upsert_transaction(&tx1).unwrap();
+
+    let mut tx2 = create_test_transaction(account.id, 2000, date);
+    tx2.tags = vec!["rent".to_string()];
+    repo.upsert_transaction(&tx2).unwrap();
+
+    insert_tag_rule(&repo, "grocery-rule", "['groceries']");
+
+    let changed = tag_service.delete_tag("groceries").unwrap();
+    assert_eq!(changed, 1);
+
+    let tx1 = repo
+        .get_transaction_by_id(&tx1.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(tx1.tags, vec!["weekly"]);
+    assert!(rule_tags(&repo, "grocery-rule").is_empty());
+
+    // Deleting a tag nobody uses is a no-op
+    assert_eq!(tag_service.delete_tag("groceries").unwrap(), 0);
+}
+
+/// Suggestions come from transactions whose descriptions normalize the same
+#[test]
+fn test_suggest_tags_from_similar_transactions() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let tag_service = TagService::new(repo.clone());
+
+    let account = create_test_account("Suggest Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let add = |description: &str, tags: &[&str]| {
+        let mut tx = create_test_transaction(account.id, -550, date);
+        tx.description = Some(description.to_string());
+        tx.tags = tags.iter().map(|t| t.to_string()).collect();
+        repo.upsert_transaction(&tx).unwrap();
+        tx.id.to_string()
+    };
+    add("STARBUCKS STORE", &["coffee"]);
+    add("Starbucks Store", &["coffee", "treat"]);
+    add("starbucks-store", &[]);
+    add("Starbucks  Store", &["coffee"]);
+    add("Shell Gas", &["fuel"]);
+    let target = add("STARBUCKS STORE", &[]);
+    let tagged = add("Starbucks Store", &["coffee"]);
+    let unknown = add("Corner Bakery", &[]);
+
+    // Five other Starbucks transactions: four coffee, one treat
+    let suggestions = tag_service.suggest_tags(&target, 10).unwrap();
+    assert_eq!(
+        suggestions,
+        vec![("coffee".to_string(), 0.8), ("treat".to_string(), 0.2)]
+    );
+    assert_eq!(tag_service.suggest_tags(&target, 1).unwrap().len(), 1);
+
+    // Tags already on the transaction aren't suggested again
+    let suggestions = tag_service.suggest_tags(&tagged, 10).unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].0, "treat");
+
+    assert!(tag_service.suggest_tags(&unknown, 10).unwrap().is_empty());
+    assert!(tag_service
+        .suggest_tags(&Uuid::new_v4().to_string(), 10)
+        .is_err());
+
+    // Account-like numbers only compare on their last four digits
+    add("AMAZON MKTPLACE 1234567890 null", &["shopping"]);
+    add("Amazon Mktplace 555550000", &["other"]);
+    let masked = add("Amazon Mktplace xxxxxx7890", &[]);
+    assert_eq!(
+        tag_service.suggest_tags(&masked, 10).unwrap(),
+        vec![("shopping".to_string(), 1.0)]
+    );
+}
+
+// ============================================================================
+// Transfer Detection Tests
+// ============================================================================
+
+fn add_described_transaction(
+    repo: &DuckDbRepository,
+    account_id: Uuid,
+    amount: i64,
+    date: NaiveDate,
+    description: &str,
+) -> Transaction {
+    let mut tx = create_test_transaction(account_id, amount, date);
+    tx.description = Some(description.to_string());
+    repo.upsert_transaction(&tx).unwrap();
+    tx
+}
+
+/// Test that transfers pair up by amount, date window and description
+#[test]
+fn test_detect_transfers() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let checking = create_test_account("Checking");
+    let savings = create_test_account("Savings");
+    let brokerage = create_test_account("Brokerage");
+    for account in [&checking, &savings, &brokerage] {
+        repo.upsert_account(account).unwrap();
+    }
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    let out =
+        add_described_transaction(&repo, checking.id, -50000, day(1), "Online Transfer to SAV");
+    let into = add_described_transaction(&repo, savings.id, 50000, day(2), "Transfer from CHK");
+    // Same amount, unrelated descriptions
+    add_described_transaction(&repo, checking.id, -50000, day(1), "Grocery Store");
+    // Right description, outside the window
+    add_described_transaction(&repo, savings.id, 50000, day(10), "Transfer from CHK");
+    // A wire that lost a $15 fee on the way
+    let wire_out = add_described_transaction(
+        &repo,
+        checking.id,
+        -100000,
+        day(5),
+        "Wire to Brokerage 9876",
+    );
+    let wire_in = add_described_transaction(
+        &repo,
+        brokerage.id,
+        98500,
+        day(7),
+        "Wire from Checking 9876",
+    );
+
+    let pairs = TransferDetector::new(repo.clone()).detect().unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].outflow_id, out.id.to_string());
+    assert_eq!(pairs[0].inflow_id, into.id.to_string());
+    assert_eq!(pairs[0].fee, Decimal::ZERO);
+    assert_eq!(pairs[0].days_apart, 1);
+
+    let pairs = TransferDetector::new(repo.clone())
+        .with_max_fee(Decimal::new(2000, 2))
+        .detect()
+        .unwrap();
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(pairs[1].outflow_id, wire_out.id.to_string());
+    assert_eq!(pairs[1].inflow_id, wire_in.id.to_string());
+    assert_eq!(pairs[1].fee, Decimal::new(1500, 2));
+
+    // A wider window still prefers the closest match, and pairs each side once
+    let pairs = TransferDetector::new(repo)
+        .with_window_days(10)
+        .detect()
+        .unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].inflow_id, into.id.to_string());
+}
+
+/// Test linking a transfer tags both sides and hides it from detection
+#[test]
+fn test_mark_as_transfer() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let checking = create_test_account("Checking");
+    let savings = create_test_account("Savings");
+    repo.upsert_account(&checking).unwrap();
+    repo.upsert_account(&savings).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let out = add_described_transaction(&repo, checking.id, -20000, date, "Transfer to savings");
+    let into = add_described_transaction(&repo, savings.id, 20000, date, "Transfer from checking");
+    let other = add_described_transaction(&repo, checking.id, -1000, date, "Coffee");
+
+    let detector = TransferDetector::new(repo.clone());
+    assert!(detector
+        .mark_as_transfer(&out.id.to_string(), &other.id.to_string())
+        .is_err());
+    let group_id = detector
+        .mark_as_transfer(&out.id.to_string(), &into.id.to_string())
+        .unwrap();
+
+    for tx in [&out, &into] {
+        let stored = repo
+            .get_transaction_by_id(&tx.id.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.tags, vec![TRANSFER_TAG]);
+    }
+    let result = repo
+        .execute_query("SELECT DISTINCT transfer_group_id FROM transactions WHERE transfer_group_id IS NOT NULL")
+        .unwrap();
+    assert_eq!(result.rows, vec![vec![serde_json::json!(group_id)]]);
+
+    assert!(detector.detect().unwrap().is_empty());
+
+    // Linked transfers no longer count as income or spending
+    let cash_flow = StatusService::new(repo).cash_flow_as_of(1, date).unwrap();
+    assert_eq!(cash_flow[0].income, Decimal::ZERO);
+    assert_eq!(cash_flow[0].expenses, Decimal::new(1000, 2));
+}
+
+// ============================================================================
+// Config Bundle Tests
+// ============================================================================
+
+fn tag_rule(rule_id: &str, sort_order: i32) -> AutoTagRule {
+    AutoTagRule {
+        rule_id: rule_id.to_string(),
+        name: rule_id.to_string(),
+        sql_condition: "amount < 0".to_string(),
+        tags: vec![rule_id.to_string()],
+        enabled: true,
+        sort_order,
+    }
+}
+
+/// Test that a bundle moves settings, integrations and rules without secrets
+#[test]
+fn test_config_bundle_round_trip() {
+    let source_dir = TempDir::new().unwrap();
+    let source = create_test_repo(&source_dir);
+    std::fs::write(
+        source_dir.path().join("settings.json"),
+        r#"{"app": {"dateBasis": "posted", "apiKey": "settings-secret"}}"#,
+    )
+    .unwrap();
+    source
+        .upsert_integration(
+            "lunchflow",
+            &serde_json::json!({"apiKey": "lf-secret", "lastSyncedAt": "2024-03-01T00:00:00Z"}),
+        )
+        .unwrap();
+    source.upsert_auto_tag_rule(&tag_rule("coffee", 0)).unwrap();
+    source.upsert_auto_tag_rule(&tag_rule("rent", 1)).unwrap();
+
+    let bundle_path = source_dir.path().join("bundle.json");
+    let service = ConfigService::new(source.clone(), source_dir.path().to_path_buf());
+    service.export_bundle(&bundle_path).unwrap();
+
+    let bundle = std::fs::read_to_string(&bundle_path).unwrap();
+    assert!(!bundle.contains("lf-secret"));
+    assert!(!bundle.contains("settings-secret"));
+    assert!(!bundle.contains("lastSyncedAt"));
+
+    // The destination already has a rule of its own
+    let dest_dir = TempDir::new().unwrap();
+    let dest = create_test_repo(&dest_dir);
+    dest.upsert_auto_tag_rule(&tag_rule("groceries", 5))
+        .unwrap();
+    let service = ConfigService::new(dest.clone(), dest_dir.path().to_path_buf());
+
+    let result = service.import_bundle(&bundle_path, false).unwrap();
+    assert!(result.settings_updated);
+    assert_eq!(result.integrations_added, vec!["lunchflow"]);
+    assert_eq!(result.integrations_missing_secrets, vec!["lunchflow"]);
+    assert_eq!(result.rules_added, 2);
+
+    let config = Config::load(dest_dir.path()).unwrap();
+    assert_eq!(config.date_basis, DateBasis::Posted);
+    let integrations = dest.get_integrations().unwrap();
+    assert_eq!(integrations[0].settings, serde_json::json!({}));
+
+    // Imported rules keep their order but come after the existing ones
+    let rules: Vec<(String, i32)> = dest
+        .get_auto_tag_rules()
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.rule_id, r.sort_order))
+        .collect();
+    assert_eq!(
+        rules,
+        vec![
+            ("groceries".to_string(), 5),
+            ("coffee".to_string(), 6),
+            ("rent".to_string(), 7),
+        ]
+    );
+
+    // Importing again changes nothing unless asked to overwrite
+    let result = service.import_bundle(&bundle_path, false).unwrap();
+    assert!(!result.settings_updated);
+    assert_eq!(result.integrations_skipped, vec!["lunchflow"]);
+    assert_eq!(result.rules_skipped, 2);
+    assert_eq!(dest.get_auto_tag_rules().unwrap().len(), 3);
+
+    let result = service.import_bundle(&bundle_path, true).unwrap();
+    assert_eq!(result.rules_updated, 2);
+
+    // Bundles can't carry named queries that write
+    let mut tampered: serde_json::Value = serde_json::from_str(&bundle).unwrap();
+    tampered["settings"]["app"]["namedQueries"] =
+        serde_json::json!({"wipe": "DELETE FROM sys_transactions WHERE :x = :x"});
+    std::fs::write(&bundle_path, tampered.to_string()).unwrap();
+    assert!(service.import_bundle(&bundle_path, true).is_err());
+    assert!(Config::load(dest_dir.path())
+        .unwrap()
+        .named_queries
+        .is_empty());
+}
+
+/// Test that secrets are only exported on request
+#[test]
+fn test_config_bundle_with_secrets() {
+    let source_dir = TempDir::new().unwrap();
+    let source = create_test_repo(&source_dir);
+    source
+        .upsert_integration("lunchflow", &serde_json::json!({"apiKey": "lf-secret"}))
+        .unwrap();
+    let bundle_path = source_dir.path().join("bundle.json");
+    ConfigService::new(source, source_dir.path().to_path_buf())
+        .export_bundle_with_secrets(&bundle_path)
+        .unwrap();
+
+    let dest_dir = TempDir::new().unwrap();
+    let dest = create_test_repo(&dest_dir);
+    let result = ConfigService::new(dest.clone(), dest_dir.path().to_path_buf())
+        .import_bundle(&bundle_path, false)
+        .unwrap();
+    assert!(result.integrations_missing_secrets.is_empty());
+    assert_eq!(
+        dest.get_integrations().unwrap()[0].settings["apiKey"],
+        "lf-secret"
+    );
+}
+
+// ============================================================================
+// Import Service Tests
+// ============================================================================
+
+/// Test CSV import with valid data
+#[test]
+fn test_csv_import_valid_data() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    // Create account for import
+    let account = create_test_account("Import Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    // Create CSV file
+    let csv_content = r#"date,amount,description
+2024-01-15,12.34,Coffee Shop
+2024-01-16,-45.67,Restaurant
+2024-01-17,100.00,Refund"#;
+
+    let csv_path = temp_dir.path().join("test_import.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    // Import using the import service
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: None,
+        account: None,
+    };
+
+    let options = ImportOptions {
+        debit_negative: false,
+        flip_signs: false,
+        skip_rows: 0,
+        number_format: NumberFormat::default(),
+        delimiter: None,
+        encoding: None,
+        date_format: None,
+        category_separator: None,
+        create_missing_accounts: false,
+        anchor_balance: None,
+        anchor_date: None,
+    };
+
+    let result = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+
+    assert_eq!(result.imported, 3, "Should import 3 transactions");
+    assert_eq!(result.skipped, 0, "Should skip no transactions");
+
+    // Verify transactions were created
+    let transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    assert_eq!(transactions.len(), 3, "Should have 3 transactions");
+
+    // Verify amounts (check one positive and one negative)
+    let coffee_tx = transactions.iter().find(|t| {
+        t.description
+            .as_ref()
+            .map(|d| d.contains("Coffee"))
+            .unwrap_or(false)
+    });
+    assert!(coffee_tx.is_some(), "Should find Coffee transaction");
+    // The import service stores amounts, verify it's positive (12.34 from CSV)
+    assert!(
+        coffee_tx.unwrap().amount > Decimal::ZERO,
+        "Coffee transaction amount should be positive"
+    );
+}
+
+/// Test CSV import deduplication
+#[test]
+fn test_csv_import_deduplication() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Dedup Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_content = r#"date,amount,description
+2024-01-15,12.34,Coffee Shop"#;
+
+    let csv_path = temp_dir.path().join("test_dedup.csv");
+    std::fs::write(&csv_path, csv_content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: None,
+        account: None,
+    };
+
+    let options = ImportOptions {
+        debit_negative: false,
+        flip_signs: false,
+        skip_rows: 0,
+        number_format: NumberFormat::default(),
+        delimiter: None,
+        encoding: None,
+        date_format: None,
+        category_separator: None,
+        create_missing_accounts: false,
+        anchor_balance: None,
+        anchor_date: None,
+    };
+
+    // First import
+    let result1 = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+
+    assert_eq!(result1.imported, 1);
+
+    // Second import of same data should be skipped
+    let result2 = import_service
+        .import(
+            Path::new(&csv_path),
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+
+    assert_eq!(result2.imported, 0, "Should not import duplicates");
+    assert_eq!(result2.skipped, 1, "Should skip the duplicate");
+
+    // Verify only one transaction exists
+    let transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    assert_eq!(transactions.len(), 1, "Should have only 1 transaction");
+}
+
+/// Import a delimited file with an explicit delimiter and return the account's transactions
+fn import_with_delimiter(content: &str, delimiter: u8, skip_rows: u32) -> Vec<Transaction> {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Delimiter Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_path = temp_dir.path().join("test_delimiter.txt");
+    std::fs::write(&csv_path, content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "Date".to_string(),
+        amount: "Amount".to_string(),
+        description: Some("Description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: None,
+        account: None,
+    };
+    let options = ImportOptions {
+        skip_rows,
+        delimiter: Some(delimiter),
+        ..Default::default()
+    };
+
+    let result = import_service
+        .import(
+            &csv_path,
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 2);
+    assert_eq!(result.skipped, 0);
+
+    let mut transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    transactions.sort_by_key(|t| t.transaction_date);
+    transactions
+}
+
+/// Test importing tab-delimited files, with and without leading rows
+#[test]
+fn test_csv_import_tab_delimited() {
+    let content = "Date\tAmount\tDescription\n\
+                   2024-01-15\t-12.34\tCoffee, to go\n\
+                   2024-01-16\t100.00\tRefund";
+    for (content, skip_rows) in [
+        (content.to_string(), 0),
+        (format!("Account statement\n{}", content), 1),
+    ] {
+        let transactions = import_with_delimiter(&content, b'\t', skip_rows);
+        assert_eq!(transactions[0].amount, Decimal::new(-1234, 2));
+        assert_eq!(
+            transactions[0].description.as_deref(),
+            Some("Coffee, to go")
+        );
+        assert_eq!(transactions[1].amount, Decimal::new(10000, 2));
+        assert_eq!(transactions[1].description.as_deref(), Some("Refund"));
+    }
+}
+
+/// Test importing pipe-delimited files, which auto-detection never picks
+#[test]
+fn test_csv_import_pipe_delimited() {
+    let content = "Date|Amount|Description\n\
+                   2024-01-15|-12.34|Coffee; to go\n\
+                   2024-01-16|100.00|Refund";
+    for (content, skip_rows) in [
+        (content.to_string(), 0),
+        (format!("Exported 2024-01-31\n{}", content), 1),
+    ] {
+        let transactions = import_with_delimiter(&content, b'|', skip_rows);
+        assert_eq!(transactions[0].amount, Decimal::new(-1234, 2));
+        assert_eq!(
+            transactions[0].description.as_deref(),
+            Some("Coffee; to go")
+        );
+        assert_eq!(transactions[1].amount, Decimal::new(10000, 2));
+        assert_eq!(transactions[1].description.as_deref(), Some("Refund"));
+    }
+}
+
+/// Test that column detection also guesses the delimiter and number format
+#[test]
+fn test_csv_detect_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let us_path = temp_dir.path().join("us.csv");
+    std::fs::write(
+        &us_path,
+        "Date,Description,Amount\n\
+         01/15/2024,Coffee,-4.50\n\
+         01/16/2024,Rent,\"-1,250.00\"\n\
+         01/17/2024,Salary,\"3,000.00\"\n",
+    )
+    .unwrap();
+    let detected = import_service.detect_columns(&us_path).unwrap();
+    assert_eq!(detected.amount.as_deref(), Some("Amount"));
+    assert_eq!(detected.delimiter, Some(b','));
+    assert_eq!(detected.number_format, Some(NumberFormat::Us));
+
+    let eu_path = temp_dir.path().join("eu.csv");
+    std::fs::write(
+        &eu_path,
+        "Date;Description;Amount\n\
+         15.01.2024;Kaffee;-4,50\n\
+         16.01.2024;Miete;-1.250,00\n\
+         17.01.2024;Gehalt;3.000,00\n",
+    )
+    .unwrap();
+    let detected = import_service.detect_columns(&eu_path).unwrap();
+    assert_eq!(detected.all_headers.len(), 3);
+    assert_eq!(detected.delimiter, Some(b';'));
+    assert_eq!(detected.number_format, Some(NumberFormat::Eu));
+    let json = serde_json::to_value(&detected).unwrap();
+    assert_eq!(json["delimiter"], ";");
+    assert_eq!(json["number_format"], "eu");
+
+    let space_path = temp_dir.path().join("eu_space.csv");
+    std::fs::write(
+        &space_path,
+        "Date;Description;Debit;Credit\n\
+         2024-01-15;Czynsz;2 400,00;\n\
+         2024-01-16;Pensja;;8 019,40\n",
+    )
+    .unwrap();
+    let detected = import_service.detect_columns(&space_path).unwrap();
+    assert_eq!(detected.debit.as_deref(), Some("Debit"));
+    assert_eq!(detected.number_format, Some(NumberFormat::EuSpace));
+
+    // Whole amounts say nothing about the format
+    let whole_path = temp_dir.path().join("whole.csv");
+    std::fs::write(&whole_path, "Date\tAmount\n2024-01-15\t-12\n").unwrap();
+    let detected = import_service.detect_columns(&whole_path).unwrap();
+    assert_eq!(detected.delimiter, Some(b'\t'));
+    assert_eq!(detected.number_format, None);
+}
+
+/// Test turning a category column into tags alongside auto-tag rules
+#[test]
+fn test_csv_import_category_tags() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Category Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let mut rule = tag_rule("caffeine", 0);
+    rule.sql_condition = "description ILIKE '%coffee%'".to_string();
+    repo.upsert_auto_tag_rule(&rule).unwrap();
+
+    let csv_content = "date,amount,description,category
+2024-01-15,-4.50,Coffee Shop,Food & Dining/Coffee
+2024-01-16,-60.00,Grocery Store,Groceries
+2024-01-17,100.00,Refund,";
+    let csv_path = temp_dir.path().join("test_categories.csv");
     std::fs::write(&csv_path, csv_content).unwrap();
 
-    // Import using the import service
-    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: Some("category".to_string()),
+        account: None,
+    };
+    let options = ImportOptions {
+        category_separator: Some("/".to_string()),
+        ..Default::default()
+    };
+
+    let result = import_service
+        .import(
+            &csv_path,
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 3);
+
+    let tags_of = |description: &str| {
+        repo.get_transactions_by_account(&account.id.to_string())
+            .unwrap()
+            .into_iter()
+            .find(|t| t.description.as_deref() == Some(description))
+            .unwrap()
+            .tags
+    };
+    assert_eq!(
+        tags_of("Coffee Shop"),
+        vec!["food & dining", "coffee", "caffeine"]
+    );
+    assert_eq!(tags_of("Grocery Store"), vec!["groceries"]);
+    assert!(tags_of("Refund").is_empty());
+}
+
+/// Test that an explicit date format overrides the guess for ambiguous dates
+#[test]
+fn test_csv_import_date_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Date Format Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_path = temp_dir.path().join("test_dates.csv");
+    std::fs::write(&csv_path, "date,amount,description\n03/04/2024,-9.99,Books").unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: None,
+        account: None,
+    };
+    let options = ImportOptions {
+        date_format: Some("%d/%m/%Y".to_string()),
+        ..Default::default()
+    };
+
+    // Guessing reads the date as US month-first
+    let guessed = import_service
+        .import(
+            &csv_path,
+            &account.id.to_string(),
+            &mappings,
+            &ImportOptions::default(),
+            true,
+        )
+        .unwrap();
+    assert_eq!(guessed.transactions.unwrap()[0].date, "2024-03-04");
+
+    let result = import_service
+        .import(
+            &csv_path,
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 1);
+    let transactions = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap();
+    assert_eq!(
+        transactions[0].transaction_date,
+        NaiveDate::from_ymd_opt(2024, 4, 3).unwrap()
+    );
+
+    // The format is used exclusively: rows in other formats are skipped
+    let iso_path = temp_dir.path().join("test_iso_dates.csv");
+    std::fs::write(&iso_path, "date,amount,description\n2024-04-05,-1.00,Pen").unwrap();
+    let result = import_service
+        .import(
+            &iso_path,
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            true,
+        )
+        .unwrap();
+    assert_eq!(result.skipped, 1);
+
+    // Profiles keep the format
+    import_service
+        .save_profile("eu-bank", &mappings, &options)
+        .unwrap();
+    let profile = import_service.get_profile("eu-bank").unwrap().unwrap();
+    assert_eq!(profile.date_format.as_deref(), Some("%d/%m/%Y"));
+}
+
+/// Skipped rows are reported with their line number and reason
+#[test]
+fn test_csv_import_skipped_rows() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+    let account_id = account.id.to_string();
+    let mappings = ColumnMappings::default();
+    let options = ImportOptions::default();
+
+    let first_path = temp_dir.path().join("first.csv");
+    std::fs::write(
+        &first_path,
+        "Date,Amount,Description
+2024-01-15,-12.34,Coffee
+",
+    )
+    .unwrap();
+    import_service
+        .import(&first_path, &account_id, &mappings, &options, false)
+        .unwrap();
+
+    let csv_path = temp_dir.path().join("second.csv");
+    std::fs::write(
+        &csv_path,
+        "Date,Amount,Description
+         2024-01-16,-5.00,Lunch
+         not-a-date,-1.00,Typo
+         2024-01-15,-12.34,Coffee
+         2024-01-17,,Pending
+",
+    )
+    .unwrap();
+    let reasons = |result: &ImportResult| -> Vec<(usize, SkipReason)> {
+        result
+            .skipped_rows
+            .iter()
+            .map(|s| (s.row, s.reason))
+            .collect()
+    };
+
+    // Preview doesn't deduplicate, so only the unparseable rows are listed
+    let preview = import_service
+        .import(&csv_path, &account_id, &mappings, &options, true)
+        .unwrap();
+    assert_eq!(preview.skipped, 2);
+    assert_eq!(
+        reasons(&preview),
+        vec![
+            (3, SkipReason::UnparseableDate),
+            (5, SkipReason::MissingAmount)
+        ]
+    );
+
+    let result = import_service
+        .import(&csv_path, &account_id, &mappings, &options, false)
+        .unwrap();
+    assert_eq!(result.imported, 1);
+    assert_eq!(result.skipped, 3);
+    assert_eq!(
+        reasons(&result),
+        vec![
+            (3, SkipReason::UnparseableDate),
+            (4, SkipReason::DuplicateFingerprint),
+            (5, SkipReason::MissingAmount)
+        ]
+    );
+
+    // Rows skipped before the header still count towards the line number
+    let preamble_path = temp_dir.path().join("preamble.csv");
+    std::fs::write(
+        &preamble_path,
+        "Bank export\nDate,Amount,Description\n2024-01-18,-2.00,Bus\nyesterday,-2.00,Bus\n",
+    )
+    .unwrap();
+    let options = ImportOptions {
+        skip_rows: 1,
+        ..Default::default()
+    };
+    let preview = import_service
+        .import(&preamble_path, &account_id, &mappings, &options, true)
+        .unwrap();
+    assert_eq!(reasons(&preview), vec![(4, SkipReason::UnparseableDate)]);
+}
+
+/// Test importing a Windows-1252 file with accented descriptions
+#[test]
+fn test_csv_import_windows_1252() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Encoding Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    // "Café Müller" and "Crème brûlée" in Windows-1252 - invalid as UTF-8
+    let mut content = b"date;amount;description\n2024-01-15;-4,50;Caf\xe9 M\xfcller\n".to_vec();
+    content.extend_from_slice(b"2024-01-16;-7,20;Cr\xe8me br\xfbl\xe9e\n");
+    let csv_path = temp_dir.path().join("test_cp1252.csv");
+    std::fs::write(&csv_path, content).unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "date".to_string(),
+        amount: "amount".to_string(),
+        description: Some("description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: None,
+        account: None,
+    };
+    let options = ImportOptions {
+        number_format: NumberFormat::Eu,
+        delimiter: Some(b';'),
+        encoding: Some("windows-1252".to_string()),
+        ..Default::default()
+    };
+
+    let result = import_service
+        .import(
+            &csv_path,
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 2);
+
+    let mut descriptions: Vec<String> = repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap()
+        .into_iter()
+        .filter_map(|t| t.description)
+        .collect();
+    descriptions.sort();
+    assert_eq!(descriptions, vec!["Café Müller", "Crème brûlée"]);
+
+    // Unknown encodings are rejected rather than guessed
+    let options = ImportOptions {
+        encoding: Some("klingon".to_string()),
+        ..Default::default()
+    };
+    let err = import_service
+        .import(
+            &csv_path,
+            &account.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Unknown encoding"));
+}
+
+/// Test that preview warns when the column mapping looks wrong
+#[test]
+fn test_csv_import_preview_mapping_warning() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Mapping Test Account");
+    repo.upsert_account(&account).unwrap();
+
+    let csv_path = temp_dir.path().join("mapping.csv");
+    std::fs::write(
+        &csv_path,
+        "Posted Date,Amount,Memo\n2024-01-15,-50.00,Grocery\n2024-01-16,-20.00,Coffee\n",
+    )
+    .unwrap();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let preview = |date: &str, amount: &str| {
+        let mappings = ColumnMappings {
+            date: date.to_string(),
+            amount: amount.to_string(),
+            description: Some("Memo".to_string()),
+            credit: None,
+            debit: None,
+            balance: None,
+            category: None,
+            account: None,
+        };
+        import_service
+            .import(
+                &csv_path,
+                &account.id.to_string(),
+                &mappings,
+                &ImportOptions::default(),
+                true,
+            )
+            .unwrap()
+    };
+
+    // Amount pointed at the date column: every row fails to parse
+    let result = preview("Posted Date", "Posted Date");
+    assert_eq!(result.warnings.len(), 1);
+    let warning = &result.warnings[0];
+    assert!(
+        warning.contains("2 of 2 rows have no valid amount"),
+        "got: {}",
+        warning
+    );
+    assert!(warning.contains("amount='Amount'"), "got: {}", warning);
+
+    let result = preview("Posted Date", "Amount");
+    assert!(result.warnings.is_empty());
+    assert_eq!(result.transactions.unwrap().len(), 2);
+}
+
+/// Test undoing an import batch returns the database to its prior state
+#[test]
+fn test_csv_import_undo_batch() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Undo Test Account");
+    repo.upsert_account(&account).unwrap();
+    let account_id = account.id.to_string();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "Date".to_string(),
+        amount: "Amount".to_string(),
+        description: Some("Description".to_string()),
+        credit: None,
+        debit: None,
+        balance: Some("Balance".to_string()),
+        category: None,
+        account: None,
+    };
+    let options = ImportOptions::default();
+
+    let first_path = temp_dir.path().join("january.csv");
+    std::fs::write(
+        &first_path,
+        "Date,Amount,Description,Balance\n2024-01-15,-50.00,Grocery,950.00\n",
+    )
+    .unwrap();
+    let first = import_service
+        .import(&first_path, &account_id, &mappings, &options, false)
+        .unwrap();
+
+    let transactions_before = repo.get_transactions_by_account(&account_id).unwrap();
+    let snapshots_before = repo.get_balance_snapshots(Some(&account_id)).unwrap();
+
+    let second_path = temp_dir.path().join("february.csv");
+    std::fs::write(
+        &second_path,
+        "Date,Amount,Description,Balance\n2024-02-01,-20.00,Coffee,930.00\n2024-02-02,-30.00,Lunch,900.00\n",
+    )
+    .unwrap();
+    let second = import_service
+        .import(&second_path, &account_id, &mappings, &options, false)
+        .unwrap();
+    assert_eq!(second.imported, 2);
+    assert_eq!(second.balance_snapshots_created, 2);
+    assert_ne!(first.batch_id, second.batch_id);
+
+    // A snapshot on one of the batch's days that the batch didn't create stays
+    let mut other = BalanceSnapshot::new(
+        account.id,
+        Decimal::new(1000, 0),
+        NaiveDate::from_ymd_opt(2024, 2, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+    );
+    other.source = Some("csv_import".to_string());
+    repo.add_balance_snapshot(&other).unwrap();
+    let mut snapshots_before = snapshots_before;
+    snapshots_before.push(other);
+
+    let batches = import_service.list_batches().unwrap();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].batch_id, second.batch_id);
+    assert_eq!(batches[0].transaction_count, 2);
+    assert_eq!(
+        batches[0].account_name.as_deref(),
+        Some("Undo Test Account")
+    );
+
+    assert_eq!(import_service.undo_batch(&second.batch_id).unwrap(), 2);
+
+    let mut transactions_after = repo.get_transactions_by_account(&account_id).unwrap();
+    let mut transactions_before = transactions_before;
+    transactions_after.sort_by_key(|t| t.id);
+    transactions_before.sort_by_key(|t| t.id);
+    let ids = |txs: &[Transaction]| txs.iter().map(|t| t.id).collect::<Vec<_>>();
+    assert_eq!(ids(&transactions_after), ids(&transactions_before));
+    let snapshot_ids =
+        |snapshots: &[BalanceSnapshot]| snapshots.iter().map(|s| s.id).collect::<HashSet<_>>();
+    assert_eq!(
+        snapshot_ids(&repo.get_balance_snapshots(Some(&account_id)).unwrap()),
+        snapshot_ids(&snapshots_before)
+    );
+
+    let batches = import_service.list_batches().unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].batch_id, first.batch_id);
+
+    // Undoing twice is an error, and the undone file can be imported again
+    assert!(import_service.undo_batch(&second.batch_id).is_err());
+    let reimported = import_service
+        .import(&second_path, &account_id, &mappings, &options, false)
+        .unwrap();
+    assert_eq!(reimported.imported, 2);
+}
+
+/// Test importing monthly exports in one run, with overlap between files
+#[test]
+fn test_csv_import_many() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Monthly Exports");
+    repo.upsert_account(&account).unwrap();
+    let account_id = account.id.to_string();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "Date".to_string(),
+        amount: "Amount".to_string(),
+        description: Some("Description".to_string()),
+        credit: None,
+        debit: None,
+        balance: None,
+        category: None,
+        account: None,
+    };
+    let options = ImportOptions::default();
+
+    let january = temp_dir.path().join("2024-01.csv");
+    std::fs::write(
+        &january,
+        "Date,Amount,Description\n2024-01-10,-4.50,Coffee\n2024-01-10,-4.50,Coffee\n2024-01-31,-60.00,Groceries\n",
+    )
+    .unwrap();
+    // The bank repeats the last day of January at the top of February
+    let february = temp_dir.path().join("2024-02.csv");
+    std::fs::write(
+        &february,
+        "Date,Amount,Description\n2024-01-31,-60.00,Groceries\n2024-02-03,-25.00,Lunch\n",
+    )
+    .unwrap();
+
+    // A missing file is caught before anything is imported
+    let missing = temp_dir.path().join("2024-03.csv");
+    assert!(import_service
+        .import_many(
+            &[january.clone(), missing],
+            &account_id,
+            &mappings,
+            &options
+        )
+        .is_err());
+    assert!(repo
+        .get_transactions_by_account(&account_id)
+        .unwrap()
+        .is_empty());
+
+    let results = import_service
+        .import_many(
+            &[january.clone(), february.clone()],
+            &account_id,
+            &mappings,
+            &options,
+        )
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    // Repeated rows within one file are separate purchases and are kept
+    assert_eq!(results[0].imported, 3);
+    assert_eq!(results[1].imported, 1);
+    assert_eq!(results[1].skipped, 1);
+    assert_ne!(results[0].batch_id, results[1].batch_id);
+
+    let totals = ImportTotals::from_results(&results);
+    assert_eq!(totals.files, 2);
+    assert_eq!(totals.discovered, 5);
+    assert_eq!(totals.imported, 4);
+    assert_eq!(totals.skipped, 1);
+    assert_eq!(
+        repo.get_transactions_by_account(&account_id).unwrap().len(),
+        4
+    );
+
+    // Running again imports nothing new
+    let again = import_service
+        .import_many(&[january, february], &account_id, &mappings, &options)
+        .unwrap();
+    assert_eq!(ImportTotals::from_results(&again).imported, 0);
+}
+
+/// Test that an import failing part way through leaves no rows behind
+#[test]
+fn test_csv_import_rolls_back_on_failure() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Atomic Import");
+    repo.upsert_account(&account).unwrap();
+    let account_id = account.id.to_string();
+
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let mappings = ColumnMappings {
+        date: "Date".to_string(),
+        amount: "Amount".to_string(),
+        description: Some("Description".to_string()),
+        credit: None,
+        debit: None,
+        balance: Some("Balance".to_string()),
+        category: None,
+        account: None,
+    };
+
+    // The third row parses but fails validation when it is stored
+    let csv_path = temp_dir.path().join("statement.csv");
+    std::fs::write(
+        &csv_path,
+        "Date,Amount,Description,Balance\n2024-01-10,-4.50,Coffee,95.50\n2024-01-11,-10.00,Lunch,85.50\n1850-01-12,-1.00,Typo,84.50\n2024-01-13,-2.00,Snack,82.50\n",
+    )
+    .unwrap();
+    assert!(import_service
+        .import(
+            &csv_path,
+            &account_id,
+            &mappings,
+            &ImportOptions::default(),
+            false
+        )
+        .is_err());
+
+    assert!(repo
+        .get_transactions_by_account(&account_id)
+        .unwrap()
+        .is_empty());
+    assert!(repo
+        .get_balance_snapshots(Some(&account_id))
+        .unwrap()
+        .is_empty());
+
+    // The repository is usable again once the transaction is rolled back
+    std::fs::write(
+        &csv_path,
+        "Date,Amount,Description,Balance\n2024-01-10,-4.50,Coffee,95.50\n2024-01-11,-10.00,Lunch,85.50\n",
+    )
+    .unwrap();
+    let result = import_service
+        .import(
+            &csv_path,
+            &account_id,
+            &mappings,
+            &ImportOptions::default(),
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 2);
+    assert_eq!(result.balance_snapshots_created, 2);
+    assert_eq!(
+        repo.get_transactions_by_account(&account_id).unwrap().len(),
+        2
+    );
+}
+
+/// Test that with_transaction commits on success and rolls back on error
+#[test]
+fn test_repository_with_transaction() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Transactional");
+    repo.upsert_account(&account).unwrap();
+    let account_id = account.id.to_string();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+    let result: anyhow::Result<()> = repo.with_transaction(|| {
+        for i in 1..=5 {
+            if i == 4 {
+                anyhow::bail!("forced failure on row {}", i);
+            }
+            repo.upsert_transaction(&create_test_transaction(account.id, -100 * i, date))?;
+        }
+        Ok(())
+    });
+    assert!(result.is_err());
+    assert!(repo
+        .get_transactions_by_account(&account_id)
+        .unwrap()
+        .is_empty());
+
+    // A nested call joins the outer transaction
+    let written = repo
+        .with_transaction(|| {
+            repo.upsert_transaction(&create_test_transaction(account.id, -100, date))?;
+            repo.with_transaction(|| {
+                repo.upsert_transaction(&create_test_transaction(account.id, -200, date))
+            })?;
+            Ok(2)
+        })
+        .unwrap();
+    assert_eq!(written, 2);
+    assert_eq!(
+        repo.get_transactions_by_account(&account_id).unwrap().len(),
+        2
+    );
+
+    // Other threads neither see the open transaction's rows nor join it
+    let (started, wait_started) = std::sync::mpsc::channel();
+    let (resume, wait_resume) = std::sync::mpsc::channel::<()>();
+    let writer = {
+        let repo = repo.clone();
+        std::thread::spawn(move || {
+            repo.with_transaction(|| {
+                repo.upsert_transaction(&create_test_transaction(account.id, -300, date))?;
+                started.send(()).unwrap();
+                wait_resume.recv().unwrap();
+                anyhow::bail!("forced rollback")
+            })
+        })
+    };
+    wait_started.recv().unwrap();
+    assert_eq!(
+        repo.get_transactions_by_account(&account_id).unwrap().len(),
+        2
+    );
+    let other = {
+        let repo = repo.clone();
+        std::thread::spawn(move || {
+            repo.upsert_transaction(&create_test_transaction(account.id, -400, date))
+        })
+    };
+    std::thread::sleep(Duration::from_millis(50));
+    resume.send(()).unwrap();
+    assert!(writer.join().unwrap().is_err());
+    other.join().unwrap().unwrap();
+    // The other thread's write waited for the rollback instead of joining it
+    let amounts: Vec<Decimal> = repo
+        .get_transactions_by_account(&account_id)
+        .unwrap()
+        .iter()
+        .map(|t| t.amount)
+        .collect();
+    assert_eq!(amounts.len(), 3);
+    assert!(amounts.contains(&Decimal::new(-400, 2)));
+    assert!(!amounts.contains(&Decimal::new(-300, 2)));
+}
+
+/// Test importing a file that covers several accounts through an account column
+#[test]
+fn test_csv_import_account_column() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let checking = create_test_account("Checking");
+    let mut savings = create_test_account("Savings");
+    savings.nickname = Some("Rainy Day".to_string());
+    repo.upsert_account(&checking).unwrap();
+    repo.upsert_account(&savings).unwrap();
+
+    let csv_path = temp_dir.path().join("all_accounts.csv");
+    std::fs::write(
+        &csv_path,
+        "Date,Account,Amount,Description\n\
+         2024-01-15,Checking,-12.34,Coffee\n\
+         2024-01-15,savings,100.00,Transfer in\n\
+         2024-01-16,Rainy Day,0.42,Interest\n\
+         2024-01-16,Brokerage,-50.00,Fee\n\
+         2024-01-17,,-8.00,Parking\n",
+    )
+    .unwrap();
+    let mappings = ColumnMappings {
+        account: Some("Account".to_string()),
+        ..Default::default()
+    };
+
+    // Rows for an unknown account are skipped, with the reason
+    let result = import_service
+        .import(
+            &csv_path,
+            &checking.id.to_string(),
+            &mappings,
+            &ImportOptions::default(),
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 4);
+    assert_eq!(result.skipped, 1);
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].contains("'Brokerage'"));
+    assert!(result.accounts_created.is_empty());
+
+    let descriptions = |account_id: Uuid| {
+        let mut descriptions: Vec<String> = repo
+            .get_transactions_by_account(&account_id.to_string())
+            .unwrap()
+            .into_iter()
+            .filter_map(|t| t.description)
+            .collect();
+        descriptions.sort();
+        descriptions
+    };
+    // The blank account cell goes to the account the import was started for
+    assert_eq!(descriptions(checking.id), vec!["Coffee", "Parking"]);
+    assert_eq!(descriptions(savings.id), vec!["Interest", "Transfer in"]);
+
+    // Preview says which accounts would be created, without creating them
+    let options = ImportOptions {
+        create_missing_accounts: true,
+        ..Default::default()
+    };
+    let preview = import_service
+        .import(
+            &csv_path,
+            &checking.id.to_string(),
+            &mappings,
+            &options,
+            true,
+        )
+        .unwrap();
+    assert_eq!(preview.accounts_created, vec!["Brokerage"]);
+    assert_eq!(repo.get_accounts(true).unwrap().len(), 2);
+
+    // Importing again with creation only adds the missing account's row
+    let result = import_service
+        .import(
+            &csv_path,
+            &checking.id.to_string(),
+            &mappings,
+            &options,
+            false,
+        )
+        .unwrap();
+    assert_eq!(result.imported, 1);
+    assert_eq!(result.accounts_created, vec!["Brokerage"]);
+    let brokerage = repo
+        .get_accounts(true)
+        .unwrap()
+        .into_iter()
+        .find(|a| a.name == "Brokerage")
+        .unwrap();
+    assert!(brokerage.is_manual);
+    assert_eq!(descriptions(brokerage.id), vec!["Fee"]);
+
+    // A mapped account column that the file doesn't have is an error
+    let mappings = ColumnMappings {
+        account: Some("Konto".to_string()),
+        ..Default::default()
+    };
+    let err = import_service
+        .import(
+            &csv_path,
+            &checking.id.to_string(),
+            &mappings,
+            &ImportOptions::default(),
+            true,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Account column 'Konto' not found"));
+}
+
+/// Test picking the import account by ID, name, nickname or the configured default
+#[test]
+fn test_import_resolve_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let mut checking = create_test_account("Everyday Checking");
+    checking.nickname = Some("Main".to_string());
+    let joint = create_test_account("Joint");
+    let mut savings = create_test_account("Savings");
+    savings.nickname = Some("joint".to_string());
+    for account in [&checking, &joint, &savings] {
+        repo.upsert_account(account).unwrap();
+    }
+    let checking_id = checking.id.to_string();
+
+    assert_eq!(
+        import_service.resolve_account(Some(&checking_id)).unwrap(),
+        checking_id
+    );
+    assert_eq!(
+        import_service
+            .resolve_account(Some("everyday checking"))
+            .unwrap(),
+        checking_id
+    );
+    assert_eq!(
+        import_service.resolve_account(Some("MAIN")).unwrap(),
+        checking_id
+    );
+    assert!(repo.find_account_by_name("nope").unwrap().is_none());
+    assert!(import_service.resolve_account(Some("nope")).is_err());
+
+    // "Joint" is one account's name and another's nickname
+    let err = import_service
+        .resolve_account(Some("Joint"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("ambiguous"), "{}", err);
+
+    // Without an account the configured default is used
+    assert!(import_service.resolve_account(None).is_err());
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.default_import_account = Some("main".to_string());
+    config.save(temp_dir.path()).unwrap();
+    assert_eq!(import_service.resolve_account(None).unwrap(), checking_id);
+}
+
+/// Test resolving an account from a full ID, an ID prefix, a name or a partial name
+#[test]
+fn test_resolve_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let checking = create_test_account("Everyday Checking");
+    let mut business = create_test_account("Business Checking");
+    business.nickname = Some("Work".to_string());
+    let savings = create_test_account("Savings");
+    for account in [&checking, &business, &savings] {
+        repo.upsert_account(account).unwrap();
+    }
+    let resolved_id = |query: &str| match repo.resolve_account(query).unwrap() {
+        AccountResolution::Exact(account) => Some(account.id),
+        AccountResolution::Candidates(_) => None,
+    };
+
+    // Exact UUID, case-insensitive name and nickname
+    assert_eq!(resolved_id(&checking.id.to_string()), Some(checking.id));
+    assert_eq!(resolved_id("savings"), Some(savings.id));
+    assert_eq!(resolved_id("WORK"), Some(business.id));
+
+    // A unique ID prefix, but not one too short to be deliberate
+    let prefix = &savings.id.to_string()[..8];
+    assert_eq!(resolved_id(prefix), Some(savings.id));
+    assert_eq!(resolved_id(&prefix.to_uppercase()), Some(savings.id));
+
+    // A partial name matching several accounts lists them all, sorted by name
+    match repo.resolve_account("checking").unwrap() {
+        AccountResolution::Candidates(candidates) => {
+            let names: Vec<&str> = candidates.iter().map(|a| a.name.as_str()).collect();
+            assert_eq!(names, vec!["Business Checking", "Everyday Checking"]);
+        }
+        AccountResolution::Exact(account) => panic!("unexpected match: {}", account.name),
+    }
+    let err = repo
+        .resolve_account("checking")
+        .unwrap()
+        .into_account("checking")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("ambiguous"), "{}", err);
+    assert!(err.contains(&checking.id.to_string()), "{}", err);
+
+    // A single partial match is suggested rather than picked
+    let err = repo
+        .resolve_account("saving")
+        .unwrap()
+        .into_account("saving")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("did you mean Savings"), "{}", err);
+
+    let err = repo
+        .resolve_account("mortgage")
+        .unwrap()
+        .into_account("mortgage")
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "Account not found: mortgage");
+}
+
+// ============================================================================
+// Data Integrity Tests
+// ============================================================================
+
+/// Test that invalid UUIDs in queries are handled properly
+#[test]
+fn test_invalid_uuid_handling() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    // Query with invalid UUID should return None, not error
+    let result = repo.get_account_by_id("not-a-valid-uuid");
+    assert!(result.is_ok(), "Should not error on invalid UUID");
+    assert!(
+        result.unwrap().is_none(),
+        "Should return None for invalid UUID"
+    );
 
-    let mappings = ColumnMappings {
-        date: "date".to_string(),
-        amount: "amount".to_string(),
-        description: Some("description".to_string()),
-        credit: None,
-        debit: None,
-        balance: None,
+    // Query with non-existent valid UUID should also return None
+    let fake_uuid = Uuid::new_v4();
+    let result = repo.get_account_by_id(&fake_uuid.to_string());
+    assert!(result.is_ok());
+    assert!(result.unwrap().is_none());
+}
+
+/// Test that the repository refuses to store invalid transactions
+#[test]
+fn test_invalid_transaction_is_not_stored() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Validation Test");
+    repo.upsert_account(&account).unwrap();
+
+    let mut ancient =
+        create_test_transaction(account.id, 1000, NaiveDate::from_ymd_opt(1, 1, 1).unwrap());
+    let err = repo.upsert_transaction(&ancient).unwrap_err();
+    assert!(format!("{:#}", err).contains("transaction_date"));
+
+    ancient.transaction_date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    ancient.posted_date = ancient.transaction_date;
+    ancient.tags = vec!["food,dining".to_string()];
+    let err = repo.insert_transaction_if_not_exists(&ancient).unwrap_err();
+    assert!(format!("{:#}", err).contains("food,dining"));
+
+    assert!(repo
+        .get_transactions_by_account(&account.id.to_string())
+        .unwrap()
+        .is_empty());
+}
+
+/// Test that user notes survive a sync upsert that rewrites the description
+#[test]
+fn test_transaction_notes_preserved_across_upsert() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Notes Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let mut tx = create_test_transaction(account.id, -4200, date);
+    tx.description = Some("POS 1234 GROCERY".to_string());
+    repo.upsert_transaction(&tx).unwrap();
+
+    let tx_id = tx.id.to_string();
+    repo.set_transaction_notes(&tx_id, Some("split with roommate".to_string()))
+        .unwrap();
+
+    // Re-sync brings a new bank description and no notes
+    tx.description = Some("Grocery Store".to_string());
+    repo.upsert_transaction(&tx).unwrap();
+
+    let stored = repo.get_transaction_by_id(&tx_id).unwrap().unwrap();
+    assert_eq!(stored.description.as_deref(), Some("Grocery Store"));
+    assert_eq!(stored.notes.as_deref(), Some("split with roommate"));
+
+    let result = repo
+        .execute_query("SELECT notes FROM transactions")
+        .unwrap();
+    assert_eq!(result.rows[0][0], serde_json::json!("split with roommate"));
+
+    repo.set_transaction_notes(&tx_id, None).unwrap();
+    let stored = repo.get_transaction_by_id(&tx_id).unwrap().unwrap();
+    assert_eq!(stored.notes, None);
+
+    let missing = repo.set_transaction_notes(&Uuid::new_v4().to_string(), Some("x".to_string()));
+    assert!(matches!(
+        missing,
+        Err(RepositoryError::NotFound {
+            kind: "Transaction",
+            ..
+        })
+    ));
+}
+
+/// Test transaction date range query
+#[test]
+fn test_transaction_date_range() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Date Range Test");
+    repo.upsert_account(&account).unwrap();
+
+    // Create transactions on different dates
+    let dates = vec![
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+    ];
+
+    for (i, date) in dates.iter().enumerate() {
+        let tx = create_test_transaction(account.id, (i + 1) as i64 * 1000, *date);
+        repo.upsert_transaction(&tx).unwrap();
+    }
+
+    let range = repo.get_transaction_date_range().unwrap();
+    assert!(range.earliest.is_some());
+    assert!(range.latest.is_some());
+    assert!(range.earliest.unwrap().contains("2024-01-01"));
+    assert!(range.latest.unwrap().contains("2024-12-31"));
+}
+
+/// Test balance snapshot ordering
+#[test]
+fn test_balance_snapshot_ordering() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Balance Test");
+    repo.upsert_account(&account).unwrap();
+
+    // Add multiple balance snapshots with increasing time
+    for i in 1i64..=5 {
+        let base_time = chrono::DateTime::from_timestamp(1704067200 + i * 60, 0).unwrap();
+        let snapshot_time = base_time.naive_utc();
+        let snapshot = BalanceSnapshot::new(account.id, Decimal::new(i * 1000, 2), snapshot_time);
+        repo.add_balance_snapshot(&snapshot).unwrap();
+    }
+
+    // The latest balance should be visible on the account
+    let accounts = repo.get_accounts(false).unwrap();
+    let account = accounts.iter().find(|a| a.name == "Balance Test").unwrap();
+
+    // Balance should be the most recent one (50.00)
+    assert!(account.balance.is_some());
+    assert_eq!(
+        account.balance.unwrap(),
+        Decimal::new(5000, 2),
+        "Should show latest balance"
+    );
+}
+
+/// Test transaction deduplication by provider ID
+#[test]
+fn test_sync_deduplication_by_provider_id() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("Dedup Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx = create_test_transaction(account.id, 5000, date);
+    tx.sf_id = Some("unique_sf_id".to_string());
+    repo.upsert_transaction(&tx).unwrap();
+
+    // Check if transaction exists by SF ID
+    let exists = repo.transaction_exists_by_sf_id("unique_sf_id").unwrap();
+    assert!(exists, "Transaction should exist by SF ID");
+
+    let not_exists = repo.transaction_exists_by_sf_id("nonexistent").unwrap();
+    assert!(!not_exists, "Non-existent SF ID should return false");
+}
+
+/// Test Lunchflow ID deduplication
+#[test]
+fn test_sync_deduplication_by_lunchflow_id() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let account = create_test_account("LF Dedup Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx = create_test_transaction(account.id, 5000, date);
+    tx.lf_id = Some("unique_lf_id".to_string());
+    repo.upsert_transaction(&tx).unwrap();
+
+    // Check if transaction exists by LF ID
+    let exists = repo.transaction_exists_by_lf_id("unique_lf_id").unwrap();
+    assert!(exists, "Transaction should exist by LF ID");
+
+    let not_exists = repo.transaction_exists_by_lf_id("nonexistent").unwrap();
+    assert!(!not_exists, "Non-existent LF ID should return false");
+}
+
+/// Test that a freshly migrated database passes the integrity check
+#[test]
+fn test_check_integrity_healthy_db() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let problems = repo.check_integrity().unwrap();
+    assert!(problems.is_empty(), "Unexpected problems: {:?}", problems);
+}
+
+/// Test that a dropped core table (e.g. interrupted migration) is reported
+#[test]
+fn test_check_integrity_missing_table() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    repo.use_connection(|conn| {
+        conn.execute_batch("DROP TABLE sys_integrations")?;
+        Ok(())
+    })
+    .unwrap();
+
+    let problems = repo.check_integrity().unwrap();
+    assert_eq!(
+        problems,
+        vec!["Missing table: sys_integrations".to_string()]
+    );
+
+    // Doctor should surface the problem rather than fail on the missing table
+    let doctor = DoctorService::new(repo, temp_dir.path().to_path_buf());
+    let result = doctor.run_checks().unwrap();
+    assert_eq!(result.checks["database_integrity"].status, "error");
+    assert_eq!(result.summary.errors, 1);
+}
+
+/// Test finding the same account linked twice and merging the copies
+#[test]
+fn test_doctor_duplicate_accounts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let doctor = DoctorService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let account = |name: &str, institution: &str| {
+        let mut account = create_test_account(name);
+        account.institution_name = Some(institution.to_string());
+        repo.upsert_account(&account).unwrap();
+        account
+    };
+    let via_simplefin = account("Everyday Checking", "Big Bank");
+    let via_lunchflow = account("EVERYDAY  checking", "big bank");
+    let other_bank = account("Everyday Checking", "Other Bank");
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    for (account_id, d) in [
+        (via_simplefin.id, 1),
+        (via_simplefin.id, 2),
+        (via_lunchflow.id, 10),
+        (other_bank.id, 20),
+    ] {
+        repo.upsert_transaction(&create_test_transaction(account_id, -500, day(d)))
+            .unwrap();
+    }
+
+    let groups = doctor.check_duplicate_accounts().unwrap();
+    assert_eq!(groups.len(), 1);
+    let group = &groups[0];
+    assert_eq!(group.name, "everyday checking");
+    assert_eq!(group.institution, "big bank");
+    assert_eq!(group.accounts.len(), 2);
+    // The copy with the most recent transaction is suggested
+    assert_eq!(group.suggested_keep, via_lunchflow.id.to_string());
+    let simplefin_copy = group
+        .accounts
+        .iter()
+        .find(|a| a.account_id == via_simplefin.id.to_string())
+        .unwrap();
+    assert_eq!(simplefin_copy.last_activity, Some(day(2)));
+    assert_eq!(simplefin_copy.transaction_count, 2);
+
+    let result = doctor.run_checks().unwrap();
+    let check = &result.checks["duplicate_accounts"];
+    assert_eq!(check.status, "warning");
+    assert_eq!(check.details.as_ref().unwrap().len(), 2);
+
+    let keep = via_lunchflow.id.to_string();
+    assert!(doctor.merge_accounts(&keep, &[keep.clone()]).is_err());
+    assert!(doctor
+        .merge_accounts(&keep, &["missing".to_string()])
+        .is_err());
+
+    let reports = doctor
+        .merge_accounts(&keep, &[via_simplefin.id.to_string()])
+        .unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].transactions_moved, 2);
+    assert!(repo
+        .get_account_by_id(&via_simplefin.id.to_string())
+        .unwrap()
+        .is_none());
+    assert_eq!(repo.get_transactions_by_account(&keep).unwrap().len(), 3);
+    assert!(doctor.check_duplicate_accounts().unwrap().is_empty());
+}
+
+/// Test per-integration connection health in doctor
+#[test]
+fn test_doctor_integration_connectivity() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let doctor = DoctorService::new(repo.clone(), temp_dir.path().to_path_buf());
+
+    let result = doctor.run_checks().unwrap();
+    let check = &result.checks["integration_connectivity"];
+    assert_eq!(check.status, "pass");
+    assert_eq!(check.message, "No integrations configured");
+
+    repo.upsert_integration("demo", &serde_json::json!({}))
+        .unwrap();
+    repo.upsert_integration("custom", &serde_json::json!({}))
+        .unwrap();
+    let result = doctor.run_checks().unwrap();
+    assert_eq!(result.checks["integration_connectivity"].status, "pass");
+
+    // Settings without credentials fail authentication
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+    let mut connections = doctor.check_connections().unwrap();
+    connections.sort_by(|a, b| a.integration.cmp(&b.integration));
+    let statuses: Vec<(&str, ConnectionStatus)> = connections
+        .iter()
+        .map(|c| (c.integration.as_str(), c.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ("custom", ConnectionStatus::Unchecked),
+            ("demo", ConnectionStatus::Ok),
+            ("lunchflow", ConnectionStatus::AuthFailed),
+        ]
+    );
+
+    let result = doctor.run_checks().unwrap();
+    let check = &result.checks["integration_connectivity"];
+    assert_eq!(check.status, "error");
+    assert_eq!(check.details.as_ref().unwrap().len(), 3);
+}
+
+// ============================================================================
+// Query Service Tests
+// ============================================================================
+
+/// Test monthly spend per tag, zero-filled across the requested months
+#[test]
+fn test_tag_trend() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+    let add = |cents, day: NaiveDate, tags: &[&str]| {
+        let mut tx = create_test_transaction(account.id, cents, day);
+        tx.tags = tags.iter().map(|t| t.to_string()).collect();
+        repo.upsert_transaction(&tx).unwrap();
+    };
+    add(-1000, date(1, 3), &["groceries"]);
+    add(-500, date(1, 28), &["groceries"]);
+    add(-700, date(2, 10), &["dining"]);
+    add(-2000, date(3, 1), &["food", "groceries"]);
+    // Refunds, transfers and spending outside the window don't count
+    add(300, date(3, 2), &["groceries"]);
+    add(-4000, date(3, 5), &["groceries", "transfer"]);
+    add(
+        -9900,
+        NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+        &["groceries"],
+    );
+
+    let query_service = QueryService::new(repo.clone());
+    let today = date(3, 15);
+    assert_eq!(
+        query_service
+            .tag_trend_as_of("groceries", 3, today)
+            .unwrap(),
+        vec![
+            (date(1, 1), Decimal::new(1500, 2)),
+            (date(2, 1), Decimal::ZERO),
+            (date(3, 1), Decimal::new(2000, 2)),
+        ]
+    );
+
+    // An unknown tag is an empty trend, not an error
+    let trend = query_service.tag_trend_as_of("nope", 2, today).unwrap();
+    assert_eq!(
+        trend,
+        vec![(date(2, 1), Decimal::ZERO), (date(3, 1), Decimal::ZERO)]
+    );
+
+    assert!(query_service
+        .tag_trend_as_of("groceries", 0, today)
+        .is_err());
+}
+
+/// Test spending per tag over a date range
+#[test]
+fn test_spending_by_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+    let add = |cents, day: NaiveDate, tags: &[&str]| {
+        let mut tx = create_test_transaction(account.id, cents, day);
+        tx.tags = tags.iter().map(|t| t.to_string()).collect();
+        repo.upsert_transaction(&tx).unwrap();
+    };
+    add(-1000, date(3), &["groceries"]);
+    // Counted once toward each of its tags
+    add(-2000, date(10), &["food", "groceries"]);
+    add(-450, date(12), &[]);
+    add(-50, date(31), &[]);
+    // Income, transfers and spending outside the range don't count
+    add(300, date(15), &["groceries"]);
+    add(-5000, date(20), &["transfer"]);
+    add(
+        -9900,
+        NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+        &["food"],
+    );
+
+    let query_service = QueryService::new(repo.clone());
+    let spending = query_service.spending_by_tag(date(1), date(31)).unwrap();
+    assert_eq!(
+        spending,
+        vec![
+            ("groceries".to_string(), Decimal::new(3000, 2)),
+            ("food".to_string(), Decimal::new(2000, 2)),
+            (UNTAGGED.to_string(), Decimal::new(500, 2)),
+        ]
+    );
+
+    let spending = query_service.spending_by_tag(date(11), date(30)).unwrap();
+    assert_eq!(spending, vec![(UNTAGGED.to_string(), Decimal::new(450, 2))]);
+
+    assert!(query_service.spending_by_tag(date(31), date(1)).is_err());
+}
+
+/// Test server-side transaction filtering, one criterion at a time and combined
+#[test]
+fn test_query_transactions_filters() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    let checking = create_test_account("Checking");
+    let savings = create_test_account("Savings");
+    repo.upsert_account(&checking).unwrap();
+    repo.upsert_account(&savings).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+    let add = |account: Uuid, cents, d, description: &str, tags: &[&str]| {
+        let mut tx = create_test_transaction(account, cents, day(d));
+        tx.description = Some(description.to_string());
+        tx.tags = tags.iter().map(|t| t.to_string()).collect();
+        repo.upsert_transaction(&tx).unwrap();
+    };
+    add(checking.id, -1250, 1, "Coffee Shop", &["food"]);
+    add(
+        checking.id,
+        -8000,
+        10,
+        "Grocery Store",
+        &["food", "groceries"],
+    );
+    add(checking.id, 250000, 15, "Salary", &[]);
+    add(savings.id, -2000, 20, "100% Fee", &["fees"]);
+
+    let descriptions = |filter: TransactionFilter| -> Vec<String> {
+        let mut found: Vec<String> = repo
+            .query_transactions(filter)
+            .unwrap()
+            .into_iter()
+            .map(|tx| tx.description.unwrap())
+            .collect();
+        found.sort();
+        found
     };
 
-    let options = ImportOptions {
-        debit_negative: false,
-        flip_signs: false,
-        skip_rows: 0,
-        number_format: NumberFormat::default(),
-        anchor_balance: None,
-        anchor_date: None,
+    assert_eq!(descriptions(TransactionFilter::default()).len(), 4);
+    assert_eq!(
+        descriptions(TransactionFilter {
+            account_id: Some(savings.id),
+            ..Default::default()
+        }),
+        vec!["100% Fee"]
+    );
+    assert_eq!(
+        descriptions(TransactionFilter {
+            min_amount: Some(Decimal::new(-2000, 2)),
+            ..Default::default()
+        }),
+        vec!["100% Fee", "Coffee Shop", "Salary"]
+    );
+    assert_eq!(
+        descriptions(TransactionFilter {
+            max_amount: Some(Decimal::new(-2000, 2)),
+            ..Default::default()
+        }),
+        vec!["100% Fee", "Grocery Store"]
+    );
+    assert_eq!(
+        descriptions(TransactionFilter {
+            start_date: Some(day(10)),
+            ..Default::default()
+        }),
+        vec!["100% Fee", "Grocery Store", "Salary"]
+    );
+    assert_eq!(
+        descriptions(TransactionFilter {
+            end_date: Some(day(10)),
+            ..Default::default()
+        }),
+        vec!["Coffee Shop", "Grocery Store"]
+    );
+    assert_eq!(
+        descriptions(TransactionFilter {
+            tag: Some("food".to_string()),
+            ..Default::default()
+        }),
+        vec!["Coffee Shop", "Grocery Store"]
+    );
+    assert_eq!(
+        descriptions(TransactionFilter {
+            search: Some("SHOP".to_string()),
+            ..Default::default()
+        }),
+        vec!["Coffee Shop"]
+    );
+    // Wildcard characters in the search term are literal
+    assert_eq!(
+        descriptions(TransactionFilter {
+            search: Some("0%".to_string()),
+            ..Default::default()
+        }),
+        vec!["100% Fee"]
+    );
+
+    let combined = TransactionFilter {
+        account_id: Some(checking.id),
+        max_amount: Some(Decimal::ZERO),
+        start_date: Some(day(5)),
+        end_date: Some(day(31)),
+        tag: Some("food".to_string()),
+        search: Some("store".to_string()),
+        ..Default::default()
     };
+    assert_eq!(descriptions(combined), vec!["Grocery Store"]);
+
+    // Values are bound, not spliced into the SQL
+    assert!(descriptions(TransactionFilter {
+        search: Some("'; DROP TABLE sys_transactions; --".to_string()),
+        ..Default::default()
+    })
+    .is_empty());
+    assert_eq!(repo.get_transaction_count().unwrap(), 4);
+}
 
-    let result = import_service
-        .import(
-            Path::new(&csv_path),
-            &account.id.to_string(),
-            &mappings,
-            &options,
-            false,
-        )
+/// Test executing custom SQL queries
+#[test]
+fn test_execute_custom_query() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    // Create test data
+    let account = create_test_account("Query Test");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for i in 0..5 {
+        let mut tx = create_test_transaction(account.id, (i + 1) * 1000, date);
+        tx.description = Some(format!("Transaction {}", i));
+        repo.upsert_transaction(&tx).unwrap();
+    }
+
+    // Execute custom query
+    let result = repo
+        .execute_query("SELECT COUNT(*) as cnt FROM sys_transactions")
         .unwrap();
 
-    assert_eq!(result.imported, 3, "Should import 3 transactions");
-    assert_eq!(result.skipped, 0, "Should skip no transactions");
+    // Should have results
+    assert!(!result.columns.is_empty());
+    assert!(!result.rows.is_empty());
+}
 
-    // Verify transactions were created
-    let transactions = repo
-        .get_transactions_by_account(&account.id.to_string())
+/// Test query validation rejects dangerous SQL
+#[test]
+fn test_query_validation() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+
+    // Invalid SQL should return error
+    let result = repo.execute_query("SELEC * FROM sys_accounts"); // typo in SELECT
+    assert!(result.is_err(), "Invalid SQL should fail");
+
+    // Keywords inside identifiers are fine
+    let result = repo
+        .execute_query("SELECT COUNT(*) AS update_count FROM sys_accounts")
         .unwrap();
-    assert_eq!(transactions.len(), 3, "Should have 3 transactions");
+    assert_eq!(result.columns, vec!["update_count"]);
 
-    // Verify amounts (check one positive and one negative)
-    let coffee_tx = transactions.iter().find(|t| {
-        t.description
-            .as_ref()
-            .map(|d| d.contains("Coffee"))
-            .unwrap_or(false)
+    // A write smuggled in after a tab is still caught, and nothing runs
+    let result = repo.execute_query("SELECT 1;\tDELETE FROM sys_accounts");
+    assert!(result.is_err(), "Hidden DELETE should be rejected");
+}
+
+/// Test that a runaway query is cancelled at the configured timeout
+#[test]
+fn test_query_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone()).with_limits(QueryLimits {
+        timeout: Some(Duration::from_millis(200)),
+        max_rows: None,
     });
-    assert!(coffee_tx.is_some(), "Should find Coffee transaction");
-    // The import service stores amounts, verify it's positive (12.34 from CSV)
-    assert!(
-        coffee_tx.unwrap().amount > Decimal::ZERO,
-        "Coffee transaction amount should be positive"
-    );
+
+    let started = Instant::now();
+    let err = query_service
+        .execute("SELECT count(*) FROM range(100000) a, range(100000) b, range(100000) c")
+        .unwrap_err();
+    assert!(err.to_string().contains("time limit"), "got: {}", err);
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    // The connection is still usable afterwards
+    let result = query_service.execute("SELECT 1").unwrap();
+    assert_eq!(result.row_count, 1);
 }
 
-/// Test CSV import deduplication
+/// Test explaining queries without running them, and that writes stay blocked
 #[test]
-fn test_csv_import_deduplication() {
+fn test_query_explain() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone());
 
-    let account = create_test_account("Dedup Test Account");
+    let account = create_test_account("Checking");
     repo.upsert_account(&account).unwrap();
+    let tx = create_test_transaction(
+        account.id,
+        -1250,
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+    );
+    repo.upsert_transaction(&tx).unwrap();
 
-    let csv_content = r#"date,amount,description
-2024-01-15,12.34,Coffee Shop"#;
+    let sql = "SELECT account_id, sum(amount) FROM sys_transactions GROUP BY account_id;";
+    for plan in [
+        query_service.explain(sql).unwrap(),
+        query_service.explain_analyze(sql).unwrap(),
+    ] {
+        assert!(plan.row_count > 0);
+        assert!(plan.columns.iter().any(|c| c == "explain_value"));
+        let text = plan.rows[0].last().unwrap().as_str().unwrap();
+        assert!(!text.trim().is_empty());
+    }
 
-    let csv_path = temp_dir.path().join("test_dedup.csv");
-    std::fs::write(&csv_path, csv_content).unwrap();
+    // Anything that could change data is rejected, even inside EXPLAIN ANALYZE
+    assert!(query_service
+        .explain_analyze("DELETE FROM sys_transactions")
+        .is_err());
+    assert!(query_service
+        .explain("EXPLAIN ANALYZE DELETE FROM sys_transactions")
+        .is_err());
+    assert!(query_service.explain("SELECT 1; SELECT 2").is_err());
+    assert!(query_service.explain("SELEC 1").is_err());
+    assert_eq!(
+        repo.get_transactions_by_account(&account.id.to_string())
+            .unwrap()
+            .len(),
+        1
+    );
+}
 
-    let import_service = ImportService::new(repo.clone(), temp_dir.path().to_path_buf());
+/// Test that a running query can be cancelled from another thread
+#[test]
+fn test_cancel_query_from_another_thread() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
 
-    let mappings = ColumnMappings {
-        date: "date".to_string(),
-        amount: "amount".to_string(),
-        description: Some("description".to_string()),
-        credit: None,
-        debit: None,
-        balance: None,
+    let (handle, pending) = repo
+        .execute_query_cancellable(
+            "SELECT count(*) FROM range(100000) a, range(100000) b, range(100000) c",
+            QueryLimits::default(),
+        )
+        .unwrap();
+    let canceller = {
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            handle.cancel();
+        })
     };
 
-    let options = ImportOptions {
-        debit_negative: false,
-        flip_signs: false,
-        skip_rows: 0,
-        number_format: NumberFormat::default(),
-        anchor_balance: None,
-        anchor_date: None,
+    let started = Instant::now();
+    let err = pending.run().unwrap_err();
+    canceller.join().unwrap();
+    assert!(
+        matches!(
+            err.downcast_ref::<RepositoryError>(),
+            Some(RepositoryError::Cancelled)
+        ),
+        "got: {}",
+        err
+    );
+    assert!(handle.is_cancelled());
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    // Cancelling after the query finished has no effect on later queries
+    let (handle, pending) = repo
+        .execute_query_cancellable("SELECT 42", QueryLimits::default())
+        .unwrap();
+    assert_eq!(pending.run().unwrap().rows[0][0], serde_json::json!(42));
+    handle.cancel();
+    assert_eq!(repo.execute_query("SELECT 1").unwrap().row_count, 1);
+
+    // A query cancelled before it starts never runs, and writes are still rejected
+    let (handle, pending) = repo
+        .execute_query_cancellable("SELECT 1", QueryLimits::default())
+        .unwrap();
+    handle.cancel();
+    assert!(pending.run().is_err());
+    assert!(repo
+        .execute_query_cancellable("DELETE FROM sys_accounts", QueryLimits::default())
+        .is_err());
+
+    // The row limit applies as for other queries
+    let limits = QueryLimits {
+        timeout: None,
+        max_rows: Some(3),
     };
+    let (_, pending) = repo
+        .execute_query_cancellable("SELECT * FROM range(10)", limits)
+        .unwrap();
+    assert_eq!(pending.run().unwrap().row_count, 3);
+}
 
-    // First import
-    let result1 = import_service
-        .import(
-            Path::new(&csv_path),
-            &account.id.to_string(),
-            &mappings,
-            &options,
-            false,
-        )
+/// Test that SELECTs without a LIMIT are capped at the row limit
+#[test]
+fn test_query_row_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone()).with_limits(QueryLimits {
+        timeout: None,
+        max_rows: Some(10),
+    });
+
+    let result = query_service.execute("SELECT * FROM range(100)").unwrap();
+    assert_eq!(result.row_count, 10);
+
+    // An explicit LIMIT wins
+    let result = query_service
+        .execute("SELECT * FROM range(100) LIMIT 50")
         .unwrap();
+    assert_eq!(result.row_count, 50);
 
-    assert_eq!(result1.imported, 1);
+    // Per-call limits override the configured ones
+    let result = query_service
+        .execute_with_limits("SELECT * FROM range(100)", QueryLimits::default())
+        .unwrap();
+    assert_eq!(result.row_count, 100);
+}
 
-    // Second import of same data should be skipped
-    let result2 = import_service
-        .import(
-            Path::new(&csv_path),
-            &account.id.to_string(),
-            &mappings,
-            &options,
-            false,
+/// Test saving, running and replacing named queries
+#[test]
+fn test_named_queries() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let query_service =
+        QueryService::new(repo.clone()).with_treeline_dir(temp_dir.path().to_path_buf());
+
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    repo.upsert_transaction(&create_test_transaction(account.id, -2500, date))
+        .unwrap();
+
+    query_service
+        .save_named("monthly", "SELECT COUNT(*) AS n FROM transactions")
+        .unwrap();
+    let result = query_service.run_named("monthly").unwrap();
+    assert_eq!(result.columns, vec!["n".to_string()]);
+    assert_eq!(result.rows[0][0], serde_json::json!(1));
+    assert!(Config::load(temp_dir.path())
+        .unwrap()
+        .named_queries
+        .contains_key("monthly"));
+
+    // Saving again replaces the query
+    query_service
+        .save_named("monthly", "SELECT 42 AS n")
+        .unwrap();
+    let result = query_service.run_named("monthly").unwrap();
+    assert_eq!(result.rows[0][0], serde_json::json!(42));
+
+    // Only read-only queries can be saved
+    assert!(query_service
+        .save_named("wipe", "DELETE FROM sys_transactions")
+        .is_err());
+    assert!(query_service
+        .save_named("two", "SELECT 1; DROP TABLE sys_accounts")
+        .is_err());
+    assert!(query_service
+        .save_named("copy", "SELECT * INTO stolen FROM transactions")
+        .is_err());
+    assert!(query_service
+        .save_named(
+            "sneaky",
+            "WITH t AS (SELECT 1) INSERT INTO sys_accounts SELECT * FROM t",
+        )
+        .is_err());
+    assert!(query_service.save_named("bad name", "SELECT 1").is_err());
+    assert!(query_service.run_named("wipe").is_err());
+
+    // Without a treeline directory there is nowhere to keep them
+    assert!(QueryService::new(repo.clone())
+        .save_named("monthly", "SELECT 1")
+        .is_err());
+}
+
+/// Test binding `:param` placeholders in named queries
+#[test]
+fn test_named_queries_with_params() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let query_service =
+        QueryService::new(repo.clone()).with_treeline_dir(temp_dir.path().to_path_buf());
+
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+    for (amount, day) in [(-2500, 5), (-10000, 20)] {
+        let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        repo.upsert_transaction(&create_test_transaction(account.id, amount, date))
+            .unwrap();
+    }
+
+    query_service
+        .save_named(
+            "spend_since",
+            "SELECT COUNT(*) AS n FROM transactions
+             WHERE transaction_date >= :since::DATE AND -amount >= :min AND ':kept' = ':kept'",
         )
         .unwrap();
 
-    assert_eq!(result2.imported, 0, "Should not import duplicates");
-    assert_eq!(result2.skipped, 1, "Should skip the duplicate");
+    let params = HashMap::from([
+        ("since".to_string(), serde_json::json!("2024-01-10")),
+        ("min".to_string(), serde_json::json!(50)),
+    ]);
+    let result = query_service
+        .run_named_with("spend_since", &params)
+        .unwrap();
+    assert_eq!(result.rows[0][0], serde_json::json!(1));
+
+    let params = HashMap::from([
+        ("since".to_string(), serde_json::json!("2024-01-01")),
+        ("min".to_string(), serde_json::json!(10)),
+    ]);
+    let result = query_service
+        .run_named_with("spend_since", &params)
+        .unwrap();
+    assert_eq!(result.rows[0][0], serde_json::json!(2));
+
+    // Every missing parameter is named
+    let params = HashMap::from([("min".to_string(), serde_json::json!(10))]);
+    let err = query_service
+        .run_named_with("spend_since", &params)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("since"), "{}", err);
+    let err = query_service
+        .run_named("spend_since")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("since, min"), "{}", err);
+
+    // Placeholders in comments are left alone, and the row limit applies
+    query_service
+        .save_named(
+            "recent",
+            "SELECT amount FROM transactions -- filter on :ignored later
+             WHERE -amount >= :min /* not :this either */",
+        )
+        .unwrap();
+    let limited = QueryService::new(repo.clone())
+        .with_treeline_dir(temp_dir.path().to_path_buf())
+        .with_limits(QueryLimits {
+            timeout: None,
+            max_rows: Some(1),
+        });
+    let params = HashMap::from([("min".to_string(), serde_json::json!(10))]);
+    let result = limited.run_named_with("recent", &params).unwrap();
+    assert_eq!(result.row_count, 1);
+
+    // A write planted in settings.json by hand is refused when run
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.named_queries.insert(
+        "wipe".to_string(),
+        "DELETE FROM sys_transactions WHERE :x = :x".to_string(),
+    );
+    config.save(temp_dir.path()).unwrap();
+    let params = HashMap::from([("x".to_string(), serde_json::json!(1))]);
+    assert!(query_service.run_named_with("wipe", &params).is_err());
+    assert_eq!(repo.get_transaction_count().unwrap(), 2);
+}
 
-    // Verify only one transaction exists
-    let transactions = repo
-        .get_transactions_by_account(&account.id.to_string())
+/// Test that repeated read queries are served from the cache until a write
+#[test]
+fn test_query_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    repo.upsert_transaction(&create_test_transaction(account.id, -1000, date))
         .unwrap();
-    assert_eq!(transactions.len(), 1, "Should have only 1 transaction");
+
+    let service = QueryService::new_with_cache(repo.clone(), 2, Duration::from_secs(60));
+    let sql = "SELECT COUNT(*) FROM transactions";
+    assert_eq!(service.execute(sql).unwrap().rows[0][0], 1);
+    assert_eq!(service.execute(sql).unwrap().rows[0][0], 1);
+    let stats = service.cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+    // A write through the repository invalidates the cache
+    repo.upsert_transaction(&create_test_transaction(account.id, -2000, date))
+        .unwrap();
+    assert_eq!(service.execute(sql).unwrap().rows[0][0], 2);
+    assert_eq!(service.cache_stats().unwrap().misses, 2);
+
+    // So does a write through the service itself, which is never cached
+    service
+        .execute_sql("DELETE FROM sys_transactions WHERE amount = -20")
+        .unwrap();
+    assert_eq!(service.execute(sql).unwrap().rows[0][0], 1);
+    assert_eq!(service.cache_stats().unwrap().misses, 3);
+
+    // Parameters are part of the key
+    let by_amount = "SELECT COUNT(*) FROM transactions WHERE amount = ?";
+    let count = |amount: i64| {
+        service
+            .execute_sql_with_params(by_amount, &[serde_json::json!(amount)])
+            .unwrap()
+            .rows[0][0]
+            .clone()
+    };
+    assert_eq!(count(-10), 1);
+    assert_eq!(count(-20), 0);
+    assert_eq!(count(-10), 1);
+    let stats = service.cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses), (2, 5));
+    assert_eq!(stats.entries, 2, "least recently used entry is evicted");
+
+    service.invalidate();
+    assert_eq!(service.cache_stats().unwrap().entries, 0);
+
+    // A write behind a WITH is a write too
+    assert_eq!(service.execute(sql).unwrap().rows[0][0], 1);
+    service
+        .execute_sql(
+            "WITH gone AS (SELECT -10 AS amount)
+             DELETE FROM sys_transactions WHERE amount IN (SELECT amount FROM gone)",
+        )
+        .unwrap();
+    assert_eq!(service.execute(sql).unwrap().rows[0][0], 0);
+
+    // Expired entries are not reused
+    let service = QueryService::new_with_cache(repo, 2, Duration::ZERO);
+    service.execute(sql).unwrap();
+    service.execute(sql).unwrap();
+    assert_eq!(service.cache_stats().unwrap().hits, 0);
 }
 
-// ============================================================================
-// Data Integrity Tests
-// ============================================================================
+/// Test that descriptions normalizing to the same merchant are grouped
+#[test]
+fn test_merchant_summary() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone());
 
-/// Test that invalid UUIDs in queries are handled properly
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for (description, cents) in [
+        ("SQ *COFFEE 12345 SEATTLE WA", -450),
+        ("SQ *COFFEE 678 PORTLAND OR", -550),
+        ("STARBUCKS #1234 SEATTLE", -300),
+        ("GROCERY OUTLET 0042", -8000),
+        ("GROCERY OUTLET 0042", 1000),
+    ] {
+        let mut tx = create_test_transaction(account.id, cents, date);
+        tx.description = Some(description.to_string());
+        repo.upsert_transaction(&tx).unwrap();
+    }
+    let mut transfer = create_test_transaction(account.id, -50000, date);
+    transfer.description = Some("SQ *COFFEE 999".to_string());
+    transfer.tags = vec![TRANSFER_TAG.to_string()];
+    repo.upsert_transaction(&transfer).unwrap();
+
+    let summary = query_service.merchant_summary().unwrap();
+    assert_eq!(
+        summary,
+        vec![
+            ("Grocery Outlet".to_string(), Decimal::new(-7000, 2), 2),
+            ("Coffee".to_string(), Decimal::new(-1000, 2), 2),
+            ("Starbucks".to_string(), Decimal::new(-300, 2), 1),
+        ]
+    );
+}
+
+/// Test the expense amount histogram, including its edge cases
 #[test]
-fn test_invalid_uuid_handling() {
+fn test_amount_histogram() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone());
 
-    // Query with invalid UUID should return None, not error
-    let result = repo.get_account_by_id("not-a-valid-uuid");
-    assert!(result.is_ok(), "Should not error on invalid UUID");
-    assert!(
-        result.unwrap().is_none(),
-        "Should return None for invalid UUID"
+    let checking = create_test_account("Checking");
+    let savings = create_test_account("Savings");
+    let empty = create_test_account("Empty");
+    for account in [&checking, &savings, &empty] {
+        repo.upsert_account(account).unwrap();
+    }
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for cents in [-1000, -2000, -3000, -4000, -10000, 5000] {
+        repo.upsert_transaction(&create_test_transaction(checking.id, cents, date))
+            .unwrap();
+    }
+    let mut transfer = create_test_transaction(checking.id, -50000, date);
+    transfer.tags = vec![TRANSFER_TAG.to_string()];
+    repo.upsert_transaction(&transfer).unwrap();
+    for _ in 0..2 {
+        repo.upsert_transaction(&create_test_transaction(savings.id, -500, date))
+            .unwrap();
+    }
+
+    let checking_id = checking.id.to_string();
+    let histogram = query_service
+        .amount_histogram(Some(&checking_id), 3)
+        .unwrap();
+    let bars: Vec<(Decimal, Decimal, i64)> = histogram
+        .iter()
+        .map(|b| (b.lower, b.upper, b.count))
+        .collect();
+    assert_eq!(
+        bars,
+        vec![
+            (Decimal::new(1000, 2), Decimal::new(4000, 2), 3),
+            (Decimal::new(4000, 2), Decimal::new(7000, 2), 1),
+            (Decimal::new(7000, 2), Decimal::new(10000, 2), 1),
+        ]
     );
 
-    // Query with non-existent valid UUID should also return None
-    let fake_uuid = Uuid::new_v4();
-    let result = repo.get_account_by_id(&fake_uuid.to_string());
-    assert!(result.is_ok());
-    assert!(result.unwrap().is_none());
+    // Every expense the same size: one bucket instead of dividing by zero
+    let savings_id = savings.id.to_string();
+    let histogram = query_service
+        .amount_histogram(Some(&savings_id), 5)
+        .unwrap();
+    assert_eq!(histogram.len(), 1);
+    assert_eq!(histogram[0].lower, Decimal::new(500, 2));
+    assert_eq!(histogram[0].count, 2);
+
+    let empty_id = empty.id.to_string();
+    assert!(query_service
+        .amount_histogram(Some(&empty_id), 5)
+        .unwrap()
+        .is_empty());
+    assert!(query_service.amount_histogram(None, 0).is_err());
+
+    let all: i64 = query_service
+        .amount_histogram(None, 4)
+        .unwrap()
+        .iter()
+        .map(|b| b.count)
+        .sum();
+    assert_eq!(all, 7);
 }
 
-/// Test transaction date range query
+/// Test OFX export of an account's transactions
 #[test]
-fn test_transaction_date_range() {
+fn test_export_ofx() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone());
+
+    let account = create_test_account("OFX Test");
+    repo.upsert_account(&account).unwrap();
+
+    let mut debit = create_test_transaction(
+        account.id,
+        -1234,
+        NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+    );
+    debit.description = Some("Coffee & <Bagel>".to_string());
+    repo.upsert_transaction(&debit).unwrap();
+
+    let credit = create_test_transaction(
+        account.id,
+        250000,
+        NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+    );
+    repo.upsert_transaction(&credit).unwrap();
+
+    let old = BalanceSnapshot::new(
+        account.id,
+        Decimal::new(10000, 2),
+        NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+    );
+    repo.add_balance_snapshot(&old).unwrap();
+    let latest = BalanceSnapshot::new(
+        account.id,
+        Decimal::new(258766, 2),
+        NaiveDate::from_ymd_opt(2024, 2, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+    );
+    repo.add_balance_snapshot(&latest).unwrap();
+
+    let path = temp_dir.path().join("export.ofx");
+    query_service
+        .export_ofx(&path, &account.id.to_string())
+        .unwrap();
+    let ofx = std::fs::read_to_string(&path).unwrap();
+
+    assert!(ofx.contains("<?OFX OFXHEADER=\"200\""));
+    assert!(ofx.contains("<BANKMSGSRSV1>"));
+    assert_eq!(ofx.matches("<STMTTRN>").count(), 2);
+
+    // Debit: negative amount, UUID as FITID, escaped description
+    assert!(ofx.contains(&format!("<FITID>{}</FITID>", debit.id)));
+    assert!(ofx.contains("<TRNTYPE>DEBIT</TRNTYPE>"));
+    assert!(ofx.contains("<TRNAMT>-12.34</TRNAMT>"));
+    assert!(ofx.contains("<DTPOSTED>20240115</DTPOSTED>"));
+    assert!(ofx.contains("<NAME>Coffee &amp; &lt;Bagel&gt;</NAME>"));
+
+    // Credit: positive amount
+    assert!(ofx.contains(&format!("<FITID>{}</FITID>", credit.id)));
+    assert!(ofx.contains("<TRNAMT>2500.00</TRNAMT>"));
+
+    // Ledger balance from the latest snapshot
+    assert!(ofx.contains("<BALAMT>2587.66</BALAMT>"));
+    assert!(ofx.contains("<DTASOF>20240201120000</DTASOF>"));
+}
+
+/// Add a transaction posted on `day` of March 2024
+fn add_march_transaction(repo: &DuckDbRepository, account_id: Uuid, cents: i64, day: u32) {
+    let date = NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+    repo.upsert_transaction(&create_test_transaction(account_id, cents, date))
+        .unwrap();
+}
+
+/// Statement opening balance carries the last earlier snapshot forward
+#[test]
+fn test_account_statement_running_balance() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone());
 
-    let account = create_test_account("Date Range Test");
+    let account = create_test_account("Checking");
     repo.upsert_account(&account).unwrap();
 
-    // Create transactions on different dates
-    let dates = vec![
-        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
-        NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
-    ];
+    // Snapshot of 1000.00 on Feb 25, then -50.00 before the period starts
+    add_snapshot_on(
+        &repo,
+        account.id,
+        100000,
+        NaiveDate::from_ymd_opt(2024, 2, 25).unwrap(),
+    );
+    let before = create_test_transaction(
+        account.id,
+        -5000,
+        NaiveDate::from_ymd_opt(2024, 2, 28).unwrap(),
+    );
+    repo.upsert_transaction(&before).unwrap();
 
-    for (i, date) in dates.iter().enumerate() {
-        let tx = create_test_transaction(account.id, (i + 1) as i64 * 1000, *date);
-        repo.upsert_transaction(&tx).unwrap();
-    }
+    add_march_transaction(&repo, account.id, -2000, 10);
+    add_march_transaction(&repo, account.id, 30000, 5);
+    add_march_transaction(&repo, account.id, -1000, 20);
+    // Outside the period
+    add_march_transaction(&repo, account.id, -7777, 31);
 
-    let range = repo.get_transaction_date_range().unwrap();
-    assert!(range.earliest.is_some());
-    assert!(range.latest.is_some());
-    assert!(range.earliest.unwrap().contains("2024-01-01"));
-    assert!(range.latest.unwrap().contains("2024-12-31"));
+    let statement = query_service
+        .account_statement(
+            &account.id.to_string(),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 25).unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(statement.account.id, account.id);
+    assert!(!statement.opening_estimated);
+    assert_eq!(statement.opening_balance, Decimal::new(95000, 2));
+    let dates: Vec<u32> = statement.lines.iter().map(|l| l.date.day()).collect();
+    assert_eq!(dates, vec![5, 10, 20]);
+    let balances: Vec<Decimal> = statement.lines.iter().map(|l| l.balance).collect();
+    assert_eq!(
+        balances,
+        vec![
+            Decimal::new(125000, 2),
+            Decimal::new(123000, 2),
+            Decimal::new(122000, 2)
+        ]
+    );
+    assert_eq!(statement.closing_balance, Decimal::new(122000, 2));
 }
 
-/// Test balance snapshot ordering
+/// Without an earlier snapshot the opening balance is worked back from the closing one
 #[test]
-fn test_balance_snapshot_ordering() {
+fn test_account_statement_without_opening_snapshot() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let query_service = QueryService::new(repo.clone());
 
-    let account = create_test_account("Balance Test");
+    let account = create_test_account("Checking");
     repo.upsert_account(&account).unwrap();
 
-    // Add multiple balance snapshots with increasing time
-    for i in 1i64..=5 {
-        let base_time = chrono::DateTime::from_timestamp(1704067200 + i * 60, 0).unwrap();
-        let snapshot_time = base_time.naive_utc();
-        let snapshot = BalanceSnapshot::new(account.id, Decimal::new(i * 1000, 2), snapshot_time);
-        repo.add_balance_snapshot(&snapshot).unwrap();
-    }
+    add_march_transaction(&repo, account.id, -2500, 3);
+    add_march_transaction(&repo, account.id, 10000, 12);
+    add_march_transaction(&repo, account.id, -1500, 28);
+    // 500.00 at the end of March, after the -15.00 on the 28th
+    add_snapshot_on(
+        &repo,
+        account.id,
+        50000,
+        NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+    );
 
-    // The latest balance should be visible on the account
-    let accounts = repo.get_accounts().unwrap();
-    let account = accounts.iter().find(|a| a.name == "Balance Test").unwrap();
+    let statement = query_service
+        .account_statement(
+            &account.id.to_string(),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+        )
+        .unwrap();
 
-    // Balance should be the most recent one (50.00)
-    assert!(account.balance.is_some());
+    assert!(statement.opening_estimated);
+    assert_eq!(statement.closing_balance, Decimal::new(51500, 2));
+    assert_eq!(statement.opening_balance, Decimal::new(44000, 2));
+    assert_eq!(statement.lines.len(), 2);
     assert_eq!(
-        account.balance.unwrap(),
-        Decimal::new(5000, 2),
-        "Should show latest balance"
+        statement.lines.last().unwrap().balance,
+        statement.closing_balance
+    );
+
+    let missing = query_service.account_statement(
+        &Uuid::new_v4().to_string(),
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
     );
+    assert!(missing.is_err());
 }
 
-/// Test transaction deduplication by provider ID
+// ============================================================================
+// Plugin View Tests
+// ============================================================================
+
+/// Test that plugin views are namespaced, survive compaction and go away on uninstall
 #[test]
-fn test_sync_deduplication_by_provider_id() {
+fn test_plugin_views() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let plugins = PluginService::new_with_repository(temp_dir.path(), repo.clone());
 
-    let account = create_test_account("Dedup Test");
+    let account = create_test_account("Checking");
     repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    repo.upsert_transaction(&create_test_transaction(account.id, -2500, date))
+        .unwrap();
 
-    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    let mut tx = create_test_transaction(account.id, 5000, date);
-    tx.sf_id = Some("unique_sf_id".to_string());
-    repo.upsert_transaction(&tx).unwrap();
-
-    // Check if transaction exists by SF ID
-    let exists = repo.transaction_exists_by_sf_id("unique_sf_id").unwrap();
-    assert!(exists, "Transaction should exist by SF ID");
+    plugins
+        .register_view(
+            "budget",
+            "monthly_rollup",
+            "SELECT strftime(transaction_date, '%Y-%m') AS month, SUM(amount) AS total
+             FROM transactions GROUP BY 1",
+        )
+        .unwrap();
 
-    let not_exists = repo.transaction_exists_by_sf_id("nonexistent").unwrap();
-    assert!(!not_exists, "Non-existent SF ID should return false");
+    let result = repo
+        .execute_query("SELECT month, total FROM plugin_budget.monthly_rollup")
+        .unwrap();
+    assert_eq!(result.row_count, 1);
+    assert_eq!(plugins.list_views("budget").unwrap().len(), 1);
+
+    // Only single SELECT statements are accepted
+    assert!(plugins
+        .register_view("budget", "bad", "DELETE FROM transactions")
+        .is_err());
+    assert!(plugins
+        .register_view("budget", "bad", "SELECT 1; DROP TABLE sys_transactions")
+        .is_err());
+    assert!(plugins
+        .register_view("budget", "bad", "SELECT * INTO copied FROM transactions")
+        .is_err());
+    assert!(plugins
+        .register_view(
+            "budget",
+            "bad",
+            "WITH t AS (SELECT 1) INSERT INTO plugin_budget.notes SELECT * FROM t"
+        )
+        .is_err());
+    assert!(plugins
+        .register_view("budget", "bad\"name", "SELECT 1")
+        .is_err());
+
+    // Views may only read what plugin SQL can, or they would leak it
+    assert!(plugins
+        .register_view("budget", "leak", "SELECT * FROM sys_integrations")
+        .is_err());
+    assert!(plugins
+        .register_view("budget", "leak", "SELECT * FROM plugin_other.notes")
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql("budget", "SELECT * FROM plugin_budget.leak")
+        .is_err());
+
+    // Compaction rewrites the database file; the view must still be there
+    CompactService::new(repo.clone()).compact().unwrap();
+    let result = repo
+        .execute_query("SELECT * FROM plugin_budget.monthly_rollup")
+        .unwrap();
+    assert_eq!(result.row_count, 1);
+
+    // Uninstalling the plugin drops its views
+    let plugin_dir = temp_dir.path().join("plugins").join("budget");
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    std::fs::write(
+        plugin_dir.join("manifest.json"),
+        r#"{"id": "budget", "name": "Budget", "version": "0.1.0"}"#,
+    )
+    .unwrap();
+    assert!(plugins.uninstall_plugin("budget").unwrap().success);
+    assert!(repo
+        .execute_query("SELECT * FROM plugin_budget.monthly_rollup")
+        .is_err());
+    assert!(plugins.list_views("budget").unwrap().is_empty());
 }
 
-/// Test Lunchflow ID deduplication
+/// Test checking a plugin's declared tables against the database, directly and via doctor
 #[test]
-fn test_sync_deduplication_by_lunchflow_id() {
+fn test_plugin_validate_schema() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let plugins = PluginService::new_with_repository(temp_dir.path(), repo.clone());
+
+    let plugin_dir = temp_dir.path().join("plugins").join("budget-kit");
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    std::fs::write(
+        plugin_dir.join("manifest.json"),
+        r#"{
+            "id": "budget-kit",
+            "name": "Budget Kit",
+            "version": "0.1.0",
+            "tables": {
+                "envelopes": {"id": "TEXT", "amount": "DOUBLE", "month": "DATE"}
+            }
+        }"#,
+    )
+    .unwrap();
 
-    let account = create_test_account("LF Dedup Test");
-    repo.upsert_account(&account).unwrap();
+    repo.execute_sql("CREATE SCHEMA plugin_budget_kit").unwrap();
+    repo.execute_sql(
+        "CREATE TABLE plugin_budget_kit.envelopes (id VARCHAR, amount DOUBLE, month DATE, note VARCHAR)",
+    )
+    .unwrap();
+    assert!(plugins.validate_schema("budget-kit").unwrap().is_empty());
 
-    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    let mut tx = create_test_transaction(account.id, 5000, date);
-    tx.lf_id = Some("unique_lf_id".to_string());
-    repo.upsert_transaction(&tx).unwrap();
+    let doctor = DoctorService::new(repo.clone(), temp_dir.path().to_path_buf());
+    let result = doctor.run_checks().unwrap();
+    assert_eq!(result.checks["plugin_schemas"].status, "pass");
 
-    // Check if transaction exists by LF ID
-    let exists = repo.transaction_exists_by_lf_id("unique_lf_id").unwrap();
-    assert!(exists, "Transaction should exist by LF ID");
+    repo.execute_sql("ALTER TABLE plugin_budget_kit.envelopes DROP COLUMN month")
+        .unwrap();
+    assert_eq!(
+        plugins.validate_schema("budget-kit").unwrap(),
+        vec!["Column plugin_budget_kit.envelopes.month is missing".to_string()]
+    );
 
-    let not_exists = repo.transaction_exists_by_lf_id("nonexistent").unwrap();
-    assert!(!not_exists, "Non-existent LF ID should return false");
-}
+    let result = doctor.run_checks().unwrap();
+    let check = &result.checks["plugin_schemas"];
+    assert_eq!(check.status, "warning");
+    assert_eq!(check.details.as_ref().unwrap()[0]["plugin"], "budget-kit");
 
-// ============================================================================
-// Query Service Tests
-// ============================================================================
+    assert!(plugins.validate_schema("not-installed").is_err());
+}
 
-/// Test executing custom SQL queries
 #[test]
-fn test_execute_custom_query() {
+fn test_plugin_sql_isolation() {
     let temp_dir = TempDir::new().unwrap();
     let repo = create_test_repo(&temp_dir);
+    let plugins = PluginService::new_with_repository(temp_dir.path(), repo.clone());
 
-    // Create test data
-    let account = create_test_account("Query Test");
+    let account = create_test_account("Checking");
     repo.upsert_account(&account).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    repo.upsert_transaction(&create_test_transaction(account.id, -2500, date))
+        .unwrap();
 
-    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
-    for i in 0..5 {
-        let mut tx = create_test_transaction(account.id, (i + 1) * 1000, date);
-        tx.description = Some(format!("Transaction {}", i));
-        repo.upsert_transaction(&tx).unwrap();
+    // Tables in the plugin's own schema are fully usable
+    plugins
+        .execute_plugin_sql("budget", "CREATE SCHEMA IF NOT EXISTS plugin_budget")
+        .unwrap();
+    plugins
+        .execute_plugin_sql(
+            "budget",
+            "CREATE TABLE plugin_budget.envelopes (id VARCHAR, amount DOUBLE)",
+        )
+        .unwrap();
+    plugins
+        .execute_plugin_sql(
+            "budget",
+            "INSERT INTO plugin_budget.envelopes VALUES ('groceries', 400), ('rent', 1500)",
+        )
+        .unwrap();
+    let result = plugins
+        .execute_plugin_sql(
+            "budget",
+            "SELECT e.id, SUM(e.amount) FROM plugin_budget.envelopes e, accounts a GROUP BY e.id",
+        )
+        .unwrap();
+    assert_eq!(result.row_count, 2);
+
+    // Core views are readable, including from CTEs and subqueries
+    let result = plugins
+        .execute_plugin_sql(
+            "budget",
+            "WITH spent AS (SELECT EXTRACT(year FROM transaction_date) AS y, amount FROM transactions)
+             SELECT y, SUM(amount) FROM spent GROUP BY y",
+        )
+        .unwrap();
+    assert_eq!(result.row_count, 1);
+
+    // Writes outside the plugin's tables are rejected before reaching the database
+    assert!(plugins
+        .execute_plugin_sql("budget", "DELETE FROM sys_accounts")
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql("budget", "UPDATE main.sys_transactions SET amount = 0")
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql(
+            "budget",
+            "INSERT INTO transactions SELECT * FROM transactions"
+        )
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql(
+            "budget",
+            "DROP TABLE plugin_budget.envelopes, plugin_other.data"
+        )
+        .is_err());
+    assert_eq!(repo.get_accounts(true).unwrap().len(), 1);
+
+    // Reads are confined too
+    assert!(plugins
+        .execute_plugin_sql(
+            "budget",
+            "SELECT * FROM plugin_budget.envelopes WHERE id IN (SELECT account_id FROM sys_balance_snapshots)",
+        )
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql("budget", "SELECT * FROM plugin_other.data")
+        .is_err());
+    // Ownership is the exact schema, not a name prefix
+    plugins
+        .execute_plugin_sql("budget-kit", "CREATE SCHEMA plugin_budget_kit")
+        .unwrap();
+    assert!(plugins
+        .execute_plugin_sql("budget", "SELECT * FROM plugin_budget_kit.envelopes")
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql("budget", "CREATE TABLE plugin_budget_extra (id VARCHAR)")
+        .is_err());
+    // Tables are checked at every depth, not only after FROM and JOIN
+    assert!(plugins
+        .execute_plugin_sql(
+            "budget",
+            "SELECT * FROM (SELECT 1) AS t, LATERAL (FROM plugin_other.data)",
+        )
+        .is_err());
+    // A CTE doesn't hide the table its own body reads
+    assert!(plugins
+        .execute_plugin_sql(
+            "budget",
+            "WITH data AS (SELECT * FROM data) SELECT * FROM data",
+        )
+        .is_err());
+    // Ids that could share a schema with another plugin are rejected
+    for id in ["budget_kit", "Budget"] {
+        assert!(plugins
+            .execute_plugin_sql(id, "SELECT * FROM transactions")
+            .is_err());
     }
+    assert!(plugins
+        .execute_plugin_sql("budget", "SELECT * FROM read_csv('/etc/passwd')")
+        .is_err());
+    assert!(plugins
+        .execute_plugin_sql("budget", "ATTACH 'other.duckdb' AS other")
+        .is_err());
+}
 
-    // Execute custom query
-    let result = repo
-        .execute_query("SELECT COUNT(*) as cnt FROM sys_transactions")
-        .unwrap();
+/// Fake plugin hook that records what it saw, or fails when told to
+struct RecordingSyncHook {
+    seen_transactions: Arc<Mutex<Vec<i64>>>,
+    fail: bool,
+}
 
-    // Should have results
-    assert!(!result.columns.is_empty());
-    assert!(!result.rows.is_empty());
+impl SyncHook for RecordingSyncHook {
+    fn on_sync_complete(&self, ctx: &TreelineContext) -> anyhow::Result<()> {
+        if self.fail {
+            anyhow::bail!("budget recompute failed");
+        }
+        let count = ctx.repository.get_transaction_count()?;
+        self.seen_transactions.lock().unwrap().push(count);
+        Ok(())
+    }
 }
 
-/// Test query validation rejects dangerous SQL
 #[test]
-fn test_query_validation() {
+fn test_plugin_sync_hook() {
     let temp_dir = TempDir::new().unwrap();
-    let repo = create_test_repo(&temp_dir);
+    let mut ctx = TreelineContext::new(temp_dir.path(), None).unwrap();
+    ctx.sync_service.setup_demo().unwrap();
+
+    let write_manifest = |id: &str, hooks: &str| {
+        let dir = temp_dir.path().join("plugins").join(id);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("manifest.json"),
+            format!(
+                r#"{{"id": "{}", "name": "{}", "hooks": {}}}"#,
+                id, id, hooks
+            ),
+        )
+        .unwrap();
+    };
+    write_manifest("budget", r#"["onSyncComplete"]"#);
+    write_manifest("broken", r#"["onSyncComplete"]"#);
+    write_manifest("quiet", "[]");
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    ctx.plugin_service
+        .register_sync_hook(
+            "budget",
+            RecordingSyncHook {
+                seen_transactions: seen.clone(),
+                fail: false,
+            },
+        )
+        .unwrap();
+    ctx.plugin_service
+        .register_sync_hook(
+            "broken",
+            RecordingSyncHook {
+                seen_transactions: seen.clone(),
+                fail: true,
+            },
+        )
+        .unwrap();
 
-    // Invalid SQL should return error
-    let result = repo.execute_query("SELEC * FROM sys_accounts"); // typo in SELECT
-    assert!(result.is_err(), "Invalid SQL should fail");
+    // Only plugins that declare the hook, and are installed, can register it
+    let quiet = RecordingSyncHook {
+        seen_transactions: seen.clone(),
+        fail: false,
+    };
+    assert!(ctx
+        .plugin_service
+        .register_sync_hook("quiet", quiet)
+        .is_err());
+
+    // Dry runs don't trigger hooks
+    ctx.sync(None, true, false).unwrap();
+    assert!(seen.lock().unwrap().is_empty());
+
+    // The hook runs after the synced data is written; a failing hook is only a warning
+    let result = ctx.sync(None, false, false).unwrap();
+    let seen_counts = seen.lock().unwrap().clone();
+    assert_eq!(seen_counts.len(), 1);
+    assert!(seen_counts[0] > 0);
+    assert_eq!(
+        result.hook_warnings,
+        vec!["broken: budget recompute failed".to_string()]
+    );
+    assert!(result.transactions_inserted > 0);
 }
 
 // ============================================================================
@@ -790,3 +6883,351 @@ fn test_vacuum_command_execution() {
     let result = repo.execute_sql("VACUUM");
     assert!(result.is_ok(), "VACUUM should succeed: {:?}", result.err());
 }
+
+#[test]
+fn test_dedupe_snapshots_keeps_one_per_day_by_priority() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let compact = CompactService::new(repo.clone());
+
+    let checking = create_test_account("Checking");
+    let savings = create_test_account("Savings");
+    repo.upsert_account(&checking).unwrap();
+    repo.upsert_account(&savings).unwrap();
+
+    let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let at = |hour| day.and_hms_opt(hour, 0, 0).unwrap();
+
+    // Five snapshots on one day; the manual one wins despite being earliest
+    let manual = BalanceSnapshot::from_manual(checking.id, Decimal::new(100, 0), at(8));
+    let mut csv = BalanceSnapshot::new(checking.id, Decimal::new(101, 0), at(10));
+    csv.source = Some("csv_import".to_string());
+    for snapshot in [
+        BalanceSnapshot::from_sync(checking.id, Decimal::new(102, 0), at(9)),
+        BalanceSnapshot::from_sync(checking.id, Decimal::new(103, 0), at(18)),
+        BalanceSnapshot::from_backfill(checking.id, Decimal::new(104, 0), at(20)),
+        csv,
+        manual.clone(),
+    ] {
+        repo.add_balance_snapshot(&snapshot).unwrap();
+    }
+
+    // Without a manual snapshot the latest sync wins; the only snapshot of a day stays
+    let latest_sync = BalanceSnapshot::from_sync(savings.id, Decimal::new(52, 0), at(17));
+    repo.add_balance_snapshot(&BalanceSnapshot::from_sync(
+        savings.id,
+        Decimal::new(50, 0),
+        at(7),
+    ))
+    .unwrap();
+    repo.add_balance_snapshot(&latest_sync).unwrap();
+    let next_day = BalanceSnapshot::from_sync(
+        savings.id,
+        Decimal::new(53, 0),
+        day.succ_opt().unwrap().and_hms_opt(9, 0, 0).unwrap(),
+    );
+    repo.add_balance_snapshot(&next_day).unwrap();
+
+    assert_eq!(compact.dedupe_snapshots().unwrap(), 5);
+
+    let checking_snapshots = repo
+        .get_balance_snapshots(Some(&checking.id.to_string()))
+        .unwrap();
+    assert_eq!(checking_snapshots.len(), 1);
+    assert_eq!(checking_snapshots[0].id, manual.id);
+
+    let savings_ids: HashSet<Uuid> = repo
+        .get_balance_snapshots(Some(&savings.id.to_string()))
+        .unwrap()
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    assert_eq!(savings_ids, HashSet::from([latest_sync.id, next_day.id]));
+
+    // Nothing left to remove
+    assert_eq!(compact.dedupe_snapshots().unwrap(), 0);
+}
+
+#[test]
+fn test_cached_existence_checks_bind_fresh_params() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let mut tx = create_test_transaction(account.id, -1000, date);
+    tx.sf_id = Some("sf-1".to_string());
+    repo.upsert_transaction(&tx).unwrap();
+
+    // The same cached statement must answer each call with its own parameter
+    for _ in 0..3 {
+        assert!(repo.transaction_exists_by_sf_id("sf-1").unwrap());
+        assert!(!repo.transaction_exists_by_sf_id("sf-2").unwrap());
+        assert!(repo.transaction_exists(&tx.id.to_string()).unwrap());
+        assert!(!repo
+            .transaction_exists(&Uuid::new_v4().to_string())
+            .unwrap());
+    }
+
+    // ...and see rows written after it was first prepared
+    let mut later = create_test_transaction(account.id, -2000, date);
+    later.sf_id = Some("sf-2".to_string());
+    repo.upsert_transaction(&later).unwrap();
+    assert!(repo.transaction_exists_by_sf_id("sf-2").unwrap());
+    assert_eq!(
+        repo.get_transactions_by_account(&account.id.to_string())
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+/// Timing for the sync loop's existence check, with and without the statement cache
+///
+/// Prints both timings; run with `--ignored --nocapture` to see them.
+/// Re-preparing the query each call is typically several times slower than
+/// the cached path.
+#[test]
+#[ignore = "benchmark; run explicitly with --ignored"]
+fn test_cached_existence_check_benchmark() {
+    const CALLS: usize = 10_000;
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for i in 0..100 {
+        let mut tx = create_test_transaction(account.id, -100 - i, date);
+        tx.sf_id = Some(format!("sf-{}", i));
+        repo.upsert_transaction(&tx).unwrap();
+    }
+
+    // Half the IDs exist, as when a sync re-fetches an overlapping window
+    let start = Instant::now();
+    let mut found = 0;
+    for i in 0..CALLS {
+        if repo
+            .transaction_exists_by_sf_id(&format!("sf-{}", i % 200))
+            .unwrap()
+        {
+            found += 1;
+        }
+    }
+    let cached = start.elapsed();
+    assert_eq!(found, CALLS / 2);
+
+    // Baseline: prepare the same SQL on every call
+    let start = Instant::now();
+    let uncached_found = repo
+        .use_connection(|conn| {
+            let mut found: usize = 0;
+            for i in 0..CALLS {
+                let count: i64 = conn
+                    .prepare("SELECT COUNT(*) FROM sys_transactions WHERE sf_id = ?")?
+                    .query_row([format!("sf-{}", i % 200)], |row| row.get(0))?;
+                if count > 0 {
+                    found += 1;
+                }
+            }
+            Ok(found)
+        })
+        .unwrap();
+    let uncached = start.elapsed();
+    assert_eq!(uncached_found, found);
+
+    println!(
+        "{} existence checks: cached {:?}, re-prepared {:?} ({:.1}x)",
+        CALLS,
+        cached,
+        uncached,
+        uncached.as_secs_f64() / cached.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+#[test]
+fn test_existing_provider_ids() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Checking");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    for i in 0..3 {
+        let mut tx = create_test_transaction(account.id, -100 - i, date);
+        tx.sf_id = Some(format!("sf-{}", i));
+        repo.upsert_transaction(&tx).unwrap();
+    }
+    let mut lunchflow = create_test_transaction(account.id, -500, date);
+    lunchflow.lf_id = Some("lf-0".to_string());
+    repo.upsert_transaction(&lunchflow).unwrap();
+
+    let ids: Vec<String> = ["sf-0", "sf-2", "sf-9", "lf-0"]
+        .iter()
+        .map(|id| id.to_string())
+        .collect();
+    assert_eq!(
+        repo.existing_sf_ids(&ids).unwrap(),
+        HashSet::from(["sf-0".to_string(), "sf-2".to_string()])
+    );
+    assert_eq!(
+        repo.existing_lf_ids(&ids).unwrap(),
+        HashSet::from(["lf-0".to_string()])
+    );
+    assert!(repo.existing_sf_ids(&[]).unwrap().is_empty());
+    assert!(repo.existing_external_ids("description", &ids).is_err());
+
+    // Large sets are looked up in chunks
+    let many: Vec<String> = (0..1200).map(|i| format!("sf-{}", i)).collect();
+    assert_eq!(repo.existing_sf_ids(&many).unwrap().len(), 3);
+}
+
+#[test]
+fn test_original_currency_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let account = create_test_account("Travel Card");
+    repo.upsert_account(&account).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+    let mut tx = create_test_transaction(account.id, -4910, date);
+    tx.original_amount = Some(Decimal::new(-4500, 2));
+    tx.original_currency = Some("EUR".to_string());
+    repo.upsert_transaction(&tx).unwrap();
+
+    let stored = repo
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.original_amount, Some(Decimal::new(-4500, 2)));
+    assert_eq!(stored.original_currency, Some("EUR".to_string()));
+
+    // A later sync without the foreign amount keeps the stored one
+    let mut resynced = tx.clone();
+    resynced.original_amount = None;
+    resynced.original_currency = None;
+    repo.upsert_transaction(&resynced).unwrap();
+    let stored = repo
+        .get_transaction_by_id(&tx.id.to_string())
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.original_currency, Some("EUR".to_string()));
+
+    // Queries see both amounts
+    let result = repo
+        .execute_query("SELECT amount, original_amount, original_currency FROM transactions")
+        .unwrap();
+    assert_eq!(result.row_count, 1);
+    assert_eq!(
+        result.columns,
+        vec!["amount", "original_amount", "original_currency"]
+    );
+    assert_eq!(result.rows[0][2], serde_json::json!("EUR"));
+}
+
+/// Sync twice under `policy`, with the user editing one description and
+/// clearing another in between; the provider changes one amount meanwhile.
+/// Returns the two stored transactions and the second sync's (updated, skipped).
+fn sync_with_conflict_policy(policy: ConflictPolicy) -> (Transaction, Transaction, (i64, i64)) {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let mut config = Config::load(temp_dir.path()).unwrap();
+    config.conflict_policy = policy;
+    config.save(temp_dir.path()).unwrap();
+
+    let provider = MockLunchflowProvider::default();
+    provider.push("lf-acc-1", "lf-tx-1", -2500);
+    provider.push("lf-acc-1", "lf-tx-2", -4000);
+    let sync_service = SyncService::new_with_provider(
+        repo.clone(),
+        temp_dir.path().to_path_buf(),
+        provider.clone(),
+    );
+    repo.upsert_integration("lunchflow", &serde_json::json!({}))
+        .unwrap();
+    sync_service.sync(Some("lunchflow"), false, false).unwrap();
+
+    let find = |lf_id: &str| {
+        repo.get_transactions()
+            .unwrap()
+            .into_iter()
+            .find(|tx| tx.lf_id.as_deref() == Some(lf_id))
+            .unwrap()
+    };
+    let mut edited = find("lf-tx-1");
+    edited.description = Some("Rent for June".to_string());
+    repo.upsert_transaction(&edited).unwrap();
+    let mut cleared = find("lf-tx-2");
+    cleared.description = None;
+    repo.upsert_transaction(&cleared).unwrap();
+
+    provider.post("lf-tx-1", -2600);
+    let result = sync_service.sync(Some("lunchflow"), false, false).unwrap();
+    let stats = &result.results[0].transaction_stats;
+    assert_eq!(stats.new, 0);
+
+    (
+        find("lf-tx-1"),
+        find("lf-tx-2"),
+        (stats.updated, stats.skipped),
+    )
+}
+
+#[test]
+fn test_conflict_policy_preserve_local() {
+    let (edited, cleared, counts) = sync_with_conflict_policy(ConflictPolicy::PreserveLocal);
+    assert_eq!(counts, (0, 2));
+    assert_eq!(edited.description.as_deref(), Some("Rent for June"));
+    assert_eq!(edited.amount, Decimal::new(-2500, 2));
+    assert_eq!(cleared.description, None);
+}
+
+#[test]
+fn test_conflict_policy_prefer_provider() {
+    let (edited, cleared, counts) = sync_with_conflict_policy(ConflictPolicy::PreferProvider);
+    assert_eq!(counts, (2, 0));
+    assert_eq!(edited.description.as_deref(), Some("Mock lf-tx-1"));
+    assert_eq!(edited.amount, Decimal::new(-2600, 2));
+    assert_eq!(cleared.description.as_deref(), Some("Mock lf-tx-2"));
+}
+
+#[test]
+fn test_conflict_policy_merge_non_empty() {
+    let (edited, cleared, counts) = sync_with_conflict_policy(ConflictPolicy::MergeNonEmpty);
+    assert_eq!(counts, (1, 1));
+    assert_eq!(edited.description.as_deref(), Some("Rent for June"));
+    assert_eq!(edited.amount, Decimal::new(-2500, 2));
+    assert_eq!(cleared.description.as_deref(), Some("Mock lf-tx-2"));
+}
+
+#[test]
+fn test_set_account_currency() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_test_repo(&temp_dir);
+    let mut account = create_test_account("Brokerage");
+    repo.upsert_account(&account).unwrap();
+    let id = account.id.to_string();
+
+    repo.set_account_currency(&id, " gbp ").unwrap();
+    let stored = repo.get_account_by_id(&id).unwrap().unwrap();
+    assert_eq!(stored.currency, "GBP");
+
+    // Invalid codes are rejected and leave the currency alone
+    for bad in ["", "US", "POUNDS", "G8P"] {
+        let err = repo.set_account_currency(&id, bad).unwrap_err();
+        assert!(err.to_string().contains("Invalid currency code"), "{}", err);
+    }
+    assert!(repo
+        .set_account_currency(&Uuid::new_v4().to_string(), "EUR")
+        .is_err());
+
+    // A sync reporting the provider's currency doesn't undo the override
+    account.currency = "USD".to_string();
+    account.sf_id = Some("sf-brokerage".to_string());
+    repo.upsert_account(&account).unwrap();
+    let stored = repo.get_account_by_id(&id).unwrap().unwrap();
+    assert_eq!(stored.currency, "GBP");
+    assert_eq!(stored.sf_id.as_deref(), Some("sf-brokerage"));
+}